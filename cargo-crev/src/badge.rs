@@ -0,0 +1,126 @@
+//! `badge`: share of verified dependencies, for the project's README
+//!
+//! Meant to be re-generated by CI, and published as an image (`svg`),
+//! or as a shields.io endpoint (`shields-json`).
+use crate::{
+    deps::scan::{RequiredDetails, Scanner},
+    opts::{BadgeFormat, CrateSelector, CrateVerify, CrateVerifyCommon, WotOpts},
+    prelude::*,
+    repo::Repo,
+};
+use crev_lib::verify::VerificationCoverage;
+
+const LABEL: &str = "crev";
+
+/// Approximate width of a character of 11px Verdana
+const CHAR_WIDTH: usize = 7;
+const PADDING: usize = 10;
+
+fn message(coverage: VerificationCoverage) -> String {
+    match coverage.percent() {
+        Some(percent) => format!("{percent}% verified"),
+        None => "no dependencies".into(),
+    }
+}
+
+/// Named color of shields.io, and its hex value
+fn color(coverage: VerificationCoverage) -> (&'static str, &'static str) {
+    match coverage.percent() {
+        None => ("lightgrey", "#9f9f9f"),
+        Some(100) => ("brightgreen", "#4c1"),
+        Some(90..) => ("green", "#97ca00"),
+        Some(75..) => ("yellowgreen", "#a4a61d"),
+        Some(50..) => ("yellow", "#dfb317"),
+        Some(25..) => ("orange", "#fe7d37"),
+        Some(_) => ("red", "#e05d44"),
+    }
+}
+
+fn svg(coverage: VerificationCoverage) -> String {
+    let message = message(coverage);
+    let (_, color) = color(coverage);
+    let label_width = LABEL.len() * CHAR_WIDTH + PADDING;
+    let message_width = message.len() * CHAR_WIDTH + PADDING;
+    let width = label_width + message_width;
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="20" role="img" aria-label="{LABEL}: {message}">
+<title>{LABEL}: {message}</title>
+<linearGradient id="s" x2="0" y2="100%"><stop offset="0" stop-color="#bbb" stop-opacity=".1"/><stop offset="1" stop-opacity=".1"/></linearGradient>
+<clipPath id="r"><rect width="{width}" height="20" rx="3" fill="#fff"/></clipPath>
+<g clip-path="url(#r)"><rect width="{label_width}" height="20" fill="#555"/><rect x="{label_width}" width="{message_width}" height="20" fill="{color}"/><rect width="{width}" height="20" fill="url(#s)"/></g>
+<g fill="#fff" text-anchor="middle" font-family="Verdana,Geneva,DejaVu Sans,sans-serif" font-size="11"><text x="{label_x}" y="14">{LABEL}</text><text x="{message_x}" y="14">{message}</text></g>
+</svg>
+"##,
+        label_x = label_width / 2,
+        message_x = label_width + message_width / 2,
+    )
+}
+
+/// See <https://shields.io/badges/endpoint-badge>
+fn shields_json(coverage: VerificationCoverage) -> serde_json::Value {
+    serde_json::json!({
+        "schemaVersion": 1,
+        "label": LABEL,
+        "message": message(coverage),
+        "color": color(coverage).0,
+    })
+}
+
+/// Coverage of the dependencies of the current project, as `verify` computes it
+fn coverage(common: CrateVerifyCommon, wot: WotOpts) -> Result<VerificationCoverage> {
+    let args = CrateVerify {
+        common,
+        wot,
+        ..Default::default()
+    };
+    // fail early outside of a cargo project
+    Repo::auto_open_cwd(args.common.cargo_opts.clone())?;
+    let scanner = Scanner::new(CrateSelector::default(), &args)?;
+
+    let mut coverage = VerificationCoverage::default();
+    for stats in scanner.run(&RequiredDetails::none()) {
+        coverage.add(stats.details().accumulative_own.trust);
+    }
+    Ok(coverage)
+}
+
+pub fn print_badge(common: CrateVerifyCommon, wot: WotOpts, format: BadgeFormat) -> Result<()> {
+    let coverage = coverage(common, wot)?;
+    match format {
+        BadgeFormat::Svg => print!("{}", svg(coverage)),
+        BadgeFormat::ShieldsJson => {
+            println!("{}", serde_json::to_string_pretty(&shields_json(coverage))?);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn badge_shows_coverage() {
+        let coverage = VerificationCoverage {
+            verified: 87,
+            total: 100,
+        };
+        let json = shields_json(coverage);
+        assert_eq!(json["message"], "87% verified");
+        assert_eq!(json["color"], "yellowgreen");
+        assert!(svg(coverage).contains(">87% verified</text>"));
+
+        let all = VerificationCoverage {
+            verified: 3,
+            total: 3,
+        };
+        assert_eq!(shields_json(all)["color"], "brightgreen");
+        // 99.9% is not all of them
+        let almost = VerificationCoverage {
+            verified: 999,
+            total: 1000,
+        };
+        assert_eq!(message(almost), "99% verified");
+        assert_eq!(message(VerificationCoverage::default()), "no dependencies");
+    }
+}
@@ -1,15 +1,150 @@
 use crate::{deps::DownloadsStats, prelude::*};
-use serde::{de::DeserializeOwned, Serialize};
+use crev_lib::pinned_inputs::{PinnedCrate, PinnedDownloads};
+use itertools::Itertools;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::{
+    collections::BTreeMap,
     fs,
     io::Read,
     path::{Path, PathBuf},
-    time::Duration,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    thread,
+    time::{Duration, Instant},
 };
 
+const API_URL: &str = "https://crates.io/api/v1";
+
+/// Minimum time between requests to crates.io, across all threads and `Client`s
+///
+/// The crates.io crawler policy asks for at most one request per second.
+const MIN_REQUEST_INTERVAL: Duration = Duration::from_millis(1000);
+
+/// Retries of a request that was rate limited, or failed on the server or network side
+const MAX_RETRIES: u32 = 4;
+
+/// Wait before the first retry; doubled for every next one
+const INITIAL_BACKOFF: Duration = Duration::from_secs(2);
+
+/// Crates looked up in one request by `Client::prefetch`
+const BATCH_SIZE: usize = 50;
+
+/// When the last request to crates.io was made, by any thread
+static LAST_REQUEST: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// Set after a request failed even after retrying: don't retry others, use the cache
+static UNAVAILABLE: AtomicBool = AtomicBool::new(false);
+
+/// Block until making a request now keeps to `MIN_REQUEST_INTERVAL`
+fn wait_for_turn() {
+    let mut last = LAST_REQUEST.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(elapsed) = last.map(|last| last.elapsed()) {
+        if elapsed < MIN_REQUEST_INTERVAL {
+            thread::sleep(MIN_REQUEST_INTERVAL - elapsed);
+        }
+    }
+    *last = Some(Instant::now());
+}
+
+#[derive(Debug, thiserror::Error)]
+enum RequestError {
+    #[error("crates.io returned HTTP status {0}")]
+    Status(u32),
+    #[error("crates.io request failed: {0}")]
+    Curl(#[from] curl::Error),
+}
+
+/// Worth retrying: rate limited (429), server errors, timeouts and connection failures
+fn is_transient(err: &anyhow::Error) -> bool {
+    match err.downcast_ref::<RequestError>() {
+        Some(RequestError::Status(status)) => *status == 429 || (500..600).contains(status),
+        Some(RequestError::Curl(e)) => {
+            e.is_operation_timedout() || e.is_couldnt_connect() || e.is_couldnt_resolve_host()
+        }
+        None => false,
+    }
+}
+
+fn is_not_found(err: &anyhow::Error) -> bool {
+    matches!(
+        err.downcast_ref::<RequestError>(),
+        Some(RequestError::Status(404))
+    )
+}
+
+enum Response {
+    /// The cached response with the `ETag` sent is still current
+    NotModified,
+    Ok {
+        body: Vec<u8>,
+        etag: Option<String>,
+    },
+}
+
+/// GET `url`, revalidating the response cached with `etag`
+fn http_get(url: &str, etag: Option<&str>) -> Result<Response, RequestError> {
+    let mut easy = curl::easy::Easy::new();
+    easy.url(url)?;
+    easy.timeout(Duration::from_secs(30))?;
+    easy.useragent(concat!("cargo-crev/", env!("CARGO_PKG_VERSION")))?;
+    if let Some(etag) = etag {
+        let mut headers = curl::easy::List::new();
+        headers.append(&format!("If-None-Match: {etag}"))?;
+        easy.http_headers(headers)?;
+    }
+    let mut body = Vec::new();
+    let mut new_etag = None;
+    {
+        let mut transfer = easy.transfer();
+        transfer.header_function(|header| {
+            if let Some((name, value)) = std::str::from_utf8(header)
+                .ok()
+                .and_then(|header| header.split_once(':'))
+            {
+                if name.trim().eq_ignore_ascii_case("etag") {
+                    new_etag = Some(value.trim().to_owned());
+                }
+            }
+            true
+        })?;
+        transfer.write_function(|data| {
+            body.extend_from_slice(data);
+            Ok(data.len())
+        })?;
+        transfer.perform()?;
+    }
+    match easy.response_code()? {
+        200 => Ok(Response::Ok {
+            body,
+            etag: new_etag,
+        }),
+        304 if etag.is_some() => Ok(Response::NotModified),
+        code => Err(RequestError::Status(code)),
+    }
+}
+
+/// Response of `crates?ids[]=...`
+#[derive(Deserialize)]
+struct CratesPage {
+    crates: Vec<crates_io_api::Crate>,
+}
+
+/// crates.io data of `PinnedInputs`
+#[derive(Clone)]
+pub enum Pin {
+    /// Use only the pinned data, never fetch
+    Replay(Arc<BTreeMap<String, PinnedCrate>>),
+    /// Get data as usual, and record what was used
+    Record(Arc<Mutex<BTreeMap<String, PinnedCrate>>>),
+}
+
 pub struct Client {
-    client: crates_io_api::SyncClient,
     cache_dir: PathBuf,
+    pin: Option<Pin>,
+    /// Cargo is offline (`CARGO_NET_OFFLINE`): use only cached data, never fetch
+    offline: bool,
 }
 
 fn is_fresh(path: &Path) -> Result<bool> {
@@ -21,15 +156,16 @@ fn is_fresh(path: &Path) -> Result<bool> {
 
 trait Cacheable: Sized {
     fn get_cache_path(base: &Path, name: &str, version: &str) -> PathBuf;
-    fn fetch(client: &crates_io_api::SyncClient, crate_: &str, _version: &str) -> Result<Self>;
+    /// API endpoint, relative to `API_URL`
+    fn api_path(crate_: &str) -> String;
 }
 
 impl Cacheable for crates_io_api::CrateResponse {
     fn get_cache_path(base: &Path, name: &str, _version: &str) -> PathBuf {
         base.join("crate").join(format!("{name}.json"))
     }
-    fn fetch(client: &crates_io_api::SyncClient, crate_: &str, _version: &str) -> Result<Self> {
-        Ok(client.get_crate(crate_)?)
+    fn api_path(crate_: &str) -> String {
+        format!("crates/{crate_}")
     }
 }
 
@@ -37,13 +173,16 @@ impl Cacheable for crates_io_api::Owners {
     fn get_cache_path(base: &Path, name: &str, _version: &str) -> PathBuf {
         base.join("owners").join(format!("{name}.json"))
     }
-    fn fetch(client: &crates_io_api::SyncClient, crate_: &str, _version: &str) -> Result<Self> {
-        Ok(crates_io_api::Owners {
-            users: client.crate_owners(crate_)?,
-        })
+    fn api_path(crate_: &str) -> String {
+        format!("crates/{crate_}/owners")
     }
 }
 
+/// `ETag` of the response cached at `path`
+fn etag_path(path: &Path) -> PathBuf {
+    path.with_extension("etag")
+}
+
 fn get_downloads_stats(resp: &crates_io_api::CrateResponse, version: &Version) -> DownloadsStats {
     DownloadsStats {
         version: resp
@@ -61,14 +200,45 @@ impl Client {
         let cache_dir = local.cache_root().join("crates_io");
         fs::create_dir_all(&cache_dir)?;
         Ok(Self {
-            client: crates_io_api::SyncClient::new(
-                "cargo-crev",
-                std::time::Duration::from_millis(1000),
-            )?,
             cache_dir,
+            pin: None,
+            offline: std::env::var_os("CARGO_NET_OFFLINE").is_some_and(|v| v == "true"),
         })
     }
 
+    /// Use or record pinned data, see `PinnedInputs`
+    pub fn pinned(mut self, pin: Option<Pin>) -> Self {
+        self.pin = pin;
+        self
+    }
+
+    /// Pinned data of `crate_`, if replaying a pin
+    ///
+    /// Data that wasn't used when it was pinned is an error, like when
+    /// crates.io doesn't have it.
+    fn replay<T>(
+        &self,
+        crate_: &str,
+        get: impl FnOnce(&PinnedCrate) -> Option<T>,
+    ) -> Option<Result<T>> {
+        let Some(Pin::Replay(pinned)) = &self.pin else {
+            return None;
+        };
+        Some(
+            pinned
+                .get(crate_)
+                .and_then(get)
+                .ok_or_else(|| format_err!("No crates.io data for {crate_} pinned")),
+        )
+    }
+
+    fn record(&self, crate_: &str, update: impl FnOnce(&mut PinnedCrate)) {
+        if let Some(Pin::Record(recorded)) = &self.pin {
+            let mut recorded = recorded.lock().unwrap_or_else(PoisonError::into_inner);
+            update(recorded.entry(crate_.to_owned()).or_default());
+        }
+    }
+
     fn load_cache(&self, path: &Path) -> Result<String> {
         let mut file = std::fs::File::open(path)?;
         let mut content = String::new();
@@ -97,10 +267,71 @@ impl Client {
         Ok(())
     }
 
-    fn fetch<T: Cacheable + Serialize>(&self, crate_: &str, version: &str) -> Result<T> {
-        let resp = T::fetch(&self.client, crate_, version)?;
-        self.store_in_cache(&T::get_cache_path(&self.cache_dir, crate_, version), &resp)?;
-        Ok(resp)
+    /// Request from crates.io, throttled, retrying with exponential backoff on transient errors
+    fn get_with_retries(&self, api_path: &str, etag: Option<&str>) -> Result<Response> {
+        let url = format!("{API_URL}/{api_path}");
+        let mut backoff = INITIAL_BACKOFF;
+        let mut retries = 0;
+        loop {
+            if UNAVAILABLE.load(Ordering::Relaxed) {
+                bail!("crates.io is unavailable, not requesting {url}");
+            }
+            wait_for_turn();
+            match http_get(&url, etag).map_err(anyhow::Error::from) {
+                Err(e) if is_transient(&e) => {
+                    if MAX_RETRIES <= retries {
+                        UNAVAILABLE.store(true, Ordering::Relaxed);
+                        return Err(e);
+                    }
+                    log::debug!(
+                        "crates.io request {url} failed, retrying in {}s: {e}",
+                        backoff.as_secs()
+                    );
+                    thread::sleep(backoff);
+                    backoff *= 2;
+                    retries += 1;
+                }
+                resp => return resp,
+            }
+        }
+    }
+
+    /// Fetch and cache, or only refresh the cached `T` if it's still current
+    ///
+    /// If the request fails, the (stale) cached `T` is returned instead.
+    fn fetch<T: Cacheable + DeserializeOwned + Serialize>(
+        &self,
+        crate_: &str,
+        version: &str,
+        cached: Option<T>,
+    ) -> Result<T> {
+        let path = T::get_cache_path(&self.cache_dir, crate_, version);
+        let etag_path = etag_path(&path);
+        let etag = cached
+            .as_ref()
+            .and_then(|_| fs::read_to_string(&etag_path).ok());
+        let response = match self.get_with_retries(&T::api_path(crate_), etag.as_deref()) {
+            Ok(response) => response,
+            Err(e) => return cached.ok_or(e),
+        };
+        match (response, cached) {
+            (Response::NotModified, Some(cached)) => {
+                // rewritten to be fresh again
+                self.store_in_cache(&path, &cached)?;
+                Ok(cached)
+            }
+            (Response::Ok { body, etag }, _) => {
+                let resp: T = serde_json::from_slice(&body)?;
+                self.store_in_cache(&path, &resp)?;
+                match etag {
+                    Some(etag) => fs::write(&etag_path, etag)?,
+                    None if etag_path.exists() => fs::remove_file(&etag_path)?,
+                    None => {}
+                }
+                Ok(resp)
+            }
+            (Response::NotModified, None) => unreachable!("revalidated without a cached response"),
+        }
     }
 
     fn get<T: Cacheable + DeserializeOwned + Serialize>(
@@ -109,26 +340,144 @@ impl Client {
         version: &str,
     ) -> Result<T> {
         let cached: Option<(T, bool)> = self.get_from_cache(crate_, version)?;
+        if self.offline {
+            return match cached {
+                Some((resp, _)) => Ok(resp),
+                None => bail!("No crates.io data for {crate_} cached, and cargo is offline"),
+            };
+        }
+
+        // don't wait for requests that would fail anyway
+        if UNAVAILABLE.load(Ordering::Relaxed) {
+            return match cached {
+                Some((resp, _)) => Ok(resp),
+                None => {
+                    bail!("No crates.io data for {crate_} cached, and crates.io is unavailable")
+                }
+            };
+        }
 
         match cached {
             Some((resp, true)) => Ok(resp),
-            Some((resp, false)) => match self.fetch(crate_, version) {
-                Ok(new_resp) => Ok(new_resp),
-                Err(_e) => Ok(resp),
-            },
-            None => self.fetch(crate_, version),
+            Some((resp, false)) => self.fetch(crate_, version, Some(resp)),
+            None => self.fetch(crate_, version, None),
+        }
+    }
+
+    /// Refresh the cached data of many crates with few requests
+    ///
+    /// Each request looks up `BATCH_SIZE` crates. Stale cached data of crates
+    /// that haven't changed since (no new versions or metadata) gets their new
+    /// total and recent download counts and counts as fresh again; per-version
+    /// download counts are kept until the crate changes. Crates with no cached
+    /// data, or that changed, are left to be fetched one by one.
+    pub fn prefetch<'a>(&self, crates: impl IntoIterator<Item = &'a str>) {
+        if matches!(self.pin, Some(Pin::Replay(_))) || self.offline {
+            return;
+        }
+        let mut stale: Vec<_> = crates
+            .into_iter()
+            .filter_map(|name| {
+                match self.get_from_cache::<crates_io_api::CrateResponse>(name, "") {
+                    Ok(Some((resp, false))) => Some((name, resp)),
+                    _ => None,
+                }
+            })
+            .collect();
+        for batch in stale.chunks_mut(BATCH_SIZE) {
+            let query = batch
+                .iter()
+                .map(|(name, _)| format!("ids%5B%5D={name}"))
+                .join("&");
+            let page = match self
+                .get_with_retries(&format!("crates?{query}&per_page={BATCH_SIZE}"), None)
+            {
+                Ok(Response::Ok { body, .. }) => serde_json::from_slice::<CratesPage>(&body),
+                Ok(Response::NotModified) => continue,
+                Err(e) => {
+                    log::debug!("Batched crates.io lookup failed: {e}");
+                    return;
+                }
+            };
+            let Ok(page) = page else {
+                continue;
+            };
+            for summary in page.crates {
+                let Some((name, resp)) = batch.iter_mut().find(|(name, _)| *name == summary.name)
+                else {
+                    continue;
+                };
+                if resp.crate_data.updated_at != summary.updated_at {
+                    continue;
+                }
+                resp.crate_data.downloads = summary.downloads;
+                resp.crate_data.recent_downloads = summary.recent_downloads;
+                let path = crates_io_api::CrateResponse::get_cache_path(&self.cache_dir, name, "");
+                if let Err(e) = self.store_in_cache(&path, &*resp) {
+                    log::debug!("Can't cache crates.io data of {name}: {e}");
+                }
+            }
         }
     }
 
     pub fn get_downloads_count(&self, crate_: &str, version: &Version) -> Result<DownloadsStats> {
-        Ok(get_downloads_stats(
-            &self.get::<crates_io_api::CrateResponse>(crate_, &version.to_string())?,
+        let version_str = version.to_string();
+        if let Some(pinned) =
+            self.replay(crate_, |pinned| pinned.downloads.get(&version_str).copied())
+        {
+            return pinned.map(|pinned| DownloadsStats {
+                version: pinned.version,
+                total: pinned.total,
+                recent: pinned.recent,
+            });
+        }
+        let stats = get_downloads_stats(
+            &self.get::<crates_io_api::CrateResponse>(crate_, &version_str)?,
             version,
-        ))
+        );
+        self.record(crate_, |pinned| {
+            pinned.downloads.insert(
+                version_str,
+                PinnedDownloads {
+                    version: stats.version,
+                    total: stats.total,
+                    recent: stats.recent,
+                },
+            );
+        });
+        Ok(stats)
     }
 
     pub fn get_owners(&self, crate_: &str) -> Result<Vec<String>> {
+        if let Some(pinned) = self.replay(crate_, |pinned| pinned.owners.clone()) {
+            return pinned;
+        }
         let owners = self.get::<crates_io_api::Owners>(crate_, "")?;
-        Ok(owners.users.into_iter().map(|u| u.login).collect())
+        let owners: Vec<_> = owners.users.into_iter().map(|u| u.login).collect();
+        self.record(crate_, |pinned| pinned.owners = Some(owners.clone()));
+        Ok(owners)
+    }
+
+    /// `false` only if crates.io says there's no such crate
+    pub fn crate_exists(&self, crate_: &str) -> Result<bool> {
+        match self.get::<crates_io_api::CrateResponse>(crate_, "") {
+            Ok(_) => Ok(true),
+            Err(e) if is_not_found(&e) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// When `version` of `crate_` was published, if crates.io knows about it
+    pub fn get_version_published(
+        &self,
+        crate_: &str,
+        version: &Version,
+    ) -> Result<Option<chrono::DateTime<chrono::Utc>>> {
+        let resp = self.get::<crates_io_api::CrateResponse>(crate_, &version.to_string())?;
+        Ok(resp
+            .versions
+            .iter()
+            .find(|v| v.num == version.to_string())
+            .map(|v| v.created_at))
     }
 }
@@ -1,18 +1,35 @@
 use ::term::color::YELLOW;
-use crev_data::{proof, review, Digest, PublicId, Version};
-use crev_lib::VerificationStatus;
+use crev_data::{
+    proof::{self, trust::TrustLevel},
+    review, Digest, Id, Level, PublicId, Version,
+};
+pub use crev_lib::verify::OpenIssue;
+use crev_lib::{usage::UsageEvent, verify::VerificationCoverage, VerificationStatus};
 use crev_wot::TrustSet;
-use std::{io, io::Write as _, path::PathBuf};
+use serde::{Deserialize, Serialize};
+use std::{
+    io,
+    io::Write as _,
+    path::{Path, PathBuf},
+};
 
-use crate::{opts::*, prelude::*, shared::CommandExitStatus, term};
+use crate::{
+    opts::*,
+    prelude::*,
+    repo::{DependencyUse, Repo},
+    shared::{CommandExitStatus, EXIT_CODE_NEGATIVE_REVIEWS},
+    term,
+};
 use cargo::core::PackageId;
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     ops::Add,
 };
 
 use self::scan::RequiredDetails;
 
+mod baseline;
+mod cache;
 mod print_term;
 pub mod scan;
 
@@ -122,6 +139,7 @@ pub struct AccumulativeCrateDetails {
     pub loc: Option<u64>,
     pub geiger_count: Option<u64>,
     pub has_custom_build: bool,
+    pub is_proc_macro: bool,
     pub is_unmaintained: bool,
     pub owner_set: OwnerSetSet,
     pub is_local_source_code: bool,
@@ -150,6 +168,7 @@ impl std::ops::Add<AccumulativeCrateDetails> for AccumulativeCrateDetails {
             loc: sum_options(self.loc, other.loc),
             geiger_count: sum_options(self.geiger_count, other.geiger_count),
             has_custom_build: self.has_custom_build || other.has_custom_build,
+            is_proc_macro: self.is_proc_macro || other.is_proc_macro,
             is_unmaintained: self.is_unmaintained || other.is_unmaintained,
             owner_set: self.owner_set + other.owner_set,
             is_local_source_code: self.is_local_source_code || other.is_local_source_code,
@@ -161,6 +180,8 @@ impl std::ops::Add<AccumulativeCrateDetails> for AccumulativeCrateDetails {
 #[derive(Clone, Debug)]
 pub struct CrateDetails {
     pub digest: Option<Digest>,
+    /// How the crate is used; always at runtime without `verify --deep`
+    pub dependency_use: DependencyUse,
     pub latest_trusted_version: Option<Version>,
     pub trusted_reviewers: HashSet<PublicId>,
     pub version_reviews: CountWithTotal,
@@ -171,6 +192,24 @@ pub struct CrateDetails {
     pub rev_dependencies: Vec<proof::PackageVersionId>,
     // Someone reported a different digest, our local copy is possibly wrong
     pub digest_mismatches: Vec<review::Package>,
+    // Directories verified by reviews of just them (`review --subtree`)
+    pub verified_subtrees: Vec<String>,
+    // Reasons the crate doesn't meet the project review policy
+    pub policy_failures: Vec<String>,
+    // Requirements of the project policy used instead of the global ones
+    pub requirements_rule: Option<String>,
+    // Exemption of the project policy, letting it pass without being verified for now
+    pub exemption: Option<String>,
+    // Waiver of a trusted Id, letting it pass without being verified until it expires
+    pub waiver: Option<String>,
+    // Waiver of a trusted Id that doesn't apply anymore
+    pub expired_waiver: Option<String>,
+    // Nobody reviewed it, and its name looks like that of a popular reviewed crate
+    pub similar_to: Option<String>,
+    // Open issues reported by trusted Ids, most severe first
+    pub open_issues: Vec<OpenIssue>,
+    // Reviews by trusted Ids making the crate fail with a negative status
+    pub negative_reviews: Vec<review::Package>,
     // own accumulative stats only
     pub accumulative_own: AccumulativeCrateDetails,
     // total recursive stats
@@ -179,12 +218,30 @@ pub struct CrateDetails {
     pub accumulative: AccumulativeCrateDetails,
 }
 
+impl CrateDetails {
+    /// Not verified, but exempt by the project policy; negative reviews still count
+    pub fn is_exempt(&self) -> bool {
+        self.exemption.is_some()
+            && !self.accumulative.verified
+            && self.accumulative.trust != VerificationStatus::Negative
+    }
+
+    /// Not verified nor exempt, but waived by a trusted Id; negative reviews still count
+    pub fn is_waived(&self) -> bool {
+        self.waiver.is_some()
+            && !self.is_exempt()
+            && !self.accumulative.verified
+            && self.accumulative.trust != VerificationStatus::Negative
+    }
+}
+
 /// Basic crate info of a crate we're scanning
 #[derive(Clone, Debug)]
 pub struct CrateInfo {
     pub id: cargo::core::PackageId, // contains the name, version
     pub root: PathBuf,
     pub has_custom_build: bool,
+    pub is_proc_macro: bool,
 }
 
 impl CrateInfo {
@@ -192,13 +249,20 @@ impl CrateInfo {
         let id = pkg.package_id();
         let root = pkg.root().to_path_buf();
         let has_custom_build = pkg.has_custom_build();
+        let is_proc_macro = pkg.proc_macro();
         CrateInfo {
             id,
             root,
             has_custom_build,
+            is_proc_macro,
         }
     }
 
+    /// Has a build script or is a proc macro, so its code runs while building
+    pub fn runs_code_at_build_time(&self) -> bool {
+        self.has_custom_build || self.is_proc_macro
+    }
+
     pub fn download_if_needed(&self, cargo_opts: CargoOpts) -> Result<()> {
         if !self.root.exists() {
             let repo = crate::Repo::auto_open_cwd(cargo_opts)?;
@@ -245,6 +309,10 @@ impl CrateStats {
         self.details.accumulative.has_custom_build
     }
 
+    pub fn is_proc_macro(&self) -> bool {
+        self.details.accumulative.is_proc_macro
+    }
+
     pub fn is_unmaintained(&self) -> bool {
         self.details.accumulative.is_unmaintained
     }
@@ -254,6 +322,32 @@ impl CrateStats {
     }
 }
 
+/// Crate name -> all its (sorted) versions, only for crates with more than one
+///
+/// Versions of the same name from different sources count as different too.
+pub type DuplicateVersions = BTreeMap<String, Vec<PackageId>>;
+
+pub fn duplicate_versions<'a>(ids: impl IntoIterator<Item = &'a PackageId>) -> DuplicateVersions {
+    let mut by_name: DuplicateVersions = BTreeMap::new();
+    for id in ids {
+        by_name.entry(id.name().to_string()).or_default().push(*id);
+    }
+    by_name.retain(|_, ids| ids.len() > 1);
+    for ids in by_name.values_mut() {
+        ids.sort();
+    }
+    by_name
+}
+
+/// Version, and the source if it's not crates.io
+pub fn version_with_source(id: &PackageId) -> String {
+    if id.source_id().is_crates_io() {
+        id.version().to_string()
+    } else {
+        format!("{} ({})", id.version(), id.source_id())
+    }
+}
+
 pub fn latest_trusted_version_string(
     base_version: &Version,
     latest_trusted_version: &Option<Version>,
@@ -279,10 +373,22 @@ pub fn latest_trusted_version_string(
     }
 }
 
+/// How much of the dependencies a reviewer covers, see `crate_mvps`
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MvpCounts {
+    /// Dependencies they reviewed
+    pub reviewed: u64,
+    /// Verified dependencies their review counts towards
+    pub verified: u64,
+    /// Verified dependencies that wouldn't be without their review
+    pub sole: u64,
+}
+
 pub fn crate_mvps(
     crate_: CrateSelector,
     common: CrateVerifyCommon,
     wot_opts: WotOpts,
+    simulate_untrust: bool,
 ) -> Result<()> {
     let args = CrateVerify {
         common,
@@ -292,38 +398,352 @@ pub fn crate_mvps(
     let scanner = scan::Scanner::new(crate_, &args)?;
     let trust_set = scanner.trust_set.clone();
     let db = scanner.db.clone();
+    let requirements = crev_lib::VerificationRequirements::from(args.common.requirements.clone());
     let events = scanner.run(&RequiredDetails::none());
 
-    let mut mvps: HashMap<PublicId, u64> = HashMap::new();
+    let mut mvps: HashMap<Id, MvpCounts> = HashMap::new();
+    let mut verified_digests = vec![];
 
     for stats in events {
         for reviewer in &stats.details.trusted_reviewers {
-            *mvps.entry(reviewer.clone()).or_default() += 1;
+            mvps.entry(reviewer.id.clone()).or_default().reviewed += 1;
+        }
+        let Some(digest) = &stats.details.digest else {
+            continue;
+        };
+        let verifying = crev_lib::verifying_reviewers(digest, &trust_set, &requirements, &db);
+        if verifying.is_empty() || (verifying.len() as u64) < requirements.redundancy {
+            continue;
         }
+        let is_sole = verifying.len() as u64 == requirements.redundancy;
+        for reviewer in verifying {
+            let counts = mvps.entry(reviewer).or_default();
+            counts.verified += 1;
+            if is_sole {
+                counts.sole += 1;
+            }
+        }
+        verified_digests.push(digest.clone());
     }
+    crate::shared::interrupt_token().check()?;
 
     let mut mvps: Vec<_> = mvps.into_iter().collect();
 
-    mvps.sort_by(|a, b| a.1.cmp(&b.1).reverse());
+    mvps.sort_by_key(|(_, counts)| {
+        std::cmp::Reverse((counts.verified, counts.sole, counts.reviewed))
+    });
 
     crate::print_mvp_ids(
-        mvps.iter().map(|(id, count)| (&id.id, *count)),
+        mvps.iter().map(|(id, counts)| (id, counts)),
         &trust_set,
         &db,
     );
 
+    if simulate_untrust {
+        print_untrust_losses(&args, &verified_digests, &requirements, &db)?;
+    }
+
+    Ok(())
+}
+
+/// Print how many of `verified_digests` each directly trusted Id accounts for
+fn print_untrust_losses(
+    args: &CrateVerify,
+    verified_digests: &[Digest],
+    requirements: &crev_lib::VerificationRequirements,
+    db: &crev_wot::ProofDB,
+) -> Result<()> {
+    let local = crev_lib::Local::auto_create_or_open()?;
+    let for_id = local.get_for_id_from_str(args.wot.for_id.as_deref())?;
+    let params = args.wot.trust_params.clone().into();
+
+    let mut losses: Vec<_> = db
+        .get_direct_trust_of(&for_id)
+        .filter(|(id, level)| TrustLevel::None < *level && **id != for_id)
+        .map(|(id, level)| {
+            let trust_set = db.calculate_trust_set_without_edge(&for_id, &params, &for_id, id);
+            let lost = verified_digests
+                .iter()
+                .filter(|digest| {
+                    !crev_lib::verify_package_digest(digest, &trust_set, requirements, db)
+                        .is_verified()
+                })
+                .count();
+            (lost, id, level)
+        })
+        .collect();
+    losses.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(b.1)));
+
+    eprintln!();
+    eprintln!("{:>4} {:^43} {:6} URL", "LOST", "ID", "TRUST");
+    for (lost, id, level) in losses {
+        let (status, url) = crate::url_to_status_str(&db.lookup_url(id));
+        println!("{lost:>4} {id} {level:6} {status} {url}");
+    }
+    eprintln!(
+        "LOST: of {} verified dependencies, ones that wouldn't be if you untrusted the Id",
+        verified_digests.len()
+    );
+    Ok(())
+}
+
+/// Print Ids that would verify the most of the currently unverified crates
+pub fn recommend_ids_to_trust(args: IdRecommend) -> Result<()> {
+    let verify_args = CrateVerify {
+        common: args.common,
+        wot: args.wot,
+        ..Default::default()
+    };
+    let scanner = scan::Scanner::new(args.crate_, &verify_args)?;
+    let trust_set = scanner.trust_set.clone();
+    let db = scanner.db.clone();
+    let local = crev_lib::Local::auto_create_or_open()?;
+    let for_id = local.get_for_id_from_str(verify_args.wot.for_id.as_deref())?;
+    let requirements =
+        crev_lib::VerificationRequirements::from(verify_args.common.requirements.clone());
+
+    let unverified: Vec<Digest> = scanner
+        .run(&RequiredDetails::none())
+        .filter(|stats| !stats.details.accumulative_own.verified)
+        .filter_map(|stats| stats.details.digest)
+        .collect();
+    crate::shared::interrupt_token().check()?;
+
+    let recommendations = crev_lib::recommend::recommend_ids_to_trust(
+        &for_id,
+        &unverified,
+        &trust_set,
+        &requirements,
+        &db,
+    );
+    if recommendations.is_empty() {
+        eprintln!(
+            "No reviewer would verify any of the {} unverified crates",
+            unverified.len()
+        );
+        return Ok(());
+    }
+    eprintln!("{:>3} {:>3} {:^43} URL", "COV", "OVL", "ID");
+    for recommendation in recommendations.iter().take(args.limit) {
+        let (status, url) = crate::url_to_status_str(&db.lookup_url(&recommendation.id));
+        println!(
+            "{:>3} {:>3} {} {} {}",
+            recommendation.covered, recommendation.overlap, recommendation.id, status, url,
+        );
+    }
+    eprintln!(
+        "COV: unverified crates (of {}) it would verify; OVL: of those it reviewed, also reviewed by Ids you trust",
+        unverified.len()
+    );
+    eprintln!("Use `cargo crev id trust --level low <id>` to trust one.");
+
     Ok(())
 }
 
-pub fn verify_deps(crate_: CrateSelector, args: CrateVerify) -> Result<CommandExitStatus> {
+/// Crates that didn't pass `verify`, by reason
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct VerifyFailures {
+    pub verified: usize,
+    /// Trusted negative reviews
+    pub negative: usize,
+    /// No (or not enough) trusted positive reviews
+    pub unreviewed: usize,
+    /// Crates used in more than one version
+    pub duplicate_versions: usize,
+    /// Crates with open issues of at least the `--deny-issues` severity
+    #[serde(default)]
+    pub issues: usize,
+    /// Not verified crates with build scripts or proc macros
+    #[serde(default)]
+    pub unreviewed_build_code: usize,
+    /// Not verified, but exempt by the project policy
+    #[serde(default)]
+    pub exempt: usize,
+    /// Not verified, but waived by a trusted Id
+    #[serde(default)]
+    pub waived: usize,
+}
+
+impl VerifyFailures {
+    pub fn add(&mut self, status: VerificationStatus) {
+        match status {
+            VerificationStatus::Verified => self.verified += 1,
+            VerificationStatus::Negative => self.negative += 1,
+            VerificationStatus::Insufficient | VerificationStatus::Local => self.unreviewed += 1,
+        }
+    }
+
+    pub fn print_summary(&self, term: &mut term::Term) -> Result<()> {
+        let mut passing_anyway = String::new();
+        if self.exempt > 0 {
+            passing_anyway += &format!(", {} exempt", self.exempt);
+        }
+        if self.waived > 0 {
+            passing_anyway += &format!(", {} waived", self.waived);
+        }
+        term.eprint(
+            format_args!(
+                "{} crates: {} verified, {} with negative reviews, {} not reviewed enough{}\n",
+                self.verified + self.negative + self.unreviewed + self.exempt + self.waived,
+                self.verified,
+                self.negative,
+                self.unreviewed,
+                passing_anyway,
+            ),
+            if self.negative + self.unreviewed > 0 {
+                Some(YELLOW)
+            } else {
+                None
+            },
+        )?;
+        if self.duplicate_versions > 0 {
+            term.eprint(
+                format_args!(
+                    "{} crates used in more than one version\n",
+                    self.duplicate_versions
+                ),
+                Some(YELLOW),
+            )?;
+        }
+        if self.issues > 0 {
+            term.eprint(
+                format_args!("{} crates with denied open issues\n", self.issues),
+                Some(YELLOW),
+            )?;
+        }
+        if self.unreviewed_build_code > 0 {
+            term.eprint(
+                format_args!(
+                    "{} of the crates not verified run code at build time (build scripts or proc macros)\n",
+                    self.unreviewed_build_code
+                ),
+                Some(YELLOW),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Negative reviews take precedence, as the more serious problem
+    pub fn exit_status(&self, args: &CrateVerify) -> CommandExitStatus {
+        if self.negative > 0 && args.on_negative == VerifyFailMode::Fail {
+            CommandExitStatus::CommandExitCode(EXIT_CODE_NEGATIVE_REVIEWS)
+        } else if self.unreviewed > 0 && args.on_unreviewed == VerifyFailMode::Fail {
+            CommandExitStatus::VerificationFailed
+        } else if self.issues > 0 {
+            CommandExitStatus::VerificationFailed
+        } else if self.unreviewed_build_code > 0 && args.require_reviewed_build_code {
+            CommandExitStatus::VerificationFailed
+        } else if self.duplicate_versions > 0 && args.deny_duplicate_versions {
+            CommandExitStatus::VerificationFailed
+        } else {
+            CommandExitStatus::Success
+        }
+    }
+}
+
+/// `verify --deep`: verified crates of each kind of dependency
+///
+/// Crates used in more than one way count for each of them.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct VerifiedByUse {
+    pub runtime: VerificationCoverage,
+    pub build: VerificationCoverage,
+    pub dev: VerificationCoverage,
+    /// Only needed because of optional dependencies
+    pub optional: usize,
+    /// Only needed on some targets
+    pub target_specific: usize,
+}
+
+impl VerifiedByUse {
+    pub fn add(&mut self, dependency_use: DependencyUse, status: VerificationStatus) {
+        for (used, coverage) in [
+            (dependency_use.runtime, &mut self.runtime),
+            (dependency_use.build, &mut self.build),
+            (dependency_use.dev, &mut self.dev),
+        ] {
+            if used {
+                coverage.add(status);
+            }
+        }
+        self.optional += usize::from(dependency_use.optional);
+        self.target_specific += usize::from(dependency_use.target_specific);
+    }
+
+    pub fn print_summary(&self) {
+        for (kind, coverage) in [
+            ("runtime", self.runtime),
+            ("build", self.build),
+            ("dev", self.dev),
+        ] {
+            if coverage.total > 0 {
+                eprintln!(
+                    "{kind} dependencies: {} of {} verified",
+                    coverage.verified, coverage.total
+                );
+            }
+        }
+        if self.optional + self.target_specific > 0 {
+            eprintln!(
+                "{} crates only needed with optional features, {} only on some targets",
+                self.optional, self.target_specific
+            );
+        }
+    }
+}
+
+pub fn verify_deps(crate_: CrateSelector, mut args: CrateVerify) -> Result<CommandExitStatus> {
+    if let Ok(local) = crev_lib::Local::auto_open() {
+        crate::usage::record_usage(&local, UsageEvent::VerifyRun);
+    }
+    if let Some(root) = args.workspace_root.clone() {
+        return verify_workspace_root(&crate_, args, &root);
+    }
     let mut term = term::Term::new();
 
-    let scanner = scan::Scanner::new(crate_, &args)?;
+    if args.cached {
+        if let Some(exit_status) = print_cached_verify(&crate_, &args, &mut term)? {
+            return Ok(exit_status);
+        }
+    }
+
+    if args.baseline.is_some() {
+        args.common.requirements = baseline::stricter_requirements(args.common.requirements);
+    }
+
+    let mut scanner = scan::Scanner::new(crate_.clone(), &args)?;
+    if args.fresh {
+        scanner = scanner.fetch_fresh_proofs(&args)?;
+    }
+    if let Some(path) = &args.baseline {
+        if scanner.selected_crate_count() == 0 {
+            eprintln!(
+                "No crates added or changed in version since {}",
+                path.display()
+            );
+        }
+    }
+    let cache = Repo::auto_open_cwd(args.common.cargo_opts.clone())
+        .and_then(|repo| {
+            if args.baseline.is_some() {
+                bail!("`--baseline` verifies only some of the crates");
+            }
+            if args.deep {
+                bail!("`--deep` results are not cached");
+            }
+            cache::VerifyCache::new(&repo, &crate_, &args, &scanner.db, &scanner.trust_set)
+        })
+        .map_err(|e| log::debug!("Not caching the result: {}", e))
+        .ok();
     let has_trusted_ids = scanner.has_trusted_ids;
     let column_widths =
         print_term::VerifyOutputColumnWidths::from_pkgsids(scanner.all_crates_ids.iter());
 
     let trust_set = scanner.trust_set.clone();
+    let duplicate_versions = scanner.duplicate_versions.clone();
+    let policy = scanner.policy.clone();
+    let pinned = scanner.pinned.clone();
 
     let events = scanner.run(&RequiredDetails {
         geiger: args.columns.show_geiger(),
@@ -348,26 +768,71 @@ pub fn verify_deps(crate_: CrateSelector, args: CrateVerify) -> Result<CommandEx
                 &mut term,
                 &args.columns,
                 args.recursive,
+                args.verbose,
                 column_widths,
+                duplicate_versions.contains_key(stats.info.id.name().as_str()),
             )?;
             Ok(stats)
         })
         .collect::<Result<_>>()?;
+    if let Some(pinned) = pinned {
+        pinned.save()?;
+    }
 
     let mut num_crates_with_digest_mismatch = 0;
-    let mut nb_unverified = 0;
+    let mut failures = VerifyFailures {
+        duplicate_versions: duplicate_versions.len(),
+        ..VerifyFailures::default()
+    };
+    let mut policy_failures = vec![];
+    let mut cached_crates = vec![];
+    let mut verified_by_use = VerifiedByUse::default();
     for dep in &deps {
         let details = dep.details();
         if dep.has_digest_mismatch() {
             num_crates_with_digest_mismatch += 1;
         }
-        if !details.accumulative.verified {
-            nb_unverified += 1;
+        cached_crates.push(cache::CachedCrate {
+            name: dep.info.id.name().to_string(),
+            version: dep.info.id.version().clone(),
+            status: print_term::status_label(details),
+            verified: details.accumulative.verified,
+            requirements_rule: details.requirements_rule.clone(),
+            exemption: details.exemption.clone().filter(|_| details.is_exempt()),
+            waiver: details.waiver.clone().filter(|_| details.is_waived()),
+            expired_waiver: details
+                .expired_waiver
+                .clone()
+                .filter(|_| !details.accumulative.verified),
+            similar_to: details.similar_to.clone(),
+            open_issues: details.open_issues.clone(),
+        });
+        let status = if details.accumulative.verified {
+            VerificationStatus::Verified
+        } else {
+            details.accumulative.trust
+        };
+        if details.is_exempt() {
+            failures.exempt += 1;
+        } else if details.is_waived() {
+            failures.waived += 1;
+        } else {
+            failures.add(status);
         }
+        if !details.accumulative_own.verified
+            && !details.is_exempt()
+            && !details.is_waived()
+            && !details.accumulative_own.is_local_source_code
+            && dep.info.runs_code_at_build_time()
+        {
+            failures.unreviewed_build_code += 1;
+        }
+        verified_by_use.add(details.dependency_use, status);
 
         if details.accumulative_own.trusted_issues.count > 0 {
             crates_with_issues = true;
         }
+        policy_failures.extend(details.policy_failures.iter().cloned());
     }
 
     if num_crates_with_digest_mismatch > 0 {
@@ -389,7 +854,7 @@ pub fn verify_deps(crate_: CrateSelector, args: CrateVerify) -> Result<CommandEx
             .map(|dep| dep.info.id.version().to_string().len())
             .max()
             .expect("at least one crate should be present");
-        for dep in deps {
+        for dep in &deps {
             if dep.has_digest_mismatch() {
                 for mismatch in &dep.details.digest_mismatches {
                     term.eprint(
@@ -411,6 +876,20 @@ pub fn verify_deps(crate_: CrateSelector, args: CrateVerify) -> Result<CommandEx
         }
     }
 
+    for dep in &deps {
+        if dep.details.accumulative_own.verified {
+            continue;
+        }
+        for path in &dep.details.verified_subtrees {
+            eprintln!(
+                "Crate {} {}: only `{}` is verified, by reviews of just that directory",
+                dep.info.id.name(),
+                dep.info.id.version(),
+                path
+            );
+        }
+    }
+
     if term.is_interactive() {
         if !args.columns.any_selected() {
             eprintln!("Some columns were hidden. Use one or more `--show-<column>` to print more details. Use `--help` for list of available columns and other options and help. Use `--show-all` to just display everything.");
@@ -427,8 +906,410 @@ pub fn verify_deps(crate_: CrateSelector, args: CrateVerify) -> Result<CommandEx
             writeln!(io::stderr(), "No trusted Ids available. Nothing to verify against. Use `cargo crev trust` to add trusted reviewers or visit https://github.com/crev-dev/cargo-crev/discussions/ for help.")?;
         }
     }
+    print_requirements_rules(&cached_crates);
+    print_exemptions(&cached_crates);
+    print_waivers(&cached_crates);
+    for exemption in policy.expired_exemptions() {
+        term.eprint(
+            format_args!("Exemption expired, remove it from the project policy: {exemption}\n"),
+            YELLOW,
+        )?;
+    }
+    print_similar_names(&mut term, &cached_crates)?;
+    policy_failures.sort();
+    print_policy_failures(&mut term, &policy_failures)?;
+    print_term::print_duplicate_versions(&mut term, &duplicate_versions)?;
+    failures.issues =
+        print_denied_issues(&mut term, cached_issues(&cached_crates), args.deny_issues)?;
+    failures.print_summary(&mut term)?;
+    if args.deep {
+        verified_by_use.print_summary();
+    }
+
+    if let Some(cache) = cache {
+        if let Err(e) = cache.store(cached_crates, policy_failures, failures) {
+            log::debug!("Couldn't cache the result: {}", e);
+        }
+    }
+
+    Ok(failures.exit_status(&args))
+}
+
+/// Crates passing only because of exemptions in the project policy
+fn print_exemptions(crates: &[cache::CachedCrate]) {
+    let mut exempt = crates
+        .iter()
+        .filter_map(|crate_| Some((crate_, crate_.exemption.as_ref()?)))
+        .peekable();
+    if exempt.peek().is_none() {
+        return;
+    }
+    eprintln!("Crates not verified, but exempt by the project policy:");
+    for (crate_, exemption) in exempt {
+        eprintln!("  {} {}: {}", crate_.name, crate_.version, exemption);
+    }
+}
+
+/// Crates passing only because of waivers, and those whose waivers expired
+fn print_waivers(crates: &[cache::CachedCrate]) {
+    let mut waived = crates
+        .iter()
+        .filter_map(|crate_| Some((crate_, crate_.waiver.as_ref()?)))
+        .peekable();
+    if waived.peek().is_some() {
+        eprintln!("Crates not verified, but waived:");
+        for (crate_, waiver) in waived {
+            eprintln!("  {} {}: {}", crate_.name, crate_.version, waiver);
+        }
+    }
+    let mut expired = crates
+        .iter()
+        .filter_map(|crate_| Some((crate_, crate_.expired_waiver.as_ref()?)))
+        .peekable();
+    if expired.peek().is_some() {
+        eprintln!(
+            "Waivers expired, review the crates or renew them with `cargo crev crate exempt`:"
+        );
+        for (crate_, waiver) in expired {
+            eprintln!("  {} {}: {}", crate_.name, crate_.version, waiver);
+        }
+    }
+}
+
+/// Crates verified with the requirements of the project policy, and which ones
+fn print_requirements_rules(crates: &[cache::CachedCrate]) {
+    let mut with_rule = crates
+        .iter()
+        .filter_map(|crate_| Some((crate_, crate_.requirements_rule.as_ref()?)))
+        .peekable();
+    if with_rule.peek().is_none() {
+        return;
+    }
+    eprintln!("Crates verified with requirements from the project policy:");
+    for (crate_, rule) in with_rule {
+        eprintln!("  {} {}: {}", crate_.name, crate_.version, rule);
+    }
+}
+
+/// Possible typosquats, see [`crate::typosquat`]
+fn print_similar_names(term: &mut term::Term, crates: &[cache::CachedCrate]) -> Result<()> {
+    let mut similar = crates
+        .iter()
+        .filter_map(|crate_| Some((crate_, crate_.similar_to.as_ref()?)))
+        .peekable();
+    if similar.peek().is_none() {
+        return Ok(());
+    }
+    term.eprint(
+        format_args!(
+            "Unreviewed crates named like popular reviewed ones (possible typosquatting):\n"
+        ),
+        ::term::color::RED,
+    )?;
+    for (crate_, similar_to) in similar {
+        eprintln!(
+            "  {} {} looks like {}",
+            crate_.name, crate_.version, similar_to
+        );
+    }
+    eprintln!("Make sure these are the crates you meant to depend on.");
+    Ok(())
+}
+
+/// Open issues of `crates`, for `print_denied_issues`
+fn cached_issues(
+    crates: &[cache::CachedCrate],
+) -> impl Iterator<Item = (String, &[OpenIssue])> + '_ {
+    crates.iter().map(|crate_| {
+        (
+            format!("{} {}", crate_.name, crate_.version),
+            crate_.open_issues.as_slice(),
+        )
+    })
+}
+
+/// Crates with open issues of at least `min_severity`, see `--deny-issues`
+///
+/// Returns how many there are.
+fn print_denied_issues<'a>(
+    term: &mut term::Term,
+    crates: impl IntoIterator<Item = (String, &'a [OpenIssue])>,
+    min_severity: Option<Level>,
+) -> Result<usize> {
+    let Some(min_severity) = min_severity else {
+        return Ok(0);
+    };
+    let denied: Vec<_> = crates
+        .into_iter()
+        .map(|(crate_, issues)| {
+            let issues: Vec<_> = issues
+                .iter()
+                .filter(|issue| min_severity <= issue.severity)
+                .collect();
+            (crate_, issues)
+        })
+        .filter(|(_, issues)| !issues.is_empty())
+        .collect();
+    if denied.is_empty() {
+        return Ok(0);
+    }
+    term.eprint(
+        format_args!("Crates with open issues of {min_severity} or higher severity:\n"),
+        ::term::color::RED,
+    )?;
+    for (crate_, issues) in &denied {
+        eprintln!("  {crate_}:");
+        for issue in issues {
+            eprintln!("    {} ({})", issue.id, issue.severity);
+        }
+    }
+    eprintln!("Use `cargo crev repo query issue <crate> [<version>]` for details.");
+    Ok(denied.len())
+}
+
+fn print_policy_failures(term: &mut term::Term, policy_failures: &[String]) -> Result<()> {
+    if policy_failures.is_empty() {
+        return Ok(());
+    }
+    term.eprint(
+        format_args!("Crates not meeting the project review policy:\n"),
+        ::term::color::RED,
+    )?;
+    for failure in policy_failures {
+        eprintln!("  {failure}");
+    }
+    eprintln!("Ask the required reviewers to review these crates, and make sure their proofs are fetched (`cargo crev repo fetch url <url>`).");
+    Ok(())
+}
+
+/// Print the result of the previous `verify`, if none of its inputs changed
+///
+/// Returns `None` if there's no such result.
+fn print_cached_verify(
+    crate_: &CrateSelector,
+    args: &CrateVerify,
+    term: &mut term::Term,
+) -> Result<Option<CommandExitStatus>> {
+    let local = crev_lib::Local::auto_create_or_open()?;
+    let db = local.load_db()?;
+    let trust_set = local.trust_set_for_id(
+        args.wot.for_id.as_deref(),
+        &args.wot.trust_params.clone().into(),
+        &db,
+    )?;
+    let repo = Repo::auto_open_cwd(args.common.cargo_opts.clone())?;
+    let cache = cache::VerifyCache::new(&repo, crate_, args, &db, &trust_set)?;
+    let Some(mut cached) = cache.load() else {
+        eprintln!("No cached result for the current inputs; verifying");
+        return Ok(None);
+    };
+
+    for cached_crate in &cached.crates {
+        println!(
+            "{:6} {} {}",
+            cached_crate.status, cached_crate.name, cached_crate.version
+        );
+    }
+    print_requirements_rules(&cached.crates);
+    print_exemptions(&cached.crates);
+    print_waivers(&cached.crates);
+    print_similar_names(term, &cached.crates)?;
+    print_policy_failures(term, &cached.policy_failures)?;
+    cached.failures.issues =
+        print_denied_issues(term, cached_issues(&cached.crates), args.deny_issues)?;
+    cached.failures.print_summary(term)?;
+    eprintln!(
+        "Cached result, computed on {}",
+        cached.computed.format("%Y-%m-%d %H:%M UTC")
+    );
+    Ok(Some(cached.failures.exit_status(args)))
+}
+
+/// Directories with a `Cargo.lock` in `root` (or under it, if `recursive`)
+fn find_cargo_projects(root: &Path, recursive: bool) -> Result<Vec<PathBuf>> {
+    let max_depth = if recursive { usize::MAX } else { 1 };
+    let mut projects = vec![];
+    let mut walker = walkdir::WalkDir::new(root)
+        .max_depth(max_depth)
+        .sort_by_file_name()
+        .into_iter();
+    while let Some(entry) = walker.next() {
+        let entry = entry?;
+        if !entry.file_type().is_dir() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy();
+        if entry.depth() > 0 && (name.starts_with('.') || name == "target") {
+            walker.skip_current_dir();
+            continue;
+        }
+        if entry.path().join("Cargo.lock").is_file() {
+            projects.push(entry.into_path());
+        }
+    }
+    Ok(projects)
+}
+
+/// `verify` for many independent projects at once
+///
+/// Prints the status of each project, and of all their dependencies together,
+/// counting a crate used by many projects once.
+fn verify_workspace_root(
+    crate_: &CrateSelector,
+    args: CrateVerify,
+    root: &Path,
+) -> Result<CommandExitStatus> {
+    if crate_.name.is_some() {
+        bail!("Can't select a crate together with `--workspace-root`");
+    }
+    let projects = find_cargo_projects(root, args.recursive)?;
+    if projects.is_empty() {
+        bail!(
+            "No cargo projects (`Cargo.lock` files) found in {}",
+            root.display()
+        );
+    }
+
+    let mut term = term::Term::new();
+    // verification status, and the number of projects using it
+    let mut all_deps: HashMap<PackageId, (VerificationStatus, usize)> = HashMap::new();
+    let mut all_open_issues: BTreeMap<PackageId, Vec<OpenIssue>> = BTreeMap::new();
+    let mut unreviewed_build_code: HashSet<PackageId> = HashSet::new();
+    let mut nb_projects_unverified = 0;
+    // counted once per crate name, even if several projects use it in more than one version
+    let mut all_duplicate_versions = DuplicateVersions::new();
+
+    for project in &projects {
+        let mut project_args = args.clone();
+        project_args.common.cargo_opts.manifest_path = Some(project.join("Cargo.toml"));
+        let scanner = scan::Scanner::new(CrateSelector::default(), &project_args)?;
+        for (name, ids) in &scanner.duplicate_versions {
+            let all_ids = all_duplicate_versions.entry(name.clone()).or_default();
+            all_ids.extend(ids);
+            all_ids.sort();
+            all_ids.dedup();
+        }
+        let deps: Vec<_> = scanner
+            .run(&RequiredDetails::none())
+            .filter(|stats| !stats.details.accumulative_own.is_local_source_code)
+            .collect();
+        crate::shared::interrupt_token().check()?;
+
+        let nb_unverified = deps
+            .iter()
+            .filter(|stats| !stats.details.accumulative_own.verified)
+            .count();
+        for stats in &deps {
+            let dep = all_deps
+                .entry(stats.info.id)
+                .or_insert((stats.details.accumulative_own.trust, 0));
+            dep.1 += 1;
+            if !stats.details.accumulative_own.verified && stats.info.runs_code_at_build_time() {
+                unreviewed_build_code.insert(stats.info.id);
+            }
+            if !stats.details.open_issues.is_empty() {
+                all_open_issues
+                    .entry(stats.info.id)
+                    .or_insert_with(|| stats.details.open_issues.clone());
+            }
+        }
+
+        let status = deps
+            .iter()
+            .map(|stats| stats.details.accumulative_own.trust)
+            .fold(VerificationStatus::Verified, VerificationStatus::min);
+        if nb_unverified > 0 {
+            nb_projects_unverified += 1;
+        }
+        term.print(
+            format_args!("{:4}", status),
+            term::verification_status_color(status),
+        )?;
+        println!(
+            " {} ({} crates, {} not verified)",
+            project.display(),
+            deps.len(),
+            nb_unverified
+        );
+    }
+
+    let mut failures = VerifyFailures {
+        duplicate_versions: all_duplicate_versions.len(),
+        unreviewed_build_code: unreviewed_build_code.len(),
+        ..VerifyFailures::default()
+    };
+    for (status, _) in all_deps.values() {
+        failures.add(*status);
+    }
+    let mut unverified: Vec<_> = all_deps
+        .iter()
+        .filter(|(_, (status, _))| !status.is_verified())
+        .collect();
+    unverified.sort_by_key(|(id, _)| **id);
+    println!(
+        "\n{} projects, {} not verified; {} unique crates, {} not verified",
+        projects.len(),
+        nb_projects_unverified,
+        all_deps.len(),
+        unverified.len()
+    );
+    for (id, (status, nb_projects)) in unverified {
+        term.print(
+            format_args!("  {:4}", status),
+            term::verification_status_color(*status),
+        )?;
+        println!(
+            " {} {} (used by {} project{})",
+            id.name(),
+            version_with_source(id),
+            nb_projects,
+            if *nb_projects == 1 { "" } else { "s" }
+        );
+    }
+    failures.issues = print_denied_issues(
+        &mut term,
+        all_open_issues.iter().map(|(id, issues)| {
+            (
+                format!("{} {}", id.name(), version_with_source(id)),
+                issues.as_slice(),
+            )
+        }),
+        args.deny_issues,
+    )?;
+    failures.print_summary(&mut term)?;
+
+    Ok(failures.exit_status(&args))
+}
+
+/// Verify a single digest computed elsewhere, without looking at any cargo project
+pub fn verify_digest(args: VerifyDigest) -> Result<CommandExitStatus> {
+    let digest_str = match args.digest {
+        Some(digest) => digest,
+        None => {
+            let mut input = String::new();
+            io::stdin().read_line(&mut input)?;
+            input
+        }
+    };
+    let digest_str = digest_str.trim();
+    let digest = crev_common::base64_decode(digest_str)
+        .ok()
+        .and_then(|bytes| Digest::from_bytes(&bytes))
+        .ok_or_else(|| format_err!("Not a valid crev digest: {}", digest_str))?;
+
+    let local = crev_lib::Local::auto_create_or_open()?;
+    let db = local.load_db()?;
+    let trust_set = local.trust_set_for_id(
+        args.wot.for_id.as_deref(),
+        &args.wot.trust_params.into(),
+        &db,
+    )?;
+    let requirements = crev_lib::VerificationRequirements::from(args.requirements);
+
+    let status = crev_lib::verify_package_digest(&digest, &trust_set, &requirements, &db);
+    println!("{status} {digest}");
 
-    Ok(if nb_unverified == 0 {
+    Ok(if status.is_verified() {
         CommandExitStatus::Success
     } else {
         CommandExitStatus::VerificationFailed
@@ -0,0 +1,97 @@
+//! `verify --baseline`: verify only crates a change of `Cargo.lock` brings in
+//!
+//! In a per-PR CI gate, crates that were already in the lockfile of the
+//! target branch were accepted before, and listing them again is just noise.
+//! Crates that are new, or in a new version, get stricter requirements instead.
+use crate::{opts::VerificationRequirements, prelude::*};
+use cargo::core::PackageId;
+use crev_data::Level;
+use serde::Deserialize;
+use std::{collections::HashSet, path::Path};
+
+#[derive(Deserialize)]
+struct Lockfile {
+    #[serde(default)]
+    package: Vec<LockedPackage>,
+}
+
+#[derive(Deserialize)]
+struct LockedPackage {
+    name: String,
+    version: String,
+}
+
+/// Crate versions locked in the baseline `Cargo.lock`
+#[derive(Debug, Default)]
+pub struct Baseline {
+    packages: HashSet<(String, String)>,
+}
+
+impl Baseline {
+    pub fn read(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format_err!("Can't read baseline {}: {e}", path.display()))?;
+        Self::parse(&content)
+            .map_err(|e| format_err!("Can't parse baseline {}: {e}", path.display()))
+    }
+
+    fn parse(content: &str) -> Result<Self> {
+        let lockfile: Lockfile = toml::from_str(content)?;
+        Ok(Self {
+            packages: lockfile
+                .package
+                .into_iter()
+                .map(|package| (package.name, package.version))
+                .collect(),
+        })
+    }
+
+    /// Same name and version is in the baseline, from any source
+    pub fn contains(&self, pkg_id: PackageId) -> bool {
+        self.packages
+            .contains(&(pkg_id.name().to_string(), pkg_id.version().to_string()))
+    }
+}
+
+/// Requirements for crates not in the baseline: at least medium trust,
+/// and low thoroughness and understanding
+pub fn stricter_requirements(requirements: VerificationRequirements) -> VerificationRequirements {
+    let mut stricter = requirements;
+    stricter.trust_level.trust_level = stricter.trust_level.trust_level.max(Level::Medium);
+    stricter.understanding_level = stricter.understanding_level.max(Level::Low);
+    stricter.thoroughness_level = stricter.thoroughness_level.max(Level::Low);
+    stricter
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_lockfile() -> Result<()> {
+        let baseline = Baseline::parse(
+            r#"
+version = 3
+
+[[package]]
+name = "serde"
+version = "1.0.193"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "my-project"
+version = "0.1.0"
+dependencies = ["serde"]
+"#,
+        )?;
+        assert!(baseline
+            .packages
+            .contains(&("serde".to_owned(), "1.0.193".to_owned())));
+        assert!(baseline
+            .packages
+            .contains(&("my-project".to_owned(), "0.1.0".to_owned())));
+        assert_eq!(baseline.packages.len(), 2);
+        assert!(Baseline::parse("package = 1").is_err());
+        Ok(())
+    }
+}
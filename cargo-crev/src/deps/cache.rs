@@ -0,0 +1,254 @@
+//! `verify --cached`: reuse the result of the previous `verify` run
+//!
+//! The last result is kept for every project, along with a key digesting
+//! everything it depends on: the lockfile, the effective trust set, the
+//! reviews by trusted Ids with the issues and advisories they report, issue
+//! updates, the requirements and the options selecting crates.
+use crate::{
+    deps::{OpenIssue, VerifyFailures},
+    opts::{CrateSelector, CrateVerify},
+    policy::{self, ReviewPolicy},
+    prelude::*,
+    repo::Repo,
+};
+use chrono::{DateTime, Utc};
+use crev_data::{Id, SOURCE_CRATES_IO};
+use crev_wot::{ProofDB, TrustSet};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeSet, HashSet},
+    fmt::Write as _,
+    path::PathBuf,
+};
+
+/// Verification result of one crate, as printed by `verify`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedCrate {
+    pub name: String,
+    pub version: Version,
+    pub status: String,
+    pub verified: bool,
+    /// Requirements of the project policy it was verified with, if not the global ones
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requirements_rule: Option<String>,
+    /// Exemption of the project policy it passed with, see [`crate::policy`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exemption: Option<String>,
+    /// Waiver it passed with, see [`crate::waiver`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub waiver: Option<String>,
+    /// Waiver that expired, while it's still not verified
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expired_waiver: Option<String>,
+    /// Popular reviewed crate its name looks like, see [`crate::typosquat`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub similar_to: Option<String>,
+    /// Open issues reported by trusted Ids
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub open_issues: Vec<OpenIssue>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct CachedVerifyResult {
+    key: String,
+    pub computed: DateTime<Utc>,
+    pub crates: Vec<CachedCrate>,
+    pub policy_failures: Vec<String>,
+    pub failures: VerifyFailures,
+}
+
+/// Where the result for the current project is kept, and its current key
+pub struct VerifyCache {
+    path: PathBuf,
+    key: String,
+}
+
+impl VerifyCache {
+    pub fn new(
+        repo: &Repo,
+        crate_: &CrateSelector,
+        args: &CrateVerify,
+        db: &ProofDB,
+        trust_set: &TrustSet,
+    ) -> Result<Self> {
+        let local = crev_lib::Local::auto_create_or_open()?;
+        let manifest_path = repo.get_manifest_path()?;
+        let lockfile_path = repo.get_lockfile_path()?;
+        let policy_path = policy::policy_path(args.policy.as_deref(), &manifest_path);
+        let requirements =
+            crev_lib::VerificationRequirements::from(args.common.requirements.clone());
+
+        let mut input = format!("cargo-crev {}\n", env!("CARGO_PKG_VERSION"));
+        writeln!(input, "trust-set {}", trust_set.stable_digest())?;
+        writeln!(input, "requirements {}", requirements.stable_digest())?;
+        writeln!(
+            input,
+            "trusted-reviews {}",
+            trusted_reviews_digest(db, trust_set)
+        )?;
+        writeln!(
+            input,
+            "issue-updates {}",
+            trusted_issue_updates_digest(db, trust_set)
+        )?;
+        let own_ids: HashSet<Id> = local
+            .get_current_user_public_ids()?
+            .into_iter()
+            .map(|id| id.id)
+            .collect();
+        if let Some(waivers) = trusted_waivers_digest(db, trust_set, &own_ids) {
+            // waivers end with the day too
+            writeln!(input, "waivers {waivers} {}", policy::today())?;
+        }
+        writeln!(
+            input,
+            "selection {:?} {:?} {:?} {} {} {}",
+            crate_,
+            args.common.cargo_opts,
+            args.common.digest_ignore,
+            args.skip_indirect,
+            args.skip_verified,
+            args.skip_known_owners
+        )?;
+        if let Some(path) = &policy_path {
+            // exemptions end with the day
+            if ReviewPolicy::load(path)?.has_exemptions() {
+                writeln!(input, "date {}", policy::today())?;
+            }
+        }
+        let mut input = input.into_bytes();
+        input.extend(std::fs::read(&lockfile_path)?);
+        if let Some(path) = &policy_path {
+            input.extend(std::fs::read(path).unwrap_or_default());
+        }
+
+        let project = lockfile_path.display().to_string();
+        Ok(Self {
+            path: local
+                .cache_root()
+                .join("verify")
+                .join(crev_common::sanitize_name_for_fs(&project))
+                .with_extension("json"),
+            key: crev_common::base64_encode(&crev_common::blake2b256sum(&input)),
+        })
+    }
+
+    /// The previous result, if none of its inputs changed since
+    pub fn load(&self) -> Option<CachedVerifyResult> {
+        let json = std::fs::read(&self.path).ok()?;
+        let cached: CachedVerifyResult = serde_json::from_slice(&json)
+            .map_err(|e| log::debug!("Ignoring {}: {}", self.path.display(), e))
+            .ok()?;
+        (cached.key == self.key).then_some(cached)
+    }
+
+    pub fn store(
+        &self,
+        crates: Vec<CachedCrate>,
+        policy_failures: Vec<String>,
+        failures: VerifyFailures,
+    ) -> Result<()> {
+        let result = CachedVerifyResult {
+            key: self.key.clone(),
+            computed: Utc::now(),
+            crates,
+            policy_failures,
+            failures,
+        };
+        std::fs::create_dir_all(self.path.parent().expect("cache file has a dir"))?;
+        crev_common::store_str_to_file(&self.path, &serde_json::to_string(&result)?)?;
+        Ok(())
+    }
+}
+
+/// Digest of the waivers by trusted or own Ids, if there are any
+fn trusted_waivers_digest(
+    db: &ProofDB,
+    trust_set: &TrustSet,
+    own_ids: &HashSet<Id>,
+) -> Option<String> {
+    let waivers: BTreeSet<String> = db
+        .all_waivers()
+        .filter(|waiver| {
+            let author = &waiver.common.from.id;
+            own_ids.contains(author) || trust_set.is_trusted(author)
+        })
+        .map(|waiver| {
+            format!(
+                "{} {} {} {}",
+                waiver.common.from.id,
+                waiver.package.id.name,
+                waiver.package.version,
+                waiver.common.date.to_rfc3339()
+            )
+        })
+        .collect();
+    if waivers.is_empty() {
+        return None;
+    }
+    let text = waivers.into_iter().collect::<Vec<_>>().join("\n");
+    Some(crev_common::base64_encode(&crev_common::blake2b256sum(
+        text.as_bytes(),
+    )))
+}
+
+/// Digest of the crates.io package reviews by trusted Ids, to notice newly fetched ones
+///
+/// The issues and advisories they report are part of it, as they open
+/// issues of other versions than the reviewed one.
+fn trusted_reviews_digest(db: &ProofDB, trust_set: &TrustSet) -> String {
+    let reviews: BTreeSet<String> = db
+        .get_pkg_reviews_for_source(SOURCE_CRATES_IO)
+        .filter(|review| trust_set.is_trusted(&review.common.from.id))
+        .map(|review| {
+            let mut line = format!(
+                "{} {} {} {}",
+                review.common.from.id,
+                review.package.id.id.name,
+                review.package.id.version,
+                review.common.date.to_rfc3339()
+            );
+            for issue in &review.issues {
+                let _ = write!(
+                    line,
+                    " issue {} {} {:?}",
+                    issue.id, issue.severity, issue.range
+                );
+            }
+            for advisory in &review.advisories {
+                let _ = write!(
+                    line,
+                    " advisory {} {} {:?}",
+                    advisory.ids.join(","),
+                    advisory.severity,
+                    advisory.range
+                );
+            }
+            line
+        })
+        .collect();
+    let text = reviews.into_iter().collect::<Vec<_>>().join("\n");
+    crev_common::base64_encode(&crev_common::blake2b256sum(text.as_bytes()))
+}
+
+/// Digest of the issue updates by trusted Ids, which close or change reported issues
+fn trusted_issue_updates_digest(db: &ProofDB, trust_set: &TrustSet) -> String {
+    let updates: BTreeSet<String> = db
+        .all_issue_updates()
+        .filter(|update| trust_set.is_trusted(&update.common.from.id))
+        .map(|update| {
+            format!(
+                "{} {} {} {} {} {}",
+                update.common.from.id,
+                update.package.name,
+                update.issue,
+                crev_common::base64_encode(&update.reported_in),
+                update.action,
+                update.common.date.to_rfc3339()
+            )
+        })
+        .collect();
+    let text = updates.into_iter().collect::<Vec<_>>().join("\n");
+    crev_common::base64_encode(&crev_common::blake2b256sum(text.as_bytes()))
+}
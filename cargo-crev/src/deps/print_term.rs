@@ -3,6 +3,7 @@
 
 use super::*;
 use crate::term::{self, Term};
+use itertools::Itertools;
 use std::{io, io::Write, write, writeln};
 
 const CRATE_VERIFY_CRATE_COLUMN_TITLE: &str = "crate";
@@ -69,7 +70,7 @@ pub fn print_header(
     }
 
     if columns.show_flags() {
-        write!(io::stdout(), "{:>4} ", "flgs")?;
+        write!(io::stdout(), "{:>6} ", "flgs")?;
     }
 
     let name_column_width = column_widths.name;
@@ -93,6 +94,23 @@ pub fn print_header(
     Ok(())
 }
 
+/// The status column: `local`, `N/A` (nothing to verify against) or the verification status
+pub fn status_label(cdep: &CrateDetails) -> String {
+    if cdep.accumulative.is_local_source_code {
+        "local".into()
+    } else if cdep.is_exempt() {
+        "exempt".into()
+    } else if cdep.is_waived() {
+        "waiv".into()
+    } else if !cdep.accumulative.has_trusted_ids
+        && cdep.accumulative.trust == VerificationStatus::Insufficient
+    {
+        "N/A".into()
+    } else {
+        cdep.accumulative.trust.to_string()
+    }
+}
+
 #[allow(clippy::collapsible_if)]
 pub fn write_details(
     cdep: &CrateDetails,
@@ -100,18 +118,13 @@ pub fn write_details(
     columns: &CrateVerifyColumns,
     recursive_mode: bool,
 ) -> Result<()> {
-    if cdep.accumulative.is_local_source_code {
-        term.print(format_args!("{:6} ", "local"), None)?;
-    } else if !cdep.accumulative.has_trusted_ids
-        && cdep.accumulative.trust == VerificationStatus::Insufficient
-    {
-        term.print(format_args!("{:6} ", "N/A"), None)?;
+    let label = status_label(cdep);
+    let color = if label == cdep.accumulative.trust.to_string() {
+        term::verification_status_color(cdep.accumulative.trust)
     } else {
-        term.print(
-            format_args!("{:6} ", cdep.accumulative.trust),
-            term::verification_status_color(cdep.accumulative.trust),
-        )?;
-    }
+        None
+    };
+    term.print(format_args!("{:6} ", label), color)?;
 
     if columns.show_reviews() {
         write!(
@@ -211,6 +224,7 @@ fn write_stats_crate_id(
     stats: &CrateStats,
     _term: &mut Term,
     column_widths: VerifyOutputColumnWidths,
+    is_duplicate: bool,
 ) -> Result<()> {
     let name_column_width = column_widths.name;
     let version_column_width = column_widths.version;
@@ -224,6 +238,7 @@ fn write_stats_crate_id(
             } else {
                 "*"
             }
+            + if is_duplicate { "+" } else { "" }
     )?;
     Ok(())
 }
@@ -233,7 +248,9 @@ pub fn print_dep(
     term: &mut Term,
     columns: &CrateVerifyColumns,
     recursive_mode: bool,
+    verbose: bool,
     column_widths: VerifyOutputColumnWidths,
+    is_duplicate: bool,
 ) -> Result<()> {
     let details = stats.details();
 
@@ -252,6 +269,12 @@ pub fn print_dep(
             write!(io::stdout(), "__")?;
         }
 
+        if stats.is_proc_macro() {
+            term.print(format_args!("PM"), ::term::color::YELLOW)?;
+        } else {
+            write!(io::stdout(), "__")?;
+        }
+
         if stats.is_unmaintained() {
             write!(io::stdout(), "UM")?;
         } else {
@@ -260,7 +283,7 @@ pub fn print_dep(
         write!(io::stdout(), " ")?;
     }
 
-    write_stats_crate_id(stats, term, column_widths)?;
+    write_stats_crate_id(stats, term, column_widths, is_duplicate)?;
 
     if columns.show_latest_trusted() {
         write!(
@@ -282,5 +305,63 @@ pub fn print_dep(
     }
 
     writeln!(io::stdout())?;
+    if verbose {
+        print_negative_reviews(term, &details.negative_reviews)?;
+    }
+    Ok(())
+}
+
+/// Longest part of a review comment shown by `print_negative_reviews`
+const COMMENT_EXCERPT_LEN: usize = 100;
+
+/// Why the crate failed, under its row: who reviewed it negatively, and what they said
+fn print_negative_reviews(term: &mut Term, reviews: &[review::Package]) -> Result<()> {
+    for review in reviews {
+        term.print(
+            format_args!("    negative review by {}", review.common.from.id),
+            ::term::color::RED,
+        )?;
+        writeln!(io::stdout(), " ({})", review.common.from.url_display())?;
+        let comment = review.comment.trim();
+        if let Some(line) = comment.lines().next() {
+            let mut excerpt: String = line.chars().take(COMMENT_EXCERPT_LEN).collect();
+            if excerpt.len() < comment.len() {
+                excerpt.push_str("...");
+            }
+            writeln!(io::stdout(), "      {excerpt}")?;
+        }
+        let ids = review
+            .issues
+            .iter()
+            .map(|issue| issue.id.as_str())
+            .chain(
+                review
+                    .advisories
+                    .iter()
+                    .flat_map(|advisory| advisory.ids.iter().map(String::as_str)),
+            )
+            .join(", ");
+        if !ids.is_empty() {
+            writeln!(io::stdout(), "      issues: {ids}")?;
+        }
+    }
+    Ok(())
+}
+
+/// Crates marked with `+` in the table, with all their versions together
+pub fn print_duplicate_versions(term: &mut Term, duplicates: &DuplicateVersions) -> Result<()> {
+    if duplicates.is_empty() {
+        return Ok(());
+    }
+    term.eprint(
+        format_args!("Crates used in more than one version (marked with +):\n"),
+        ::term::color::YELLOW,
+    )?;
+    for (name, ids) in duplicates {
+        eprintln!(
+            "  {name} {}",
+            ids.iter().map(version_with_source).join(", ")
+        );
+    }
     Ok(())
 }
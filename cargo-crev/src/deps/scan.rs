@@ -1,30 +1,35 @@
 use crate::{
     crates_io,
     deps::{
-        AccumulativeCrateDetails, CountWithTotal, CrateDetails, CrateInfo, CrateStats, OwnerSetSet,
+        duplicate_versions, AccumulativeCrateDetails, CountWithTotal, CrateDetails, CrateInfo,
+        CrateStats, DuplicateVersions, OwnerSetSet,
     },
-    opts::{CargoOpts, CrateSelector, CrateVerify},
+    opts::{CargoOpts, CrateSelector, CrateVerify, RequirementsOverride},
+    policy::{self, ReviewPolicy},
     prelude::*,
-    repo::Repo,
-    shared::{
-        cargo_full_ignore_list, cargo_min_ignore_list, get_crate_digest_mismatches,
-        get_geiger_count, read_known_owners_list,
-    },
+    repo::{CratesIoMirrors, DependencyUse, Repo},
+    shared::{cargo_full_ignore_list, get_geiger_count, interrupt_token, read_known_owners_list},
+    typosquat::PopularNames,
 };
 use cargo::core::PackageId;
-use crev_data::proof::{self, CommonOps};
-use crev_data::SOURCE_CRATES_IO;
-use crev_lib::{self, VerificationStatus};
+use crev_data::{Digest, Id};
+use crev_lib::{
+    self, get_dir_digest_cancellable,
+    pinned_inputs::PinnedInputs,
+    verify::{get_git_crate_digest, get_registry_crate_digest, select_registry_crate_digest},
+    DigestIgnorePolicy, VerificationStatus, Warning,
+};
 use crev_wot::{self, ProofDB, TrustSet};
 use crossbeam::{self, channel::unbounded};
 use log::debug;
+use rayon::prelude::*;
 use std::{
     collections::{HashMap, HashSet},
     default::Default,
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::{
         atomic::{self, AtomicBool, Ordering},
-        Arc, Mutex,
+        Arc, Mutex, RwLock,
     },
     thread::sleep,
     time::Duration,
@@ -60,20 +65,124 @@ pub struct Scanner {
     pub trust_set: TrustSet,
     /// True if trust_set is not empty
     pub has_trusted_ids: bool,
-    min_ignore_list: fnv::FnvHashSet<PathBuf>,
+    /// The user's own Ids, whose waivers always count
+    own_ids: Arc<HashSet<crev_data::Id>>,
+    digest_policy: DigestIgnorePolicy,
     full_ignore_list: fnv::FnvHashSet<PathBuf>,
     local: Arc<crev_lib::Local>,
     known_owners: HashSet<String>,
     requirements: crev_lib::VerificationRequirements,
+    /// `verify --deep`: how each crate is used, for its requirements
+    dependency_uses: Arc<HashMap<PackageId, DependencyUse>>,
+    build_requirements: RequirementsOverride,
+    dev_requirements: RequirementsOverride,
     recursive: bool,
     crate_info_by_id: HashMap<PackageId, CrateInfo>,
     // all the packages that we might need to potentially analyse
     pub all_crates_ids: Vec<PackageId>,
+    // crates present in more than one version or from more than one source
+    pub duplicate_versions: DuplicateVersions,
     // packages that we will have to return to the caller
     selected_crates_ids: HashSet<PackageId>,
     cargo_opts: CargoOpts,
+    pub policy: Arc<ReviewPolicy>,
+    crates_io_mirrors: CratesIoMirrors,
+    /// With `--pin-inputs`
+    pub pinned: Option<Pinned>,
     graph: Arc<crate::repo::Graph>,
     crate_details_by_id: Arc<Mutex<HashMap<PackageId, CrateDetails>>>,
+    popular_names: Arc<PopularNames>,
+    /// Digests computed before the proofs were loaded, see `fetch_fresh_proofs`
+    hashed_crates: Arc<Mutex<HashMap<PackageId, HashedCrate>>>,
+}
+
+/// Digests of a crate, before it's known which one was reviewed
+#[derive(Debug, Clone)]
+struct HashedCrate {
+    digest: Digest,
+    /// With the local ignore list, for registry crates whose `digest` wasn't reviewed yet
+    local_digest: Option<Digest>,
+}
+
+/// Inputs pinned with `--pin-inputs`, see `PinnedInputs`
+#[derive(Clone)]
+pub struct Pinned {
+    path: PathBuf,
+    inputs: PinnedInputs,
+    crates_io: crates_io::Pin,
+}
+
+impl Pinned {
+    /// Add the crates.io data used to the pin file, if it was pinned by this run
+    pub fn save(&self) -> Result<()> {
+        if let crates_io::Pin::Record(recorded) = &self.crates_io {
+            let mut inputs = self.inputs.clone();
+            inputs.crates_io = recorded.lock().unwrap().clone();
+            crev_common::save_to_yaml_file(&self.path, &inputs)?;
+        }
+        Ok(())
+    }
+}
+
+/// Load the db with the inputs pinned in `path`, pinning the current ones first if it doesn't exist
+fn load_db_pinned(
+    local: &crev_lib::Local,
+    path: &Path,
+    for_id: Option<&str>,
+) -> Result<(ProofDB, Pinned)> {
+    let (inputs, crates_io) = if path.exists() {
+        let inputs: PinnedInputs = crev_common::read_from_yaml_file(path)?;
+        if let Some(for_id) = for_id {
+            if inputs.for_id.as_ref() != Some(&Id::crevid_from_str(for_id)?) {
+                return Err(crev_lib::Error::PinnedInputsMismatch(format!(
+                    "the web of trust of another Id is pinned in {}",
+                    path.display()
+                ))
+                .into());
+            }
+        }
+        let crates_io = crates_io::Pin::Replay(Arc::new(inputs.crates_io.clone()));
+        (inputs, crates_io)
+    } else {
+        let inputs = local.pin_inputs(for_id)?;
+        crev_common::save_to_yaml_file(path, &inputs)?;
+        eprintln!("Inputs pinned in {}", path.display());
+        (inputs, crates_io::Pin::Record(Default::default()))
+    };
+    let db = local.load_db_pinned(&inputs)?;
+    let pinned = Pinned {
+        path: path.to_owned(),
+        inputs,
+        crates_io,
+    };
+    Ok((db, pinned))
+}
+
+fn load_trust_set(
+    local: &crev_lib::Local,
+    args: &CrateVerify,
+    db: &ProofDB,
+    pinned: Option<&Pinned>,
+) -> Result<TrustSet> {
+    let params = args.wot.trust_params.clone().into();
+    let trust_set = match pinned {
+        // not the current Id, which can be another one on another machine
+        Some(pinned) => pinned
+            .inputs
+            .for_id
+            .as_ref()
+            .map(|for_id| db.calculate_trust_set(for_id, &params))
+            .unwrap_or_default(),
+        None => local.trust_set_for_id(args.wot.for_id.as_deref(), &params, db)?,
+    };
+    Ok(match &args.trust_snapshot {
+        Some(path) => {
+            let min_level = args.common.requirements.trust_level.trust_level.into();
+            let snapshot = crate::wot::read_trust_snapshot(path, local, &trust_set, min_level)?;
+            TrustSet::from_snapshot(&snapshot)
+        }
+        None => trust_set,
+    })
 }
 
 // Something in (presumably) in the C bindings we're using is unsound and will SIGSEGV
@@ -104,19 +213,27 @@ impl Drop for ScannerHandle {
 
 impl Scanner {
     pub fn new(root_crate: CrateSelector, args: &CrateVerify) -> Result<Scanner> {
-        let local = crev_lib::Local::auto_create_or_open()?;
-        let db = local.load_db()?;
-        let trust_set = local.trust_set_for_id(
-            args.wot.for_id.as_deref(),
-            &args.wot.trust_params.clone().into(),
-            &db,
-        )?;
-        let min_ignore_list = cargo_min_ignore_list();
-        let full_ignore_list = cargo_full_ignore_list(false);
+        let mut local = crev_lib::Local::auto_create_or_open()?;
+        local.set_cancellation_token(interrupt_token().clone());
+        let (db, pinned) = match &args.pin_inputs {
+            Some(path) => {
+                let (db, pinned) = load_db_pinned(&local, path, args.wot.for_id.as_deref())?;
+                (db, Some(pinned))
+            }
+            None => (local.load_db()?, None),
+        };
+        let trust_set = load_trust_set(&local, args, &db, pinned.as_ref())?;
+        let digest_policy = DigestIgnorePolicy::cargo_crate();
+        let full_ignore_list = cargo_full_ignore_list(
+            false,
+            &local.load_user_config()?,
+            &args.common.digest_ignore,
+        );
         let known_owners = read_known_owners_list().unwrap_or_else(|_| HashSet::new());
         let requirements =
             crev_lib::VerificationRequirements::from(args.common.requirements.clone());
         let repo = Repo::auto_open_cwd(args.common.cargo_opts.clone())?;
+        let policy = ReviewPolicy::for_project(args.policy.as_deref(), &repo.get_manifest_path()?)?;
 
         if root_crate.unrelated {
             // we would have to create a ephemeral workspace, etc.
@@ -128,7 +245,12 @@ impl Scanner {
 
         let (all_pkgs_set, _resolve) = repo.get_package_set()?;
 
-        let graph = repo.get_dependency_graph(roots.clone())?;
+        let graph = repo.get_dependency_graph(roots.clone(), args.deep)?;
+        let dependency_uses = if args.deep {
+            graph.dependency_uses(&roots)
+        } else {
+            HashMap::new()
+        };
 
         let all_pkgs_ids = graph.get_all_pkg_ids();
 
@@ -138,16 +260,27 @@ impl Scanner {
             .map(|pkg| (pkg.package_id(), CrateInfo::from_pkg(pkg)))
             .collect();
 
-        let all_crates_ids = crate_info_by_id.keys().copied().collect();
+        let baseline = args
+            .baseline
+            .as_deref()
+            .map(super::baseline::Baseline::read)
+            .transpose()?;
+
+        let all_crates_ids: Vec<_> = crate_info_by_id.keys().copied().collect();
+        let duplicate_versions = duplicate_versions(&all_crates_ids);
 
         let selected_crates_ids = crate_info_by_id
             .iter()
             .filter_map(|(id, _crate_info)| {
-                if !args.skip_indirect
-                    || roots_set.contains(id)
-                    || graph
-                        .get_reverse_dependencies_of(*id)
-                        .any(|r_dep| roots.contains(&r_dep))
+                let in_baseline = baseline
+                    .as_ref()
+                    .is_some_and(|baseline| baseline.contains(*id));
+                if !in_baseline
+                    && (!args.skip_indirect
+                        || roots_set.contains(id)
+                        || graph
+                            .get_reverse_dependencies_of(*id)
+                            .any(|r_dep| roots.contains(&r_dep)))
                 {
                     Some(id)
                 } else {
@@ -158,28 +291,121 @@ impl Scanner {
             .collect();
 
         let has_trusted_ids = trust_set.iter_trusted_ids().next().is_some();
+        let own_ids = local
+            .get_current_user_public_ids()?
+            .into_iter()
+            .map(|id| id.id)
+            .collect();
+        let popular_names = PopularNames::from_db(&db);
 
         Ok(Scanner {
             db: Arc::new(db),
             trust_set,
             has_trusted_ids,
-            min_ignore_list,
+            own_ids: Arc::new(own_ids),
+            digest_policy,
             full_ignore_list,
             local: Arc::new(local),
             known_owners,
             requirements,
+            dependency_uses: Arc::new(dependency_uses),
+            build_requirements: args.requirements_build_deps.unwrap_or_default(),
+            dev_requirements: args.requirements_dev_deps.unwrap_or_default(),
             recursive: args.recursive,
             crate_info_by_id,
             all_crates_ids,
+            duplicate_versions,
             selected_crates_ids,
             cargo_opts: args.common.cargo_opts.clone(),
+            crates_io_mirrors: repo.crates_io_mirrors().clone(),
+            pinned,
+            policy: Arc::new(policy),
             graph: Arc::new(graph),
             crate_details_by_id: Default::default(),
+            popular_names: Arc::new(popular_names),
+            hashed_crates: Default::default(),
         })
     }
 
+    /// `verify --fresh`: fetch updated proofs of trusted Ids while crates are downloaded and hashed
+    ///
+    /// Fetched proofs are imported as soon as each repo is fetched. The
+    /// trust set and the statuses of crates are computed once both are done.
+    pub fn fetch_fresh_proofs(mut self, args: &CrateVerify) -> Result<Self> {
+        let db = Arc::try_unwrap(std::mem::take(&mut self.db))
+            .map_err(|_| format_err!("Proofs are already in use by a scan"))?;
+        let db = RwLock::new(db);
+        let crates: Vec<_> = self.crate_info_by_id.values().cloned().collect();
+
+        std::thread::scope(|scope| {
+            let fetch = scope.spawn(|| {
+                self.local.fetch_trusted_into(
+                    args.wot.trust_params.clone().into(),
+                    args.wot.for_id.as_deref(),
+                    &db,
+                    &mut Warning::auto_log(),
+                )
+            });
+            let hashed = crates
+                .par_iter()
+                .try_for_each(|info| self.hash_crate(info, &db));
+            fetch.join().expect("proof fetching thread panicked")?;
+            hashed
+        })?;
+
+        let db = db.into_inner().expect("proof db lock poisoned");
+        self.trust_set = load_trust_set(&self.local, args, &db, self.pinned.as_ref())?;
+        self.has_trusted_ids = self.trust_set.iter_trusted_ids().next().is_some();
+        self.popular_names = Arc::new(PopularNames::from_db(&db));
+        self.db = Arc::new(db);
+        Ok(self)
+    }
+
+    /// Download the crate and compute its digests for `get_crate_details`
+    ///
+    /// `db` has the proofs fetched so far.
+    fn hash_crate(&self, info: &CrateInfo, db: &RwLock<ProofDB>) -> Result<()> {
+        info.download_if_needed(self.cargo_opts.clone())?;
+        let source_id = info.id.source_id();
+        let hashed = if source_id.is_git() {
+            HashedCrate {
+                digest: get_git_crate_digest(&info.root, self.digest_policy.ignore_list())?,
+                local_digest: None,
+            }
+        } else if source_id.is_registry() {
+            let cancel = self.local.cancellation_token();
+            let ignore_list = self.digest_policy.ignore_list();
+            let digest = get_dir_digest_cancellable(&info.root, ignore_list, cancel)?;
+            // proofs are only added, so a digest reviewed now stays the one to use
+            let reviewed = db
+                .read()
+                .expect("proof db lock poisoned")
+                .get_package_reviews_by_digest(&digest)
+                .next()
+                .is_some();
+            let local_digest = if reviewed || &self.full_ignore_list == ignore_list {
+                None
+            } else {
+                Some(get_dir_digest_cancellable(
+                    &info.root,
+                    &self.full_ignore_list,
+                    cancel,
+                )?)
+            };
+            HashedCrate {
+                digest,
+                local_digest,
+            }
+        } else {
+            return Ok(());
+        };
+        self.hashed_crates.lock().unwrap().insert(info.id, hashed);
+        Ok(())
+    }
+
     fn crates_io(&self) -> Result<crates_io::Client> {
-        crates_io::Client::new(&self.local)
+        let pin = self.pinned.as_ref().map(|pinned| pinned.crates_io.clone());
+        Ok(crates_io::Client::new(&self.local)?.pinned(pin))
     }
 
     pub fn selected_crate_count(&self) -> usize {
@@ -219,6 +445,17 @@ impl Scanner {
             };
         }
 
+        if required_details.downloads {
+            if let Ok(crates_io) = self.crates_io() {
+                crates_io.prefetch(
+                    self.all_crates_ids
+                        .iter()
+                        .filter(|id| id.source_id().is_crates_io())
+                        .map(|id| id.name().as_str()),
+                );
+            }
+        }
+
         let ready_tx_count = Arc::new(atomic::AtomicUsize::new(0));
         let threads: Vec<_> = (0..std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1))
             .map(|_| {
@@ -232,7 +469,9 @@ impl Scanner {
                     let canceled_flag = canceled_flag.clone();
                     move || {
                         pending_rx.into_iter().for_each(move |pkg_id: PackageId| {
-                            if canceled_flag.load(Ordering::SeqCst) {
+                            if canceled_flag.load(Ordering::SeqCst)
+                                || self_clone.local.cancellation_token().is_cancelled()
+                            {
                                 *pending_tx.lock().unwrap() = None;
                                 return;
                             }
@@ -260,9 +499,14 @@ impl Scanner {
                             let info = self_clone.crate_info_by_id[&pkg_id].clone();
 
                             debug!("Get details of {pkg_id}");
-                            let details = self_clone
-                                .get_crate_details(&info, required_details)
-                                .expect("Unable to scan crate");
+                            let details = match self_clone.get_crate_details(&info, required_details) {
+                                Ok(details) => details,
+                                Err(_) if self_clone.local.cancellation_token().is_cancelled() => {
+                                    *pending_tx.lock().unwrap() = None;
+                                    return;
+                                }
+                                Err(e) => panic!("Unable to scan crate: {e:?}"),
+                            };
                             {
                                 debug!("Insert details of {pkg_id}");
                                 let mut crate_details_by_id =
@@ -298,64 +542,127 @@ impl Scanner {
         }
     }
 
+    /// `requirements` of a crate, made stricter by those for every other way it's used
+    fn requirements_for_use(
+        &self,
+        requirements: crev_lib::VerificationRequirements,
+        dependency_use: DependencyUse,
+    ) -> crev_lib::VerificationRequirements {
+        let build = dependency_use
+            .build
+            .then(|| self.build_requirements.apply(&requirements));
+        let dev = dependency_use
+            .dev
+            .then(|| self.dev_requirements.apply(&requirements));
+        let runtime = dependency_use.runtime.then(|| requirements.clone());
+        [runtime, build, dev]
+            .into_iter()
+            .flatten()
+            .reduce(|a, b| a.stricter(&b))
+            .unwrap_or(requirements)
+    }
+
     fn get_crate_details(
         &mut self,
         info: &CrateInfo,
         required_details: RequiredDetails,
     ) -> Result<CrateDetails> {
         let pkg_name = info.id.name();
-        let proof_pkg_id = proof::PackageId {
-            source: SOURCE_CRATES_IO.into(),
-            name: pkg_name.to_string(),
-        };
-
         let pkg_version = info.id.version();
+        let (requirements, requirements_rule) =
+            self.policy.requirements_for(&pkg_name, &self.requirements);
+        let requirements_rule = requirements_rule.map(ToString::to_string);
+        let exemption = self
+            .policy
+            .exemption_for(&pkg_name)
+            .map(ToString::to_string);
+        let dependency_use = self
+            .dependency_uses
+            .get(&info.id)
+            .copied()
+            .unwrap_or_default();
+        let requirements = self.requirements_for_use(requirements, dependency_use);
+        let (waiver, expired_waiver) = match crate::waiver::find_waiver(
+            &self.db,
+            &self.trust_set,
+            &self.own_ids,
+            requirements.trust_level.into(),
+            &self.crates_io_mirrors.crev_pkg_id(&info.id),
+        ) {
+            Some(waiver) if waiver.is_expired(policy::today()) => {
+                (None, Some(crate::waiver::describe(waiver)))
+            }
+            waiver => (waiver.map(crate::waiver::describe), None),
+        };
         info.download_if_needed(self.cargo_opts.clone())?;
         let geiger_count = if required_details.geiger {
             get_geiger_count(&info.root).ok()
         } else {
             None
         };
-        let is_local_source_code = !info.id.source_id().is_registry();
-        let ignore_list = if is_local_source_code {
-            &self.min_ignore_list
-        } else {
-            &self.full_ignore_list
-        };
-        let digest = if !is_local_source_code {
-            Some(crev_lib::get_dir_digest(&info.root, ignore_list)?)
+        let is_git = info.id.source_id().is_git();
+        let is_local_source_code = !info.id.source_id().is_registry() && !is_git;
+        let hashed = self.hashed_crates.lock().unwrap().get(&info.id).cloned();
+        let digest = if let Some(hashed) = hashed {
+            Some(select_registry_crate_digest(
+                &self.db,
+                hashed.digest,
+                hashed.local_digest,
+            ))
+        } else if is_git {
+            Some(get_git_crate_digest(
+                &info.root,
+                self.digest_policy.ignore_list(),
+            )?)
+        } else if !is_local_source_code {
+            Some(get_registry_crate_digest(
+                &self.db,
+                &info.root,
+                &self.digest_policy,
+                &self.full_ignore_list,
+                self.local.cancellation_token(),
+            )?)
         } else {
             None
         };
-        let digest_mismatches = digest
+        let report = crev_lib::verify::verify_crate(
+            &crev_lib::verify::Dependency {
+                source: crate::crev_review_source(&info.id.source_id()),
+                name: pkg_name.to_string(),
+                version: pkg_version.clone(),
+                root: info.root.clone(),
+                is_local_source_code,
+            },
+            digest.clone(),
+            &self.digest_policy,
+            &self.trust_set,
+            &requirements,
+            &self.db,
+        );
+        let policy_failures = digest
             .as_ref()
-            .map(|digest| get_crate_digest_mismatches(&self.db, &pkg_name, pkg_version, digest))
+            .map(|digest| self.policy.check(&pkg_name, pkg_version, digest, &self.db))
             .unwrap_or_default();
-        let verification_result = if let Some(digest) = digest.as_ref() {
-            crev_lib::verify_package_digest(digest, &self.trust_set, &self.requirements, &self.db)
+        // reviews by anyone else are not enough then
+        let verification_result = if policy_failures.is_empty() || !report.is_verified() {
+            report.status
         } else {
-            VerificationStatus::Local
+            VerificationStatus::Insufficient
         };
         let verified = verification_result.is_verified();
 
         let pkg_name = info.id.name().to_string();
 
-        let version_reviews: Vec<_> = self
-            .db
-            .get_package_reviews_for_package(
-                SOURCE_CRATES_IO,
-                Some(&pkg_name),
-                Some(info.id.version()),
-            )
-            .collect();
-
-        let version_reviews_count = version_reviews.len();
-        let total_reviews_count =
-            self.db
-                .get_package_review_count(SOURCE_CRATES_IO, Some(&pkg_name), None);
         let version_review_count = CountWithTotal {
-            count: version_reviews_count as u64,
-            total: total_reviews_count as u64,
+            count: report.version_reviews as u64,
+            total: report.total_reviews as u64,
+        };
+        let similar_to = if report.total_reviews == 0 && !is_local_source_code {
+            self.popular_names
+                .similar_to(&pkg_name)
+                .map(ToString::to_string)
+        } else {
+            None
         };
 
         let crates_io = self.crates_io()?;
@@ -383,25 +690,9 @@ impl Scanner {
             }
         });
 
-        let issues_from_trusted = self.db.get_open_issues_for_version(
-            SOURCE_CRATES_IO,
-            &pkg_name,
-            pkg_version,
-            &self.trust_set,
-            self.requirements.trust_level.into(),
-        );
-
-        let issues_from_all = self.db.get_open_issues_for_version(
-            SOURCE_CRATES_IO,
-            &pkg_name,
-            pkg_version,
-            &self.trust_set,
-            crev_data::Level::None.into(),
-        );
-
         let issues = CountWithTotal {
-            count: issues_from_trusted.len() as u64,
-            total: issues_from_all.len() as u64,
+            count: report.open_issues.len() as u64,
+            total: report.all_open_issues as u64,
         };
 
         let loc = if required_details.loc {
@@ -410,19 +701,6 @@ impl Scanner {
             None
         };
 
-        let latest_trusted_version = crev_lib::find_latest_trusted_version(
-            &self.trust_set,
-            SOURCE_CRATES_IO,
-            &pkg_name,
-            &self.requirements,
-            &self.db,
-        );
-
-        let is_unmaintained = self
-            .db
-            .get_pkg_flags(&proof_pkg_id)
-            .any(|(id, flags)| self.trust_set.is_trusted(id) && flags.unmaintained);
-
         let owner_set = OwnerSetSet::new(info.id, owner_list.into_iter().flatten());
 
         let accumulative_own = AccumulativeCrateDetails {
@@ -433,7 +711,8 @@ impl Scanner {
             loc: loc.map(|l| l as u64),
             verified,
             has_custom_build: info.has_custom_build,
-            is_unmaintained,
+            is_proc_macro: info.is_proc_macro,
+            is_unmaintained: report.is_unmaintained,
             owner_set,
             is_local_source_code,
         };
@@ -455,19 +734,27 @@ impl Scanner {
 
         Ok(CrateDetails {
             digest,
-            trusted_reviewers: version_reviews
-                .into_iter()
-                .map(|pkg_review| pkg_review.from().clone())
-                .filter(|id| {
-                    self.trust_set.get_effective_trust_level(&id.id)
-                        >= self.requirements.trust_level.into()
-                })
-                .collect(),
-            latest_trusted_version,
+            dependency_use,
+            trusted_reviewers: report.trusted_reviewers,
+            latest_trusted_version: report.latest_trusted_version,
             version_reviews: version_review_count,
             downloads,
             known_owners,
-            digest_mismatches,
+            digest_mismatches: report.digest_mismatches,
+            verified_subtrees: report
+                .subtrees
+                .iter()
+                .filter(|subtree| subtree.status.is_verified())
+                .map(|subtree| subtree.path.clone())
+                .collect(),
+            policy_failures,
+            requirements_rule,
+            exemption,
+            waiver,
+            expired_waiver,
+            similar_to,
+            open_issues: report.open_issues,
+            negative_reviews: report.negative_reviews,
             leftpad_idx: downloads
                 .and_then(|d| d.recent.checked_div(accumulative_own.loc.unwrap_or(0)))
                 .unwrap_or(0),
@@ -481,12 +768,12 @@ impl Scanner {
             dependencies: self
                 .graph
                 .get_dependencies_of(info.id)
-                .map(|c| crate::cargo_pkg_id_to_crev_pkg_id(&c))
+                .map(|c| self.crates_io_mirrors.crev_pkg_id(&c))
                 .collect(),
             rev_dependencies: self
                 .graph
                 .get_reverse_dependencies_of(info.id)
-                .map(|c| crate::cargo_pkg_id_to_crev_pkg_id(&c))
+                .map(|c| self.crates_io_mirrors.crev_pkg_id(&c))
                 .collect(),
         })
     }
@@ -0,0 +1,169 @@
+//! `crate diff --json`: what changed between two versions of a crate
+//!
+//! Instead of the diff itself, a summary of the changed files, for review
+//! automation to decide what to look at first.
+use crate::prelude::*;
+use crev_lib::util::is_ignored_path;
+use serde::Serialize;
+use similar::{ChangeTag, TextDiff};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FileChange {
+    Added,
+    Removed,
+    Modified,
+}
+
+/// A file that differs between the versions
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct FileDiff {
+    /// Relative to the crate root, with `/` separators
+    pub path: String,
+    pub change: FileChange,
+    /// Blake2b256 of the content, in base64
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub src_digest: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dst_digest: Option<String>,
+    /// `None` for files that aren't text
+    pub lines_added: Option<usize>,
+    pub lines_removed: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct DiffSummary {
+    pub name: String,
+    pub src_version: Version,
+    pub dst_version: Version,
+    /// Sorted by path; files that didn't change are left out
+    pub files: Vec<FileDiff>,
+}
+
+/// Files under `root`, by their relative paths
+fn read_files(
+    root: &Path,
+    ignore_list: &fnv::FnvHashSet<PathBuf>,
+) -> Result<BTreeMap<PathBuf, Vec<u8>>> {
+    let mut files = BTreeMap::new();
+    for entry in walkdir::WalkDir::new(root) {
+        let entry = entry?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let rel_path = entry.path().strip_prefix(root)?;
+        if is_ignored_path(rel_path, ignore_list) {
+            continue;
+        }
+        files.insert(rel_path.to_owned(), std::fs::read(entry.path())?);
+    }
+    Ok(files)
+}
+
+fn digest(content: &[u8]) -> String {
+    crev_common::base64_encode(&crev_common::blake2b256sum(content))
+}
+
+/// Lines added and removed, `None` unless both are text
+fn line_counts(src: &[u8], dst: &[u8]) -> (Option<usize>, Option<usize>) {
+    let (Ok(src), Ok(dst)) = (std::str::from_utf8(src), std::str::from_utf8(dst)) else {
+        return (None, None);
+    };
+    let (mut added, mut removed) = (0, 0);
+    for change in TextDiff::from_lines(src, dst).iter_all_changes() {
+        match change.tag() {
+            ChangeTag::Insert => added += 1,
+            ChangeTag::Delete => removed += 1,
+            ChangeTag::Equal => {}
+        }
+    }
+    (Some(added), Some(removed))
+}
+
+/// Files that differ between `src_root` and `dst_root`
+pub fn diff_dirs(
+    src_root: &Path,
+    dst_root: &Path,
+    ignore_list: &fnv::FnvHashSet<PathBuf>,
+) -> Result<Vec<FileDiff>> {
+    let src_files = read_files(src_root, ignore_list)?;
+    let mut dst_files = read_files(dst_root, ignore_list)?;
+
+    let mut diffs = vec![];
+    for (path, src) in src_files {
+        let dst = dst_files.remove(&path);
+        if dst.as_ref() == Some(&src) {
+            continue;
+        }
+        let (lines_added, lines_removed) = line_counts(&src, dst.as_deref().unwrap_or_default());
+        diffs.push(FileDiff {
+            path: path.to_string_lossy().replace('\\', "/"),
+            change: if dst.is_some() {
+                FileChange::Modified
+            } else {
+                FileChange::Removed
+            },
+            src_digest: Some(digest(&src)),
+            dst_digest: dst.as_deref().map(digest),
+            lines_added,
+            lines_removed,
+        });
+    }
+    for (path, dst) in dst_files {
+        let (lines_added, lines_removed) = line_counts(&[], &dst);
+        diffs.push(FileDiff {
+            path: path.to_string_lossy().replace('\\', "/"),
+            change: FileChange::Added,
+            src_digest: None,
+            dst_digest: Some(digest(&dst)),
+            lines_added,
+            lines_removed,
+        });
+    }
+    diffs.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(diffs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarizes_changed_files() -> Result<()> {
+        let src = tempfile::tempdir()?;
+        let dst = tempfile::tempdir()?;
+        for dir in [src.path(), dst.path()] {
+            std::fs::create_dir(dir.join("src"))?;
+            std::fs::write(dir.join("Cargo.toml"), "[package]\n")?;
+            std::fs::write(dir.join(".cargo-ok"), dir.to_string_lossy().as_bytes())?;
+        }
+        std::fs::write(src.path().join("src/lib.rs"), "a\nb\nc\n")?;
+        std::fs::write(dst.path().join("src/lib.rs"), "a\nB\nc\nd\n")?;
+        std::fs::write(src.path().join("removed.rs"), "x\n")?;
+        std::fs::write(dst.path().join("data.bin"), [0xff, 0xfe])?;
+
+        let ignore_list = crate::shared::cargo_min_ignore_list();
+        let diffs = diff_dirs(src.path(), dst.path(), &ignore_list)?;
+        let summary: Vec<_> = diffs
+            .iter()
+            .map(|d| (d.path.as_str(), d.change, d.lines_added, d.lines_removed))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("data.bin", FileChange::Added, None, None),
+                ("removed.rs", FileChange::Removed, Some(0), Some(1)),
+                ("src/lib.rs", FileChange::Modified, Some(2), Some(1)),
+            ]
+        );
+        assert!(diffs[0].src_digest.is_none());
+        assert_ne!(diffs[2].src_digest, diffs[2].dst_digest);
+        Ok(())
+    }
+}
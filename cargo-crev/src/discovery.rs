@@ -0,0 +1,144 @@
+//! Official crev Ids of organizations, published on their domains
+//!
+//! `https://<domain>/.well-known/crev.json` lists the Ids with their proof repos:
+//!
+//! ```json
+//! {"ids": [{"id": "<Id>", "url": "https://github.com/example/crev-proofs"}]}
+//! ```
+//!
+//! A listed Id is only used if its proof repo confirms it (the Id signed
+//! that URL itself), so neither the domain nor the repo alone can make
+//! `trust https://<domain>` trust some other Id.
+use crate::prelude::*;
+use crev_data::Id;
+use crev_wot::ProofDB;
+use serde::Deserialize;
+use std::time::Duration;
+
+pub const WELL_KNOWN_PATH: &str = "/.well-known/crev.json";
+
+/// Bigger responses are not a list of Ids
+const MAX_RESPONSE_SIZE: usize = 1 << 20;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WellKnownId {
+    pub id: String,
+    /// Proof repo of the Id
+    pub url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct WellKnownFile {
+    ids: Vec<WellKnownId>,
+}
+
+/// The domain of `https://example.com`, which has no path unlike proof repo URLs
+#[must_use]
+pub fn domain_of_url(url: &str) -> Option<&str> {
+    let rest = url.strip_prefix("https://")?;
+    let domain = rest.strip_suffix('/').unwrap_or(rest);
+    (!domain.is_empty() && !domain.contains('/')).then_some(domain)
+}
+
+pub fn parse_well_known(json: &[u8]) -> Result<Vec<WellKnownId>> {
+    Ok(serde_json::from_slice::<WellKnownFile>(json)?.ids)
+}
+
+/// Body of a successful HTTPS GET, without following redirects
+fn http_get(url: &str) -> Result<Vec<u8>> {
+    let mut easy = curl::easy::Easy::new();
+    easy.url(url)?;
+    easy.timeout(Duration::from_secs(30))?;
+    easy.useragent(concat!("cargo-crev/", env!("CARGO_PKG_VERSION")))?;
+    let mut body = Vec::new();
+    {
+        let mut transfer = easy.transfer();
+        transfer.write_function(|data| {
+            if body.len() + data.len() > MAX_RESPONSE_SIZE {
+                // makes curl abort the transfer
+                return Ok(0);
+            }
+            body.extend_from_slice(data);
+            Ok(data.len())
+        })?;
+        transfer.perform()?;
+    }
+    let code = easy.response_code()?;
+    if code != 200 {
+        bail!("{url} returned HTTP status {code}");
+    }
+    Ok(body)
+}
+
+/// Ids published by `domain`, confirmed by their proof repos, which are fetched into `db`
+pub fn discover_ids(local: &crev_lib::Local, db: &mut ProofDB, domain: &str) -> Result<Vec<Id>> {
+    let url = format!("https://{domain}{WELL_KNOWN_PATH}");
+    let listed = parse_well_known(&http_get(&url)?)
+        .map_err(|e| format_err!("Can't read the Ids listed at {url}: {e}"))?;
+
+    let mut ids = vec![];
+    for entry in listed {
+        let id = match Id::crevid_from_str(&entry.id) {
+            Ok(id) => id,
+            Err(e) => {
+                eprintln!(
+                    "warning: {domain} lists '{}', which is not a valid crev Id: {e}",
+                    entry.id
+                );
+                continue;
+            }
+        };
+        if let Err(e) = local.fetch_url_into(&entry.url, db) {
+            eprintln!(
+                "warning: Can't fetch {} listed by {domain} for {id}: {e}",
+                entry.url
+            );
+            continue;
+        }
+        match db.lookup_url(&id).from_self() {
+            Some(url) if url.url == entry.url => {
+                eprintln!(
+                    "{domain} lists {id}, confirmed by its proof repo {}",
+                    entry.url
+                );
+                ids.push(id);
+            }
+            _ => eprintln!(
+                "warning: {domain} lists {id} with proof repo {}, but that repo doesn't confirm it",
+                entry.url
+            ),
+        }
+    }
+    if ids.is_empty() {
+        bail!("None of the Ids listed at {url} could be confirmed");
+    }
+    Ok(ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn domains_are_told_apart_from_proof_repos() {
+        assert_eq!(domain_of_url("https://example.com"), Some("example.com"));
+        assert_eq!(domain_of_url("https://example.com/"), Some("example.com"));
+        assert_eq!(
+            domain_of_url("https://github.com/example/crev-proofs"),
+            None
+        );
+        assert_eq!(domain_of_url("http://example.com"), None);
+        assert_eq!(domain_of_url("https://"), None);
+    }
+
+    #[test]
+    fn well_known_file_lists_ids() -> Result<()> {
+        let ids = parse_well_known(
+            br#"{"ids": [{"id": "FYlr8YoYGVvDwHQxqEIs89reKKDy-oWisoO0qXXEfHE", "url": "https://github.com/dpc/crev-proofs"}]}"#,
+        )?;
+        assert_eq!(ids.len(), 1);
+        assert_eq!(ids[0].url, "https://github.com/dpc/crev-proofs");
+        assert!(parse_well_known(b"{}").is_err());
+        Ok(())
+    }
+}
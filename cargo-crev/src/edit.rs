@@ -1,6 +1,9 @@
 use anyhow::{bail, Result};
 use crev_common::{run_with_shell_cmd, CancelledError};
-use crev_data::{proof, proof::content::ContentExt};
+use crev_data::{
+    proof,
+    proof::{content::ContentExt, DraftFormat},
+};
 use crev_lib::{local::Local, util::get_documentation_for};
 use std::{
     env, ffi,
@@ -26,9 +29,11 @@ fn get_editor_to_use() -> Result<ffi::OsString> {
 }
 
 /// Returns the edited string, and bool indicating if the file was ever written to/ (saved).
-fn edit_text_iteractively_raw(text: &str) -> Result<(String, bool)> {
+///
+/// `extension` of the temporary file is for syntax highlighting in the editor.
+fn edit_text_iteractively_raw(text: &str, extension: &str) -> Result<(String, bool)> {
     let dir = tempfile::tempdir()?;
-    let file_path = dir.path().join("crev.review.yaml");
+    let file_path = dir.path().join(format!("crev.review.{extension}"));
     std::fs::write(&file_path, text)?;
 
     let starting_ts = std::fs::metadata(&file_path)?
@@ -48,12 +53,12 @@ fn edit_text_iteractively_raw(text: &str) -> Result<(String, bool)> {
 }
 
 pub fn edit_text_iteractively(text: &str) -> Result<String> {
-    Ok(edit_text_iteractively_raw(text)?.0)
+    Ok(edit_text_iteractively_raw(text, "yaml")?.0)
 }
 
-pub fn edit_text_iteractively_until_written_to(text: &str) -> Result<String> {
+pub fn edit_text_iteractively_until_written_to(text: &str, extension: &str) -> Result<String> {
     loop {
-        let (text, modified) = edit_text_iteractively_raw(text)?;
+        let (text, modified) = edit_text_iteractively_raw(text, extension)?;
         if !modified {
             eprintln!(
                 "File not written to. Make sure to save it at least once to confirm the data."
@@ -89,12 +94,33 @@ pub fn edit_file(path: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Show `text` in `$PAGER` (`less` by default)
+pub fn view_text_in_pager(text: &str) -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let file_path = dir.path().join("crev.txt");
+    std::fs::write(&file_path, text)?;
+
+    let pager = env::var_os("PAGER").unwrap_or_else(|| "less".into());
+    let status = run_with_shell_cmd(&pager, Some(&file_path))?;
+    if !status.success() {
+        bail!(
+            "Can't launch pager {}: {}",
+            pager.to_str().unwrap_or("?"),
+            status
+        );
+    }
+    Ok(())
+}
+
 pub fn edit_proof_content_iteractively<C: proof::ContentWithDraft>(
     content: &C,
     previous_date: Option<&proof::Date>,
     base_version: Option<&crev_data::Version>,
     extra_leading_comment: Option<&str>,
     extra_follow_content_fn: impl FnOnce(&mut String) -> Result<()>,
+    lint_fn: impl Fn(&C) -> Vec<String>,
+    required_fn: impl Fn(&C) -> std::result::Result<(), crev_lib::Error>,
+    format: DraftFormat,
 ) -> Result<C> {
     let mut text = String::new();
     if let Some(date) = previous_date {
@@ -104,24 +130,33 @@ pub fn edit_proof_content_iteractively<C: proof::ContentWithDraft>(
             date.to_rfc3339()
         )?;
     }
-    let draft = content.to_draft();
+    let draft = content.to_draft_in(format)?;
 
     writeln!(&mut text, "# {}", draft.title())?;
     if let Some(extra_comment) = extra_leading_comment {
-        writeln!(&mut text, "# {extra_comment}")?;
+        for line in extra_comment.lines() {
+            writeln!(&mut text, "# {line}")?;
+        }
     }
     if let Some(base_version) = base_version {
         writeln!(&mut text, "# Diff base version: {base_version}")?;
     }
     text.write_str(draft.body())?;
-    (extra_follow_content_fn)(&mut text)?;
+    // suggestions are commented out YAML snippets
+    if format == DraftFormat::Yaml {
+        (extra_follow_content_fn)(&mut text)?;
+    }
     text.write_str("\n\n")?;
     for line in get_documentation_for(content).lines() {
         writeln!(&mut text, "# {line}")?;
     }
+    let extension = match format {
+        DraftFormat::Yaml => "yaml",
+        DraftFormat::Toml => "toml",
+    };
     loop {
-        text = edit_text_iteractively_until_written_to(&text)?;
-        match content.apply_draft(&text) {
+        text = edit_text_iteractively_until_written_to(&text, extension)?;
+        match content.apply_draft_in(&text, format) {
             Err(e) => {
                 eprintln!("There was an error parsing content: {e}");
                 crev_common::try_again_or_cancel()?;
@@ -130,7 +165,11 @@ pub fn edit_proof_content_iteractively<C: proof::ContentWithDraft>(
                 if let Err(e) = content.ensure_serializes_to_valid_proof() {
                     eprintln!("There was an error validating serialized proof: {e}");
                     crev_common::try_again_or_cancel()?;
-                } else {
+                } else if let Err(e) = required_fn(&content) {
+                    // unlike lint warnings, can't be ignored
+                    eprintln!("{e}");
+                    crev_common::try_again_or_cancel()?;
+                } else if confirm_lint_warnings(&lint_fn(&content), "Save anyway? (y/N/q) ")? {
                     return Ok(content);
                 }
             }
@@ -138,6 +177,29 @@ pub fn edit_proof_content_iteractively<C: proof::ContentWithDraft>(
     }
 }
 
+/// Show lint warnings, and ask if they should be fixed first
+///
+/// Returns `true` if the content should be used as it is.
+pub fn confirm_lint_warnings(warnings: &[String], prompt: &str) -> Result<bool> {
+    if warnings.is_empty() {
+        return Ok(true);
+    }
+    for warning in warnings {
+        eprintln!("Warning: {warning}");
+    }
+    let reply = rprompt::prompt_reply_from_bufread(
+        &mut std::io::stdin().lock(),
+        &mut std::io::stderr(),
+        prompt,
+    )?;
+
+    match reply.as_str() {
+        "y" | "Y" => Ok(true),
+        "q" | "Q" => Err(CancelledError::ByUser.into()),
+        _ => Ok(false),
+    }
+}
+
 /// interactively edit currnent user's yaml config file
 pub fn edit_user_config(local: &Local) -> Result<()> {
     let config = local.load_user_config()?;
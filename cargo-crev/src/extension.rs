@@ -0,0 +1,169 @@
+//! Extension subcommands: `cargo crev x-<name> [args]`
+//!
+//! Like `cargo <name>` runs `cargo-<name>`, `cargo crev x-<name>` runs the
+//! `cargo-crev-x-<name>` executable found in `PATH`, so tools built on crev
+//! can be published as separate crates.
+//!
+//! Such a crate can implement [`Extension`] and call [`run_main`] from the
+//! `main` of its `cargo-crev-x-<name>` binary.
+use anyhow::{bail, Context, Result};
+use std::{
+    collections::BTreeMap,
+    ffi::{OsStr, OsString},
+    path::PathBuf,
+};
+
+/// Subcommands starting with it are extensions
+pub const PREFIX: &str = "x-";
+
+/// Executables named `cargo-crev-x-<name>` provide the `x-<name>` subcommand
+const EXECUTABLE_PREFIX: &str = "cargo-crev-";
+
+/// A subcommand that isn't part of the core command line
+pub trait Extension: Send + Sync {
+    /// Subcommand name, starting with [`PREFIX`]
+    fn name(&self) -> &str;
+
+    /// One line description for listings
+    fn about(&self) -> String;
+
+    /// Run with the arguments following the subcommand name, returning the exit code
+    fn run(&self, args: &[OsString]) -> Result<i32>;
+}
+
+/// `main` of a `cargo-crev-x-<name>` executable implementing `extension`
+///
+/// Errors are printed to stderr, and exit with code 2.
+pub fn run_main(extension: &dyn Extension) -> ! {
+    // the executable path, and the subcommand name passed by cargo-crev
+    let args: Vec<_> = std::env::args_os().skip(2).collect();
+    match extension.run(&args) {
+        Ok(code) => std::process::exit(code),
+        Err(e) => {
+            eprintln!("{e:?}");
+            std::process::exit(2)
+        }
+    }
+}
+
+/// A `cargo-crev-x-<name>` executable
+///
+/// It gets the subcommand name as its first argument (like cargo does for
+/// its subcommands), and the path of cargo-crev in `CARGO_CREV`.
+struct Executable {
+    name: String,
+    path: PathBuf,
+}
+
+impl Extension for Executable {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn about(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn run(&self, args: &[OsString]) -> Result<i32> {
+        let status = std::process::Command::new(&self.path)
+            .arg(&self.name)
+            .args(args)
+            .env("CARGO_CREV", std::env::current_exe()?)
+            .status()
+            .with_context(|| format!("Can't run {}", self.path.display()))?;
+        match status.code() {
+            Some(code) => Ok(code),
+            None => bail!("{} was terminated by a signal", self.path.display()),
+        }
+    }
+}
+
+/// Extensions available to this process
+pub struct Registry {
+    extensions: BTreeMap<String, Box<dyn Extension>>,
+}
+
+impl Registry {
+    /// Executables in `PATH`
+    pub fn discover() -> Self {
+        let mut registry = Self {
+            extensions: BTreeMap::new(),
+        };
+        for executable in find_executables() {
+            registry.register(Box::new(executable));
+        }
+        registry
+    }
+
+    /// Add an extension, replacing any with the same name
+    pub fn register(&mut self, extension: Box<dyn Extension>) {
+        debug_assert!(extension.name().starts_with(PREFIX));
+        self.extensions
+            .insert(extension.name().to_owned(), extension);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn Extension> {
+        self.extensions.get(name).map(|e| &**e)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &dyn Extension> {
+        self.extensions.values().map(|e| &**e)
+    }
+
+    /// Run the extension `name`, with `args` following it on the command line
+    pub fn run(&self, name: &str, args: &[OsString]) -> Result<i32> {
+        let Some(extension) = self.get(name) else {
+            let available: Vec<_> = self.iter().map(|e| e.name()).collect();
+            if available.is_empty() {
+                bail!(
+                    "No extension `{}`: no `{}{}` executable in PATH",
+                    name,
+                    EXECUTABLE_PREFIX,
+                    name
+                );
+            }
+            bail!(
+                "No extension `{}`; available: {}",
+                name,
+                available.join(", ")
+            );
+        };
+        extension.run(args)
+    }
+}
+
+/// `cargo-crev-x-*` executables in `PATH`, the first one of each name
+fn find_executables() -> Vec<Executable> {
+    let Some(path) = std::env::var_os("PATH") else {
+        return vec![];
+    };
+    let mut found = BTreeMap::new();
+    for dir in std::env::split_paths(&path) {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            let path = entry.path();
+            let Some(name) = extension_name(&path) else {
+                continue;
+            };
+            if path.is_file() && !found.contains_key(&name) {
+                found.insert(name.clone(), Executable { name, path });
+            }
+        }
+    }
+    found.into_values().collect()
+}
+
+/// `x-<name>` for a `cargo-crev-x-<name>` executable
+fn extension_name(path: &std::path::Path) -> Option<String> {
+    let file_name = if cfg!(windows) {
+        path.file_stem()
+    } else {
+        path.file_name()
+    };
+    let name = file_name
+        .and_then(OsStr::to_str)?
+        .strip_prefix(EXECUTABLE_PREFIX)?;
+    (name.len() > PREFIX.len() && name.starts_with(PREFIX)).then(|| name.to_owned())
+}
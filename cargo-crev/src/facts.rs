@@ -0,0 +1,302 @@
+//! `crate facts`: all the supply chain signals about one crate version in one place
+//!
+//! Nothing is computed here; this just collects what the other commands
+//! (`verify`, `crate info`, `repo query advisory`, ...) already know.
+use crate::{
+    crates_io,
+    opts::{CrateSelector, CrateVerifyCommon, WotOpts},
+    prelude::*,
+    shared::{cargo_full_ignore_list, get_geiger_count, read_known_owners_list, JsonError},
+    term::Term,
+    Repo,
+};
+use crev_data::{proof, Level, SOURCE_CRATES_IO};
+use crev_lib::{verify::get_registry_crate_digest, DigestIgnorePolicy, VerificationStatus};
+use serde::Serialize;
+use std::collections::HashSet;
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct AdvisoryFact {
+    pub ids: Vec<String>,
+    pub severity: Level,
+    pub reported_by: crev_data::Id,
+    pub comment: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct CrateFacts {
+    pub package: proof::PackageVersionId,
+    pub digest: String,
+    /// `pass`, `none`, `warn` (see `crev_lib::VerificationStatus`)
+    pub verification: String,
+    pub trusted_reviews: usize,
+    pub all_reviews: usize,
+    pub latest_trusted_version: Option<Version>,
+    pub advisories: Vec<AdvisoryFact>,
+    pub open_issues: usize,
+    pub unmaintained: bool,
+    pub alternatives: HashSet<proof::PackageId>,
+    pub unsafe_count: Option<u64>,
+    pub has_build_script: bool,
+    /// `None` if crates.io couldn't be reached
+    pub owners: Option<Vec<String>>,
+    /// Owners not on your list of known owners
+    pub unknown_owners: Option<Vec<String>>,
+    /// RFC 3339
+    pub published: Option<String>,
+    pub days_since_published: Option<i64>,
+}
+
+pub fn get_crate_facts(
+    crate_: CrateSelector,
+    common_opts: CrateVerifyCommon,
+    wot_opts: WotOpts,
+) -> Result<CrateFacts> {
+    crate_.ensure_name_given()?;
+
+    let local = crev_lib::Local::auto_create_or_open()?;
+    let db = local.load_db()?;
+    let trust_set = local.trust_set_for_id(
+        wot_opts.for_id.as_deref(),
+        &wot_opts.trust_params.into(),
+        &db,
+    )?;
+    let requirements = crev_lib::VerificationRequirements::from(common_opts.requirements);
+
+    let repo = Repo::auto_open_cwd(common_opts.cargo_opts)?;
+    let pkg_id = repo.find_pkgid_by_crate_selector(&crate_)?;
+    let package = repo.get_crate(&pkg_id)?;
+    let crev_pkg_id = repo.crates_io_mirrors().crev_pkg_id(&pkg_id);
+    let name = &crev_pkg_id.id.name;
+    let version = &crev_pkg_id.version;
+
+    let ignore_list = cargo_full_ignore_list(
+        false,
+        &local.load_user_config()?,
+        &common_opts.digest_ignore,
+    );
+    let digest = get_registry_crate_digest(
+        &db,
+        package.root(),
+        &DigestIgnorePolicy::cargo_crate(),
+        &ignore_list,
+        local.cancellation_token(),
+    )?;
+    let verification = crev_lib::verify_package_digest(&digest, &trust_set, &requirements, &db);
+
+    let reviews: Vec<_> = db
+        .get_pkg_reviews_for_version(SOURCE_CRATES_IO, name, version)
+        .collect();
+    let trusted_reviews = reviews
+        .iter()
+        .filter(|review| trust_set.is_trusted(&review.common.from.id))
+        .count();
+
+    let advisories = db
+        .get_advisories_for_version(SOURCE_CRATES_IO, name, version)
+        .filter(|review| trust_set.is_trusted(&review.common.from.id))
+        .flat_map(|review| {
+            review
+                .advisories
+                .iter()
+                .filter(|advisory| {
+                    advisory.is_for_version_when_reported_in_version(
+                        version,
+                        &review.package.id.version,
+                    )
+                })
+                .map(move |advisory| AdvisoryFact {
+                    ids: advisory.ids.clone(),
+                    severity: advisory.severity,
+                    reported_by: review.common.from.id.clone(),
+                    comment: advisory.comment.clone(),
+                })
+        })
+        .collect();
+
+    let open_issues = db
+        .get_open_issues_for_version(
+            SOURCE_CRATES_IO,
+            name,
+            version,
+            &trust_set,
+            requirements.trust_level.into(),
+        )
+        .len();
+
+    let unmaintained = db
+        .get_pkg_flags(&crev_pkg_id.id)
+        .any(|(id, flags)| trust_set.is_trusted(id) && flags.unmaintained);
+
+    let alternatives = db
+        .get_pkg_alternatives(&crev_pkg_id.id)
+        .into_iter()
+        .filter(|(author, _)| trust_set.is_trusted(author))
+        .map(|(_, id)| id)
+        .collect();
+
+    let crates_io = crates_io::Client::new(&local)?;
+    let owners = crates_io.get_owners(name).ok();
+    let unknown_owners = owners.as_ref().map(|owners| {
+        let known_owners = read_known_owners_list().unwrap_or_default();
+        owners
+            .iter()
+            .filter(|owner| !known_owners.contains(*owner))
+            .cloned()
+            .collect()
+    });
+    let published = crates_io
+        .get_version_published(name, version)
+        .ok()
+        .flatten();
+
+    Ok(CrateFacts {
+        package: crev_pkg_id.clone(),
+        digest: digest.to_string(),
+        verification: match verification {
+            VerificationStatus::Local => "local".into(),
+            status => status.to_string(),
+        },
+        trusted_reviews,
+        all_reviews: reviews.len(),
+        latest_trusted_version: crev_lib::find_latest_trusted_version(
+            &trust_set,
+            SOURCE_CRATES_IO,
+            name,
+            &requirements,
+            &db,
+        ),
+        advisories,
+        open_issues,
+        unmaintained,
+        alternatives,
+        unsafe_count: get_geiger_count(package.root()).ok(),
+        has_build_script: package.has_custom_build(),
+        owners,
+        unknown_owners,
+        published: published.map(|date| date.to_rfc3339()),
+        days_since_published: published.map(|date| (chrono::Utc::now() - date).num_days()),
+    })
+}
+
+pub fn print_crate_facts(
+    crate_: CrateSelector,
+    common_opts: CrateVerifyCommon,
+    wot_opts: WotOpts,
+    json: bool,
+) -> Result<()> {
+    let facts = match get_crate_facts(crate_, common_opts, wot_opts) {
+        Ok(facts) => facts,
+        Err(e) => {
+            if json {
+                JsonError::print(&e)?;
+            }
+            return Err(e);
+        }
+    };
+    if json {
+        println!("{}", serde_json::to_string_pretty(&facts)?);
+        return Ok(());
+    }
+
+    let mut term = Term::new();
+    let warn = Some(::term::color::YELLOW);
+    let bad = Some(::term::color::RED);
+    let flag = |cond: bool, color| if cond { color } else { None };
+    let unknown = "?".to_string();
+
+    term.print(
+        format_args!("{} {}\n", facts.package.id.name, facts.package.version),
+        None,
+    )?;
+    term.print(format_args!("{:<16} {}\n", "digest:", facts.digest), None)?;
+    term.print(
+        format_args!("{:<16} {}\n", "verification:", facts.verification),
+        flag(facts.verification == "none", warn).or(flag(facts.verification == "warn", bad)),
+    )?;
+    term.print(
+        format_args!(
+            "{:<16} {} trusted, {} total\n",
+            "reviews:", facts.trusted_reviews, facts.all_reviews
+        ),
+        None,
+    )?;
+    if let Some(version) = &facts.latest_trusted_version {
+        term.print(
+            format_args!("{:<16} {}\n", "latest trusted:", version),
+            None,
+        )?;
+    }
+    term.print(
+        format_args!("{:<16} {}\n", "advisories:", facts.advisories.len()),
+        flag(!facts.advisories.is_empty(), bad),
+    )?;
+    for advisory in &facts.advisories {
+        term.print(
+            format_args!(
+                "  {} ({}) reported by {}\n",
+                advisory.ids.join(", "),
+                advisory.severity,
+                advisory.reported_by
+            ),
+            bad,
+        )?;
+    }
+    term.print(
+        format_args!("{:<16} {}\n", "open issues:", facts.open_issues),
+        flag(facts.open_issues > 0, warn),
+    )?;
+    term.print(
+        format_args!("{:<16} {}\n", "unmaintained:", facts.unmaintained),
+        flag(facts.unmaintained, warn),
+    )?;
+    if !facts.alternatives.is_empty() {
+        let alternatives: Vec<_> = facts.alternatives.iter().map(|a| a.name.as_str()).collect();
+        term.print(
+            format_args!("{:<16} {}\n", "alternatives:", alternatives.join(", ")),
+            None,
+        )?;
+    }
+    term.print(
+        format_args!(
+            "{:<16} {}\n",
+            "unsafe:",
+            facts
+                .unsafe_count
+                .map_or(unknown.clone(), |c| c.to_string())
+        ),
+        None,
+    )?;
+    term.print(
+        format_args!("{:<16} {}\n", "build script:", facts.has_build_script),
+        flag(facts.has_build_script, warn),
+    )?;
+    term.print(
+        format_args!(
+            "{:<16} {}\n",
+            "owners:",
+            facts
+                .owners
+                .as_ref()
+                .map_or(unknown.clone(), |o| o.join(", "))
+        ),
+        None,
+    )?;
+    if let Some(unknown_owners) = facts.unknown_owners.as_ref().filter(|o| !o.is_empty()) {
+        term.print(
+            format_args!("{:<16} {}\n", "unknown owners:", unknown_owners.join(", ")),
+            warn,
+        )?;
+    }
+    match (&facts.published, facts.days_since_published) {
+        (Some(date), Some(days)) => term.print(
+            format_args!("{:<16} {} ({} days ago)\n", "published:", date, days),
+            None,
+        )?,
+        _ => term.print(format_args!("{:<16} {}\n", "published:", unknown), None)?,
+    }
+
+    Ok(())
+}
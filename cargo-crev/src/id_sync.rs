@@ -0,0 +1,101 @@
+//! `id sync`: moving your own Id between your machines
+//!
+//! `id export` prints the Id as YAML, with the secret key encrypted only by
+//! the passphrase. Pasted into a chat or an email, it's one weak passphrase
+//! away from being stolen. `id sync export` encrypts it again, with
+//! [age](https://age-encryption.org), to the age public key of the receiving
+//! machine, and `id sync import` checks it's the expected Id before importing.
+use crate::{opts, prelude::*};
+use age::armor::{ArmoredReader, ArmoredWriter, Format};
+use crev_data::Id;
+use crev_lib::{id::LockedId, Local};
+use std::{
+    io::{Read as _, Write as _},
+    str::FromStr,
+};
+
+/// Encrypt `plaintext` to age `recipients` (`age1...` public keys), ASCII-armored
+fn encrypt(plaintext: &[u8], recipients: &[String]) -> Result<String> {
+    let recipients = recipients
+        .iter()
+        .map(|recipient| {
+            age::x25519::Recipient::from_str(recipient)
+                .map_err(|e| format_err!("Invalid age recipient `{recipient}`: {e}"))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let encryptor = age::Encryptor::with_recipients(
+        recipients
+            .iter()
+            .map(|recipient| recipient as &dyn age::Recipient),
+    )?;
+
+    let mut encrypted = vec![];
+    let armor = ArmoredWriter::wrap_output(&mut encrypted, Format::AsciiArmor)?;
+    let mut writer = encryptor.wrap_output(armor)?;
+    writer.write_all(plaintext)?;
+    writer.finish().and_then(|armor| armor.finish())?;
+    Ok(String::from_utf8(encrypted)?)
+}
+
+/// Decrypt the output of `encrypt` with one of `identities`
+fn decrypt(encrypted: &[u8], identities: &[Box<dyn age::Identity>]) -> Result<Vec<u8>> {
+    let decryptor = age::Decryptor::new(ArmoredReader::new(encrypted))?;
+    let mut reader = decryptor.decrypt(
+        identities
+            .iter()
+            .map(|identity| identity.as_ref() as &dyn age::Identity),
+    )?;
+    let mut decrypted = vec![];
+    reader.read_to_end(&mut decrypted)?;
+    Ok(decrypted)
+}
+
+/// Print the Id encrypted to `--age` recipients
+pub fn export(args: &opts::IdSyncExport) -> Result<()> {
+    let local = Local::auto_open()?;
+    let locked_id = local.export_locked_id(args.id.clone())?;
+    let id = LockedId::from_str(&locked_id)?.to_public_id().id;
+    print!("{}", encrypt(locked_id.as_bytes(), &args.recipients)?);
+    eprintln!(
+        "Exported Id {id}. On the other machine, check it's the same Id:\n\
+         cargo crev id sync import --identity <age identity file> --expect {id}"
+    );
+    Ok(())
+}
+
+/// Decrypt an Id printed by `export`, checking it's the `--expect`ed one
+///
+/// Returns the `LockedId` YAML, for `id import`.
+pub fn decrypt_expected_id(args: &opts::IdSyncImport, encrypted: &[u8]) -> Result<String> {
+    let expected = Id::crevid_from_str(&args.expect)?;
+    let identities = age::IdentityFile::from_file(args.identity.display().to_string())
+        .map_err(|e| format_err!("Can't read {}: {e}", args.identity.display()))?
+        .into_identities()?;
+    let locked_id = String::from_utf8(decrypt(encrypted, &identities)?)?;
+
+    let id = LockedId::from_str(&locked_id)?.to_public_id().id;
+    if id != expected {
+        bail!("The encrypted Id is {id}, not {expected}. Not importing it.");
+    }
+    Ok(locked_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypts_to_recipients() -> Result<()> {
+        let identity = age::x25519::Identity::generate();
+        let other = age::x25519::Identity::generate();
+        let encrypted = encrypt(b"secret", &[identity.to_public().to_string()])?;
+        assert!(encrypted.starts_with("-----BEGIN AGE ENCRYPTED FILE-----"));
+
+        let identities: Vec<Box<dyn age::Identity>> = vec![Box::new(identity)];
+        assert_eq!(decrypt(encrypted.as_bytes(), &identities)?, b"secret");
+
+        let others: Vec<Box<dyn age::Identity>> = vec![Box::new(other)];
+        assert!(decrypt(encrypted.as_bytes(), &others).is_err());
+        Ok(())
+    }
+}
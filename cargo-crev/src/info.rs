@@ -7,9 +7,16 @@ use crate::{
     Repo,
 };
 use anyhow::{bail, Result};
-use crev_data::proof;
+use crev_data::{
+    proof::{self, CommonOps},
+    Id,
+};
 use serde::{Deserialize, Serialize};
-use std::{collections::HashSet, io};
+use std::{
+    collections::{BTreeSet, HashSet},
+    io,
+    path::Path,
+};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
@@ -18,6 +25,8 @@ pub struct Details {
     pub loc: Option<u64>,
     pub geiger_count: Option<u64>,
     pub has_custom_build: bool,
+    #[serde(default)]
+    pub is_proc_macro: bool,
     pub unmaintained: bool,
 }
 
@@ -28,6 +37,7 @@ impl From<AccumulativeCrateDetails> for Details {
             loc: details.loc,
             geiger_count: details.geiger_count,
             has_custom_build: details.has_custom_build,
+            is_proc_macro: details.is_proc_macro,
             unmaintained: details.is_unmaintained,
         }
     }
@@ -42,6 +52,61 @@ pub struct CrateInfoDepOutput {
     pub rev_dependencies: Vec<proof::PackageVersionId>,
 }
 
+/// A review of the crate version by a trusted Id
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct CrateInfoReview {
+    pub from: Id,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub rating: Option<crev_data::Rating>,
+    #[serde(flatten)]
+    pub scope: proof::review::ReviewScope,
+}
+
+/// How many trusted reviewers went through a part of the crate
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct PathCoverage {
+    pub path: String,
+    pub reviewers: usize,
+}
+
+/// Top-level modules of the crate (entries of its `src`), and any other
+/// paths the reviews say they covered
+fn coverage_paths(crate_root: &Path, reviews: &[proof::review::Package]) -> Vec<String> {
+    let mut paths = BTreeSet::new();
+    if let Ok(entries) = std::fs::read_dir(crate_root.join("src")) {
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let path = entry.path();
+            if path.is_dir() || path.extension().is_some_and(|ext| ext == "rs") {
+                paths.insert(format!("src/{name}"));
+            }
+        }
+    }
+    paths.extend(
+        reviews
+            .iter()
+            .flat_map(|review| review.covered_paths.iter().cloned()),
+    );
+    paths.into_iter().collect()
+}
+
+fn path_coverage(crate_root: &Path, reviews: &[proof::review::Package]) -> Vec<PathCoverage> {
+    coverage_paths(crate_root, reviews)
+        .into_iter()
+        .map(|path| PathCoverage {
+            reviewers: reviews
+                .iter()
+                .filter(|review| review.covers_path(&path))
+                .map(|review| &review.from().id)
+                .collect::<HashSet<_>>()
+                .len(),
+            path,
+        })
+        .collect()
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct CrateInfoOutput {
@@ -49,6 +114,10 @@ pub struct CrateInfoOutput {
     #[serde(flatten)]
     pub deps: Option<CrateInfoDepOutput>,
     pub alternatives: HashSet<proof::PackageId>,
+    pub reviews: Vec<CrateInfoReview>,
+    /// Parts of the crate covered by the `reviews`
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub path_coverage: Vec<PathCoverage>,
     // pub flags: proof::Flags,
 }
 
@@ -95,7 +164,23 @@ pub fn get_crate_info(
 
     let repo = Repo::auto_open_cwd(common_opts.cargo_opts.clone())?;
     let pkg_id = repo.find_pkgid_by_crate_selector(&root_crate)?;
-    let crev_pkg_id = crate::cargo_pkg_id_to_crev_pkg_id(&pkg_id);
+    let crev_pkg_id = repo.crates_io_mirrors().crev_pkg_id(&pkg_id);
+    let reviews: Vec<_> = db
+        .get_package_reviews_for_package(
+            &crev_pkg_id.id.source,
+            Some(&crev_pkg_id.id.name),
+            Some(&crev_pkg_id.version),
+        )
+        .filter(|review| trust_set.is_trusted(&review.from().id))
+        .cloned()
+        .collect();
+    // advisories and issue reports aren't reviews of the code
+    let code_reviews: Vec<_> = reviews
+        .iter()
+        .filter(|review| review.review().is_some())
+        .cloned()
+        .collect();
+    let path_coverage = path_coverage(repo.get_crate(&pkg_id)?.root(), &code_reviews);
     Ok(CrateInfoOutput {
         package: crev_pkg_id.clone(),
         deps: if root_crate.unrelated {
@@ -114,6 +199,15 @@ pub fn get_crate_info(
             .map(|(_, id)| id)
             .cloned()
             .collect(),
+        reviews: reviews
+            .iter()
+            .map(|review| CrateInfoReview {
+                from: review.from().id.clone(),
+                rating: review.review().map(|review| review.rating),
+                scope: review.scope(),
+            })
+            .collect(),
+        path_coverage,
         // flags: db
         //     .get_pkg_flags(&crev_pkg_id.id)
         //     .filter(|(author, _)| trust_set.contains_trusted(author))
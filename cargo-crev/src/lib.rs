@@ -1,4 +1,5 @@
-// this is a dummy library just to make docs.rs render what I need
+// this is mostly a dummy library just to make docs.rs render what I need,
+// and the API for writing `cargo crev x-<name>` extensions
 #![allow(clippy::module_name_repetitions)]
 #![allow(clippy::redundant_closure_for_method_calls)]
 #![cfg_attr(
@@ -9,3 +10,5 @@
 #[cfg(feature = "documentation")]
 /// Documentation
 pub mod doc;
+
+pub mod extension;
@@ -0,0 +1,292 @@
+//! Semantic checks of proof drafts
+//!
+//! They run in the edit loop after a draft parses fine. Problems found
+//! are only warnings, and the user can save the proof anyway.
+//!
+//! [`lint_outgoing_proof`] runs on `publish`, and looks for private data
+//! that ended up in comments by accident.
+use crate::crates_io;
+use crev_data::{proof, proof::CommonOps, Id, SOURCE_CRATES_IO};
+use crev_wot::{ProofDB, UrlOfId};
+use std::collections::HashSet;
+
+/// Warnings about a package review draft by `author`
+///
+/// Checks against crates.io are skipped if `crates_io` is `None`,
+/// or crates.io can't be reached.
+pub fn lint_package_review(
+    review: &proof::review::Package,
+    author: &Id,
+    db: &ProofDB,
+    crates_io: Option<&crates_io::Client>,
+) -> Vec<String> {
+    let mut warnings = vec![];
+    let name = &review.package.id.id.name;
+    let version = &review.package.id.version;
+
+    if let Some(crates_io) = crates_io.filter(|_| review.package.id.id.source == SOURCE_CRATES_IO) {
+        if let Ok(None) = crates_io.get_version_published(name, version) {
+            warnings.push(format!("{name} {version} is not published on crates.io"));
+        }
+        for alternative in &review.alternatives {
+            if alternative.source == SOURCE_CRATES_IO
+                && matches!(crates_io.crate_exists(&alternative.name), Ok(false))
+            {
+                warnings.push(format!(
+                    "alternative `{}` is not a crate on crates.io",
+                    alternative.name
+                ));
+            }
+        }
+    }
+
+    let mut seen_advisory_ids = HashSet::new();
+    for advisory in &review.advisories {
+        for id in &advisory.ids {
+            if !seen_advisory_ids.insert(id) {
+                warnings.push(format!("advisory `{id}` is listed more than once"));
+            }
+        }
+    }
+
+    let own_reviews: Vec<_> = db
+        .get_pkg_reviews_for_name(&review.package.id.id.source, name)
+        .filter(|other| &other.common.from.id == author && &other.package.id.version != version)
+        .collect();
+    for other in &own_reviews {
+        let other_version = &other.package.id.version;
+        for advisory in &review.advisories {
+            for other_advisory in &other.advisories {
+                let shares_id = advisory
+                    .ids
+                    .iter()
+                    .any(|id| other_advisory.ids.contains(id));
+                let overlaps = advisory
+                    .is_for_version_when_reported_in_version(other_version, version)
+                    || other_advisory
+                        .is_for_version_when_reported_in_version(version, other_version);
+                if shares_id && overlaps {
+                    warnings.push(format!(
+                        "advisory `{}` overlaps with the one you reported in version {other_version}",
+                        advisory.ids.join(", ")
+                    ));
+                }
+            }
+        }
+    }
+    if let Some(newest) = own_reviews
+        .iter()
+        .filter(|other| &other.package.id.version > version)
+        .max_by(|a, b| a.package.id.version.cmp(&b.package.id.version))
+    {
+        warnings.push(format!(
+            "you already reviewed a newer version ({}); this review won't be your latest one for {name}",
+            newest.package.id.version
+        ));
+    }
+
+    warnings
+}
+
+/// Warnings about a trust proof draft by `author`
+pub fn lint_trust(trust: &proof::Trust, author: &Id, db: &ProofDB) -> Vec<String> {
+    let mut warnings = vec![];
+
+    let mut seen = HashSet::new();
+    for item in &trust.override_ {
+        let id = &item.id.id;
+        if id == author {
+            warnings.push("overriding your own Id has no effect".into());
+        } else if !seen.insert(id) {
+            warnings.push(format!("override of {id} is listed more than once"));
+        } else if let UrlOfId::None = db.lookup_url(id) {
+            warnings.push(format!(
+                "override of {id}: no proof repository known for this Id"
+            ));
+        }
+    }
+    for group in &trust.groups {
+        if db.get_group(&group.from.id, &group.name).is_none() {
+            warnings.push(format!(
+                "group {group}: not published in the fetched proofs (yet); it will trust no one until it is"
+            ));
+        }
+    }
+
+    warnings
+}
+
+/// Comments longer than this are likely pasted by accident
+const LONG_COMMENT_CHARS: usize = 4000;
+
+/// Prefixes of API tokens of well known services
+const SECRET_PREFIXES: &[&str] = &[
+    "ghp_",
+    "gho_",
+    "ghu_",
+    "ghs_",
+    "github_pat_",
+    "glpat-",
+    "xoxb-",
+    "xoxp-",
+    "AKIA",
+    "sk_live_",
+    "npm_",
+];
+
+/// Top level domains used for hosts on private networks
+const INTERNAL_DOMAINS: &[&str] = &[".internal", ".local", ".lan", ".corp", ".intranet", ".home"];
+
+/// Warnings about a proof about to be published, for likely private data in its comments
+pub fn lint_outgoing_proof(proof: &proof::Proof) -> Vec<String> {
+    let Ok(body) = serde_yaml::from_str::<serde_yaml::Value>(proof.body()) else {
+        return vec![];
+    };
+    let subject = match body["package"]["name"].as_str() {
+        Some(name) => format!("{} of {name}", proof.kind()),
+        None => proof.kind().to_owned(),
+    };
+    let mut comments = vec![];
+    collect_comments(&body, &mut comments);
+    comments
+        .into_iter()
+        .flat_map(lint_comment)
+        .map(|warning| format!("{subject}: {warning}"))
+        .collect()
+}
+
+fn collect_comments<'a>(value: &'a serde_yaml::Value, comments: &mut Vec<&'a str>) {
+    match value {
+        serde_yaml::Value::Mapping(map) => {
+            for (key, value) in map {
+                match (key.as_str(), value.as_str()) {
+                    (Some("comment"), Some(comment)) => comments.push(comment),
+                    _ => collect_comments(value, comments),
+                }
+            }
+        }
+        serde_yaml::Value::Sequence(seq) => {
+            for value in seq {
+                collect_comments(value, comments);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn lint_comment(comment: &str) -> Vec<String> {
+    let mut warnings = vec![];
+    if comment.chars().count() > LONG_COMMENT_CHARS {
+        warnings.push(format!(
+            "comment is unusually long ({} characters)",
+            comment.chars().count()
+        ));
+    }
+    if comment.contains("-----BEGIN") && comment.contains("PRIVATE KEY") {
+        warnings.push("comment contains a private key".into());
+    }
+    let words = comment.split(|c: char| c.is_whitespace() || "\"'`()<>[]{},;".contains(c));
+    for word in words {
+        let word = word.trim_end_matches(['.', ':', '!', '?']);
+        if SECRET_PREFIXES
+            .iter()
+            .any(|prefix| word.starts_with(prefix) && word.len() >= prefix.len() + 16)
+        {
+            let start: String = word.chars().take(8).collect();
+            warnings.push(format!("`{start}...` looks like an API token"));
+        } else if ["password=", "passwd=", "secret=", "token="]
+            .iter()
+            .any(|key| word.to_ascii_lowercase().contains(key))
+        {
+            warnings.push(format!("`{word}` looks like a credential"));
+        } else if is_email(word) {
+            warnings.push(format!("`{word}` looks like an email address"));
+        } else if let Some(host) = internal_host(word) {
+            warnings.push(format!("`{host}` looks like an internal hostname"));
+        }
+    }
+    warnings
+}
+
+fn is_email(word: &str) -> bool {
+    let Some((user, domain)) = word.split_once('@') else {
+        return false;
+    };
+    !user.is_empty()
+        && !user.contains('/')
+        && domain.contains('.')
+        && !domain.starts_with('.')
+        && domain
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+}
+
+/// The host of `word`, if it's a host name or URL on a private network
+fn internal_host(word: &str) -> Option<&str> {
+    let host = word.split_once("://").map_or(word, |(_, rest)| rest);
+    let host = host.split(['/', ':']).next()?;
+    let host = host.rsplit_once('@').map_or(host, |(_, host)| host);
+    let is_private_ip = {
+        let octets: Vec<u8> = host.split('.').filter_map(|o| o.parse().ok()).collect();
+        octets.len() == 4
+            && host.split('.').count() == 4
+            && matches!(octets[..], [10, ..] | [192, 168, ..] | [172, 16..=31, ..])
+    };
+    let is_internal_name = host.contains('.')
+        && INTERNAL_DOMAINS
+            .iter()
+            .any(|domain| host.to_ascii_lowercase().ends_with(domain));
+    (is_private_ip || is_internal_name).then_some(host)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_private_data_in_comments() {
+        let comment = "Checked with ghp_0123456789abcdefghij at build.corp.internal:8080,\n\
+                       ask jane.doe@example.com or see http://10.1.2.3/notes";
+        let warnings = lint_comment(comment);
+        assert_eq!(warnings.len(), 4, "{warnings:?}");
+        assert!(warnings[1].contains("`build.corp.internal`"));
+
+        let comment = "Uses `unsafe` in src/lib.rs:10, see https://github.com/rust-lang/rust/issues/1 \
+                       and crev id FYlr8YoYGVvDwHQxqEIs89reKKDy-oWisoO0qXXEfHE. Version 1.2.3.4 is fine.";
+        assert_eq!(lint_comment(comment), Vec::<String>::new());
+        assert_eq!(lint_comment(&"a".repeat(LONG_COMMENT_CHARS + 1)).len(), 1);
+    }
+
+    #[test]
+    fn finds_private_data_in_outgoing_proofs() -> anyhow::Result<()> {
+        use crev_data::{proof::ContentExt, UnlockedId, Version};
+
+        let id = UnlockedId::generate_for_git_url("https://example.com/crev-proofs");
+        let review = |comment: &str| -> anyhow::Result<proof::Proof> {
+            Ok(proof::review::PackageBuilder::default()
+                .from(id.id.clone())
+                .package(proof::PackageInfo {
+                    id: proof::PackageVersionId::new(
+                        SOURCE_CRATES_IO.to_owned(),
+                        "leftpad".into(),
+                        Version::parse("1.0.0")?,
+                    ),
+                    digest: vec![],
+                    digest_type: proof::default_digest_type(),
+                    revision: String::new(),
+                    revision_type: proof::default_revision_type(),
+                    digest_ignore: None,
+                })
+                .comment(comment.into())
+                .build()
+                .map_err(|e| anyhow::format_err!("{e}"))?
+                .sign_by(&id)?)
+        };
+
+        let warnings = lint_outgoing_proof(&review("ask jane.doe@example.com")?);
+        assert_eq!(warnings.len(), 1, "{warnings:?}");
+        assert!(warnings[0].starts_with("package review of leftpad: "));
+        assert!(lint_outgoing_proof(&review("looks fine")?).is_empty());
+        Ok(())
+    }
+}
@@ -8,7 +8,9 @@
     doc = "See [user documentation module](./doc/user/index.html)."
 )]
 use crate::prelude::*;
-use crev_data::{proof::ContentExt, UnlockedId, SOURCE_CRATES_IO};
+use cargo_crev::extension;
+use clap::CommandFactory;
+use crev_data::{proof::ContentExt, UnlockedId, SOURCE_CRATES_IO, SOURCE_GIT_PREFIX};
 use crev_lib::id::LockedId;
 use crev_lib::{self, local::Local};
 use log::info;
@@ -20,34 +22,58 @@ use std::{
     io::{self, BufRead, Write as _},
     panic,
     path::PathBuf,
+    sync::atomic::{AtomicBool, Ordering},
 };
-use structopt::StructOpt;
 
 #[cfg(feature = "documentation")]
 /// Documentation
 pub mod doc;
 
+mod badge;
 mod crates_io;
 mod deps;
+mod diff;
+mod discovery;
 mod dyn_proof;
 mod edit;
+mod facts;
+mod id_sync;
 mod info;
+mod lint;
 mod opts;
+mod org;
+mod patch;
+mod policy;
+mod pre_publish;
 mod prelude;
+mod provenance;
+mod queue;
+mod release_notes;
 mod repo;
 mod review;
+mod rustsec;
+mod sbom;
+mod session;
 mod shared;
 mod term;
 mod tokei;
+mod typosquat;
+mod usage;
+mod waiver;
+mod why_unverified;
 mod wot;
+mod wot_export;
 
 use crate::{
     repo::Repo,
-    review::{create_review_proof, list_reviews},
+    review::{create_review_proof, export_attestation, list_reviews},
     shared::*,
 };
-use crev_data::{proof, Id, TrustLevel};
-use crev_lib::{TrustProofType, Warning};
+use crev_data::{
+    proof::{self, CommonOps},
+    Id, TrustLevel,
+};
+use crev_lib::{DigestIgnorePolicy, TrustProofType, Warning};
 use crev_wot::{PkgVersionReviewId, ProofDB, TrustSet, UrlOfId};
 use log::debug;
 
@@ -74,7 +100,16 @@ impl LocalExt for Local {
 }
 pub fn repo_publish() -> Result<()> {
     let local = Local::auto_open()?;
-    let mut status = local.run_git_verbose(vec!["diff".into(), "--exit-code".into()])?;
+    let id = local.read_current_unlocked_id(&term::read_passphrase)?;
+    let released = local.release_due_embargoed_proofs()?;
+    if released > 0 {
+        eprintln!("Released {released} embargoed proofs");
+    }
+    local.write_proof_index(&id)?;
+
+    // against `HEAD`, to include the staged index files
+    let mut status =
+        local.run_git_verbose(vec!["diff".into(), "HEAD".into(), "--exit-code".into()])?;
 
     if status.code().unwrap_or(-2) == 1 {
         status = local.run_git_verbose(vec![
@@ -85,17 +120,27 @@ pub fn repo_publish() -> Result<()> {
         ])?;
     }
 
-    if status.code().unwrap_or(-1) == 0 {
+    // a new yearly branch has nothing to pull yet
+    if status.code().unwrap_or(-1) == 0 && local.proof_dir_branch_is_on_origin()? {
         status = local.run_git_verbose(vec!["pull".into(), "--rebase".into()])?;
     }
     if status.code().unwrap_or(-1) == 0 {
+        let warnings: Vec<_> = local
+            .unpublished_proofs()?
+            .iter()
+            .flat_map(lint::lint_outgoing_proof)
+            .collect();
+        if !edit::confirm_lint_warnings(&warnings, "Publish anyway? (y/N/q) ")? {
+            bail!("Nothing was published. The proofs are committed locally; fix them and run `cargo crev publish` again");
+        }
         status = local.run_git_verbose(vec!["push".into()])?;
     }
     std::process::exit(status.code().unwrap_or(-159));
 }
 
 fn repo_update(args: opts::Update, warnings: &mut Vec<Warning>) -> Result<()> {
-    let local = Local::auto_open()?;
+    let mut local = Local::auto_open()?;
+    local.set_cancellation_token(interrupt_token().clone());
     let status = local.run_git_verbose(vec!["pull".into(), "--rebase".into()])?;
     if !status.success() {
         std::process::exit(status.code().unwrap_or(-159));
@@ -107,26 +152,266 @@ fn repo_update(args: opts::Update, warnings: &mut Vec<Warning>) -> Result<()> {
 }
 
 pub fn proof_find(args: opts::ProofFind) -> Result<()> {
+    use opts::ProofFindKind;
+
     let local = crev_lib::Local::auto_open()?;
     let db = local.load_db()?;
-    let mut iter = Box::new(db.get_pkg_reviews_for_source(SOURCE_CRATES_IO))
-        as Box<dyn Iterator<Item = &proof::review::Package>>;
+    let author = args
+        .author
+        .as_deref()
+        .map(crev_data::id::Id::crevid_from_str)
+        .transpose()?;
+    let with_comment = args
+        .comment
+        .as_deref()
+        .map(|words| db.find_by_comment(words));
+    let is_selected_author = for_id_author_filter(&local, &db, &args.wot)?;
+
+    let kind_selected = |kind| args.kind.is_empty() || args.kind.contains(&kind);
+    let selected = |from: &Id, signature: &str| {
+        author.as_ref().map_or(true, |id| id == from)
+            && is_selected_author(from)
+            && with_comment
+                .as_ref()
+                .map_or(true, |found| found.contains(signature))
+    };
+    let package_selected = |package: &proof::PackageVersionId| {
+        args.source
+            .as_ref()
+            .map_or(true, |source| &package.id.source == source)
+            && args.crate_.as_ref().map_or(true, |crate_| {
+                &package.id.name == crate_
+                    && args
+                        .version
+                        .as_ref()
+                        .map_or(true, |version| &package.version == version)
+            })
+    };
+    let print = |signature: &str, proof: &dyn std::fmt::Display| {
+        println!("---");
+        if args.show_origin {
+            match db.get_proof_origin(signature) {
+                Some(origin) => println!("# Loaded from: {origin}"),
+                None => println!("# Loaded from: unknown"),
+            }
+        }
+        println!("{proof}");
+    };
 
-    if let Some(author) = args.author.as_ref() {
-        let id = crev_data::id::Id::crevid_from_str(author)?;
-        iter = Box::new(iter.filter(move |r| r.common.from.id == id));
+    if kind_selected(ProofFindKind::Package) {
+        for review in db.get_all_pkg_reviews() {
+            let Some(signature) = db.get_pkg_review_signature(review) else {
+                continue;
+            };
+            if selected(&review.common.from.id, signature) && package_selected(&review.package.id) {
+                print(signature, review);
+            }
+        }
+    }
+    if kind_selected(ProofFindKind::Code) {
+        let mut reviews: Vec<_> = db
+            .all_code_reviews()
+            .filter(|(signature, review)| {
+                selected(&review.common.from.id, signature) && package_selected(&review.package.id)
+            })
+            .collect();
+        reviews.sort_by_key(|(_, review)| review.date_utc());
+        for (signature, review) in reviews {
+            print(signature, review);
+        }
+    }
+    // trust proofs aren't about packages
+    if kind_selected(ProofFindKind::Trust) && args.crate_.is_none() && args.source.is_none() {
+        let mut trusts: Vec<_> = db
+            .all_trust_proofs()
+            .filter(|(signature, trust)| selected(&trust.common.from.id, signature))
+            .collect();
+        trusts.sort_by_key(|(_, trust)| trust.date_utc());
+        for (signature, trust) in trusts {
+            print(signature, trust);
+        }
     }
 
-    if let Some(crate_) = args.crate_.as_ref() {
-        iter = Box::new(iter.filter(move |r| &r.package.id.id.name == crate_));
-        if let Some(version) = args.version.as_ref() {
-            iter = Box::new(iter.filter(move |r| &r.package.id.version == version));
+    Ok(())
+}
+
+pub fn proof_staged(args: opts::ProofStaged) -> Result<()> {
+    let local = crev_lib::Local::auto_open()?;
+    let staging = local.proof_staging();
+    match args {
+        opts::ProofStaged::List { full } => {
+            for staged in staging.list()? {
+                if full {
+                    println!("--- {}\n{}", staged.name, staged.proof);
+                } else {
+                    println!(
+                        "{} {} {}",
+                        staged.name,
+                        staged.proof.date().to_rfc3339(),
+                        staged.proof.kind()
+                    );
+                }
+            }
+        }
+        opts::ProofStaged::Drop { names } => {
+            for prefix in &names {
+                let name = match staging.find(prefix)?.as_slice() {
+                    [staged] => staged.name.clone(),
+                    [] => bail!("No staged proof named {}", prefix),
+                    _ => bail!("More than one staged proof starts with {}", prefix),
+                };
+                staging.remove(&name)?;
+                eprintln!("Dropped {name}");
+            }
+        }
+        opts::ProofStaged::Commit { message } => {
+            let count = local.commit_staged_proofs(&message)?;
+            eprintln!("Stored {count} staged proofs. Use `cargo crev publish` to push them.");
         }
     }
-    for review in iter {
-        println!("---\n{review}");
+    Ok(())
+}
+
+/// Comment of the proofs overriding retracted ones
+const RETRACTED_COMMENT: &str = "Retracted";
+
+pub fn proof_retract(args: opts::ProofRetract) -> Result<()> {
+    let local = crev_lib::Local::auto_open()?;
+    let Some(own) = local.last_own_proof()? else {
+        bail!("There are no proofs of the current Id in its proof repository");
+    };
+    let description = format!(
+        "{} proof of {}",
+        own.proof.kind(),
+        own.proof.date().to_rfc3339()
+    );
+
+    if !own.published {
+        local.remove_own_proof(&own)?;
+        let name = local.proof_staging().add(&own.proof)?;
+        if !args.common_proof_create.no_commit {
+            local.proof_dir_commit(&format!("Retract {description}"))?;
+        }
+        eprintln!(
+            "Removed the {description} from {}. It's staged as {name}: \
+             `cargo crev proof staged commit` puts it back, `cargo crev proof staged drop {name}` discards it.",
+            own.rel_path.display()
+        );
+        return Ok(());
     }
 
+    let id = local.read_current_unlocked_id(&term::read_passphrase)?;
+    let retraction = sign_retraction(&own.proof, &id)?;
+    maybe_store(
+        &local,
+        &id,
+        &retraction,
+        &format!("Retract {description}"),
+        &args.common_proof_create,
+    )?;
+    eprintln!(
+        "The {description} is published already, so it's overridden by a new proof. \
+         Retracting again before publishing removes that one."
+    );
+    Ok(())
+}
+
+/// A new proof taking back the published `proof` of `id`
+fn sign_retraction(proof: &proof::Proof, id: &UnlockedId) -> Result<proof::Proof> {
+    match proof.kind() {
+        proof::PackageReview::KIND => {
+            let mut review: proof::PackageReview = proof.parse_content()?;
+            *review.review_possibly_none_mut() = crev_data::Review::new_none();
+            review.issues.clear();
+            review.advisories.clear();
+            review.flags = proof::Flags::default();
+            review.alternatives.clear();
+            review.comment = RETRACTED_COMMENT.into();
+            review.common.original = None;
+            review.touch_date();
+            Ok(review.sign_by(id)?)
+        }
+        proof::Trust::KIND => {
+            let mut trust: proof::Trust = proof.parse_content()?;
+            trust.trust = TrustLevel::None;
+            trust.override_.clear();
+            trust.comment = RETRACTED_COMMENT.into();
+            trust.common.original = None;
+            trust.touch_date();
+            Ok(trust.sign_by(id)?)
+        }
+        kind => bail!("A published {kind} proof can't be retracted"),
+    }
+}
+
+/// Import a `LockedId` as the current Id, and get its proof repo
+fn import_own_id(locked_id: &str) -> Result<()> {
+    let local = Local::auto_create_or_open()?;
+    let id = local.import_locked_id(locked_id)?;
+    // Note: It's unclear how much of this should be done by
+    // the library
+    local.save_current_id(&id.id)?;
+
+    let url = &id
+        .url
+        .as_ref()
+        .expect("A public id must have an associated URL");
+    let proof_dir_path = local.get_proofs_dir_path_for_url(url)?;
+    if !proof_dir_path.exists() {
+        let mut warnings = Vec::new();
+        local.clone_proof_dir_from_git(&url.url, false, &mut warnings)?;
+        Warning::log_all(&warnings);
+    }
+    Ok(())
+}
+
+pub fn proof_validate(unpublished_only: bool) -> Result<()> {
+    let local = crev_lib::Local::auto_open()?;
+    let files = local.proof_files_to_validate(unpublished_only)?;
+    let mut invalid = 0;
+    for (path, content) in &files {
+        let problems = crev_lib::validate::validate_proof_file(content);
+        if !problems.is_empty() {
+            invalid += 1;
+        }
+        for problem in problems {
+            eprintln!("{}: {problem}", path.display());
+        }
+    }
+    if invalid > 0 {
+        bail!("{invalid} of {} proof files are invalid", files.len());
+    }
+    eprintln!("{} proof files are valid", files.len());
+    Ok(())
+}
+
+/// First lines of the `pre-push` hook installed by `repo install-hooks`
+const PRE_PUSH_HOOK_HEADER: &str = "#!/bin/sh\n# Installed by `cargo crev repo install-hooks`\n";
+
+pub fn repo_install_hooks(force: bool) -> Result<()> {
+    let local = Local::auto_open()?;
+    let repo = git2::Repository::open(local.get_proofs_dir_path()?)?;
+    let hooks_dir = repo.path().join("hooks");
+    let path = hooks_dir.join("pre-push");
+    if let Ok(existing) = std::fs::read_to_string(&path) {
+        if !force && !existing.starts_with(PRE_PUSH_HOOK_HEADER) {
+            bail!(
+                "{} exists already. Use `--force` to replace it",
+                path.display()
+            );
+        }
+    }
+    std::fs::create_dir_all(&hooks_dir)?;
+    std::fs::write(
+        &path,
+        format!("{PRE_PUSH_HOOK_HEADER}exec cargo crev proof validate --staged\n"),
+    )?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt as _;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+    }
+    eprintln!("Installed {}", path.display());
     Ok(())
 }
 
@@ -213,7 +498,13 @@ pub fn proof_reissue(args: opts::ProofReissue) -> Result<()> {
             digest_base64 = crev_common::base64_encode(&orig_proof_digest.0)
         );
 
-        maybe_store(&local, &proof, &commit_msg, &args.common_proof_create)?;
+        maybe_store(
+            &local,
+            &sign_id,
+            &proof,
+            &commit_msg,
+            &args.common_proof_create,
+        )?;
     }
 
     Ok(())
@@ -222,13 +513,20 @@ pub fn proof_reissue(args: opts::ProofReissue) -> Result<()> {
 fn crate_review(args: &opts::CrateReview, default_trust_type: TrustProofType) -> Result<()> {
     let local = ensure_crev_id_exists_or_make_one()?;
 
+    if let Some(length) = args.session {
+        session::run_review_session(&local, &args.common, length)?;
+    }
+
     handle_goto_mode_command(&args.common, Some(&local), |sel| {
         let is_advisory =
             args.advisory || args.affected.is_some() || (!args.issue && args.severity.is_some());
         create_review_proof(
             sel,
             if args.issue {
-                Some(crev_data::Level::Medium)
+                Some(opts::ReportCommon {
+                    severity: crev_data::Level::Medium,
+                    ..Default::default()
+                })
             } else {
                 None
             },
@@ -238,6 +536,7 @@ fn crate_review(args: &opts::CrateReview, default_trust_type: TrustProofType) ->
                     affected: args
                         .affected
                         .unwrap_or(crev_data::proof::review::package::VersionRange::Major),
+                    ..Default::default()
                 })
             } else {
                 None
@@ -250,6 +549,17 @@ fn crate_review(args: &opts::CrateReview, default_trust_type: TrustProofType) ->
             &args.common_proof_create,
             args.skip_activity_check || is_advisory || args.issue,
             args.overrides,
+            args.from_patch.as_deref(),
+            args.subtree
+                .as_deref()
+                .map(|path| (path, args.subtree_exclude.as_slice())),
+            &args.covered,
+            args.force,
+            &if args.registry_normalized {
+                DigestIgnorePolicy::registry_normalized()
+            } else {
+                DigestIgnorePolicy::cargo_crate()
+            },
             args.cargo_opts.clone(),
         )?;
         let has_public_url = local
@@ -268,6 +578,10 @@ fn crate_review(args: &opts::CrateReview, default_trust_type: TrustProofType) ->
 
 #[must_use]
 pub fn cargo_registry_to_crev_source_id(source_id: &cargo::core::SourceId) -> String {
+    if source_id.is_git() {
+        // without the branch or commit, that's the `revision` of the package
+        return format!("{SOURCE_GIT_PREFIX}{}", source_id.url());
+    }
     let s = source_id.as_url().to_string();
     if &s == "registry+https://github.com/rust-lang/crates.io-index" {
         SOURCE_CRATES_IO.into()
@@ -276,6 +590,19 @@ pub fn cargo_registry_to_crev_source_id(source_id: &cargo::core::SourceId) -> St
     }
 }
 
+/// Source crev reviews of a crate from `source_id` are for
+///
+/// Crates from git are reviewed with their repository as the source,
+/// crates from any registry as crates.io crates.
+#[must_use]
+pub fn crev_review_source(source_id: &cargo::core::SourceId) -> String {
+    if source_id.is_git() {
+        cargo_registry_to_crev_source_id(source_id)
+    } else {
+        SOURCE_CRATES_IO.into()
+    }
+}
+
 #[must_use]
 pub fn cargo_pkg_id_to_crev_pkg_id(id: &cargo::core::PackageId) -> proof::PackageVersionId {
     proof::PackageVersionId {
@@ -303,6 +630,9 @@ fn print_ids<'a>(ids: impl Iterator<Item = &'a Id>, trust_set: &TrustSet, db: &P
             url,
         );
     }
+    eprintln!(
+        "REV: dependencies reviewed; VER: verified with their review; SOLE: verified only thanks to their review"
+    );
 }
 
 fn url_to_status_str<'a>(id_url: &UrlOfId<'a>) -> (&'static str, &'a str) {
@@ -314,12 +644,22 @@ fn url_to_status_str<'a>(id_url: &UrlOfId<'a>) -> (&'static str, &'a str) {
     }
 }
 
-fn print_mvp_ids<'a>(ids: impl Iterator<Item = (&'a Id, u64)>, trust_set: &TrustSet, db: &ProofDB) {
-    for (id, count) in ids {
+fn print_mvp_ids<'a>(
+    ids: impl Iterator<Item = (&'a Id, &'a deps::MvpCounts)>,
+    trust_set: &TrustSet,
+    db: &ProofDB,
+) {
+    eprintln!(
+        "{:>3} {:>3} {:>4} {:^43} {:6} URL",
+        "REV", "VER", "SOLE", "ID", "TRUST"
+    );
+    for (id, counts) in ids {
         let (status, url) = url_to_status_str(&db.lookup_url(id));
         println!(
-            "{:>3} {} {:6} {} {}",
-            count,
+            "{:>3} {:>3} {:>4} {} {:6} {} {}",
+            counts.reviewed,
+            counts.verified,
+            counts.sole,
             id,
             trust_set.get_effective_trust_level(id),
             status,
@@ -350,6 +690,36 @@ fn run_command(command: opts::Command) -> Result<CommandExitStatus> {
             opts::Id::Passwd => {
                 current_id_change_passphrase()?;
             }
+            opts::Id::Rotate => {
+                let local = Local::auto_open()?;
+                let old = local.read_current_unlocked_id(&term::read_passphrase)?;
+                let locked_id = local.rotate_current_id(&old, read_new_passphrase)?;
+                let new = locked_id.to_public_id().id;
+                println!("Your new CrevID {new} replaces {}.", old.id.id);
+                println!("Run `cargo crev publish` so others' trust moves to the new Id.");
+                if !locked_id.has_no_passphrase() {
+                    println!("Back it up on another device, to prevent losing it:");
+                    println!("{locked_id}");
+                }
+            }
+            opts::Id::Keyring(opts::IdKeyring::Store) => {
+                let local = Local::auto_open()?;
+                let locked_id = local.read_current_locked_id()?;
+                let passphrase = term::read_passphrase()?;
+                // don't store a passphrase that doesn't work
+                locked_id.to_unlocked(&passphrase)?;
+                crev_lib::passphrase::keyring_set(&locked_id.to_public_id().id, &passphrase)?;
+                eprintln!("Passphrase stored in the OS keyring.");
+            }
+            opts::Id::Keyring(opts::IdKeyring::Forget) => {
+                let local = Local::auto_open()?;
+                let id = local.read_current_locked_id()?.to_public_id().id;
+                if crev_lib::passphrase::keyring_delete(&id)? {
+                    eprintln!("Passphrase removed from the OS keyring.");
+                } else {
+                    eprintln!("No passphrase stored in the OS keyring.");
+                }
+            }
             opts::Id::Current => {
                 let local = Local::auto_open()?;
                 let current = local
@@ -384,27 +754,18 @@ fn run_command(command: opts::Command) -> Result<CommandExitStatus> {
                 println!("{}", local.export_locked_id(args.id)?);
             }
             opts::Id::Import => {
-                let local = Local::auto_create_or_open()?;
                 let s = load_stdin_with_prompt()?;
-                let id = local.import_locked_id(&String::from_utf8(s)?)?;
-                // Note: It's unclear how much of this should be done by
-                // the library
-                local.save_current_id(&id.id)?;
-
-                let url = &id
-                    .url
-                    .as_ref()
-                    .expect("A public id must have an associated URL");
-                let proof_dir_path = local.get_proofs_dir_path_for_url(url)?;
-                if !proof_dir_path.exists() {
-                    let mut warnings = Vec::new();
-                    local.clone_proof_dir_from_git(&url.url, false, &mut warnings)?;
-                    Warning::log_all(&warnings);
-                }
+                import_own_id(&String::from_utf8(s)?)?;
+            }
+            opts::Id::Sync(opts::IdSync::Export(args)) => id_sync::export(&args)?,
+            opts::Id::Sync(opts::IdSync::Import(args)) => {
+                let s = load_stdin_with_prompt()?;
+                import_own_id(&id_sync::decrypt_expected_id(&args, &s)?)?;
             }
             opts::Id::Trust(args) => {
                 set_trust_level_for_ids(
                     &ids_from_string(&args.public_ids)?,
+                    &args.groups,
                     &args.common_proof_create,
                     args.level.unwrap_or(TrustLevel::Medium),
                     args.level.is_none(),
@@ -414,6 +775,7 @@ fn run_command(command: opts::Command) -> Result<CommandExitStatus> {
             opts::Id::Untrust(args) => {
                 set_trust_level_for_ids(
                     &ids_from_string(&args.public_ids)?,
+                    &args.groups,
                     &args.common_proof_create,
                     TrustLevel::None,
                     true,
@@ -423,12 +785,36 @@ fn run_command(command: opts::Command) -> Result<CommandExitStatus> {
             opts::Id::Distrust(args) => {
                 set_trust_level_for_ids(
                     &ids_from_string(&args.public_ids)?,
+                    &args.groups,
                     &args.common_proof_create,
                     TrustLevel::Distrust,
                     true,
                     args.overrides,
                 )?;
             }
+            opts::Id::Recommend(args) => {
+                deps::recommend_ids_to_trust(args)?;
+            }
+            opts::Id::Inspect(args) => {
+                crate::wot::inspect_id(args)?;
+            }
+            opts::Id::Group(cmd) => match cmd {
+                opts::IdGroup::Publish {
+                    name,
+                    public_ids,
+                    comment,
+                    common_proof_create,
+                } => {
+                    crate::wot::publish_group(
+                        name,
+                        ids_from_string(&public_ids)?,
+                        comment,
+                        &common_proof_create,
+                    )?;
+                }
+                opts::IdGroup::Show { group } => crate::wot::show_group(&group)?,
+                opts::IdGroup::List { id } => crate::wot::list_groups(id.as_deref())?,
+            },
             opts::Id::Query(cmd) => match cmd {
                 opts::IdQuery::Current { trust_params } => {
                     let local = Local::auto_open()?;
@@ -501,11 +887,54 @@ fn run_command(command: opts::Command) -> Result<CommandExitStatus> {
 
                     print_ids(tmp.iter().map(|(_, _, id)| id), &trust_set, &db);
                 }
+                opts::IdQuery::Mentions { for_id } => {
+                    let local = crev_lib::Local::auto_open()?;
+                    let db = local.load_db()?;
+                    let for_id = local.get_for_id_from_str(for_id.as_deref())?;
+                    let override_comment = |override_: &[crev_data::proof::OverrideItem]| {
+                        override_
+                            .iter()
+                            .find(|item| item.id.id == for_id)
+                            .map(|item| item.comment.clone())
+                            .unwrap_or_default()
+                    };
+
+                    println!("Trusted by:");
+                    for (id, trust_level) in db.get_reverse_trust_for(&for_id) {
+                        let (status, url) = url_to_status_str(&db.lookup_url(id));
+                        println!("  {id} {trust_level:6} {status} {url}");
+                    }
+
+                    println!("Trust proofs overriding it:");
+                    for trust in db.get_trust_proofs_overriding(&for_id) {
+                        let ids: Vec<_> = trust.ids.iter().map(|id| id.id.to_string()).collect();
+                        println!(
+                            "  {} (trusting {}) {}",
+                            trust.common.from.id,
+                            ids.join(", "),
+                            override_comment(&trust.override_)
+                        );
+                    }
+
+                    println!("Package reviews overriding it:");
+                    for review in db.get_package_reviews_overriding(&for_id) {
+                        println!(
+                            "  {} ({} {}) {}",
+                            review.common.from.id,
+                            review.package.id.id.name,
+                            review.package.id.version,
+                            override_comment(&review.override_)
+                        );
+                    }
+                }
             },
         },
         opts::Command::Trust(args) => {
-            let (urls, ids): (Vec<_>, Vec<_>) = args
+            let (domains, rest): (Vec<_>, Vec<_>) = args
                 .public_ids_or_urls
+                .into_iter()
+                .partition(|arg| discovery::domain_of_url(arg).is_some());
+            let (urls, ids): (Vec<_>, Vec<_>) = rest
                 .into_iter()
                 .partition(|arg| arg.starts_with("https://"));
             let mut ids = ids_from_string(&ids)?;
@@ -513,6 +942,14 @@ fn run_command(command: opts::Command) -> Result<CommandExitStatus> {
             let local = crev_lib::Local::auto_create_or_open()?;
             let mut db = local.load_db()?;
 
+            // Ids published by organizations on their domains
+            for domain in domains
+                .iter()
+                .filter_map(|arg| discovery::domain_of_url(arg))
+            {
+                ids.extend(discovery::discover_ids(&local, &mut db, domain)?);
+            }
+
             // Fetch the URLs
             for url in &urls {
                 local.fetch_url_into(url, &mut db)?;
@@ -539,6 +976,7 @@ fn run_command(command: opts::Command) -> Result<CommandExitStatus> {
             }
             set_trust_level_for_ids(
                 &ids,
+                &[],
                 &args.common_proof_create,
                 args.level.unwrap_or(TrustLevel::Medium),
                 args.level.is_none(),
@@ -565,12 +1003,28 @@ fn run_command(command: opts::Command) -> Result<CommandExitStatus> {
             opts::Crate::Verify(opts) => {
                 return deps::verify_deps(opts.crate_, opts.opts);
             }
-            opts::Crate::Mvp { crate_, opts, wot } => {
-                deps::crate_mvps(crate_, opts, wot)?;
+            opts::Crate::Mvp {
+                crate_,
+                opts,
+                wot,
+                simulate_untrust,
+            } => {
+                deps::crate_mvps(crate_, opts, wot, simulate_untrust)?;
             }
             opts::Crate::Info { crate_, opts, wot } => {
                 info::print_crate_info(crate_.auto_unrelated()?, opts, wot)?;
             }
+            opts::Crate::WhyUnverified { crate_, opts, wot } => {
+                why_unverified::explain_crate(crate_.auto_unrelated()?, opts, wot)?;
+            }
+            opts::Crate::Facts {
+                crate_,
+                opts,
+                wot,
+                json,
+            } => {
+                facts::print_crate_facts(crate_.auto_unrelated()?, opts, wot, json)?;
+            }
             opts::Crate::Goto(args) => {
                 goto_crate_src(&args.auto_unrelated()?)?;
             }
@@ -578,8 +1032,14 @@ fn run_command(command: opts::Command) -> Result<CommandExitStatus> {
                 expand_crate_src(&args.crate_.auto_unrelated()?)?;
             }
             opts::Crate::Open(args) => {
+                let link = args.link();
                 handle_goto_mode_command(&args.common.clone(), None, |sel| {
-                    crate_open(&sel.clone().auto_unrelated()?, args.cmd, args.cmd_save)
+                    crate_open(
+                        &sel.clone().auto_unrelated()?,
+                        args.cmd,
+                        args.cmd_save,
+                        link,
+                    )
                 })?;
             }
             opts::Crate::Clean(args) => {
@@ -603,6 +1063,15 @@ fn run_command(command: opts::Command) -> Result<CommandExitStatus> {
             opts::Crate::Search(args) => {
                 lookup_crates(&args.query, args.count)?;
             }
+            opts::Crate::RequestReview {
+                crate_,
+                comment,
+                common_proof_create,
+            } => {
+                queue::request_review(crate_.auto_unrelated()?, comment, &common_proof_create)?;
+            }
+            opts::Crate::UpdateIssue(args) => update_issue(args)?,
+            opts::Crate::Exempt(args) => waiver::create_waiver(args)?,
         },
         opts::Command::Config(args) => match args {
             opts::Config::Dir => {
@@ -622,7 +1091,7 @@ fn run_command(command: opts::Command) -> Result<CommandExitStatus> {
                 edit::edit_user_config(&local)?;
             }
             opts::Config::Completions { shell } => {
-                use structopt::clap::Shell;
+                use clap_complete::Shell;
                 let shell = match shell
                     .unwrap_or(
                         PathBuf::from(std::env::var("SHELL")?)
@@ -642,10 +1111,11 @@ fn run_command(command: opts::Command) -> Result<CommandExitStatus> {
                         bail!("{} shell not supported", other);
                     }
                 };
-                opts::Opts::clap().gen_completions_to(
+                clap_complete::generate(
+                    shell,
+                    &mut opts::Opts::command(),
                     // we have to pretend, we're generating for main cargo binary
                     "cargo",
-                    shell,
                     &mut std::io::stdout(),
                 );
             }
@@ -655,6 +1125,7 @@ fn run_command(command: opts::Command) -> Result<CommandExitStatus> {
                 let local = crev_lib::Local::auto_create_or_open()?;
                 println!("{}", local.get_proofs_dir_path()?.display());
             }
+            opts::Repo::InstallHooks { force } => repo_install_hooks(force)?,
             opts::Repo::Git(git) => {
                 let local = Local::auto_open()?;
                 let status = local.run_git_verbose(git.args)?;
@@ -663,8 +1134,8 @@ fn run_command(command: opts::Command) -> Result<CommandExitStatus> {
                 ));
             }
             opts::Repo::Query(args) => match args {
-                opts::RepoQuery::Review(args) => list_reviews(&args.crate_)?,
-                opts::RepoQuery::Advisory(args) => list_advisories(&args.crate_)?,
+                opts::RepoQuery::Review(args) => list_reviews(&args)?,
+                opts::RepoQuery::Advisory(args) => list_advisories(&args)?,
                 opts::RepoQuery::Issue(args) => list_issues(&args)?,
             },
             opts::Repo::Publish => repo_publish()?,
@@ -672,8 +1143,13 @@ fn run_command(command: opts::Command) -> Result<CommandExitStatus> {
                 opts::RepoFetch::Trusted {
                     distance_params,
                     for_id,
+                    max_age,
                 } => {
-                    let local = Local::auto_create_or_open()?;
+                    let mut local = Local::auto_create_or_open()?;
+                    local.set_cancellation_token(interrupt_token().clone());
+                    local.set_fetch_max_age(
+                        max_age.map(|hours| std::time::Duration::from_secs(hours * 60 * 60)),
+                    );
                     local.fetch_trusted(
                         distance_params.into(),
                         for_id.as_deref(),
@@ -681,15 +1157,40 @@ fn run_command(command: opts::Command) -> Result<CommandExitStatus> {
                     )?;
                 }
                 opts::RepoFetch::Url(params) => {
-                    let local = Local::auto_create_or_open()?;
+                    let mut local = Local::auto_create_or_open()?;
+                    local.set_cancellation_token(interrupt_token().clone());
                     local.fetch_url(&params.url)?;
                 }
-                opts::RepoFetch::All => {
-                    let local = Local::auto_create_or_open()?;
+                opts::RepoFetch::All(args) => {
+                    let mut local = Local::auto_create_or_open()?;
+                    local.set_cancellation_token(interrupt_token().clone());
                     info!("Fetching...");
-                    local.fetch_all(&mut Warning::auto_log())?;
+                    local.fetch_all_resumable(args.retry_failed, &mut Warning::auto_log())?;
+                }
+                opts::RepoFetch::Archives => {
+                    let mut local = Local::auto_create_or_open()?;
+                    local.set_cancellation_token(interrupt_token().clone());
+                    let mut db = local.load_db()?;
+                    local.fetch_epoch_archives(&mut db, &mut Warning::auto_log())?;
                 }
             },
+            opts::Repo::Epoch(args) => {
+                let local = Local::auto_open()?;
+                let id = local.read_current_unlocked_id(&term::read_passphrase)?;
+                let archive_url = &args.archive_url;
+                let epoch = local.build_epoch_proof(id.as_public_id(), archive_url)?;
+                let proof = epoch.sign_by(&id)?;
+                maybe_store(
+                    &local,
+                    &id,
+                    &proof,
+                    "Add epoch proof",
+                    &args.common_proof_create,
+                )?;
+                eprintln!("Push your proof repository to {archive_url} and keep it there:");
+                eprintln!("  cargo crev repo git push {archive_url} HEAD");
+                eprintln!("Then you can remove older proofs from your proof repository.");
+            }
             opts::Repo::Update(args) => repo_update(args, &mut Warning::auto_log())?,
             opts::Repo::Edit(cmd) => match cmd {
                 opts::RepoEdit::Readme => {
@@ -701,6 +1202,13 @@ fn run_command(command: opts::Command) -> Result<CommandExitStatus> {
                 }
             },
 
+            opts::Repo::Import(opts::RepoImport {
+                source: Some(opts::RepoImportSource::Rustsec { advisory_db }),
+                ..
+            }) => {
+                let local = Local::auto_create_or_open()?;
+                rustsec::import_advisory_db(&local, advisory_db)?;
+            }
             opts::Repo::Import(args) => {
                 let local = Local::auto_create_or_open()?;
                 let id = local.read_current_unlocked_id(&term::read_passphrase)?;
@@ -718,7 +1226,7 @@ fn run_command(command: opts::Command) -> Result<CommandExitStatus> {
                             }
                             content.set_author(id.as_public_id());
                             let proof = content.sign_by(&id)?;
-                            maybe_store(&local, &proof, commit_msg, &args.common)?;
+                            maybe_store(&local, &id, &proof, commit_msg, &args.common)?;
                         }
                         Err(e) => {
                             eprintln!("Ignoried unknown proof - {e}");
@@ -734,13 +1242,31 @@ fn run_command(command: opts::Command) -> Result<CommandExitStatus> {
             opts::Proof::Reissue(args) => {
                 proof_reissue(args)?;
             }
+            opts::Proof::Staged(args) => {
+                proof_staged(args)?;
+            }
+            opts::Proof::Retract(args) => {
+                proof_retract(args)?;
+            }
+            opts::Proof::Validate { staged } => {
+                proof_validate(staged)?;
+            }
+        },
+        opts::Command::Export(args) => match args {
+            opts::Export::Attestation { crate_ } => export_attestation(&crate_)?,
         },
         opts::Command::Goto(args) => {
             goto_crate_src(&args.auto_unrelated()?)?;
         }
         opts::Command::Open(args) => {
+            let link = args.link();
             handle_goto_mode_command(&args.common.clone(), None, |crate_| {
-                crate_open(&crate_.clone().auto_unrelated()?, args.cmd, args.cmd_save)
+                crate_open(
+                    &crate_.clone().auto_unrelated()?,
+                    args.cmd,
+                    args.cmd_save,
+                    link,
+                )
             })?;
         }
         opts::Command::Publish => repo_publish()?,
@@ -751,10 +1277,62 @@ fn run_command(command: opts::Command) -> Result<CommandExitStatus> {
             opts::Wot::Log { wot } => {
                 crate::wot::print_log(wot)?;
             }
+            opts::Wot::SuggestPrune { wot, stale_years } => {
+                crate::wot::print_prune_suggestions(wot, stale_years)?;
+            }
+            opts::Wot::Snapshot(opts::WotSnapshot::Sign { wot, comment }) => {
+                crate::wot::print_signed_trust_snapshot(wot, comment)?;
+            }
+            opts::Wot::Snapshot(opts::WotSnapshot::Import { wot, path }) => {
+                crate::wot::import_trust_snapshot(&path, wot)?;
+            }
+            opts::Wot::Tune { common, wot } => {
+                crate::wot::print_tune(common, wot)?;
+            }
+            opts::Wot::Health { common, wot } => {
+                crate::wot::print_health(common, wot)?;
+            }
+            opts::Wot::Export { wot, format } => {
+                crate::wot_export::export(wot, format)?;
+            }
+        },
+        opts::Command::Stats(args) => match args {
+            opts::Stats::Me => usage::print_my_usage_stats()?,
+        },
+        opts::Command::PrePublishCheck(args) => {
+            return pre_publish::pre_publish_check(args);
+        }
+        opts::Command::Queue(args) => match args {
+            opts::Queue::Requests { wot, requirements } => {
+                queue::print_review_requests(wot, requirements)?;
+            }
+            opts::Queue::List { full } => queue::list_embargoed(full)?,
+            opts::Queue::Release { names } => queue::release_embargoed(&names)?,
+        },
+        opts::Command::Advisory(args) => match args {
+            opts::Advisory::FromRustsec(args) => {
+                let local = ensure_crev_id_exists_or_make_one()?;
+                rustsec::review_from_rustsec(&local, args)?;
+            }
+        },
+        opts::Command::Org(args) => match args {
+            opts::Org::Summary { common, wot } => org::print_summary(common, wot)?,
+            opts::Org::Report { from, json } => org::print_report(&from, json)?,
+        },
+        opts::Command::Badge(args) => badge::print_badge(args.common, args.wot, args.format)?,
+        opts::Command::Report(args) => match args {
+            opts::Report::Sbom {
+                common,
+                wot,
+                format,
+            } => sbom::print_sbom(common, wot, format)?,
         },
         opts::Command::Verify(opts) => {
             return deps::verify_deps(opts.crate_, opts.opts);
         }
+        opts::Command::VerifyDigest(args) => {
+            return deps::verify_digest(args);
+        }
     }
 
     Ok(CommandExitStatus::Success)
@@ -892,6 +1470,7 @@ fn generate_new_id_interactively(url: Option<&str>, use_https_push: bool) -> Res
 
 fn set_trust_level_for_ids(
     ids: &[Id],
+    groups: &[String],
     common_proof_create: &crate::opts::CommonProofCreate,
     trust_level: TrustLevel,
     edit_interactively: bool,
@@ -910,57 +1489,73 @@ fn set_trust_level_for_ids(
         vec![]
     };
 
+    let groups = groups
+        .iter()
+        .map(|group| wot::parse_group_ref(group, Some(&unlocked_id.id.id)))
+        .collect::<Result<_>>()?;
     let mut trust = local.build_trust_proof(
         unlocked_id.as_public_id(),
         ids.to_vec(),
+        groups,
         trust_level,
         overrides,
     )?;
 
     if edit_interactively {
-        let extra_comment = if trust_level == TrustLevel::Distrust {
-            Some("WARNING: Distrust has severe consequences. Read documentation below.")
-        } else {
-            None
-        };
-        trust = edit::edit_proof_content_iteractively(&trust, None, None, extra_comment, |text| {
-            if show_override_suggestions && trust.override_.is_empty() {
-                writeln!(text, "# override:")?;
-            }
-
-            if show_override_suggestions {
-                let db = local.load_db()?;
-                for (id, trust_level) in ids.iter().flat_map(|id| db.get_reverse_trust_for(id)) {
-                    let (status, url) = url_to_status_str(&db.lookup_url(id));
-                    writeln!(text, "# - id-type: crev")?; // TODO: support other ids?
-                    writeln!(text, "#   id: {id} # level: {trust_level}")?;
-                    writeln!(text, "#   url: {url} # {status}")?;
-                    writeln!(text, "#   comment: \"\"")?;
+        let db = local.load_db()?;
+        let mut extra_comment = String::new();
+        if trust_level == TrustLevel::Distrust {
+            extra_comment
+                .push_str("WARNING: Distrust has severe consequences. Read documentation below.\n");
+        }
+        for id in ids {
+            extra_comment.push('\n');
+            extra_comment.push_str(&wot::trust_draft_context(
+                &db,
+                &unlocked_id.id.id,
+                id,
+                trust_level,
+            )?);
+        }
+        trust = edit::edit_proof_content_iteractively(
+            &trust,
+            None,
+            None,
+            Some(extra_comment.as_str()),
+            |text| {
+                if show_override_suggestions && trust.override_.is_empty() {
+                    writeln!(text, "# override:")?;
                 }
-            }
 
-            Ok(())
-        })?;
+                if show_override_suggestions {
+                    for (id, trust_level) in ids.iter().flat_map(|id| db.get_reverse_trust_for(id))
+                    {
+                        let (status, url) = url_to_status_str(&db.lookup_url(id));
+                        writeln!(text, "# - id-type: crev")?; // TODO: support other ids?
+                        writeln!(text, "#   id: {id} # level: {trust_level}")?;
+                        writeln!(text, "#   url: {url} # {status}")?;
+                        writeln!(text, "#   comment: \"\"")?;
+                    }
+                }
+
+                Ok(())
+            },
+            |trust| lint::lint_trust(trust, &unlocked_id.id.id, &db),
+            |_| Ok(()),
+            common_proof_create.draft_format,
+        )?;
     }
 
     trust.touch_date();
     let proof = trust.sign_by(&unlocked_id)?;
 
-    if common_proof_create.print_unsigned {
-        print!("{}", proof.body());
-    }
-    if common_proof_create.print_signed {
-        print!("{proof}");
-    }
-    if !common_proof_create.no_store {
-        crev_lib::proof::store_id_trust_proof(
-            &proof,
-            ids,
-            trust_level,
-            !common_proof_create.no_commit,
-        )?;
-    }
-    Ok(())
+    maybe_store(
+        &local,
+        &unlocked_id,
+        &proof,
+        &crev_lib::proof::create_id_trust_commit_message(ids, trust_level),
+        common_proof_create,
+    )
 }
 
 fn ensure_crev_id_exists_or_make_one() -> Result<Local> {
@@ -1042,6 +1637,15 @@ fn change_passphrase(
     local.save_locked_id(&locked_id)?;
     local.save_current_id(unlocked_id.as_ref())?;
 
+    // keep a stored passphrase in sync, or it would keep failing
+    if crev_lib::passphrase::keyring_get(&unlocked_id.id.id)?.is_some() {
+        if locked_id.has_no_passphrase() {
+            crev_lib::passphrase::keyring_delete(&unlocked_id.id.id)?;
+        } else {
+            crev_lib::passphrase::keyring_set(&unlocked_id.id.id, passphrase)?;
+        }
+    }
+
     if locked_id.has_no_passphrase() {
         eprintln!("Passphrase disabled.");
     } else {
@@ -1055,6 +1659,9 @@ fn change_passphrase(
     Ok(locked_id)
 }
 
+/// Format log messages as JSON, see `opts::LogFormat`
+static LOG_AS_JSON: AtomicBool = AtomicBool::new(false);
+
 fn main() {
     let mut builder = env_logger::builder();
     let default_log_settings = std::env::var_os("RUST_LOG").is_none();
@@ -1078,7 +1685,14 @@ fn main() {
     }
 
     builder.format(|buf, record| {
-            if record.level() == log::Level::Info {
+            if LOG_AS_JSON.load(Ordering::Relaxed) {
+                let json = serde_json::json!({
+                    "level": record.level().as_str().to_lowercase(),
+                    "target": record.target(),
+                    "message": record.args().to_string(),
+                });
+                writeln!(buf, "{json}")
+            } else if record.level() == log::Level::Info {
                 writeln!(buf, "{}", record.args())
             } else if record.level() > log::Level::Info {
                 writeln!(
@@ -1097,9 +1711,36 @@ fn main() {
         })
         .init();
     debug!("Starting cargo-crev");
-    let opts = opts::Opts::from_args();
+    // `cargo-crev crev x-<name> ...`; extensions parse their own arguments
+    let args: Vec<_> = std::env::args_os().collect();
+    if let Some(name) = args
+        .get(2)
+        .and_then(|arg| arg.to_str())
+        .filter(|arg| arg.starts_with(extension::PREFIX))
+    {
+        let name = name.to_owned();
+        handle_command_result_and_panics(move || {
+            extension::Registry::discover()
+                .run(&name, &args[3..])
+                .map(|code| match code {
+                    0 => CommandExitStatus::Success,
+                    code => CommandExitStatus::CommandExitCode(code),
+                })
+        })
+    }
+    let opts = opts::parse_args();
+    if opts.log_format == opts::LogFormat::Json {
+        LOG_AS_JSON.store(true, Ordering::Relaxed);
+        Warning::log_as_json(true);
+    }
     let opts::MainCommand::Crev(command) = opts.command;
-    handle_command_result_and_panics(|| run_command(command))
+    install_interrupt_handler();
+    handle_command_result_and_panics(|| {
+        let status = run_command(command)?;
+        // a command that stopped early must not report success
+        interrupt_token().check()?;
+        Ok(status)
+    })
 }
 
 fn is_possibly_broken_pipe_msg(s: &str) -> bool {
@@ -1138,6 +1779,9 @@ fn handle_command_result_and_panics(
                 }
             }
             eprintln!("{e:?}");
+            if let Some((code, category)) = crev_lib_error_code(&e) {
+                eprintln!("\nError code: {code} ({category})");
+            }
             std::process::exit(-2)
         }
     }) {
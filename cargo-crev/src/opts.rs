@@ -1,18 +1,18 @@
-use anyhow::{bail, Result};
+use anyhow::{bail, format_err, Result};
+use clap::{Args, Parser, Subcommand};
 use crev_data::{Level, Version};
 use std::{ffi::OsString, path::PathBuf};
-use structopt::StructOpt;
 use term::color;
 
 use crate::term::Term;
 
-#[derive(Debug, StructOpt, Clone, Default)]
+#[derive(Debug, Args, Clone, Default)]
 pub struct CrateSelector {
     /// This crate is not necessarily a dependency of the current cargo project
-    #[structopt(long = "unrelated", short = "u")]
+    #[arg(long = "unrelated", short = 'u')]
     pub unrelated: bool,
 
-    #[structopt(long = "vers", short = "v")]
+    #[arg(long = "vers", short = 'v')]
     version: Option<Version>,
 
     pub name: Option<String>,
@@ -85,41 +85,47 @@ impl ReviewCrateSelector {
     }
 }
 
-#[derive(Debug, StructOpt, Clone, Default)]
+#[derive(Debug, Args, Clone, Default)]
 pub struct CargoOpts {
-    #[structopt(long = "features", value_name = "FEATURES")]
+    #[arg(long = "features", value_name = "FEATURES")]
     /// [cargo] Space-separated list of features to activate
     pub features: Option<String>,
 
-    #[structopt(long = "all-features")]
+    #[arg(long = "all-features")]
     /// [cargo] Activate all available features
     pub all_features: bool,
 
-    #[structopt(long = "no-default-features")]
+    #[arg(long = "no-default-features")]
     /// [cargo] Do not activate the `default` feature
     pub no_default_features: bool,
 
-    #[structopt(long = "dev-dependencies")]
+    #[arg(long = "dev-dependencies")]
     /// [cargo] Activate dev dependencies.
     dev_dependencies: bool,
 
-    #[structopt(long = "no-dev-dependencies")]
+    #[arg(long = "no-dev-dependencies")]
     /// [cargo] Skip dev dependencies.
     no_dev_dependencies: bool,
 
-    #[structopt(long = "manifest-path", value_name = "PATH", parse(from_os_str))]
+    #[arg(long = "manifest-path", value_name = "PATH")]
     /// [cargo] Path to Cargo.toml
     pub manifest_path: Option<PathBuf>,
 
-    #[structopt(short = "Z", value_name = "FLAG")]
-
     /// [cargo] Unstable (nightly-only) flags to Cargo
-    #[structopt(long = "unstable-flags")]
+    #[arg(short = 'Z', long = "unstable-flags", value_name = "FLAG")]
     pub unstable_flags: Vec<String>,
 
-    /// [cargo] Skip targets other than specified (no value = autodetect)
-    #[structopt(long = "target")]
+    /// [cargo] Skip dependencies of platforms other than this target (default: host)
+    #[arg(long = "target")]
     pub target: Option<Option<String>>,
+
+    /// [cargo] Include dependencies of all target platforms, not only of `--target`
+    #[arg(long = "all-platforms", conflicts_with = "target")]
+    pub all_platforms: bool,
+
+    #[arg(long = "map-crates-io-mirrors")]
+    /// Treat crates from registries replacing crates.io in cargo config as crates.io crates
+    pub map_crates_io_mirrors: bool,
 }
 
 impl CargoOpts {
@@ -132,51 +138,100 @@ impl CargoOpts {
     }
 }
 
-#[derive(Debug, StructOpt, Clone)]
+#[derive(Debug, Args, Clone)]
 pub struct IdNew {
-    #[structopt(long = "url")]
+    #[arg(long = "url")]
     /// Publicly-visible HTTPS URL of a git repository to be associated with the new Id
     pub url: Option<String>,
-    #[structopt(long = "github-username")]
+    #[arg(long = "github-username")]
     /// Github username (instead of --url)
     pub github_username: Option<String>,
-    #[structopt(long = "https-push")]
+    #[arg(long = "https-push")]
     /// Use public HTTP URL for both pulling and pushing. Otherwise SSH is used for push
     pub use_https_push: bool,
 }
 
-#[derive(Debug, StructOpt, Clone)]
+#[derive(Debug, Args, Clone)]
 pub struct IdSwitch {
     /// Id to switch to
     pub id: String,
 }
 
+#[derive(Debug, Subcommand, Clone)]
+pub enum IdSync {
+    /// Print your Id encrypted with age, to import on another machine
+    #[command(name = "export")]
+    Export(IdSyncExport),
+
+    /// Import an Id printed by `id sync export`, from stdin
+    #[command(name = "import")]
+    Import(IdSyncImport),
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct IdSyncExport {
+    /// age public key (`age1...`) of the receiving machine; can be repeated
+    #[arg(long = "age", value_name = "RECIPIENT", required = true)]
+    pub recipients: Vec<String>,
+
+    /// Id to export [default: current Id]
+    pub id: Option<String>,
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct IdSyncImport {
+    /// age identity file with the key to decrypt with (like `age -i`)
+    #[arg(long = "identity", short = 'i')]
+    pub identity: PathBuf,
+
+    /// The Id that was exported; anything else is rejected
+    #[arg(long = "expect", value_name = "ID")]
+    pub expect: String,
+}
+
+#[derive(Debug, Subcommand, Clone)]
+pub enum IdKeyring {
+    /// Save the passphrase of the current Id in the OS keyring
+    #[command(name = "store")]
+    Store,
+
+    /// Remove the passphrase of the current Id from the OS keyring
+    #[command(name = "forget")]
+    Forget,
+}
+
 /// Parameters describing trust graph traversal
-#[derive(Debug, StructOpt, Clone, Default)]
+#[derive(Debug, Args, Clone, Default)]
 pub struct TrustDistanceParams {
     /// [trust-graph-traversal] Consider only direct trust relationships
-    #[structopt(long = "direct")]
+    #[arg(long = "direct")]
     pub direct: bool,
 
-    #[structopt(long = "depth", default_value = "20")]
+    #[arg(long = "depth", default_value = "20")]
     /// [trust-graph-traversal] Maximum allowed distance from the root identity when traversing trust graph
     pub depth: u64,
 
     /// [trust-graph-traversal] Cost of traversing trust graph edge of high trust level
-    #[structopt(long = "high-cost", default_value = "0")]
+    #[arg(long = "high-cost", default_value = "0")]
     pub high_cost: u64,
     /// [trust-graph-traversal] Cost of traversing trust graph edge of medium trust level
-    #[structopt(long = "medium-cost", default_value = "1")]
+    #[arg(long = "medium-cost", default_value = "1")]
     pub medium_cost: u64,
     /// [trust-graph-traversal] Cost of traversing trust graph edge of low trust level
-    #[structopt(long = "low-cost", default_value = "5")]
+    #[arg(long = "low-cost", default_value = "5")]
     pub low_cost: u64,
     /// [trust-graph-traversal] Cost of traversing trust graph edge of none trust level
-    #[structopt(long = "none-cost", default_value = "21")]
+    #[arg(long = "none-cost", default_value = "21")]
     pub none_cost: u64,
     /// [trust-graph-traversal] Cost of traversing trust graph edge of distrust trust level
-    #[structopt(long = "distrust-cost", default_value = "21")]
+    #[arg(long = "distrust-cost", default_value = "21")]
     pub distrust_cost: u64,
+    /// [trust-graph-traversal] Ignore distrust reported by Ids with a lower effective trust level
+    #[arg(long = "distrust-min-trust", default_value = "none")]
+    pub distrust_min_trust: crev_data::TrustLevel,
+    /// [trust-graph-traversal] Number of Ids that must report distrust for it to take effect
+    #[arg(long = "distrust-quorum", default_value = "1")]
+    pub distrust_quorum: usize,
 }
 
 impl From<TrustDistanceParams> for crev_lib::TrustDistanceParams {
@@ -189,6 +244,10 @@ impl From<TrustDistanceParams> for crev_lib::TrustDistanceParams {
                 low_trust_distance: 1,
                 none_trust_distance: 1000,
                 distrust_distance: 1000,
+                distrust_policy: crev_lib::DistrustPolicy {
+                    min_reporter_trust: params.distrust_min_trust,
+                    quorum: params.distrust_quorum,
+                },
             }
         } else {
             crev_lib::TrustDistanceParams {
@@ -198,60 +257,73 @@ impl From<TrustDistanceParams> for crev_lib::TrustDistanceParams {
                 low_trust_distance: params.low_cost,
                 none_trust_distance: params.none_cost,
                 distrust_distance: params.distrust_cost,
+                distrust_policy: crev_lib::DistrustPolicy {
+                    min_reporter_trust: params.distrust_min_trust,
+                    quorum: params.distrust_quorum,
+                },
             }
         }
     }
 }
 
-#[derive(Debug, StructOpt, Clone)]
+#[derive(Debug, Args, Clone)]
 pub struct Diff {
     /// Source version - defaults to the last reviewed one
-    #[structopt(long = "src")]
+    #[arg(long = "src")]
     pub src: Option<Version>,
 
     /// Destination version - defaults to the current one
-    #[structopt(long = "dst")]
+    #[arg(long = "dst")]
     pub dst: Option<Version>,
 
     /// This crate is not necessarily a dependency of the current cargo project
-    #[structopt(long = "unrelated", short = "u")]
+    #[arg(long = "unrelated", short = 'u')]
     pub unrelated: bool,
 
-    #[structopt(flatten)]
+    /// Show the diff that a differential review by this Id was made against
+    #[arg(long = "reviewed-by", conflicts_with = "src")]
+    pub reviewed_by: Option<String>,
+
+    /// Print the changed files, with their digests and line counts, as JSON
+    /// instead of running `diff`
+    #[arg(long = "json", conflicts_with = "args")]
+    pub json: bool,
+
+    #[command(flatten)]
     pub requirements: VerificationRequirements,
 
-    #[structopt(flatten)]
-    pub trust_params: TrustDistanceParams,
+    #[command(flatten)]
+    pub wot: WotOpts,
 
     /// Crate name
     pub name: String,
 
     /// Arguments to the `diff` command
-    #[structopt(parse(from_os_str))]
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
     pub args: Vec<OsString>,
 }
 
-#[derive(Debug, StructOpt, Clone, Default)]
+#[derive(Debug, Args, Clone, Default)]
 pub struct TrustLevelRequirements {
     /// Minimum trust level required
-    #[structopt(long = "trust", default_value = "low")]
+    #[arg(long = "trust", default_value = "low")]
     pub trust_level: crev_data::Level,
 }
 
 /// Verification Requirements
-#[derive(Debug, StructOpt, Clone, Default)]
+#[derive(Debug, Args, Clone, Default)]
 pub struct VerificationRequirements {
-    #[structopt(flatten)]
+    #[command(flatten)]
     pub trust_level: TrustLevelRequirements,
 
     /// Number of reviews required
-    #[structopt(long = "redundancy", default_value = "1")]
+    #[arg(long = "redundancy", default_value = "1")]
     pub redundancy: u64,
     /// Required understanding
-    #[structopt(long = "understanding", default_value = "none")]
+    #[arg(long = "understanding", default_value = "none")]
     pub understanding_level: Level,
     /// Required thoroughness
-    #[structopt(long = "thoroughness", default_value = "none")]
+    #[arg(long = "thoroughness", default_value = "none")]
     pub thoroughness_level: Level,
 }
 
@@ -266,74 +338,170 @@ impl From<VerificationRequirements> for crev_lib::VerificationRequirements {
     }
 }
 
-#[derive(Debug, StructOpt, Clone, Default)]
+/// Requirements for some kind of dependencies, eg. `thoroughness=low,redundancy=2`
+///
+/// Settings that aren't given are the ones of `VerificationRequirements`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RequirementsOverride {
+    pub trust_level: Option<Level>,
+    pub understanding: Option<Level>,
+    pub thoroughness: Option<Level>,
+    pub redundancy: Option<u64>,
+}
+
+impl RequirementsOverride {
+    pub fn apply(
+        &self,
+        global: &crev_lib::VerificationRequirements,
+    ) -> crev_lib::VerificationRequirements {
+        crev_lib::VerificationRequirements {
+            trust_level: self.trust_level.unwrap_or(global.trust_level),
+            understanding: self.understanding.unwrap_or(global.understanding),
+            thoroughness: self.thoroughness.unwrap_or(global.thoroughness),
+            redundancy: self.redundancy.unwrap_or(global.redundancy),
+        }
+    }
+}
+
+impl std::str::FromStr for RequirementsOverride {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut requirements = Self::default();
+        for setting in s.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            let Some((name, value)) = setting.split_once('=') else {
+                bail!("Must be `name=value`, not `{}`", setting);
+            };
+            let level = || {
+                value
+                    .trim()
+                    .parse::<Level>()
+                    .map_err(|_| format_err!("Must be none, low, medium or high, not `{}`", value))
+            };
+            match name.trim() {
+                "trust" => requirements.trust_level = Some(level()?),
+                "understanding" => requirements.understanding = Some(level()?),
+                "thoroughness" => requirements.thoroughness = Some(level()?),
+                "redundancy" => requirements.redundancy = Some(value.trim().parse()?),
+                _ => bail!(
+                    "Must be one of trust, understanding, thoroughness, redundancy, not `{}`",
+                    name
+                ),
+            }
+        }
+        Ok(requirements)
+    }
+}
+
+#[derive(Debug, Args, Clone, Default)]
 pub struct Update {
-    #[structopt(flatten)]
+    #[command(flatten)]
     pub cargo_opts: CargoOpts,
 }
 
-#[derive(Debug, StructOpt, Clone, Default)]
+#[derive(Debug, Args, Clone, Default)]
 pub struct WotOpts {
-    #[structopt(flatten)]
+    #[command(flatten)]
     pub trust_params: TrustDistanceParams,
 
-    #[structopt(long = "for-id")]
+    #[arg(long = "for-id")]
     /// Root identity to calculate the Web of Trust for [default: current user id]
     pub for_id: Option<String>,
 }
 
-#[derive(Debug, StructOpt, Clone, Default)]
+#[derive(Debug, Subcommand, Clone)]
+pub enum Export {
+    /// Export your review of a crate as a signed in-toto attestation
+    ///
+    /// Prints a DSSE envelope with an in-toto statement: the subject is the crate
+    /// digest, the predicate is your review. Signed by your current Id.
+    #[command(name = "attestation")]
+    Attestation {
+        #[command(flatten)]
+        crate_: CrateSelector,
+    },
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct VerifyDigest {
+    /// Digest of the crate content (base64); read from stdin if not given
+    ///
+    /// See `cargo_specific` in the user documentation for how it is computed.
+    pub digest: Option<String>,
+
+    #[command(flatten)]
+    pub requirements: VerificationRequirements,
+
+    #[command(flatten)]
+    pub wot: WotOpts,
+}
+
+#[derive(Debug, Args, Clone, Default)]
+pub struct DigestIgnoreOpts {
+    #[arg(long = "digest-ignore")]
+    /// Leave this path out of crate digests; `*suffix` matches file names at any depth
+    pub digest_ignore: Vec<String>,
+
+    #[arg(long = "no-default-digest-ignore")]
+    /// Don't leave VCS data, `target` and editor files (or `digest-ignore` from the config) out of crate digests
+    pub no_default_digest_ignore: bool,
+}
+
+#[derive(Debug, Args, Clone, Default)]
 pub struct CrateVerifyCommon {
-    #[structopt(flatten)]
+    #[command(flatten)]
     pub requirements: VerificationRequirements,
 
-    #[structopt(flatten)]
+    #[command(flatten)]
     pub cargo_opts: CargoOpts,
+
+    #[command(flatten)]
+    pub digest_ignore: DigestIgnoreOpts,
 }
 
-#[derive(Debug, StructOpt, Clone, Copy, Default)]
+#[derive(Debug, Args, Clone, Copy, Default)]
 pub struct CrateVerifyColumns {
-    #[structopt(long = "show-digest")]
+    #[arg(long = "show-digest")]
     /// Show crate content digest
     pub show_digest: Option<Option<bool>>,
 
-    #[structopt(long = "show-leftpad-index")]
+    #[arg(long = "show-leftpad-index")]
     /// Show crate leftpad index (recent downloads / loc)
     pub show_leftpad_index: Option<Option<bool>>,
 
-    #[structopt(long = "show-downloads")]
+    #[arg(long = "show-downloads")]
     /// Show crate download counts
     pub show_downloads: Option<Option<bool>>,
 
-    #[structopt(long = "show-owners")]
+    #[arg(long = "show-owners")]
     /// Show crate owners counts
     pub show_owners: Option<Option<bool>>,
 
-    #[structopt(long = "show-latest-trusted")]
+    #[arg(long = "show-latest-trusted")]
     /// Show latest trusted version
     pub show_latest_trusted: Option<Option<bool>>,
 
-    #[structopt(long = "show-reviews")]
+    #[arg(long = "show-reviews")]
     /// Show reviews count
     pub show_reviews: Option<Option<bool>>,
 
-    #[structopt(long = "show-loc")]
+    #[arg(long = "show-loc")]
     /// Show Lines of Code
     pub show_loc: Option<Option<bool>>,
 
-    #[structopt(long = "show-issues")]
+    #[arg(long = "show-issues")]
     /// Show count of issues reported
     pub show_issues: Option<Option<bool>>,
 
-    #[structopt(long = "show-geiger")]
+    #[arg(long = "show-geiger")]
     /// Show geiger (unsafe lines) count
     pub show_geiger: Option<Option<bool>>,
 
-    #[structopt(long = "show-flags")]
+    #[arg(long = "show-flags")]
     /// Show crate flags
     pub show_flags: Option<Option<bool>>,
 
-    #[structopt(long = "show-all")]
+    #[arg(long = "show-all")]
     /// Show all
     pub show_all: bool,
 }
@@ -378,8 +546,8 @@ impl CrateVerifyColumns {
     show_x!(show_geiger, false);
 }
 
-#[derive(Debug, StructOpt, Clone, Default)]
-#[structopt(
+#[derive(Debug, Args, Clone, Default)]
+#[command(
     after_help = r#"Recursive mode will calculate most metrics for the crate together with all its transitive dependencies.
 
 Column description:
@@ -398,6 +566,7 @@ Column description:
 - geiger     - Geiger score: number of `unsafe` lines
 - flgs       - Flags for specific types of packages
   - CB         - Custom Build (runs arbitrary code at build time)
+  - PM         - Proc Macro (runs arbitrary code at compile time)
   - UM         - Unmaintained crate
 - name       - Crate name
 - version    - Crate version
@@ -405,279 +574,635 @@ Column description:
 "#
 )]
 pub struct CrateVerify {
-    #[structopt(flatten)]
+    #[command(flatten)]
     pub common: CrateVerifyCommon,
 
-    #[structopt(flatten)]
+    #[command(flatten)]
     pub wot: WotOpts,
 
-    #[structopt(flatten)]
+    #[command(flatten)]
     pub columns: CrateVerifyColumns,
 
-    #[structopt(long = "interactive", short = "i")]
+    #[arg(long = "interactive", short = 'i')]
     #[allow(unused)]
     /// No-op
     pub interactive: bool,
 
-    #[structopt(long = "skip-verified")]
+    #[arg(long = "skip-verified")]
     /// Display only crates not passing the verification
     pub skip_verified: bool,
 
-    #[structopt(long = "skip-known-owners")]
+    #[arg(long = "skip-known-owners")]
     /// Skip crate from known owners (use `edit known` to edit the list)
     pub skip_known_owners: bool,
 
-    #[structopt(long = "skip-indirect")]
+    #[arg(long = "skip-indirect")]
     /// Skip dependencies that are not direct
     pub skip_indirect: bool,
 
-    #[structopt(long = "recursive")]
-    /// Calculate recursive metrics for your packages
+    #[arg(long = "recursive")]
+    /// Calculate recursive metrics for your packages, and with `--workspace-root`
+    /// look for cargo projects at any depth, not only directly in DIR
     pub recursive: bool,
+
+    #[arg(long = "verbose")]
+    /// Under crates with negative reviews, show the reviewers' comments and reported issues
+    pub verbose: bool,
+
+    #[arg(long = "workspace-root", value_name = "DIR")]
+    /// Verify every cargo project (directory with a `Cargo.lock`) in DIR and its subdirectories
+    pub workspace_root: Option<PathBuf>,
+
+    #[arg(long = "policy", value_name = "FILE")]
+    /// Project review policy, requiring reviews of some crates by specific Ids
+    /// [default: `.crev-policy.yaml` or `crev-policy.yaml` next to the root `Cargo.toml`, if present]
+    pub policy: Option<PathBuf>,
+
+    #[arg(long = "on-negative", value_name = "fail|warn", default_value = "fail")]
+    /// Exit with code 3 if any crate has negative reviews, or only warn
+    pub on_negative: VerifyFailMode,
+
+    #[arg(
+        long = "on-unreviewed",
+        value_name = "fail|warn",
+        default_value = "fail"
+    )]
+    /// Exit with code 255 if any crate lacks enough positive reviews, or only warn
+    pub on_unreviewed: VerifyFailMode,
+
+    #[arg(long = "pin-inputs", value_name = "FILE")]
+    /// Record the proof repo commits (your own committed proofs included), the
+    /// `--for-id` Id and the crates.io data used in FILE, or if it already
+    /// exists, use exactly the ones recorded in it, on any machine
+    pub pin_inputs: Option<PathBuf>,
+
+    #[arg(long = "deny-duplicate-versions")]
+    /// Fail if any crate is used in more than one version (or from more than one source)
+    pub deny_duplicate_versions: bool,
+
+    #[arg(long = "deny-issues", value_name = "SEVERITY")]
+    /// Fail if any crate has open issues, reported by trusted Ids, of at least
+    /// this severity [none low medium high]
+    pub deny_issues: Option<Level>,
+
+    #[arg(long = "require-reviewed-build-code")]
+    /// Fail if any crate with a build script or a proc macro, which run code
+    /// while building, isn't verified
+    pub require_reviewed_build_code: bool,
+
+    #[arg(long = "cached", conflicts_with_all = ["workspace_root", "pin_inputs"])]
+    /// Print the previous result if the lockfile, trust, reviews and requirements
+    /// didn't change since
+    pub cached: bool,
+
+    #[arg(
+        long = "trust-snapshot",
+        value_name = "FILE",
+        conflicts_with = "cached"
+    )]
+    /// Trust the Ids in a signed trust snapshot (`wot snapshot sign`)
+    /// instead of your web of trust. It must be signed by your own Id,
+    /// or by an Id you trust at least at `--trust`
+    pub trust_snapshot: Option<PathBuf>,
+
+    #[arg(
+        long = "baseline",
+        value_name = "CARGO_LOCK",
+        conflicts_with_all = ["workspace_root", "cached"]
+    )]
+    /// Verify only crates added or changed in version since this (older) `Cargo.lock`,
+    /// requiring at least `--trust medium --thoroughness low --understanding low`
+    pub baseline: Option<PathBuf>,
+
+    #[arg(
+        long = "fresh",
+        conflicts_with_all = ["workspace_root", "cached", "pin_inputs", "trust_snapshot"]
+    )]
+    /// Fetch updated proofs of trusted Ids while the crates are downloaded and hashed,
+    /// like `repo fetch trusted` followed by `verify`, but faster
+    pub fresh: bool,
+
+    #[arg(long = "deep", conflicts_with = "cached")]
+    /// Verify build dependencies too (and dev dependencies with `--dev-dependencies`),
+    /// each with the requirements for every way it's used, and count them separately
+    pub deep: bool,
+
+    #[arg(
+        long = "requirements-build-deps",
+        value_name = "REQS",
+        requires = "deep"
+    )]
+    /// Requirements for crates used by build scripts, eg. `thoroughness=low,redundancy=2`
+    /// [settings: trust understanding thoroughness redundancy]
+    pub requirements_build_deps: Option<RequirementsOverride>,
+
+    #[arg(long = "requirements-dev-deps", value_name = "REQS", requires = "deep")]
+    /// Requirements for crates used only by tests, examples and benchmarks, like
+    /// `--requirements-build-deps`
+    pub requirements_dev_deps: Option<RequirementsOverride>,
+}
+
+/// What `verify` does about crates that didn't pass, for one kind of reason
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VerifyFailMode {
+    #[default]
+    Fail,
+    Warn,
+}
+
+impl std::str::FromStr for VerifyFailMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "fail" => Self::Fail,
+            "warn" => Self::Warn,
+            _ => bail!("Must be `fail` or `warn`, not `{}`", s),
+        })
+    }
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct IdRecommend {
+    #[command(flatten)]
+    pub crate_: CrateSelector,
+
+    #[command(flatten)]
+    pub common: CrateVerifyCommon,
+
+    #[command(flatten)]
+    pub wot: WotOpts,
+
+    #[arg(long = "limit", default_value = "10")]
+    /// How many Ids to list
+    pub limit: usize,
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct IdInspect {
+    /// Public Id to inspect
+    pub public_id: String,
+
+    #[command(flatten)]
+    pub wot: WotOpts,
+
+    #[command(flatten)]
+    pub cargo_opts: CargoOpts,
+
+    #[arg(long = "limit", default_value = "20")]
+    /// How many of the most recent reviews to show
+    pub limit: usize,
+
+    #[arg(long = "no-pager")]
+    /// Print to stdout even if it's a terminal
+    pub no_pager: bool,
 }
 
-#[derive(Debug, StructOpt, Clone)]
+#[derive(Debug, Args, Clone)]
 pub struct IdTrust {
-    #[structopt(long = "overrides")]
+    #[arg(long = "overrides")]
     /// Enable overrides suggestions
     pub overrides: bool,
 
     /// Public IDs to create Trust Proof for
     pub public_ids: Vec<String>,
 
+    /// Groups to trust the current members of, as `NAME@ID` (see `id group publish`)
+    #[arg(long = "group", value_name = "NAME@ID")]
+    pub groups: Vec<String>,
+
     /// Shortcut for setting trust level without editing
-    #[structopt(long = "level")]
+    #[arg(long = "level")]
     pub level: Option<crev_data::TrustLevel>,
 
-    #[structopt(flatten)]
+    #[command(flatten)]
     pub common_proof_create: CommonProofCreate,
 }
 
-#[derive(Debug, StructOpt, Clone)]
+#[derive(Debug, Args, Clone)]
 pub struct TrustUrls {
-    #[structopt(long = "overrides")]
+    #[arg(long = "overrides")]
     /// Enable overrides suggestions
     pub overrides: bool,
 
-    /// Public IDs or proof repo URLs to create Trust Proof for
+    /// Public IDs, proof repo URLs, or `https://<domain>` of organizations
+    /// publishing their Ids at `/.well-known/crev.json`, to create Trust Proof for
     pub public_ids_or_urls: Vec<String>,
 
     /// Shortcut for setting trust level without editing.
     /// Possible values are: "none" or "untrust", "low", "medium", "high" and "distrust".
-    #[structopt(long = "level")]
+    #[arg(long = "level")]
     pub level: Option<crev_data::TrustLevel>,
 
-    #[structopt(flatten)]
+    #[command(flatten)]
     pub common_proof_create: CommonProofCreate,
 }
 
-#[derive(Debug, StructOpt, Clone)]
+#[derive(Debug, Args, Clone)]
 pub struct RepoFetchUrl {
     /// URL to public proof repository
     pub url: String,
 }
 
-#[derive(Debug, StructOpt, Clone)]
+#[derive(Debug, Args, Clone)]
+pub struct RepoFetchAll {
+    /// Only retry repositories that failed during the previous run
+    #[arg(long = "retry-failed")]
+    pub retry_failed: bool,
+}
+
+#[derive(Debug, Subcommand, Clone)]
 pub enum RepoFetch {
     /// Fetch updates from trusted Ids
-    #[structopt(name = "trusted")]
+    ///
+    /// New repos are cloned shallow, and repos whose branches didn't change
+    /// since the last fetch are skipped.
+    #[command(name = "trusted")]
     Trusted {
-        #[structopt(flatten)]
+        #[command(flatten)]
         distance_params: TrustDistanceParams,
 
-        #[structopt(long = "for-id")]
+        #[arg(long = "for-id")]
         for_id: Option<String>,
+
+        /// Don't fetch repos fetched less than that many hours ago
+        #[arg(long = "max-age", value_name = "HOURS")]
+        max_age: Option<u64>,
     },
 
-    #[structopt(name = "url")]
+    #[command(name = "url")]
     /// Fetch from a single public proof repository
     Url(RepoFetchUrl),
 
-    #[structopt(name = "all")]
+    #[command(name = "all")]
     /// Fetch all previously retrieved public proof repositories
-    All,
+    ///
+    /// Progress is saved, so an interrupted run continues where it stopped.
+    All(RepoFetchAll),
+
+    #[command(name = "archives")]
+    /// Fetch archived proofs of truncated proof repositories (see `repo epoch`)
+    Archives,
 }
 
-#[derive(Debug, StructOpt, Clone)]
+#[derive(Debug, Args, Clone)]
+pub struct RepoEpoch {
+    /// URL of the git repository keeping your current proofs
+    #[arg(long = "archive-url")]
+    pub archive_url: String,
+
+    #[command(flatten)]
+    pub common_proof_create: CommonProofCreate,
+}
+
+#[derive(Debug, Subcommand, Clone)]
 pub enum IdQuery {
     /// Show current Id
-    #[structopt(name = "current")]
+    #[command(name = "current")]
     Current {
-        #[structopt(flatten)]
+        #[command(flatten)]
         trust_params: TrustDistanceParams,
     },
 
     /// Show all known Ids
-    #[structopt(name = "all")]
+    #[command(name = "all")]
     All {
-        #[structopt(flatten)]
+        #[command(flatten)]
         trust_params: TrustDistanceParams,
 
-        #[structopt(long = "for-id")]
+        #[arg(long = "for-id")]
         for_id: Option<String>,
     },
 
     /// Show own Ids
-    #[structopt(name = "own")]
+    #[command(name = "own")]
     Own {
-        #[structopt(flatten)]
+        #[command(flatten)]
         trust_params: TrustDistanceParams,
     },
 
     /// List trusted ids
-    #[structopt(name = "trusted")]
+    #[command(name = "trusted")]
     Trusted {
-        #[structopt(flatten)]
+        #[command(flatten)]
         trust_params: TrustDistanceParams,
 
-        #[structopt(long = "for-id")]
+        #[arg(long = "for-id")]
         for_id: Option<String>,
 
-        #[structopt(flatten)]
+        #[command(flatten)]
         trust_level: TrustLevelRequirements,
     },
+
+    /// Show proofs that mention an Id: who trusts it, and who overrides its proofs
+    #[command(name = "mentions")]
+    Mentions {
+        /// Id to look up [default: current user id]
+        #[arg(long = "for-id")]
+        for_id: Option<String>,
+    },
 }
 
-#[derive(Debug, StructOpt, Clone)]
+#[derive(Debug, Args, Clone)]
 pub struct RepoQueryReview {
-    #[structopt(flatten)]
+    #[command(flatten)]
     pub crate_: CrateSelector,
+
+    #[command(flatten)]
+    pub wot: WotOpts,
+
+    /// Print as JSON, each review with its `scope`: digest type, revision and
+    /// whether it's a full or a diff review
+    #[arg(long = "json")]
+    pub json: bool,
 }
 
-#[derive(Debug, StructOpt, Clone)]
+#[derive(Debug, Args, Clone)]
 pub struct RepoQueryAdvisory {
-    #[structopt(flatten)]
+    #[command(flatten)]
     pub crate_: CrateSelector,
+
+    #[command(flatten)]
+    pub wot: WotOpts,
 }
 
-#[derive(Debug, StructOpt, Clone)]
+#[derive(Debug, Args, Clone)]
 pub struct RepoQueryIssue {
-    #[structopt(flatten)]
+    #[command(flatten)]
     pub crate_: CrateSelector,
 
-    #[structopt(flatten)]
-    pub trust_params: TrustDistanceParams,
+    #[command(flatten)]
+    pub wot: WotOpts,
 
     /// Minimum trust level of the reviewers for reviews
-    #[structopt(long = "trust", default_value = "none")]
+    #[arg(long = "trust", default_value = "none")]
     pub trust_level: crev_data::Level,
 }
 
-#[derive(Debug, StructOpt, Clone)]
+#[derive(Debug, Args, Clone)]
+#[command(group(clap::ArgGroup::new("action").required(true)))]
+pub struct UpdateIssue {
+    #[command(flatten)]
+    pub crate_: CrateSelector,
+
+    /// Id of the reported issue
+    #[arg(long = "id")]
+    pub issue: String,
+
+    /// The issue is fixed or was reported by mistake
+    #[arg(long = "close", group = "action")]
+    pub close: bool,
+
+    /// The issue is the same as another one
+    #[arg(long = "duplicate-of", value_name = "ID", group = "action")]
+    pub duplicate_of: Option<String>,
+
+    /// Corrected severity of the issue [none low medium high]
+    #[arg(long = "severity", group = "action")]
+    pub severity: Option<Level>,
+
+    /// crev Id that reported the issue, if more than one did
+    #[arg(long = "reporter")]
+    pub reporter: Option<String>,
+
+    /// Comment for human readers
+    #[arg(long = "comment", default_value = "")]
+    pub comment: String,
+
+    #[command(flatten)]
+    pub common_proof_create: CommonProofCreate,
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct CrateExempt {
+    #[command(flatten)]
+    pub crate_: CrateSelector,
+
+    /// Last day the waiver applies (`YYYY-MM-DD`)
+    #[arg(long = "expires", value_name = "DATE", value_parser = crate::waiver::parse_expiry_date)]
+    pub expires: chrono::NaiveDate,
+
+    /// Why the crate can't wait for reviews
+    #[arg(long = "reason")]
+    pub reason: String,
+
+    /// Store the waiver in your proof repository, so people trusting you use it too
+    #[arg(long = "share")]
+    pub share: bool,
+
+    #[command(flatten)]
+    pub common_proof_create: CommonProofCreate,
+}
+
+#[derive(Debug, Args, Clone)]
 pub struct CrateDir {
-    #[structopt(flatten)]
+    #[command(flatten)]
     pub common: ReviewCrateSelector,
 }
 
-#[derive(Debug, StructOpt, Clone)]
+#[derive(Debug, Subcommand, Clone)]
 pub enum RepoQuery {
     /// Query reviews
-    #[structopt(name = "review")]
+    ///
+    /// Reviews by anyone, or with `--for-id` only by Ids in its Web of Trust.
+    #[command(name = "review")]
     Review(RepoQueryReview),
 
     /// Query applicable advisories
-    #[structopt(name = "advisory")]
+    ///
+    /// Advisories by anyone, or with `--for-id` only by Ids in its Web of Trust.
+    #[command(name = "advisory")]
     Advisory(RepoQueryAdvisory),
 
     /// Query applicable issues
-    #[structopt(name = "issue")]
+    #[command(name = "issue")]
     Issue(RepoQueryIssue),
 }
 
-#[derive(Debug, StructOpt, Clone)]
+#[derive(Debug, Subcommand, Clone)]
 pub enum RepoEdit {
     /// Edit your README.md file
-    #[structopt(name = "readme")]
+    #[command(name = "readme")]
     Readme,
 
     /// Edit your KNOWN_CRATE_OWNERS.md file
-    #[structopt(name = "known")]
+    #[command(name = "known")]
     Known,
 }
 
-#[derive(Debug, StructOpt, Clone)]
+#[derive(Debug, Args, Clone)]
 pub struct RepoGit {
     /// Arguments to the `git` command
-    #[structopt(parse(from_os_str))]
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
     pub args: Vec<OsString>,
 }
 
-#[derive(Debug, StructOpt, Clone)]
+#[derive(Debug, Args, Clone)]
 pub struct ReviewCrateSelector {
-    #[structopt(flatten)]
+    #[command(flatten)]
     pub crate_: CrateSelector,
 
     /// Review the delta since the given version
-    #[structopt(long = "diff", name = "base-version")]
+    #[arg(long = "diff", id = "base_version")]
     #[allow(clippy::option_option)]
     pub diff: Option<Option<Version>>,
 }
 
-#[derive(Debug, StructOpt, Clone)]
+#[derive(Debug, Args, Clone)]
 pub struct CrateOpen {
     /// Shell command to execute with crate directory as an argument. Eg. "code --wait -n" for VSCode
-    #[structopt(long = "cmd")]
+    #[arg(long = "cmd")]
     pub cmd: Option<String>,
 
     /// Save the `--cmd` argument to be used a default in the future
-    #[structopt(long = "cmd-save")]
+    #[arg(long = "save-cmd")]
     pub cmd_save: bool,
 
-    #[structopt(flatten)]
+    /// Open the docs.rs page of the crate in the browser instead
+    #[arg(long = "docs", conflicts_with_all = ["cmd", "cmd_save", "repo"])]
+    pub docs: bool,
+
+    /// Open the repository of the crate (from its `Cargo.toml`) in the browser instead
+    #[arg(long = "repo", conflicts_with_all = ["cmd", "cmd_save"])]
+    pub repo: bool,
+
+    #[command(flatten)]
     pub common: ReviewCrateSelector,
 }
 
-#[derive(Debug, StructOpt, Clone)]
+impl CrateOpen {
+    pub fn link(&self) -> Option<crate::shared::CrateLink> {
+        if self.docs {
+            Some(crate::shared::CrateLink::Docs)
+        } else if self.repo {
+            Some(crate::shared::CrateLink::Repository)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Args, Clone)]
 pub struct CommonProofCreate {
     /// Don't auto-commit local Proof Repository
-    #[structopt(long = "no-commit")]
+    #[arg(long = "no-commit")]
     pub no_commit: bool,
 
     /// Print unsigned proof content on stdout
-    #[structopt(long = "print-unsigned")]
+    #[arg(long = "print-unsigned")]
     pub print_unsigned: bool,
 
     /// Print signed proof content on stdout
-    #[structopt(long = "print-signed")]
+    #[arg(long = "print-signed")]
     pub print_signed: bool,
 
     /// Don't store the proof
-    #[structopt(long = "no-store")]
+    #[arg(long = "no-store")]
     pub no_store: bool,
+
+    /// Keep the proof aside instead of storing it, see `proof staged`
+    #[arg(long = "stage", conflicts_with = "no_store")]
+    pub stage: bool,
+
+    /// Format of the draft to edit: `yaml` or `toml` (the proof is always YAML)
+    #[arg(long = "draft-format", value_name = "FORMAT", default_value = "yaml")]
+    pub draft_format: crev_data::proof::DraftFormat,
+
+    /// Encrypt the proof, so only these Ids (and you) can read it
+    #[arg(long = "encrypt-to", value_name = "ID", num_args = 1..)]
+    pub encrypt_to: Vec<String>,
+
+    /// Keep the proof out of the proof repository until this date (`YYYY-MM-DD` or RFC 3339)
+    ///
+    /// For embargoed advisories. `cargo crev publish` stores the proof once the date
+    /// passes, see `queue list`.
+    #[arg(
+        long = "publish-after",
+        value_name = "DATE",
+        value_parser = crate::queue::parse_publish_after,
+        conflicts_with_all = ["no_store", "stage"]
+    )]
+    pub publish_after: Option<crev_lib::embargo::Date>,
 }
 
-#[derive(Debug, StructOpt, Clone)]
+#[derive(Debug, Args, Clone)]
 pub struct CrateReview {
-    #[structopt(flatten)]
+    #[command(flatten)]
     pub common: ReviewCrateSelector,
 
-    #[structopt(flatten)]
+    #[command(flatten)]
     pub common_proof_create: CommonProofCreate,
 
     /// Create advisory urging to upgrade to a safe version
-    #[structopt(long = "advisory")]
+    #[arg(long = "advisory")]
     pub advisory: bool,
 
     /// This release contains advisory (important fix)
-    #[structopt(long = "affected")]
+    #[arg(long = "affected")]
     pub affected: Option<crev_data::proof::review::package::VersionRange>,
 
     /// Severity of bug/security issue [none low medium high]
-    #[structopt(long = "severity")]
+    #[arg(long = "severity")]
     pub severity: Option<Level>,
 
     /// Flag the crate as buggy/low-quality/dangerous
-    #[structopt(long = "issue")]
+    #[arg(long = "issue")]
     pub issue: bool,
 
-    #[structopt(long = "skip-activity-check")]
+    #[arg(long = "skip-activity-check")]
     pub skip_activity_check: bool,
 
-    #[structopt(long = "overrides")]
+    #[arg(long = "overrides")]
     /// Enable overrides suggestions
     pub overrides: bool,
 
-    #[structopt(flatten)]
+    /// Open the crate for a time-boxed review session first (eg. `45m`, `1h30m`)
+    ///
+    /// Files that an editor hook reports (see `$CREV_REVIEW_SESSION_JOURNAL`) are
+    /// journaled, and the review is pre-filled with a thoroughness matching how much
+    /// of the code you've looked at.
+    #[arg(long = "session", value_name = "LENGTH", value_parser = crate::session::parse_session_length)]
+    pub session: Option<std::time::Duration>,
+
+    /// Review a patch (unified diff) you've read outside of crev, eg. an upstream PR
+    ///
+    /// The crate version is checked to contain the patch, and the patch digest and
+    /// the lines it changes are recorded in the proof. The review is of the patch
+    /// only: it doesn't count towards verifying the crate.
+    #[arg(long = "from-patch", value_name = "FILE", conflicts_with_all = ["base_version", "session"])]
+    pub from_patch: Option<PathBuf>,
+
+    /// Review only this directory of the crate, eg. `src`
+    ///
+    /// The proof records the directory and its digest, so the review can be checked
+    /// against copies of the crate that differ outside of it. The review is of the
+    /// directory only: it doesn't count towards verifying the whole crate.
+    #[arg(long = "subtree", value_name = "DIR", conflicts_with = "from_patch")]
+    pub subtree: Option<String>,
+
+    /// Leave this path (relative to `--subtree`) out of the review, eg. `tests/fixtures`
+    #[arg(long = "subtree-exclude", value_name = "PATH", requires = "subtree")]
+    pub subtree_exclude: Vec<String>,
+
+    /// Record that the review went through this part of the crate, eg. `src/parser`
+    ///
+    /// Can be given more than once. `crate info` sums up which parts of a crate
+    /// reviewers have covered.
+    #[arg(long = "covered", value_name = "PATH", conflicts_with = "from_patch")]
+    pub covered: Vec<String>,
+
+    /// Create the review even if the source doesn't match the registry checksum
+    #[arg(long = "force")]
+    pub force: bool,
+
+    /// Record a digest that's the same for the crate from the registry and its source in git
+    ///
+    /// Files `cargo package` adds are left out, and the original `Cargo.toml`
+    /// is digested instead of the normalized one.
+    #[arg(long = "registry-normalized")]
+    pub registry_normalized: bool,
+
+    #[command(flatten)]
     pub cargo_opts: CargoOpts,
 }
 
@@ -686,25 +1211,36 @@ pub struct AdviseCommon {
     /// This release contains advisory (important fix)
     pub affected: crev_data::proof::review::package::VersionRange,
     pub severity: Level,
+    /// Advisory ids to pre-fill, eg. `RUSTSEC-2019-0001`
+    pub ids: Vec<String>,
+    pub comment: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ReportCommon {
+    pub severity: Level,
+    /// Issue id to pre-fill
+    pub id: String,
+    pub comment: String,
 }
 
-#[derive(Debug, StructOpt, Clone)]
+#[derive(Debug, Args, Clone)]
 pub struct CrateSearch {
     /// Number of results
-    #[structopt(long = "count", default_value = "10")]
+    #[arg(long = "count", default_value = "10")]
     pub count: usize,
     /// Query to use
     pub query: String,
 }
 
-#[derive(Debug, StructOpt, Clone)]
+#[derive(Debug, Args, Clone)]
 pub struct IdExport {
     pub id: Option<String>,
 }
 
-#[derive(Debug, StructOpt, Clone)]
+#[derive(Debug, Args, Clone)]
 pub struct IdSetUrl {
-    #[structopt(long = "https-push")]
+    #[arg(long = "https-push")]
     /// Setup `https` instead of recommended `ssh`-based push url
     pub use_https_push: bool,
 
@@ -712,354 +1248,1100 @@ pub struct IdSetUrl {
     pub url: String,
 }
 
-#[derive(Debug, StructOpt, Clone)]
+#[derive(Debug, Args, Clone)]
+#[command(args_conflicts_with_subcommands = true)]
 pub struct RepoImport {
+    /// Convert proofs from another source, instead of reading them from stdin
+    #[command(subcommand)]
+    pub source: Option<RepoImportSource>,
+
     /// Reset proof date to current date
-    #[structopt(long = "reset-date")]
+    #[arg(long = "reset-date")]
     pub reset_date: bool,
 
-    #[structopt(flatten)]
+    #[command(flatten)]
     pub common: CommonProofCreate,
 }
 
-#[derive(Debug, StructOpt, Clone)]
+#[derive(Debug, Subcommand, Clone)]
+pub enum RepoImportSource {
+    /// Convert all the RustSec advisories into proofs
+    ///
+    /// The proofs are signed by a local Id generated for them, and count once
+    /// you trust it. Run again to pick up new advisories.
+    #[command(name = "rustsec")]
+    Rustsec {
+        /// Checked out advisory database to use, instead of fetching it
+        #[arg(long = "advisory-db")]
+        advisory_db: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Subcommand, Clone)]
 pub enum Id {
     /// Create a new Id
-    #[structopt(name = "new")]
+    #[command(name = "new")]
     New(IdNew),
 
     /// Export your own Id
-    #[structopt(name = "export")]
+    #[command(name = "export")]
     Export(IdExport),
 
     /// Import an Id as your own
-    #[structopt(name = "import")]
+    #[command(name = "import")]
     Import,
 
+    /// Move your own Id between your machines, encrypted with age
+    #[command(name = "sync")]
+    #[command(subcommand)]
+    Sync(IdSync),
+
     /// Show your current Id
-    #[structopt(name = "current")]
+    #[command(name = "current")]
     Current,
 
     /// Change current Id
-    #[structopt(name = "switch")]
+    #[command(name = "switch")]
     Switch(IdSwitch),
 
     /// Change passphrase
-    #[structopt(name = "passwd")]
+    #[command(name = "passwd")]
     Passwd,
 
+    /// Replace your current Id with a new one, keeping the trust others have in it
+    ///
+    /// Creates a new Id using the same proof repository, and successor proofs
+    /// signed by both Ids. Once published, trust in the old Id counts as trust in
+    /// the new one. Keep the old Id around: its proofs are still valid.
+    #[command(name = "rotate")]
+    Rotate,
+
+    /// Manage the passphrase in the OS keyring (needs the `keyring` feature)
+    #[command(name = "keyring")]
+    #[command(subcommand)]
+    Keyring(IdKeyring),
+
     /// Change public HTTPS repo URL for the current Id
-    #[structopt(name = "set-url")]
+    #[command(name = "set-url")]
     SetUrl(IdSetUrl),
 
     /// Trust an Id
-    #[structopt(name = "trust")]
+    #[command(name = "trust")]
     Trust(IdTrust),
 
     /// Untrust (remove) trust
-    #[structopt(name = "untrust")]
+    #[command(name = "untrust")]
     Untrust(IdTrust),
 
     /// Distrust an Id
-    #[structopt(name = "distrust")]
+    #[command(name = "distrust")]
     Distrust(IdTrust),
 
     /// Query Ids
-    #[structopt(name = "query")]
+    #[command(name = "query")]
+    #[command(subcommand)]
     Query(IdQuery),
+
+    /// Suggest Ids to trust next
+    ///
+    /// Ranks Ids you don't trust yet by how many of your unverified dependencies
+    /// they reviewed well enough to verify, if you trusted them.
+    #[command(name = "recommend")]
+    Recommend(IdRecommend),
+
+    /// Show what's known about an Id, before deciding how much to trust it
+    ///
+    /// Its recent reviews and advisories, its place in your Web of Trust,
+    /// and the crates you use that it reviewed. Shown in `$PAGER` on a terminal.
+    #[command(name = "inspect")]
+    Inspect(IdInspect),
+
+    /// Named groups of Ids, that others can trust or require reviews from
+    #[command(name = "group")]
+    #[command(subcommand)]
+    Group(IdGroup),
 }
 
-#[derive(Debug, StructOpt, Clone)]
+#[derive(Debug, Subcommand, Clone)]
+pub enum IdGroup {
+    /// Publish (or replace) a group of Ids under a name
+    #[command(name = "publish")]
+    Publish {
+        /// Name of the group: lowercase letters, digits and `-`
+        name: String,
+
+        /// Public IDs of the members
+        #[arg(required = true)]
+        public_ids: Vec<String>,
+
+        /// What the group is, and who is in it
+        #[arg(long = "comment", default_value = "")]
+        comment: String,
+
+        #[command(flatten)]
+        common_proof_create: CommonProofCreate,
+    },
+    /// Show the current members of a group
+    #[command(name = "show")]
+    Show {
+        /// `NAME@ID`, or only `NAME` for a group of the current Id
+        group: String,
+    },
+    /// List groups published by an Id
+    #[command(name = "list")]
+    List {
+        /// Id to list the groups of [default: current Id]
+        id: Option<String>,
+    },
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct PrePublishCheck {
+    #[command(flatten)]
+    pub common: CrateVerifyCommon,
+
+    #[command(flatten)]
+    pub wot: WotOpts,
+
+    #[arg(long = "policy", value_name = "FILE")]
+    /// Project review policy, as for `verify`
+    pub policy: Option<PathBuf>,
+
+    #[arg(long = "badge-output", value_name = "FILE")]
+    /// Write the Markdown snippet to FILE instead of stdout
+    pub badge_output: Option<PathBuf>,
+}
+
+#[derive(Debug, Args, Clone)]
 pub struct CrateVerifyFull {
-    #[structopt(flatten)]
+    #[command(flatten)]
     pub opts: CrateVerify,
 
-    #[structopt(flatten)]
+    #[command(flatten)]
     pub crate_: CrateSelector,
 }
 
-#[derive(Debug, StructOpt, Clone)]
+#[derive(Debug, Subcommand, Clone)]
 pub enum Crate {
     /// Start a shell in source directory of a crate under review
-    #[structopt(name = "goto")]
+    #[command(name = "goto")]
     Goto(CrateSelector),
 
     /// Open the source code of a crate
-    #[structopt(name = "open")]
+    #[command(name = "open")]
     Open(CrateOpen),
 
     /// WIP: Expand the crate source using `cargo-expand` like functionality
     // https://github.com/dtolnay/cargo-expand/issues/11
-    #[structopt(name = "expand")]
+    #[command(name = "expand")]
     Expand(ReviewCrateSelector),
 
     /// Clean the source code directory of a crate (eg. after review)
-    #[structopt(name = "clean")]
+    #[command(name = "clean")]
     Clean(CrateSelector),
 
     /// Diff between two versions of a package
-    #[structopt(name = "diff")]
-    #[structopt(setting = structopt::clap::AppSettings::TrailingVarArg)]
-    #[structopt(setting = structopt::clap::AppSettings::AllowLeadingHyphen)]
+    #[command(name = "diff")]
     Diff(Diff),
 
     /// Display the path of the source code directory of a crate
-    #[structopt(name = "dir")]
+    #[command(name = "dir")]
     Dir(CrateDir),
 
     /// Verify dependencies
-    #[structopt(name = "verify")]
+    #[command(name = "verify")]
     Verify(CrateVerifyFull),
 
     /// Most valuable players (reviewers)
-    #[structopt(name = "mvp")]
+    ///
+    /// For each trusted reviewer: dependencies they reviewed, verified with their
+    /// review, and verified only thanks to their review.
+    #[command(name = "mvp")]
     Mvp {
-        #[structopt(flatten)]
+        #[command(flatten)]
         opts: CrateVerifyCommon,
 
-        #[structopt(flatten)]
+        #[command(flatten)]
         wot: WotOpts,
 
-        #[structopt(flatten)]
+        #[command(flatten)]
         crate_: CrateSelector,
+
+        /// Also show how many verified dependencies would be lost by untrusting
+        /// each Id you trust directly (with the Ids trusted through them)
+        #[arg(long = "simulate-untrust")]
+        simulate_untrust: bool,
     },
 
     /// Review a crate (code review, security advisory, flag issues)
-    #[structopt(name = "review")]
+    #[command(name = "review")]
     Review(CrateReview),
 
     /// Unreview (overwrite with an null review)
-    #[structopt(name = "unreview")]
+    #[command(name = "unreview")]
     Unreview(CrateReview),
 
     /// Search crates on crates.io sorting by review count
-    #[structopt(name = "search")]
+    #[command(name = "search")]
     Search(CrateSearch),
 
+    /// Ask for reviews of a crate version, eg. of your own crate
+    ///
+    /// Publishes a signed review request; people trusting you will see it
+    /// in `queue requests`.
+    #[command(name = "request-review")]
+    RequestReview {
+        #[command(flatten)]
+        crate_: CrateSelector,
+
+        /// What reviewers should look at
+        #[arg(long = "comment", default_value = "")]
+        comment: String,
+
+        #[command(flatten)]
+        common_proof_create: CommonProofCreate,
+    },
+
+    /// Close a reported issue, mark it as a duplicate or correct its severity
+    ///
+    /// Publishes a signed issue update for one report of the issue. Only
+    /// updates of Ids you trust change what `verify` shows.
+    #[command(name = "update-issue")]
+    UpdateIssue(UpdateIssue),
+
+    /// Let a crate version pass `verify` without enough reviews, until a date
+    ///
+    /// Signs a waiver, kept on this machine unless `--share` is given. `verify`
+    /// shows crates passing only thanks to a waiver as `waiv`.
+    #[command(name = "exempt")]
+    Exempt(CrateExempt),
+
     /// Display rich info about the given crate
-    #[structopt(name = "info")]
+    #[command(name = "info")]
     Info {
-        #[structopt(flatten)]
+        #[command(flatten)]
+        opts: CrateVerifyCommon,
+
+        #[command(flatten)]
+        wot: WotOpts,
+
+        #[command(flatten)]
+        crate_: CrateSelector,
+    },
+
+    /// Explain why a crate is not verified, and what would change that
+    ///
+    /// Lists every review of the crate version with the reason it doesn't
+    /// count (untrusted reviewer, too shallow, different digest, overridden,
+    /// negative), and the single changes of trust or requirements that would
+    /// make it verified.
+    #[command(name = "why-unverified")]
+    WhyUnverified {
+        #[command(flatten)]
         opts: CrateVerifyCommon,
 
-        #[structopt(flatten)]
+        #[command(flatten)]
         wot: WotOpts,
 
-        #[structopt(flatten)]
+        #[command(flatten)]
         crate_: CrateSelector,
     },
+
+    /// Show all supply chain signals about a crate in one place
+    #[command(name = "facts")]
+    Facts {
+        #[command(flatten)]
+        opts: CrateVerifyCommon,
+
+        #[command(flatten)]
+        wot: WotOpts,
+
+        #[command(flatten)]
+        crate_: CrateSelector,
+
+        /// Print as JSON
+        #[arg(long = "json")]
+        json: bool,
+    },
 }
 
-#[derive(Debug, StructOpt, Clone)]
+#[derive(Debug, Subcommand, Clone)]
 pub enum Config {
     /// Edit the config file
-    #[structopt(name = "edit")]
+    #[command(name = "edit")]
     Edit,
 
     /// Completions
-    #[structopt(name = "completions")]
+    #[command(name = "completions")]
     Completions {
-        #[structopt(long = "shell")]
+        #[arg(long = "shell")]
         shell: Option<String>,
     },
 
     /// Print the dir containing config files
-    #[structopt(name = "dir")]
+    #[command(name = "dir")]
     Dir,
 
     /// Print the dir containing data files
-    #[structopt(name = "data-dir")]
+    #[command(name = "data-dir")]
     DataDir,
 
     /// Print the dir containing cache files
-    #[structopt(name = "cache-dir")]
+    #[command(name = "cache-dir")]
     CacheDir,
 }
 
-#[derive(Debug, StructOpt, Clone)]
+/// Kinds of proofs `proof find` searches
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProofFindKind {
+    Package,
+    Code,
+    Trust,
+}
+
+impl std::str::FromStr for ProofFindKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "package" => Self::Package,
+            "code" => Self::Code,
+            "trust" => Self::Trust,
+            _ => bail!("Must be `package`, `code` or `trust`, not `{}`", s),
+        })
+    }
+}
+
+#[derive(Debug, Args, Clone)]
 pub struct ProofFind {
-    #[structopt(name = "crate", long = "crate")]
+    /// Reviews of this crate (package and code reviews only)
+    #[arg(id = "crate", long = "crate")]
     pub crate_: Option<String>,
 
-    #[structopt(name = "vers", long = "vers")]
+    #[arg(id = "vers", long = "vers")]
     pub version: Option<Version>,
 
     /// Find a proof by a crev Id
-    #[structopt(name = "author", long = "author")]
+    #[arg(long = "author")]
     pub author: Option<String>,
+
+    /// Kind of proofs to find [default: all]
+    #[arg(long = "kind", value_name = "package|code|trust")]
+    pub kind: Vec<ProofFindKind>,
+
+    /// Reviews of packages from this source, eg. `https://crates.io` [default: all]
+    #[arg(long = "source", value_name = "URL")]
+    pub source: Option<String>,
+
+    /// Proofs with comments containing all of these words, in any case
+    #[arg(long = "comment", value_name = "WORDS")]
+    pub comment: Option<String>,
+
+    /// Show the proof repo and file each proof was loaded from
+    #[arg(long = "show-origin")]
+    pub show_origin: bool,
+
+    #[command(flatten)]
+    pub wot: WotOpts,
 }
 
-#[derive(Debug, StructOpt, Clone)]
+#[derive(Debug, Args, Clone)]
 pub struct ProofReissue {
-    #[structopt(name = "crate", long = "crate")]
+    #[arg(id = "crate", long = "crate")]
     pub crate_: Option<String>,
 
-    #[structopt(name = "vers", long = "vers")]
+    #[arg(id = "vers", long = "vers")]
     pub version: Option<Version>,
 
     /// Reissue all proofs by a crev Id. Mandatory.
-    #[structopt(name = "author", long = "author")]
+    #[arg(long = "author")]
     pub author: String,
 
     /// Comment for human readers. Mandatory.
-    #[structopt(name = "comment", long = "comment")]
+    #[arg(long = "comment")]
     pub comment: String,
 
     /// Skip check if we already reissued a review using the current id
-    #[structopt(long = "skip-reissue-check")]
+    #[arg(long = "skip-reissue-check")]
     pub skip_reissue_check: bool,
 
-    #[structopt(flatten)]
+    #[command(flatten)]
     pub common_proof_create: CommonProofCreate,
 }
 
-#[derive(Debug, StructOpt, Clone)]
+#[derive(Debug, Subcommand, Clone)]
 /// Local Proof Repository
 pub enum Repo {
     /// Publish to remote repository
-    #[structopt(name = "publish")]
+    #[command(name = "publish")]
     Publish,
 
     /// Update data from online sources (proof repositories, crates.io)
-    #[structopt(name = "update")]
+    #[command(name = "update")]
     Update(Update),
 
     /// Run raw git commands in the local proof repository
-    #[structopt(name = "git")]
-    #[structopt(setting = structopt::clap::AppSettings::TrailingVarArg)]
-    #[structopt(setting = structopt::clap::AppSettings::AllowLeadingHyphen)]
+    #[command(name = "git")]
     Git(RepoGit),
 
     /// Edit README.md of the current Id, ...
-    #[structopt(name = "edit")]
+    #[command(name = "edit")]
+    #[command(subcommand)]
     Edit(RepoEdit),
 
     /// Import proofs
-    #[structopt(name = "import")]
+    #[command(name = "import")]
     Import(RepoImport),
 
     /*
     /// Export proofs
-    #[structopt(name = "export")]
+    #[command(name = "export")]
     Export,
     */
     /// Query proofs
-    #[structopt(name = "query")]
+    #[command(name = "query")]
+    #[command(subcommand)]
     Query(RepoQuery),
 
     /// Fetch proofs from external sources
-    #[structopt(name = "fetch")]
+    #[command(name = "fetch")]
+    #[command(subcommand)]
     Fetch(RepoFetch),
 
+    /// Mark your current proofs as archived, before truncating the proof repository
+    ///
+    /// Creates an epoch proof saying that your proofs are kept in the archive repository.
+    /// Push the proof repository to it before removing old proofs from this one.
+    #[command(name = "epoch")]
+    Epoch(RepoEpoch),
+
     /// Print the dir containing local copy of the proof repository
-    #[structopt(name = "dir")]
+    #[command(name = "dir")]
     Dir,
+
+    /// Install a git `pre-push` hook that runs `proof validate --staged`
+    ///
+    /// So broken proofs can't be pushed by accident.
+    #[command(name = "install-hooks")]
+    InstallHooks {
+        /// Replace a `pre-push` hook that wasn't installed by cargo-crev
+        #[arg(long = "force")]
+        force: bool,
+    },
 }
 
-#[derive(Debug, StructOpt, Clone)]
+#[derive(Debug, Subcommand, Clone)]
 /// Local Proof Repository
 pub enum Proof {
     /// Find a proof
-    #[structopt(name = "find")]
+    ///
+    /// Proofs by anyone, or with `--for-id` only by Ids in its Web of Trust.
+    #[command(name = "find")]
     Find(ProofFind),
     /// Reissue proofs with current id
-    #[structopt(name = "reissue")]
+    #[command(name = "reissue")]
     Reissue(ProofReissue),
+    /// Proofs created with `--stage`
+    #[command(name = "staged")]
+    #[command(subcommand)]
+    Staged(ProofStaged),
+    /// Take back your latest proof
+    ///
+    /// If it isn't published yet, it's removed from your proof repository and
+    /// staged, so `proof staged commit` puts it back. A published proof can't be
+    /// removed from other people's copies: it's overridden by a new proof instead,
+    /// a review without rating, or trust of level none.
+    #[command(name = "retract")]
+    Retract(ProofRetract),
+    /// Check proof files of your proof repository: signatures, content and formatting
+    ///
+    /// Fails if any proof file is invalid. Checks files as committed, or added
+    /// to the git index.
+    #[command(name = "validate")]
+    Validate {
+        /// Only files changed in commits that aren't pushed yet, or in the git index
+        #[arg(long = "staged")]
+        staged: bool,
+    },
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct ProofRetract {
+    /// The latest proof of the current Id in its proof repository
+    #[arg(long = "last", required = true)]
+    pub last: bool,
+
+    #[command(flatten)]
+    pub common_proof_create: CommonProofCreate,
 }
 
-#[derive(Debug, StructOpt, Clone)]
+#[derive(Debug, Subcommand, Clone)]
+pub enum ProofStaged {
+    /// List staged proofs, oldest first
+    #[command(name = "list")]
+    List {
+        /// Print whole proofs
+        #[arg(long = "full")]
+        full: bool,
+    },
+    /// Discard staged proofs
+    #[command(name = "drop")]
+    Drop {
+        /// Names of the proofs, as listed (or their unique prefixes)
+        #[arg(required = true)]
+        names: Vec<String>,
+    },
+    /// Store all staged proofs in the local proof repository, in one commit
+    #[command(name = "commit")]
+    Commit {
+        /// Commit message
+        #[arg(long = "message", short = 'm', default_value = "Add staged proofs")]
+        message: String,
+    },
+}
+
+#[derive(Debug, Subcommand, Clone)]
 pub enum Wot {
     /// Create a new Id
-    #[structopt(name = "log")]
+    #[command(name = "log")]
     Log {
-        #[structopt(flatten)]
+        #[command(flatten)]
+        wot: WotOpts,
+    },
+
+    /// Suggest trust proofs you could retract
+    ///
+    /// Lists Ids you trust directly that are trusted just as much through others
+    /// (redundant), and Ids without any proofs in `--stale-years` (stale).
+    #[command(name = "suggest-prune")]
+    SuggestPrune {
+        #[command(flatten)]
+        wot: WotOpts,
+
+        /// Ids with no proofs in that many years are stale
+        #[arg(long = "stale-years", default_value = "2")]
+        stale_years: u32,
+    },
+
+    /// Signed snapshots of computed trust sets
+    #[command(name = "snapshot")]
+    #[command(subcommand)]
+    Snapshot(WotSnapshot),
+
+    /// Show how many dependencies are verified with other trust parameters
+    ///
+    /// Varies `--depth` and each `--*-cost` in turn, keeping the others as given,
+    /// and prints the number of trusted Ids and verified crates for every value.
+    /// Uses the global requirements, not the project policy.
+    #[command(name = "tune")]
+    Tune {
+        #[command(flatten)]
+        common: CrateVerifyCommon,
+
+        #[command(flatten)]
+        wot: WotOpts,
+    },
+
+    /// Show how well your web of trust covers dependencies, and where it's weak
+    ///
+    /// Prints the average distance to trusted reviewers of dependencies,
+    /// Ids whose removal would leave the most dependencies unverified
+    /// (single points of failure), and trust for Ids without any proofs.
+    #[command(name = "health")]
+    Health {
+        #[command(flatten)]
+        common: CrateVerifyCommon,
+
+        #[command(flatten)]
+        wot: WotOpts,
+    },
+
+    /// Print the trust graph reachable from your Id
+    ///
+    /// Nodes are Ids, with their effective trust and proof repo URL. Edges are
+    /// trust proofs, with their level and date. Distrust is shown, but not followed.
+    #[command(name = "export")]
+    Export {
+        #[command(flatten)]
+        wot: WotOpts,
+
+        /// Output format [dot json]
+        #[arg(long = "format", default_value = "dot")]
+        format: crate::wot_export::WotExportFormat,
+    },
+}
+
+#[derive(Debug, Subcommand, Clone)]
+pub enum WotSnapshot {
+    /// Print your computed trust set as a signed document
+    ///
+    /// Others can compare it with their trust set, or verify with it
+    /// to reproduce your results (`verify --trust-snapshot`).
+    #[command(name = "sign")]
+    Sign {
+        #[command(flatten)]
+        wot: WotOpts,
+
+        /// Comment included in the snapshot
+        #[arg(long = "comment", default_value = "")]
+        comment: String,
+    },
+
+    /// Check the signature of a trust snapshot and compare it with your trust set
+    #[command(name = "import")]
+    Import {
+        #[command(flatten)]
+        wot: WotOpts,
+
+        /// File with the signed snapshot
+        path: PathBuf,
+    },
+}
+
+#[derive(Debug, Subcommand, Clone)]
+pub enum Queue {
+    /// Review requests from your web of trust, least reviewed first
+    ///
+    /// Versions you already reviewed are left out.
+    #[command(name = "requests")]
+    Requests {
+        #[command(flatten)]
+        wot: WotOpts,
+
+        #[command(flatten)]
+        requirements: VerificationRequirements,
+    },
+
+    /// List proofs waiting for their `--publish-after` date, soonest first
+    #[command(name = "list")]
+    List {
+        /// Print whole proofs
+        #[arg(long = "full")]
+        full: bool,
+    },
+
+    /// Store embargoed proofs in the local proof repository
+    ///
+    /// Without names, only the proofs whose date has passed, like `publish` does.
+    #[command(name = "release")]
+    Release {
+        /// Names of the proofs to store now, as listed (or their unique prefixes)
+        names: Vec<String>,
+    },
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct AdvisoryFromRustsec {
+    /// RustSec advisory id, eg. `RUSTSEC-2019-0001`
+    pub id: String,
+
+    /// Version to review [default: the first patched version]
+    ///
+    /// Required for advisories with no patched version, which are drafted as issues instead.
+    #[arg(long = "vers")]
+    pub version: Option<Version>,
+
+    /// Severity instead of the one derived from the advisory [none low medium high]
+    #[arg(long = "severity")]
+    pub severity: Option<Level>,
+
+    /// Use this checkout of the RustSec advisory database instead of fetching it
+    #[arg(long = "advisory-db", value_name = "DIR")]
+    pub advisory_db: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub common_proof_create: CommonProofCreate,
+
+    #[command(flatten)]
+    pub cargo_opts: CargoOpts,
+}
+
+#[derive(Debug, Subcommand, Clone)]
+pub enum Advisory {
+    /// Draft an advisory review from a RustSec advisory
+    ///
+    /// The review of the fixed version (or an issue, if nothing was fixed yet) is
+    /// pre-filled with the ids, description and severity of the advisory, to confirm and sign.
+    #[command(name = "from-rustsec")]
+    FromRustsec(AdvisoryFromRustsec),
+}
+
+/// Format of `badge`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BadgeFormat {
+    #[default]
+    Svg,
+    /// JSON for a shields.io endpoint badge
+    ShieldsJson,
+}
+
+impl std::str::FromStr for BadgeFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "svg" => Self::Svg,
+            "shields-json" => Self::ShieldsJson,
+            _ => bail!("Must be `svg` or `shields-json`, not `{}`", s),
+        })
+    }
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct Badge {
+    #[command(flatten)]
+    pub common: CrateVerifyCommon,
+
+    #[command(flatten)]
+    pub wot: WotOpts,
+
+    /// Output format [svg shields-json]
+    #[arg(long = "format", default_value = "svg")]
+    pub format: BadgeFormat,
+}
+
+/// Format of `report sbom`
+#[derive(Debug, Clone, Copy, Default)]
+pub enum SbomFormat {
+    /// CycloneDX JSON
+    #[default]
+    CycloneDx,
+}
+
+impl std::str::FromStr for SbomFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "cyclonedx" => Self::CycloneDx,
+            _ => bail!("Must be `cyclonedx`, not `{}`", s),
+        })
+    }
+}
+
+#[derive(Debug, Subcommand, Clone)]
+pub enum Report {
+    /// Print a software bill of materials of the current project, with crev verification results
+    ///
+    /// Every dependency is a component with `crev:*` properties: `crev:status`
+    /// (as in `verify`), `crev:review-count`, and a `crev:issue` for every open
+    /// issue and a `crev:reviewer` for every trusted reviewer.
+    #[command(name = "sbom")]
+    Sbom {
+        #[command(flatten)]
+        common: CrateVerifyCommon,
+
+        #[command(flatten)]
         wot: WotOpts,
+
+        /// Output format [cyclonedx]
+        #[arg(long = "format", default_value = "cyclonedx")]
+        format: SbomFormat,
     },
 }
 
-#[derive(Debug, StructOpt, Clone)]
-#[structopt(setting = structopt::clap::AppSettings::DeriveDisplayOrder)]
-#[structopt(setting = structopt::clap::AppSettings::DisableHelpSubcommand)]
+#[derive(Debug, Subcommand, Clone)]
+pub enum Org {
+    /// Print a summary of `verify` for the current project as JSON
+    ///
+    /// Save it as `<your-name>.json` in a directory shared with your team.
+    #[command(name = "summary")]
+    Summary {
+        #[command(flatten)]
+        common: CrateVerifyCommon,
+
+        #[command(flatten)]
+        wot: WotOpts,
+    },
+
+    /// Merge summaries of team members into one coverage report
+    ///
+    /// Shows which dependencies are verified by whom, gaps first.
+    #[command(name = "report")]
+    Report {
+        /// Directories with summaries (`*.json`) from `org summary`
+        #[arg(long = "from", value_name = "DIR", num_args = 1.., required = true)]
+        from: Vec<PathBuf>,
+
+        /// Print the report as JSON
+        #[arg(long = "json")]
+        json: bool,
+    },
+}
+
+#[derive(Debug, Subcommand, Clone)]
+pub enum Stats {
+    /// Your reviews, verify runs and reviewed crates per month
+    ///
+    /// Counted only with `usage-stats: true` in the config (see `config edit`),
+    /// and never sent anywhere.
+    #[command(name = "me")]
+    Me,
+}
+
+#[derive(Debug, Subcommand, Clone)]
+#[command(disable_help_subcommand = true)]
 #[allow(clippy::large_enum_variant)]
 pub enum Command {
     /// Local configuration
-    #[structopt(name = "config")]
+    #[command(name = "config")]
+    #[command(subcommand)]
     Config(Config),
 
     /// Crate related operations (review, verify...)
-    #[structopt(name = "crate")]
+    #[command(name = "crate")]
+    #[command(subcommand)]
     Crate(Crate),
 
     /// Id (own and of other users)
-    #[structopt(name = "id")]
+    #[command(name = "id")]
+    #[command(subcommand)]
     Id(Id),
 
+    /// Export proofs for use by other tools
+    #[command(name = "export")]
+    #[command(subcommand)]
+    Export(Export),
+
     /// Find a proof in the proof repo
-    #[structopt(name = "proof")]
+    #[command(name = "proof")]
+    #[command(subcommand)]
     Proof(Proof),
 
     /// Proof Repository
-    #[structopt(name = "repo")]
+    #[command(name = "repo")]
+    #[command(subcommand)]
     Repo(Repo),
 
     /// Add a Trust proof by an Id or a URL
     Trust(TrustUrls),
 
     /// Web of Trust
-    #[structopt(name = "wot")]
+    #[command(name = "wot")]
+    #[command(subcommand)]
     Wot(Wot),
 
+    /// Local-only statistics
+    #[command(name = "stats")]
+    #[command(subcommand)]
+    Stats(Stats),
+
+    /// Check the dependencies of the crate you're about to publish
+    ///
+    /// Prints a badge for the README, saying how many of them are verified.
+    #[command(name = "pre-publish-check")]
+    PrePublishCheck(PrePublishCheck),
+
+    /// What to review next
+    #[command(name = "queue")]
+    #[command(subcommand)]
+    Queue(Queue),
+
+    /// Verification coverage across a team
+    #[command(name = "org")]
+    #[command(subcommand)]
+    Org(Org),
+
+    /// Print a badge with the share of verified dependencies of the current project
+    ///
+    /// For the README, re-generated by CI: `cargo crev badge > crev.svg`
+    #[command(name = "badge")]
+    Badge(Badge),
+
+    /// Reports about the dependencies of the current project for other tools
+    #[command(name = "report")]
+    #[command(subcommand)]
+    Report(Report),
+
+    /// Advisories based on other sources
+    #[command(name = "advisory")]
+    #[command(subcommand)]
+    Advisory(Advisory),
+
     /// Shortcut for `crate goto`
-    #[structopt(name = "goto")]
+    #[command(name = "goto")]
     Goto(CrateSelector),
 
     /// Shortcut for `crate open`
     ///
     /// Crev will remember the last crate you've opened and default `crev review` to the same crate.
-    #[structopt(name = "open")]
+    #[command(name = "open")]
     Open(CrateOpen),
 
     /// Shortcut for `repo publish`
-    #[structopt(name = "publish")]
+    #[command(name = "publish")]
     Publish,
 
     /// Shortcut for `crate review`
     ///
     /// You can omit crate name if you're called `open` recently.
-    #[structopt(name = "review")]
+    #[command(name = "review")]
     Review(CrateReview),
 
     /// Shortcut for `repo update`
-    #[structopt(name = "update")]
+    #[command(name = "update")]
     Update(Update),
 
     /// Shortcut for `crate verify`
-    #[structopt(name = "verify")]
+    #[command(name = "verify")]
     Verify(CrateVerifyFull),
+
+    /// Check if a crate digest is verified by your Web of Trust
+    ///
+    /// For build systems that compute crev digests themselves.
+    /// Exits with non-zero status if the digest is not verified.
+    #[command(name = "verify-digest")]
+    VerifyDigest(VerifyDigest),
 }
 
 /// Cargo will pass the name of the `cargo-<tool>`
 /// as first argument, so we just have to match it here.
-#[derive(Debug, StructOpt, Clone)]
+#[derive(Debug, Subcommand, Clone)]
 pub enum MainCommand {
-    #[structopt(name = "crev")]
-    #[structopt(after_help = r#"All commands can be abbreviated.
+    #[command(name = "crev")]
+    #[command(after_help = r#"All commands can be abbreviated.
+
+Extensions: `cargo crev x-<name>` runs `cargo-crev-x-<name>` from PATH.
 
 Help and feedback: https://github.com/crev-dev/cargo-crev/discussions/
 User documentation: https://docs.rs/crate/cargo-crev
         "#)]
+    #[command(subcommand)]
     Crev(Command),
 }
 
-#[derive(Debug, StructOpt, Clone)]
-#[structopt(about = "Distributed code review system")]
+#[derive(Debug, Parser, Clone)]
+#[command(about = "Distributed code review system", version)]
 // without this the name will be `cargo-crev-crev` because the `crev` main command will be automatically appended
-#[structopt(bin_name = "cargo")]
-#[structopt(global_setting = structopt::clap::AppSettings::ColoredHelp)]
-#[structopt(global_setting = structopt::clap::AppSettings::InferSubcommands)]
+#[command(bin_name = "cargo")]
+#[command(infer_subcommands = true)]
 pub struct Opts {
-    #[structopt(subcommand)]
+    #[command(subcommand)]
     pub command: MainCommand,
-    //    #[structopt(flatten)]
+    //    #[command(flatten)]
     //    verbosity: Verbosity,
+    /// Format of warnings and log messages on stderr [text json]
+    ///
+    /// With `json`, each is a JSON object on its own line; warnings have a stable `code`.
+    #[arg(long = "log-format", global = true, default_value = "text")]
+    pub log_format: LogFormat,
+}
+
+/// See `Opts::log_format`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+impl std::str::FromStr for LogFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "text" => Self::Text,
+            "json" => Self::Json,
+            _ => bail!("Must be `text` or `json`, not `{}`", s),
+        })
+    }
+}
+
+/// Flags that were renamed, as `(old, new)`; the old ones still work, with a warning
+///
+/// When renaming a flag, add it here and keep it for a few releases.
+const DEPRECATED_FLAGS: &[(&str, &str)] = &[("--cmd-save", "--save-cmd")];
+
+/// Parse the command line, after replacing deprecated flags
+pub fn parse_args() -> Opts {
+    Opts::parse_from(replace_deprecated_flags(
+        std::env::args_os(),
+        DEPRECATED_FLAGS,
+    ))
+}
+
+/// Replace `--old` and `--old=value` with the new flag, up to a `--`
+fn replace_deprecated_flags(
+    args: impl IntoIterator<Item = OsString>,
+    deprecated: &[(&str, &str)],
+) -> Vec<OsString> {
+    let mut after_separator = false;
+    args.into_iter()
+        .map(|arg| {
+            if after_separator {
+                return arg;
+            }
+            let Some(arg_str) = arg.to_str() else {
+                return arg;
+            };
+            if arg_str == "--" {
+                after_separator = true;
+                return arg;
+            }
+            let (flag, value) = match arg_str.split_once('=') {
+                Some((flag, value)) => (flag, Some(value)),
+                None => (arg_str, None),
+            };
+            let Some((old, new)) = deprecated.iter().find(|(old, _)| *old == flag) else {
+                return arg;
+            };
+            eprintln!("Warning: `{old}` is deprecated, use `{new}` instead");
+            match value {
+                Some(value) => format!("{new}={value}").into(),
+                None => (*new).into(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn cli_is_consistent() {
+        Opts::command().debug_assert();
+    }
+
+    #[test]
+    fn replaces_deprecated_flags() {
+        let args = [
+            "cargo-crev",
+            "crev",
+            "verify",
+            "--old",
+            "--old=x",
+            "--",
+            "--old",
+        ];
+        let replaced =
+            replace_deprecated_flags(args.iter().map(OsString::from), &[("--old", "--new")]);
+        assert_eq!(
+            replaced,
+            [
+                "cargo-crev",
+                "crev",
+                "verify",
+                "--new",
+                "--new=x",
+                "--",
+                "--old"
+            ]
+        );
+    }
+
+    #[test]
+    fn deprecated_flags_still_parse() {
+        let args = ["cargo-crev", "crev", "open", "foo", "--cmd-save"];
+        let opts = Opts::try_parse_from(replace_deprecated_flags(
+            args.iter().map(OsString::from),
+            DEPRECATED_FLAGS,
+        ))
+        .unwrap();
+        let MainCommand::Crev(Command::Open(open)) = opts.command else {
+            panic!("not parsed as `open`: {opts:?}");
+        };
+        assert!(open.cmd_save);
+    }
 }
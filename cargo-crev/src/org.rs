@@ -0,0 +1,353 @@
+//! `org`: verification coverage across a team
+//!
+//! Everyone exports a summary of `verify` for the project (`org summary`), and
+//! `org report --from <dir>...` merges the summaries found in the given
+//! directories: which dependencies are verified by whom, and where the gaps are.
+use crate::{
+    deps::scan::{RequiredDetails, Scanner},
+    opts::{CrateSelector, CrateVerify, CrateVerifyCommon, WotOpts},
+    prelude::*,
+    repo::Repo,
+};
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use crev_data::{Id, SOURCE_CRATES_IO};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+/// Bumped on incompatible changes of [`VerifySummary`]
+const SUMMARY_FORMAT_VERSION: u32 = 1;
+
+/// What one developer's `verify` found, in a mergeable form
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct VerifySummary {
+    pub format_version: u32,
+    /// Current Id of whoever made the summary, if they have one
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub id: Option<Id>,
+    pub computed: DateTime<Utc>,
+    pub crates: Vec<SummaryCrate>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SummaryCrate {
+    /// Eg. `https://crates.io`, or `git+<url>`; `local` for path dependencies
+    pub source: String,
+    pub name: String,
+    pub version: Version,
+    /// `None` for local crates
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub digest: Option<String>,
+    pub verified: bool,
+    /// Trusted Ids that reviewed this version
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub trusted_reviewers: Vec<Id>,
+}
+
+/// One dependency across all the summaries
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct OrgCrate {
+    pub source: String,
+    pub name: String,
+    pub version: Version,
+    /// Summaries (by name) that have the crate
+    pub used_by: BTreeSet<String>,
+    /// Summaries (by name) in which it's verified
+    pub verified_by: BTreeSet<String>,
+    /// Reviewers trusted by anyone that reviewed it
+    pub reviewers: BTreeSet<String>,
+    /// Summaries disagree about its digest; someone has a modified copy
+    pub digest_conflict: bool,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct OrgReport {
+    /// Name of every merged summary (its file name), with its Id
+    pub summaries: BTreeMap<String, Option<String>>,
+    pub crates: Vec<OrgCrate>,
+}
+
+impl OrgReport {
+    /// Merge summaries, named eg. after their files
+    pub fn merge(summaries: &[(String, VerifySummary)]) -> Self {
+        // crates of the same name from different sources are different crates
+        let mut crates: BTreeMap<(String, String, Version), (OrgCrate, BTreeSet<String>)> =
+            BTreeMap::new();
+        for (summary_name, summary) in summaries {
+            for crate_ in &summary.crates {
+                let key = (
+                    crate_.source.clone(),
+                    crate_.name.clone(),
+                    crate_.version.clone(),
+                );
+                let (org_crate, digests) = crates.entry(key).or_insert_with(|| {
+                    let org_crate = OrgCrate {
+                        source: crate_.source.clone(),
+                        name: crate_.name.clone(),
+                        version: crate_.version.clone(),
+                        used_by: BTreeSet::new(),
+                        verified_by: BTreeSet::new(),
+                        reviewers: BTreeSet::new(),
+                        digest_conflict: false,
+                    };
+                    (org_crate, BTreeSet::new())
+                });
+                org_crate.used_by.insert(summary_name.clone());
+                if crate_.verified {
+                    org_crate.verified_by.insert(summary_name.clone());
+                }
+                org_crate
+                    .reviewers
+                    .extend(crate_.trusted_reviewers.iter().map(ToString::to_string));
+                digests.extend(crate_.digest.clone());
+            }
+        }
+
+        let mut crates: Vec<_> = crates
+            .into_values()
+            .map(|(mut org_crate, digests)| {
+                org_crate.digest_conflict = digests.len() > 1;
+                org_crate
+            })
+            .collect();
+        // the biggest gaps first
+        crates.sort_by_key(|c| {
+            (
+                c.verified_by.len(),
+                !c.digest_conflict,
+                c.name.clone(),
+                c.source.clone(),
+            )
+        });
+        Self {
+            summaries: summaries
+                .iter()
+                .map(|(name, s)| (name.clone(), s.id.as_ref().map(ToString::to_string)))
+                .collect(),
+            crates,
+        }
+    }
+
+    pub fn print(&self) {
+        let total = self.summaries.len();
+        let (mut everyone, mut some, mut nobody) = (0, 0, 0);
+        for crate_ in &self.crates {
+            let status = if crate_.verified_by.is_empty() {
+                nobody += 1;
+                "gap"
+            } else if crate_.verified_by.len() < crate_.used_by.len() {
+                some += 1;
+                "partial"
+            } else {
+                everyone += 1;
+                "ok"
+            };
+            let name = if crate_.source == SOURCE_CRATES_IO {
+                crate_.name.clone()
+            } else {
+                format!("{} ({})", crate_.name, crate_.source)
+            };
+            println!(
+                "{:<8} {:>3}/{:<3} {:<30} {:<15} {}{}",
+                status,
+                crate_.verified_by.len(),
+                crate_.used_by.len(),
+                name,
+                crate_.version,
+                crate_
+                    .verified_by
+                    .iter()
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join(","),
+                if crate_.digest_conflict {
+                    " (digests differ!)"
+                } else {
+                    ""
+                }
+            );
+        }
+        eprintln!(
+            "{} crates in {} summaries: {} verified by everyone using them, {} by some, {} by nobody",
+            self.crates.len(),
+            total,
+            everyone,
+            some,
+            nobody
+        );
+    }
+}
+
+/// Print the summary of `verify` for the current project as JSON
+pub fn print_summary(common: CrateVerifyCommon, wot: WotOpts) -> Result<()> {
+    let args = CrateVerify {
+        common,
+        wot,
+        ..Default::default()
+    };
+    // fail early outside of a cargo project
+    Repo::auto_open_cwd(args.common.cargo_opts.clone())?;
+    let local = crev_lib::Local::auto_create_or_open()?;
+    let scanner = Scanner::new(CrateSelector::default(), &args)?;
+
+    let mut crates: Vec<_> = scanner
+        .run(&RequiredDetails::none())
+        .map(|stats| {
+            let details = stats.details();
+            let mut trusted_reviewers: Vec<_> = details
+                .trusted_reviewers
+                .iter()
+                .map(|reviewer| reviewer.id.clone())
+                .collect();
+            trusted_reviewers.sort();
+            let source_id = stats.info.id.source_id();
+            SummaryCrate {
+                // paths differ from one checkout to another
+                source: if source_id.is_path() {
+                    "local".into()
+                } else {
+                    crate::cargo_registry_to_crev_source_id(&source_id)
+                },
+                name: stats.info.id.name().to_string(),
+                version: stats.info.id.version().clone(),
+                digest: details.digest.as_ref().map(ToString::to_string),
+                verified: details.accumulative_own.verified,
+                trusted_reviewers,
+            }
+        })
+        .collect();
+    crates.sort_by(|a, b| (&a.name, &a.version, &a.source).cmp(&(&b.name, &b.version, &b.source)));
+
+    let summary = VerifySummary {
+        format_version: SUMMARY_FORMAT_VERSION,
+        id: local.get_current_userid_opt()?,
+        computed: Utc::now(),
+        crates,
+    };
+    println!("{}", serde_json::to_string_pretty(&summary)?);
+    Ok(())
+}
+
+/// Summaries (`*.json`) in `dir`, named after their files
+fn read_summaries(dir: &Path) -> Result<Vec<(String, VerifySummary)>> {
+    let mut summaries = vec![];
+    for entry in std::fs::read_dir(dir).with_context(|| format!("Can't read {}", dir.display()))? {
+        let path = entry?.path();
+        if path.extension().map_or(true, |ext| ext != "json") {
+            continue;
+        }
+        let summary: VerifySummary = serde_json::from_slice(&std::fs::read(&path)?)
+            .with_context(|| format!("{} is not a summary from `org summary`", path.display()))?;
+        if summary.format_version != SUMMARY_FORMAT_VERSION {
+            bail!(
+                "{} has format version {}, expected {}; re-create it with this version of cargo-crev",
+                path.display(),
+                summary.format_version,
+                SUMMARY_FORMAT_VERSION
+            );
+        }
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        summaries.push((name, summary));
+    }
+    Ok(summaries)
+}
+
+pub fn print_report(dirs: &[PathBuf], json: bool) -> Result<()> {
+    let mut summaries = vec![];
+    for dir in dirs {
+        summaries.extend(read_summaries(dir)?);
+    }
+    if summaries.is_empty() {
+        bail!("No summaries (`*.json` files from `cargo crev org summary`) found");
+    }
+    let report = OrgReport::merge(&summaries);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        report.print();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(crates: &[(&str, &str, bool)]) -> VerifySummary {
+        summary_from(SOURCE_CRATES_IO, crates)
+    }
+
+    fn summary_from(source: &str, crates: &[(&str, &str, bool)]) -> VerifySummary {
+        VerifySummary {
+            format_version: SUMMARY_FORMAT_VERSION,
+            id: None,
+            computed: Utc::now(),
+            crates: crates
+                .iter()
+                .map(|&(name, digest, verified)| SummaryCrate {
+                    source: source.into(),
+                    name: name.into(),
+                    version: Version::new(1, 0, 0),
+                    digest: Some(digest.into()),
+                    verified,
+                    trusted_reviewers: vec![],
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn merges_summaries() {
+        let report = OrgReport::merge(&[
+            (
+                "alice".into(),
+                summary(&[("log", "a", true), ("rand", "b", false)]),
+            ),
+            (
+                "bob".into(),
+                summary(&[
+                    ("log", "a", false),
+                    ("rand", "c", false),
+                    ("syn", "d", true),
+                ]),
+            ),
+        ]);
+
+        let names: Vec<_> = report.crates.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["rand", "log", "syn"]);
+        let rand = &report.crates[0];
+        assert!(rand.verified_by.is_empty());
+        assert!(rand.digest_conflict);
+        let log = &report.crates[1];
+        assert_eq!(log.used_by.len(), 2);
+        assert_eq!(log.verified_by.iter().collect::<Vec<_>>(), ["alice"]);
+        assert!(!log.digest_conflict);
+    }
+
+    #[test]
+    fn keeps_crates_of_other_sources_apart() {
+        let report = OrgReport::merge(&[
+            ("alice".into(), summary(&[("log", "a", true)])),
+            (
+                "bob".into(),
+                summary_from("git+https://example.com/log", &[("log", "b", false)]),
+            ),
+        ]);
+
+        assert_eq!(report.crates.len(), 2);
+        assert!(report.crates.iter().all(|c| !c.digest_conflict));
+        assert_eq!(report.crates[0].source, "git+https://example.com/log");
+        assert!(report.crates[0].verified_by.is_empty());
+        assert_eq!(report.crates[1].source, SOURCE_CRATES_IO);
+    }
+}
@@ -0,0 +1,218 @@
+//! `review --from-patch`: reviews of patches read outside of crev (eg. upstream PRs)
+//!
+//! Only unified diffs (`diff -u`, `git diff`, `.diff` of a PR) are understood.
+//! Paths in the patch are mapped to the crate by dropping leading components
+//! (`a/`, or the crate's directory in its repository) until they exist in it.
+use crate::prelude::*;
+use anyhow::Context;
+use crev_data::proof::review::Patch;
+use std::path::{Path, PathBuf};
+
+/// Changes to one file
+#[derive(Debug, Default)]
+struct FileDiff {
+    /// Path on the "old" side, `None` for created files
+    old_path: Option<String>,
+    /// Path on the "new" side, `None` for deleted files
+    new_path: Option<String>,
+    hunks: Vec<Hunk>,
+}
+
+#[derive(Debug, Default)]
+struct Hunk {
+    /// Lines after the change: context and added ones
+    new_lines: Vec<String>,
+    lines_changed: u64,
+}
+
+/// A patch checked against the source of the crate it's claimed for
+pub struct CheckedPatch {
+    pub patch: Patch,
+    /// Why the patch doesn't match the crate; empty if it does
+    pub mismatches: Vec<String>,
+}
+
+/// Path from a `---`/`+++` line, without the timestamp; `None` for `/dev/null`
+fn parse_header_path(s: &str) -> Option<String> {
+    let path = s.split('\t').next().unwrap_or_default().trim();
+    (path != "/dev/null").then(|| path.to_owned())
+}
+
+/// Number of lines on each side, from `@@ -l,s +l,s @@`
+fn parse_hunk_header(line: &str) -> Option<(usize, usize)> {
+    let mut ranges = line.strip_prefix("@@ ")?.split(' ');
+    let len = |range: &str| {
+        range
+            .split_once(',')
+            .map_or(Some(1), |(_, len)| len.parse().ok())
+    };
+    let old = len(ranges.next()?.strip_prefix('-')?)?;
+    let new = len(ranges.next()?.strip_prefix('+')?)?;
+    Some((old, new))
+}
+
+fn parse_unified_diff(text: &str) -> Result<Vec<FileDiff>> {
+    let mut files: Vec<FileDiff> = vec![];
+    let mut lines = text.lines().peekable();
+    while let Some(line) = lines.next() {
+        if let Some(old) = line.strip_prefix("--- ") {
+            let Some(new) = lines.peek().and_then(|l| l.strip_prefix("+++ ")) else {
+                continue;
+            };
+            files.push(FileDiff {
+                old_path: parse_header_path(old),
+                new_path: parse_header_path(new),
+                hunks: vec![],
+            });
+            lines.next();
+        } else if line.starts_with("@@ ") {
+            let file = files
+                .last_mut()
+                .ok_or_else(|| format_err!("Hunk without a file header: `{}`", line))?;
+            let (mut old_left, mut new_left) = parse_hunk_header(line)
+                .ok_or_else(|| format_err!("Malformed hunk header: `{}`", line))?;
+            let mut hunk = Hunk::default();
+            while old_left > 0 || new_left > 0 {
+                let Some(line) = lines.next() else {
+                    bail!("Patch ends in the middle of a hunk");
+                };
+                // some tools strip the space of empty context lines
+                let (kind, content) = match line.chars().next() {
+                    Some(kind) => (kind, &line[kind.len_utf8()..]),
+                    None => (' ', ""),
+                };
+                match kind {
+                    ' ' => {
+                        old_left = old_left.saturating_sub(1);
+                        new_left = new_left.saturating_sub(1);
+                        hunk.new_lines.push(content.to_owned());
+                    }
+                    '-' => {
+                        old_left = old_left.saturating_sub(1);
+                        hunk.lines_changed += 1;
+                    }
+                    '+' => {
+                        new_left = new_left.saturating_sub(1);
+                        hunk.lines_changed += 1;
+                        hunk.new_lines.push(content.to_owned());
+                    }
+                    // `\ No newline at end of file`
+                    '\\' => {}
+                    _ => bail!("Unexpected line in a hunk: `{}`", line),
+                }
+            }
+            file.hunks.push(hunk);
+        }
+    }
+    if files.is_empty() {
+        bail!("No file changes found; is it a unified diff?");
+    }
+    Ok(files)
+}
+
+/// Find `path` from the patch in `crate_root`, dropping its leading components as needed
+fn map_to_crate(crate_root: &Path, path: &str) -> Option<PathBuf> {
+    let mut components = Path::new(path).components();
+    loop {
+        let relative = components.as_path();
+        if relative.as_os_str().is_empty() {
+            return None;
+        }
+        if crate_root.join(relative).is_file() {
+            return Some(relative.to_owned());
+        }
+        components.next();
+    }
+}
+
+/// Does `haystack` contain `needle` as consecutive lines
+fn contains_lines(haystack: &[&str], needle: &[String]) -> bool {
+    needle.is_empty()
+        || haystack
+            .windows(needle.len())
+            .any(|window| window.iter().zip(needle).all(|(a, b)| a == b))
+}
+
+/// Lines in the Rust source files of the crate
+fn count_source_lines(crate_root: &Path) -> u64 {
+    walkdir::WalkDir::new(crate_root)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            entry.file_type().is_file() && entry.path().extension().map_or(false, |ext| ext == "rs")
+        })
+        .filter_map(|entry| std::fs::read_to_string(entry.path()).ok())
+        .map(|content| content.lines().count() as u64)
+        .sum()
+}
+
+/// Read the patch at `patch_path` and check that the crate at `crate_root` contains it
+///
+/// The crate version a review is for has to have the changes applied, so
+/// the lines after the change of every hunk must be found in it.
+pub fn check_patch(patch_path: &Path, crate_root: &Path) -> Result<CheckedPatch> {
+    let text = std::fs::read_to_string(patch_path)
+        .with_context(|| format!("Can't read patch {}", patch_path.display()))?;
+    let files = parse_unified_diff(&text)?;
+
+    let mut mismatches = vec![];
+    let mut touched = vec![];
+    let mut lines_changed = 0;
+    for file in &files {
+        lines_changed += file.hunks.iter().map(|h| h.lines_changed).sum::<u64>();
+        let Some(new_path) = &file.new_path else {
+            let old_path = file.old_path.as_deref().unwrap_or_default();
+            if let Some(path) = map_to_crate(crate_root, old_path) {
+                mismatches.push(format!("{} is deleted by the patch", path.display()));
+            }
+            continue;
+        };
+        let Some(path) = map_to_crate(crate_root, new_path) else {
+            mismatches.push(format!("{new_path} is not in the crate"));
+            continue;
+        };
+        let content = std::fs::read_to_string(crate_root.join(&path))
+            .with_context(|| format!("Can't read {}", path.display()))?;
+        let content: Vec<_> = content.lines().collect();
+        let missing = file
+            .hunks
+            .iter()
+            .filter(|hunk| !contains_lines(&content, &hunk.new_lines))
+            .count();
+        if missing > 0 {
+            mismatches.push(format!(
+                "{} doesn't contain {} of {} hunks",
+                path.display(),
+                missing,
+                file.hunks.len()
+            ));
+        }
+        touched.push(path.display().to_string());
+    }
+
+    Ok(CheckedPatch {
+        patch: Patch {
+            digest: crev_common::blake2b256sum(text.as_bytes()).to_vec(),
+            files: touched,
+            lines_changed,
+            lines_total: count_source_lines(crate_root),
+        },
+        mismatches,
+    })
+}
+
+/// One line summary of how much of the crate the patch covers
+pub fn coverage_comment(patch: &Patch) -> String {
+    let percent = if patch.lines_total == 0 {
+        0.0
+    } else {
+        patch.lines_changed as f64 * 100. / patch.lines_total as f64
+    };
+    format!(
+        "Reviewed from a patch: {} files, {} lines changed ({:.1}% of {} lines of code)",
+        patch.files.len(),
+        patch.lines_changed,
+        percent,
+        patch.lines_total,
+    )
+}
@@ -0,0 +1,123 @@
+//! Project review policy, see [`crev_lib::project_policy`]
+//!
+//! Read from `.crev-policy.yaml` (or `crev-policy.yaml`) next to the root
+//! `Cargo.toml` of the project, or from the file given with `--policy`.
+use crate::prelude::*;
+use anyhow::Context;
+use chrono::NaiveDate;
+use crev_data::Digest;
+use crev_lib::{
+    project_policy::{self, Exemption, ProjectPolicy, RequirementsRule},
+    VerificationRequirements,
+};
+use std::path::{Path, PathBuf};
+
+/// Loaded and validated review policy
+#[derive(Debug, Clone, Default)]
+pub struct ReviewPolicy {
+    project: ProjectPolicy,
+}
+
+/// The policy file given with `--policy`, or the one next to the project's `Cargo.toml`
+pub fn policy_path(explicit_path: Option<&Path>, manifest_path: &Path) -> Option<PathBuf> {
+    match explicit_path {
+        Some(path) => Some(path.to_owned()),
+        None => project_policy::find_in(manifest_path.parent().unwrap_or_else(|| Path::new("."))),
+    }
+}
+
+impl ReviewPolicy {
+    pub fn load(path: &Path) -> Result<Self> {
+        let project = ProjectPolicy::load(path)
+            .with_context(|| format!("Can't load review policy {}", path.display()))?;
+        Ok(Self { project })
+    }
+
+    /// The policy given with `--policy`, or the one next to the project's `Cargo.toml`, if any
+    pub fn for_project(explicit_path: Option<&Path>, manifest_path: &Path) -> Result<Self> {
+        match policy_path(explicit_path, manifest_path) {
+            Some(path) => Self::load(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Requirements for the crate `name`: `global` made stricter by the project's,
+    /// overridden by the first matching rule, if any
+    pub fn requirements_for(
+        &self,
+        name: &str,
+        global: &VerificationRequirements,
+    ) -> (VerificationRequirements, Option<&RequirementsRule>) {
+        self.project.requirements_for(name, global)
+    }
+
+    pub fn has_exemptions(&self) -> bool {
+        self.project.has_exemptions()
+    }
+
+    /// The exemption from verification of the crate `name` still in effect, if any
+    pub fn exemption_for(&self, name: &str) -> Option<&Exemption> {
+        self.project.exemption_for(name, today())
+    }
+
+    /// Exemptions past their date, that the project should drop
+    pub fn expired_exemptions(&self) -> Vec<&Exemption> {
+        self.project.expired_exemptions(today()).collect()
+    }
+
+    /// Why the crate with `digest` doesn't meet the policy; empty if it does
+    pub fn check(
+        &self,
+        name: &str,
+        version: &Version,
+        digest: &Digest,
+        db: &crev_wot::ProofDB,
+    ) -> Vec<String> {
+        self.project.check_reviewers(name, version, digest, db)
+    }
+}
+
+/// Exemptions and waivers apply until the end of their day, local time
+pub fn today() -> NaiveDate {
+    chrono::Local::now().date_naive()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crev_data::Level;
+
+    #[test]
+    fn first_matching_requirements_apply() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join(project_policy::FILE_NAMES[0]);
+        std::fs::write(
+            &path,
+            "requirements:\n  - crates: [\"serde*\"]\n    redundancy: 2\n  - crates: [\"serde_json\", \"*-sys\"]\n    thoroughness: medium\n",
+        )?;
+        let policy = ReviewPolicy::load(&path)?;
+        let global = VerificationRequirements {
+            trust_level: Level::High,
+            understanding: Level::None,
+            thoroughness: Level::None,
+            redundancy: 1,
+        };
+
+        let (requirements, rule) = policy.requirements_for("serde_json", &global);
+        assert_eq!(
+            rule.map(ToString::to_string).as_deref(),
+            Some("serde*: redundancy 2")
+        );
+        assert_eq!(requirements.redundancy, 2);
+        assert_eq!(requirements.trust_level, Level::High);
+        assert_eq!(requirements.thoroughness, Level::None);
+
+        let (requirements, rule) = policy.requirements_for("openssl-sys", &global);
+        assert!(rule.is_some());
+        assert_eq!(requirements.redundancy, 1);
+        assert_eq!(requirements.thoroughness, Level::Medium);
+
+        assert!(policy.requirements_for("log", &global).1.is_none());
+        Ok(())
+    }
+}
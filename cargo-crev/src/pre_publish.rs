@@ -0,0 +1,124 @@
+//! `pre-publish-check`: for crate authors, whether the dependencies of the
+//! crate about to be published pass verification
+//!
+//! Prints a Markdown badge with the result, to put in the README.
+use crate::{
+    deps::{
+        scan::{RequiredDetails, Scanner},
+        VerifyFailures,
+    },
+    opts::{CrateSelector, CrateVerify, PrePublishCheck},
+    prelude::*,
+    repo::Repo,
+    shared::CommandExitStatus,
+    term,
+};
+use crev_lib::VerificationStatus;
+
+const CREV_URL: &str = "https://github.com/crev-dev/cargo-crev";
+
+/// Shields.io escapes `-` and `_` in badge texts by doubling them
+fn badge_escape(text: &str) -> String {
+    text.replace('-', "--")
+        .replace('_', "__")
+        .replace(' ', "%20")
+        .replace('/', "%2F")
+}
+
+/// Badge and one line of details, in Markdown
+fn markdown_snippet(failures: &VerifyFailures, requirements: &str) -> String {
+    let total = failures.verified + failures.negative + failures.unreviewed;
+    let color = if failures.negative > 0 {
+        "red"
+    } else if failures.unreviewed > 0 {
+        "yellow"
+    } else {
+        "brightgreen"
+    };
+    let message = format!("{}/{} deps verified", failures.verified, total);
+    format!(
+        "[![crev: {message}](https://img.shields.io/badge/crev-{}-{color})]({CREV_URL})\n\n\
+         {} of {} dependencies verified with [cargo-crev]({CREV_URL}) ({requirements}) \
+         on {}.\n",
+        badge_escape(&message),
+        failures.verified,
+        total,
+        chrono::Local::now().date_naive(),
+    )
+}
+
+pub fn pre_publish_check(args: PrePublishCheck) -> Result<CommandExitStatus> {
+    let verify_args = CrateVerify {
+        common: args.common,
+        wot: args.wot,
+        policy: args.policy,
+        ..Default::default()
+    };
+    // fail early outside of a cargo project
+    Repo::auto_open_cwd(verify_args.common.cargo_opts.clone())?;
+    let scanner = Scanner::new(CrateSelector::default(), &verify_args)?;
+
+    let mut failures = VerifyFailures::default();
+    let mut failing = vec![];
+    // the crate itself and other local crates have no reviews to check
+    for stats in scanner
+        .run(&RequiredDetails::none())
+        .filter(|stats| !stats.details.accumulative_own.is_local_source_code)
+    {
+        let status = if stats.details.accumulative_own.verified {
+            VerificationStatus::Verified
+        } else {
+            stats.details.accumulative_own.trust
+        };
+        failures.add(status);
+        if !status.is_verified() {
+            failing.push(format!(
+                "{:6} {} {}",
+                status,
+                stats.info.id.name(),
+                stats.info.id.version()
+            ));
+        }
+    }
+    failing.sort();
+    for line in &failing {
+        eprintln!("{line}");
+    }
+    failures.print_summary(&mut term::Term::new())?;
+
+    let requirements = &verify_args.common.requirements;
+    let requirements = format!(
+        "trust level {}, redundancy {}",
+        requirements.trust_level.trust_level, requirements.redundancy
+    );
+    let snippet = markdown_snippet(&failures, &requirements);
+    if let Some(path) = &args.badge_output {
+        std::fs::write(path, &snippet)?;
+        eprintln!("README snippet written to {}", path.display());
+    } else {
+        print!("{snippet}");
+    }
+
+    Ok(failures.exit_status(&verify_args))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn badge_shows_verified_count() {
+        let failures = VerifyFailures {
+            verified: 40,
+            negative: 0,
+            unreviewed: 2,
+            ..VerifyFailures::default()
+        };
+        let snippet = markdown_snippet(&failures, "trust level medium, redundancy 1");
+        assert!(snippet.starts_with(
+            "[![crev: 40/42 deps verified](https://img.shields.io/badge/crev-40%2F42%20deps%20verified-yellow)]"
+        ));
+        assert!(snippet.contains("40 of 42 dependencies verified"));
+        assert_eq!(badge_escape("a-b_c"), "a--b__c");
+    }
+}
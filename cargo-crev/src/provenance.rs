@@ -0,0 +1,158 @@
+//! Where the code under review comes from
+//!
+//! Shown as a banner in review drafts, so reviews of locally modified sources
+//! don't get signed by accident: the digested directory, its digest, and
+//! whether it is what the registry published.
+use crate::{prelude::*, repo::Repo};
+use anyhow::Context;
+use cargo::core::Package;
+use crev_lib::DigestIgnorePolicy;
+use sha2::{Digest as _, Sha256};
+use std::{
+    fmt,
+    fs::File,
+    path::{Path, PathBuf},
+};
+
+/// Does the digested directory match the registry checksum of the crate
+pub enum ChecksumStatus {
+    Matches,
+    Mismatch(String),
+    NotChecked(String),
+}
+
+impl fmt::Display for ChecksumStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChecksumStatus::Matches => f.write_str("matches"),
+            ChecksumStatus::Mismatch(reason) => write!(f, "MISMATCH: {reason}"),
+            ChecksumStatus::NotChecked(reason) => write!(f, "not checked: {reason}"),
+        }
+    }
+}
+
+pub struct Provenance {
+    /// The directory the digest was computed for
+    pub path: PathBuf,
+    pub digest: crev_data::Digest,
+    pub checksum: ChecksumStatus,
+}
+
+impl Provenance {
+    /// Check the `digest` of the source of `crate_`, computed with `policy`, against the registry
+    pub fn check(
+        repo: &Repo,
+        crate_: &Package,
+        digest: crev_data::Digest,
+        policy: &DigestIgnorePolicy,
+    ) -> Self {
+        let checksum = match check_checksum(repo, crate_, &digest, policy) {
+            Ok(status) => status,
+            Err(e) => ChecksumStatus::NotChecked(format!("{e:#}")),
+        };
+        Self {
+            path: crate_.root().to_owned(),
+            digest,
+            checksum,
+        }
+    }
+
+    pub fn is_mismatch(&self) -> bool {
+        matches!(self.checksum, ChecksumStatus::Mismatch(_))
+    }
+
+    /// For the leading comment of a review draft
+    pub fn banner(&self) -> String {
+        format!(
+            "Reviewed source: {}\nDigest: {}\nRegistry checksum: {}",
+            self.path.display(),
+            self.digest,
+            self.checksum
+        )
+    }
+}
+
+/// The `.crate` file cargo extracted `crate_root` from, if it's in cargo's registry cache
+///
+/// Cargo extracts `registry/cache/<registry>/<name>-<version>.crate`
+/// to `registry/src/<registry>/<name>-<version>`.
+fn cached_crate_file(crate_root: &Path) -> Option<PathBuf> {
+    let registry_dir = crate_root.parent()?;
+    let src_dir = registry_dir.parent()?;
+    if src_dir.file_name()? != "src" {
+        return None;
+    }
+    let mut file_name = crate_root.file_name()?.to_owned();
+    file_name.push(".crate");
+    Some(
+        src_dir
+            .parent()?
+            .join("cache")
+            .join(registry_dir.file_name()?)
+            .join(file_name),
+    )
+}
+
+fn sha256_hex(path: &Path) -> Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn check_checksum(
+    repo: &Repo,
+    crate_: &Package,
+    digest: &crev_data::Digest,
+    policy: &DigestIgnorePolicy,
+) -> Result<ChecksumStatus> {
+    let Some(expected) = repo.get_registry_checksum(&crate_.package_id())? else {
+        return Ok(ChecksumStatus::NotChecked(
+            "the registry has no checksum for it".into(),
+        ));
+    };
+    let Some(crate_file) = cached_crate_file(crate_.root()).filter(|path| path.is_file()) else {
+        return Ok(ChecksumStatus::NotChecked(
+            "the downloaded .crate file is not in cargo's cache".into(),
+        ));
+    };
+    if sha256_hex(&crate_file)? != expected {
+        return Ok(ChecksumStatus::Mismatch(format!(
+            "{} is not the file the registry published",
+            crate_file.display()
+        )));
+    }
+
+    let unpacked = tempfile::tempdir()?;
+    tar::Archive::new(flate2::read::GzDecoder::new(File::open(&crate_file)?))
+        .unpack(unpacked.path())
+        .with_context(|| format!("Can't unpack {}", crate_file.display()))?;
+    let unpacked_root = unpacked
+        .path()
+        .join(crate_.root().file_name().expect("crate dir has a name"));
+    let unpacked_digest = policy.digest(&unpacked_root)?;
+    Ok(if &unpacked_digest == digest {
+        ChecksumStatus::Matches
+    } else {
+        ChecksumStatus::Mismatch(format!(
+            "the files differ from {} (digest {})",
+            crate_file.display(),
+            unpacked_digest
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_cached_crate_file() {
+        let registry = Path::new("/home/u/.cargo/registry");
+        assert_eq!(
+            cached_crate_file(&registry.join("src/index.crates.io-6f17d22bba15001f/log-0.4.6")),
+            Some(registry.join("cache/index.crates.io-6f17d22bba15001f/log-0.4.6.crate"))
+        );
+        assert_eq!(cached_crate_file(Path::new("/home/u/projects/log")), None);
+    }
+}
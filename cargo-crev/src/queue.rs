@@ -0,0 +1,197 @@
+//! Review requests: maintainers asking for reviews of their crate versions
+//!
+//! `crate request-review` publishes a request; `queue requests` lists
+//! requests from your web of trust, versions missing the most reviews first.
+//!
+//! Proofs created with `--publish-after` wait in the embargo queue until
+//! their date; `queue list` and `queue release` manage them.
+use crate::{
+    opts::{CommonProofCreate, CrateSelector, VerificationRequirements, WotOpts},
+    prelude::*,
+    shared::maybe_store,
+    term, Repo,
+};
+use crev_data::{
+    proof::{self, CommonOps, ContentExt},
+    Rating, TrustLevel,
+};
+use crev_lib::embargo::Date;
+use crev_wot::{ProofDB, TrustSet};
+use std::collections::HashMap;
+
+pub fn request_review(
+    crate_: CrateSelector,
+    comment: String,
+    common_proof_create: &CommonProofCreate,
+) -> Result<()> {
+    crate_.ensure_name_given()?;
+    let repo = Repo::auto_open_cwd_default()?;
+    let pkg_id = repo.find_pkgid_by_crate_selector(&crate_)?;
+    let package = repo.crates_io_mirrors().crev_pkg_id(&pkg_id);
+
+    let local = crev_lib::Local::auto_open()?;
+    let id = local.read_current_unlocked_id(&term::read_passphrase)?;
+    let request = id
+        .as_public_id()
+        .create_review_request_proof(package, comment)?;
+    let proof = request.sign_by(&id)?;
+    maybe_store(
+        &local,
+        &id,
+        &proof,
+        "Add review request",
+        common_proof_create,
+    )
+}
+
+/// Trusted reviews of the requested version that count for `requirements`
+fn sufficient_review_count(
+    package: &proof::PackageVersionId,
+    trust_set: &TrustSet,
+    requirements: &crev_lib::VerificationRequirements,
+    db: &ProofDB,
+) -> u64 {
+    db.get_pkg_reviews_for_version(&package.id.source, &package.id.name, &package.version)
+        .filter(|review| {
+            let opinion = review.review_possibly_none();
+            !opinion.is_none()
+                && Rating::Neutral <= opinion.rating
+                && requirements.thoroughness <= opinion.thoroughness
+                && requirements.understanding <= opinion.understanding
+                && TrustLevel::from(requirements.trust_level)
+                    <= trust_set.get_effective_trust_level(&review.common.from.id)
+        })
+        .count() as u64
+}
+
+pub fn print_review_requests(
+    wot_opts: WotOpts,
+    requirements: VerificationRequirements,
+) -> Result<()> {
+    let local = crev_lib::Local::auto_create_or_open()?;
+    let db = local.load_db()?;
+    let for_id = local.get_for_id_from_str(wot_opts.for_id.as_deref())?;
+    let trust_set = db.calculate_trust_set(&for_id, &wot_opts.trust_params.into());
+    let requirements = crev_lib::VerificationRequirements::from(requirements);
+
+    let mut by_package: HashMap<&proof::PackageVersionId, Vec<&proof::ReviewRequest>> =
+        HashMap::new();
+    for request in db.all_review_requests() {
+        if trust_set.is_trusted(&request.from().id) {
+            by_package
+                .entry(&request.package)
+                .or_default()
+                .push(request);
+        }
+    }
+
+    let mut queue: Vec<_> = by_package
+        .into_iter()
+        .filter(|(package, _)| {
+            db.get_pkg_review(
+                &package.id.source,
+                &package.id.name,
+                &package.version,
+                &for_id,
+            )
+            .is_none()
+        })
+        .map(|(package, mut requests)| {
+            requests.sort_by_key(|request| std::cmp::Reverse(request.date_utc()));
+            let reviews = sufficient_review_count(package, &trust_set, &requirements, &db);
+            (package, requests, reviews)
+        })
+        .collect();
+    if queue.is_empty() {
+        eprintln!("No review requests from your web of trust");
+        return Ok(());
+    }
+    queue.sort_by(
+        |(a_pkg, a_requests, a_reviews), (b_pkg, b_requests, b_reviews)| {
+            a_reviews
+                .cmp(b_reviews)
+                .then(b_requests.len().cmp(&a_requests.len()))
+                .then_with(|| a_pkg.id.name.cmp(&b_pkg.id.name))
+                .then_with(|| a_pkg.version.cmp(&b_pkg.version))
+        },
+    );
+
+    for (package, requests, reviews) in &queue {
+        println!(
+            "{} {} reviews: {}/{} requested by: {}",
+            package.id.name,
+            package.version,
+            reviews,
+            requirements.redundancy,
+            requests.len()
+        );
+        for request in requests {
+            print!("\\_ {}", request.from().id);
+            if let Some(line) = request.comment.lines().next() {
+                print!(" {line}");
+            }
+            println!();
+        }
+    }
+    eprintln!("Review with `cargo crev crate review -u <name> <version>`");
+
+    Ok(())
+}
+
+/// Parse `--publish-after`: RFC 3339, or a `YYYY-MM-DD` day (from local midnight)
+pub fn parse_publish_after(s: &str) -> Result<Date> {
+    if let Ok(date) = chrono::DateTime::parse_from_rfc3339(s) {
+        return Ok(date);
+    }
+    let day = chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .map_err(|_| format_err!("Invalid date `{}`: use YYYY-MM-DD or RFC 3339", s))?;
+    let date = day
+        .and_time(chrono::NaiveTime::MIN)
+        .and_local_timezone(chrono::Local)
+        .earliest()
+        .ok_or_else(|| format_err!("Invalid date `{}`: no such local time", s))?;
+    Ok(date.fixed_offset())
+}
+
+pub fn list_embargoed(full: bool) -> Result<()> {
+    let local = crev_lib::Local::auto_open()?;
+    let now = crev_common::now();
+    for embargoed in local.proof_embargo().list()? {
+        if full {
+            println!("--- {}\n{}", embargoed.name, embargoed.proof);
+        } else {
+            println!(
+                "{} {} {}{}",
+                embargoed.name,
+                embargoed.publish_after.to_rfc3339(),
+                embargoed.proof.kind(),
+                if embargoed.is_due(&now) { " (due)" } else { "" }
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Store embargoed proofs named `names` right away, or all due ones if none are named
+pub fn release_embargoed(names: &[String]) -> Result<()> {
+    let local = crev_lib::Local::auto_open()?;
+    let count = if names.is_empty() {
+        local.release_due_embargoed_proofs()?
+    } else {
+        let embargo = local.proof_embargo();
+        let mut to_release = vec![];
+        for prefix in names {
+            let mut found = embargo.find(prefix)?;
+            if found.len() > 1 {
+                bail!("More than one embargoed proof starts with {}", prefix);
+            }
+            let Some(embargoed) = found.pop() else {
+                bail!("No embargoed proof named {}", prefix);
+            };
+            to_release.push(embargoed);
+        }
+        local.release_embargoed_proofs(&to_release)?
+    };
+    eprintln!("Stored {count} embargoed proofs. Use `cargo crev publish` to push them.");
+    Ok(())
+}
@@ -0,0 +1,289 @@
+//! Changelog and release notes of the crate version under review
+//!
+//! Taken from the changelog shipped in the crate, or else from its upstream
+//! repository (`package.repository` in its manifest): the message of the
+//! release tag, or the changelog there. The upstream repository is only
+//! cloned with `fetch-release-notes` set in the config, and up to
+//! [`MAX_UPSTREAM_BYTES`]. Upstream results are cached.
+use crate::prelude::*;
+use cargo::core::Package;
+use crev_common::{sanitize_name_for_fs, sanitize_url_for_fs};
+use crev_lib::Local;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Name of the file with the release notes in sanitized crate copies
+pub const RELEASE_NOTES_FILE_NAME: &str = "RELEASE-NOTES-CREV.md";
+
+/// Release notes longer than that are cut short in review drafts
+const MAX_DRAFT_LINES: usize = 40;
+
+/// Upstream repositories bigger than that aren't fetched
+pub const MAX_UPSTREAM_BYTES: u64 = 100 * 1024 * 1024;
+
+const CHANGELOG_NAMES: &[&str] = &[
+    "changelog",
+    "changes",
+    "history",
+    "news",
+    "release-notes",
+    "releases",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseNotes {
+    /// Where it was found, eg. `CHANGELOG.md` or `tag v1.2.3 of https://...`
+    pub source: String,
+    pub text: String,
+}
+
+impl ReleaseNotes {
+    /// For the leading comment of a review draft
+    pub fn draft_comment(&self) -> String {
+        let lines: Vec<_> = self.text.lines().collect();
+        let mut comment = format!("Release notes (from {}):", self.source);
+        for line in lines.iter().take(MAX_DRAFT_LINES) {
+            comment.push_str("\n  ");
+            comment.push_str(line);
+        }
+        if lines.len() > MAX_DRAFT_LINES {
+            comment.push_str(&format!(
+                "\n  ... {} more lines",
+                lines.len() - MAX_DRAFT_LINES
+            ));
+        }
+        comment
+    }
+
+    /// Write to `dir` (a sanitized crate copy) as a read-only file
+    pub fn store_in(&self, dir: &Path) -> Result<()> {
+        let path = dir.join(RELEASE_NOTES_FILE_NAME);
+        if path.exists() {
+            let mut permissions = std::fs::metadata(&path)?.permissions();
+            #[allow(clippy::permissions_set_readonly_false)]
+            permissions.set_readonly(false);
+            std::fs::set_permissions(&path, permissions)?;
+        }
+        std::fs::write(
+            &path,
+            format!("<!-- from {} -->\n\n{}\n", self.source, self.text),
+        )?;
+        let mut permissions = std::fs::metadata(&path)?.permissions();
+        permissions.set_readonly(true);
+        std::fs::set_permissions(&path, permissions)?;
+        Ok(())
+    }
+}
+
+/// Is the line a heading for `version`, eg. `## [1.2.3] - 2021-01-01` or `# v1.2.3`
+fn heading_level_for_version(line: &str, version: &str) -> Option<usize> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    if level == 0 {
+        return None;
+    }
+    let is_version_char = |c: char| c.is_ascii_alphanumeric() || c == '.' || c == '-' || c == '+';
+    let mentions_version = line.match_indices(version).any(|(start, _)| {
+        let before = line[..start].chars().next_back();
+        let after = line[start + version.len()..].chars().next();
+        before.map_or(true, |c| !(c.is_ascii_digit() || c == '.'))
+            && after.map_or(true, |c| !is_version_char(c))
+    });
+    mentions_version.then_some(level)
+}
+
+/// The section of a markdown changelog about `version`, without its heading
+fn extract_version_section(changelog: &str, version: &str) -> Option<String> {
+    let mut lines = changelog.lines();
+    let level = lines
+        .by_ref()
+        .find_map(|line| heading_level_for_version(line, version))?;
+    let section: Vec<_> = lines
+        .take_while(|line| {
+            let line_level = line.chars().take_while(|&c| c == '#').count();
+            line_level == 0 || line_level > level
+        })
+        .collect();
+    let section = section.join("\n");
+    let section = section.trim();
+    (!section.is_empty()).then(|| section.to_owned())
+}
+
+/// Changelog files in `dir`, by their usual names
+fn find_changelogs(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return vec![];
+    };
+    let mut found: Vec<_> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            path.file_stem()
+                .and_then(|stem| stem.to_str())
+                .map(|stem| stem.to_ascii_lowercase().replace('_', "-"))
+                .is_some_and(|stem| CHANGELOG_NAMES.contains(&stem.as_str()))
+        })
+        .collect();
+    found.sort();
+    found
+}
+
+fn from_changelogs_in(dir: &Path, version: &str, source_suffix: &str) -> Option<ReleaseNotes> {
+    find_changelogs(dir).into_iter().find_map(|path| {
+        let text = std::fs::read_to_string(&path).ok()?;
+        let text = extract_version_section(&text, version)?;
+        let file_name = path.file_name()?.to_string_lossy();
+        Some(ReleaseNotes {
+            source: format!("{file_name}{source_suffix}"),
+            text,
+        })
+    })
+}
+
+/// Clone or update the upstream repository in the cache
+fn checkout_upstream(local: &Local, url: &str) -> Result<git2::Repository> {
+    let dir = local
+        .cache_root()
+        .join("upstream")
+        .join(sanitize_url_for_fs(url));
+    let cancel = local.cancellation_token();
+    if let Ok(repo) = git2::Repository::open(&dir) {
+        crev_lib::util::git::fetch_and_checkout_git_repo(&repo, cancel, Some(MAX_UPSTREAM_BYTES))?;
+        return Ok(repo);
+    }
+    match crev_lib::util::git::clone(url, &dir, cancel, Some(MAX_UPSTREAM_BYTES)) {
+        Ok(repo) => Ok(repo),
+        Err(e) => {
+            let _ = std::fs::remove_dir_all(&dir);
+            Err(e.into())
+        }
+    }
+}
+
+fn from_upstream(
+    local: &Local,
+    url: &str,
+    name: &str,
+    version: &str,
+) -> Result<Option<ReleaseNotes>> {
+    let repo = checkout_upstream(local, url)?;
+    for tag in [
+        format!("v{version}"),
+        version.to_owned(),
+        format!("{name}-v{version}"),
+        format!("{name}-{version}"),
+        format!("{name}@{version}"),
+    ] {
+        let Ok(reference) = repo.find_reference(&format!("refs/tags/{tag}")) else {
+            continue;
+        };
+        let message = reference
+            .peel_to_tag()
+            .ok()
+            .and_then(|tag| tag.message().map(str::trim).map(str::to_owned))
+            .filter(|message| !message.is_empty());
+        if let Some(text) = message {
+            return Ok(Some(ReleaseNotes {
+                source: format!("tag {tag} of {url}"),
+                text,
+            }));
+        }
+    }
+    let Some(workdir) = repo.workdir() else {
+        return Ok(None);
+    };
+    let suffix = format!(" of {url}");
+    Ok(from_changelogs_in(workdir, version, &suffix)
+        .or_else(|| from_changelogs_in(&workdir.join(name), version, &suffix)))
+}
+
+fn cache_path(local: &Local, name: &str, version: &str) -> PathBuf {
+    local
+        .cache_root()
+        .join("release-notes")
+        .join(sanitize_name_for_fs(name))
+        .join(format!("{}.json", sanitize_name_for_fs(version).display()))
+}
+
+/// Release notes of the version of `crate_`, if any can be found
+///
+/// Failures to fetch them are only logged; they're just extra context.
+pub fn get_release_notes(local: &Local, crate_: &Package) -> Option<ReleaseNotes> {
+    let name = crate_.name().to_string();
+    let version = crate_.version().to_string();
+    if let Some(notes) = from_changelogs_in(crate_.root(), &version, "") {
+        return Some(notes);
+    }
+
+    let url = crate_.manifest().metadata().repository.as_deref()?;
+    let fetch_enabled = local
+        .load_user_config()
+        .is_ok_and(|config| config.fetch_release_notes);
+    if !fetch_enabled {
+        return None;
+    }
+    let cache_path = cache_path(local, &name, &version);
+    if let Some(cached) = std::fs::read(&cache_path)
+        .ok()
+        .and_then(|json| serde_json::from_slice::<Option<ReleaseNotes>>(&json).ok())
+    {
+        return cached;
+    }
+    eprintln!("Fetching release notes from {url}");
+    match from_upstream(local, url, &name, &version) {
+        Ok(notes) => {
+            let stored = serde_json::to_string(&notes)
+                .map_err(anyhow::Error::from)
+                .and_then(|json| {
+                    std::fs::create_dir_all(cache_path.parent().expect("not root"))?;
+                    Ok(crev_common::store_str_to_file(&cache_path, &json)?)
+                });
+            if let Err(e) = stored {
+                log::debug!("Can't cache release notes: {}", e);
+            }
+            notes
+        }
+        Err(e) => {
+            eprintln!("Couldn't fetch release notes: {e}");
+            None
+        }
+    }
+}
+
+/// Put the release notes of `crate_` next to its sanitized copy in `dest_dir`
+pub fn add_to_sanitized_copy(local: &Local, crate_: &Package, dest_dir: &Path) {
+    if let Some(notes) = get_release_notes(local, crate_) {
+        match notes.store_in(dest_dir) {
+            Ok(()) => eprintln!("Release notes are in {RELEASE_NOTES_FILE_NAME}"),
+            Err(e) => eprintln!("Couldn't write release notes: {e}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_version_section() {
+        let changelog = "# Changelog\n\n\
+            ## [Unreleased]\n- next\n\n\
+            ## [1.10.0] - 2021-02-01\n### Added\n- ten\n\n\
+            ## [1.1.0] - 2021-01-01\n- one\n\n\
+            ## v1.0.0\n- first\n";
+        assert_eq!(
+            extract_version_section(changelog, "1.10.0").as_deref(),
+            Some("### Added\n- ten")
+        );
+        assert_eq!(
+            extract_version_section(changelog, "1.1.0").as_deref(),
+            Some("- one")
+        );
+        assert_eq!(
+            extract_version_section(changelog, "1.0.0").as_deref(),
+            Some("- first")
+        );
+        assert_eq!(extract_version_section(changelog, "1.0"), None);
+        assert_eq!(extract_version_section(changelog, "0.1.0"), None);
+    }
+}
@@ -21,7 +21,8 @@ use cargo::{
     },
 };
 use cargo_platform::Cfg;
-use petgraph::graph::NodeIndex;
+use crev_data::{proof, SOURCE_CRATES_IO};
+use petgraph::{graph::NodeIndex, visit::EdgeRef};
 use std::{
     collections::{hash_map::Entry, BTreeSet, HashMap, HashSet},
     env,
@@ -38,9 +39,98 @@ struct Node {
     metadata: ManifestMetadata,
 }
 
+/// A dependency of one package on another
+#[derive(Debug, Clone, Copy)]
+struct Edge {
+    kind: DepKind,
+    optional: bool,
+    /// Only for some targets (`[target.'cfg(...)'.dependencies]`)
+    target_specific: bool,
+}
+
+/// What a crate is needed for, along one path of dependencies from the roots
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct PathUse {
+    kind: DepKind,
+    optional: bool,
+    target_specific: bool,
+}
+
+impl PathUse {
+    const ROOT: Self = Self {
+        kind: DepKind::Normal,
+        optional: false,
+        target_specific: false,
+    };
+
+    /// Dependencies of dev dependencies are only for tests too, and those of
+    /// build dependencies only for the build
+    fn then(self, edge: Edge) -> Self {
+        let kind = match (self.kind, edge.kind) {
+            (DepKind::Development, _) | (_, DepKind::Development) => DepKind::Development,
+            (DepKind::Build, _) | (_, DepKind::Build) => DepKind::Build,
+            (DepKind::Normal, DepKind::Normal) => DepKind::Normal,
+        };
+        Self {
+            kind,
+            optional: self.optional || edge.optional,
+            target_specific: self.target_specific || edge.target_specific,
+        }
+    }
+}
+
+/// How a crate is used by the roots, over all paths of dependencies to it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DependencyUse {
+    /// Compiled into the roots
+    pub runtime: bool,
+    /// Compiled for build scripts
+    pub build: bool,
+    /// Only for tests, examples and benchmarks
+    pub dev: bool,
+    /// Only needed because of optional dependencies (enabled features)
+    pub optional: bool,
+    /// Only needed on some targets
+    pub target_specific: bool,
+}
+
+impl Default for DependencyUse {
+    /// Like a root: used at runtime, unconditionally
+    fn default() -> Self {
+        Self {
+            runtime: true,
+            build: false,
+            dev: false,
+            optional: false,
+            target_specific: false,
+        }
+    }
+}
+
+impl DependencyUse {
+    fn from_paths(paths: impl IntoIterator<Item = PathUse>) -> Self {
+        let mut dep_use = Self {
+            runtime: false,
+            optional: true,
+            target_specific: true,
+            ..Self::default()
+        };
+        for path in paths {
+            match path.kind {
+                DepKind::Normal => dep_use.runtime = true,
+                DepKind::Build => dep_use.build = true,
+                DepKind::Development => dep_use.dev = true,
+            }
+            dep_use.optional &= path.optional;
+            dep_use.target_specific &= path.target_specific;
+        }
+        dep_use
+    }
+}
+
 #[derive(Debug)]
 pub struct Graph {
-    graph: petgraph::Graph<Node, DepKind>,
+    graph: petgraph::Graph<Node, Edge>,
     nodes: HashMap<PackageId, NodeIndex>,
 }
 
@@ -106,6 +196,38 @@ impl Graph {
 
         processed
     }
+
+    /// How every crate in the graph is used by `roots`
+    pub fn dependency_uses(&self, roots: &[PackageId]) -> HashMap<PackageId, DependencyUse> {
+        let mut reached: HashSet<(NodeIndex, PathUse)> = HashSet::new();
+        let mut pending: Vec<_> = roots
+            .iter()
+            .filter_map(|root| Some((*self.nodes.get(root)?, PathUse::ROOT)))
+            .collect();
+        while let Some((node_idx, path)) = pending.pop() {
+            if !reached.insert((node_idx, path)) {
+                continue;
+            }
+            for edge in self
+                .graph
+                .edges_directed(node_idx, petgraph::Direction::Outgoing)
+            {
+                pending.push((edge.target(), path.then(*edge.weight())));
+            }
+        }
+
+        let mut paths_by_node: HashMap<NodeIndex, Vec<PathUse>> = HashMap::new();
+        for (node_idx, path) in reached {
+            paths_by_node.entry(node_idx).or_default().push(path);
+        }
+        paths_by_node
+            .into_iter()
+            .filter_map(|(node_idx, paths)| {
+                let id = self.graph.node_weight(node_idx)?.id;
+                Some((id, DependencyUse::from_paths(paths)))
+            })
+            .collect()
+    }
 }
 
 fn get_cfgs(rustc: &Rustc, target: Option<&str>) -> Result<Vec<Cfg>> {
@@ -166,6 +288,7 @@ fn build_graph<'a>(
     target: Option<&str>,
     cfgs: &[Cfg],
     dev_dependencies: bool,
+    build_dependencies: bool,
 ) -> CargoResult<Graph> {
     let mut graph = Graph {
         graph: petgraph::Graph::new(),
@@ -196,7 +319,10 @@ fn build_graph<'a>(
                     let is_local = !d.source_id().is_registry();
                     // Dev/build dependencies can lead to circular dependencies (in combination with normal deps),
                     // so ignore dev deps on local crates, as it's not helpful anyway
-                    d.kind() == DepKind::Normal || (dev_dependencies && !is_local)
+                    d.kind() == DepKind::Normal
+                        || (!is_local
+                            && (dev_dependencies
+                                || (build_dependencies && d.kind() == DepKind::Build)))
                 })
                 .filter(|d| {
                     d.platform()
@@ -220,7 +346,12 @@ fn build_graph<'a>(
                         *e.insert(graph.graph.add_node(node))
                     }
                 };
-                graph.graph.add_edge(idx, dep_idx, dep.kind());
+                let edge = Edge {
+                    kind: dep.kind(),
+                    optional: dep.is_optional(),
+                    target_specific: dep.platform().is_some(),
+                };
+                graph.graph.add_edge(idx, dep_idx, edge);
             }
         }
     }
@@ -288,11 +419,63 @@ fn prune_directory_source_replacements(
     Ok(())
 }
 
+/// Source ids (as in `Cargo.lock`) of registries replacing crates.io, directly or
+/// through other replacements, eg. `registry+https://mirror.example.com/index`
+fn crates_io_mirror_source_urls(config: &HashMap<String, ConfigValue>) -> BTreeSet<String> {
+    let mut urls = BTreeSet::new();
+    let Some(ConfigValue::Table(source_config, _)) = config.get("source") else {
+        return urls;
+    };
+    let mut visited = HashSet::new();
+    let mut source_name = "crates-io";
+    while visited.insert(source_name) {
+        let Some(ConfigValue::Table(entry, _)) = source_config.get(source_name) else {
+            break;
+        };
+        if let Some(ConfigValue::String(url, _)) = entry.get("registry") {
+            let url = url.trim_end_matches('/');
+            urls.insert(if url.starts_with("sparse+") {
+                url.to_owned()
+            } else {
+                format!("registry+{url}")
+            });
+        }
+        match entry.get("replace-with") {
+            Some(ConfigValue::String(replacement, _)) => source_name = replacement,
+            _ => break,
+        }
+    }
+    urls
+}
+
+/// Registries whose crates are reviewed as crates.io crates (`--map-crates-io-mirrors`)
+#[derive(Debug, Clone, Default)]
+pub struct CratesIoMirrors {
+    source_urls: BTreeSet<String>,
+}
+
+impl CratesIoMirrors {
+    pub fn is_mirror(&self, source_id: SourceId) -> bool {
+        let url = source_id.as_url().to_string();
+        self.source_urls.contains(url.trim_end_matches('/'))
+    }
+
+    /// Like [`crate::cargo_pkg_id_to_crev_pkg_id`], but mirrors map to crates.io
+    pub fn crev_pkg_id(&self, id: &PackageId) -> proof::PackageVersionId {
+        let mut crev_pkg_id = crate::cargo_pkg_id_to_crev_pkg_id(id);
+        if self.is_mirror(id.source_id()) {
+            crev_pkg_id.id.source = SOURCE_CRATES_IO.into();
+        }
+        crev_pkg_id
+    }
+}
+
 /// A handle to the current Rust project
 pub struct Repo {
     config: GlobalContext,
     cargo_opts: opts::CargoOpts,
     features_list: Vec<String>,
+    crates_io_mirrors: CratesIoMirrors,
 }
 
 impl Repo {
@@ -309,6 +492,11 @@ impl Repo {
         })
     }
 
+    /// `Cargo.lock` of the workspace
+    pub fn get_lockfile_path(&self) -> Result<PathBuf> {
+        Ok(self.workspace()?.root().join("Cargo.lock"))
+    }
+
     pub fn auto_open_cwd(cargo_opts: opts::CargoOpts) -> Result<Self> {
         let mut config = GlobalContext::default()?;
 
@@ -327,6 +515,16 @@ impl Repo {
         config.load_values()?;
         prune_directory_source_replacements(config.values_mut()?)?;
 
+        let crates_io_mirrors = if cargo_opts.map_crates_io_mirrors {
+            let source_urls = crates_io_mirror_source_urls(config.values()?);
+            for url in &source_urls {
+                eprintln!("Warning: crates from `{url}` are treated as crates.io crates");
+            }
+            CratesIoMirrors { source_urls }
+        } else {
+            CratesIoMirrors::default()
+        };
+
         // how it used to be; can't find it anywhere anymore
         // let features_set =
         //     Method::split_features(&[cargo_opts.features.clone().unwrap_or_else(String::new)]);
@@ -344,9 +542,14 @@ impl Repo {
             config,
             cargo_opts,
             features_list,
+            crates_io_mirrors,
         })
     }
 
+    pub fn crates_io_mirrors(&self) -> &CratesIoMirrors {
+        &self.crates_io_mirrors
+    }
+
     fn workspace(&self) -> Result<Workspace<'_>> {
         Workspace::new(&self.get_manifest_path()?, &self.config)
     }
@@ -371,7 +574,13 @@ impl Repo {
         Ok((workspace, registry))
     }
 
-    pub fn get_dependency_graph(&self, roots: Vec<PackageId>) -> CargoResult<Graph> {
+    /// Graph of the dependencies of `roots`, with build dependencies also
+    /// without `--dev-dependencies` if `build_dependencies` is set
+    pub fn get_dependency_graph(
+        &self,
+        roots: Vec<PackageId>,
+        build_dependencies: bool,
+    ) -> CargoResult<Graph> {
         let (workspace, registry) = self.get_registry_from_workspace_members()?;
 
         let (packages, resolve) = our_resolve(
@@ -385,11 +594,18 @@ impl Repo {
         let rustc = self.config.load_global_rustc(Some(&workspace))?;
         let host = rustc.host.to_string();
 
-        let target = self
-            .cargo_opts
-            .target
-            .as_ref()
-            .map(|target| target.as_ref().unwrap_or(&host).as_str());
+        // dependencies of other platforms don't get compiled, so they're left out
+        let target = if self.cargo_opts.all_platforms {
+            None
+        } else {
+            Some(
+                self.cargo_opts
+                    .target
+                    .as_ref()
+                    .and_then(|target| target.as_deref())
+                    .unwrap_or(&host),
+            )
+        };
 
         let cfgs = get_cfgs(&rustc, target)?;
         let graph = build_graph(
@@ -399,6 +615,7 @@ impl Repo {
             target,
             &cfgs,
             self.cargo_opts.dev_dependencies()?,
+            build_dependencies,
         )?;
 
         Ok(graph)
@@ -543,28 +760,64 @@ impl Repo {
         name: &str,
         version: Option<&Version>,
     ) -> Result<Option<PackageId>> {
-        let mut ret = vec![];
+        let mut exact = vec![];
+        let mut fuzzy = vec![];
 
         self.for_every_non_local_dep_crate_id(|pkg_id| {
-            if name == pkg_id.name().as_str()
-                && (version.is_none() || version == Some(pkg_id.version()))
-            {
-                ret.push(*pkg_id);
+            if version.is_some() && version != Some(pkg_id.version()) {
+                return Ok(());
+            }
+            if name == pkg_id.name().as_str() {
+                exact.push(*pkg_id);
+            } else if let Some(score) = fuzzy_name_score(name, pkg_id.name().as_str()) {
+                fuzzy.push((score, *pkg_id));
             }
             Ok(())
         })?;
 
-        match ret.len() {
+        let is_exact = !exact.is_empty();
+        let mut candidates = if is_exact {
+            exact.sort();
+            exact
+        } else {
+            fuzzy.sort();
+            fuzzy.into_iter().map(|(_, pkg_id)| pkg_id).collect()
+        };
+        candidates.dedup();
+
+        match candidates.len() {
             0 => Ok(None),
-            1 => Ok(Some(ret[0])),
-            n => bail!(
-                "Ambiguous selection: {} matches found: {}",
-                n,
-                ret.iter()
-                    .map(|pkgid| pkgid.version().to_string())
-                    .collect::<Vec<_>>()
-                    .join(", ")
-            ),
+            1 if is_exact => Ok(Some(candidates[0])),
+            n => {
+                let labels: Vec<_> = candidates
+                    .iter()
+                    .map(|pkg_id| {
+                        format!(
+                            "{} {} ({})",
+                            pkg_id.name(),
+                            pkg_id.version(),
+                            pkg_id.source_id()
+                        )
+                    })
+                    .collect();
+                // a similar name is not necessarily the crate that was meant
+                let prompt = if n == 1 {
+                    format!("No crate named '{name}', but a similar one was found:")
+                } else {
+                    format!("Multiple crates match '{name}':")
+                };
+                match crate::shared::select_interactively(&prompt, &labels)? {
+                    Some(i) => Ok(Some(candidates[i])),
+                    None if n == 1 => {
+                        bail!("No crate named '{}'. Did you mean {}?", name, labels[0])
+                    }
+                    None => bail!(
+                        "Ambiguous selection: {} matches found: {}",
+                        n,
+                        labels.join(", ")
+                    ),
+                }
+            }
         }
     }
 
@@ -580,6 +833,32 @@ impl Repo {
         Ok(package_set.get_one(*pkg_id)?.clone())
     }
 
+    /// Checksum of the `.crate` file in the registry index (hex of sha256)
+    ///
+    /// `None` if the registry has no checksum for it, or it's not from crates.io.
+    pub fn get_registry_checksum(&self, pkg_id: &PackageId) -> Result<Option<String>> {
+        let mut yanked_whitelist = HashSet::default();
+        yanked_whitelist.insert(*pkg_id);
+        let mut source = self.load_source_with_whitelist(yanked_whitelist)?;
+        let version_req = format!("={}", pkg_id.version());
+        let dependency_request =
+            Dependency::parse(pkg_id.name(), Some(&version_req), source.source_id())?;
+        let _lock = self
+            .config
+            .acquire_package_cache_lock(CacheLockMode::DownloadExclusive)?;
+        let summaries = loop {
+            match source.query_vec(&dependency_request, QueryKind::Exact) {
+                std::task::Poll::Ready(res) => break res?,
+                std::task::Poll::Pending => source.block_until_ready()?,
+            }
+        };
+        Ok(summaries
+            .iter()
+            .find(|s| s.package_id() == *pkg_id)
+            .and_then(|s| s.as_summary().checksum())
+            .map(str::to_owned))
+    }
+
     pub fn find_independent_pkg_id_by_selector(
         &self,
         name: &str,
@@ -660,11 +939,86 @@ impl Repo {
     }
 }
 
+/// Normalize crate name for fuzzy matching: `Foo_Bar` is the same as `foo-bar`
+fn normalize_crate_name(name: &str) -> String {
+    name.to_lowercase().replace('_', "-")
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut cur = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(ca != *cb);
+            cur.push(substitution.min(prev[j + 1] + 1).min(cur[j] + 1));
+        }
+        prev = cur;
+    }
+    prev[b.len()]
+}
+
+/// How well `query` matches crate `name`, lower is better; `None` if not at all
+///
+/// Ignores case and `-`/`_`, then prefers prefixes, substrings and
+/// finally names a couple of typos away.
+fn fuzzy_name_score(query: &str, name: &str) -> Option<usize> {
+    let query = normalize_crate_name(query);
+    let name = normalize_crate_name(name);
+    if query == name {
+        Some(0)
+    } else if name.starts_with(&query) {
+        Some(1)
+    } else if name.contains(&query) {
+        Some(2)
+    } else {
+        let distance = edit_distance(&query, &name);
+        (distance <= (query.len() / 4).max(1)).then_some(2 + distance)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use cargo::util::context::Definition;
 
+    #[test]
+    fn dependency_use_over_all_paths() {
+        let edge = |kind, optional| Edge {
+            kind,
+            optional,
+            target_specific: false,
+        };
+        let build = PathUse::ROOT.then(edge(DepKind::Build, false));
+        // a normal dependency of a build dependency is built for the build script
+        let build_dep_dep = build.then(edge(DepKind::Normal, true));
+        assert_eq!(build_dep_dep.kind, DepKind::Build);
+        let dev = PathUse::ROOT.then(edge(DepKind::Development, false));
+        assert_eq!(
+            dev.then(edge(DepKind::Build, false)).kind,
+            DepKind::Development
+        );
+
+        let dep_use = DependencyUse::from_paths([build_dep_dep]);
+        assert!(dep_use.build && !dep_use.runtime && !dep_use.dev);
+        assert!(dep_use.optional);
+
+        let runtime = PathUse::ROOT.then(edge(DepKind::Normal, false));
+        let dep_use = DependencyUse::from_paths([build_dep_dep, runtime]);
+        assert!(dep_use.build && dep_use.runtime);
+        // it's not only there because of the optional dependency
+        assert!(!dep_use.optional);
+    }
+
+    #[test]
+    fn fuzzy_name_score_ranks_matches() {
+        assert_eq!(fuzzy_name_score("Serde_Json", "serde-json"), Some(0));
+        assert_eq!(fuzzy_name_score("serde", "serde_json"), Some(1));
+        assert_eq!(fuzzy_name_score("json", "serde_json"), Some(2));
+        assert_eq!(fuzzy_name_score("toko", "tokio"), Some(3));
+        assert_eq!(fuzzy_name_score("rand", "tokio"), None);
+    }
+
     #[test]
     fn test_prune_directory_source_replacement() {
         // Test that:
@@ -834,4 +1188,61 @@ mod tests {
         prune_directory_source_replacements(&mut config_table).unwrap();
         assert_eq!(config_table, expected_config_table);
     }
+
+    #[test]
+    fn test_crates_io_mirror_source_urls() {
+        // crates-io -> corp-mirror -> corp-sparse-mirror; unrelated-registry is not a mirror
+        let table = |entries: &[(&str, &str)]| {
+            ConfigValue::Table(
+                entries
+                    .iter()
+                    .map(|(k, v)| {
+                        (
+                            k.to_string(),
+                            ConfigValue::String(v.to_string(), Definition::Cli(None)),
+                        )
+                    })
+                    .collect(),
+                Definition::Cli(None),
+            )
+        };
+        let source_table = ConfigValue::Table(
+            [
+                (
+                    "crates-io".into(),
+                    table(&[("replace-with", "corp-mirror")]),
+                ),
+                (
+                    "corp-mirror".into(),
+                    table(&[
+                        ("registry", "https://mirror.example.com/index/"),
+                        ("replace-with", "corp-sparse-mirror"),
+                    ]),
+                ),
+                (
+                    "corp-sparse-mirror".into(),
+                    table(&[("registry", "sparse+https://sparse.example.com/index/")]),
+                ),
+                (
+                    "unrelated-registry".into(),
+                    table(&[("registry", "https://other.example.com/index")]),
+                ),
+            ]
+            .into_iter()
+            .collect(),
+            Definition::Cli(None),
+        );
+        let config_table = [("source".into(), source_table)].into_iter().collect();
+
+        assert_eq!(
+            crates_io_mirror_source_urls(&config_table),
+            [
+                "registry+https://mirror.example.com/index".to_string(),
+                "sparse+https://sparse.example.com/index".to_string(),
+            ]
+            .into_iter()
+            .collect()
+        );
+        assert!(crates_io_mirror_source_urls(&HashMap::new()).is_empty());
+    }
 }
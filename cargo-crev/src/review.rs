@@ -1,5 +1,5 @@
 use crate::{
-    edit,
+    crates_io, edit, lint,
     opts::CargoOpts,
     opts::{self, ReviewCrateSelector},
     prelude::*,
@@ -7,13 +7,19 @@ use crate::{
 };
 use anyhow::format_err;
 use crev_data::{
-    proof::{self, ContentExt},
-    Rating, SOURCE_CRATES_IO,
+    proof::{self, CommonOps, ContentExt},
+    DigestType, Rating, SOURCE_CRATES_IO,
 };
-use crev_lib::{self, local::Local, TrustProofType};
-use std::{default::Default, fmt::Write};
+use crev_lib::{
+    self, attestation,
+    local::Local,
+    usage::UsageEvent,
+    verify::{get_git_crate_digest, get_git_crate_digest_with},
+    DigestIgnorePolicy, TrustProofType,
+};
+use std::{default::Default, fmt::Write, path::Path};
 
-use crate::{repo::Repo, shared::*};
+use crate::{provenance::Provenance, repo::Repo, shared::*};
 
 /// Review a crate
 ///
@@ -21,12 +27,17 @@ use crate::{repo::Repo, shared::*};
 #[allow(clippy::option_option)]
 pub fn create_review_proof(
     crate_sel: &ReviewCrateSelector,
-    report_severity: Option<crev_data::Level>,
+    report_common: Option<opts::ReportCommon>,
     advise_common: Option<opts::AdviseCommon>,
     trust: TrustProofType,
     proof_create_opt: &opts::CommonProofCreate,
     skip_activity_check: bool,
     show_override_suggestions: bool,
+    from_patch: Option<&Path>,
+    subtree: Option<(&str, &[String])>,
+    covered_paths: &[String],
+    force: bool,
+    digest_policy: &DigestIgnorePolicy,
     cargo_opts: CargoOpts,
 ) -> Result<()> {
     let diff_version = &crate_sel.diff;
@@ -63,6 +74,23 @@ pub fn create_review_proof(
     );
     let local = Local::auto_open()?;
 
+    let patch = from_patch
+        .map(|path| crate::patch::check_patch(path, crate_root))
+        .transpose()?
+        .map(|checked| {
+            if !checked.mismatches.is_empty() {
+                eprintln!(
+                    "Warning: the patch doesn't apply to {} v{}:",
+                    pkg_id.name(),
+                    effective_crate_version
+                );
+                for mismatch in &checked.mismatches {
+                    eprintln!("  {mismatch}");
+                }
+            }
+            checked.patch
+        });
+
     let diff_base_version = match crate_review_activity_check(
         &local,
         &pkg_id.name(),
@@ -100,16 +128,104 @@ pub fn create_review_proof(
         Err(ActivityCheckError::Other(e)) => return Err(e.into()),
     };
 
-    let (digest_clean, vcs) =
-        check_package_clean_state(&repo, crate_root, &crate_.name(), effective_crate_version)?;
+    let source_id = pkg_id.source_id();
+    let source = crate::crev_review_source(&source_id);
+    let (digest_clean, revision) = if source_id.is_git() {
+        // only committed files are digested, so the checkout doesn't need to be clean
+        let digest = get_git_crate_digest(crate_root, digest_policy.ignore_list())?;
+        let revision = source_id.precise_git_fragment().unwrap_or_default();
+        (digest, revision.to_owned())
+    } else {
+        let (digest, vcs) = check_package_clean_state(
+            &repo,
+            &local,
+            crate_root,
+            &crate_.name(),
+            effective_crate_version,
+            digest_policy,
+        )?;
+        (digest, vcs_info_to_revision_string(vcs))
+    };
+
+    let provenance = Provenance::check(&repo, &crate_, digest_clean.clone(), digest_policy);
+    if provenance.is_mismatch() {
+        eprintln!("{}", provenance.banner());
+        if !force {
+            bail!(
+                "The source of {} v{} doesn't match the registry checksum. \
+                 Use `--force` to review it anyway.",
+                pkg_id.name(),
+                effective_crate_version
+            );
+        }
+    }
+
+    // the provenance check compares digests made the default way
+    let digest_type = local.load_user_config()?.digest_type;
+    let digest_clean = if digest_type == DigestType::default() {
+        digest_clean
+    } else if source_id.is_git() {
+        get_git_crate_digest_with(crate_root, digest_policy.ignore_list(), digest_type)?
+    } else {
+        digest_policy.digest_with(crate_root, digest_type)?
+    };
+
+    let subtree = subtree
+        .map(|(path, exclude)| {
+            let path = path.trim_end_matches('/').replace('\\', "/");
+            let exclude: Vec<_> = exclude
+                .iter()
+                .map(|p| p.trim_end_matches('/').replace('\\', "/"))
+                .collect();
+            if let Some(invalid) = std::iter::once(&path)
+                .chain(&exclude)
+                .find(|p| !proof::review::Subtree::is_valid_path(p))
+            {
+                bail!("`{}` must be a relative path without `.` or `..`", invalid);
+            }
+            let digest = crev_lib::get_subtree_digest(
+                crate_root,
+                &path,
+                &exclude,
+                digest_policy.ignore_list(),
+            )?;
+            Ok(proof::review::Subtree {
+                path,
+                exclude,
+                digest: digest.into_vec(),
+            })
+        })
+        .transpose()?;
+
+    let covered_paths: Vec<_> = covered_paths
+        .iter()
+        .map(|p| p.trim_end_matches('/').replace('\\', "/"))
+        .collect();
+    for path in &covered_paths {
+        if !proof::review::Subtree::is_valid_path(path) {
+            bail!("`{}` must be a relative path without `.` or `..`", path);
+        }
+        if !crate_root.join(path).exists() {
+            bail!(
+                "`{path}` is not in {} {effective_crate_version}",
+                crate_.name()
+            );
+        }
+    }
 
     let diff_base = if let Some(ref diff_base_version) = diff_base_version {
         let crate_id = repo.find_pkgid(&crate_.name(), Some(diff_base_version), true)?;
         let crate_ = repo.get_crate(&crate_id)?;
         let crate_root = crate_.root();
 
-        let (digest, vcs) =
-            check_package_clean_state(&repo, crate_root, &crate_.name(), diff_base_version)?;
+        let (digest, vcs) = check_package_clean_state(
+            &repo,
+            &local,
+            crate_root,
+            &crate_.name(),
+            diff_base_version,
+            digest_policy,
+        )?;
 
         Some(proof::PackageInfo {
             id: proof::PackageVersionId::new(
@@ -121,6 +237,7 @@ pub fn create_review_proof(
             digest_type: proof::default_digest_type(),
             revision: vcs_info_to_revision_string(vcs),
             revision_type: proof::default_revision_type(),
+            digest_ignore: Some(digest_policy.id().to_owned()),
         })
     } else {
         None
@@ -130,19 +247,15 @@ pub fn create_review_proof(
 
     let db = local.load_db()?;
 
-    let default_review_content = if advise_common.is_some() || report_severity.is_some() {
+    let is_advisory_or_issue = advise_common.is_some() || report_common.is_some();
+    let default_review_content = if is_advisory_or_issue {
         crev_data::Review::new_none()
     } else {
         trust.to_review()
     };
 
     let (previous_date, mut review) = if let Some(mut previous_review) = db
-        .get_pkg_review(
-            SOURCE_CRATES_IO,
-            &crate_.name(),
-            effective_crate_version,
-            &id.id.id,
-        )
+        .get_pkg_review(&source, &crate_.name(), effective_crate_version, &id.id.id)
         .cloned()
     {
         if trust == TrustProofType::Untrust {
@@ -154,14 +267,15 @@ pub fn create_review_proof(
             .from(id.id.clone())
             .package(proof::PackageInfo {
                 id: proof::PackageVersionId::new(
-                    SOURCE_CRATES_IO.to_owned(),
+                    source.clone(),
                     crate_.name().to_string(),
                     effective_crate_version.clone(),
                 ),
                 digest: digest_clean.into_vec(),
-                digest_type: proof::default_digest_type(),
-                revision: vcs_info_to_revision_string(vcs),
+                digest_type: digest_type.to_string(),
+                revision,
                 revision_type: proof::default_revision_type(),
+                digest_ignore: Some(digest_policy.id().to_owned()),
             })
             .review(default_review_content)
             .diff_base(diff_base)
@@ -186,11 +300,16 @@ pub fn create_review_proof(
     if let Some(advise_common) = advise_common {
         let mut advisory: proof::review::package::Advisory = advise_common.affected.into();
         advisory.severity = advise_common.severity;
+        advisory.ids = advise_common.ids;
+        advisory.comment = advise_common.comment;
         review.advisories.push(advisory);
     }
-    if let Some(severity) = report_severity {
-        let mut report = proof::review::package::Issue::new_with_severity(String::new(), severity);
-        report.severity = severity;
+    if let Some(report_common) = report_common {
+        let mut report = proof::review::package::Issue::new_with_severity(
+            report_common.id,
+            report_common.severity,
+        );
+        report.comment = report_common.comment;
         review.issues.push(report);
         review.review_possibly_none_mut().rating = Rating::Negative;
     }
@@ -207,11 +326,92 @@ pub fn create_review_proof(
         review.common.original = None;
     }
 
+    if let Some(patch) = &patch {
+        // the patch is what was reviewed, so the review is never found by
+        // (and never counts for) the digest of the whole package
+        review.package.digest.clone_from(&patch.digest);
+        review.package.digest_type = proof::default_digest_type();
+        review.patch = Some(patch.clone());
+    }
+    if let Some(subtree) = &subtree {
+        // likewise, a review of a subtree only counts for the subtree digest
+        review.package.digest.clone_from(&subtree.digest);
+        review.package.digest_type = proof::default_digest_type();
+        review.subtree = Some(subtree.clone());
+    }
+    if !covered_paths.is_empty() {
+        review.covered_paths = covered_paths;
+    }
+
+    let session = local
+        .read_review_activity(SOURCE_CRATES_IO, &crate_.name(), effective_crate_version)?
+        .and_then(|activity| activity.session)
+        .filter(|session| session.minutes.is_some());
+    let session_comment = session.as_ref().map(|session| {
+        format!(
+            "Review session: {} minutes, visited {} of {} source files. Suggested thoroughness: {}",
+            session.minutes.unwrap_or_default(),
+            session.visited.len(),
+            session.files_total,
+            session.suggested_thoroughness(),
+        )
+    });
+    if let Some(session) = session.as_ref().filter(|_| previous_date.is_none()) {
+        review.review_possibly_none_mut().thoroughness = session.suggested_thoroughness();
+    }
+
+    // help budget the review effort; not needed for advisories and issue reports
+    let module_breakdown = if !is_advisory_or_issue {
+        match crate::tokei::get_module_breakdown(crate_root) {
+            Ok(modules) => {
+                let table = crate::tokei::format_module_breakdown(&modules)?;
+                eprint!("{table}");
+                Some(table)
+            }
+            Err(e) => {
+                eprintln!("Couldn't count lines of code: {e}");
+                None
+            }
+        }
+    } else {
+        None
+    };
+    let patch_comment = patch.as_ref().map(crate::patch::coverage_comment);
+    let subtree_comment = subtree.as_ref().map(subtree_comment);
+    let release_notes_comment = if !is_advisory_or_issue {
+        crate::release_notes::get_release_notes(&local, &crate_).map(|notes| notes.draft_comment())
+    } else {
+        None
+    };
+    // advisories and issue reports don't follow the review checklist
+    let template = if !is_advisory_or_issue {
+        local.load_review_template()?
+    } else {
+        None
+    };
+    let template_comment = template.as_ref().and_then(|t| t.draft_comment());
+    let leading_comment: Vec<_> = std::iter::once(provenance.banner())
+        .chain(session_comment)
+        .chain(patch_comment)
+        .chain(subtree_comment)
+        .chain(module_breakdown)
+        .chain(release_notes_comment)
+        .chain(template_comment)
+        .collect();
+    let leading_comment = leading_comment.join("\n");
+
+    local.run_pre_sign_hook(&mut review, crate_root)?;
+    // after the hook, so its summary doesn't end up in a section
+    if let Some(template) = &template {
+        template.pre_populate(&mut review);
+    }
+
+    let crates_io = crates_io::Client::new(&local).ok();
     let mut review = edit::edit_proof_content_iteractively(
         &review,
         previous_date.as_ref(),
         diff_base_version.as_ref(),
-        None,
+        Some(leading_comment.as_str()).filter(|c| !c.is_empty()),
         |text| {
             if show_override_suggestions && review.override_.is_empty() {
                 writeln!(text, "# override:")?;
@@ -219,7 +419,7 @@ pub fn create_review_proof(
 
             if show_override_suggestions {
                 for review in db.get_package_reviews_for_package(
-                    SOURCE_CRATES_IO,
+                    &source,
                     Some(&pkg_id.name()),
                     Some(pkg_id.version()),
                 ) {
@@ -234,6 +434,13 @@ pub fn create_review_proof(
 
             Ok(())
         },
+        |review| lint::lint_package_review(review, &id.id.id, &db, crates_io.as_ref()),
+        |review| {
+            template
+                .as_ref()
+                .map_or(Ok(()), |template| template.ensure_satisfied(review))
+        },
+        proof_create_opt.draft_format,
     )?;
 
     review.touch_date();
@@ -249,25 +456,80 @@ pub fn create_review_proof(
             "Add"
         },
     );
-    maybe_store(&local, &proof, &commit_msg, proof_create_opt)
+    maybe_store(&local, &id, &proof, &commit_msg, proof_create_opt)?;
+    if !proof_create_opt.no_store {
+        let crate_name = crate_.name().as_str();
+        crate::usage::record_usage(&local, UsageEvent::Review { crate_name });
+    }
+    Ok(())
 }
 
-pub fn find_reviews(crate_: &opts::CrateSelector) -> Result<Vec<proof::review::Package>> {
+/// One line saying which part of the crate the review is of
+fn subtree_comment(subtree: &proof::review::Subtree) -> String {
+    if subtree.exclude.is_empty() {
+        format!("Review of `{}` only", subtree.path)
+    } else {
+        format!(
+            "Review of `{}` only, without: {}",
+            subtree.path,
+            subtree.exclude.join(", ")
+        )
+    }
+}
+
+pub fn find_reviews(
+    crate_: &opts::CrateSelector,
+    wot: &opts::WotOpts,
+) -> Result<Vec<proof::review::Package>> {
     let local = crev_lib::Local::auto_open()?;
     let db = local.load_db()?;
+    let is_selected_author = for_id_author_filter(&local, &db, wot)?;
     Ok(db
         .get_package_reviews_for_package(
             SOURCE_CRATES_IO,
             crate_.name.as_deref(),
             crate_.version()?,
         )
+        .filter(|review| is_selected_author(&review.from().id))
         .cloned()
         .collect())
 }
 
-pub fn list_reviews(crate_: &opts::CrateSelector) -> Result<()> {
-    for review in find_reviews(crate_)? {
-        println!("---\n{review}");
+/// Print your review of the crate as a signed in-toto attestation
+pub fn export_attestation(crate_: &opts::CrateSelector) -> Result<()> {
+    crate_.ensure_name_given()?;
+    let name = crate_.name.as_deref().expect("checked above");
+    let Some(version) = crate_.version()? else {
+        bail!("Crate version argument required!");
+    };
+
+    let local = Local::auto_open()?;
+    let id = local.read_current_unlocked_id(&term::read_passphrase)?;
+    let db = local.load_db()?;
+    let review = db
+        .get_pkg_review(SOURCE_CRATES_IO, name, version, &id.id.id)
+        .ok_or_else(|| format_err!("You haven't reviewed {} {}", name, version))?;
+
+    let statement = attestation::Statement::for_package_review(review)?;
+    let envelope = attestation::Envelope::sign(&id, &statement)?;
+    println!("{}", envelope.to_json()?);
+
+    Ok(())
+}
+
+pub fn list_reviews(args: &opts::RepoQueryReview) -> Result<()> {
+    let reviews = find_reviews(&args.crate_, &args.wot)?;
+    if args.json {
+        let reviews: Vec<_> = reviews
+            .iter()
+            .map(|review| serde_json::json!({ "review": review, "scope": review.scope() }))
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&reviews)?);
+        return Ok(());
+    }
+    for review in reviews {
+        // a YAML comment, as defaults are left out of the review itself
+        println!("---\n# {}\n{review}", review.scope());
     }
 
     Ok(())
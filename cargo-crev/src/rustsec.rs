@@ -0,0 +1,499 @@
+//! Advisory review drafts pre-filled from the RustSec advisory database
+//!
+//! RustSec (<https://rustsec.org>) already describes most known
+//! vulnerabilities in crates.io crates. Instead of retyping them, an advisory
+//! (or, when there's no fixed release, an issue) review is drafted from the
+//! RustSec entry, for the user to confirm, edit and sign.
+//!
+//! `cargo crev repo import rustsec` converts the whole database at once
+//! instead, into proofs signed by an Id kept just for them.
+use crate::{
+    opts::{self, ReviewCrateSelector},
+    prelude::*,
+    review::create_review_proof,
+};
+use crev_data::{
+    proof::{
+        self,
+        review::package::{self, VersionRange},
+        ContentExt,
+    },
+    Level, TrustLevel, SOURCE_CRATES_IO,
+};
+use crev_lib::{DigestIgnorePolicy, Local, TrustProofType};
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+const ADVISORY_DB_URL: &str = "https://github.com/rustsec/advisory-db";
+
+/// `RUSTSEC-*.md` files start with the metadata in a fenced TOML block
+const FRONT_MATTER_START: &str = "```toml";
+const FRONT_MATTER_END: &str = "```";
+
+#[derive(Debug, Deserialize)]
+struct FrontMatter {
+    advisory: Metadata,
+    #[serde(default)]
+    versions: Versions,
+}
+
+#[derive(Debug, Deserialize)]
+struct Metadata {
+    id: String,
+    package: String,
+    date: String,
+    url: Option<String>,
+    #[serde(default)]
+    aliases: Vec<String>,
+    cvss: Option<String>,
+    /// Kind of an informational advisory, eg. `unmaintained`
+    informational: Option<String>,
+    withdrawn: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Versions {
+    #[serde(default)]
+    patched: Vec<String>,
+    #[serde(default)]
+    unaffected: Vec<String>,
+}
+
+/// An entry of the RustSec advisory database
+#[derive(Debug)]
+pub struct Advisory {
+    metadata: Metadata,
+    versions: Versions,
+    title: String,
+    description: String,
+}
+
+impl Advisory {
+    pub fn parse(text: &str) -> Result<Self> {
+        let text = text.trim_start();
+        let rest = text
+            .strip_prefix(FRONT_MATTER_START)
+            .ok_or_else(|| format_err!("advisory doesn't start with a TOML block"))?;
+        let (front_matter, body) = rest
+            .split_once(&format!("\n{FRONT_MATTER_END}"))
+            .ok_or_else(|| format_err!("advisory TOML block is not closed"))?;
+        let front_matter: FrontMatter = toml::from_str(front_matter)
+            .map_err(|e| format_err!("invalid advisory metadata: {e}"))?;
+
+        let body = body.trim();
+        let (title, description) = match body.strip_prefix("# ") {
+            Some(body) => body.split_once('\n').unwrap_or((body, "")),
+            None => ("", body),
+        };
+        Ok(Self {
+            metadata: front_matter.advisory,
+            versions: front_matter.versions,
+            title: title.trim().to_owned(),
+            description: description.trim().to_owned(),
+        })
+    }
+
+    /// Lowest version of the newest patched release line, if anything was patched
+    fn first_patched_version(&self) -> Option<Version> {
+        self.versions
+            .patched
+            .iter()
+            .filter_map(|req| min_version(req))
+            .max()
+    }
+
+    /// Range of versions the advisory applies to, as seen from the first patched version
+    ///
+    /// Fixes backported to older release lines mean the advisory is only
+    /// about the release line of the fix.
+    fn range(&self) -> VersionRange {
+        if self.versions.patched.len() <= 1 {
+            VersionRange::All
+        } else {
+            VersionRange::Major
+        }
+    }
+
+    fn severity(&self) -> Level {
+        if self.metadata.informational.is_some() {
+            return Level::Low;
+        }
+        let Some(cvss) = &self.metadata.cvss else {
+            return Level::Medium;
+        };
+        // Confidentiality, Integrity and Availability impact metrics
+        let impacts: Vec<&str> = cvss
+            .split('/')
+            .filter_map(|metric| metric.split_once(':'))
+            .filter(|(name, _)| ["C", "I", "A"].contains(name))
+            .map(|(_, value)| value)
+            .collect();
+        if impacts.contains(&"H") {
+            Level::High
+        } else if impacts.contains(&"L") {
+            Level::Medium
+        } else {
+            Level::Low
+        }
+    }
+
+    /// RustSec id first, then aliases (eg. CVE and GHSA ids)
+    fn ids(&self) -> Vec<String> {
+        std::iter::once(self.metadata.id.clone())
+            .chain(self.metadata.aliases.iter().cloned())
+            .collect()
+    }
+
+    fn comment(&self) -> String {
+        let mut comment = String::new();
+        if !self.title.is_empty() {
+            comment.push_str(&self.title);
+            comment.push_str("\n\n");
+        }
+        if !self.description.is_empty() {
+            comment.push_str(&self.description);
+            comment.push_str("\n\n");
+        }
+        comment.push_str(&format!(
+            "From RustSec advisory {} ({}): https://rustsec.org/advisories/{}.html",
+            self.metadata.id, self.metadata.date, self.metadata.id
+        ));
+        if let Some(url) = &self.metadata.url {
+            comment.push_str(&format!("\nSee also: {url}"));
+        }
+        if !self.versions.patched.is_empty() {
+            comment.push_str(&format!("\nPatched: {}", self.versions.patched.join("; ")));
+        }
+        if !self.versions.unaffected.is_empty() {
+            comment.push_str(&format!(
+                "\nUnaffected: {}",
+                self.versions.unaffected.join("; ")
+            ));
+        }
+        comment
+    }
+}
+
+/// Lowest version matched by a requirement like `>= 1.2.3, < 2` or `^0.4`
+fn min_version(req: &str) -> Option<Version> {
+    req.split(',').map(str::trim).find_map(|comparator| {
+        let version = [">=", "^", "~", "="]
+            .iter()
+            .find_map(|op| comparator.strip_prefix(op))
+            .unwrap_or(comparator)
+            .trim();
+        if version.starts_with(['<', '>']) {
+            return None;
+        }
+        let mut parts: Vec<&str> = version.split('.').collect();
+        while parts.len() < 3 {
+            parts.push("0");
+        }
+        Version::parse(&parts.join(".")).ok()
+    })
+}
+
+/// Clone or update the advisory database in the cache
+///
+/// If updating fails, the copy from the last time is used.
+fn checkout_advisory_db(local: &Local) -> Result<PathBuf> {
+    let dir = local.cache_root().join("advisory-db");
+    let cancel = local.cancellation_token();
+    if let Ok(repo) = git2::Repository::open(&dir) {
+        if let Err(e) = crev_lib::util::git::fetch_and_checkout_git_repo(&repo, cancel, None) {
+            log::warn!("Couldn't update the RustSec advisory database: {e}");
+        }
+        return Ok(dir);
+    }
+    eprintln!("Fetching the RustSec advisory database from {ADVISORY_DB_URL}");
+    if let Err(e) = crev_lib::util::git::clone(ADVISORY_DB_URL, &dir, cancel, None) {
+        let _ = std::fs::remove_dir_all(&dir);
+        bail!("Couldn't fetch the RustSec advisory database: {e}");
+    }
+    Ok(dir)
+}
+
+/// Find and parse advisory `id` in the database checked out in `db_dir`
+fn find_advisory(db_dir: &Path, id: &str) -> Result<Advisory> {
+    let file_name = format!("{id}.md");
+    let crates_dir = db_dir.join("crates");
+    for entry in std::fs::read_dir(&crates_dir)
+        .map_err(|e| format_err!("Can't read {}: {e}", crates_dir.display()))?
+    {
+        let path = entry?.path().join(&file_name);
+        if path.exists() {
+            return Advisory::parse(&std::fs::read_to_string(&path)?)
+                .map_err(|e| format_err!("{}: {e}", path.display()));
+        }
+    }
+    bail!("Advisory {id} not found in {}", db_dir.display());
+}
+
+/// All advisories of the database checked out in `db_dir`, except withdrawn ones
+fn all_advisories(db_dir: &Path) -> Result<Vec<Advisory>> {
+    let crates_dir = db_dir.join("crates");
+    let mut advisories = vec![];
+    for crate_dir in std::fs::read_dir(&crates_dir)
+        .map_err(|e| format_err!("Can't read {}: {e}", crates_dir.display()))?
+    {
+        for entry in std::fs::read_dir(crate_dir?.path())? {
+            let path = entry?.path();
+            if path.extension().map_or(true, |ext| ext != "md") {
+                continue;
+            }
+            match Advisory::parse(&std::fs::read_to_string(&path)?) {
+                Ok(advisory) if advisory.metadata.withdrawn.is_none() => advisories.push(advisory),
+                Ok(_) => {}
+                Err(e) => log::warn!("Skipping {}: {e}", path.display()),
+            }
+        }
+    }
+    Ok(advisories)
+}
+
+/// Advisories and issues to put in one review of each crate version
+#[derive(Debug, Default)]
+struct ImportedReview {
+    advisories: Vec<package::Advisory>,
+    issues: Vec<package::Issue>,
+}
+
+/// Group `advisories` by the crate version their review is for
+///
+/// Like drafted reviews: an advisory for the first patched version, or an
+/// issue when nothing was patched. Issues go to version `0.0.0`, since their
+/// default range covers all the versions after it.
+fn imported_reviews(advisories: &[Advisory]) -> BTreeMap<(String, Version), ImportedReview> {
+    let mut reviews: BTreeMap<_, ImportedReview> = BTreeMap::new();
+    for advisory in advisories {
+        let version = advisory.first_patched_version();
+        let review = reviews
+            .entry((
+                advisory.metadata.package.clone(),
+                version.clone().unwrap_or_else(|| Version::new(0, 0, 0)),
+            ))
+            .or_default();
+        if version.is_some() {
+            review.advisories.push(package::Advisory {
+                ids: advisory.ids(),
+                severity: advisory.severity(),
+                range: advisory.range(),
+                comment: advisory.comment(),
+            });
+        } else {
+            let mut issue = package::Issue::new_with_severity(
+                advisory.metadata.id.clone(),
+                advisory.severity(),
+            );
+            issue.comment = advisory.comment();
+            review.issues.push(issue);
+        }
+    }
+    reviews
+}
+
+/// Convert the whole advisory database into proofs signed by the Id of imported RustSec proofs
+///
+/// Proofs imported before are replaced, so withdrawn advisories go away.
+pub fn import_advisory_db(local: &Local, advisory_db: Option<PathBuf>) -> Result<()> {
+    let db_dir = match advisory_db {
+        Some(dir) => dir,
+        None => checkout_advisory_db(local)?,
+    };
+    let advisories = all_advisories(&db_dir)?;
+    let imported = local.imported_proofs("rustsec");
+    let id = imported.signing_id()?;
+
+    let mut proofs = vec![];
+    for ((name, version), review) in imported_reviews(&advisories) {
+        let review = proof::review::PackageBuilder::default()
+            .from(id.id.clone())
+            .package(proof::PackageInfo {
+                id: proof::PackageVersionId::new(SOURCE_CRATES_IO.to_owned(), name, version),
+                digest: vec![],
+                digest_type: proof::default_digest_type(),
+                revision: String::new(),
+                revision_type: proof::default_revision_type(),
+                digest_ignore: None,
+            })
+            .review(proof::review::Review::new_none())
+            .advisories(review.advisories)
+            .issues(review.issues)
+            .build()
+            .map_err(|e| format_err!("{e}"))?;
+        proofs.push(review.sign_by(&id)?);
+    }
+    imported.replace(&proofs)?;
+    eprintln!(
+        "Imported {} RustSec advisories as {} proofs by {}",
+        advisories.len(),
+        proofs.len(),
+        id.id.id
+    );
+
+    if let Some(current) = local.get_current_userid_opt()? {
+        let db = local.load_db()?;
+        let trusts = db
+            .get_trust_proof_between(&current, &id.id.id)
+            .is_some_and(|trust| trust.trust > TrustLevel::None);
+        if !trusts {
+            eprintln!(
+                "They'll count once you trust that Id: cargo crev id trust {}",
+                id.id.id
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Draft a review of the affected crate, with the advisory pre-filled
+pub fn review_from_rustsec(local: &Local, args: opts::AdvisoryFromRustsec) -> Result<()> {
+    let id = args.id.to_uppercase();
+    let db_dir = match &args.advisory_db {
+        Some(dir) => dir.clone(),
+        None => checkout_advisory_db(local)?,
+    };
+    let advisory = find_advisory(&db_dir, &id)?;
+    if let Some(withdrawn) = &advisory.metadata.withdrawn {
+        bail!("Advisory {id} was withdrawn on {withdrawn}");
+    }
+
+    let first_patched = advisory.first_patched_version();
+    let Some(version) = args.version.clone().or_else(|| first_patched.clone()) else {
+        bail!(
+            "No version of {} was patched; use `--vers` to pick the affected version to report an issue for",
+            advisory.metadata.package
+        );
+    };
+    let severity = args.severity.unwrap_or_else(|| advisory.severity());
+
+    let (advise_common, report_common) = if first_patched.is_some() {
+        eprintln!(
+            "Drafting an advisory for {} {version} (fixes {id})",
+            advisory.metadata.package
+        );
+        (
+            Some(opts::AdviseCommon {
+                affected: advisory.range(),
+                severity,
+                ids: advisory.ids(),
+                comment: advisory.comment(),
+            }),
+            None,
+        )
+    } else {
+        eprintln!(
+            "Drafting an issue for {} {version} (affected by {id}, with no patched version)",
+            advisory.metadata.package
+        );
+        (
+            None,
+            Some(opts::ReportCommon {
+                severity,
+                id: advisory.metadata.id.clone(),
+                comment: advisory.comment(),
+            }),
+        )
+    };
+
+    create_review_proof(
+        &ReviewCrateSelector {
+            crate_: opts::CrateSelector::new(
+                Some(advisory.metadata.package.clone()),
+                Some(version),
+                true,
+            ),
+            diff: None,
+        },
+        report_common,
+        advise_common,
+        TrustProofType::Distrust,
+        &args.common_proof_create,
+        true,
+        false,
+        None,
+        None,
+        &[],
+        false,
+        &DigestIgnorePolicy::cargo_crate(),
+        args.cargo_opts,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADVISORY: &str = r#"```toml
+[advisory]
+id = "RUSTSEC-2021-0001"
+package = "somecrate"
+date = "2021-01-05"
+url = "https://github.com/example/somecrate/issues/1"
+aliases = ["CVE-2021-1234", "GHSA-xxxx-yyyy-zzzz"]
+cvss = "CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:N/I:H/A:N"
+
+[versions]
+patched = [">= 1.4.2", ">= 1.3.7, < 1.4.0"]
+unaffected = ["< 1.0.0"]
+```
+
+# Use after free in `Thing::drop`
+
+Dropping a `Thing` twice frees its buffer twice.
+"#;
+
+    #[test]
+    fn parses_advisory() -> Result<()> {
+        let advisory = Advisory::parse(ADVISORY)?;
+        assert_eq!(advisory.metadata.package, "somecrate");
+        assert_eq!(advisory.title, "Use after free in `Thing::drop`");
+        assert_eq!(
+            advisory.description,
+            "Dropping a `Thing` twice frees its buffer twice."
+        );
+        assert_eq!(
+            advisory.ids(),
+            ["RUSTSEC-2021-0001", "CVE-2021-1234", "GHSA-xxxx-yyyy-zzzz"]
+        );
+        assert_eq!(
+            advisory.first_patched_version(),
+            Some(Version::new(1, 4, 2))
+        );
+        assert_eq!(advisory.range(), VersionRange::Major);
+        assert_eq!(advisory.severity(), Level::High);
+        Ok(())
+    }
+
+    #[test]
+    fn imports_advisories_by_version() -> Result<()> {
+        let unpatched = ADVISORY
+            .replace("RUSTSEC-2021-0001", "RUSTSEC-2021-0002")
+            .replace(r#"patched = [">= 1.4.2", ">= 1.3.7, < 1.4.0"]"#, "");
+        let advisories = [Advisory::parse(ADVISORY)?, Advisory::parse(&unpatched)?];
+        let reviews = imported_reviews(&advisories);
+        assert_eq!(reviews.len(), 2);
+
+        let patched = &reviews[&("somecrate".to_owned(), Version::new(1, 4, 2))];
+        assert_eq!(patched.advisories.len(), 1);
+        assert_eq!(patched.advisories[0].ids[0], "RUSTSEC-2021-0001");
+        assert!(patched.issues.is_empty());
+
+        let unpatched = &reviews[&("somecrate".to_owned(), Version::new(0, 0, 0))];
+        assert_eq!(unpatched.issues.len(), 1);
+        assert_eq!(unpatched.issues[0].id, "RUSTSEC-2021-0002");
+        assert_eq!(unpatched.issues[0].severity, Level::High);
+        Ok(())
+    }
+
+    #[test]
+    fn min_versions() {
+        assert_eq!(min_version(">= 1.2.3"), Some(Version::new(1, 2, 3)));
+        assert_eq!(min_version("^0.4"), Some(Version::new(0, 4, 0)));
+        assert_eq!(min_version(">= 0.5.1, < 0.6"), Some(Version::new(0, 5, 1)));
+        assert_eq!(min_version("< 0.6"), None);
+    }
+}
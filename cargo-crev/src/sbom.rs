@@ -0,0 +1,220 @@
+//! `report sbom`: the dependencies of the current project as a software bill
+//! of materials, with what `verify` found about each of them
+//!
+//! Written as CycloneDX (<https://cyclonedx.org>) JSON. crev results are
+//! properties of the components, named `crev:*`, so that tools that don't
+//! know about crev still read the rest of the document.
+use crate::{
+    deps::scan::{RequiredDetails, Scanner},
+    opts::{CrateSelector, CrateVerify, CrateVerifyCommon, SbomFormat, WotOpts},
+    prelude::*,
+    repo::Repo,
+};
+use chrono::Utc;
+use crev_data::{proof::PackageVersionId, Digest, Id};
+use crev_lib::VerificationStatus;
+use serde_json::{json, Value};
+
+const CYCLONEDX_SPEC_VERSION: &str = "1.5";
+
+/// What goes into the component of one dependency
+#[derive(Debug)]
+struct SbomComponent {
+    name: String,
+    version: Version,
+    /// From crates.io, so it has a package URL
+    is_crates_io: bool,
+    /// `None` for local source code
+    digest: Option<Digest>,
+    status: VerificationStatus,
+    version_reviews: u64,
+    /// Open issues reported by trusted Ids
+    issues: Vec<String>,
+    trusted_reviewers: Vec<Id>,
+    dependencies: Vec<PackageVersionId>,
+}
+
+/// See <https://github.com/package-url/purl-spec>
+fn purl(name: &str, version: &Version) -> String {
+    format!("pkg:cargo/{name}@{version}")
+}
+
+fn status_str(status: VerificationStatus) -> &'static str {
+    match status {
+        VerificationStatus::Local => "local",
+        VerificationStatus::Verified => "pass",
+        VerificationStatus::Insufficient => "none",
+        VerificationStatus::Negative => "warn",
+    }
+}
+
+impl SbomComponent {
+    fn bom_ref(&self) -> String {
+        purl(&self.name, &self.version)
+    }
+
+    fn to_cyclonedx(&self) -> Value {
+        let mut properties = vec![
+            json!({ "name": "crev:status", "value": status_str(self.status) }),
+            json!({ "name": "crev:review-count", "value": self.version_reviews.to_string() }),
+        ];
+        properties.extend(
+            self.issues
+                .iter()
+                .map(|id| json!({ "name": "crev:issue", "value": id })),
+        );
+        properties.extend(
+            self.trusted_reviewers
+                .iter()
+                .map(|id| json!({ "name": "crev:reviewer", "value": id.to_string() })),
+        );
+
+        let mut component = json!({
+            "type": "library",
+            "bom-ref": self.bom_ref(),
+            "name": self.name,
+            "version": self.version.to_string(),
+            "properties": properties,
+        });
+        if self.is_crates_io {
+            component["purl"] = self.bom_ref().into();
+        }
+        if let Some(digest) = &self.digest {
+            // crate digests are blake2b, and CycloneDX wants hashes in hex
+            let hex: String = digest
+                .as_slice()
+                .iter()
+                .map(|byte| format!("{byte:02x}"))
+                .collect();
+            component["hashes"] = json!([{ "alg": "BLAKE2b-256", "content": hex }]);
+        }
+        component
+    }
+}
+
+fn cyclonedx(components: &[SbomComponent]) -> Value {
+    let dependencies: Vec<_> = components
+        .iter()
+        .map(|component| {
+            let depends_on: Vec<_> = component
+                .dependencies
+                .iter()
+                .map(|dep| purl(&dep.id.name, &dep.version))
+                .collect();
+            json!({ "ref": component.bom_ref(), "dependsOn": depends_on })
+        })
+        .collect();
+    json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": CYCLONEDX_SPEC_VERSION,
+        "version": 1,
+        "metadata": {
+            "timestamp": Utc::now().to_rfc3339(),
+            "tools": {
+                "components": [{
+                    "type": "application",
+                    "name": "cargo-crev",
+                    "version": env!("CARGO_PKG_VERSION"),
+                }],
+            },
+        },
+        "components": components.iter().map(SbomComponent::to_cyclonedx).collect::<Vec<_>>(),
+        "dependencies": dependencies,
+    })
+}
+
+/// Print the SBOM of the current project, as `verify` sees its dependencies
+pub fn print_sbom(common: CrateVerifyCommon, wot: WotOpts, format: SbomFormat) -> Result<()> {
+    let args = CrateVerify {
+        common,
+        wot,
+        ..Default::default()
+    };
+    // fail early outside of a cargo project
+    Repo::auto_open_cwd(args.common.cargo_opts.clone())?;
+    let scanner = Scanner::new(CrateSelector::default(), &args)?;
+
+    let mut components: Vec<_> = scanner
+        .run(&RequiredDetails::none())
+        .map(|stats| {
+            let details = stats.details();
+            let mut trusted_reviewers: Vec<_> = details
+                .trusted_reviewers
+                .iter()
+                .map(|reviewer| reviewer.id.clone())
+                .collect();
+            trusted_reviewers.sort();
+            SbomComponent {
+                name: stats.info.id.name().to_string(),
+                version: stats.info.id.version().clone(),
+                is_crates_io: stats.info.id.source_id().is_crates_io(),
+                digest: details.digest.clone(),
+                status: details.accumulative_own.trust,
+                version_reviews: details.version_reviews.count,
+                issues: details.open_issues.iter().map(|i| i.id.clone()).collect(),
+                trusted_reviewers,
+                dependencies: details.dependencies.clone(),
+            }
+        })
+        .collect();
+    components.sort_by(|a, b| (&a.name, &a.version).cmp(&(&b.name, &b.version)));
+
+    match format {
+        SbomFormat::CycloneDx => {
+            println!("{}", serde_json::to_string_pretty(&cyclonedx(&components))?);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crev_data::SOURCE_CRATES_IO;
+
+    #[test]
+    fn cyclonedx_components_have_crev_properties() {
+        let reviewer = crev_data::UnlockedId::generate(None).id.id;
+        let component = SbomComponent {
+            name: "somecrate".into(),
+            version: Version::new(1, 2, 3),
+            is_crates_io: true,
+            digest: Digest::from_bytes(&[0xab; 32]),
+            status: VerificationStatus::Verified,
+            version_reviews: 2,
+            issues: vec!["RUSTSEC-2021-0001".into()],
+            trusted_reviewers: vec![reviewer.clone()],
+            dependencies: vec![PackageVersionId::new(
+                SOURCE_CRATES_IO.into(),
+                "dep".into(),
+                Version::new(0, 1, 0),
+            )],
+        };
+        let bom = cyclonedx(&[component]);
+        assert_eq!(bom["bomFormat"], "CycloneDX");
+
+        let component = &bom["components"][0];
+        assert_eq!(component["purl"], "pkg:cargo/somecrate@1.2.3");
+        assert_eq!(component["hashes"][0]["content"], "ab".repeat(32));
+        let properties: Vec<(&str, &str)> = component["properties"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| (p["name"].as_str().unwrap(), p["value"].as_str().unwrap()))
+            .collect();
+        let reviewer = reviewer.to_string();
+        assert_eq!(
+            properties,
+            [
+                ("crev:status", "pass"),
+                ("crev:review-count", "2"),
+                ("crev:issue", "RUSTSEC-2021-0001"),
+                ("crev:reviewer", reviewer.as_str()),
+            ]
+        );
+        assert_eq!(
+            bom["dependencies"][0]["dependsOn"][0],
+            "pkg:cargo/dep@0.1.0"
+        );
+    }
+}
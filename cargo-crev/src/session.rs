@@ -0,0 +1,151 @@
+//! `review --session`: time-boxed review with a journal of the files visited
+//!
+//! Files count as visited if an editor hook reported them (see
+//! `SESSION_JOURNAL_ENV`). File access times are not used: `noatime` and
+//! `relatime` mounts, indexers and backup tools make them unreliable.
+use crate::{opts::ReviewCrateSelector, prelude::*, repo::Repo, shared::get_open_cmd};
+use crev_data::SOURCE_CRATES_IO;
+use crev_lib::{local::Local, ReviewActivity, ReviewSession};
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+    sync::mpsc,
+    time::{Duration, SystemTime},
+};
+
+/// Name of ENV with a file that editor hooks can append opened paths to, one per line
+///
+/// Eg. for Vim: `autocmd BufRead * silent !echo %:p >> $CREV_REVIEW_SESSION_JOURNAL`
+pub const SESSION_JOURNAL_ENV: &str = "CREV_REVIEW_SESSION_JOURNAL";
+
+/// Parse session length like `45m`, `1h30m` or `90` (minutes)
+pub fn parse_session_length(s: &str) -> Result<Duration> {
+    if let Ok(minutes) = s.parse::<u64>() {
+        return Ok(Duration::from_secs(minutes * 60));
+    }
+    let mut secs = 0;
+    let mut number = String::new();
+    for ch in s.chars() {
+        if ch.is_ascii_digit() {
+            number.push(ch);
+            continue;
+        }
+        let unit = match ch {
+            'h' => 60 * 60,
+            'm' => 60,
+            's' => 1,
+            _ => bail!("Invalid session length `{}`: unknown unit `{}`", s, ch),
+        };
+        let value: u64 = std::mem::take(&mut number)
+            .parse()
+            .map_err(|_| format_err!("Invalid session length `{}`", s))?;
+        secs += value * unit;
+    }
+    if !number.is_empty() || secs == 0 {
+        bail!("Invalid session length `{}`; use eg. `45m` or `1h30m`", s);
+    }
+    Ok(Duration::from_secs(secs))
+}
+
+/// Rust source files of the crate, relative to its root
+fn source_files(root: &Path) -> BTreeSet<PathBuf> {
+    walkdir::WalkDir::new(root)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            entry.file_type().is_file() && entry.path().extension().map_or(false, |ext| ext == "rs")
+        })
+        .filter_map(|entry| entry.path().strip_prefix(root).ok().map(Path::to_path_buf))
+        .collect()
+}
+
+/// Files reported by editor hooks
+fn visited_files(root: &Path, files: &BTreeSet<PathBuf>, journal: &Path) -> BTreeSet<PathBuf> {
+    std::fs::read_to_string(journal)
+        .unwrap_or_default()
+        .lines()
+        .map(|line| {
+            let path = Path::new(line.trim());
+            path.strip_prefix(root).unwrap_or(path).to_path_buf()
+        })
+        .filter(|path| files.contains(path))
+        .collect()
+}
+
+/// Wait until the user says they're done, or the time is up
+fn wait_for_session_end(remaining: Duration) {
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut line = String::new();
+        let _ = std::io::stdin().read_line(&mut line);
+        let _ = tx.send(());
+    });
+
+    eprintln!(
+        "Review session in progress. Press Enter when done (time left: {} minutes).",
+        remaining.as_secs() / 60
+    );
+    if rx.recv_timeout(remaining).is_err() {
+        // don't leave the thread reading the next answer from stdin
+        eprintln!("Time's up! Press Enter to write your review.");
+        let _ = rx.recv();
+    }
+}
+
+/// Open the crate for review, and journal the session into its `ReviewActivity`
+pub fn run_review_session(
+    local: &Local,
+    crate_sel: &ReviewCrateSelector,
+    length: Duration,
+) -> Result<()> {
+    crate_sel.crate_.ensure_name_given()?;
+    let repo = Repo::auto_open_cwd_default()?;
+    let crate_id = repo.find_pkgid_by_crate_selector(&crate_sel.crate_)?;
+    let cargo_crate = repo.get_crate(&crate_id)?;
+    let name = cargo_crate.name().to_string();
+    let version = cargo_crate.version();
+
+    let dest_dir =
+        local.sanitized_crate_copy(SOURCE_CRATES_IO, &name, version, cargo_crate.root())?;
+    let files = source_files(&dest_dir);
+    crate::release_notes::add_to_sanitized_copy(local, &cargo_crate, &dest_dir);
+
+    let mut activity = ReviewActivity::new(crate_sel.diff.clone().flatten());
+    let mut session = ReviewSession::new(length.as_secs() / 60, files.len());
+    activity.session = Some(session.clone());
+    local.record_review_activity(SOURCE_CRATES_IO, &name, version, &activity)?;
+
+    let journal_dir = tempfile::tempdir()?;
+    let journal = journal_dir.path().join("visited.txt");
+    std::fs::write(&journal, "")?;
+    std::env::set_var(SESSION_JOURNAL_ENV, &journal);
+
+    let started = SystemTime::now();
+    let status =
+        crev_lib::util::run_with_shell_cmd(get_open_cmd(local)?.as_ref(), Some(&dest_dir))?;
+    if !status.success() {
+        bail!("Shell returned {}", status);
+    }
+    wait_for_session_end(length.saturating_sub(started.elapsed().unwrap_or_default()));
+
+    session.minutes = Some(started.elapsed().unwrap_or_default().as_secs() / 60);
+    session.visited = visited_files(&dest_dir, &files, &journal);
+    if session.visited.is_empty() {
+        eprintln!(
+            "No visited files were reported. Set up an editor hook appending opened paths to ${SESSION_JOURNAL_ENV} to journal them."
+        );
+    }
+    eprintln!(
+        "Session finished after {} minutes. Visited {} of {} source files ({:.0}%), suggested thoroughness: {}",
+        session.minutes.unwrap_or_default(),
+        session.visited.len(),
+        session.files_total,
+        session.coverage() * 100.,
+        session.suggested_thoroughness(),
+    );
+
+    activity.timestamp = crev_common::now();
+    activity.session = Some(session);
+    local.record_review_activity(SOURCE_CRATES_IO, &name, version, &activity)?;
+    Ok(())
+}
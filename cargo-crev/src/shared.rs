@@ -8,10 +8,18 @@ use crate::{
     repo::Repo,
 };
 use anyhow::{format_err, Context, Result};
-use crev_data::{proof, review::Package, SOURCE_CRATES_IO};
-use crev_lib::{self, local::Local, ProofStore, ReviewMode};
-use resiter::FlatMap;
-use serde::Deserialize;
+use crev_common::CancellationToken;
+use crev_data::{
+    proof,
+    proof::{CommonOps, ContentExt},
+    Id, TrustLevel, UnlockedId, SOURCE_CRATES_IO,
+};
+use crev_lib::{
+    self,
+    local::{Local, UserConfig},
+    DigestIgnorePolicy, ErrorCategory, ErrorCode, ProofStore, ReviewMode,
+};
+use serde::{Deserialize, Serialize};
 use std::{
     collections::HashSet,
     env,
@@ -19,6 +27,7 @@ use std::{
     io,
     path::{Path, PathBuf},
     process,
+    sync::OnceLock,
 };
 
 /// Name of ENV with original location `crev goto` was called from
@@ -68,11 +77,45 @@ impl VcsInfoJson {
     }
 }
 
+/// Cancelled on Ctrl-C, so long-running operations can stop at a safe point
+///
+/// See `install_interrupt_handler`.
+pub fn interrupt_token() -> &'static CancellationToken {
+    static TOKEN: OnceLock<CancellationToken> = OnceLock::new();
+    TOKEN.get_or_init(CancellationToken::new)
+}
+
+/// First Ctrl-C cancels `interrupt_token()`, the second one exits immediately
+pub fn install_interrupt_handler() {
+    let res = ctrlc::set_handler(|| {
+        let token = interrupt_token();
+        if token.is_cancelled() {
+            process::exit(130);
+        }
+        eprintln!("Interrupting... (press Ctrl-C again to exit immediately)");
+        token.cancel();
+    });
+    if let Err(e) = res {
+        log::warn!("Failed to install the Ctrl-C handler: {}", e);
+    }
+}
+
 /// Ignore things that are commonly added during the review (eg. by RLS)
-pub fn cargo_full_ignore_list(ignore_cargo_lock: bool) -> fnv::FnvHashSet<PathBuf> {
-    let mut ignore_list = HashSet::default();
-    ignore_list.insert(PathBuf::from(".cargo-ok"));
-    ignore_list.insert(PathBuf::from("target"));
+///
+/// That's `digest-ignore` from the config (`crev_lib::util::DEFAULT_DIGEST_IGNORE`
+/// if not set), unless disabled in `opts`, plus any paths given in `opts`.
+/// Only for local copies; reviews are matched with
+/// `crev_lib::verify::get_registry_crate_digest`.
+pub fn cargo_full_ignore_list(
+    ignore_cargo_lock: bool,
+    config: &UserConfig,
+    opts: &opts::DigestIgnoreOpts,
+) -> fnv::FnvHashSet<PathBuf> {
+    let mut ignore_list = cargo_min_ignore_list();
+    if !opts.no_default_digest_ignore {
+        ignore_list.extend(config.digest_ignore_list().into_iter().map(PathBuf::from));
+    }
+    ignore_list.extend(opts.digest_ignore.iter().map(PathBuf::from));
     if ignore_cargo_lock {
         ignore_list.insert(PathBuf::from("Cargo.lock"));
     }
@@ -81,10 +124,10 @@ pub fn cargo_full_ignore_list(ignore_cargo_lock: bool) -> fnv::FnvHashSet<PathBu
 }
 
 /// Ignore only the marker added by `cargo` after fully downloading and extracting crate
+///
+/// That's the list of [`DigestIgnorePolicy::cargo_crate`], which reviews are made with.
 pub fn cargo_min_ignore_list() -> fnv::FnvHashSet<PathBuf> {
-    let mut ignore_list = HashSet::default();
-    ignore_list.insert(PathBuf::from(".cargo-ok"));
-    ignore_list
+    DigestIgnorePolicy::cargo_crate().ignore_list().clone()
 }
 
 #[cfg(target_family = "unix")]
@@ -105,6 +148,33 @@ pub fn exec_into(mut command: process::Command) -> Result<()> {
     Ok(())
 }
 
+/// Let the user pick one of `options`
+///
+/// Returns `None` without asking if not running in a terminal.
+pub fn select_interactively(prompt: &str, options: &[String]) -> Result<Option<usize>> {
+    if !atty::is(atty::Stream::Stdin) || !atty::is(atty::Stream::Stderr) {
+        return Ok(None);
+    }
+    eprintln!("{prompt}");
+    for (i, option) in options.iter().enumerate() {
+        eprintln!("{:>3}) {option}", i + 1);
+    }
+    loop {
+        let reply = rprompt::prompt_reply_from_bufread(
+            &mut std::io::stdin().lock(),
+            &mut std::io::stderr(),
+            format!("Select 1-{} (q to quit): ", options.len()),
+        )?;
+        match reply.trim() {
+            "q" | "Q" => return Err(crev_common::CancelledError::ByUser.into()),
+            reply => match reply.parse::<usize>() {
+                Ok(n) if (1..=options.len()).contains(&n) => return Ok(Some(n - 1)),
+                _ => eprintln!("Invalid selection"),
+            },
+        }
+    }
+}
+
 /// `cd` into crate source code and start shell
 ///
 /// Set some `envs` to help other commands work
@@ -248,8 +318,12 @@ pub fn get_open_cmd(local: &Local) -> Result<String> {
     if let Some(cmd) = config.open_cmd {
         return Ok(cmd);
     }
+    Ok(system_open_cmd().into())
+}
 
-    Ok(if cfg!(target_os = "windows") {
+/// Command opening files and URLs with their default application
+fn system_open_cmd() -> &'static str {
+    if cfg!(target_os = "windows") {
         "start"
     } else if cfg!(target_os = "macos") {
         "open"
@@ -259,21 +333,52 @@ pub fn get_open_cmd(local: &Local) -> Result<String> {
         eprintln!("Unsupported platform. Please submit a PR!");
         "xdg-open"
     }
-    .into())
 }
 
-/// Open a crate
+/// Web page of a crate to open instead of its source code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrateLink {
+    /// docs.rs page of the version
+    Docs,
+    /// `package.repository` of the manifest
+    Repository,
+}
+
+/// Open the `link` page of a crate in the browser
+fn crate_open_link(cargo_crate: &cargo::core::Package, link: CrateLink) -> Result<()> {
+    let name = cargo_crate.name();
+    let url = match link {
+        CrateLink::Docs => format!("https://docs.rs/{name}/{}", cargo_crate.version()),
+        CrateLink::Repository => match &cargo_crate.manifest().metadata().repository {
+            Some(url) => url.clone(),
+            None => bail!("{name} doesn't have a repository in its Cargo.toml"),
+        },
+    };
+    eprintln!("Opening {url}");
+    let status =
+        crev_lib::util::run_with_shell_cmd(system_open_cmd().as_ref(), Some(Path::new(&url)))?;
+    if !status.success() {
+        bail!("Shell returned {}", status);
+    }
+    Ok(())
+}
+
+/// Open a crate, or with `link`, one of its web pages
 ///
 /// * `unrelated` - the crate might not actually be a dependency
 pub fn crate_open(
     crate_sel: &ReviewCrateSelector,
     cmd: Option<String>,
     cmd_save: bool,
+    link: Option<CrateLink>,
 ) -> Result<()> {
     let local = Local::auto_create_or_open()?;
     let repo = Repo::auto_open_cwd_default()?;
     let crate_id = repo.find_pkgid_by_crate_selector(&crate_sel.crate_)?;
     let cargo_crate = repo.get_crate(&crate_id)?;
+    if let Some(link) = link {
+        return crate_open_link(&cargo_crate, link);
+    }
 
     if let Some(Some(base_ver)) = &crate_sel.diff {
         println!("View the diff online:\nhttps://diff.rs/{name}/{base_ver}/{name}/{new_ver}/Cargo.toml\n",
@@ -297,6 +402,7 @@ pub fn crate_open(
     // It's not safe to open Cargo's crate dir directly, because editor integration (like cargo check)
     // could automatically start running crate's potentially malicious build script or proc macros.
     let dest_dir = local.sanitized_crate_copy(SOURCE_CRATES_IO, &name, version, src_dir)?;
+    crate::release_notes::add_to_sanitized_copy(&local, &cargo_crate, &dest_dir);
 
     let open_cmd = match cmd {
         Some(cmd) => cmd,
@@ -412,9 +518,11 @@ pub fn crate_review_activity_check(
 
 pub fn check_package_clean_state(
     repo: &Repo,
+    local: &Local,
     crate_root: &Path,
     name: &str,
     version: &Version,
+    policy: &DigestIgnorePolicy,
 ) -> Result<(crev_data::Digest, Option<VcsInfoJson>)> {
     // to protect from creating a digest from a crate in unclean state
     // we move the old directory, download a fresh one and double
@@ -439,15 +547,17 @@ pub fn check_package_clean_state(
     assert_eq!(crate_root, crate_root_second);
     assert_eq!(version, crate_version_second);
 
-    let digest_clean =
-        crev_lib::get_recursive_digest_for_dir(crate_root, &cargo_min_ignore_list())?;
+    let digest_clean = policy.digest(crate_root)?;
     // if the `Cargo.lock` not exist in the clean package, we can ignore it being created
     // in the reviewed version; that's normal
     let ignore_cargo_lock = !crate_root.join("Cargo.lock").exists();
-    let digest_reviewed = crev_lib::get_recursive_digest_for_dir(
-        &reviewed_pkg_dir,
-        &cargo_full_ignore_list(ignore_cargo_lock),
-    )?;
+    let digest_reviewed = policy
+        .ignoring(cargo_full_ignore_list(
+            ignore_cargo_lock,
+            &local.load_user_config()?,
+            &opts::DigestIgnoreOpts::default(),
+        ))
+        .digest(&reviewed_pkg_dir)?;
 
     if digest_clean != digest_reviewed {
         eprintln!(
@@ -468,17 +578,46 @@ pub fn check_package_clean_state(
     Ok((digest_clean, vcs))
 }
 
-pub fn find_advisories(crate_: &opts::CrateSelector) -> Result<Vec<proof::review::Package>> {
+/// Is an author in the Web of Trust of `--for-id`; everyone is, without it
+pub fn for_id_author_filter(
+    local: &Local,
+    db: &crev_wot::ProofDB,
+    wot: &opts::WotOpts,
+) -> Result<impl Fn(&Id) -> bool> {
+    let trust_set = match wot.for_id.as_deref() {
+        Some(for_id) => Some(db.calculate_trust_set(
+            &local.get_for_id_from_str(Some(for_id))?,
+            &wot.trust_params.clone().into(),
+        )),
+        None => None,
+    };
+    Ok(move |id: &Id| {
+        trust_set.as_ref().map_or(true, |trust_set| {
+            trust_set.get_effective_trust_level(id) >= TrustLevel::Low
+        })
+    })
+}
+
+pub fn find_advisories(
+    crate_: &opts::CrateSelector,
+    wot: &opts::WotOpts,
+) -> Result<Vec<proof::review::Package>> {
     let local = crev_lib::Local::auto_open()?;
     let db = local.load_db()?;
+    let is_selected_author = for_id_author_filter(&local, &db, wot)?;
 
     Ok(db
         .get_advisories(SOURCE_CRATES_IO, crate_.name.as_deref(), crate_.version()?)
+        .filter(|review| is_selected_author(&review.from().id))
         .cloned()
         .collect())
 }
 
 pub fn run_diff(args: &opts::Diff) -> Result<std::process::ExitStatus> {
+    if let Some(reviewer) = &args.reviewed_by {
+        return run_reviewed_diff(args, reviewer);
+    }
+
     let repo = Repo::auto_open_cwd_default()?;
     let name = &args.name;
 
@@ -487,12 +626,12 @@ pub fn run_diff(args: &opts::Diff) -> Result<std::process::ExitStatus> {
     let dst_crate = repo.get_crate(&dst_crate_id)?;
 
     let requirements = crev_lib::VerificationRequirements::from(args.requirements.clone());
-    let trust_distance_params = &args.trust_params.clone().into();
+    let trust_distance_params = &args.wot.trust_params.clone().into();
 
     let local = crev_lib::Local::auto_create_or_open()?;
-    let current_id = local.get_current_userid()?;
+    let for_id = local.get_for_id_from_str(args.wot.for_id.as_deref())?;
     let db = local.load_db()?;
-    let trust_set = db.calculate_trust_set(&current_id, trust_distance_params);
+    let trust_set = db.calculate_trust_set(&for_id, trust_distance_params);
     let src_version = args
         .src
         .clone()
@@ -513,9 +652,110 @@ pub fn run_diff(args: &opts::Diff) -> Result<std::process::ExitStatus> {
         SOURCE_CRATES_IO,
         name,
         dst_crate.version(),
-        &crev_lib::ReviewActivity::new(Some(src_version)),
+        &crev_lib::ReviewActivity::new(Some(src_version.clone())),
     )?;
 
+    if args.json {
+        return print_diff_summary(
+            name,
+            (&src_version, src_crate.root()),
+            (dst_crate.version(), dst_crate.root()),
+        );
+    }
+    run_diff_command(src_crate.root(), dst_crate.root(), &args.args)
+}
+
+/// Show the diff exactly as it was seen by the author of a differential review
+///
+/// Both versions are fetched, and their digests are compared with the ones
+/// recorded in the review, so any mismatch is reported.
+fn run_reviewed_diff(args: &opts::Diff, reviewer: &str) -> Result<std::process::ExitStatus> {
+    let reviewer = crev_data::id::Id::crevid_from_str(reviewer)?;
+    let repo = Repo::auto_open_cwd_default()?;
+    let name = &args.name;
+
+    let local = crev_lib::Local::auto_create_or_open()?;
+    let db = local.load_db()?;
+    let review = db
+        .get_package_reviews_for_package(SOURCE_CRATES_IO, Some(name), args.dst.as_ref())
+        .filter(|review| review.from().id == reviewer && review.diff_base.is_some())
+        .max_by(|a, b| a.package.id.version.cmp(&b.package.id.version))
+        .ok_or_else(|| format_err!("No differential review of {} by {} found", name, reviewer))?;
+    let diff_base = review
+        .diff_base
+        .as_ref()
+        .expect("reviews without a diff base were filtered out");
+
+    let src_root = fetch_reviewed_crate(&repo, diff_base)?;
+    let dst_root = fetch_reviewed_crate(&repo, &review.package)?;
+
+    eprintln!(
+        "Diff of {} {} -> {} as reviewed by {} on {}",
+        name,
+        diff_base.id.version,
+        review.package.id.version,
+        reviewer,
+        review.date().to_rfc3339()
+    );
+
+    if args.json {
+        return print_diff_summary(
+            name,
+            (&diff_base.id.version, src_root.as_path()),
+            (&review.package.id.version, dst_root.as_path()),
+        );
+    }
+    run_diff_command(&src_root, &dst_root, &args.args)
+}
+
+/// `crate diff --json`, see [`crate::diff`]
+fn print_diff_summary(
+    name: &str,
+    (src_version, src_root): (&Version, &Path),
+    (dst_version, dst_root): (&Version, &Path),
+) -> Result<std::process::ExitStatus> {
+    let summary = crate::diff::DiffSummary {
+        name: name.to_owned(),
+        src_version: src_version.clone(),
+        dst_version: dst_version.clone(),
+        files: crate::diff::diff_dirs(src_root, dst_root, &cargo_min_ignore_list())?,
+    };
+    println!("{}", serde_json::to_string_pretty(&summary)?);
+    Ok(std::process::ExitStatus::default())
+}
+
+/// Download the crate version a review refers to, and check it's the same code that was reviewed
+fn fetch_reviewed_crate(repo: &Repo, info: &proof::PackageInfo) -> Result<PathBuf> {
+    let crate_id = repo.find_pkgid(&info.id.id.name, Some(&info.id.version), true)?;
+    let crate_ = repo.get_crate(&crate_id)?;
+    let root = crate_.root().to_owned();
+
+    let Some(digest_type) = info.known_digest_type() else {
+        eprintln!(
+            "Warning: the review of {} {} uses an unknown digest type `{}`, so the code can't be checked against it.",
+            info.id.id.name, info.id.version, info.digest_type,
+        );
+        return Ok(root);
+    };
+    let policy = DigestIgnorePolicy::of_package(info)?;
+    let digest = policy.digest_with(&root, digest_type)?;
+    if digest.as_slice() != info.digest.as_slice() {
+        eprintln!(
+            "Warning: digest of {} {} ({}) doesn't match the one in the review. The diff may differ from what the reviewer saw.",
+            info.id.id.name,
+            info.id.version,
+            root.display(),
+        );
+    }
+
+    Ok(root)
+}
+
+fn run_diff_command(
+    src_root: &Path,
+    dst_root: &Path,
+    args: &[OsString],
+) -> Result<std::process::ExitStatus> {
     use std::process::Command;
 
     let diff = |exe| {
@@ -523,9 +763,9 @@ pub fn run_diff(args: &opts::Diff) -> Result<std::process::ExitStatus> {
         command
             .arg("-r")
             .arg("-N")
-            .arg(src_crate.root())
-            .arg(dst_crate.root())
-            .args(&args.args);
+            .arg(src_root)
+            .arg(dst_root)
+            .args(args);
         command
     };
 
@@ -563,8 +803,8 @@ pub fn show_dir(sel: &opts::CrateSelector) -> Result<()> {
     Ok(())
 }
 
-pub fn list_advisories(crate_: &opts::CrateSelector) -> Result<()> {
-    for review in find_advisories(crate_)? {
+pub fn list_advisories(args: &opts::RepoQueryAdvisory) -> Result<()> {
+    for review in find_advisories(&args.crate_, &args.wot)? {
         println!("---\n{review}");
     }
 
@@ -572,12 +812,12 @@ pub fn list_advisories(crate_: &opts::CrateSelector) -> Result<()> {
 }
 
 pub fn list_issues(args: &opts::RepoQueryIssue) -> Result<()> {
-    let trust_distance_params = args.trust_params.clone().into();
+    let trust_distance_params = args.wot.trust_params.clone().into();
 
     let local = crev_lib::Local::auto_open()?;
-    let current_id = local.get_current_userid()?;
+    let for_id = local.get_for_id_from_str(args.wot.for_id.as_deref())?;
     let db = local.load_db()?;
-    let trust_set = db.calculate_trust_set(&current_id, &trust_distance_params);
+    let trust_set = db.calculate_trust_set(&for_id, &trust_distance_params);
 
     for review in db.get_pkg_reviews_with_issues_for(
         SOURCE_CRATES_IO,
@@ -592,6 +832,76 @@ pub fn list_issues(args: &opts::RepoQueryIssue) -> Result<()> {
     Ok(())
 }
 
+/// Publish an issue update for the one report of the issue matching `args`
+pub fn update_issue(args: opts::UpdateIssue) -> Result<()> {
+    let name = args
+        .crate_
+        .name
+        .as_deref()
+        .ok_or_else(|| format_err!("Crate name argument required"))?;
+    let version = args.crate_.version()?;
+    let reporter = args
+        .reporter
+        .as_deref()
+        .map(crev_data::Id::crevid_from_str)
+        .transpose()?;
+    let action = match (args.close, args.duplicate_of, args.severity) {
+        (true, _, _) => proof::IssueAction::Close,
+        (_, Some(of), _) => proof::IssueAction::Duplicate(of),
+        (_, _, Some(severity)) => proof::IssueAction::Severity(severity),
+        _ => bail!("One of `--close`, `--duplicate-of` or `--severity` is required"),
+    };
+
+    let local = crev_lib::Local::auto_open()?;
+    let db = local.load_db()?;
+    let reports: Vec<_> = db
+        .get_pkg_reviews_for_name(SOURCE_CRATES_IO, name)
+        .filter(|review| version.map_or(true, |version| &review.package.id.version == version))
+        .filter(|review| reporter.as_ref().map_or(true, |id| &review.from().id == id))
+        .filter(|review| {
+            review.issues.iter().any(|issue| issue.id == args.issue)
+                || review
+                    .advisories
+                    .iter()
+                    .any(|advisory| advisory.ids.contains(&args.issue))
+        })
+        .collect();
+    let report = match reports.as_slice() {
+        [report] => *report,
+        [] => bail!("No known review of {} reports issue `{}`", name, args.issue),
+        reports => bail!(
+            "Issue `{}` is reported in {} reviews; pick one with `--reporter` or `--vers`:\n{}",
+            args.issue,
+            reports.len(),
+            reports
+                .iter()
+                .map(|review| format!("{} {}", review.from().id, review.package.id.version))
+                .collect::<Vec<_>>()
+                .join("\n")
+        ),
+    };
+    let digest = db
+        .get_proof_digest_by_pkg_review_id(&crev_wot::PkgVersionReviewId::from(report))
+        .ok_or_else(|| format_err!("Digest of the reporting review is not known"))?;
+
+    let id = local.read_current_unlocked_id(&crate::term::read_passphrase)?;
+    let update = id.as_public_id().create_issue_update_proof(
+        report.package.id.id.clone(),
+        args.issue,
+        digest,
+        action,
+        args.comment,
+    )?;
+    let proof = update.sign_by(&id)?;
+    maybe_store(
+        &local,
+        &id,
+        &proof,
+        "Update issue",
+        &args.common_proof_create,
+    )
+}
+
 /// Are we executing from a shell started by `cargo crev goto`?
 ///
 /// If yes - return the path the original directory where the
@@ -680,20 +990,59 @@ pub fn iter_rs_files_in_dir(dir: &Path) -> impl Iterator<Item = Result<PathBuf>>
 // Note: this function is very slow
 pub fn get_geiger_count(path: &Path) -> Result<u64> {
     let mut count = 0;
-    for metrics in iter_rs_files_in_dir(path)
-        .flat_map_ok(|path| geiger::find::find_unsafe_in_file(&path, geiger::IncludeTests::No))
-    {
-        let counters = metrics?.counters;
-        count += counters.functions.unsafe_
-            + counters.exprs.unsafe_
-            + counters.item_impls.unsafe_
-            + counters.item_traits.unsafe_
-            + counters.methods.unsafe_;
+    for path in iter_rs_files_in_dir(path) {
+        count += get_geiger_count_for_file(&path?)?;
     }
 
     Ok(count)
 }
 
+pub fn get_geiger_count_for_file(path: &Path) -> Result<u64> {
+    let counters = geiger::find::find_unsafe_in_file(path, geiger::IncludeTests::No)?.counters;
+    Ok(counters.functions.unsafe_
+        + counters.exprs.unsafe_
+        + counters.item_impls.unsafe_
+        + counters.item_traits.unsafe_
+        + counters.methods.unsafe_)
+}
+
+/// Exit code of `verify` when crates with negative reviews were found
+pub const EXIT_CODE_NEGATIVE_REVIEWS: i32 = 3;
+
+/// Code of the `crev_lib::Error` behind `err`, if any
+pub fn crev_lib_error_code(err: &anyhow::Error) -> Option<(&'static str, ErrorCategory)> {
+    err.chain()
+        .find_map(|e| e.downcast_ref::<crev_lib::Error>())
+        .map(|e| (e.code(), e.category()))
+}
+
+/// An error, as printed by commands with `--json` output
+#[derive(Debug, Serialize)]
+pub struct JsonError {
+    /// See `crev_lib::ErrorCode`; `None` if the error is not from `crev_lib`
+    pub code: Option<&'static str>,
+    pub category: Option<ErrorCategory>,
+    pub message: String,
+}
+
+impl JsonError {
+    pub fn new(err: &anyhow::Error) -> Self {
+        let code = crev_lib_error_code(err);
+        Self {
+            code: code.map(|(code, _)| code),
+            category: code.map(|(_, category)| category),
+            message: format!("{err:#}"),
+        }
+    }
+
+    /// `{"error": {...}}` on stdout
+    pub fn print(err: &anyhow::Error) -> Result<()> {
+        let output = serde_json::json!({ "error": Self::new(err) });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        Ok(())
+    }
+}
+
 /// Result of `run_command`
 ///
 /// This is to distinguish expected non-success results,
@@ -707,24 +1056,26 @@ pub enum CommandExitStatus {
     CommandExitCode(i32),
 }
 
-pub fn get_crate_digest_mismatches(
-    db: &crev_wot::ProofDB,
-    name: &str,
-    version: &Version,
-    digest: &crev_data::Digest,
-) -> Vec<Package> {
-    db.get_package_reviews_for_package(SOURCE_CRATES_IO, Some(name), Some(version))
-        .filter(|review| review.package.digest != digest.as_slice())
-        .cloned()
-        .collect()
-}
-
 pub fn maybe_store(
     local: &Local,
+    id: &UnlockedId,
     proof: &crev_data::proof::Proof,
     commit_msg: &str,
     proof_create_opt: &opts::CommonProofCreate,
 ) -> Result<()> {
+    let encrypted;
+    let proof = if proof_create_opt.encrypt_to.is_empty() {
+        proof
+    } else {
+        let recipients = proof_create_opt
+            .encrypt_to
+            .iter()
+            .map(|s| Id::crevid_from_str(s))
+            .collect::<Result<Vec<_>, _>>()?;
+        encrypted = crev_lib::encryption::encrypt_proof(proof, &recipients, id)?;
+        &encrypted
+    };
+
     if proof_create_opt.print_unsigned {
         print!("{}", proof.body());
     }
@@ -733,7 +1084,16 @@ pub fn maybe_store(
         print!("{proof}");
     }
 
-    if !proof_create_opt.no_store {
+    if proof_create_opt.stage {
+        let name = local.proof_staging().add(proof)?;
+        eprintln!("Proof staged as {name}. Store it with `cargo crev proof staged commit`.");
+    } else if let Some(publish_after) = proof_create_opt.publish_after {
+        let name = local.proof_embargo().add(proof, publish_after)?;
+        eprintln!(
+            "Proof embargoed as {name} until {}. `cargo crev publish` stores it after that.",
+            publish_after.to_rfc3339()
+        );
+    } else if !proof_create_opt.no_store {
         local.insert(proof)?;
 
         if !proof_create_opt.no_commit {
@@ -131,11 +131,7 @@ pub fn read_passphrase() -> io::Result<String> {
         eprintln!("Using passphrase set in CREV_PASSPHRASE");
         return Ok(pass);
     } else if let Some(cmd) = env::var_os("CREV_PASSPHRASE_CMD") {
-        return Ok(
-            String::from_utf8_lossy(&crev_common::run_with_shell_cmd_capture_stdout(&cmd, None)?)
-                .trim()
-                .to_owned(),
-        );
+        return crev_lib::passphrase::passphrase_from_cmd(&cmd);
     }
     eprint!("Enter passphrase to unlock: ");
     rpassword::read_password()
@@ -1,8 +1,12 @@
 use crate::prelude::*;
-use std::path::Path;
-use tokei::{Config, LanguageType, Languages};
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    path::{Component, Path},
+};
+use tokei::{Config, Language, LanguageType, Languages};
 
-pub fn get_rust_line_count(path: &Path) -> Result<usize> {
+fn get_rust_stats(path: &Path) -> Result<Language> {
     let excluded = &["tests/", "examples/"];
     let config = Config {
         treat_doc_strings_as_comments: Some(true),
@@ -12,8 +16,84 @@ pub fn get_rust_line_count(path: &Path) -> Result<usize> {
     };
     let mut languages = Languages::new();
     languages.get_statistics(&[path], excluded, &config);
-    let rust = languages
-        .get(&LanguageType::Rust)
-        .ok_or_else(|| format_err!("Rust should work"))?;
-    Ok(rust.code)
+    languages
+        .remove(&LanguageType::Rust)
+        .ok_or_else(|| format_err!("Rust should work"))
+}
+
+pub fn get_rust_line_count(path: &Path) -> Result<usize> {
+    Ok(get_rust_stats(path)?.code)
+}
+
+/// Size of one top-level module of a crate
+#[derive(Clone, Debug)]
+pub struct ModuleStats {
+    pub loc: usize,
+    /// `None` if any of the files couldn't be scanned
+    pub unsafe_count: Option<u64>,
+}
+
+/// `src/foo.rs` and everything in `src/foo/` is `src/foo`,
+/// anything outside of `src` is grouped by its first path component
+fn top_level_module(rel_path: &Path) -> String {
+    let mut components = rel_path.components().filter_map(|c| match c {
+        Component::Normal(name) => Some(Path::new(name)),
+        _ => None,
+    });
+    match (components.next(), components.next()) {
+        (Some(src), Some(module)) if src == Path::new("src") => {
+            let stem = module.file_stem().unwrap_or(module.as_os_str());
+            format!("src/{}", stem.to_string_lossy())
+        }
+        (Some(first), _) => first.display().to_string(),
+        (None, _) => rel_path.display().to_string(),
+    }
+}
+
+/// Lines of Rust code and unsafe usages of each top-level module of a crate
+pub fn get_module_breakdown(path: &Path) -> Result<BTreeMap<String, ModuleStats>> {
+    let mut modules: BTreeMap<String, ModuleStats> = BTreeMap::new();
+    for report in get_rust_stats(path)?.reports {
+        let rel_path = report.name.strip_prefix(path).unwrap_or(&report.name);
+        let unsafe_count = crate::shared::get_geiger_count_for_file(&report.name).ok();
+        let module = modules
+            .entry(top_level_module(rel_path))
+            .or_insert_with(|| ModuleStats {
+                loc: 0,
+                unsafe_count: Some(0),
+            });
+        module.loc += report.stats.summarise().code;
+        module.unsafe_count = module.unsafe_count.zip(unsafe_count).map(|(a, b)| a + b);
+    }
+    Ok(modules)
+}
+
+/// Table of `get_module_breakdown` results, largest modules first
+pub fn format_module_breakdown(modules: &BTreeMap<String, ModuleStats>) -> Result<String> {
+    let mut modules: Vec<_> = modules.iter().collect();
+    modules.sort_by(|a, b| b.1.loc.cmp(&a.1.loc).then(a.0.cmp(b.0)));
+    let name_width = modules
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or(0)
+        .max("module".len());
+
+    let mut out = String::new();
+    writeln!(
+        out,
+        "{:<name_width$} {:>7} {:>7}",
+        "module", "loc", "unsafe"
+    )?;
+    for (name, stats) in modules {
+        let unsafe_count = stats
+            .unsafe_count
+            .map_or_else(|| "?".to_string(), |c| c.to_string());
+        writeln!(
+            out,
+            "{:<name_width$} {:>7} {:>7}",
+            name, stats.loc, unsafe_count
+        )?;
+    }
+    Ok(out)
 }
@@ -0,0 +1,127 @@
+//! Dependencies named like popular reviewed crates
+//!
+//! A crate nobody reviewed, with a name one typo (or a look-alike character)
+//! away from a crate many people reviewed, is more likely a typosquat than
+//! a coincidence.
+use crev_data::{Id, SOURCE_CRATES_IO};
+use crev_wot::ProofDB;
+use std::collections::{HashMap, HashSet};
+
+/// Crates with at least this many reviewers are popular
+const MIN_REVIEWERS: usize = 3;
+
+/// Names shorter than this are too often one edit away from each other
+const MIN_LEN_FOR_EDIT_DISTANCE: usize = 5;
+
+/// Names of crates.io crates many Ids reviewed
+pub struct PopularNames {
+    /// Name, and its `normalize`d and `skeleton` forms
+    names: Vec<(String, String, String)>,
+}
+
+impl PopularNames {
+    pub fn from_db(db: &ProofDB) -> Self {
+        let mut reviewers: HashMap<&str, HashSet<&Id>> = HashMap::new();
+        for review in db.get_pkg_reviews_for_source(SOURCE_CRATES_IO) {
+            reviewers
+                .entry(&review.package.id.id.name)
+                .or_default()
+                .insert(&review.common.from.id);
+        }
+        Self::new(
+            reviewers
+                .into_iter()
+                .filter(|(_, ids)| MIN_REVIEWERS <= ids.len())
+                .map(|(name, _)| name),
+        )
+    }
+
+    pub fn new<'a>(names: impl IntoIterator<Item = &'a str>) -> Self {
+        Self {
+            names: names
+                .into_iter()
+                .map(|name| (name.to_owned(), normalize(name), skeleton(name)))
+                .collect(),
+        }
+    }
+
+    /// The popular crate `name` is suspiciously similar to, if any
+    pub fn similar_to(&self, name: &str) -> Option<&str> {
+        let normalized = normalize(name);
+        let skeleton = skeleton(name);
+        self.names
+            .iter()
+            // crates.io treats `-` and `_` as the same, so it's the crate itself
+            .filter(|(_, popular, _)| *popular != normalized)
+            .find(|(_, popular, popular_skeleton)| {
+                *popular_skeleton == skeleton
+                    || (MIN_LEN_FOR_EDIT_DISTANCE <= popular.len()
+                        && edit_distance(popular, &normalized) <= 1)
+            })
+            .map(|(name, _, _)| name.as_str())
+    }
+}
+
+/// Lowercase, with `_` as `-`
+fn normalize(name: &str) -> String {
+    name.to_ascii_lowercase().replace('_', "-")
+}
+
+/// `normalize`d, with look-alike characters replaced and separators removed
+fn skeleton(name: &str) -> String {
+    normalize(name)
+        .replace("rn", "m")
+        .replace("vv", "w")
+        .chars()
+        .filter(|&c| c != '-')
+        .map(|c| match c {
+            '0' => 'o',
+            '1' | 'i' => 'l',
+            '5' => 's',
+            c => c,
+        })
+        .collect()
+}
+
+/// Edits (insertions, deletions, substitutions and swaps of neighbours) between `a` and `b`
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    rows[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if 1 < i && 1 < j && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = distance;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_similar_names() {
+        let popular = PopularNames::new(["serde_json", "log", "rand", "crossbeam"]);
+        assert_eq!(popular.similar_to("serde-jsom"), Some("serde_json"));
+        assert_eq!(popular.similar_to("sedre_json"), Some("serde_json"));
+        assert_eq!(popular.similar_to("crossbearn"), Some("crossbeam"));
+        assert_eq!(popular.similar_to("1og"), Some("log"));
+        // the crate itself
+        assert_eq!(popular.similar_to("serde-json"), None);
+        // short names are one edit away from many others
+        assert_eq!(popular.similar_to("rank"), None);
+        assert_eq!(popular.similar_to("tokio"), None);
+    }
+}
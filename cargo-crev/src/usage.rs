@@ -0,0 +1,52 @@
+//! `stats me`: your own review activity, from local-only usage counters
+//!
+//! See [`crev_lib::usage`]; counting is off unless `usage-stats: true`
+//! is set with `cargo crev config edit`.
+use crate::prelude::*;
+use crev_lib::{usage::UsageEvent, Local};
+use std::collections::HashSet;
+
+/// Count `event`, if the user opted in; failing to count is not worth failing the command
+pub fn record_usage(local: &Local, event: UsageEvent<'_>) {
+    if let Err(e) = local.record_usage(event) {
+        log::debug!("Failed to record usage stats: {}", e);
+    }
+}
+
+pub fn print_my_usage_stats() -> Result<()> {
+    let local = Local::auto_open()?;
+    if !local.load_user_config()?.usage_stats {
+        eprintln!("Usage stats are not being recorded.");
+        eprintln!("Set `usage-stats: true` with `cargo crev config edit` to start.");
+    }
+    let stats = local.read_usage_stats()?;
+    if stats.months.is_empty() {
+        return Ok(());
+    }
+
+    println!(
+        "{:<7} {:>7} {:>11} {:>6}",
+        "month", "reviews", "verify-runs", "crates"
+    );
+    for (month, usage) in &stats.months {
+        println!(
+            "{:<7} {:>7} {:>11} {:>6}",
+            month,
+            usage.reviews,
+            usage.verify_runs,
+            usage.crates_reviewed.len()
+        );
+    }
+    let months = stats.months.values();
+    let reviews: u64 = months.clone().map(|usage| usage.reviews).sum();
+    let verify_runs: u64 = months.clone().map(|usage| usage.verify_runs).sum();
+    let crates: HashSet<_> = months.flat_map(|usage| &usage.crates_reviewed).collect();
+    println!(
+        "{:<7} {:>7} {:>11} {:>6}",
+        "total",
+        reviews,
+        verify_runs,
+        crates.len()
+    );
+    Ok(())
+}
@@ -0,0 +1,136 @@
+//! Waivers: crate versions allowed to pass `verify` without enough reviews
+//!
+//! `crate exempt` signs a waiver lasting until a date. It's kept on this
+//! machine, unless `--share` puts it in the proof repository, for people
+//! trusting you to use it too. `verify` shows crates passing only thanks to
+//! a waiver as `waiv`; they fail again once it expires. Only waivers of your
+//! own Ids count, or of Ids trusted at least as much as reviewers must be.
+use crate::{opts::CrateExempt, policy, prelude::*, shared::maybe_store, term, Repo};
+use chrono::NaiveDate;
+use crev_data::{
+    proof::{self, trust::TrustLevel, CommonOps, ContentExt},
+    Id,
+};
+use crev_wot::{ProofDB, TrustSet};
+use std::collections::HashSet;
+
+/// Parse `--expires`, a `YYYY-MM-DD` day
+pub fn parse_expiry_date(s: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .map_err(|_| format_err!("Invalid date `{}`: use YYYY-MM-DD", s))
+}
+
+pub fn create_waiver(args: CrateExempt) -> Result<()> {
+    let crate_ = args.crate_.auto_unrelated()?;
+    crate_.ensure_name_given()?;
+    if args.expires < policy::today() {
+        bail!("The waiver would have expired already on {}", args.expires);
+    }
+    let repo = Repo::auto_open_cwd_default()?;
+    let pkg_id = repo.find_pkgid_by_crate_selector(&crate_)?;
+    let package = repo.crates_io_mirrors().crev_pkg_id(&pkg_id);
+
+    let local = crev_lib::Local::auto_open()?;
+    let id = local.read_current_unlocked_id(&term::read_passphrase)?;
+    let waiver = id
+        .as_public_id()
+        .create_waiver_proof(package, args.expires, args.reason)?;
+    let proof = waiver.sign_by(&id)?;
+
+    if args.share {
+        return maybe_store(&local, &id, &proof, "Add waiver", &args.common_proof_create);
+    }
+    if args.common_proof_create.stage {
+        // staged proofs go to the proof repository, which unshared waivers stay out of
+        bail!("Only waivers shared with `--share` can be staged");
+    }
+    if args.common_proof_create.print_unsigned {
+        print!("{}", proof.body());
+    }
+    if args.common_proof_create.print_signed {
+        print!("{proof}");
+    }
+    if !args.common_proof_create.no_store {
+        local.store_local_waiver(&proof)?;
+        eprintln!("Waiver kept on this machine only. Use `--share` to publish it.");
+    }
+    Ok(())
+}
+
+/// The waiver of `package` lasting the longest, expired or not
+///
+/// Only waivers of `own_ids`, or of trusted Ids at least at `trust_level`, count.
+pub fn find_waiver<'a>(
+    db: &'a ProofDB,
+    trust_set: &TrustSet,
+    own_ids: &HashSet<Id>,
+    trust_level: TrustLevel,
+    package: &proof::PackageVersionId,
+) -> Option<&'a proof::Waiver> {
+    db.get_waivers_for_package_version(package)
+        .filter(|waiver| {
+            let author = &waiver.from().id;
+            own_ids.contains(author)
+                || (trust_set.is_trusted(author)
+                    && trust_set.get_effective_trust_level(author) >= trust_level)
+        })
+        .max_by_key(|waiver| waiver.expires)
+}
+
+/// Eg. `until 2025-06-01 by <id>: vendored, audited separately`
+pub fn describe(waiver: &proof::Waiver) -> String {
+    format!(
+        "until {} by {}: {}",
+        waiver.expires,
+        waiver.from().id,
+        waiver.reason
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crev_data::{UnlockedId, Url, Version};
+    use crev_wot::{FetchSource, TrustDistanceParams};
+    use std::sync::Arc;
+
+    #[test]
+    fn waivers_need_the_required_trust() -> Result<()> {
+        let url = FetchSource::Url(Arc::new(Url::new_git("https://example.com")));
+        let me = UnlockedId::generate_for_git_url("https://me");
+        let low = UnlockedId::generate_for_git_url("https://low");
+        let other = UnlockedId::generate_for_git_url("https://other");
+        let package = proof::PackageVersionId::new(
+            "https://crates.io".into(),
+            "leftpad".into(),
+            Version::parse("1.0.0").unwrap(),
+        );
+        let expires = NaiveDate::from_ymd_opt(2030, 1, 1).unwrap();
+        let waiver = |id: &UnlockedId| -> Result<proof::Proof> {
+            Ok(id
+                .as_public_id()
+                .create_waiver_proof(package.clone(), expires, "vendored".into())?
+                .sign_by(id)?)
+        };
+        let trust =
+            me.create_signed_trust_proof(vec![low.as_public_id()], TrustLevel::Low, vec![])?;
+
+        let mut db = ProofDB::new();
+        db.import_from_iter(
+            vec![trust, waiver(&low)?]
+                .into_iter()
+                .map(|proof| (proof, url.clone())),
+        );
+        let trust_set = db.calculate_trust_set(&me.id.id, &TrustDistanceParams::default());
+        let no_ids = HashSet::new();
+
+        assert!(find_waiver(&db, &trust_set, &no_ids, TrustLevel::Low, &package).is_some());
+        assert!(find_waiver(&db, &trust_set, &no_ids, TrustLevel::Medium, &package).is_none());
+
+        db.import_from_iter(std::iter::once((waiver(&other)?, url.clone())));
+        let own_ids = HashSet::from([other.id.id.clone()]);
+        let found = find_waiver(&db, &trust_set, &own_ids, TrustLevel::High, &package);
+        assert_eq!(found.map(|waiver| &waiver.from().id), Some(&other.id.id));
+        Ok(())
+    }
+}
@@ -0,0 +1,228 @@
+//! `crate why-unverified`: why a crate doesn't pass `verify`, and what would change that
+//!
+//! Every review of the crate version is judged the way `verify` judges it
+//! (see `crev_lib::ReviewVerdict`), and then each requirement is relaxed in
+//! turn to find the smallest change that would make the crate verified.
+use crate::{
+    opts::{CrateSelector, CrateVerifyCommon, WotOpts},
+    prelude::*,
+    shared::cargo_full_ignore_list,
+    Repo,
+};
+use crev_data::{proof::review, Id, Level, TrustLevel};
+use crev_lib::{
+    verify::get_registry_crate_digest, DigestIgnorePolicy, ReviewVerdict, VerificationRequirements,
+    VerificationStatus,
+};
+use std::collections::BTreeSet;
+
+/// A single change that would make the crate verified
+#[derive(Debug, PartialEq, Eq)]
+enum Fix {
+    /// Trust these Ids (directly) at this level
+    Trust(Vec<Id>, TrustLevel),
+    LowerTrustLevel(Level),
+    LowerThoroughness(Level),
+    LowerUnderstanding(Level),
+    LowerRedundancy(u64),
+}
+
+fn verification_status(
+    reviews: &[(review::Package, ReviewVerdict)],
+    digest: &crev_data::Digest,
+    trust_set: &crev_wot::TrustSet,
+    requirements: &VerificationRequirements,
+) -> VerificationStatus {
+    let verdicts: Vec<_> = reviews
+        .iter()
+        .map(|(review, _)| ReviewVerdict::new(review, digest, trust_set, requirements))
+        .collect();
+    VerificationStatus::from_verdicts(&verdicts, requirements)
+}
+
+/// Highest level below `current` that still makes the crate verified, if any
+fn relaxed_level(current: Level, verifies: impl Fn(Level) -> bool) -> Option<Level> {
+    [Level::High, Level::Medium, Level::Low, Level::None]
+        .into_iter()
+        .filter(|level| *level < current)
+        .find(|level| verifies(*level))
+}
+
+/// Changes that would make an `Insufficient` crate verified, each on its own
+fn find_fixes(
+    reviews: &[(review::Package, ReviewVerdict)],
+    digest: &crev_data::Digest,
+    trust_set: &crev_wot::TrustSet,
+    requirements: &VerificationRequirements,
+) -> Vec<Fix> {
+    let mut fixes = vec![];
+    let counted = reviews
+        .iter()
+        .filter(|(_, verdict)| *verdict == ReviewVerdict::Counted)
+        .count() as u64;
+    let missing = requirements.redundancy.saturating_sub(counted);
+
+    let trust_level = TrustLevel::from(requirements.trust_level).max(TrustLevel::Low);
+    let to_trust: BTreeSet<_> = reviews
+        .iter()
+        .filter(|(_, verdict)| {
+            matches!(
+                verdict,
+                ReviewVerdict::UntrustedReviewer | ReviewVerdict::TrustTooLow(_)
+            )
+        })
+        .filter(|(review, _)| {
+            crev_lib::meets_requirements(review.review_possibly_none(), requirements)
+        })
+        .map(|(review, _)| review.common.from.id.clone())
+        .collect();
+    if missing as usize <= to_trust.len() {
+        fixes.push(Fix::Trust(
+            to_trust.into_iter().take(missing as usize).collect(),
+            trust_level,
+        ));
+    }
+
+    let verifies = |requirements: VerificationRequirements| {
+        verification_status(reviews, digest, trust_set, &requirements).is_verified()
+    };
+    if let Some(level) = relaxed_level(requirements.trust_level, |trust_level| {
+        verifies(VerificationRequirements {
+            trust_level,
+            ..requirements.clone()
+        })
+    }) {
+        fixes.push(Fix::LowerTrustLevel(level));
+    }
+    if let Some(level) = relaxed_level(requirements.thoroughness, |thoroughness| {
+        verifies(VerificationRequirements {
+            thoroughness,
+            ..requirements.clone()
+        })
+    }) {
+        fixes.push(Fix::LowerThoroughness(level));
+    }
+    if let Some(level) = relaxed_level(requirements.understanding, |understanding| {
+        verifies(VerificationRequirements {
+            understanding,
+            ..requirements.clone()
+        })
+    }) {
+        fixes.push(Fix::LowerUnderstanding(level));
+    }
+    if 0 < counted {
+        fixes.push(Fix::LowerRedundancy(counted));
+    }
+    fixes
+}
+
+fn print_fix(fix: &Fix) {
+    match fix {
+        Fix::Trust(ids, level) => {
+            let ids: Vec<_> = ids.iter().map(ToString::to_string).collect();
+            println!(
+                "  trust {}: cargo crev id trust --level {level} {}",
+                ids.join(", "),
+                ids.join(" ")
+            );
+        }
+        Fix::LowerTrustLevel(level) => println!("  lower the required trust: --trust {level}"),
+        Fix::LowerThoroughness(level) => {
+            println!("  lower the required thoroughness: --thoroughness {level}");
+        }
+        Fix::LowerUnderstanding(level) => {
+            println!("  lower the required understanding: --understanding {level}");
+        }
+        Fix::LowerRedundancy(redundancy) => {
+            println!("  lower the number of required reviews: --redundancy {redundancy}");
+        }
+    }
+}
+
+pub fn explain_crate(
+    crate_: CrateSelector,
+    common_opts: CrateVerifyCommon,
+    wot_opts: WotOpts,
+) -> Result<()> {
+    crate_.ensure_name_given()?;
+
+    let local = crev_lib::Local::auto_create_or_open()?;
+    let db = local.load_db()?;
+    let trust_set = local.trust_set_for_id(
+        wot_opts.for_id.as_deref(),
+        &wot_opts.trust_params.into(),
+        &db,
+    )?;
+    let requirements = crev_lib::VerificationRequirements::from(common_opts.requirements);
+
+    let repo = Repo::auto_open_cwd(common_opts.cargo_opts)?;
+    let pkg_id = repo.find_pkgid_by_crate_selector(&crate_)?;
+    let package = repo.get_crate(&pkg_id)?;
+    let crev_pkg_id = repo.crates_io_mirrors().crev_pkg_id(&pkg_id);
+
+    let ignore_list = cargo_full_ignore_list(
+        false,
+        &local.load_user_config()?,
+        &common_opts.digest_ignore,
+    );
+    let digest = get_registry_crate_digest(
+        &db,
+        package.root(),
+        &DigestIgnorePolicy::cargo_crate(),
+        &ignore_list,
+        local.cancellation_token(),
+    )?;
+
+    let reviews = crev_lib::explain_package_verification(
+        &digest,
+        &crev_pkg_id,
+        &trust_set,
+        &requirements,
+        &db,
+    );
+    let status = verification_status(&reviews, &digest, &trust_set, &requirements);
+
+    println!("{} {} ({digest})", crev_pkg_id.id.name, crev_pkg_id.version);
+    if reviews.is_empty() {
+        println!("No reviews of this version.");
+    }
+    for (review, verdict) in &reviews {
+        let body = review.review_possibly_none();
+        println!(
+            "  {} thoroughness={} understanding={} rating={}: {verdict}",
+            review.common.from.id, body.thoroughness, body.understanding, body.rating
+        );
+    }
+
+    match status {
+        VerificationStatus::Verified | VerificationStatus::Local => {
+            println!("Verified.");
+        }
+        VerificationStatus::Negative => {
+            println!(
+                "Fails: a trusted Id rated it negative. No other review outweighs that; \
+                 untrust the reviewer, or have an Id trusted more than them override the review."
+            );
+        }
+        VerificationStatus::Insufficient => {
+            let counted = reviews
+                .iter()
+                .filter(|(_, verdict)| *verdict == ReviewVerdict::Counted)
+                .count();
+            println!(
+                "Not verified: {counted} of {} required reviews count.",
+                requirements.redundancy
+            );
+            let fixes = find_fixes(&reviews, &digest, &trust_set, &requirements);
+            if fixes.is_empty() {
+                println!("No single change of trust or requirements would verify it; it needs new reviews.");
+            } else {
+                println!("Any one of these would verify it:");
+                for fix in &fixes {
+                    print_fix(fix);
+                }
+            }
+        }
+    }
+    Ok(())
+}
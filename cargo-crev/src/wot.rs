@@ -1,9 +1,28 @@
-use std::{io, io::Write as _};
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    fmt::Write as _,
+    io,
+    io::Write as _,
+    path::Path,
+};
 
-use crate::{opts::WotOpts, term, url_to_status_str};
+use crate::{
+    deps::scan::{RequiredDetails, Scanner},
+    edit,
+    opts::{self, CrateSelector, CrateVerify, CrateVerifyCommon, IdInspect, WotOpts},
+    repo::Repo,
+    term, url_to_status_str,
+};
 use ::term::color::{BLUE, GREEN, RED, YELLOW};
-use anyhow::Result;
-use crev_wot::trust_set::TraverseLogItem::{Edge, Node};
+use anyhow::{bail, Context, Result};
+use crev_data::{
+    proof::{self, CommonOps, ContentExt},
+    Id, TrustLevel,
+};
+use crev_wot::{
+    trust_set::TraverseLogItem::{Edge, Node},
+    ProofDB, PruneSuggestion, TrustSet,
+};
 use itertools::Itertools;
 
 pub fn print_log(wot_opts: WotOpts) -> Result<()> {
@@ -97,3 +116,798 @@ pub fn print_log(wot_opts: WotOpts) -> Result<()> {
 
     Ok(())
 }
+
+/// What's known about `id`, to have in front of you when setting trust for it
+///
+/// Your current trust, its reviews, and who else trusts or distrusts it.
+/// Conflicts (distrust by others, or changing your existing trust) are called out.
+pub fn trust_draft_context(
+    db: &ProofDB,
+    own_id: &Id,
+    id: &Id,
+    new_level: TrustLevel,
+) -> Result<String> {
+    let mut text = String::new();
+    let (status, url) = url_to_status_str(&db.lookup_url(id));
+    writeln!(text, "{id} {status} {url}")?;
+    match db.get_trust_proof_between(own_id, id) {
+        Some(trust) => {
+            writeln!(
+                text,
+                "  Your current trust: {} (since {})",
+                trust.trust,
+                trust.date().format("%Y-%m-%d")
+            )?;
+            if trust.trust != new_level {
+                writeln!(
+                    text,
+                    "  NOTE: changes your trust from {} to {new_level}",
+                    trust.trust
+                )?;
+            }
+        }
+        None => writeln!(text, "  Your current trust: none")?,
+    }
+    writeln!(
+        text,
+        "  Package reviews: {}",
+        db.get_package_review_count_by_author(id)
+    )?;
+
+    let (distrusted_by, trusted_by): (Vec<_>, Vec<_>) = db
+        .get_reverse_trust_for(id)
+        .filter(|(from, level)| *from != own_id && *level != TrustLevel::None)
+        .sorted_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)))
+        .partition(|(_, level)| *level == TrustLevel::Distrust);
+    if trusted_by.is_empty() {
+        writeln!(text, "  Trusted by: nobody else")?;
+    } else {
+        writeln!(text, "  Trusted by:")?;
+        for (from, level) in trusted_by {
+            let (status, url) = url_to_status_str(&db.lookup_url(from));
+            writeln!(text, "    {from} {level:>6} {status} {url}")?;
+        }
+    }
+    if !distrusted_by.is_empty() {
+        writeln!(text, "  WARNING: distrusted by:")?;
+        for (from, _) in distrusted_by {
+            let (status, url) = url_to_status_str(&db.lookup_url(from));
+            writeln!(text, "    {from} {status} {url}")?;
+        }
+    }
+    Ok(text)
+}
+
+pub fn print_prune_suggestions(wot_opts: WotOpts, stale_years: u32) -> Result<()> {
+    let local = crev_lib::Local::auto_create_or_open()?;
+    let db = local.load_db()?;
+    let for_id = local.get_for_id_from_str(wot_opts.for_id.as_deref())?;
+    let stale_before = chrono::Utc::now() - chrono::Duration::days(365 * i64::from(stale_years));
+
+    let suggestions = db.suggest_trust_prune(&for_id, &wot_opts.trust_params.into(), stale_before);
+    if suggestions.is_empty() {
+        eprintln!("Nothing to prune");
+        return Ok(());
+    }
+    for suggestion in &suggestions {
+        let (status, url) = url_to_status_str(&db.lookup_url(suggestion.id()));
+        match suggestion {
+            PruneSuggestion::Redundant { id, trust_level } => {
+                println!("{id} {trust_level:>6} redundant: trusted as much through others");
+            }
+            PruneSuggestion::Stale {
+                id,
+                trust_level,
+                latest_proof,
+            } => match latest_proof {
+                Some(date) => println!(
+                    "{id} {trust_level:>6} stale: no proofs since {}",
+                    date.format("%Y-%m-%d")
+                ),
+                None => println!("{id} {trust_level:>6} stale: no proofs at all"),
+            },
+        }
+        println!("\\_ {status} {url}");
+    }
+    eprintln!("Retract with `cargo crev id untrust <id>`");
+
+    Ok(())
+}
+
+/// Values `wot tune` tries for each parameter, besides the given one
+const TUNE_DEPTHS: &[u64] = &[0, 1, 2, 3, 5, 10, 20];
+const TUNE_COSTS: &[u64] = &[0, 1, 2, 5, 10, 21];
+
+/// Results of `wot tune` for the trust parameters tried so far
+///
+/// Many parameters give the same trust set, so verification results
+/// are kept by trust set, not only by parameters.
+struct TuneCache<'a> {
+    db: &'a ProofDB,
+    for_id: Id,
+    requirements: crev_lib::VerificationRequirements,
+    digests: Vec<crev_data::Digest>,
+    /// trusted Ids and the `stable_digest` of the trust set, by parameters
+    trust_sets: HashMap<[u64; 6], (usize, Vec<u8>)>,
+    /// verified crates, by `stable_digest` of the trust set
+    verified: HashMap<Vec<u8>, usize>,
+}
+
+impl TuneCache<'_> {
+    /// Trusted Ids and verified crates with `params`
+    fn counts(&mut self, params: &opts::TrustDistanceParams) -> (usize, usize) {
+        let key = [
+            params.depth,
+            params.high_cost,
+            params.medium_cost,
+            params.low_cost,
+            params.none_cost,
+            params.distrust_cost,
+        ];
+        let (db, for_id) = (self.db, &self.for_id);
+        let mut computed = None;
+        let (trusted, set_digest) = self.trust_sets.entry(key).or_insert_with(|| {
+            let trust_set = db.calculate_trust_set(for_id, &params.clone().into());
+            let entry = (
+                trust_set.iter_trusted_ids().count(),
+                trust_set.stable_digest().as_slice().to_vec(),
+            );
+            computed = Some(trust_set);
+            entry
+        });
+        let (digests, requirements) = (&self.digests, &self.requirements);
+        let verified = *self.verified.entry(set_digest.clone()).or_insert_with(|| {
+            let trust_set =
+                computed.unwrap_or_else(|| db.calculate_trust_set(for_id, &params.clone().into()));
+            digests
+                .iter()
+                .filter(|digest| {
+                    crev_lib::verify_package_digest(digest, &trust_set, requirements, db)
+                        .is_verified()
+                })
+                .count()
+        });
+        (*trusted, verified)
+    }
+}
+
+/// Print a sweep over trust parameters, see `opts::Wot::Tune`
+pub fn print_tune(common: CrateVerifyCommon, wot: WotOpts) -> Result<()> {
+    let args = CrateVerify {
+        common,
+        wot,
+        ..Default::default()
+    };
+    if args.wot.trust_params.direct {
+        bail!("`--direct` ignores the parameters `wot tune` varies");
+    }
+    // fail early outside of a cargo project
+    Repo::auto_open_cwd(args.common.cargo_opts.clone())?;
+    let local = crev_lib::Local::auto_create_or_open()?;
+    let scanner = Scanner::new(CrateSelector::default(), &args)?;
+    let db = scanner.db.clone();
+    let digests: Vec<_> = scanner
+        .run(&RequiredDetails::none())
+        .filter_map(|stats| stats.details().digest.clone())
+        .collect();
+    let mut cache = TuneCache {
+        db: &db,
+        for_id: local.get_for_id_from_str(args.wot.for_id.as_deref())?,
+        requirements: args.common.requirements.clone().into(),
+        digests,
+        trust_sets: HashMap::new(),
+        verified: HashMap::new(),
+    };
+
+    let base = args.wot.trust_params;
+    type Field = fn(&mut opts::TrustDistanceParams) -> &mut u64;
+    let sweeps: [(&str, Field, &[u64]); 5] = [
+        ("depth", |p| &mut p.depth, TUNE_DEPTHS),
+        ("high-cost", |p| &mut p.high_cost, TUNE_COSTS),
+        ("medium-cost", |p| &mut p.medium_cost, TUNE_COSTS),
+        ("low-cost", |p| &mut p.low_cost, TUNE_COSTS),
+        ("none-cost", |p| &mut p.none_cost, TUNE_COSTS),
+    ];
+    println!(
+        "{:<12} {:>6} {:>12} {:>16}",
+        "parameter", "value", "trusted-ids", "verified-crates"
+    );
+    for (name, field, values) in sweeps {
+        let current = *field(&mut base.clone());
+        let values: BTreeSet<u64> = values.iter().copied().chain([current]).collect();
+        for value in values {
+            let mut params = base.clone();
+            *field(&mut params) = value;
+            let (trusted, verified) = cache.counts(&params);
+            let verified = format!("{verified}/{}", cache.digests.len());
+            println!(
+                "{:<12} {:>6} {:>12} {:>16}{}",
+                name,
+                value,
+                trusted,
+                verified,
+                if value == current { " (current)" } else { "" }
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Number of Ids `wot health` lists as single points of failure
+const HEALTH_TOP_IDS: usize = 10;
+
+/// Report on how well the web of trust covers the dependencies of the project
+///
+/// Single points of failure are found by computing the trust set again
+/// without each trusted Id in turn, and counting dependencies that are no longer verified.
+pub fn print_health(common: CrateVerifyCommon, wot: WotOpts) -> Result<()> {
+    let args = CrateVerify {
+        common,
+        wot,
+        ..Default::default()
+    };
+    // fail early outside of a cargo project
+    Repo::auto_open_cwd(args.common.cargo_opts.clone())?;
+    let local = crev_lib::Local::auto_create_or_open()?;
+    let scanner = Scanner::new(CrateSelector::default(), &args)?;
+    let db = scanner.db.clone();
+    let digests: Vec<_> = scanner
+        .run(&RequiredDetails::none())
+        .filter_map(|stats| stats.details().digest.clone())
+        .collect();
+    let for_id = local.get_for_id_from_str(args.wot.for_id.as_deref())?;
+    let params: crev_wot::TrustDistanceParams = args.wot.trust_params.into();
+    let requirements: crev_lib::VerificationRequirements = args.common.requirements.into();
+    let count_verified = |trust_set: &TrustSet| {
+        digests
+            .iter()
+            .filter(|digest| {
+                crev_lib::verify_package_digest(digest, trust_set, &requirements, &db).is_verified()
+            })
+            .count()
+    };
+
+    let trust_set = db.calculate_trust_set(&for_id, &params);
+    let verified = count_verified(&trust_set);
+    println!("Dependencies verified: {verified}/{}", digests.len());
+
+    let reviewers: HashSet<&Id> = digests
+        .iter()
+        .flat_map(|digest| db.get_package_reviews_by_digest(digest))
+        .filter_map(|review| {
+            trust_set
+                .trusted
+                .get_key_value(&review.common.from.id)
+                .map(|(id, _)| id)
+        })
+        .filter(|id| **id != for_id)
+        .collect();
+    if reviewers.is_empty() {
+        println!("No trusted Id reviewed any dependency");
+    } else {
+        let total_distance: u64 = reviewers
+            .iter()
+            .map(|id| trust_set.trusted[*id].distance)
+            .sum();
+        println!(
+            "Trusted reviewers of dependencies: {}, at average distance {:.1}",
+            reviewers.len(),
+            total_distance as f64 / reviewers.len() as f64
+        );
+    }
+
+    // removing an Id that neither trusts anyone nor reviewed a dependency changes nothing
+    let mut lost: Vec<(usize, &Id)> = trust_set
+        .iter_trusted_ids()
+        .filter(|id| **id != for_id)
+        .filter(|id| reviewers.contains(id) || db.get_direct_trust_of(id).next().is_some())
+        .map(|id| {
+            let without = db.calculate_trust_set_without_id(&for_id, &params, id);
+            (verified.saturating_sub(count_verified(&without)), id)
+        })
+        .filter(|(lost, _)| 0 < *lost)
+        .collect();
+    lost.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(b.1)));
+    if lost.is_empty() {
+        println!("Single points of failure: none");
+    } else {
+        println!("Single points of failure (verified dependencies lost without the Id):");
+        for (lost, id) in lost.iter().take(HEALTH_TOP_IDS) {
+            let details = &trust_set.trusted[*id];
+            let (status, url) = url_to_status_str(&db.lookup_url(id));
+            println!(
+                "  {id} {:>6} dist {:>3}: -{lost}",
+                details.effective_trust_level, details.distance
+            );
+            println!("  \\_ {status} {url}");
+        }
+    }
+
+    let orphaned: BTreeSet<(&Id, &Id, TrustLevel)> = trust_set
+        .iter_trusted_ids()
+        .flat_map(|from| {
+            db.get_direct_trust_of(from)
+                .map(move |(to, level)| (from, to, level))
+        })
+        .filter(|(_, to, level)| TrustLevel::None < *level && *to != &for_id)
+        .filter(|(_, to, _)| db.get_latest_proof_date_of(to).is_none())
+        .collect();
+    if orphaned.is_empty() {
+        println!("Orphaned trust: none");
+    } else {
+        println!("Orphaned trust (trusted Ids without any proofs; is their proof repo fetched?):");
+        for (from, to, level) in orphaned {
+            let (status, url) = url_to_status_str(&db.lookup_url(to));
+            println!("  {from} -> {to} {level:>6}");
+            println!("  \\_ {status} {url}");
+        }
+    }
+    Ok(())
+}
+
+/// Sign the trust set of the current Id as a snapshot, and print it
+pub fn print_signed_trust_snapshot(wot_opts: WotOpts, comment: String) -> Result<()> {
+    let local = crev_lib::Local::auto_open()?;
+    let id = local.read_current_unlocked_id(&term::read_passphrase)?;
+    if local.get_for_id_from_str(wot_opts.for_id.as_deref())? != id.id.id {
+        bail!("Only your own trust set can be signed; don't use `--for-id`");
+    }
+    let db = local.load_db()?;
+    let params: crev_wot::TrustDistanceParams = wot_opts.trust_params.into();
+    let trust_set = db.calculate_trust_set(&id.id.id, &params);
+
+    let (trusted, distrusted) = trust_set.snapshot_entries();
+    let mut snapshot =
+        id.as_public_id()
+            .create_trust_snapshot((&params).into(), trusted, distrusted)?;
+    (snapshot.ignored_trust, snapshot.ignored_reviews) = trust_set.snapshot_overrides();
+    snapshot.comment = comment;
+    print!("{}", snapshot.sign_by(&id)?);
+    Ok(())
+}
+
+/// Read a signed trust snapshot, checking its signature and its signer
+///
+/// The signer must be one of your own Ids, or trusted at least at `min_level` in `trust_set`.
+pub fn read_trust_snapshot(
+    path: &Path,
+    local: &crev_lib::Local,
+    trust_set: &TrustSet,
+    min_level: TrustLevel,
+) -> Result<proof::TrustSnapshot> {
+    let file = std::fs::File::open(path)
+        .with_context(|| format!("Can't open trust snapshot {}", path.display()))?;
+    let proofs = proof::Proof::parse_from(file)?;
+    let [proof] = proofs.as_slice() else {
+        bail!(
+            "{} should contain exactly one signed document",
+            path.display()
+        );
+    };
+    proof
+        .verify()
+        .with_context(|| format!("Invalid signature of {}", path.display()))?;
+    if proof.kind() != proof::TrustSnapshot::KIND {
+        bail!(
+            "{} is a {}, not a trust snapshot",
+            path.display(),
+            proof.kind()
+        );
+    }
+    let signer = &proof.from().id;
+    let is_own = local
+        .get_current_user_public_ids()?
+        .iter()
+        .any(|own| &own.id == signer);
+    let level = trust_set.get_effective_trust_level(signer);
+    if !is_own && !(trust_set.is_trusted(signer) && level >= min_level) {
+        bail!(
+            "{} is signed by {signer}, trusted {level}; it must be your own Id, or trusted at least {min_level}",
+            path.display()
+        );
+    }
+    Ok(proof.parse_content()?)
+}
+
+/// Check a trust snapshot signed by an Id you trust, and compare it with your trust set
+pub fn import_trust_snapshot(path: &Path, wot_opts: WotOpts) -> Result<()> {
+    let local = crev_lib::Local::auto_create_or_open()?;
+    let db = local.load_db()?;
+    let params: crev_wot::TrustDistanceParams = wot_opts.trust_params.clone().into();
+    let trust_set = local.trust_set_for_id(wot_opts.for_id.as_deref(), &params, &db)?;
+    let snapshot = read_trust_snapshot(path, &local, &trust_set, TrustLevel::Low)?;
+    let theirs = crev_wot::TrustSet::from_snapshot(&snapshot);
+
+    let author = &snapshot.from().id;
+    let (status, url) = url_to_status_str(&db.lookup_url(author));
+    println!("Signature OK: {author} {status} {url}");
+    println!("Computed on: {}", snapshot.date().format("%Y-%m-%d %H:%M"));
+    println!(
+        "Your trust in its author: {}",
+        trust_set.get_effective_trust_level(author)
+    );
+    if snapshot.params != proof::TrustSnapshotParams::from(&params) {
+        println!("NOTE: computed with trust parameters different from yours");
+    }
+    if !snapshot.comment.is_empty() {
+        println!("Comment: {}", snapshot.comment);
+    }
+
+    let ids: BTreeSet<&Id> = trust_set
+        .trusted
+        .keys()
+        .chain(trust_set.distrusted.keys())
+        .chain(theirs.trusted.keys())
+        .chain(theirs.distrusted.keys())
+        .collect();
+    let differences: Vec<_> = ids
+        .into_iter()
+        .filter_map(|id| {
+            let yours = trust_set.get_effective_trust_level_opt(id);
+            let their = theirs.get_effective_trust_level_opt(id);
+            (yours != their).then_some((id, yours, their))
+        })
+        .collect();
+    println!(
+        "Ids: {} trusted, {} distrusted; {} differ from your trust set",
+        snapshot.trusted.len(),
+        snapshot.distrusted.len(),
+        differences.len()
+    );
+    let level_str = |level: Option<TrustLevel>| level.map_or("-".to_owned(), |l| l.to_string());
+    for (id, yours, their) in differences {
+        let (status, url) = url_to_status_str(&db.lookup_url(id));
+        println!(
+            "{id} theirs: {:>8} yours: {:>8} {status} {url}",
+            level_str(their),
+            level_str(yours)
+        );
+    }
+    eprintln!(
+        "Verify with their trust with `cargo crev verify --trust-snapshot {}`",
+        path.display()
+    );
+    Ok(())
+}
+
+/// Everything known about `id`, to decide how much to trust it
+///
+/// `used` are the crates of the current project, if there is one.
+fn inspect_id_text(
+    db: &ProofDB,
+    trust_set: &TrustSet,
+    for_id: &Id,
+    id: &Id,
+    used: Option<&HashSet<proof::PackageVersionId>>,
+    limit: usize,
+) -> Result<String> {
+    let mut text = String::new();
+    let (status, url) = url_to_status_str(&db.lookup_url(id));
+    writeln!(text, "{id} {status} {url}")?;
+    match db.get_latest_proof_date_of(id) {
+        Some(date) => writeln!(text, "Latest proof: {}", date.format("%Y-%m-%d"))?,
+        None => writeln!(text, "Latest proof: none")?,
+    }
+
+    writeln!(text, "\nWeb of Trust of {for_id}:")?;
+    if let Some(details) = trust_set.trusted.get(id) {
+        writeln!(
+            text,
+            "  Effective trust: {}, distance {}",
+            details.effective_trust_level, details.distance
+        )?;
+        let reported_by = details
+            .reported_by
+            .iter()
+            .sorted_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)))
+            .map(|(from, level)| format!("{from} ({level})"))
+            .join(", ");
+        writeln!(text, "  Through: {reported_by}")?;
+    } else if let Some(details) = trust_set.distrusted.get(id) {
+        writeln!(
+            text,
+            "  DISTRUSTED by: {}",
+            details.reported_by.iter().sorted().join(", ")
+        )?;
+    } else {
+        writeln!(text, "  Not trusted")?;
+    }
+    match db.get_trust_proof_between(for_id, id) {
+        Some(trust) => writeln!(
+            text,
+            "  Direct trust: {} (since {})",
+            trust.trust,
+            trust.date().format("%Y-%m-%d")
+        )?,
+        None => writeln!(text, "  Direct trust: none")?,
+    }
+    let (distrusted_by, trusted_by): (Vec<_>, Vec<_>) = db
+        .get_reverse_trust_for(id)
+        .filter(|(_, level)| *level != TrustLevel::None)
+        .partition(|(_, level)| *level == TrustLevel::Distrust);
+    let in_wot = |ids: &[(&Id, TrustLevel)]| {
+        ids.iter()
+            .filter(|(from, _)| trust_set.is_trusted(from))
+            .count()
+    };
+    writeln!(
+        text,
+        "  Trusted by {} Ids ({} trusted by you), distrusted by {} ({} trusted by you)",
+        trusted_by.len(),
+        in_wot(&trusted_by),
+        distrusted_by.len(),
+        in_wot(&distrusted_by)
+    )?;
+    let trusts: Vec<_> = db
+        .get_direct_trust_of(id)
+        .filter(|(_, level)| *level != TrustLevel::None)
+        .collect();
+    writeln!(
+        text,
+        "  Trusts {} Ids ({} trusted by you)",
+        trusts.len(),
+        in_wot(&trusts)
+    )?;
+
+    let reviews: Vec<_> = db
+        .get_package_reviews_by_author(id)
+        .sorted_by(|a, b| b.date_utc().cmp(&a.date_utc()))
+        .collect();
+
+    match used {
+        Some(used) => {
+            let used_packages: HashSet<_> = used.iter().map(|package| &package.id).collect();
+            let (same_version, other_version): (Vec<&proof::review::Package>, Vec<_>) = reviews
+                .iter()
+                .copied()
+                .filter(|review| used_packages.contains(&review.package.id.id))
+                .partition(|review| used.contains(&review.package.id));
+            writeln!(
+                text,
+                "\nCrates you use ({}) it reviewed: {} of the version you use, {} of other versions",
+                used.len(),
+                same_version.len(),
+                other_version.len()
+            )?;
+            for review in same_version {
+                let opinion = review.review_possibly_none();
+                writeln!(
+                    text,
+                    "  {} {} {} {}/{}",
+                    review.package.id.id.name,
+                    review.package.id.version,
+                    opinion.rating,
+                    opinion.thoroughness,
+                    opinion.understanding
+                )?;
+            }
+        }
+        None => writeln!(text, "\nCrates you use: not in a cargo project")?,
+    }
+
+    let advisories: Vec<_> = reviews
+        .iter()
+        .flat_map(|review| {
+            review
+                .advisories
+                .iter()
+                .map(move |advisory| (review, advisory))
+        })
+        .collect();
+    writeln!(text, "\nAdvisories: {}", advisories.len())?;
+    for (review, advisory) in advisories {
+        writeln!(
+            text,
+            "  {} {} {} {} {}",
+            review.date_utc().format("%Y-%m-%d"),
+            review.package.id.id.name,
+            review.package.id.version,
+            advisory.severity,
+            advisory.ids.join(",")
+        )?;
+        for line in advisory.comment.lines() {
+            writeln!(text, "    {line}")?;
+        }
+    }
+
+    writeln!(
+        text,
+        "\nReviews: {} (most recent {} below)",
+        reviews.len(),
+        limit.min(reviews.len())
+    )?;
+    for review in reviews.iter().take(limit) {
+        let opinion = review.review_possibly_none();
+        writeln!(
+            text,
+            "  {} {} {} {} {}/{}",
+            review.date_utc().format("%Y-%m-%d"),
+            review.package.id.id.name,
+            review.package.id.version,
+            opinion.rating,
+            opinion.thoroughness,
+            opinion.understanding
+        )?;
+        for line in review.comment.lines() {
+            writeln!(text, "    {line}")?;
+        }
+    }
+    Ok(text)
+}
+
+/// `id inspect`: show what's known about an Id, in a pager when on a terminal
+pub fn inspect_id(args: IdInspect) -> Result<()> {
+    let id = Id::crevid_from_str(&args.public_id)
+        .with_context(|| format!("'{}' is not a valid crev Id", args.public_id))?;
+    let local = crev_lib::Local::auto_create_or_open()?;
+    let db = local.load_db()?;
+    let for_id = local.get_for_id_from_str(args.wot.for_id.as_deref())?;
+    let trust_set = db.calculate_trust_set(&for_id, &args.wot.trust_params.into());
+
+    let in_cargo_project = args.cargo_opts.manifest_path.is_some()
+        || cargo::util::important_paths::find_root_manifest_for_wd(&std::env::current_dir()?)
+            .is_ok();
+    let used = if in_cargo_project {
+        let repo = Repo::auto_open_cwd(args.cargo_opts)?;
+        let mirrors = repo.crates_io_mirrors();
+        let mut used = HashSet::new();
+        repo.for_every_non_local_dep_crate_id(|pkg_id| {
+            used.insert(mirrors.crev_pkg_id(pkg_id));
+            Ok(())
+        })
+        .context("Can't list the crates of the current project")?;
+        Some(used)
+    } else {
+        None
+    };
+
+    let text = inspect_id_text(&db, &trust_set, &for_id, &id, used.as_ref(), args.limit)?;
+    if !args.no_pager && term::Term::new().is_interactive() {
+        edit::view_text_in_pager(&text)
+    } else {
+        print!("{text}");
+        Ok(())
+    }
+}
+
+/// Parse `NAME@ID` (or only `NAME`, for a group of `default_owner`) into the owner and the name
+pub fn parse_group_ref(s: &str, default_owner: Option<&Id>) -> Result<(Id, String)> {
+    let (name, owner) = match s.rsplit_once('@') {
+        Some((name, owner)) => (
+            name,
+            Id::crevid_from_str(owner)
+                .with_context(|| format!("'{owner}' is not a valid crev Id"))?,
+        ),
+        None => match default_owner {
+            Some(owner) => (s, owner.clone()),
+            None => bail!("'{s}' is not a group: use NAME@ID"),
+        },
+    };
+    if !proof::is_valid_group_name(name) {
+        bail!("'{name}' is not a valid group name: use lowercase letters, digits and `-`");
+    }
+    Ok((owner, name.to_owned()))
+}
+
+/// `id group publish`: sign the group `name` of `ids`, replacing the previous version
+pub fn publish_group(
+    name: String,
+    ids: Vec<Id>,
+    comment: String,
+    common_proof_create: &opts::CommonProofCreate,
+) -> Result<()> {
+    let local = crev_lib::Local::auto_open()?;
+    let id = local.read_current_unlocked_id(&term::read_passphrase)?;
+    let commit_msg = format!("Publish group {name}");
+    let group = local.build_group_proof(id.as_public_id(), name, ids, comment)?;
+    let proof = group.sign_by(&id)?;
+    crate::shared::maybe_store(&local, &id, &proof, &commit_msg, common_proof_create)
+}
+
+/// `id group show`: members of a group, as known from the fetched proofs
+pub fn show_group(group: &str) -> Result<()> {
+    let local = crev_lib::Local::auto_create_or_open()?;
+    let current_id = local.get_current_userid_opt()?;
+    let (owner, name) = parse_group_ref(group, current_id.as_ref())?;
+    let db = local.load_db()?;
+    let Some(group) = db.get_group(&owner, &name) else {
+        bail!("No group {name}@{owner} in the fetched proofs. Try `cargo crev repo fetch all`.");
+    };
+    println!(
+        "{name}@{owner} (published {})",
+        group.date_utc().format("%Y-%m-%d")
+    );
+    if !group.comment.is_empty() {
+        println!("{}", group.comment);
+    }
+    for member in &group.ids {
+        let (status, url) = url_to_status_str(&db.lookup_url(&member.id));
+        println!("{} {status} {url}", member.id);
+    }
+    Ok(())
+}
+
+/// `id group list`: groups published by an Id
+pub fn list_groups(id: Option<&str>) -> Result<()> {
+    let local = crev_lib::Local::auto_create_or_open()?;
+    let owner = local.get_for_id_from_str(id)?;
+    let db = local.load_db()?;
+    for group in db
+        .get_groups_of(&owner)
+        .sorted_by(|a, b| a.name.cmp(&b.name))
+    {
+        println!("{}@{owner} {} members", group.name, group.ids.len());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crev_data::{UnlockedId, Url, Version, SOURCE_CRATES_IO};
+    use crev_wot::{FetchSource, TrustDistanceParams};
+    use std::sync::Arc;
+
+    #[test]
+    fn inspect_shows_trust_and_reviews_of_used_crates() -> Result<()> {
+        let url = FetchSource::Url(Arc::new(Url::new_git("https://example.com")));
+        let me = UnlockedId::generate_for_git_url("https://me");
+        let alice = UnlockedId::generate_for_git_url("https://alice");
+        let package = |version: &str| {
+            proof::PackageVersionId::new(
+                SOURCE_CRATES_IO.to_owned(),
+                "leftpad".into(),
+                Version::parse(version).unwrap(),
+            )
+        };
+        let review = |version: &str| -> Result<proof::Proof> {
+            Ok(proof::review::PackageBuilder::default()
+                .from(alice.id.clone())
+                .package(proof::PackageInfo {
+                    id: package(version),
+                    digest: vec![],
+                    digest_type: proof::default_digest_type(),
+                    revision: String::new(),
+                    revision_type: proof::default_revision_type(),
+                    digest_ignore: None,
+                })
+                .review(proof::review::Review::new_positive())
+                .comment(format!("checked {version}"))
+                .build()
+                .map_err(|e| anyhow::format_err!("{e}"))?
+                .sign_by(&alice)?)
+        };
+        let trust =
+            me.create_signed_trust_proof(vec![alice.as_public_id()], TrustLevel::High, vec![])?;
+
+        let mut db = ProofDB::new();
+        db.import_from_iter(
+            vec![trust, review("1.0.0")?, review("2.0.0")?]
+                .into_iter()
+                .map(|proof| (proof, url.clone())),
+        );
+        let trust_set = db.calculate_trust_set(&me.id.id, &TrustDistanceParams::default());
+        let used = HashSet::from([package("2.0.0")]);
+
+        let text = inspect_id_text(&db, &trust_set, &me.id.id, &alice.id.id, Some(&used), 1)?;
+        assert!(text.contains("Effective trust: high"), "{text}");
+        assert!(text.contains("Direct trust: high"), "{text}");
+        assert!(
+            text.contains("Trusted by 1 Ids (1 trusted by you)"),
+            "{text}"
+        );
+        assert!(
+            text.contains("1 of the version you use, 1 of other versions"),
+            "{text}"
+        );
+        assert!(text.contains("Reviews: 2 (most recent 1 below)"), "{text}");
+
+        let text = inspect_id_text(&db, &trust_set, &me.id.id, &alice.id.id, None, 20)?;
+        assert!(
+            text.contains("Crates you use: not in a cargo project"),
+            "{text}"
+        );
+        assert_eq!(text.matches("    checked ").count(), 2, "{text}");
+        Ok(())
+    }
+}
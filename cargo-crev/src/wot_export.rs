@@ -0,0 +1,192 @@
+//! `wot export`: the trust graph reachable from an Id, for visualization
+//!
+//! Graphviz DOT (eg. `cargo crev wot export | dot -Tsvg > wot.svg`), or JSON.
+use crate::{opts::WotOpts, prelude::*};
+use chrono::{DateTime, Utc};
+use crev_data::{Id, TrustLevel};
+use crev_wot::{ProofDB, TrustEdge, TrustSet};
+use serde::Serialize;
+use std::{collections::BTreeSet, fmt::Write as _};
+
+/// Format of `wot export`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WotExportFormat {
+    #[default]
+    Dot,
+    Json,
+}
+
+impl std::str::FromStr for WotExportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "dot" => Self::Dot,
+            "json" => Self::Json,
+            _ => bail!("Must be `dot` or `json`, not `{}`", s),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct GraphNode {
+    pub id: String,
+    /// Effective trust in the Id, if it's in the trust set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trust: Option<TrustLevel>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct GraphEdge {
+    pub from: String,
+    pub to: String,
+    pub trust: TrustLevel,
+    pub date: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct TrustGraph {
+    pub root: String,
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+impl TrustGraph {
+    pub fn new(root: &Id, edges: Vec<TrustEdge>, trust_set: &TrustSet, db: &ProofDB) -> Self {
+        let ids: BTreeSet<&Id> = std::iter::once(root)
+            .chain(edges.iter().flat_map(|edge| [&edge.from, &edge.to]))
+            .collect();
+        let nodes = ids
+            .into_iter()
+            .map(|id| GraphNode {
+                id: id.to_string(),
+                trust: trust_set.get_effective_trust_level_opt(id),
+                url: db
+                    .lookup_url(id)
+                    .any_unverified()
+                    .map(|url| url.url.clone()),
+            })
+            .collect();
+        let edges = edges
+            .into_iter()
+            .map(|edge| GraphEdge {
+                from: edge.from.to_string(),
+                to: edge.to.to_string(),
+                trust: edge.level,
+                date: edge.date,
+            })
+            .collect();
+        Self {
+            root: root.to_string(),
+            nodes,
+            edges,
+        }
+    }
+
+    #[must_use]
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph wot {\n  node [shape=box];\n");
+        for node in &self.nodes {
+            let mut label = node.id.clone();
+            if let Some(trust) = node.trust {
+                let _ = write!(label, "\ntrust: {trust}");
+            }
+            if let Some(url) = &node.url {
+                let _ = write!(label, "\n{url}");
+            }
+            let style = if node.id == self.root {
+                ", style=bold"
+            } else {
+                ""
+            };
+            let _ = writeln!(
+                dot,
+                "  {} [label={}{style}];",
+                quoted(&node.id),
+                quoted(&label)
+            );
+        }
+        for edge in &self.edges {
+            let style = if edge.trust < TrustLevel::None {
+                ", color=red"
+            } else {
+                ""
+            };
+            let label = format!("{} {}", edge.trust, edge.date.format("%Y-%m-%d"));
+            let _ = writeln!(
+                dot,
+                "  {} -> {} [label={}{style}];",
+                quoted(&edge.from),
+                quoted(&edge.to),
+                quoted(&label)
+            );
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// A DOT string literal
+fn quoted(s: &str) -> String {
+    let escaped = s
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+    format!("\"{escaped}\"")
+}
+
+pub fn export(wot_opts: WotOpts, format: WotExportFormat) -> Result<()> {
+    let local = crev_lib::Local::auto_create_or_open()?;
+    let db = local.load_db()?;
+    let root = local.get_for_id_from_str(wot_opts.for_id.as_deref())?;
+    let trust_set = local.trust_set_for_id(
+        wot_opts.for_id.as_deref(),
+        &wot_opts.trust_params.clone().into(),
+        &db,
+    )?;
+    let graph = TrustGraph::new(&root, db.get_trust_graph_from(&root), &trust_set, &db);
+    match format {
+        WotExportFormat::Dot => print!("{}", graph.to_dot()),
+        WotExportFormat::Json => println!("{}", serde_json::to_string_pretty(&graph)?),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dot_escapes_labels() {
+        let graph = TrustGraph {
+            root: "a".into(),
+            nodes: vec![
+                GraphNode {
+                    id: "a".into(),
+                    trust: None,
+                    url: Some("https://example.com/\"a\"".into()),
+                },
+                GraphNode {
+                    id: "b".into(),
+                    trust: Some(TrustLevel::Low),
+                    url: None,
+                },
+            ],
+            edges: vec![GraphEdge {
+                from: "a".into(),
+                to: "b".into(),
+                trust: TrustLevel::Distrust,
+                date: DateTime::from_timestamp(0, 0).unwrap(),
+            }],
+        };
+        let dot = graph.to_dot();
+        assert!(dot.contains(r#""a" [label="a\nhttps://example.com/\"a\"", style=bold];"#));
+        assert!(dot.contains(r#""b" [label="b\ntrust: low"];"#));
+        assert!(dot.contains(r#""a" -> "b" [label="distrust 1970-01-01", color=red];"#));
+    }
+}
@@ -0,0 +1,98 @@
+//! Scenarios driving the real binary, see `crev-test-harness`
+//!
+//! They need `cargo` (to lock the test projects) and take a while, so run them with
+//! `cargo test -p cargo-crev --test e2e -- --ignored`.
+use crev_test_harness::Harness;
+
+fn harness() -> Harness {
+    Harness::new(env!("CARGO_BIN_EXE_cargo-crev"))
+}
+
+#[test]
+#[ignore]
+fn reviews_count_once_the_reviewer_is_trusted() {
+    let harness = harness();
+    harness.add_crate("leftpad", "1.0.0", "pub fn pad() {}\n");
+    let project = harness.project("app", &[("leftpad", "1.0.0")]);
+
+    let alice = harness.user("alice");
+    alice.review(&project, "leftpad").assert_success();
+    let alice_proofs = harness.proof_remote("alice");
+    alice.publish_proofs(&alice_proofs);
+
+    let bob = harness.user("bob");
+    bob.fetch(&alice_proofs).assert_success();
+    bob.verify(&project).assert_failure();
+
+    bob.trust(&alice).assert_success();
+    let verify = bob.verify(&project);
+    verify.assert_success();
+    assert!(
+        verify
+            .stdout()
+            .lines()
+            .any(|line| line.contains("leftpad") && line.contains("pass")),
+        "{verify:?}"
+    );
+}
+
+#[test]
+#[ignore]
+fn untrusted_reviews_dont_count() {
+    let harness = harness();
+    harness.add_crate("leftpad", "1.0.0", "pub fn pad() {}\n");
+    harness.add_crate("rightpad", "1.0.0", "pub fn pad() {}\n");
+    let project = harness.project("app", &[("leftpad", "1.0.0"), ("rightpad", "1.0.0")]);
+
+    let alice = harness.user("alice");
+    alice.review(&project, "leftpad").assert_success();
+    let alice_proofs = harness.proof_remote("alice");
+    alice.publish_proofs(&alice_proofs);
+
+    let mallory = harness.user("mallory");
+    mallory.review(&project, "rightpad").assert_success();
+    let mallory_proofs = harness.proof_remote("mallory");
+    mallory.publish_proofs(&mallory_proofs);
+
+    let bob = harness.user("bob");
+    bob.fetch(&alice_proofs).assert_success();
+    bob.fetch(&mallory_proofs).assert_success();
+    bob.trust(&alice).assert_success();
+
+    let verify = bob.verify(&project);
+    verify.assert_failure();
+    let stdout = verify.stdout();
+    let line_of = |name: &str| {
+        stdout
+            .lines()
+            .find(|line| line.contains(name))
+            .unwrap_or_else(|| panic!("no {name} in {verify:?}"))
+    };
+    assert!(line_of("leftpad").contains("pass"), "{verify:?}");
+    assert!(line_of("rightpad").contains("none"), "{verify:?}");
+}
+
+#[test]
+#[ignore]
+fn verify_leaves_out_dependencies_of_disabled_features() {
+    let harness = harness();
+    harness.add_crate("leftpad", "1.0.0", "pub fn pad() {}\n");
+    harness.add_crate("rightpad", "1.0.0", "pub fn pad() {}\n");
+    let project =
+        harness.project_with_optional("app", &[("leftpad", "1.0.0")], &[("rightpad", "1.0.0")]);
+    let bob = harness.user("bob");
+
+    let lists_rightpad = |args: &[&str]| {
+        let verify = bob.run(&project, [&["crate", "verify"], args].concat(), "");
+        let stdout = verify.stdout();
+        assert!(stdout.contains("leftpad"), "{verify:?}");
+        stdout.contains("rightpad")
+    };
+    assert!(lists_rightpad(&[]));
+    assert!(!lists_rightpad(&["--no-default-features"]));
+    assert!(lists_rightpad(&[
+        "--no-default-features",
+        "--features",
+        "rightpad"
+    ]));
+}
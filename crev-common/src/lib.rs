@@ -11,7 +11,7 @@ pub mod serde;
 
 pub use crate::blake2b256::Blake2b256;
 
-use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
 use base64::Engine;
 use blake2::{digest::FixedOutput, Digest};
 use std::{
@@ -20,6 +20,10 @@ use std::{
     io::{self, BufRead, Write},
     path::{Path, PathBuf},
     process,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
 };
 
 #[derive(Debug, thiserror::Error)]
@@ -62,6 +66,18 @@ pub fn base64_encode<T: ?Sized + AsRef<[u8]>>(input: &T) -> String {
     URL_SAFE_NO_PAD.encode(input)
 }
 
+/// Standard alphabet with padding, as used by eg. OpenSSH
+pub fn base64_standard_decode<T: ?Sized + AsRef<[u8]>>(
+    input: &T,
+) -> Result<Vec<u8>, base64::DecodeError> {
+    STANDARD.decode(input)
+}
+
+/// Standard alphabet with padding, as used by eg. OpenSSH
+pub fn base64_standard_encode<T: ?Sized + AsRef<[u8]>>(input: &T) -> String {
+    STANDARD.encode(input)
+}
+
 /// Takes a name and converts it to something safe for use in paths etc.
 ///
 /// # Examples
@@ -197,6 +213,39 @@ pub enum CancelledError {
     ByUser,
     #[error("Cancelled due to terminal I/O error")]
     NoInput,
+    #[error("Interrupted")]
+    Interrupted,
+}
+
+/// Flag used to ask long-running operations to stop at the next safe point
+///
+/// Clones share the same flag, so it can be handed to e.g. a Ctrl-C handler,
+/// while the operation itself periodically calls `check()`.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    /// Cancellation checkpoint
+    pub fn check(&self) -> std::result::Result<(), CancelledError> {
+        if self.is_cancelled() {
+            return Err(CancelledError::Interrupted);
+        }
+        Ok(())
+    }
 }
 
 pub fn try_again_or_cancel() -> std::result::Result<(), CancelledError> {
@@ -247,7 +296,20 @@ pub fn run_with_shell_cmd_custom(
     arg: Option<&Path>,
     capture_stdout: bool,
 ) -> io::Result<std::process::Output> {
-    if cfg!(windows) {
+    shell_cmd(cmd, arg)?
+        .stdin(process::Stdio::inherit())
+        .stderr(process::Stdio::inherit())
+        .stdout(if capture_stdout {
+            process::Stdio::piped()
+        } else {
+            process::Stdio::inherit()
+        })
+        .output()
+}
+
+/// `cmd` run by the platform's shell, with `arg` (escaped) appended
+pub fn shell_cmd(cmd: &OsStr, arg: Option<&Path>) -> io::Result<process::Command> {
+    Ok(if cfg!(windows) {
         // cmd.exe /c "..." or cmd.exe /k "..." avoid unescaping "...", which makes .arg()'s built-in escaping problematic:
         // https://github.com/rust-lang/rust/blob/379c380a60e7b3adb6c6f595222cbfa2d9160a20/src/libstd/sys/windows/process.rs#L488
         // We can bypass this by (ab)using env vars.  Bonus points:  invalid unicode still works.
@@ -278,15 +340,7 @@ pub fn run_with_shell_cmd_custom(
         proc
     } else {
         panic!("What platform are you running this on? Please submit a PR!");
-    }
-    .stdin(process::Stdio::inherit())
-    .stderr(process::Stdio::inherit())
-    .stdout(if capture_stdout {
-        process::Stdio::piped()
-    } else {
-        process::Stdio::inherit()
     })
-    .output()
 }
 
 pub fn save_to_yaml_file<T>(path: &Path, t: &T) -> Result<(), YAMLIOError>
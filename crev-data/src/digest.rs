@@ -1,40 +1,26 @@
-use std::fmt;
+pub use crev_schema::digest::*;
+use sha2::digest::DynDigest;
 
-/// This is a cryptographic hash of everything in a crate, which reliably identifies its exact source code
-#[derive(Eq, PartialEq, Debug, Clone)]
-pub struct Digest([u8; 32]);
+/// Hash functions of [`DigestType`]s
+pub trait DigestTypeExt {
+    /// A fresh hasher, eg. to digest files one at a time
+    fn hasher(self) -> Box<dyn DynDigest>;
 
-impl From<[u8; 32]> for Digest {
-    fn from(arr: [u8; 32]) -> Self {
-        Self(arr)
-    }
+    fn digest(self, bytes: &[u8]) -> Digest;
 }
 
-impl Digest {
-    #[must_use]
-    pub fn as_slice(&self) -> &[u8] {
-        &self.0
-    }
-
-    #[must_use]
-    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
-        if bytes.len() == 32 {
-            let mut out = [0; 32];
-            out.copy_from_slice(bytes);
-            Some(Self(out))
-        } else {
-            None
+impl DigestTypeExt for DigestType {
+    fn hasher(self) -> Box<dyn DynDigest> {
+        match self {
+            Self::Blake2b => Box::<crev_common::Blake2b256>::default(),
+            Self::Sha256 => Box::<sha2::Sha256>::default(),
+            Self::Sha512 => Box::<sha2::Sha512>::default(),
         }
     }
 
-    #[must_use]
-    pub fn into_vec(self) -> Vec<u8> {
-        self.as_slice().to_vec()
-    }
-}
-
-impl fmt::Display for Digest {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.write_str(&crev_common::base64_encode(&self.0))
+    fn digest(self, bytes: &[u8]) -> Digest {
+        let mut hasher = self.hasher();
+        hasher.update(bytes);
+        Digest::from_bytes(&hasher.finalize()).expect("digests are 32 or 64 bytes")
     }
 }
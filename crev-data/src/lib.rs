@@ -1,6 +1,9 @@
 //! This crate contains only code handling data types
 //! used by `crev`, without getting into details
 //! how actually `crev` works (where and how it manages data).
+//!
+//! The types themselves are defined in `crev-schema`, and re-exported
+//! here along with signing, verification and drafts for editing them.
 #![allow(clippy::default_trait_access)]
 #![allow(clippy::items_after_statements)]
 #![allow(clippy::missing_errors_doc)]
@@ -9,16 +12,16 @@
 
 pub mod digest;
 pub mod id;
-pub mod level;
 pub mod proof;
 pub mod url;
 #[macro_use]
 pub mod util;
 use crate::{id::IdError, proof::content::ValidationError};
+pub use crev_schema::level;
 pub use semver::Version;
 
 pub use crate::{
-    digest::Digest,
+    digest::{Digest, DigestType, DigestTypeExt},
     id::{Id, PublicId, UnlockedId},
     level::Level,
     proof::{
@@ -35,6 +38,12 @@ pub type RegistrySource<'a> = &'a str;
 /// Constant for `source` arguments, indicating
 pub const SOURCE_CRATES_IO: RegistrySource<'static> = "https://crates.io";
 
+/// Prefix of the `source` of packages from a git repository, followed by its URL
+/// (eg. `git+https://github.com/crev-dev/cargo-crev`)
+///
+/// The commit is recorded as the `revision` of the package, not in the source.
+pub const SOURCE_GIT_PREFIX: &str = "git+";
+
 #[cfg(test)]
 mod tests;
 
@@ -68,6 +77,12 @@ pub enum Error {
     #[error("YAML formatting: {}", _0)]
     YAMLFormat(Box<str>),
 
+    #[error("TOML formatting: {}", _0)]
+    TOMLFormat(Box<str>),
+
+    #[error("Unknown draft format: {} (expected `yaml` or `toml`)", _0)]
+    UnknownDraftFormat(Box<str>),
+
     #[error(transparent)]
     Id(#[from] IdError),
 
@@ -90,11 +105,20 @@ pub enum Error {
     SerializedTooManyProofs(usize),
 }
 
+impl From<proof::BuildProofError> for Error {
+    fn from(e: proof::BuildProofError) -> Self {
+        Self::BuildingProof(e.0)
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum ParseError {
     #[error("Draft parse error: {}", _0)]
     Draft(#[source] serde_yaml::Error),
 
+    #[error("TOML draft parse error: {}", _0)]
+    DraftToml(#[source] toml::de::Error),
+
     #[error("Proof parse error: {}", _0)]
     Proof(#[source] serde_yaml::Error),
 }
@@ -1,15 +1,8 @@
 use crate::{proof, proof::Proof, Error, ParseError, Result};
 use chrono::{self, prelude::*};
-use crev_common::{
-    self,
-    serde::{as_base64, as_rfc3339_fixed, from_base64, from_rfc3339_fixed},
-};
-use derive_builder::Builder;
-use serde::{self, Deserialize, Serialize};
+pub use crev_schema::proof::content::*;
 use std::{fmt, io};
 
-pub type Date = chrono::DateTime<FixedOffset>;
-
 /// Common operations on types containing `Common`
 pub trait CommonOps {
     // until we support legacy, we have to stick to `Common` here
@@ -61,39 +54,6 @@ pub trait CommonOps {
     }
 }
 
-/// Reference to original proof when reissuing
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct OriginalReference {
-    /// original proof digest (blake2b256)
-    #[serde(serialize_with = "as_base64", deserialize_with = "from_base64")]
-    pub proof: Vec<u8>,
-    /// Any text
-    #[serde(skip_serializing_if = "String::is_empty", default = "Default::default")]
-    pub comment: String,
-}
-
-/// A `Common` part of every `Content` format
-#[derive(Clone, Builder, Debug, Serialize, Deserialize)]
-pub struct Common {
-    /// Type of this review/trust/whatever file
-    pub kind: Option<String>,
-    /// A version, to allow future backward-incompatible extensions
-    /// and changes.
-    pub version: i64,
-    #[builder(default = "crev_common::now()")]
-    #[serde(
-        serialize_with = "as_rfc3339_fixed",
-        deserialize_with = "from_rfc3339_fixed"
-    )]
-    /// Timestamp of proof creation
-    pub date: chrono::DateTime<FixedOffset>,
-    /// Author of the proof
-    pub from: crate::PublicId,
-    /// Reference to original proof when reissuing
-    #[serde(skip_serializing_if = "Option::is_none", default = "Option::default")]
-    pub original: Option<OriginalReference>,
-}
-
 impl CommonOps for Common {
     fn common(&self) -> &Common {
         self
@@ -128,6 +88,38 @@ pub enum ValidationError {
     /// Advisories with an empty `id` field are not allowed
     #[error("Advisories with an empty `id` field are not allowed")]
     AdvisoriesWithAnEmptyIDFieldAreNotAllowed,
+
+    /// Subtree paths must be relative and `/`-separated, without `.` or `..`
+    #[error("Invalid subtree path: {}", _0)]
+    InvalidSubtreePath(Box<str>),
+
+    /// Covered paths are relative to the package root, like subtree paths
+    #[error("Invalid covered path: {}", _0)]
+    InvalidCoveredPath(Box<str>),
+
+    /// Issue updates can't mark an issue as a duplicate of itself
+    #[error("An issue can't be a duplicate of itself")]
+    IssueDuplicateOfItself,
+
+    /// Group names are lowercase ASCII letters, digits and `-`
+    #[error("Invalid group name: {}", _0)]
+    InvalidGroupName(Box<str>),
+
+    /// Encrypted proofs need someone to be able to read them
+    #[error("Encrypted proof without recipients")]
+    EncryptedWithoutRecipients,
+
+    /// Waivers have to say why the package can't wait for reviews
+    #[error("Waiver without a reason")]
+    WaiverWithoutReason,
+
+    /// An Id can't be its own successor
+    #[error("An Id can't be its own successor")]
+    SuccessorOfItself,
+
+    /// Successor proofs are signed by the predecessor or the successor
+    #[error("Successor proof not signed by the predecessor or the successor")]
+    SuccessorSignedByOtherId,
 }
 
 pub type ValidationResult<T> = std::result::Result<T, ValidationError>;
@@ -192,6 +184,73 @@ impl Draft {
     }
 }
 
+/// Format of a `Draft` body presented to the user
+///
+/// Drafts are YAML; other formats are converted to YAML before
+/// being applied, so the signed proof body is the same.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DraftFormat {
+    #[default]
+    Yaml,
+    Toml,
+}
+
+impl DraftFormat {
+    /// Convert a YAML draft body to this format
+    ///
+    /// Comment lines are lost, and so are `null` values, which
+    /// TOML can't express; drafts have no fields where it matters.
+    pub fn convert_yaml(self, yaml: &str) -> Result<String> {
+        match self {
+            DraftFormat::Yaml => Ok(yaml.to_owned()),
+            DraftFormat::Toml => {
+                let mut value: serde_yaml::Value =
+                    serde_yaml::from_str(yaml).map_err(ParseError::Draft)?;
+                remove_nulls(&mut value);
+                toml::to_string_pretty(&value).map_err(|e| Error::TOMLFormat(e.to_string().into()))
+            }
+        }
+    }
+
+    /// Convert a draft body in this format to YAML for `ContentWithDraft::apply_draft`
+    pub fn to_yaml(self, body: &str) -> Result<String> {
+        match self {
+            DraftFormat::Yaml => Ok(body.to_owned()),
+            DraftFormat::Toml => {
+                let value: serde_yaml::Value =
+                    toml::from_str(body).map_err(ParseError::DraftToml)?;
+                serde_yaml::to_string(&value).map_err(|e| Error::YAMLFormat(e.to_string().into()))
+            }
+        }
+    }
+}
+
+impl std::str::FromStr for DraftFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "yaml" => Ok(DraftFormat::Yaml),
+            "toml" => Ok(DraftFormat::Toml),
+            _ => Err(Error::UnknownDraftFormat(s.into())),
+        }
+    }
+}
+
+fn remove_nulls(value: &mut serde_yaml::Value) {
+    match value {
+        serde_yaml::Value::Mapping(map) => {
+            map.retain(|_, v| !v.is_null());
+            map.values_mut().for_each(remove_nulls);
+        }
+        serde_yaml::Value::Sequence(seq) => {
+            seq.retain(|v| !v.is_null());
+            seq.iter_mut().for_each(remove_nulls);
+        }
+        _ => {}
+    }
+}
+
 /// A content with draft support
 ///
 /// Draft is a compact, human
@@ -201,6 +260,23 @@ pub trait ContentWithDraft: Content {
     fn apply_draft(&self, body: &str) -> Result<Self>
     where
         Self: Sized;
+
+    /// `to_draft`, with the body in `format`
+    fn to_draft_in(&self, format: DraftFormat) -> Result<Draft> {
+        let draft = self.to_draft();
+        Ok(Draft {
+            body: format.convert_yaml(&draft.body)?,
+            ..draft
+        })
+    }
+
+    /// `apply_draft` for a body in `format`
+    fn apply_draft_in(&self, body: &str, format: DraftFormat) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        self.apply_draft(&format.to_yaml(body)?)
+    }
 }
 
 pub trait ContentExt: Content {
@@ -0,0 +1,27 @@
+use crate::proof::{
+    self,
+    content::{ValidationError, ValidationResult},
+    CommonOps,
+};
+pub use crev_schema::proof::encrypted::*;
+use std::fmt;
+
+impl proof::CommonOps for Encrypted {
+    fn common(&self) -> &proof::Common {
+        &self.common
+    }
+}
+
+impl proof::Content for Encrypted {
+    fn serialize_to(&self, fmt: &mut dyn std::fmt::Write) -> fmt::Result {
+        write!(fmt, "{self}")
+    }
+
+    fn validate_data(&self) -> ValidationResult<()> {
+        self.ensure_kind_is(Self::KIND)?;
+        if self.recipients.is_empty() {
+            return Err(ValidationError::EncryptedWithoutRecipients);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,37 @@
+use crate::{
+    proof::{self, content::ValidationResult, CommonOps},
+    Digest,
+};
+pub use crev_schema::proof::epoch::*;
+use std::{collections::BTreeSet, fmt};
+
+impl proof::CommonOps for Epoch {
+    fn common(&self) -> &proof::Common {
+        &self.common
+    }
+}
+
+impl ArchivedProof for proof::Proof {
+    fn archive_digest<'a>(proofs: impl IntoIterator<Item = &'a Self>) -> Digest {
+        archive_digest(proofs)
+    }
+}
+
+impl proof::Content for Epoch {
+    fn serialize_to(&self, fmt: &mut dyn std::fmt::Write) -> fmt::Result {
+        write!(fmt, "{self}")
+    }
+
+    fn validate_data(&self) -> ValidationResult<()> {
+        self.ensure_kind_is(Self::KIND)?;
+        Ok(())
+    }
+}
+
+/// Digest of a set of proofs, independent of their order, duplicates
+/// and the files they are stored in
+pub fn archive_digest<'a>(proofs: impl IntoIterator<Item = &'a proof::Proof>) -> Digest {
+    let digests: BTreeSet<&[u8; 32]> = proofs.into_iter().map(proof::Proof::digest).collect();
+    let concatenated: Vec<u8> = digests.into_iter().flatten().copied().collect();
+    Digest::from(crev_common::blake2b256sum(&concatenated))
+}
@@ -0,0 +1,36 @@
+use crate::proof::{
+    self,
+    content::{ValidationError, ValidationResult},
+    CommonOps,
+};
+pub use crev_schema::proof::group::*;
+use std::fmt;
+
+/// Group names are lowercase ASCII letters, digits and `-`, eg. `rust-sec-team`
+#[must_use]
+pub fn is_valid_group_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+impl proof::CommonOps for Group {
+    fn common(&self) -> &proof::Common {
+        &self.common
+    }
+}
+
+impl proof::Content for Group {
+    fn serialize_to(&self, fmt: &mut dyn std::fmt::Write) -> fmt::Result {
+        write!(fmt, "{self}")
+    }
+
+    fn validate_data(&self) -> ValidationResult<()> {
+        self.ensure_kind_is(Self::KIND)?;
+        if !is_valid_group_name(&self.name) {
+            return Err(ValidationError::InvalidGroupName(self.name.as_str().into()));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,35 @@
+use crate::proof::{
+    self,
+    content::{ValidationError, ValidationResult},
+    CommonOps,
+};
+pub use crev_schema::proof::issue_update::*;
+use std::fmt;
+
+impl proof::CommonOps for IssueUpdate {
+    fn common(&self) -> &proof::Common {
+        &self.common
+    }
+}
+
+impl proof::Content for IssueUpdate {
+    fn serialize_to(&self, fmt: &mut dyn std::fmt::Write) -> fmt::Result {
+        write!(fmt, "{self}")
+    }
+
+    fn validate_data(&self) -> ValidationResult<()> {
+        self.ensure_kind_is(Self::KIND)?;
+        if self.issue.is_empty() {
+            return Err(ValidationError::IssuesWithAnEmptyIDFieldAreNotAllowed);
+        }
+        match &self.action {
+            IssueAction::Duplicate(of) if of.is_empty() => {
+                Err(ValidationError::IssuesWithAnEmptyIDFieldAreNotAllowed)
+            }
+            IssueAction::Duplicate(of) if *of == self.issue => {
+                Err(ValidationError::IssueDuplicateOfItself)
+            }
+            _ => Ok(()),
+        }
+    }
+}
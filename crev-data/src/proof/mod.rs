@@ -1,41 +1,46 @@
 //! Some common stuff for both Review and Trust Proofs
 
 pub use crate::proof::content::{
-    Common, CommonOps, Content, ContentDeserialize, ContentExt, ContentWithDraft, Draft, WithReview,
+    Common, CommonOps, Content, ContentDeserialize, ContentExt, ContentWithDraft, Draft,
+    DraftFormat, WithReview,
 };
 use crate::{Error, ParseError, PublicId, Result};
-use chrono::{self, prelude::*};
+pub use crev_schema::proof::{
+    default_digest_type, default_revision_type, package_info, revision, BuildProofError, Date,
+    DateUtc, Digest, OverrideItem,
+};
+pub use encrypted::*;
+pub use epoch::*;
+pub use group::*;
+pub use issue_update::*;
 pub use package_info::*;
 pub use review::{Code as CodeReview, Package as PackageReview, *};
+pub use review_request::*;
 pub use revision::*;
 use serde::{Deserialize, Serialize};
 use std::{
     fmt,
     io::{self, BufRead},
 };
+pub use successor::*;
 pub use trust::*;
+pub use trust_snapshot::*;
+pub use waiver::*;
 
 pub mod content;
-pub mod package_info;
+pub mod encrypted;
+pub mod epoch;
+pub mod group;
+pub mod issue_update;
 pub mod review;
-pub mod revision;
+pub mod review_request;
+pub mod successor;
 pub mod trust;
+pub mod trust_snapshot;
+pub mod waiver;
 
 const MAX_PROOF_BODY_LENGTH: usize = 32_000;
 
-pub type Date = chrono::DateTime<FixedOffset>;
-pub type DateUtc = chrono::DateTime<Utc>;
-
-#[derive(Debug, Clone)]
-pub struct Digest(pub [u8; 32]);
-
-impl Digest {
-    #[must_use]
-    pub fn to_base64(&self) -> String {
-        crev_common::base64_encode(&self.0)
-    }
-}
-
 /// Serialized Proof (crate review or trust of someone)
 ///
 /// A signed proof containing some signed `Content`
@@ -334,40 +339,6 @@ impl Proof {
     }
 }
 
-fn equals_default_digest_type(s: &str) -> bool {
-    s == default_digest_type()
-}
-
-#[must_use]
-pub fn default_digest_type() -> String {
-    "blake2b".into()
-}
-
-fn equals_default_revision_type(s: &str) -> bool {
-    s == default_revision_type()
-}
-
-#[must_use]
-pub fn default_revision_type() -> String {
-    "git".into()
-}
-
-fn equals_default<T: Default + PartialEq>(t: &T) -> bool {
-    *t == Default::default()
-}
-
-/// A particular ID to override judgment of
-///
-/// Used to correct (well, discard really) specific judgments without
-/// loosing the overall work.
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct OverrideItem {
-    #[serde(flatten)]
-    pub id: PublicId,
-    #[serde(skip_serializing_if = "String::is_empty", default = "Default::default")]
-    pub comment: String,
-}
-
 /// Like [`OverrideItem`] but with a different serialization.
 ///
 /// When editing a draft in code editor, we don't want
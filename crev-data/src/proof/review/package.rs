@@ -1,45 +1,17 @@
 use crate::{
     proof::{
         self,
-        content::{OriginalReference, ValidationError, ValidationResult},
-        OverrideItem, OverrideItemDraft,
+        content::{ValidationError, ValidationResult},
+        OverrideItemDraft,
     },
-    serde_content_serialize, serde_draft_serialize, Error, Level, ParseError,
+    serde_draft_serialize, Error, ParseError,
 };
-use crev_common::{self, is_equal_default, is_set_empty, is_vec_empty};
+use crev_common::{is_set_empty, is_vec_empty};
+pub use crev_schema::proof::review::package::*;
 use derive_builder::Builder;
 use proof::{CommonOps, Content};
-use semver::Version;
 use serde::{Deserialize, Serialize};
-use std::{
-    collections::HashSet,
-    default::Default,
-    fmt::{self, Debug},
-    ops,
-};
-use typed_builder::TypedBuilder;
-
-const CURRENT_PACKAGE_REVIEW_PROOF_SERIALIZATION_VERSION: i64 = -1;
-
-fn cur_version() -> i64 {
-    CURRENT_PACKAGE_REVIEW_PROOF_SERIALIZATION_VERSION
-}
-
-/// Possible flags to mark on the package
-#[derive(Clone, Builder, Debug, Serialize, Deserialize, Default, PartialEq, Eq)]
-pub struct Flags {
-    #[serde(default = "Default::default", skip_serializing_if = "is_equal_default")]
-    pub unmaintained: bool,
-}
-
-impl ops::Add<Flags> for Flags {
-    type Output = Self;
-    fn add(self, other: Flags) -> Self {
-        Self {
-            unmaintained: self.unmaintained || other.unmaintained,
-        }
-    }
-}
+use std::{collections::HashSet, default::Default, fmt};
 
 impl From<FlagsDraft> for Flags {
     fn from(flags: FlagsDraft) -> Self {
@@ -63,80 +35,9 @@ impl From<Flags> for FlagsDraft {
     }
 }
 
-/// Body of a Package Review Proof
-#[derive(Clone, Builder, Debug, Serialize, Deserialize)]
-// TODO: https://github.com/colin-kiegel/rust-derive-builder/issues/136
-pub struct Package {
-    #[serde(flatten)]
-    pub common: proof::Common,
-
-    #[serde(rename = "package")]
-    pub package: proof::PackageInfo,
-
-    #[serde(skip_serializing_if = "Option::is_none", default = "Default::default")]
-    #[serde(rename = "package-diff-base")]
-    #[builder(default = "Default::default()")]
-    pub diff_base: Option<proof::PackageInfo>,
-
-    #[builder(default = "Default::default()")]
-    #[serde(default = "Default::default", skip_serializing_if = "is_equal_default")]
-    review: super::Review,
-
-    #[builder(default = "Default::default()")]
-    #[serde(skip_serializing_if = "is_vec_empty", default = "Default::default")]
-    pub issues: Vec<Issue>,
-
-    #[builder(default = "Default::default()")]
-    #[serde(skip_serializing_if = "is_vec_empty", default = "Default::default")]
-    pub advisories: Vec<Advisory>,
-
-    #[serde(default = "Default::default", skip_serializing_if = "is_equal_default")]
-    #[builder(default = "Default::default()")]
-    pub flags: Flags,
-
-    #[builder(default = "Default::default()")]
-    #[serde(skip_serializing_if = "is_set_empty", default = "Default::default")]
-    pub alternatives: HashSet<proof::PackageId>,
-
-    #[serde(skip_serializing_if = "String::is_empty", default = "Default::default")]
-    #[builder(default = "Default::default()")]
-    pub comment: String,
-
-    #[builder(default = "Default::default()")]
-    #[serde(
-        default = "Default::default",
-        skip_serializing_if = "Vec::is_empty",
-        rename = "override"
-    )]
-    pub override_: Vec<OverrideItem>,
-}
-
-impl PackageBuilder {
-    pub fn from<VALUE: Into<crate::PublicId>>(&mut self, value: VALUE) -> &mut Self {
-        if let Some(ref mut common) = self.common {
-            common.from = value.into();
-        } else {
-            self.common = Some(proof::Common {
-                kind: Some(Package::KIND.into()),
-                version: cur_version(),
-                date: crev_common::now(),
-                from: value.into(),
-                original: None,
-            });
-        }
-        self
-    }
-}
-
-impl fmt::Display for Package {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.serialize_to(f).map_err(|_| fmt::Error)
-    }
-}
-
 impl proof::WithReview for Package {
     fn review(&self) -> &super::Review {
-        &self.review
+        self.review_possibly_none()
     }
 }
 
@@ -151,33 +52,19 @@ impl proof::CommonOps for Package {
     }
 }
 
-impl Package {
-    pub fn touch_date(&mut self) {
-        self.common.date = crev_common::now();
-    }
-
-    pub fn change_from(&mut self, id: crate::PublicId) {
-        self.common.from = id;
-    }
-
-    pub fn set_original_reference(&mut self, orig_reference: OriginalReference) {
-        self.common.original = Some(orig_reference);
-    }
-
-    pub fn ensure_kind_is_backfilled(&mut self) {
-        if self.common.kind.is_none() {
-            // backfill "kind" for old reviews. Don't use common().kind() here, it will panic.
-            self.common.kind = Some(self.kind().to_string());
-        }
-    }
-}
-
 /// Like `Package` but serializes for interactive editing
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Draft {
     #[serde(default = "Default::default")]
     review: super::Review,
 
+    #[serde(
+        default = "Default::default",
+        skip_serializing_if = "is_vec_empty",
+        rename = "covered-paths"
+    )]
+    pub covered_paths: Vec<String>,
+
     #[serde(default = "Default::default", skip_serializing_if = "is_vec_empty")]
     pub advisories: Vec<Advisory>,
 
@@ -209,7 +96,8 @@ impl Draft {
 impl From<Package> for Draft {
     fn from(package: Package) -> Self {
         Draft {
-            review: package.review,
+            review: package.review_possibly_none().clone(),
+            covered_paths: package.covered_paths,
             advisories: package.advisories,
             issues: package.issues,
             comment: package.comment,
@@ -249,6 +137,20 @@ impl proof::Content for Package {
             }
         }
 
+        if let Some(subtree) = &self.subtree {
+            let mut paths = std::iter::once(&subtree.path).chain(&subtree.exclude);
+            if let Some(path) = paths.find(|p| !Subtree::is_valid_path(p)) {
+                return Err(ValidationError::InvalidSubtreePath(path.clone().into()));
+            }
+        }
+        if let Some(path) = self
+            .covered_paths
+            .iter()
+            .find(|p| !Subtree::is_valid_path(p))
+        {
+            return Err(ValidationError::InvalidCoveredPath(path.clone().into()));
+        }
+
         for advisory in &self.advisories {
             if advisory.ids.is_empty() {
                 return Err(ValidationError::AdvisoriesWithNoIDSAreNotAllowed);
@@ -264,8 +166,7 @@ impl proof::Content for Package {
     }
 
     fn serialize_to(&self, fmt: &mut dyn std::fmt::Write) -> fmt::Result {
-        serde_content_serialize!(self, fmt);
-        Ok(())
+        write!(fmt, "{self}")
     }
 }
 
@@ -284,8 +185,9 @@ impl proof::ContentWithDraft for Package {
         let draft = Draft::parse(s)?;
 
         let mut package = self.clone();
-        package.review = draft.review;
+        *package.review_possibly_none_mut() = draft.review;
         package.comment = draft.comment;
+        package.covered_paths = draft.covered_paths;
         package.advisories = draft.advisories;
         package.issues = draft.issues;
         package.alternatives = draft
@@ -301,227 +203,9 @@ impl proof::ContentWithDraft for Package {
     }
 }
 
-impl Package {
-    pub const KIND: &'static str = "package review";
-
-    #[must_use]
-    pub fn is_advisory_for(&self, version: &Version) -> bool {
-        for advisory in &self.advisories {
-            if advisory.is_for_version_when_reported_in_version(version, &self.package.id.version) {
-                return true;
-            }
-        }
-        false
-    }
-
-    /// Get the `Review`
-    ///
-    /// This forces the user to handle reviews that are
-    /// empty (everything is set to None) explicitly.
-    #[must_use]
-    pub fn review(&self) -> Option<&super::Review> {
-        if self.review.is_none() {
-            None
-        } else {
-            Some(&self.review)
-        }
-    }
-
-    /// Get the underlying review.
-    ///
-    /// The caller is responsible for handling the case where
-    /// `review.is_none()`.
-    #[must_use]
-    pub fn review_possibly_none(&self) -> &super::Review {
-        &self.review
-    }
-
-    pub fn review_possibly_none_mut(&mut self) -> &mut super::Review {
-        &mut self.review
-    }
-}
-
 impl fmt::Display for Draft {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         serde_draft_serialize!(self, fmt);
         Ok(())
     }
 }
-
-#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Default)]
-#[serde(rename_all = "kebab-case")]
-pub enum VersionRange {
-    Minor,
-    Major,
-    #[default]
-    All,
-}
-
-#[derive(Debug, Clone)]
-pub struct VersionRangeParseError(());
-
-impl fmt::Display for VersionRangeParseError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Could not parse an incorrect advisory range value")
-    }
-}
-
-impl std::str::FromStr for VersionRange {
-    type Err = VersionRangeParseError;
-
-    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-        Ok(match s {
-            "all" => VersionRange::All,
-            "major" => VersionRange::Major,
-            "minor" => VersionRange::Minor,
-            _ => return Err(VersionRangeParseError(())),
-        })
-    }
-}
-
-impl VersionRange {
-    fn all() -> Self {
-        VersionRange::All
-    }
-
-    #[allow(clippy::trivially_copy_pass_by_ref)]
-    fn is_all_ref(&self) -> bool {
-        VersionRange::All == *self
-    }
-}
-
-/// Advisory to upgrade to the package version
-///
-/// Advisory means a general important fix was included in this
-/// release, and all previous releases were potentially affected.
-/// We don't play with exact ranges.
-#[derive(Clone, TypedBuilder, Debug, Serialize, Deserialize)]
-#[serde(rename_all = "kebab-case")]
-#[derive(Default)]
-pub struct Advisory {
-    pub ids: Vec<String>,
-
-    #[builder(default)]
-    pub severity: Level,
-
-    #[builder(default)]
-    #[serde(
-        default = "VersionRange::all",
-        skip_serializing_if = "VersionRange::is_all_ref"
-    )]
-    pub range: VersionRange,
-
-    #[builder(default)]
-    #[serde(default = "Default::default")]
-    pub comment: String,
-}
-
-impl From<VersionRange> for Advisory {
-    fn from(r: VersionRange) -> Self {
-        Advisory {
-            range: r,
-            ..Default::default()
-        }
-    }
-}
-
-impl Advisory {
-    #[must_use]
-    pub fn is_for_version_when_reported_in_version(
-        &self,
-        for_version: &Version,
-        in_pkg_version: &Version,
-    ) -> bool {
-        if for_version < in_pkg_version {
-            match self.range {
-                VersionRange::All => return true,
-                VersionRange::Major => {
-                    if in_pkg_version.major == for_version.major {
-                        return true;
-                    }
-                }
-                VersionRange::Minor => {
-                    if in_pkg_version.major == for_version.major
-                        && in_pkg_version.minor == for_version.minor
-                    {
-                        return true;
-                    }
-                }
-            }
-        }
-        false
-    }
-}
-
-/// Issue with a package version
-///
-/// `Issue` is a kind of opposite of [`Advisory`]. It reports
-/// a problem with package in a given version. It leaves the
-/// question open if any previous and following versions might
-/// also be affected, but will be considered open and affecting
-/// all following versions within the `range` until an advisory
-/// is found for it, matching the id.
-#[derive(Clone, TypedBuilder, Debug, Serialize, Deserialize)]
-#[serde(rename_all = "kebab-case")]
-pub struct Issue {
-    pub id: String,
-    #[builder(default)]
-    pub severity: Level,
-
-    #[builder(default)]
-    #[serde(
-        default = "VersionRange::all",
-        skip_serializing_if = "VersionRange::is_all_ref"
-    )]
-    pub range: VersionRange,
-
-    #[builder(default)]
-    #[serde(default = "Default::default")]
-    pub comment: String,
-}
-
-impl Issue {
-    #[must_use]
-    pub fn new(id: String) -> Self {
-        Self {
-            id,
-            range: Default::default(),
-            severity: Default::default(),
-            comment: Default::default(),
-        }
-    }
-    #[must_use]
-    pub fn new_with_severity(id: String, severity: Level) -> Self {
-        Self {
-            id,
-            range: Default::default(),
-            severity,
-            comment: Default::default(),
-        }
-    }
-    #[must_use]
-    pub fn is_for_version_when_reported_in_version(
-        &self,
-        for_version: &Version,
-        in_pkg_version: &Version,
-    ) -> bool {
-        if for_version >= in_pkg_version {
-            match self.range {
-                VersionRange::All => return true,
-                VersionRange::Major => {
-                    if in_pkg_version.major == for_version.major {
-                        return true;
-                    }
-                }
-                VersionRange::Minor => {
-                    if in_pkg_version.major == for_version.major
-                        && in_pkg_version.minor == for_version.minor
-                    {
-                        return true;
-                    }
-                }
-            }
-        }
-        false
-    }
-}
@@ -0,0 +1,20 @@
+use crate::proof::{self, content::ValidationResult, CommonOps};
+pub use crev_schema::proof::review_request::*;
+use std::fmt;
+
+impl proof::CommonOps for ReviewRequest {
+    fn common(&self) -> &proof::Common {
+        &self.common
+    }
+}
+
+impl proof::Content for ReviewRequest {
+    fn serialize_to(&self, fmt: &mut dyn std::fmt::Write) -> fmt::Result {
+        write!(fmt, "{self}")
+    }
+
+    fn validate_data(&self) -> ValidationResult<()> {
+        self.ensure_kind_is(Self::KIND)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,30 @@
+use crate::proof::{
+    self,
+    content::{ValidationError, ValidationResult},
+    CommonOps,
+};
+pub use crev_schema::proof::successor::*;
+use std::fmt;
+
+impl proof::CommonOps for Successor {
+    fn common(&self) -> &proof::Common {
+        &self.common
+    }
+}
+
+impl proof::Content for Successor {
+    fn serialize_to(&self, fmt: &mut dyn std::fmt::Write) -> fmt::Result {
+        write!(fmt, "{self}")
+    }
+
+    fn validate_data(&self) -> ValidationResult<()> {
+        self.ensure_kind_is(Self::KIND)?;
+        if self.predecessor.id == self.successor.id {
+            return Err(ValidationError::SuccessorOfItself);
+        }
+        if self.from().id != self.predecessor.id && self.from().id != self.successor.id {
+            return Err(ValidationError::SuccessorSignedByOtherId);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,27 @@
+use crate::proof::{
+    self,
+    content::{ValidationError, ValidationResult},
+    CommonOps,
+};
+pub use crev_schema::proof::waiver::*;
+use std::fmt;
+
+impl proof::CommonOps for Waiver {
+    fn common(&self) -> &proof::Common {
+        &self.common
+    }
+}
+
+impl proof::Content for Waiver {
+    fn serialize_to(&self, fmt: &mut dyn std::fmt::Write) -> fmt::Result {
+        write!(fmt, "{self}")
+    }
+
+    fn validate_data(&self) -> ValidationResult<()> {
+        self.ensure_kind_is(Self::KIND)?;
+        if self.reason.trim().is_empty() {
+            return Err(ValidationError::WaiverWithoutReason);
+        }
+        Ok(())
+    }
+}
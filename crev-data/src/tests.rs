@@ -1,7 +1,7 @@
 use crate::{
     id::UnlockedId,
-    proof::{self, Content, ContentExt, ContentWithDraft, Proof},
-    Error, Result, Url,
+    proof::{self, CommonOps, Content, ContentExt, ContentWithDraft, Proof},
+    DigestType, DigestTypeExt, Error, Result, Url,
 };
 use semver::Version;
 use std::{default::Default, path::PathBuf};
@@ -170,6 +170,7 @@ pub fn generate_id_and_proof() -> Result<(UnlockedId, Proof)> {
         digest_type: proof::default_digest_type(),
         revision: String::new(),
         revision_type: proof::default_revision_type(),
+        digest_ignore: None,
     };
     let review = proof::review::CodeBuilder::default()
         .from(id.id.clone())
@@ -230,6 +231,7 @@ pub fn ensure_serializes_to_valid_proof_works() -> Result<()> {
         digest_type: proof::default_digest_type(),
         revision: String::new(),
         revision_type: proof::default_revision_type(),
+        digest_ignore: None,
     };
 
     let mut package = a.as_public_id().create_package_review_proof(
@@ -287,3 +289,369 @@ override:
 
     Ok(())
 }
+
+#[test]
+pub fn epoch_proof_roundtrip() -> Result<()> {
+    let (id, review_proof) = generate_id_and_proof()?;
+    let (_other_id, other_proof) = generate_id_and_proof()?;
+
+    let digest = proof::archive_digest([&review_proof, &other_proof]);
+    // order and duplicates don't matter
+    assert_eq!(
+        digest,
+        proof::archive_digest([&other_proof, &review_proof, &other_proof])
+    );
+    assert_ne!(digest, proof::archive_digest([&review_proof]));
+
+    let epoch = id.as_public_id().create_epoch_proof(
+        Url::new_git("https://mypage.com/trust-archive.git"),
+        &digest,
+    )?;
+    let proof = epoch.sign_by(&id)?;
+    proof.verify()?;
+
+    let parsed = Proof::parse_from(proof.to_string().as_bytes())?;
+    let parsed: proof::Epoch = parsed[0].parse_content()?;
+    assert_eq!(parsed.archive.url, "https://mypage.com/trust-archive.git");
+    assert!(parsed.archive_matches([&other_proof, &review_proof]));
+    assert!(!parsed.archive_matches([&review_proof]));
+
+    Ok(())
+}
+
+#[test]
+pub fn review_request_proof_roundtrip() -> Result<()> {
+    let id = UnlockedId::generate(Some(Url::new_git("https://mypage.com/trust.git")));
+    let package = proof::PackageVersionId::new(
+        "SOURCE_ID".to_owned(),
+        "name".into(),
+        Version::parse("1.0.0").unwrap(),
+    );
+
+    let request = id
+        .as_public_id()
+        .create_review_request_proof(package.clone(), "new unsafe code in src/raw.rs".into())?;
+    let proof = request.sign_by(&id)?;
+    proof.verify()?;
+
+    let parsed = Proof::parse_from(proof.to_string().as_bytes())?;
+    assert_eq!(parsed[0].kind(), proof::ReviewRequest::KIND);
+    let parsed: proof::ReviewRequest = parsed[0].parse_content()?;
+    assert_eq!(parsed.package, package);
+    assert_eq!(parsed.comment, "new unsafe code in src/raw.rs");
+
+    Ok(())
+}
+
+#[test]
+pub fn issue_update_proof_roundtrip() -> Result<()> {
+    let id = UnlockedId::generate(Some(Url::new_git("https://mypage.com/trust.git")));
+    let package = proof::PackageId {
+        source: "SOURCE_ID".to_owned(),
+        name: "name".into(),
+    };
+    let reported_in = proof::Digest([7; 32]);
+
+    for action in [
+        proof::IssueAction::Close,
+        proof::IssueAction::Duplicate("RUSTSEC-2019-0001".into()),
+        proof::IssueAction::Severity(crate::Level::High),
+    ] {
+        let update = id.as_public_id().create_issue_update_proof(
+            package.clone(),
+            "use-after-free".into(),
+            &reported_in,
+            action.clone(),
+            String::new(),
+        )?;
+        let proof = update.sign_by(&id)?;
+        proof.verify()?;
+
+        let parsed = Proof::parse_from(proof.to_string().as_bytes())?;
+        assert_eq!(parsed[0].kind(), proof::IssueUpdate::KIND);
+        let parsed: proof::IssueUpdate = parsed[0].parse_content()?;
+        assert_eq!(parsed.package, package);
+        assert_eq!(parsed.issue, "use-after-free");
+        assert_eq!(parsed.reported_in, reported_in.0);
+        assert_eq!(parsed.action, action);
+    }
+
+    let update = id.as_public_id().create_issue_update_proof(
+        package,
+        "use-after-free".into(),
+        &reported_in,
+        proof::IssueAction::Duplicate("use-after-free".into()),
+        String::new(),
+    )?;
+    assert!(update.validate_data().is_err());
+
+    Ok(())
+}
+
+#[test]
+pub fn group_proof_roundtrip() -> Result<()> {
+    let owner = UnlockedId::generate(Some(Url::new_git("https://mypage.com/trust.git")));
+    let member = UnlockedId::generate(Some(Url::new_git("https://other.com/trust.git")));
+
+    let group = owner.as_public_id().create_group_proof(
+        "rust-sec-team".into(),
+        vec![member.as_public_id().clone()],
+        String::new(),
+    )?;
+    let proof = group.sign_by(&owner)?;
+    proof.verify()?;
+    let parsed = Proof::parse_from(proof.to_string().as_bytes())?;
+    assert_eq!(parsed[0].kind(), proof::Group::KIND);
+    let parsed: proof::Group = parsed[0].parse_content()?;
+    assert_eq!(parsed.name, "rust-sec-team");
+    assert_eq!(parsed.ids, vec![member.as_public_id().clone()]);
+
+    let mut trust =
+        member
+            .as_public_id()
+            .create_trust_proof(vec![], proof::TrustLevel::Low, vec![])?;
+    trust.groups.push(proof::GroupRef {
+        from: owner.as_public_id().clone(),
+        name: "rust-sec-team".into(),
+    });
+    let parsed: proof::Trust =
+        Proof::parse_from(trust.sign_by(&member)?.to_string().as_bytes())?[0].parse_content()?;
+    assert_eq!(parsed.groups, trust.groups);
+
+    let group =
+        owner
+            .as_public_id()
+            .create_group_proof("Rust Sec Team".into(), vec![], String::new())?;
+    assert!(group.validate_data().is_err());
+
+    Ok(())
+}
+
+#[test]
+pub fn parse_package_digest_type() -> Result<()> {
+    let s = r#"
+version: -1
+date: "2018-12-18T23:10:21.111854021-08:00"
+from:
+  id-type: crev
+  id: FYlr8YoYGVvDwHQxqEIs89reKKDy-oWisoO0qXXEfHE
+  url: "https://github.com/dpc/crev-proofs"
+package:
+  source: "https://crates.io"
+  name: log
+  version: 0.4.6
+  digest: BhDmOOjfESqs8i3z9qsQANH8A39eKklgQKuVtrwN-Tw
+  digest-type: sha256
+review:
+  thoroughness: low
+  understanding: medium
+  rating: positive
+"#;
+
+    let proof: proof::package::Package = serde_yaml::from_str(s).expect("deserialization failed");
+    proof.validate_data()?;
+    assert_eq!(proof.package.known_digest_type(), Some(DigestType::Sha256));
+
+    let mut unknown = proof.package.clone();
+    unknown.digest_type = "md5".into();
+    assert_eq!(unknown.known_digest_type(), None);
+
+    for digest_type in DigestType::ALL {
+        assert_eq!(
+            digest_type.to_string().parse::<DigestType>().ok(),
+            Some(digest_type)
+        );
+        assert_eq!(
+            digest_type.digest(b"crev").as_slice().len(),
+            digest_type.output_len()
+        );
+    }
+    assert_ne!(
+        DigestType::Blake2b.digest(b"crev"),
+        DigestType::Sha256.digest(b"crev")
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn package_review_scope() -> Result<()> {
+    let s = r#"
+version: -1
+date: "2018-12-18T23:10:21.111854021-08:00"
+from:
+  id-type: crev
+  id: FYlr8YoYGVvDwHQxqEIs89reKKDy-oWisoO0qXXEfHE
+  url: "https://github.com/dpc/crev-proofs"
+package:
+  source: "https://crates.io"
+  name: log
+  version: 0.4.6
+  revision: 9c2b3d8
+  digest: BhDmOOjfESqs8i3z9qsQANH8A39eKklgQKuVtrwN-Tw
+package-diff-base:
+  source: "https://crates.io"
+  name: log
+  version: 0.4.5
+  digest: BhDmOOjfESqs8i3z9qsQANH8A39eKklgQKuVtrwN-Tw
+review:
+  thoroughness: low
+  understanding: medium
+  rating: positive
+"#;
+
+    let mut proof: proof::package::Package =
+        serde_yaml::from_str(s).expect("deserialization failed");
+    let scope = proof.scope();
+    assert_eq!(scope.coverage, proof::package::ReviewCoverage::Diff);
+    assert_eq!(scope.diff_base, Some(Version::new(0, 4, 5)));
+    assert_eq!(
+        scope.to_string(),
+        "diff review from 0.4.5, blake2b digest, git revision 9c2b3d8"
+    );
+
+    proof.diff_base = None;
+    assert_eq!(proof.scope().coverage, proof::package::ReviewCoverage::Full);
+    Ok(())
+}
+
+#[test]
+pub fn parse_package_subtree() -> Result<()> {
+    let s = r#"
+version: -1
+date: "2018-12-18T23:10:21.111854021-08:00"
+from:
+  id-type: crev
+  id: FYlr8YoYGVvDwHQxqEIs89reKKDy-oWisoO0qXXEfHE
+  url: "https://github.com/dpc/crev-proofs"
+package:
+  source: "https://crates.io"
+  name: log
+  version: 0.4.6
+  digest: BhDmOOjfESqs8i3z9qsQANH8A39eKklgQKuVtrwN-Tw
+subtree:
+  path: src
+  exclude:
+    - tests/fixtures
+  digest: BhDmOOjfESqs8i3z9qsQANH8A39eKklgQKuVtrwN-Tw
+review:
+  thoroughness: low
+  understanding: medium
+  rating: positive
+"#;
+
+    let mut proof: proof::package::Package =
+        serde_yaml::from_str(s).expect("deserialization failed");
+    proof.validate_data()?;
+
+    let subtree = proof.subtree.clone().expect("has a subtree");
+    assert_eq!(subtree.path, "src");
+    assert_eq!(subtree.exclude, ["tests/fixtures"]);
+    // kept through editing the draft
+    let draft = proof.to_draft();
+    assert_eq!(proof.apply_draft(&draft.body)?.subtree, Some(subtree));
+
+    for path in ["", "/src", "src/../..", "src//x", "./src"] {
+        proof.subtree.as_mut().unwrap().path = path.into();
+        assert!(proof.validate_data().is_err(), "{path}");
+    }
+
+    Ok(())
+}
+
+#[test]
+pub fn package_review_covered_paths() -> Result<()> {
+    let s = r#"
+version: -1
+date: "2018-12-18T23:10:21.111854021-08:00"
+from:
+  id-type: crev
+  id: FYlr8YoYGVvDwHQxqEIs89reKKDy-oWisoO0qXXEfHE
+  url: "https://github.com/dpc/crev-proofs"
+package:
+  source: "https://crates.io"
+  name: log
+  version: 0.4.6
+  digest: BhDmOOjfESqs8i3z9qsQANH8A39eKklgQKuVtrwN-Tw
+review:
+  thoroughness: low
+  understanding: medium
+  rating: positive
+"#;
+
+    let proof: proof::package::Package =
+        serde_yaml::from_str(s).expect("deserialization failed");
+    // a full review covers everything
+    assert!(proof.covers_path("src/ffi"));
+
+    let draft = proof.to_draft();
+    let draft = format!("{}\ncovered-paths:\n  - src/parser\n", draft.body);
+    let mut proof = proof.apply_draft(&draft)?;
+    assert_eq!(proof.covered_paths, ["src/parser"]);
+    assert_eq!(proof.scope().covered_paths, ["src/parser"]);
+    assert!(proof.covers_path("src/parser"));
+    assert!(proof.covers_path("src/parser/lexer.rs"));
+    assert!(!proof.covers_path("src/parser.rs"));
+    assert!(!proof.covers_path("src/ffi"));
+
+    proof.covered_paths.clear();
+    proof.subtree = Some(proof::package::Subtree {
+        path: "src".into(),
+        exclude: vec!["ffi".into()],
+        digest: vec![],
+    });
+    assert!(proof.covers_path("src/parser"));
+    assert!(!proof.covers_path("src/ffi"));
+    assert!(!proof.covers_path("tests"));
+
+    proof.covered_paths = vec!["../src".into()];
+    assert!(proof.validate_data().is_err());
+    Ok(())
+}
+
+// editing a draft in TOML must not change the signed proof body
+#[test]
+pub fn toml_draft_round_trip() -> Result<()> {
+    let s = r#"
+version: -1
+date: "2018-12-18T23:10:21.111854021-08:00"
+from:
+  id-type: crev
+  id: FYlr8YoYGVvDwHQxqEIs89reKKDy-oWisoO0qXXEfHE
+  url: "https://github.com/dpc/crev-proofs"
+package:
+  source: "https://crates.io"
+  name: log
+  version: 0.4.6
+  digest: BhDmOOjfESqs8i3z9qsQANH8A39eKklgQKuVtrwN-Tw
+review:
+  thoroughness: low
+  understanding: medium
+  rating: positive
+issues:
+  - id: RUSTSEC-2018-0001
+    severity: high
+    comment: "Unsound `Send` impl"
+override:
+  - id-type: crev
+    id: "-sApEowWcAS9J0R7aO18cghvhLBpuMhyeUuWQq_fits"
+    url: "https://github.com/foo/bar"
+    comment: TEST
+comment: |-
+  First line
+  "Quoted" second line
+"#;
+
+    let proof: proof::package::Package = serde_yaml::from_str(s).expect("deserialization failed");
+    proof.validate_data()?;
+
+    let draft = proof.to_draft_in(proof::DraftFormat::Toml)?;
+    assert!(draft.body().contains("[review]"));
+    let new_proof = proof.apply_draft_in(draft.body(), proof::DraftFormat::Toml)?;
+    assert_eq!(proof.serialize()?, new_proof.serialize()?);
+
+    assert!(proof
+        .apply_draft_in("review = ", proof::DraftFormat::Toml)
+        .is_err());
+    Ok(())
+}
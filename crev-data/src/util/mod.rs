@@ -1,3 +1,4 @@
+pub use crev_schema::util::write_comment_proof;
 use rand::{self, Rng};
 use std::fmt;
 
@@ -8,17 +9,6 @@ pub fn random_id_str() -> String {
     crev_common::base64_encode(&out)
 }
 
-pub fn write_comment_proof(comment: &str, f: &mut dyn fmt::Write) -> fmt::Result {
-    if comment.is_empty() {
-        return Ok(());
-    }
-    writeln!(f, "comment: |-")?;
-    for line in comment.lines() {
-        writeln!(f, "  {line}")?;
-    }
-    Ok(())
-}
-
 pub fn write_comment_draft(comment: &str, f: &mut dyn fmt::Write) -> fmt::Result {
     writeln!(f, "comment: |-")?;
     for line in comment.lines() {
@@ -8,8 +8,9 @@ use crev_common::{
     self,
     serde::{as_rfc3339_fixed, from_rfc3339_fixed},
 };
-use crev_data::Version;
+use crev_data::{Level, Version};
 use serde::{Deserialize, Serialize};
+use std::{collections::BTreeSet, path::PathBuf};
 
 pub type Date = chrono::DateTime<chrono::FixedOffset>;
 
@@ -58,6 +59,9 @@ pub struct ReviewActivity {
     )]
     pub timestamp: Date,
     pub diff_base: Option<Version>,
+    /// Set if the code was reviewed in a time-boxed session
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session: Option<ReviewSession>,
 }
 
 impl ReviewActivity {
@@ -66,6 +70,7 @@ impl ReviewActivity {
         Self {
             timestamp: crev_common::now(),
             diff_base,
+            session: None,
         }
     }
 
@@ -78,3 +83,58 @@ impl ReviewActivity {
         }
     }
 }
+
+/// Journal of a time-boxed review session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewSession {
+    #[serde(
+        serialize_with = "as_rfc3339_fixed",
+        deserialize_with = "from_rfc3339_fixed"
+    )]
+    pub started: Date,
+    /// Planned length, in minutes
+    pub planned_minutes: u64,
+    /// Actual length, in minutes; not set while the session is in progress
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minutes: Option<u64>,
+    /// Number of source files in the crate
+    pub files_total: usize,
+    /// Source files opened during the session, relative to the crate root
+    #[serde(default)]
+    pub visited: BTreeSet<PathBuf>,
+}
+
+impl ReviewSession {
+    #[must_use]
+    pub fn new(planned_minutes: u64, files_total: usize) -> Self {
+        Self {
+            started: crev_common::now(),
+            planned_minutes,
+            minutes: None,
+            files_total,
+            visited: BTreeSet::new(),
+        }
+    }
+
+    /// Fraction of the source files visited, `0.0..=1.0`
+    #[must_use]
+    pub fn coverage(&self) -> f64 {
+        if self.files_total == 0 {
+            return 1.0;
+        }
+        (self.visited.len() as f64 / self.files_total as f64).min(1.0)
+    }
+
+    /// Review thoroughness that the coverage supports
+    ///
+    /// This is only a starting point for the reviewer, who knows better.
+    #[must_use]
+    pub fn suggested_thoroughness(&self) -> Level {
+        match self.coverage() {
+            c if c < 0.1 => Level::None,
+            c if c < 0.5 => Level::Low,
+            c if c < 0.9 => Level::Medium,
+            _ => Level::High,
+        }
+    }
+}
@@ -0,0 +1,149 @@
+//! Package reviews as [in-toto](https://in-toto.io) attestations
+//!
+//! A review is exported as an in-toto `Statement` whose subject is the
+//! reviewed crate (identified by its crev digest) and whose predicate is the
+//! review itself. The statement is wrapped in a [DSSE](https://github.com/secure-systems-lab/dsse)
+//! envelope signed by the reviewer's Id, which is what SLSA tooling consumes.
+use crate::{Error, Result};
+use crev_data::{id::UnlockedId, proof, DigestType, Id};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+pub const STATEMENT_TYPE: &str = "https://in-toto.io/Statement/v1";
+pub const PREDICATE_TYPE: &str = "https://crev.dev/attestation/package-review/v1";
+pub const PAYLOAD_TYPE: &str = "application/vnd.in-toto+json";
+
+/// Name of a crev digest of `digest_type` in in-toto's `DigestSet`
+///
+/// The value is lowercase hex, as in-toto requires, not the base64 used by crev.
+#[must_use]
+pub fn digest_algorithm(digest_type: DigestType) -> &'static str {
+    match digest_type {
+        DigestType::Blake2b => "blake2b256",
+        DigestType::Sha256 => "sha256",
+        DigestType::Sha512 => "sha512",
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Subject {
+    /// Package URL, eg. `pkg:cargo/serde@1.0.0`
+    pub name: String,
+    pub digest: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Statement {
+    #[serde(rename = "_type")]
+    pub type_: String,
+    pub subject: Vec<Subject>,
+    #[serde(rename = "predicateType")]
+    pub predicate_type: String,
+    /// The package review, as JSON
+    pub predicate: serde_json::Value,
+}
+
+impl Statement {
+    pub fn for_package_review(review: &proof::review::Package) -> Result<Self> {
+        let package = &review.package;
+        let digest_type = package
+            .known_digest_type()
+            .ok_or_else(|| Error::UnknownDigestType(package.digest_type.clone()))?;
+        let digest = package
+            .digest
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+
+        Ok(Self {
+            type_: STATEMENT_TYPE.into(),
+            subject: vec![Subject {
+                name: package_url(package),
+                digest: BTreeMap::from([(digest_algorithm(digest_type).to_owned(), digest)]),
+            }],
+            predicate_type: PREDICATE_TYPE.into(),
+            predicate: serde_json::to_value(review)?,
+        })
+    }
+}
+
+/// [Package URL](https://github.com/package-url/purl-spec) of a reviewed package
+fn package_url(package: &proof::PackageInfo) -> String {
+    let id = &package.id;
+    if id.id.source == crev_data::SOURCE_CRATES_IO {
+        format!("pkg:cargo/{}@{}", id.id.name, id.version)
+    } else {
+        format!(
+            "pkg:generic/{}@{}?repository_url={}",
+            id.id.name, id.version, id.id.source
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Signature {
+    /// crev Id of the signer
+    pub keyid: String,
+    pub sig: String,
+}
+
+/// DSSE envelope of a signed `Statement`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Envelope {
+    #[serde(rename = "payloadType")]
+    pub payload_type: String,
+    /// base64 of the statement JSON
+    pub payload: String,
+    pub signatures: Vec<Signature>,
+}
+
+/// DSSE "pre-authentication encoding": the bytes that actually get signed
+fn pae(payload_type: &str, payload: &[u8]) -> Vec<u8> {
+    let mut buf = format!(
+        "DSSEv1 {} {} {} ",
+        payload_type.len(),
+        payload_type,
+        payload.len()
+    )
+    .into_bytes();
+    buf.extend_from_slice(payload);
+    buf
+}
+
+impl Envelope {
+    pub fn sign(id: &UnlockedId, statement: &Statement) -> Result<Self> {
+        let payload = serde_json::to_vec(statement)?;
+        let sig = id.sign(&pae(PAYLOAD_TYPE, &payload));
+        Ok(Self {
+            payload_type: PAYLOAD_TYPE.into(),
+            payload: crev_common::base64_encode(&payload),
+            signatures: vec![Signature {
+                keyid: id.id.id.to_string(),
+                sig: crev_common::base64_encode(&sig),
+            }],
+        })
+    }
+
+    /// Check all signatures, and return the signed statement
+    pub fn verify(&self) -> Result<Statement> {
+        if self.payload_type != PAYLOAD_TYPE {
+            return Err(Error::UnsupportedAttestationPayloadType(
+                self.payload_type.clone(),
+            ));
+        }
+        let payload = crev_common::base64_decode(&self.payload)
+            .map_err(|e| Error::InvalidAttestationPayload(e.to_string()))?;
+        if self.signatures.is_empty() {
+            return Err(Error::InvalidAttestationPayload("not signed".to_string()));
+        }
+        for signature in &self.signatures {
+            Id::crevid_from_str(&signature.keyid)?
+                .verify_signature(&pae(&self.payload_type, &payload), &signature.sig)?;
+        }
+        Ok(serde_json::from_slice(&payload)?)
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
@@ -0,0 +1,190 @@
+//! Rules for files left out of package digests
+//!
+//! A review only applies to the code it was made for if the digest computed
+//! when verifying uses the same rules as the one computed by the reviewer.
+//! Both go through a [`DigestIgnorePolicy`], and package reviews record the
+//! [`DigestIgnorePolicy::id`] they were made with (`digest-ignore`).
+use crate::{util, Error, Result};
+use crev_data::{proof, Digest, DigestType};
+use std::path::{Path, PathBuf};
+
+/// Policy of reviews that don't record one: only cargo's `.cargo-ok` marker is left out
+pub const CARGO_CRATE_V1: &str = "cargo-crate-v1";
+
+/// Policy of digests that are the same for a crate from a registry and its source in git
+///
+/// See [`DigestIgnorePolicy::registry_normalized`].
+pub const REGISTRY_NORMALIZED_V1: &str = "registry-normalized-v1";
+
+/// Files `cargo package` adds to the crates it publishes
+const REGISTRY_ADDED_FILES: &[&str] = &[".cargo_vcs_info.json", "Cargo.lock"];
+
+/// Where `cargo package` keeps the `Cargo.toml` it replaced with a normalized one
+const ORIGINAL_MANIFEST: &str = "Cargo.toml.orig";
+const MANIFEST: &str = "Cargo.toml";
+
+/// A named set of paths left out of package digests
+///
+/// See `util::is_ignored_path` for the syntax of entries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DigestIgnorePolicy {
+    id: String,
+    ignore_list: fnv::FnvHashSet<PathBuf>,
+    /// Digest `Cargo.toml.orig` as `Cargo.toml`, if there is one
+    original_manifest: bool,
+}
+
+impl DigestIgnorePolicy {
+    /// A custom policy, eg. for other package managers
+    ///
+    /// Ids have to be unique: digests of reviews recording the `id` are
+    /// recomputed with `ignore_list`.
+    pub fn new(id: impl Into<String>, ignore_list: impl IntoIterator<Item = PathBuf>) -> Self {
+        Self {
+            id: id.into(),
+            ignore_list: ignore_list.into_iter().collect(),
+            original_manifest: false,
+        }
+    }
+
+    /// The policy new reviews of crates are made with
+    #[must_use]
+    pub fn cargo_crate() -> Self {
+        Self::new(CARGO_CRATE_V1, [PathBuf::from(".cargo-ok")])
+    }
+
+    /// Digests of crates from a registry that match the digests of their source in git
+    ///
+    /// The files `cargo package` adds are left out, and the original `Cargo.toml`
+    /// (`Cargo.toml.orig` in the registry) is digested instead of the normalized one.
+    /// Files left out of the package with `exclude` still make a difference.
+    #[must_use]
+    pub fn registry_normalized() -> Self {
+        Self {
+            original_manifest: true,
+            ..Self::new(
+                REGISTRY_NORMALIZED_V1,
+                std::iter::once(".cargo-ok")
+                    .chain(REGISTRY_ADDED_FILES.iter().copied())
+                    .map(PathBuf::from),
+            )
+        }
+    }
+
+    /// Built-in policy with this `id`
+    pub fn by_id(id: &str) -> Result<Self> {
+        match id {
+            CARGO_CRATE_V1 => Ok(Self::cargo_crate()),
+            REGISTRY_NORMALIZED_V1 => Ok(Self::registry_normalized()),
+            _ => Err(Error::UnknownDigestIgnorePolicy(id.into())),
+        }
+    }
+
+    /// The policy the digest of `package` was computed with
+    pub fn of_package(package: &proof::PackageInfo) -> Result<Self> {
+        Self::by_id(package.digest_ignore.as_deref().unwrap_or(CARGO_CRATE_V1))
+    }
+
+    #[must_use]
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    #[must_use]
+    pub fn ignore_list(&self) -> &fnv::FnvHashSet<PathBuf> {
+        &self.ignore_list
+    }
+
+    /// This policy, leaving out `paths` too
+    ///
+    /// For local copies of a package, which can have files the package doesn't.
+    #[must_use]
+    pub fn ignoring(&self, paths: impl IntoIterator<Item = PathBuf>) -> Self {
+        let mut policy = self.clone();
+        policy.ignore_list.extend(paths);
+        policy
+    }
+
+    /// Digest of the package at `path`
+    pub fn digest(&self, path: &Path) -> Result<Digest> {
+        self.digest_with(path, DigestType::default())
+    }
+
+    /// Digest of the package at `path`, with the hash function of `digest_type`
+    pub fn digest_with(&self, path: &Path, digest_type: DigestType) -> Result<Digest> {
+        if self.original_manifest && path.join(ORIGINAL_MANIFEST).is_file() {
+            return self.original_manifest_digest(path, digest_type);
+        }
+        crate::get_dir_digest_with(path, &self.ignore_list, digest_type)
+    }
+
+    /// Digest of a copy of the package, with `Cargo.toml.orig` as `Cargo.toml`
+    fn original_manifest_digest(&self, path: &Path, digest_type: DigestType) -> Result<Digest> {
+        let copy = std::env::temp_dir().join(format!(
+            "crev-digest-{}",
+            crev_common::base64_encode(&crev_common::rand::random_vec(12))
+        ));
+        let digest = self
+            .copy_with_original_manifest(path, &copy)
+            .and_then(|()| crate::get_dir_digest_with(&copy, &self.ignore_list, digest_type));
+        let _ = std::fs::remove_dir_all(&copy);
+        digest
+    }
+
+    fn copy_with_original_manifest(&self, src: &Path, dst: &Path) -> Result<()> {
+        let rel_path = |entry: &walkdir::DirEntry| {
+            entry
+                .path()
+                .strip_prefix(src)
+                .expect("must be prefix")
+                .to_owned()
+        };
+        for entry in walkdir::WalkDir::new(src)
+            .into_iter()
+            .filter_entry(|entry| !util::is_ignored_path(&rel_path(entry), &self.ignore_list))
+        {
+            let entry = entry.map_err(std::io::Error::from)?;
+            let mut rel_path = rel_path(&entry);
+            if rel_path == Path::new(MANIFEST) {
+                continue;
+            }
+            if rel_path == Path::new(ORIGINAL_MANIFEST) {
+                rel_path = PathBuf::from(MANIFEST);
+            }
+            if entry.file_type().is_dir() {
+                std::fs::create_dir_all(dst.join(rel_path))?;
+            } else {
+                std::fs::copy(entry.path(), dst.join(rel_path))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Does a review of `package` apply to the code with `digest` (computed with this policy)
+    ///
+    /// If the review was made with another policy or digest type, the digest
+    /// of the code at `path` is computed again with those. Reviews made with
+    /// policies or digest types that aren't known are assumed to match, as
+    /// there is no way to tell.
+    pub fn matches_package(
+        &self,
+        package: &proof::PackageInfo,
+        digest: &Digest,
+        path: &Path,
+    ) -> bool {
+        let Some(digest_type) = package.known_digest_type() else {
+            return true;
+        };
+        if digest_type == DigestType::default()
+            && package.digest_ignore.as_deref().unwrap_or(CARGO_CRATE_V1) == self.id
+        {
+            return package.digest == digest.as_slice();
+        }
+        match Self::of_package(package) {
+            Ok(policy) => policy
+                .digest_with(path, digest_type)
+                .is_ok_and(|digest| package.digest == digest.as_slice()),
+            Err(_) => true,
+        }
+    }
+}
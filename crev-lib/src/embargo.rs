@@ -0,0 +1,128 @@
+//! Proofs kept back until a date, eg. advisories of embargoed vulnerabilities
+//!
+//! Embargoed proofs are signed, but kept in the data directory instead of the
+//! proof repository. `Local::release_due_embargoed_proofs` (run on publish)
+//! moves the ones whose date has passed to the proof repository.
+use crate::{Error, Result};
+use crev_common::serde::{as_rfc3339_fixed, from_rfc3339_fixed};
+use crev_data::proof::{self, CommonOps};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+pub type Date = chrono::DateTime<chrono::FixedOffset>;
+
+const EMBARGOED_PROOF_SUFFIX: &str = ".embargoed.yaml";
+
+/// As stored on disk
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct EmbargoFile {
+    #[serde(
+        serialize_with = "as_rfc3339_fixed",
+        deserialize_with = "from_rfc3339_fixed"
+    )]
+    publish_after: Date,
+    proof: String,
+}
+
+/// An embargoed proof, with the name to refer to it by
+#[derive(Debug)]
+pub struct EmbargoedProof {
+    /// Digest of the proof, base64-encoded
+    pub name: String,
+    /// Not published before this date
+    pub publish_after: Date,
+    pub proof: proof::Proof,
+}
+
+impl EmbargoedProof {
+    #[must_use]
+    pub fn is_due(&self, now: &Date) -> bool {
+        self.publish_after <= *now
+    }
+}
+
+/// Directory of embargoed proofs, one file each, named after their digests
+#[derive(Debug, Clone)]
+pub struct ProofEmbargo {
+    dir: PathBuf,
+}
+
+impl ProofEmbargo {
+    pub(crate) fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{name}{EMBARGOED_PROOF_SUFFIX}"))
+    }
+
+    /// Keep `proof` back until `publish_after`, returning its name
+    pub fn add(&self, proof: &proof::Proof, publish_after: Date) -> Result<String> {
+        let name = crev_common::base64_encode(proof.digest());
+        let file = EmbargoFile {
+            publish_after,
+            proof: proof.to_string(),
+        };
+        crev_common::save_to_yaml_file(&self.path(&name), &file)
+            .map_err(|e| Error::Embargo(Box::new(e)))?;
+        Ok(name)
+    }
+
+    /// All embargoed proofs, soonest due first
+    pub fn list(&self) -> Result<Vec<EmbargoedProof>> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(e.into()),
+        };
+        let mut embargoed = vec![];
+        for entry in entries {
+            let path = entry?.path();
+            let Some(name) = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_suffix(EMBARGOED_PROOF_SUFFIX))
+            else {
+                continue;
+            };
+            let file: EmbargoFile =
+                crev_common::read_from_yaml_file(&path).map_err(|e| Error::Embargo(Box::new(e)))?;
+            for proof in proof::Proof::parse_from(file.proof.as_bytes())? {
+                embargoed.push(EmbargoedProof {
+                    name: name.to_owned(),
+                    publish_after: file.publish_after,
+                    proof,
+                });
+            }
+        }
+        embargoed.sort_by_key(|embargoed| (embargoed.publish_after, embargoed.proof.date_utc()));
+        Ok(embargoed)
+    }
+
+    /// Embargoed proofs with names starting with `prefix`
+    pub fn find(&self, prefix: &str) -> Result<Vec<EmbargoedProof>> {
+        Ok(self
+            .list()?
+            .into_iter()
+            .filter(|embargoed| embargoed.name.starts_with(prefix))
+            .collect())
+    }
+
+    /// Drop the proof named `name`. Returns `false` if there was none.
+    pub fn remove(&self, name: &str) -> Result<bool> {
+        match fs::remove_file(self.path(name)) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
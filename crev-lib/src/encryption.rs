@@ -0,0 +1,133 @@
+//! Encrypted proofs, that only some Ids can read
+//!
+//! The signed proof is encrypted (AES-SIV) with a random key, and that key
+//! is encrypted to every recipient with an X25519 key agreement between an
+//! ephemeral key and the recipient's Id (an Ed25519 public key, converted).
+//! The result is a [`proof::Encrypted`], signed by the author as usual, so it
+//! can be published in a public proof repository.
+use crate::{Error, Result};
+use aes_siv::{
+    aead::generic_array::GenericArray,
+    siv::{Aes256Siv, IV_SIZE},
+    KeyInit, Tag,
+};
+use blake2::{Blake2b512, Digest as _};
+use crev_common::rand::random_vec;
+use crev_data::{
+    proof::{self, CommonOps, ContentExt},
+    Id, UnlockedId,
+};
+use curve25519_dalek::MontgomeryPoint;
+use ed25519_dalek::VerifyingKey;
+
+/// Associated data, so keys and proofs can't be swapped with each other
+const PROOF_AD: &[u8] = b"crev encrypted proof";
+const KEY_AD: &[u8] = b"crev encrypted proof key";
+
+fn seal(key: &[u8], associated_data: &[u8], plaintext: &[u8]) -> Vec<u8> {
+    let mut siv = Aes256Siv::new(&GenericArray::clone_from_slice(key));
+    let mut buffer = vec![0; IV_SIZE + plaintext.len()];
+    buffer[IV_SIZE..].copy_from_slice(plaintext);
+    let tag = siv
+        .encrypt_in_place_detached([associated_data], &mut buffer[IV_SIZE..])
+        .expect("aes-encrypt");
+    buffer[..IV_SIZE].copy_from_slice(&tag);
+    buffer
+}
+
+fn open(key: &[u8], associated_data: &[u8], sealed: &[u8]) -> Result<Vec<u8>> {
+    if sealed.len() < IV_SIZE {
+        return Err(Error::DecryptionFailed);
+    }
+    let mut siv = Aes256Siv::new(&GenericArray::clone_from_slice(key));
+    let tag = Tag::clone_from_slice(&sealed[..IV_SIZE]);
+    let mut buffer = sealed[IV_SIZE..].to_vec();
+    siv.decrypt_in_place_detached([associated_data], &mut buffer, &tag)
+        .map_err(|_| Error::DecryptionFailed)?;
+    Ok(buffer)
+}
+
+/// Key the content key is encrypted to `recipient` with
+fn wrapping_key(shared: &MontgomeryPoint, ephemeral: &MontgomeryPoint, recipient: &Id) -> Vec<u8> {
+    let mut hasher = Blake2b512::new();
+    hasher.update(shared.as_bytes());
+    hasher.update(ephemeral.as_bytes());
+    hasher.update(recipient.to_bytes());
+    hasher.finalize().to_vec()
+}
+
+fn x25519_public_key(id: &Id) -> Result<MontgomeryPoint> {
+    let invalid = || Error::InvalidRecipient(id.to_string().into());
+    let bytes: [u8; 32] = id.to_bytes().try_into().map_err(|_| invalid())?;
+    Ok(VerifyingKey::from_bytes(&bytes)
+        .map_err(|_| invalid())?
+        .to_montgomery())
+}
+
+/// Encrypt the signed `proof` to `recipients`, as a new proof signed by `author`
+///
+/// The author is always one of the recipients, so they can read their own proofs.
+pub fn encrypt_proof(
+    proof: &proof::Proof,
+    recipients: &[Id],
+    author: &UnlockedId,
+) -> Result<proof::Proof> {
+    let content_key = random_vec(64);
+    let ephemeral_secret: [u8; 32] = random_vec(32).try_into().expect("32 bytes");
+    let ephemeral = MontgomeryPoint::mul_base_clamped(ephemeral_secret);
+
+    let mut ids: Vec<&Id> = recipients.iter().chain([&author.id.id]).collect();
+    ids.sort();
+    ids.dedup();
+    let recipients = ids
+        .into_iter()
+        .map(|id| {
+            let shared = x25519_public_key(id)?.mul_clamped(ephemeral_secret);
+            Ok(proof::EncryptedRecipient {
+                id: id.clone(),
+                key: seal(&wrapping_key(&shared, &ephemeral, id), KEY_AD, &content_key),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let ciphertext = seal(&content_key, PROOF_AD, proof.to_string().as_bytes());
+
+    Ok(author
+        .as_public_id()
+        .create_encrypted_proof(ephemeral.to_bytes().to_vec(), recipients, ciphertext)?
+        .sign_by(author)?)
+}
+
+/// The signed proof inside `encrypted`, decrypted with the key of `id`
+///
+/// `None` if `id` isn't one of the recipients. The proof inside must be
+/// signed by the author of the encrypted proof.
+pub fn decrypt_proof(
+    encrypted: &proof::Encrypted,
+    id: &UnlockedId,
+) -> Result<Option<proof::Proof>> {
+    let Some(recipient) = encrypted.recipient(&id.id.id) else {
+        return Ok(None);
+    };
+    let ephemeral = MontgomeryPoint(
+        encrypted
+            .ephemeral
+            .as_slice()
+            .try_into()
+            .map_err(|_| Error::DecryptionFailed)?,
+    );
+    let shared = ephemeral.mul_clamped(id.keypair.secret.to_scalar_bytes());
+    let content_key = open(
+        &wrapping_key(&shared, &ephemeral, &id.id.id),
+        KEY_AD,
+        &recipient.key,
+    )?;
+    let plaintext = open(&content_key, PROOF_AD, &encrypted.ciphertext)?;
+
+    let [proof] = <[proof::Proof; 1]>::try_from(proof::Proof::parse_from(plaintext.as_slice())?)
+        .map_err(|_| Error::DecryptionFailed)?;
+    proof.verify()?;
+    if proof.from().id != encrypted.from().id {
+        return Err(Error::DecryptionFailed);
+    }
+    Ok(Some(proof))
+}
@@ -0,0 +1,158 @@
+//! Stable codes of [`Error`]s
+//!
+//! Messages get reworded; codes don't. Scripts (and issue reports) should
+//! match on the code, eg. `E203`, or on the broader [`ErrorCategory`].
+//! Codes are never reused: a removed variant keeps its code retired.
+//!
+//! [`Warning`]s have codes too, starting with `W`.
+use crate::{Error, Warning};
+use serde::Serialize;
+use std::fmt;
+
+/// Broad class of a failure
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ErrorCategory {
+    /// Missing or invalid configuration of crev or the project
+    Config,
+    /// Ids, passphrases and keys
+    Id,
+    /// Proof repositories, fetching and publishing
+    Git,
+    /// Malformed or unreadable proofs and other data
+    Data,
+    /// Something doesn't match its digest or signature
+    Integrity,
+    /// Not available in this build or format version
+    Unsupported,
+    /// Reading or writing files
+    Io,
+    /// Stopped by the user
+    Cancelled,
+}
+
+impl fmt::Display for ErrorCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ErrorCategory::Config => "config",
+            ErrorCategory::Id => "id",
+            ErrorCategory::Git => "git",
+            ErrorCategory::Data => "data",
+            ErrorCategory::Integrity => "integrity",
+            ErrorCategory::Unsupported => "unsupported",
+            ErrorCategory::Io => "io",
+            ErrorCategory::Cancelled => "cancelled",
+        })
+    }
+}
+
+/// Errors with a stable code
+pub trait ErrorCode {
+    /// `E` and three digits; the first digit follows the category
+    fn code(&self) -> &'static str;
+
+    fn category(&self) -> ErrorCategory;
+}
+
+impl ErrorCode for Error {
+    fn code(&self) -> &'static str {
+        match self {
+            // config
+            Error::UserConfigNotInitialized => "E101",
+            Error::UserConfigAlreadyExists => "E102",
+            Error::UserConfigLoadError(_) => "E103",
+            Error::UserConfigParse(_) => "E104",
+            Error::NoHomeDirectory => "E105",
+            Error::PackageConfigNotInitialized => "E106",
+            Error::PackageDirNotFound(_) => "E107",
+            Error::PathAlreadyExists(_) => "E108",
+            Error::PathNotInStageRootPath => "E109",
+            Error::HookFailed(_) => "E110",
+            // id
+            Error::IdLoadError(_) => "E201",
+            Error::IDFileNotFound => "E202",
+            Error::IncorrectPassphrase => "E203",
+            Error::CurrentIDNotSet => "E204",
+            Error::IDNotSpecifiedAndCurrentIDNotSet => "E205",
+            Error::PubKeyMismatch => "E206",
+            Error::NoIdsGiven => "E207",
+            Error::Passphrase(_) => "E208",
+            Error::Id(_) => "E209",
+            #[cfg(feature = "keyring")]
+            Error::Keyring(_) => "E210",
+            Error::InvalidRecipient(_) => "E211",
+            // git
+            Error::GitRepositoryIsNotInACleanState => "E301",
+            Error::CouldNotCloneGitHttpsURL(_) => "E302",
+            Error::OriginHasNoURL(_) => "E303",
+            Error::GitUrlNotConfigured => "E304",
+            Error::GitEntryWithoutAPath => "E305",
+            Error::Git(_) => "E306",
+            Error::ProofRepoTooBig(_) => "E307",
+            Error::NotAGitCheckout(_) => "E308",
+            // data
+            Error::YAML(_) => "E401",
+            Error::CBOR(_) => "E402",
+            Error::JSON(_) => "E403",
+            Error::Data(_) => "E404",
+            Error::ReviewActivity(_) => "E405",
+            Error::FetchState(_) => "E406",
+            Error::ErrorIteratingLocalProofStore(_) => "E407",
+            Error::InvalidAttestationPayload(_) => "E408",
+            Error::UsageStats(_) => "E409",
+            Error::ReviewTemplate(_) => "E410",
+            Error::ReviewTemplateNotSatisfied(_) => "E411",
+            Error::Embargo(_) => "E412",
+            Error::InvalidManifest(_) => "E413",
+            Error::InvalidProjectPolicy(_) => "E414",
+            // integrity
+            Error::FileNotCurrent(_) => "E501",
+            Error::ProofIndexDigestMismatch => "E502",
+            Error::EpochArchiveDigestMismatch(_) => "E503",
+            Error::Digest(_) => "E504",
+            Error::PinnedInputsMismatch(_) => "E505",
+            Error::DecryptionFailed => "E506",
+            Error::ProofNotInFile(_) => "E507",
+            Error::CantPinInputs(_) => "E508",
+            // unsupported
+            Error::UnsupportedVersion(_) => "E601",
+            Error::UnsupportedAttestationPayloadType(_) => "E602",
+            Error::KeyringNotSupported => "E603",
+            Error::UnknownDigestIgnorePolicy(_) => "E604",
+            Error::UnknownDigestType(_) => "E605",
+            // io
+            Error::IO(_) => "E701",
+            Error::FileWrite(_, _) => "E702",
+            Error::CrateSourceSanitizationError(_) => "E703",
+            // cancelled
+            Error::Cancelled(_) => "E801",
+        }
+    }
+
+    fn category(&self) -> ErrorCategory {
+        match self.code().as_bytes()[1] {
+            b'1' => ErrorCategory::Config,
+            b'2' => ErrorCategory::Id,
+            b'3' => ErrorCategory::Git,
+            b'4' => ErrorCategory::Data,
+            b'5' => ErrorCategory::Integrity,
+            b'6' => ErrorCategory::Unsupported,
+            b'7' => ErrorCategory::Io,
+            _ => ErrorCategory::Cancelled,
+        }
+    }
+}
+
+impl Warning {
+    /// `W` and three digits, see the module docs
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        match self {
+            Warning::Error(_) => "W101",
+            Warning::NoRepoUrlAtPath(..) => "W102",
+            Warning::IdUrlNotKnonw(_) => "W103",
+            Warning::GitPushUrl(_) => "W104",
+            Warning::FetchError(..) => "W105",
+        }
+    }
+}
@@ -0,0 +1,60 @@
+//! Progress of a long-running `fetch all`
+//!
+//! Fetching every known proof repository can take a long time,
+//! so we record which URLs were already handled, and resume
+//! from there if the previous run was interrupted.
+use crev_common::{
+    self,
+    serde::{as_rfc3339_fixed, from_rfc3339_fixed},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+pub type Date = chrono::DateTime<chrono::FixedOffset>;
+
+/// Record of a `fetch all` run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FetchAllState {
+    #[serde(
+        serialize_with = "as_rfc3339_fixed",
+        deserialize_with = "from_rfc3339_fixed"
+    )]
+    pub started: Date,
+    /// Set once every known repository has been attempted
+    #[serde(default)]
+    pub finished: bool,
+    /// URLs fetched and imported successfully
+    #[serde(default)]
+    pub completed: BTreeSet<String>,
+    /// URLs that failed, with the reason
+    #[serde(default)]
+    pub failed: BTreeMap<String, String>,
+}
+
+impl Default for FetchAllState {
+    fn default() -> Self {
+        Self {
+            started: crev_common::now(),
+            finished: false,
+            completed: BTreeSet::new(),
+            failed: BTreeMap::new(),
+        }
+    }
+}
+
+impl FetchAllState {
+    /// URLs that don't need to be fetched again in this run
+    pub fn attempted_urls(&self) -> impl Iterator<Item = &String> {
+        self.completed.iter().chain(self.failed.keys())
+    }
+
+    pub fn record_success(&mut self, url: &str) {
+        self.failed.remove(url);
+        self.completed.insert(url.to_owned());
+    }
+
+    pub fn record_failure(&mut self, url: &str, reason: String) {
+        self.completed.remove(url);
+        self.failed.insert(url.to_owned(), reason);
+    }
+}
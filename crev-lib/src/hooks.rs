@@ -0,0 +1,194 @@
+//! Callbacks for applications embedding [`Local`](crate::Local)
+//!
+//! Register them with `Local::on_proof_inserted` and `Local::on_repo_fetched`
+//! to react to changes (reindex, notify, ...) instead of watching the
+//! filesystem. Callbacks run synchronously, in the order they were registered,
+//! on the thread doing the work; they can't register more callbacks.
+//!
+//! Users can also configure shell commands to run around proof creation,
+//! see [`HookCmds`].
+use crate::{util::git::CommitSignatureStatus, Error, Result};
+use crev_data::proof;
+use crev_wot::ImportReport;
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    ffi::OsStr,
+    io::{self, Write},
+    path::Path,
+    process::Stdio,
+    sync::Mutex,
+};
+
+/// A proof was written to the local proof repository
+#[derive(Debug)]
+pub struct ProofInserted<'a> {
+    pub proof: &'a proof::Proof,
+    /// Where it was appended to, relative to the proof repository
+    pub rel_path: &'a Path,
+}
+
+/// Someone's proof repository was fetched and imported
+#[derive(Debug)]
+pub struct RepoFetched<'a> {
+    pub url: &'a str,
+    /// The local checkout of the repository
+    pub dir: &'a Path,
+    pub report: &'a ImportReport,
+    /// Trust proofs that weren't known before
+    pub new_trust_count: usize,
+    /// Package reviews that weren't known before
+    pub new_pkg_review_count: usize,
+    /// Of the `HEAD` commit; `None` if it couldn't be checked
+    pub signature_status: Option<CommitSignatureStatus>,
+}
+
+pub type ProofInsertedFn = Box<dyn Fn(&ProofInserted<'_>) + Send + Sync>;
+pub type RepoFetchedFn = Box<dyn Fn(&RepoFetched<'_>) + Send + Sync>;
+
+#[derive(Default)]
+pub(crate) struct Hooks {
+    proof_inserted: Mutex<Vec<ProofInsertedFn>>,
+    repo_fetched: Mutex<Vec<RepoFetchedFn>>,
+}
+
+impl Hooks {
+    /// Hooks printing what the command line tools always printed
+    pub(crate) fn with_default_logging() -> Self {
+        let hooks = Self::default();
+        hooks.add_repo_fetched(Box::new(log_repo_fetched));
+        hooks
+    }
+
+    pub(crate) fn add_proof_inserted(&self, f: ProofInsertedFn) {
+        self.proof_inserted.lock().unwrap().push(f);
+    }
+
+    pub(crate) fn add_repo_fetched(&self, f: RepoFetchedFn) {
+        self.repo_fetched.lock().unwrap().push(f);
+    }
+
+    pub(crate) fn clear(&self) {
+        self.proof_inserted.lock().unwrap().clear();
+        self.repo_fetched.lock().unwrap().clear();
+    }
+
+    pub(crate) fn proof_inserted(&self, event: &ProofInserted<'_>) {
+        for f in self.proof_inserted.lock().unwrap().iter() {
+            f(event);
+        }
+    }
+
+    pub(crate) fn repo_fetched(&self, event: &RepoFetched<'_>) {
+        for f in self.repo_fetched.lock().unwrap().iter() {
+            f(event);
+        }
+    }
+}
+
+/// Summary of a fetch, one line per repository
+fn log_repo_fetched(event: &RepoFetched<'_>) {
+    let RepoFetched {
+        url,
+        report,
+        new_trust_count,
+        new_pkg_review_count,
+        ..
+    } = event;
+    let msg = match (*new_trust_count > 0, *new_pkg_review_count > 0) {
+        (true, true) => {
+            format!("new: {new_trust_count} trust, {new_pkg_review_count} package reviews")
+        }
+        (true, false) => format!("new: {new_trust_count} trust",),
+        (false, true) => format!("new: {new_pkg_review_count} package reviews"),
+        (false, false) => "no updates".into(),
+    };
+
+    info!("{:<60} {}", url, msg);
+    match &event.signature_status {
+        None | Some(CommitSignatureStatus::Unsigned) => {}
+        Some(status @ CommitSignatureStatus::Invalid) => {
+            warn!("{:<60} commit: {}", url, status);
+        }
+        Some(status) => info!("{:<60} commit: {}", url, status),
+    }
+    if report.rejected_count() > 0 {
+        warn!("{:<60} {}", url, report);
+    } else {
+        debug!("{:<60} {}", url, report);
+    }
+}
+
+/// Shell commands run around proof creation (`hooks` in the user config)
+///
+/// Hooks run in the shell, with `CREV_HOOK` set to the name of the hook
+/// and `CREV_PROOF_KIND` to the kind of the proof (eg. `package review`).
+/// Their stderr goes to the terminal.
+///
+/// * `pre-sign` runs before a package review draft is opened for editing,
+///   in the directory of the package source. It gets the draft on stdin,
+///   and `CREV_PACKAGE_NAME`, `CREV_PACKAGE_VERSION` and `CREV_PACKAGE_DIR`.
+///   What it prints is appended to the comment of the review. If it fails,
+///   no review is created.
+/// * `post-store` runs after any proof was written to the local proof
+///   repository, in its directory. It gets the proof on stdin, and
+///   `CREV_PROOF_PATH`, the file the proof was appended to. If it fails,
+///   the proof stays stored and a warning is logged.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct HookCmds {
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub pre_sign: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub post_store: Option<String>,
+}
+
+impl HookCmds {
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.pre_sign.is_none() && self.post_store.is_none()
+    }
+}
+
+/// Run the `hook` command `cmd` in `dir`, feeding it `input`
+///
+/// Returns the stdout of the command if `capture_stdout`.
+pub(crate) fn run_hook_cmd(
+    hook: &'static str,
+    cmd: &str,
+    dir: &Path,
+    env: &[(&str, &OsStr)],
+    input: &[u8],
+    capture_stdout: bool,
+) -> Result<Vec<u8>> {
+    let failed = |reason: String| Error::HookFailed(Box::new((hook, reason)));
+    let mut child = crev_common::shell_cmd(cmd.as_ref(), None)?
+        .current_dir(dir)
+        .env("CREV_HOOK", hook)
+        .envs(env.iter().copied())
+        .stdin(Stdio::piped())
+        .stdout(if capture_stdout {
+            Stdio::piped()
+        } else {
+            Stdio::inherit()
+        })
+        .stderr(Stdio::inherit())
+        .spawn()
+        .map_err(|e| failed(format!("can't run `{cmd}`: {e}")))?;
+
+    // written from another thread, so a hook printing a lot before reading
+    // its input doesn't block us both
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let input = input.to_vec();
+    let writer = std::thread::spawn(move || stdin.write_all(&input));
+    let output = child.wait_with_output()?;
+    match writer.join().expect("writer doesn't panic") {
+        // the hook doesn't have to read its input
+        Err(e) if e.kind() != io::ErrorKind::BrokenPipe => return Err(e.into()),
+        _ => {}
+    }
+    if !output.status.success() {
+        return Err(failed(format!("`{cmd}` exited with {}", output.status)));
+    }
+    Ok(output.stdout)
+}
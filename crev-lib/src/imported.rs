@@ -0,0 +1,53 @@
+//! Proofs converted from other sources, eg. the RustSec advisory database
+//!
+//! Each source has a directory in the data directory, with an Id of its own
+//! (locked with an empty passphrase, as nobody is there to type one) and the
+//! proofs signed with it. `Local::load_db` loads them like proofs of anyone
+//! else, so they count as much as the user trusts that Id.
+use crate::{id::LockedId, Result};
+use crev_data::{proof, UnlockedId};
+use std::{
+    fmt::Write as _,
+    path::{Path, PathBuf},
+};
+
+const ID_FILE_NAME: &str = "id.yaml";
+const PROOFS_FILE_NAME: &str = "proofs.crev";
+
+/// Directory of the proofs imported from one source
+#[derive(Debug, Clone)]
+pub struct ImportedProofs {
+    dir: PathBuf,
+}
+
+impl ImportedProofs {
+    pub(crate) fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The Id proofs of this source are signed with, generated the first time
+    pub fn signing_id(&self) -> Result<UnlockedId> {
+        let path = self.dir.join(ID_FILE_NAME);
+        if path.exists() {
+            return LockedId::read_from_yaml_file(&path)?.to_unlocked("");
+        }
+        let id = UnlockedId::generate(None);
+        LockedId::from_unlocked_id(&id, "")?.save_to(&path)?;
+        Ok(id)
+    }
+
+    /// Replace the proofs imported before with `proofs`
+    pub fn replace(&self, proofs: &[proof::Proof]) -> Result<()> {
+        let mut text = String::new();
+        for proof in proofs {
+            let _ = writeln!(text, "{proof}");
+        }
+        crev_common::store_str_to_file(&self.dir.join(PROOFS_FILE_NAME), &text)?;
+        Ok(())
+    }
+}
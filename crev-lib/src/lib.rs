@@ -7,14 +7,34 @@
 #![allow(clippy::redundant_closure_for_method_calls)]
 
 pub mod activity;
+pub mod attestation;
+pub mod digest_ignore;
+pub mod embargo;
+pub mod encryption;
+pub mod error_code;
+pub mod fetch_state;
+pub mod hooks;
 pub mod id;
+pub mod imported;
 pub mod local;
+pub mod passphrase;
+pub mod pinned_inputs;
+pub mod project_policy;
 pub mod proof;
+pub mod proof_cache;
+pub mod proof_factory;
+pub mod proof_index;
+pub mod recommend;
 pub mod repo;
+pub mod review_template;
 pub mod staging;
+pub mod usage;
 pub mod util;
+pub mod validate;
+pub mod verify;
 pub use crate::local::Local;
-pub use activity::{ReviewActivity, ReviewMode};
+pub use crate::proof_factory::ProofFactory;
+pub use activity::{ReviewActivity, ReviewMode, ReviewSession};
 use crev_data::{
     self,
     id::IdError,
@@ -23,16 +43,20 @@ use crev_data::{
         trust::TrustLevel,
         CommonOps,
     },
-    Digest, Id, RegistrySource, Version,
+    Digest, DigestType, Id, RegistrySource, Version,
 };
 use crev_wot::PkgVersionReviewId;
-pub use crev_wot::TrustDistanceParams;
+pub use crev_wot::{DistrustPolicy, TrustDistanceParams};
+pub use digest_ignore::DigestIgnorePolicy;
+pub use error_code::{ErrorCategory, ErrorCode};
 use log::warn;
+use serde::Serialize;
 use std::error::Error as _;
 use std::{
     collections::{HashMap, HashSet},
     fmt,
     path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
 };
 
 /// Failures that can happen in this library
@@ -154,6 +178,90 @@ pub enum Error {
     #[error("Review activity parse error: {}", _0)]
     ReviewActivity(#[source] Box<crev_common::YAMLIOError>),
 
+    /// YAML ;(
+    #[error("Fetch progress state error: {}", _0)]
+    FetchState(#[source] Box<crev_common::YAMLIOError>),
+
+    /// YAML ;(
+    #[error("Usage stats error: {}", _0)]
+    UsageStats(#[source] Box<crev_common::YAMLIOError>),
+
+    /// YAML ;(
+    #[error("Review template error: {}", _0)]
+    ReviewTemplate(#[source] Box<crev_common::YAMLIOError>),
+
+    /// YAML ;(
+    #[error("Embargoed proof error: {}", _0)]
+    Embargo(#[source] Box<crev_common::YAMLIOError>),
+
+    /// A crate's `Cargo.toml` isn't valid TOML
+    #[error("Can't parse {}: {}", _0.0.display(), _0.1)]
+    InvalidManifest(Box<(PathBuf, String)>),
+
+    /// The review lacks something the review template requires
+    #[error("The review doesn't follow the review template: {}", _0)]
+    ReviewTemplateNotSatisfied(Box<str>),
+
+    /// `index.json` was changed after `index-manifest.json` was signed
+    #[error("Proof index doesn't match the digest in its signed manifest")]
+    ProofIndexDigestMismatch,
+
+    /// The archive of a truncated proof repo has other proofs than its epoch proof says
+    #[error("Proof archive {} doesn't match the digest in its epoch proof", _0)]
+    EpochArchiveDigestMismatch(String),
+
+    /// Someone's proof repo is over `ProofLimits::max_repo_size`
+    #[error(
+        "Proof repo {} is bigger than {} bytes; add it to `proof-limits.unlimited-urls` in the config if you trust it",
+        _0.0,
+        _0.1
+    )]
+    ProofRepoTooBig(Box<(String, u64)>),
+
+    #[error("{} is not in a git checkout", _0.display())]
+    NotAGitCheckout(Box<Path>),
+
+    /// Proofs available now are not the ones recorded in `PinnedInputs`
+    #[error("Inputs differ from the pinned ones: {}", _0)]
+    PinnedInputsMismatch(String),
+
+    /// Inputs that can't be used on other machines, like uncommitted proofs
+    #[error("Can't pin the inputs: {}", _0)]
+    CantPinInputs(String),
+
+    /// Only in-toto statements are supported
+    #[error("Unsupported attestation payload type: {}", _0)]
+    UnsupportedAttestationPayloadType(String),
+
+    /// Attestation envelope is malformed
+    #[error("Invalid attestation: {}", _0)]
+    InvalidAttestationPayload(String),
+
+    /// Built without the `keyring` feature
+    #[error("This build of crev has no OS keyring support")]
+    KeyringNotSupported,
+
+    /// A review's digest was computed with rules this version doesn't know
+    #[error("Unknown digest ignore policy: {}", _0)]
+    UnknownDigestIgnorePolicy(String),
+
+    /// A review's digest was computed with a hash function this version doesn't know
+    #[error("Unknown digest type: {}", _0)]
+    UnknownDigestType(String),
+
+    /// A command from `hooks` in the user config, see [`hooks::HookCmds`]
+    #[error("The `{}` hook failed: {}", _0.0, _0.1)]
+    HookFailed(Box<(&'static str, String)>),
+
+    /// See [`keyring::Error`]
+    #[cfg(feature = "keyring")]
+    #[error("Keyring: {}", _0)]
+    Keyring(#[from] keyring::Error),
+
+    /// See [`serde_json::Error`]
+    #[error(transparent)]
+    JSON(#[from] serde_json::Error),
+
     /// YAML ;(
     #[error("Error parsing user config: {}", _0)]
     UserConfigParse(#[source] serde_yaml::Error),
@@ -181,6 +289,28 @@ pub enum Error {
     /// See [`IdError`]
     #[error(transparent)]
     Id(#[from] IdError),
+
+    /// Proofs can be encrypted only to valid Ids
+    #[error("Can't encrypt to {}: not a valid Id", _0)]
+    InvalidRecipient(Box<str>),
+
+    /// Wrong key, or the encrypted proof was tampered with
+    #[error("Can't decrypt the encrypted proof")]
+    DecryptionFailed,
+
+    /// `.crev-policy.yaml` of a project is not valid
+    #[error("Invalid project policy: {}", _0)]
+    InvalidProjectPolicy(Box<str>),
+
+    /// The proof file was changed by hand since the proof was read from it
+    #[error("The proof isn't in {} anymore", _0.display())]
+    ProofNotInFile(Box<Path>),
+}
+
+impl From<crev_data::proof::BuildProofError> for Error {
+    fn from(e: crev_data::proof::BuildProofError) -> Self {
+        Self::Data(e.into())
+    }
 }
 
 /// [`crate::Error`]
@@ -266,6 +396,31 @@ impl Default for VerificationRequirements {
     }
 }
 
+impl VerificationRequirements {
+    /// Digest of the requirements, stable between runs
+    ///
+    /// See [`crev_wot::TrustSet::stable_digest`].
+    #[must_use]
+    pub fn stable_digest(&self) -> Digest {
+        let text = format!(
+            "trust-level {}\nunderstanding {}\nthoroughness {}\nredundancy {}",
+            self.trust_level, self.understanding, self.thoroughness, self.redundancy
+        );
+        Digest::from(crev_common::blake2b256sum(text.as_bytes()))
+    }
+
+    /// Requirements meeting both `self` and `other`
+    #[must_use]
+    pub fn stricter(&self, other: &Self) -> Self {
+        Self {
+            trust_level: self.trust_level.max(other.trust_level),
+            understanding: self.understanding.max(other.understanding),
+            thoroughness: self.thoroughness.max(other.thoroughness),
+            redundancy: self.redundancy.max(other.redundancy),
+        }
+    }
+}
+
 /// Result of verification
 ///
 /// Not named `Result` to avoid confusion with `Result` type.
@@ -288,6 +443,25 @@ impl VerificationStatus {
         self == VerificationStatus::Verified
     }
 
+    /// Status of a package with reviews judged as `verdicts`
+    #[must_use]
+    pub fn from_verdicts(
+        verdicts: &[ReviewVerdict],
+        requirements: &VerificationRequirements,
+    ) -> Self {
+        let counted = verdicts
+            .iter()
+            .filter(|verdict| **verdict == ReviewVerdict::Counted)
+            .count() as u64;
+        if verdicts.contains(&ReviewVerdict::Negative) {
+            VerificationStatus::Negative
+        } else if counted >= requirements.redundancy {
+            VerificationStatus::Verified
+        } else {
+            VerificationStatus::Insufficient
+        }
+    }
+
     /// Pick worse of both
     #[must_use]
     pub fn min(self, other: Self) -> Self {
@@ -312,6 +486,45 @@ impl fmt::Display for VerificationStatus {
     }
 }
 
+/// Reviews matching `Digest` by trusted Ids, one per Id, without overridden ones
+fn trusted_reviews_by_digest(
+    digest: &Digest,
+    trust_set: &crev_wot::TrustSet,
+    db: &crev_wot::ProofDB,
+) -> HashMap<Id, review::Package> {
+    let trusted_ids: HashSet<_> = trust_set.get_trusted_ids();
+    db.get_package_reviews_by_digest(digest)
+        .filter(|review| trusted_ids.contains(&review.from().id))
+        .filter(|review| !is_overridden(review, trust_set))
+        .map(|review| (review.from().id.clone(), review))
+        .collect()
+}
+
+/// Did Ids trusted more than the reviewer ask to ignore `review`?
+fn is_overridden(review: &review::Package, trust_set: &crev_wot::TrustSet) -> bool {
+    match trust_set
+        .package_review_ignore_override
+        .get(&PkgVersionReviewId::from(review))
+    {
+        Some(reporters) => {
+            trust_set.get_effective_trust_level(&review.common.from.id)
+                < reporters.max_level().unwrap_or(TrustLevel::None)
+        }
+        None => false,
+    }
+}
+
+/// Is `review` positive and thorough enough to count towards `requirements`?
+pub fn meets_requirements(
+    review: &crev_data::Review,
+    requirements: &VerificationRequirements,
+) -> bool {
+    !review.is_none()
+        && Rating::Neutral <= review.rating
+        && requirements.thoroughness <= review.thoroughness
+        && requirements.understanding <= review.understanding
+}
+
 /// Find reviews matching `Digest` (exact data of the crate)
 /// and see if there are enough positive reviews for it.
 pub fn verify_package_digest(
@@ -320,54 +533,163 @@ pub fn verify_package_digest(
     requirements: &VerificationRequirements,
     db: &crev_wot::ProofDB,
 ) -> VerificationStatus {
-    let reviews: HashMap<Id, review::Package> = db
-        .get_package_reviews_by_digest(digest)
-        .filter(|review| {
-            match trust_set
-                .package_review_ignore_override
-                .get(&PkgVersionReviewId::from(review))
-            {
-                Some(reporters) => {
-                    reporters.max_level().unwrap_or(TrustLevel::None)
-                        <= trust_set.get_effective_trust_level(&review.common.from.id)
-                }
-                None => true,
-            }
-        })
-        .map(|review| (review.from().id.clone(), review))
+    let verdicts: Vec<_> = trusted_reviews_by_digest(digest, trust_set, db)
+        .values()
+        .map(|review| ReviewVerdict::new(review, digest, trust_set, requirements))
         .collect();
-    // Faster somehow maybe?
-    let reviews_by: HashSet<Id, _> = reviews.keys().cloned().collect();
-    let trusted_ids: HashSet<_> = trust_set.get_trusted_ids();
-    let matching_reviewers = trusted_ids.intersection(&reviews_by);
-    let mut trust_count = 0;
-    let mut negative_count = 0;
-    for matching_reviewer in matching_reviewers {
-        let review = &reviews[matching_reviewer].review_possibly_none();
-        if !review.is_none()
-            && Rating::Neutral <= review.rating
-            && requirements.thoroughness <= review.thoroughness
-            && requirements.understanding <= review.understanding
+    VerificationStatus::from_verdicts(&verdicts, requirements)
+}
+
+/// Why a review does or doesn't count in `verify_package_digest`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReviewVerdict {
+    /// Counts towards `VerificationRequirements::redundancy`
+    Counted,
+    /// Negative rating by a trusted Id: the package fails verification
+    Negative,
+    /// Review of different files than the ones verified
+    DigestMismatch,
+    /// Review of a patch (`review --from-patch`), not of the whole package
+    PatchOnly,
+    /// Review of a subtree (`review --subtree`), not of the whole package
+    SubtreeOnly,
+    /// The reviewer is not in the trust set
+    UntrustedReviewer,
+    /// Ids trusted more than the reviewer asked to ignore this review
+    Overridden,
+    /// Review withdrawn with `unreview`
+    Withdrawn,
+    /// Reported thoroughness is lower than required
+    ThoroughnessTooLow(crev_data::Level),
+    /// Reported understanding is lower than required
+    UnderstandingTooLow(crev_data::Level),
+    /// The reviewer is trusted, but less than required
+    TrustTooLow(TrustLevel),
+}
+
+impl ReviewVerdict {
+    /// Judge `review` the way `verify_package_digest` would, if `review` was
+    /// the reviewer's only review of the package
+    #[must_use]
+    pub fn new(
+        review: &review::Package,
+        digest: &Digest,
+        trust_set: &crev_wot::TrustSet,
+        requirements: &VerificationRequirements,
+    ) -> Self {
+        let reviewer = &review.common.from.id;
+        let review_body = review.review_possibly_none();
+        let trust_level = trust_set.get_effective_trust_level(reviewer);
+        if review.patch.is_some() {
+            Self::PatchOnly
+        } else if review
+            .subtree
+            .as_ref()
+            .is_some_and(|subtree| subtree.digest != digest.as_slice())
         {
-            if TrustLevel::from(requirements.trust_level)
-                <= trust_set.get_effective_trust_level(matching_reviewer)
-            {
-                trust_count += 1;
+            Self::SubtreeOnly
+        } else if review.package.digest != digest.as_slice() {
+            Self::DigestMismatch
+        } else if !trust_set.is_trusted(reviewer) {
+            Self::UntrustedReviewer
+        } else if is_overridden(review, trust_set) {
+            Self::Overridden
+        } else if review_body.is_none() {
+            Self::Withdrawn
+        } else if review_body.rating <= Rating::Negative {
+            Self::Negative
+        } else if !meets_requirements(review_body, requirements) {
+            if review_body.thoroughness < requirements.thoroughness {
+                Self::ThoroughnessTooLow(review_body.thoroughness)
+            } else {
+                Self::UnderstandingTooLow(review_body.understanding)
             }
-        } else if review.rating <= Rating::Negative {
-            negative_count += 1;
+        } else if trust_level < TrustLevel::from(requirements.trust_level) {
+            Self::TrustTooLow(trust_level)
+        } else {
+            Self::Counted
         }
     }
+}
 
-    if negative_count > 0 {
-        VerificationStatus::Negative
-    } else if trust_count >= requirements.redundancy {
-        VerificationStatus::Verified
-    } else {
-        VerificationStatus::Insufficient
+impl fmt::Display for ReviewVerdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Counted => f.write_str("counted"),
+            Self::Negative => f.write_str("negative review"),
+            Self::DigestMismatch => f.write_str("reviewed files differ (digest mismatch)"),
+            Self::PatchOnly => f.write_str("review of a patch only"),
+            Self::SubtreeOnly => f.write_str("review of a subtree only"),
+            Self::UntrustedReviewer => f.write_str("reviewer not trusted"),
+            Self::Overridden => f.write_str("overridden"),
+            Self::Withdrawn => f.write_str("withdrawn"),
+            Self::ThoroughnessTooLow(level) => write!(f, "thoroughness too low ({level})"),
+            Self::UnderstandingTooLow(level) => write!(f, "understanding too low ({level})"),
+            Self::TrustTooLow(level) => write!(f, "reviewer trusted too little ({level})"),
+        }
     }
 }
 
+/// All reviews of a package version, and why each does or doesn't count
+/// towards verifying `digest`
+///
+/// Includes reviews by untrusted Ids, overridden reviews, and reviews of the
+/// same version with a different digest, which `verify_package_digest` skips.
+pub fn explain_package_verification(
+    digest: &Digest,
+    package: &crev_data::proof::PackageVersionId,
+    trust_set: &crev_wot::TrustSet,
+    requirements: &VerificationRequirements,
+    db: &crev_wot::ProofDB,
+) -> Vec<(review::Package, ReviewVerdict)> {
+    let other_digests = db
+        .get_pkg_reviews_for_version(&package.id.source, &package.id.name, &package.version)
+        .filter(|review| review.package.digest != digest.as_slice())
+        .cloned();
+    db.get_package_reviews_by_digest(digest)
+        .chain(other_digests)
+        .map(|review| {
+            let verdict = ReviewVerdict::new(&review, digest, trust_set, requirements);
+            (review, verdict)
+        })
+        .collect()
+}
+
+/// Ids whose reviews of `digest` count towards `requirements.redundancy`
+/// in `verify_package_digest`
+pub fn verifying_reviewers(
+    digest: &Digest,
+    trust_set: &crev_wot::TrustSet,
+    requirements: &VerificationRequirements,
+    db: &crev_wot::ProofDB,
+) -> Vec<Id> {
+    trusted_reviews_by_digest(digest, trust_set, db)
+        .into_iter()
+        .filter(|(reviewer, review)| {
+            meets_requirements(review.review_possibly_none(), requirements)
+                && TrustLevel::from(requirements.trust_level)
+                    <= trust_set.get_effective_trust_level(reviewer)
+        })
+        .map(|(reviewer, _)| reviewer)
+        .collect()
+}
+
+/// Reviews that make `verify_package_digest` return `VerificationStatus::Negative`
+///
+/// Sorted by date, oldest first.
+pub fn negative_package_reviews(
+    digest: &Digest,
+    trust_set: &crev_wot::TrustSet,
+    db: &crev_wot::ProofDB,
+) -> Vec<review::Package> {
+    let mut reviews: Vec<_> = trusted_reviews_by_digest(digest, trust_set, db)
+        .into_values()
+        .filter(|review| review.review_possibly_none().rating <= Rating::Negative)
+        .collect();
+    reviews.sort_by_key(|review| review.date_utc());
+    reviews
+}
+
 /// Warnings gathered during operation, errors downgraded to warnings.
 #[derive(Debug, thiserror::Error)]
 pub enum Warning {
@@ -387,6 +709,9 @@ pub enum Warning {
     FetchError(String, #[source] Error, PathBuf),
 }
 
+/// See `Warning::log_as_json`
+static WARNINGS_AS_JSON: AtomicBool = AtomicBool::new(false);
+
 impl Warning {
     #[must_use]
     pub fn auto_log() -> LogOnDrop {
@@ -397,7 +722,20 @@ impl Warning {
         warnings.iter().for_each(|w| w.log());
     }
 
+    /// Make `log` print warnings to stderr as JSON, one per line, instead of log messages
+    ///
+    /// For CI to collect them. See `WarningRecord` for the format.
+    pub fn log_as_json(enabled: bool) {
+        WARNINGS_AS_JSON.store(enabled, Ordering::Relaxed);
+    }
+
     pub fn log(&self) {
+        if WARNINGS_AS_JSON.load(Ordering::Relaxed) {
+            if let Ok(json) = serde_json::to_string(self) {
+                eprintln!("{json}");
+                return;
+            }
+        }
         warn!("{}", self);
         let mut s = self.source();
         while let Some(w) = s {
@@ -405,6 +743,73 @@ impl Warning {
             s = w.source();
         }
     }
+
+    /// The error this warning was downgraded from, if any
+    fn error(&self) -> Option<&Error> {
+        match self {
+            Warning::Error(e) | Warning::NoRepoUrlAtPath(_, e) | Warning::FetchError(_, e, _) => {
+                Some(e)
+            }
+            Warning::IdUrlNotKnonw(_) | Warning::GitPushUrl(_) => None,
+        }
+    }
+}
+
+/// How a `Warning` is serialized
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct WarningRecord<'a> {
+    /// Always `warning`, to tell these lines apart from other JSON on stderr
+    pub level: &'static str,
+    /// Stable code, see [`Warning::code`]
+    pub code: &'static str,
+    pub message: String,
+    /// Code of the error it was downgraded from, see [`ErrorCode`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<&'a Path>,
+    /// Messages of the chain of underlying errors
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub causes: Vec<String>,
+}
+
+impl<'a> From<&'a Warning> for WarningRecord<'a> {
+    fn from(warning: &'a Warning) -> Self {
+        let mut causes = vec![];
+        let mut source = warning.source();
+        while let Some(e) = source {
+            causes.push(e.to_string());
+            source = e.source();
+        }
+        let (url, id, path) = match warning {
+            Warning::Error(_) => (None, None, None),
+            Warning::NoRepoUrlAtPath(path, _) => (None, None, Some(path.as_path())),
+            Warning::IdUrlNotKnonw(id) => (None, Some(id.to_string()), None),
+            Warning::GitPushUrl(url) => (Some(url.as_str()), None, None),
+            Warning::FetchError(url, _, path) => (Some(url.as_str()), None, Some(path.as_path())),
+        };
+        WarningRecord {
+            level: "warning",
+            code: warning.code(),
+            message: warning.to_string(),
+            error_code: warning.error().map(ErrorCode::code),
+            url,
+            id,
+            path,
+            causes,
+        }
+    }
+}
+
+impl Serialize for Warning {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        WarningRecord::from(self).serialize(serializer)
+    }
 }
 
 pub struct LogOnDrop(pub Vec<Warning>);
@@ -495,10 +900,86 @@ pub fn dir_verify(
 }
 
 /// Scan dir and hash everything in it, to get a unique identifier of the package's source code
+///
+/// Paths on `ignore_list` are skipped, see `util::is_ignored_path`.
 pub fn get_dir_digest(path: &Path, ignore_list: &fnv::FnvHashSet<PathBuf>) -> Result<Digest> {
     Ok(Digest::from_bytes(&util::get_recursive_digest_for_dir(path, ignore_list)?).unwrap())
 }
 
+/// Like `get_dir_digest`, with the hash function of `digest_type`
+pub fn get_dir_digest_with(
+    path: &Path,
+    ignore_list: &fnv::FnvHashSet<PathBuf>,
+    digest_type: DigestType,
+) -> Result<Digest> {
+    let digest = util::get_recursive_digest_for_dir_with(
+        digest_type,
+        path,
+        ignore_list,
+        &crev_common::CancellationToken::new(),
+    )?;
+    Ok(Digest::from_bytes(&digest).unwrap())
+}
+
+/// Like `get_dir_digest`, but returns `Error::Cancelled` as soon as `cancel` is cancelled
+pub fn get_dir_digest_cancellable(
+    path: &Path,
+    ignore_list: &fnv::FnvHashSet<PathBuf>,
+    cancel: &crev_common::CancellationToken,
+) -> Result<Digest> {
+    let digest = util::get_recursive_digest_for_dir_cancellable(path, ignore_list, cancel)?;
+    cancel.check()?;
+    Ok(Digest::from_bytes(&digest).unwrap())
+}
+
+/// Digest of the `subtree` directory of the package at `package_root`, without `exclude`
+///
+/// `exclude` is relative to the subtree. Entries of `ignore_list` apply as they
+/// would to the whole package, so the digest is computed the same way the
+/// package digest is, for the subtree alone.
+pub fn get_subtree_digest(
+    package_root: &Path,
+    subtree: &str,
+    exclude: &[String],
+    ignore_list: &fnv::FnvHashSet<PathBuf>,
+) -> Result<Digest> {
+    let root = package_root.join(subtree);
+    if !root.is_dir() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("{} is not a directory", root.display()),
+        )
+        .into());
+    }
+    let mut subtree_ignore_list: fnv::FnvHashSet<PathBuf> = ignore_list
+        .iter()
+        .filter_map(|entry| {
+            if entry.to_str().is_some_and(|entry| entry.starts_with('*')) {
+                Some(entry.clone())
+            } else {
+                entry.strip_prefix(subtree).ok().map(Path::to_owned)
+            }
+        })
+        .collect();
+    subtree_ignore_list.extend(exclude.iter().map(PathBuf::from));
+    get_dir_digest(&root, &subtree_ignore_list)
+}
+
+/// Does the package at `package_root` contain the subtree a review is of
+///
+/// It can, even if its package digest is different from the one in the review.
+pub fn package_contains_subtree(
+    package_root: &Path,
+    subtree: &review::Subtree,
+    ignore_list: &fnv::FnvHashSet<PathBuf>,
+) -> Result<bool> {
+    if !package_root.join(&subtree.path).is_dir() {
+        return Ok(false);
+    }
+    let digest = get_subtree_digest(package_root, &subtree.path, &subtree.exclude, ignore_list)?;
+    Ok(digest.as_slice() == subtree.digest.as_slice())
+}
+
 /// See `get_dir_digest`
 pub fn get_recursive_digest_for_git_dir(
     root_path: &Path,
@@ -513,7 +994,7 @@ pub fn get_recursive_digest_for_git_dir(
     status_opts.include_untracked(false);
     for entry in git_repo.statuses(Some(&mut status_opts))?.iter() {
         let entry_path = PathBuf::from(entry.path().ok_or(Error::GitEntryWithoutAPath)?);
-        if ignore_list.contains(&entry_path) {
+        if util::is_ignored_path(&entry_path, ignore_list) {
             continue;
         };
 
@@ -1,17 +1,33 @@
 use crate::{
     activity::{LatestReviewActivity, ReviewActivity},
+    embargo::{EmbargoedProof, ProofEmbargo},
+    encryption,
+    fetch_state::FetchAllState,
+    hooks::{self, HookCmds, Hooks, ProofInserted, RepoFetched},
     id::{self, LockedId, PassphraseFn},
+    imported::ImportedProofs,
+    passphrase,
+    pinned_inputs::PinnedInputs,
+    proof_cache::ProofCache,
+    proof_index::{self, ProofIndex, SignedManifest},
+    review_template::{ReviewTemplate, REVIEW_TEMPLATE_FILE_NAME},
+    staging::ProofStaging,
+    usage::{UsageEvent, UsageStats},
     util::{self, git::is_unrecoverable},
     Error, ProofStore, Result, Warning,
 };
+use chrono::Datelike;
 use crev_common::{
     self, sanitize_name_for_fs, sanitize_url_for_fs,
     serde::{as_base64, from_base64},
+    CancellationToken,
 };
 use crev_data::{
     id::UnlockedId,
-    proof::{self, trust::TrustLevel, OverrideItem},
-    Id, PublicId, RegistrySource, Url,
+    proof::{
+        self, trust::TrustLevel, CommonOps, Content, ContentExt, ContentWithDraft, OverrideItem,
+    },
+    DigestType, Id, PublicId, RegistrySource, Url,
 };
 use default::default;
 use directories::ProjectDirs;
@@ -19,17 +35,34 @@ use log::{debug, error, info, warn};
 use resiter::{FilterMap, Map};
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::HashSet,
-    ffi::OsString,
+    collections::{BTreeMap, HashSet},
+    ffi::{OsStr, OsString},
     fs,
-    io::{BufRead, BufReader, Write},
+    io::{BufRead, Write},
     path::{Path, PathBuf},
     str::FromStr,
-    sync::{Arc, Mutex},
+    sync::{Arc, Mutex, RwLock},
+    time::Duration,
 };
 
 const CURRENT_USER_CONFIG_SERIALIZATION_VERSION: i64 = -1;
 
+/// Env var with a directory to keep config, data and cache in, instead of the user's home
+///
+/// A relative path is relative to the directory of the running executable,
+/// not the current one, so a whole setup (eg. on a USB stick, or checked in
+/// next to the project's tools) keeps working wherever it's moved.
+pub const PORTABLE_ROOT_ENV: &str = "CREV_PORTABLE_ROOT";
+
+/// `root` of the portable mode, made absolute against `exe_dir`
+pub(crate) fn resolve_portable_root(root: &Path, exe_dir: &Path) -> PathBuf {
+    if root.is_absolute() {
+        root.to_owned()
+    } else {
+        exe_dir.join(root)
+    }
+}
+
 /// Random 32 bytes
 fn generete_salt() -> Vec<u8> {
     crev_common::rand::random_vec(32)
@@ -73,6 +106,138 @@ pub struct UserConfig {
         default = "Option::default"
     )]
     pub open_cmd: Option<String>,
+
+    /// Replaces `util::DEFAULT_DIGEST_IGNORE` when digesting local copies of packages
+    #[serde(
+        rename = "digest-ignore",
+        skip_serializing_if = "Option::is_none",
+        default = "Option::default"
+    )]
+    pub digest_ignore: Option<Vec<String>>,
+
+    /// Hash function of the digests of new reviews, eg. `sha256` for other tools to check them
+    #[serde(
+        rename = "digest-type",
+        skip_serializing_if = "crev_common::is_equal_default",
+        default
+    )]
+    pub digest_type: DigestType,
+
+    /// Shell command printing the passphrase of the current Id (eg. `pass show crev`)
+    #[serde(
+        rename = "passphrase-cmd",
+        skip_serializing_if = "is_none_or_empty",
+        default = "Option::default"
+    )]
+    pub passphrase_cmd: Option<String>,
+
+    /// Keep local-only usage counters, see [`crate::usage`]
+    #[serde(
+        rename = "usage-stats",
+        skip_serializing_if = "std::ops::Not::not",
+        default
+    )]
+    pub usage_stats: bool,
+
+    /// Clone the upstream repositories of crates under review, for their release notes
+    #[serde(
+        rename = "fetch-release-notes",
+        skip_serializing_if = "std::ops::Not::not",
+        default
+    )]
+    pub fetch_release_notes: bool,
+
+    /// Sign commits of your proof repo with a GPG or SSH key
+    #[serde(
+        rename = "commit-signing",
+        skip_serializing_if = "Option::is_none",
+        default = "Option::default"
+    )]
+    pub commit_signing: Option<util::git::CommitSigning>,
+
+    /// How proofs are spread in your proof repo
+    #[serde(
+        rename = "proof-repo-layout",
+        skip_serializing_if = "crev_common::is_equal_default",
+        default
+    )]
+    pub proof_repo_layout: ProofRepoLayout,
+
+    /// Limits on proof repos of other people
+    #[serde(
+        rename = "proof-limits",
+        skip_serializing_if = "crev_common::is_equal_default",
+        default
+    )]
+    pub proof_limits: ProofLimits,
+
+    /// Shell commands to run around proof creation
+    #[serde(rename = "hooks", skip_serializing_if = "HookCmds::is_empty", default)]
+    pub hooks: HookCmds,
+}
+
+/// How to keep the working tree of a proof repo small as proofs pile up
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProofRepoLayout {
+    /// All proofs in one branch
+    #[default]
+    Single,
+    /// Proofs under a directory named after the year they were signed in, eg. `2024/`
+    YearlyDirectories,
+    /// New proofs go to the branch of the current year, eg. `crev-proofs-2024`
+    ///
+    /// Only that branch is checked out; the others are read from git.
+    YearlyBranches,
+}
+
+/// Limits on proof repos fetched from other people
+///
+/// So a malicious (or just broken) proof repo can't exhaust memory or disk.
+/// Repos over the limits are partially loaded, or not fetched at all,
+/// with a warning.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct ProofLimits {
+    /// Proofs loaded from one repo at most
+    pub max_proofs_per_repo: usize,
+    /// Proof files bigger than that, in bytes, are skipped
+    pub max_file_size: u64,
+    /// Repos bigger than that, in bytes, are not fetched
+    pub max_repo_size: u64,
+    /// URLs of big repos you trust, which the limits don't apply to
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unlimited_urls: Vec<String>,
+}
+
+impl Default for ProofLimits {
+    fn default() -> Self {
+        Self {
+            max_proofs_per_repo: 200_000,
+            max_file_size: 10 << 20,
+            max_repo_size: 1 << 30,
+            unlimited_urls: vec![],
+        }
+    }
+}
+
+impl ProofLimits {
+    #[must_use]
+    pub fn is_unlimited(&self, url: &str) -> bool {
+        self.unlimited_urls.iter().any(|unlimited| unlimited == url)
+    }
+
+    /// `max_file_size`, unless `url` is unlimited
+    #[must_use]
+    pub fn max_file_size_for(&self, url: &str) -> Option<u64> {
+        (!self.is_unlimited(url)).then_some(self.max_file_size)
+    }
+
+    /// `max_repo_size`, unless `url` is unlimited
+    #[must_use]
+    pub fn max_repo_size_for(&self, url: &str) -> Option<u64> {
+        (!self.is_unlimited(url)).then_some(self.max_repo_size)
+    }
 }
 
 impl Default for UserConfig {
@@ -82,6 +247,15 @@ impl Default for UserConfig {
             current_id: None,
             host_salt: generete_salt(),
             open_cmd: None,
+            digest_ignore: None,
+            digest_type: DigestType::default(),
+            passphrase_cmd: None,
+            usage_stats: false,
+            fetch_release_notes: false,
+            commit_signing: None,
+            proof_repo_layout: ProofRepoLayout::Single,
+            proof_limits: ProofLimits::default(),
+            hooks: HookCmds::default(),
         }
     }
 }
@@ -95,6 +269,28 @@ impl UserConfig {
     pub fn get_current_userid_opt(&self) -> Option<&Id> {
         self.current_id.as_ref()
     }
+
+    /// Paths to leave out of digests of local copies, as configured or the defaults
+    #[must_use]
+    pub fn digest_ignore_list(&self) -> Vec<String> {
+        match &self.digest_ignore {
+            Some(list) => list.clone(),
+            None => util::DEFAULT_DIGEST_IGNORE
+                .iter()
+                .map(|s| (*s).to_string())
+                .collect(),
+        }
+    }
+}
+
+/// A proof of the current Id in its proof repo, see `Local::last_own_proof`
+#[derive(Debug, Clone)]
+pub struct OwnProof {
+    pub proof: proof::Proof,
+    /// File the proof is in, relative to the proof repo
+    pub rel_path: PathBuf,
+    /// In the upstream branch already
+    pub published: bool,
 }
 
 /// Local config stored in `~/.config/crev`
@@ -106,42 +302,283 @@ pub struct Local {
     cache_path: PathBuf,
     cur_url: Mutex<Option<Url>>,
     user_config: Mutex<Option<UserConfig>>,
+    cancellation: CancellationToken,
+    hooks: Hooks,
+    portable_root: Option<PathBuf>,
+    fetch_max_age: Option<Duration>,
 }
 
 impl Local {
     /// Load config from the environment
     #[allow(clippy::new_ret_no_self)]
     fn new() -> Result<Self> {
-        let proj_dir = match std::env::var_os("CARGO_CREV_ROOT_DIR_OVERRIDE") {
-            None => ProjectDirs::from("", "", "crev"),
-            Some(path) => ProjectDirs::from_path(path.into()),
-        }
-        .ok_or(Error::NoHomeDirectory)?;
-        let config_path = proj_dir.config_dir().into();
-        let data_path = proj_dir.data_dir().into();
-        let cache_path = proj_dir.cache_dir().into();
+        let portable_root = match std::env::var_os(PORTABLE_ROOT_ENV) {
+            Some(root) if !root.is_empty() => {
+                let exe = std::env::current_exe()?;
+                let exe_dir = exe.parent().ok_or(Error::NoHomeDirectory)?;
+                Some(resolve_portable_root(Path::new(&root), exe_dir))
+            }
+            _ => None,
+        };
+        let (config_path, data_path, cache_path) = if let Some(root) = &portable_root {
+            (root.join("config"), root.join("data"), root.join("cache"))
+        } else {
+            let proj_dir = match std::env::var_os("CARGO_CREV_ROOT_DIR_OVERRIDE") {
+                None => ProjectDirs::from("", "", "crev"),
+                Some(path) => ProjectDirs::from_path(path.into()),
+            }
+            .ok_or(Error::NoHomeDirectory)?;
+            (
+                proj_dir.config_dir().into(),
+                proj_dir.data_dir().into(),
+                proj_dir.cache_dir().into(),
+            )
+        };
         Ok(Self {
             config_path,
             data_path,
             cache_path,
+            portable_root,
             cur_url: Mutex::new(None),
             user_config: Mutex::new(None),
+            cancellation: CancellationToken::new(),
+            hooks: Hooks::with_default_logging(),
+            fetch_max_age: None,
         })
     }
 
+    /// Use `token` to interrupt long-running operations (fetching, etc.)
+    ///
+    /// Interrupted operations clean up after themselves and return
+    /// `Error::Cancelled`.
+    pub fn set_cancellation_token(&mut self, token: CancellationToken) {
+        self.cancellation = token;
+    }
+
+    pub fn cancellation_token(&self) -> &CancellationToken {
+        &self.cancellation
+    }
+
+    /// Don't fetch proof repos that were fetched less than `max_age` ago
+    pub fn set_fetch_max_age(&mut self, max_age: Option<Duration>) {
+        self.fetch_max_age = max_age;
+    }
+
+    /// Call `f` after every proof stored in the local proof repository
+    pub fn on_proof_inserted(&self, f: impl Fn(&ProofInserted<'_>) + Send + Sync + 'static) {
+        self.hooks.add_proof_inserted(Box::new(f));
+    }
+
+    /// Call `f` after every proof repository fetched and imported
+    pub fn on_repo_fetched(&self, f: impl Fn(&RepoFetched<'_>) + Send + Sync + 'static) {
+        self.hooks.add_repo_fetched(Box::new(f));
+    }
+
+    /// Remove all callbacks, including the default one logging fetch results
+    pub fn clear_hooks(&self) {
+        self.hooks.clear();
+    }
+
     /// Load all reviews and trust proofs for the current user
     pub fn load_db(&self) -> Result<crev_wot::ProofDB> {
+        let limits = self.proof_limits();
         let mut db = crev_wot::ProofDB::new();
-        for local_id in self.get_current_user_public_ids()? {
-            db.record_trusted_url_from_own_id(&local_id);
+        db.limit_proofs_per_url(limits.max_proofs_per_repo, limits.unlimited_urls.clone());
+        let local_ids = self.get_current_user_public_ids()?;
+        for local_id in &local_ids {
+            db.record_trusted_url_from_own_id(local_id);
         }
-        db.import_from_iter(
-            self.all_local_proofs()
-                .map(move |p| (p, crev_wot::FetchSource::LocalUser)),
+        let cache = self.proof_cache();
+        // the proof iterators check signatures
+        db.import_verified_from_iter_with_origin(
+            self.all_local_proofs_with_files(cache.clone())
+                .map(|(p, file)| {
+                    let origin = crev_wot::ProofOrigin {
+                        fetched_from: crev_wot::FetchSource::LocalUser,
+                        file,
+                    };
+                    (p, origin)
+                }),
         );
-        db.import_from_iter(proofs_iter_for_remotes_checkouts(
+        db.import_verified_from_iter_with_origin(proofs_iter_for_remotes_checkouts(
             self.cache_remotes_path(),
+            &limits,
+            cache.clone(),
         )?);
+        let imported_proofs_path = self.imported_proofs_path();
+        if imported_proofs_path.exists() {
+            db.import_verified_from_iter_with_origin(
+                proof_files_iter_for_path(imported_proofs_path, None, cache.clone()).map(
+                    |(p, file)| {
+                        let origin = crev_wot::ProofOrigin {
+                            fetched_from: crev_wot::FetchSource::LocalUser,
+                            file: Some(file),
+                        };
+                        (p, origin)
+                    },
+                ),
+            );
+        }
+        let local_waivers_path = self.local_waivers_path();
+        if local_waivers_path.exists() {
+            db.import_verified_from_iter_with_origin(
+                proof_files_iter_for_path(local_waivers_path, None, cache.clone()).map(
+                    |(p, file)| {
+                        let origin = crev_wot::ProofOrigin {
+                            fetched_from: crev_wot::FetchSource::LocalUser,
+                            file: Some(file),
+                        };
+                        (p, origin)
+                    },
+                ),
+            );
+        }
+        if let Some(cache) = cache {
+            cache.prune_unused();
+        }
+        self.import_decrypted_proofs(&mut db, &local_ids);
+        Ok(db)
+    }
+
+    /// Import the proofs encrypted to the user's Ids, see [`crate::encryption`]
+    ///
+    /// Only Ids that can be unlocked without asking for the passphrase are used.
+    fn import_decrypted_proofs(&self, db: &mut crev_wot::ProofDB, local_ids: &[PublicId]) {
+        for public_id in local_ids {
+            if !db
+                .get_encrypted_proofs()
+                .any(|encrypted| encrypted.recipient(&public_id.id).is_some())
+            {
+                continue;
+            }
+            let Some(id) = self.read_unlocked_id_non_interactive(&public_id.id) else {
+                debug!(
+                    "Can't decrypt proofs for {} without its passphrase",
+                    public_id.id
+                );
+                continue;
+            };
+            db.import_decrypted(|encrypted| {
+                encryption::decrypt_proof(encrypted, &id)
+                    .map_err(|e| warn!("Encrypted proof by {}: {}", encrypted.from().id, e))
+                    .ok()
+                    .flatten()
+            });
+        }
+    }
+
+    /// Cache of proofs read by `load_db`, see [`crate::proof_cache`]
+    ///
+    /// `None` without the `proof-cache` feature, or if the cache can't be
+    /// opened, eg. because another `crev` process uses it.
+    #[cfg(feature = "proof-cache")]
+    fn proof_cache(&self) -> Option<ProofCache> {
+        ProofCache::open(&self.cache_path.join("proof-db"), self.portable_root())
+            .map_err(|e| debug!("Not using the proof cache: {e}"))
+            .ok()
+    }
+
+    #[cfg(not(feature = "proof-cache"))]
+    #[allow(clippy::unused_self)]
+    fn proof_cache(&self) -> Option<ProofCache> {
+        None
+    }
+
+    /// Limits on proof repos of others, as configured
+    #[must_use]
+    pub fn proof_limits(&self) -> ProofLimits {
+        self.user_config
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|config| config.proof_limits.clone())
+            .unwrap_or_default()
+    }
+
+    /// Record what `load_db_pinned` should load, see [`PinnedInputs`]
+    ///
+    /// Proof repos of others are pinned at the commits they were last fetched
+    /// at, and your own ones at their last commit, so that the pin can be used
+    /// on any machine that fetched them. Your proofs have to be committed, and
+    /// to have a URL. Proofs imported from files aren't pinned.
+    pub fn pin_inputs(&self, for_id: Option<&str>) -> Result<PinnedInputs> {
+        let mut proof_repos = BTreeMap::new();
+        for (path, url) in remotes_checkouts_iter(self.cache_remotes_path())? {
+            let repo = git2::Repository::open(&path)?;
+            proof_repos.insert(url.url, pinned_commits(&repo)?);
+        }
+        for entry in fs::read_dir(self.user_proofs_path()).into_iter().flatten() {
+            let path = entry?.path();
+            let Ok(repo) = git2::Repository::open(&path) else {
+                continue;
+            };
+            let has_proofs = proofs_iter_for_path(path.clone(), None).next().is_some();
+            let Some(url) = repo
+                .find_remote("origin")
+                .ok()
+                .and_then(|origin| origin.url().map(ToOwned::to_owned))
+            else {
+                if has_proofs {
+                    return Err(Error::CantPinInputs(format!(
+                        "your proofs in {} have no URL to be fetched from",
+                        path.display()
+                    )));
+                }
+                continue;
+            };
+            let mut status_opts = git2::StatusOptions::new();
+            status_opts
+                .include_untracked(true)
+                .recurse_untracked_dirs(true);
+            if !repo.statuses(Some(&mut status_opts))?.is_empty() {
+                return Err(Error::CantPinInputs(format!(
+                    "your proofs in {} have uncommitted changes",
+                    path.display()
+                )));
+            }
+            if repo.head().is_ok() {
+                proof_repos.insert(Url::new_git(url).url, pinned_commits(&repo)?);
+            }
+        }
+        Ok(PinnedInputs {
+            for_id: self.get_for_id_from_str_opt(for_id)?,
+            proof_repos,
+            crates_io: BTreeMap::new(),
+        })
+    }
+
+    /// Like `load_db`, but with exactly the proofs recorded in `pinned`
+    ///
+    /// Proof repos are read at their pinned commits, which have to be fetched
+    /// already, even if they were fetched again since. Your own proofs are
+    /// read from your proof repos, or the fetched copies of them, alike.
+    /// Repos that weren't pinned are left out.
+    pub fn load_db_pinned(&self, pinned: &PinnedInputs) -> Result<crev_wot::ProofDB> {
+        let limits = self.proof_limits();
+        let mut db = crev_wot::ProofDB::new();
+        db.limit_proofs_per_url(limits.max_proofs_per_repo, limits.unlimited_urls.clone());
+        for (url, commits) in &pinned.proof_repos {
+            self.cancellation.check()?;
+            let git_url = Url::new_git(url.as_str());
+            let dirs = [
+                self.get_remote_git_cache_path(url)?,
+                self.get_proofs_dir_path_for_url(&git_url)?,
+            ];
+            let max_file_size = limits.max_file_size_for(url);
+            let fetch_source = crev_wot::FetchSource::Url(Arc::new(git_url));
+            for commit in commits {
+                let proofs = dirs
+                    .iter()
+                    .find_map(|dir| proofs_at_git_commit(dir, commit, max_file_size).ok())
+                    .ok_or_else(|| {
+                        Error::PinnedInputsMismatch(format!(
+                            "commit {commit} of {url} is not available, fetch it first"
+                        ))
+                    })?;
+                let fetch_source = fetch_source.clone();
+                db.import_from_iter(proofs.into_iter().map(move |p| (p, fetch_source.clone())));
+            }
+        }
         Ok(db)
     }
 
@@ -160,6 +597,11 @@ impl Local {
         &self.cache_path
     }
 
+    /// The directory holding all of the above, in portable mode (see [`PORTABLE_ROOT_ENV`])
+    pub fn portable_root(&self) -> Option<&Path> {
+        self.portable_root.as_deref()
+    }
+
     /// Fails if it doesn't exist. See `auto_create_or_open()`
     pub fn auto_open() -> Result<Self> {
         let repo = Self::new()?;
@@ -321,6 +763,13 @@ impl Local {
         self.cache_path.join("remotes")
     }
 
+    /// Where clones of proof repos are made, before being moved to `cache_remotes_path()`
+    ///
+    /// Anything left here was interrupted, and is safe to delete.
+    fn cache_partial_clones_path(&self) -> PathBuf {
+        self.cache_path.join("remotes-partial")
+    }
+
     /// Cache where metadata about in-progress reviews (etc) is stored
     fn cache_activity_path(&self) -> PathBuf {
         self.cache_path.join("activity")
@@ -374,6 +823,80 @@ impl Local {
             .with_extension("yaml")
     }
 
+    /// Yaml file path for progress of `fetch_all`
+    fn cache_fetch_all_state_path(&self) -> PathBuf {
+        self.cache_path.join("fetch_all_state.yaml")
+    }
+
+    /// Progress of the most recent `fetch_all`, if any was recorded
+    pub fn read_fetch_all_state(&self) -> Result<Option<FetchAllState>> {
+        let path = self.cache_fetch_all_state_path();
+
+        if path.exists() {
+            Ok(Some(
+                crev_common::read_from_yaml_file(&path)
+                    .map_err(|e| Error::FetchState(Box::new(e)))?,
+            ))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Save progress of `fetch_all` to disk
+    fn store_fetch_all_state(&self, state: &FetchAllState) -> Result<()> {
+        crev_common::save_to_yaml_file(&self.cache_fetch_all_state_path(), state)
+            .map_err(|e| Error::FetchState(Box::new(e)))
+    }
+
+    /// Update and persist `fetch_all` progress, if it's being tracked
+    fn record_fetch_progress(
+        &self,
+        state: Option<&mut FetchAllState>,
+        url: &str,
+        res: std::result::Result<(), String>,
+    ) {
+        if let Some(state) = state {
+            if res.is_err() && self.cancellation.is_cancelled() {
+                // will be fetched again when the run is resumed
+                return;
+            }
+            match res {
+                Ok(()) => state.record_success(url),
+                Err(reason) => state.record_failure(url, reason),
+            }
+            if let Err(e) = self.store_fetch_all_state(state) {
+                warn!("Failed to save fetch progress: {}", e);
+            }
+        }
+    }
+
+    /// Yaml file path for usage counters
+    fn usage_stats_path(&self) -> PathBuf {
+        self.data_path.join("usage_stats.yaml")
+    }
+
+    /// Usage counters recorded so far (empty if never enabled)
+    pub fn read_usage_stats(&self) -> Result<UsageStats> {
+        let path = self.usage_stats_path();
+
+        if path.exists() {
+            crev_common::read_from_yaml_file(&path).map_err(|e| Error::UsageStats(Box::new(e)))
+        } else {
+            Ok(UsageStats::default())
+        }
+    }
+
+    /// Count `event`, if enabled with `usage-stats` in the user config
+    pub fn record_usage(&self, event: UsageEvent<'_>) -> Result<()> {
+        if !self.load_user_config()?.usage_stats {
+            return Ok(());
+        }
+        let mut stats = self.read_usage_stats()?;
+        stats.record(event, &crev_common::now());
+        crev_common::save_to_yaml_file(&self.usage_stats_path(), &stats)
+            .map_err(|e| Error::UsageStats(Box::new(e)))
+    }
+
     fn cache_latest_review_activity_path(&self) -> PathBuf {
         self.cache_activity_path().join("latest_review.yaml")
     }
@@ -498,15 +1021,46 @@ impl Local {
             .ok_or(Error::CurrentIDNotSet)
     }
 
+    /// Passphrase from `passphrase-cmd` in the config, or from the OS keyring
+    fn read_stored_passphrase(&self, id: &Id) -> Result<Option<String>> {
+        if let Some(cmd) = self.load_user_config()?.passphrase_cmd {
+            return Ok(Some(passphrase::passphrase_from_cmd(cmd.as_ref())?));
+        }
+        passphrase::keyring_get(id)
+    }
+
+    /// Unlocks the Id only if that doesn't need any input:
+    /// it has no passphrase, or a stored one
+    fn read_unlocked_id_non_interactive(&self, id: &Id) -> Option<UnlockedId> {
+        let locked = self.read_locked_id(id).ok()?;
+        let passphrase = if locked.has_no_passphrase() {
+            String::new()
+        } else {
+            self.read_stored_passphrase(id).ok()??
+        };
+        locked.to_unlocked(&passphrase).ok()
+    }
+
     /// Just reads the yaml file and unlocks it, doesn't change anything
     ///
-    /// Asks for passphrase up to 5 times
+    /// Tries the stored passphrase first (see [`passphrase`]),
+    /// then asks for passphrase up to 5 times
     pub fn read_unlocked_id(
         &self,
         id: &Id,
         passphrase_callback: PassphraseFn<'_>,
     ) -> Result<UnlockedId> {
         let locked = self.read_locked_id(id)?;
+        if !locked.has_no_passphrase() {
+            match self.read_stored_passphrase(id) {
+                Ok(Some(passphrase)) => match locked.to_unlocked(&passphrase) {
+                    Ok(o) => return Ok(o),
+                    Err(e) => warn!("Stored passphrase doesn't unlock {}: {}", id, e),
+                },
+                Ok(None) => {}
+                Err(e) => warn!("Couldn't read stored passphrase: {}", e),
+            }
+        }
         let mut i = 0;
         loop {
             let passphrase = if locked.has_no_passphrase() {
@@ -650,7 +1204,7 @@ impl Local {
 
         self.ensure_proofs_root_exists()?;
 
-        match util::git::clone(git_https_url, &proof_dir) {
+        match util::git::clone(git_https_url, &proof_dir, &self.cancellation, None) {
             Ok(repo) => {
                 debug!("{} cloned to {}", git_https_url, proof_dir.display());
                 repo.remote_set_url("origin", &push_url)?;
@@ -706,8 +1260,18 @@ impl Local {
     }
 
     // Get path relative to `get_proofs_dir_path` to store the `proof`
-    fn get_proof_rel_store_path(&self, proof: &proof::Proof, host_salt: &[u8]) -> PathBuf {
-        crate::proof::rel_store_path(proof, host_salt)
+    fn get_proof_rel_store_path(
+        &self,
+        proof: &proof::Proof,
+        host_salt: &[u8],
+        layout: ProofRepoLayout,
+    ) -> PathBuf {
+        let path = crate::proof::rel_store_path(proof, host_salt);
+        if layout == ProofRepoLayout::YearlyDirectories {
+            PathBuf::from(proof.date_utc().year().to_string()).join(path)
+        } else {
+            path
+        }
     }
 
     /// Proof repo URL associated with the current user Id
@@ -776,9 +1340,32 @@ impl Local {
         }
     }
 
+    /// `id` with its URL, if it can be verified (fetching it if needed)
+    fn public_id_with_url(&self, id: Id, db: &mut crev_wot::ProofDB) -> PublicId {
+        let url = match db.lookup_url(&id) {
+            crev_wot::UrlOfId::FromSelf(url) | crev_wot::UrlOfId::FromSelfVerified(url) => {
+                Some(url)
+            }
+            crev_wot::UrlOfId::FromOthers(maybe_url) => {
+                let maybe_url = maybe_url.url.clone();
+                // Ignore errors - if we weren't able to fetch it, that's OK.
+                let _ = self.fetch_url_into(&maybe_url, db);
+                db.lookup_url(&id).from_self()
+            }
+            crev_wot::UrlOfId::None => None,
+        };
+        if let Some(url) = url {
+            PublicId::new(id, url.clone())
+        } else {
+            PublicId::new_id_only(id)
+        }
+    }
+
     /// Creates new unsigned trust proof object, not edited
     ///
-    /// Ensures the proof contains valid URLs for Ids where possible.
+    /// Trusts `ids`, and the current members of `groups` (owner and name of
+    /// each, see [`proof::Group`]). Ensures the proof contains valid URLs for
+    /// Ids where possible.
     ///
     /// Currently ignores previous proofs
     ///
@@ -787,37 +1374,50 @@ impl Local {
         &self,
         from_id: &PublicId,
         ids: Vec<Id>,
+        groups: Vec<(Id, String)>,
         trust_level: TrustLevel,
         override_: Vec<OverrideItem>,
     ) -> Result<proof::trust::Trust> {
-        if ids.is_empty() {
+        if ids.is_empty() && groups.is_empty() {
             return Err(Error::NoIdsGiven);
         }
 
         let mut db = self.load_db()?;
-        let mut public_ids = Vec::with_capacity(ids.len());
+        let public_ids: Vec<_> = ids
+            .into_iter()
+            .map(|id| self.public_id_with_url(id, &mut db))
+            .collect();
+        let groups = groups
+            .into_iter()
+            .map(|(owner, name)| proof::GroupRef {
+                from: self.public_id_with_url(owner, &mut db),
+                name,
+            })
+            .collect();
 
-        for id in ids {
-            let url = match db.lookup_url(&id) {
-                crev_wot::UrlOfId::FromSelf(url) | crev_wot::UrlOfId::FromSelfVerified(url) => {
-                    Some(url)
-                }
-                crev_wot::UrlOfId::FromOthers(maybe_url) => {
-                    let maybe_url = maybe_url.url.clone();
-                    // Ignore errors - if we weren't able to fetch it, that's OK.
-                    let _ = self.fetch_url_into(&maybe_url, &mut db);
-                    db.lookup_url(&id).from_self()
-                }
-                crev_wot::UrlOfId::None => None,
-            };
-            if let Some(url) = url {
-                public_ids.push(PublicId::new(id, url.clone()));
-            } else {
-                public_ids.push(PublicId::new_id_only(id));
-            }
-        }
+        let mut trust = from_id.create_trust_proof(&public_ids, trust_level, override_)?;
+        trust.groups = groups;
+        Ok(trust)
+    }
 
-        Ok(from_id.create_trust_proof(&public_ids, trust_level, override_)?)
+    /// Creates new unsigned group proof, publishing `ids` as the group `name`
+    ///
+    /// Ensures the proof contains valid URLs for Ids where possible.
+    pub fn build_group_proof(
+        &self,
+        from_id: &PublicId,
+        name: String,
+        ids: Vec<Id>,
+        comment: String,
+    ) -> Result<proof::Group> {
+        let mut db = self.load_db()?;
+        let public_ids = ids
+            .into_iter()
+            .map(|id| self.public_id_with_url(id, &mut db))
+            .collect();
+        let group = from_id.create_group_proof(name, public_ids, comment)?;
+        group.validate_data().map_err(crev_data::Error::from)?;
+        Ok(group)
     }
 
     /// Fetch other people's proof repository from a git URL, into the current database on disk
@@ -835,7 +1435,10 @@ impl Local {
         let mut db = crev_wot::ProofDB::new();
         let url = Url::new_git(url);
         let fetch_source = self.get_fetch_source_for_url(url.clone())?;
-        db.import_from_iter(proofs_iter_for_path(dir).map(move |p| (p, fetch_source.clone())));
+        let max_file_size = self.proof_limits().max_file_size_for(&url.url);
+        db.import_from_iter(
+            proofs_iter_for_path(dir, max_file_size).map(move |p| (p, fetch_source.clone())),
+        );
         info!("Found proofs from:");
         for (id, count) in db.all_author_ids() {
             let tmp;
@@ -852,6 +1455,71 @@ impl Local {
         Ok(())
     }
 
+    /// Creates new unsigned epoch proof, for truncating the proof repository
+    ///
+    /// Its digest covers all proofs of `from_id` (except epoch proofs)
+    /// currently in the proof repository, which have to be moved to
+    /// the `archive_url` repository. See [`proof::Epoch`].
+    pub fn build_epoch_proof(&self, from_id: &PublicId, archive_url: &str) -> Result<proof::Epoch> {
+        let proofs: Vec<_> = proofs_iter_for_path(self.get_proofs_dir_path()?, None)
+            .filter(|proof| proof.author_id() == &from_id.id && proof.kind() != proof::Epoch::KIND)
+            .collect();
+        let digest = proof::archive_digest(&proofs);
+        Ok(from_id.create_epoch_proof(Url::new_git(archive_url), &digest)?)
+    }
+
+    /// Fetch archives of truncated proof repositories of authors known to `db`,
+    /// and import them into `db`
+    ///
+    /// Archives can contain older epoch proofs, so this repeats until there are
+    /// no new archives. Archives that don't match the digest of their epoch proof
+    /// are not imported, and removed from the cache.
+    pub fn fetch_epoch_archives(
+        &self,
+        db: &mut crev_wot::ProofDB,
+        warnings: &mut Vec<Warning>,
+    ) -> Result<()> {
+        let mut already_fetched = HashSet::new();
+        loop {
+            let epochs: Vec<proof::Epoch> = db
+                .all_epochs()
+                .filter(|epoch| {
+                    !already_fetched.contains(&(epoch.archive.url.clone(), epoch.digest.clone()))
+                })
+                .cloned()
+                .collect();
+            if epochs.is_empty() {
+                return Ok(());
+            }
+
+            for epoch in epochs {
+                self.cancellation.check()?;
+                let url = epoch.archive.url.clone();
+                already_fetched.insert((url.clone(), epoch.digest.clone()));
+
+                let dir = match self.fetch_remote_git(&url) {
+                    Ok(dir) => dir,
+                    Err(e) => {
+                        error!("Error: Failed to get dir for archive {}: {}", url, e);
+                        continue;
+                    }
+                };
+                let max_file_size = self.proof_limits().max_file_size_for(&url);
+                let archived: Vec<_> = proofs_iter_for_path(dir.clone(), max_file_size)
+                    .filter(|proof| {
+                        proof.author_id() == epoch.author_id() && proof.kind() != proof::Epoch::KIND
+                    })
+                    .collect();
+                if !epoch.archive_matches(&archived) {
+                    self.delete_remote_cache_directory(&dir);
+                    warnings.push(Warning::Error(Error::EpochArchiveDigestMismatch(url)));
+                    continue;
+                }
+                self.import_proof_dir_and_print_counts(&dir, &url, db)?;
+            }
+        }
+    }
+
     pub fn trust_set_for_id(
         &self,
         for_id: Option<&str>,
@@ -879,16 +1547,21 @@ impl Local {
         let mut already_fetched_urls = remotes_checkouts_iter(self.cache_remotes_path())?
             .map(|(_, url)| url.url)
             .collect();
-        let mut db = self.load_db()?;
+        let db = RwLock::new(self.load_db()?);
         let for_id = self.get_for_id_from_str(for_id)?;
 
         loop {
-            let trust_set = db.calculate_trust_set(&for_id, &trust_params);
+            self.cancellation.check()?;
+            let trust_set = db
+                .read()
+                .unwrap()
+                .calculate_trust_set(&for_id, &trust_params);
             let fetched_new = self.fetch_ids_not_fetched_yet(
                 trust_set.iter_trusted_ids().cloned(),
                 &mut already_fetched_ids,
                 &mut already_fetched_urls,
-                &mut db,
+                &db,
+                None,
                 warnings,
             );
             if !fetched_new {
@@ -904,19 +1577,40 @@ impl Local {
         trust_params: crate::TrustDistanceParams,
         for_id: Option<&str>,
         warnings: &mut Vec<Warning>,
+    ) -> Result<()> {
+        let db = RwLock::new(self.load_db()?);
+        self.fetch_trusted_into(trust_params, for_id, &db, warnings)
+    }
+
+    /// Like `fetch_trusted`, importing the fetched proofs to `db`
+    ///
+    /// Proofs of every repo are imported as soon as it's fetched, so other
+    /// threads can use `db` in the meantime, eg. to verify crates with
+    /// the proofs fetched so far. `db` is locked for writing only while a
+    /// repo is imported.
+    pub fn fetch_trusted_into(
+        &self,
+        trust_params: crate::TrustDistanceParams,
+        for_id: Option<&str>,
+        db: &RwLock<crev_wot::ProofDB>,
+        warnings: &mut Vec<Warning>,
     ) -> Result<()> {
         let mut already_fetched_ids = HashSet::new();
         let mut already_fetched_urls = HashSet::new();
-        let mut db = self.load_db()?;
         let for_id = self.get_for_id_from_str(for_id)?;
 
         loop {
-            let trust_set = db.calculate_trust_set(&for_id, &trust_params);
+            self.cancellation.check()?;
+            let trust_set = db
+                .read()
+                .unwrap()
+                .calculate_trust_set(&for_id, &trust_params);
             if !self.fetch_ids_not_fetched_yet(
                 trust_set.iter_trusted_ids().cloned(),
                 &mut already_fetched_ids,
                 &mut already_fetched_urls,
-                &mut db,
+                db,
+                None,
                 warnings,
             ) {
                 break;
@@ -929,17 +1623,21 @@ impl Local {
     fn fetch_all_ids_recursively(
         &self,
         mut already_fetched_urls: HashSet<String>,
-        db: &mut crev_wot::ProofDB,
+        db: &RwLock<crev_wot::ProofDB>,
+        mut state: Option<&mut FetchAllState>,
         warnings: &mut Vec<Warning>,
     ) -> Result<()> {
         let mut already_fetched_ids = HashSet::new();
 
         loop {
+            self.cancellation.check()?;
+            let ids = db.read().unwrap().all_known_ids();
             if !self.fetch_ids_not_fetched_yet(
-                db.all_known_ids().into_iter(),
+                ids.into_iter(),
                 &mut already_fetched_ids,
                 &mut already_fetched_urls,
                 db,
+                state.as_deref_mut(),
                 warnings,
             ) {
                 break;
@@ -949,12 +1647,15 @@ impl Local {
     }
 
     /// True if something was fetched
+    ///
+    /// If `state` is given, the result of every fetch is recorded in it.
     fn fetch_ids_not_fetched_yet(
         &self,
         ids: impl Iterator<Item = Id> + Send,
         already_fetched_ids: &mut HashSet<Id>,
         already_fetched_urls: &mut HashSet<String>,
-        db: &mut crev_wot::ProofDB,
+        db: &RwLock<crev_wot::ProofDB>,
+        mut state: Option<&mut FetchAllState>,
         warnings: &mut Vec<Warning>,
     ) -> bool {
         use std::sync::mpsc::channel;
@@ -968,16 +1669,23 @@ impl Local {
 
         pool.scope(|scope| {
             for id in ids {
+                if self.cancellation.is_cancelled() {
+                    break;
+                }
                 let tx = tx.clone();
 
                 if already_fetched_ids.contains(&id) {
                     continue;
                 }
 
-                if let Some(url) = db.lookup_url(&id).any_unverified() {
-                    let url = &url.url;
-
-                    if already_fetched_urls.contains(url) {
+                let url = db
+                    .read()
+                    .unwrap()
+                    .lookup_url(&id)
+                    .any_unverified()
+                    .map(|url| url.url.clone());
+                if let Some(url) = url {
+                    if already_fetched_urls.contains(&url) {
                         continue;
                     }
                     let url_clone = url.clone();
@@ -985,7 +1693,7 @@ impl Local {
                         tx.send((url_clone.clone(), self.fetch_remote_git(&url_clone)))
                             .expect("send to work");
                     });
-                    already_fetched_urls.insert(url.clone());
+                    already_fetched_urls.insert(url);
                 } else {
                     warnings.push(Warning::IdUrlNotKnonw(id.clone()));
                 }
@@ -997,15 +1705,21 @@ impl Local {
             for (url, res) in rx {
                 let dir = match res {
                     Ok(dir) => dir,
+                    Err(Error::Cancelled(_)) => continue,
                     Err(e) => {
                         error!("Error: Failed to get dir for repo {}: {}", url, e);
+                        self.record_fetch_progress(state.as_deref_mut(), &url, Err(e.to_string()));
                         continue;
                     }
                 };
-                if let Err(e) = self.import_proof_dir_and_print_counts(&dir, &url, db) {
+                let imported =
+                    self.import_proof_dir_and_print_counts(&dir, &url, &mut db.write().unwrap());
+                if let Err(e) = imported {
+                    self.record_fetch_progress(state.as_deref_mut(), &url, Err(e.to_string()));
                     warnings.push(Warning::FetchError(url, e, dir));
                     continue;
                 }
+                self.record_fetch_progress(state.as_deref_mut(), &url, Ok(()));
                 something_was_fetched = true;
             }
         });
@@ -1044,31 +1758,141 @@ impl Local {
     /// Returns url where it was cloned/fetched
     ///
     /// Adds the repo to the local proof repo cache.
+    ///
+    /// Repos bigger than `ProofLimits::max_repo_size` are removed from the cache.
+    ///
+    /// Broken checkouts are repaired: stale git locks are removed and the
+    /// fetch is retried once, and corrupted checkouts are cloned again.
     pub fn fetch_remote_git(&self, url: &str) -> Result<PathBuf> {
+        self.cancellation.check()?;
         let dir = self.get_remote_git_cache_path(url)?;
+        let max_repo_size = self.proof_limits().max_repo_size_for(url);
 
-        let inner = || {
-            if dir.exists() {
-                let repo = git2::Repository::open(&dir)?;
-                util::git::fetch_and_checkout_git_repo(&repo)
-            } else {
-                util::git::clone(url, &dir).map(drop)
+        if !dir.exists() {
+            self.clone_remote_git(url, &dir, max_repo_size)?;
+            self.check_repo_size(url, &dir, max_repo_size)?;
+            mark_fetched(&dir);
+            return Ok(dir);
+        }
+        if let Some(max_age) = self.fetch_max_age {
+            if fetched_within(&dir, max_age) {
+                debug!("Not fetching {url}, fetched less than {max_age:?} ago");
+                return Ok(dir);
+            }
+        }
+
+        let mut result = self.update_remote_git(url, &dir, max_repo_size);
+        if matches!(&result, Err(err) if err.code() == git2::ErrorCode::Locked) {
+            let removed = util::git::remove_stale_locks(&dir, STALE_GIT_LOCK_AGE);
+            if removed > 0 {
+                warn!("{:<60} removed {} stale git lock files", url, removed);
+                result = self.update_remote_git(url, &dir, max_repo_size);
+            }
+        }
+        match result {
+            Ok(()) => {
+                self.check_repo_size(url, &dir, max_repo_size)?;
+                mark_fetched(&dir);
+                Ok(dir)
+            }
+            Err(_) if self.cancellation.is_cancelled() => {
+                Err(crev_common::CancelledError::Interrupted.into())
+            }
+            Err(err) if util::git::is_size_limit_error(&err, &self.cancellation) => {
+                self.delete_remote_cache_directory(&dir);
+                Err(Error::ProofRepoTooBig(Box::new((
+                    url.to_owned(),
+                    max_repo_size.unwrap_or_default(),
+                ))))
             }
-        };
-        match inner() {
-            Ok(()) => Ok(dir),
             Err(err) if is_unrecoverable(&err) => {
                 debug!("Deleting {}, because {err}", dir.display());
                 self.delete_remote_cache_directory(&dir);
                 Err(err.into())
             }
+            Err(err) if util::git::is_corrupted(&err) => {
+                warn!("{:<60} broken checkout ({}), cloning again", url, err);
+                self.delete_remote_cache_directory(&dir);
+                self.clone_remote_git(url, &dir, max_repo_size)?;
+                self.check_repo_size(url, &dir, max_repo_size)?;
+                mark_fetched(&dir);
+                info!("{:<60} repaired", url);
+                Ok(dir)
+            }
             Err(err) => Err(err.into()),
         }
     }
 
+    /// Fetch a proof repo already in the cache, unless it didn't change
+    fn update_remote_git(
+        &self,
+        url: &str,
+        dir: &Path,
+        max_repo_size: Option<u64>,
+    ) -> std::result::Result<(), git2::Error> {
+        let repo = git2::Repository::open(dir)?;
+        util::git::check_checkout(&repo)?;
+        if util::git::is_up_to_date_with_origin(&repo, &self.cancellation)? {
+            debug!("Not fetching {url}, it didn't change");
+            return Ok(());
+        }
+        let dir_size = util::dir_size(dir);
+        // the limit is for the repo as a whole, not only what's fetched now
+        let max_bytes = max_repo_size.map(|max| max.saturating_sub(dir_size));
+        util::git::fetch_and_checkout_git_repo(&repo, &self.cancellation, max_bytes)
+    }
+
+    /// Clone a proof repo into `dir`
+    ///
+    /// The clone is made in `cache_partial_clones_path()` and moved to `dir`
+    /// only once complete, so an interrupted clone is never mistaken for
+    /// a proof repo, and is simply started over next time.
+    fn clone_remote_git(&self, url: &str, dir: &Path, max_repo_size: Option<u64>) -> Result<()> {
+        let partial_dir = self
+            .cache_partial_clones_path()
+            .join(dir.file_name().expect("remote cache dir has a name"));
+        if partial_dir.exists() {
+            fs::remove_dir_all(&partial_dir)?;
+        }
+        fs::create_dir_all(self.cache_partial_clones_path())?;
+
+        if let Err(err) =
+            util::git::clone_shallow(url, &partial_dir, &self.cancellation, max_repo_size)
+        {
+            if let Err(e) = fs::remove_dir_all(&partial_dir) {
+                warn!("Failed to remove {}: {}", partial_dir.display(), e);
+            }
+            self.cancellation.check()?;
+            if util::git::is_size_limit_error(&err, &self.cancellation) {
+                return Err(Error::ProofRepoTooBig(Box::new((
+                    url.to_owned(),
+                    max_repo_size.unwrap_or_default(),
+                ))));
+            }
+            return Err(err.into());
+        }
+        fs::rename(&partial_dir, dir)?;
+        Ok(())
+    }
+
+    /// Remove the checkout of `url` from the cache if it's bigger than `max_repo_size`
+    ///
+    /// What's transferred is compressed, so the checkout can be bigger.
+    fn check_repo_size(&self, url: &str, dir: &Path, max_repo_size: Option<u64>) -> Result<()> {
+        let Some(max) = max_repo_size else {
+            return Ok(());
+        };
+        if max < util::dir_size(dir) {
+            self.delete_remote_cache_directory(dir);
+            return Err(Error::ProofRepoTooBig(Box::new((url.to_owned(), max))));
+        }
+        Ok(())
+    }
+
     /// Fetches and imports to the given db
     ///
-    /// Same as `fetch_url_into`, but with more stats
+    /// Same as `fetch_url_into`, but with more stats, reported to
+    /// the `on_repo_fetched` callbacks
     ///
     /// dir - where the proofs were downloaded to
     /// url - url from which it was fetched
@@ -1082,41 +1906,85 @@ impl Local {
         let prev_trust_count = db.unique_trust_proof_count();
 
         let fetch_source = self.get_fetch_source_for_url(Url::new_git(url))?;
-        db.import_from_iter(
-            proofs_iter_for_path(dir.to_owned()).map(move |p| (p, fetch_source.clone())),
+        let max_file_size = self.proof_limits().max_file_size_for(url);
+        let report = db.import_from_iter(
+            proofs_iter_for_repo(dir.to_owned(), max_file_size)
+                .map(move |p| (p, fetch_source.clone())),
         );
 
-        let new_pkg_review_count = db.unique_package_review_proof_count() - prev_pkg_review_count;
-        let new_trust_count = db.unique_trust_proof_count() - prev_trust_count;
-
-        let msg = match (new_trust_count > 0, new_pkg_review_count > 0) {
-            (true, true) => {
-                format!("new: {new_trust_count} trust, {new_pkg_review_count} package reviews")
-            }
-            (true, false) => format!("new: {new_trust_count} trust",),
-            (false, true) => format!("new: {new_pkg_review_count} package reviews"),
-            (false, false) => "no updates".into(),
-        };
-
-        info!("{:<60} {}", url, msg);
+        let signature_status = git2::Repository::open(dir)
+            .and_then(|repo| util::git::head_commit_signature_status(&repo))
+            .map_err(|e| debug!("Couldn't check commit signature of {}: {}", url, e))
+            .ok();
+        self.hooks.repo_fetched(&RepoFetched {
+            url,
+            dir,
+            report: &report,
+            new_trust_count: db.unique_trust_proof_count() - prev_trust_count,
+            new_pkg_review_count: db.unique_package_review_proof_count() - prev_pkg_review_count,
+            signature_status,
+        });
         Ok(())
     }
 
     /// Fetch and discover proof repos. Like `fetch_all_ids_recursively`,
     /// but adds `https://github.com/dpc/crev-proofs` and repos in cache that didn't belong to any Ids.
+    ///
+    /// Same as `fetch_all_resumable` without retrying only the failed repos.
     pub fn fetch_all(&self, warnings: &mut Vec<Warning>) -> Result<()> {
-        let mut fetched_urls = HashSet::new();
+        self.fetch_all_resumable(false, warnings)
+    }
+
+    /// Like `fetch_all`, but keeps track of progress in a state file in the cache.
+    ///
+    /// If the previous run was interrupted, repos that were already fetched
+    /// (or failed) are skipped. With `retry_failed`, only the repos that
+    /// failed during the previous run are fetched again.
+    pub fn fetch_all_resumable(
+        &self,
+        retry_failed: bool,
+        warnings: &mut Vec<Warning>,
+    ) -> Result<()> {
+        let prev_state = self.read_fetch_all_state().unwrap_or_else(|e| {
+            warn!("Ignoring unreadable fetch progress: {}", e);
+            None
+        });
+
+        if retry_failed {
+            return self.fetch_all_retry_failed(prev_state.unwrap_or_default(), warnings);
+        }
+
+        let mut state = match prev_state {
+            Some(state) if !state.finished => {
+                info!(
+                    "Resuming previous fetch: {} done, {} failed",
+                    state.completed.len(),
+                    state.failed.len()
+                );
+                state
+            }
+            _ => FetchAllState::default(),
+        };
+        self.store_fetch_all_state(&state)?;
+
+        let mut fetched_urls: HashSet<String> = state.attempted_urls().cloned().collect();
         let mut db = self.load_db()?;
 
         // Temporarily hardcode `dpc`'s proof-repo url
         let dpc_url = "https://github.com/dpc/crev-proofs";
-        if let Ok(dir) = self
-            .fetch_remote_git(dpc_url)
-            .map_err(|e| warnings.push(e.into()))
-        {
-            let _ = self
-                .import_proof_dir_and_print_counts(&dir, dpc_url, &mut db)
-                .map_err(|e| warnings.push(e.into()));
+        if !fetched_urls.contains(dpc_url) {
+            self.cancellation.check()?;
+            let res = self
+                .fetch_remote_git(dpc_url)
+                .and_then(|dir| self.import_proof_dir_and_print_counts(&dir, dpc_url, &mut db));
+            match res {
+                Ok(()) => self.record_fetch_progress(Some(&mut state), dpc_url, Ok(())),
+                Err(e @ Error::Cancelled(_)) => return Err(e),
+                Err(e) => {
+                    self.record_fetch_progress(Some(&mut state), dpc_url, Err(e.to_string()));
+                    warnings.push(e.into());
+                }
+            }
         }
         fetched_urls.insert(dpc_url.to_owned());
 
@@ -1134,17 +2002,58 @@ impl Local {
                 }
             };
 
+            let max_file_size = self.proof_limits().max_file_size_for(&url);
             let _ = self
                 .get_fetch_source_for_url(Url::new_git(url))
                 .map(|fetch_source| {
                     db.import_from_iter(
-                        proofs_iter_for_path(path.clone()).map(move |p| (p, fetch_source.clone())),
+                        proofs_iter_for_path(path.clone(), max_file_size)
+                            .map(move |p| (p, fetch_source.clone())),
                     );
                 })
                 .map_err(|e| warnings.push(e.into()));
         }
 
-        self.fetch_all_ids_recursively(fetched_urls, &mut db, warnings)?;
+        self.fetch_all_ids_recursively(fetched_urls, &RwLock::new(db), Some(&mut state), warnings)?;
+
+        state.finished = true;
+        self.store_fetch_all_state(&state)?;
+        if !state.failed.is_empty() {
+            warn!(
+                "{} proof repositories could not be fetched and can be retried",
+                state.failed.len()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Fetch again only the repos recorded as failed in `state`
+    fn fetch_all_retry_failed(
+        &self,
+        mut state: FetchAllState,
+        warnings: &mut Vec<Warning>,
+    ) -> Result<()> {
+        let failed_urls: Vec<String> = state.failed.keys().cloned().collect();
+        if failed_urls.is_empty() {
+            info!("No previously failed proof repositories to retry");
+            return Ok(());
+        }
+
+        let mut db = self.load_db()?;
+        for url in failed_urls {
+            self.cancellation.check()?;
+            let res = self
+                .fetch_remote_git(&url)
+                .and_then(|dir| self.import_proof_dir_and_print_counts(&dir, &url, &mut db));
+            match res {
+                Ok(()) => self.record_fetch_progress(Some(&mut state), &url, Ok(())),
+                Err(e) => {
+                    self.record_fetch_progress(Some(&mut state), &url, Err(e.to_string()));
+                    warnings.push(e.into());
+                }
+            }
+        }
 
         Ok(())
     }
@@ -1183,6 +2092,59 @@ impl Local {
         Ok(status)
     }
 
+    /// Run the `pre-sign` hook (see [`HookCmds`]) on the draft of `review`
+    ///
+    /// What the hook prints is appended to the comment of `review`.
+    pub fn run_pre_sign_hook(
+        &self,
+        review: &mut proof::review::Package,
+        package_dir: &Path,
+    ) -> Result<()> {
+        let Some(cmd) = self.load_user_config()?.hooks.pre_sign else {
+            return Ok(());
+        };
+        let version = review.package.id.version.to_string();
+        let output = hooks::run_hook_cmd(
+            "pre-sign",
+            &cmd,
+            package_dir,
+            &[
+                ("CREV_PROOF_KIND", OsStr::new(review.kind())),
+                ("CREV_PACKAGE_NAME", OsStr::new(&review.package.id.id.name)),
+                ("CREV_PACKAGE_VERSION", OsStr::new(&version)),
+                ("CREV_PACKAGE_DIR", package_dir.as_os_str()),
+            ],
+            review.to_draft().body().as_bytes(),
+            true,
+        )?;
+        let output = String::from_utf8_lossy(&output);
+        let summary = output.trim();
+        if !summary.is_empty() {
+            if !review.comment.is_empty() {
+                review.comment.push_str("\n\n");
+            }
+            review.comment.push_str(summary);
+        }
+        Ok(())
+    }
+
+    /// The review template of the proof repository, or else of the user config dir
+    ///
+    /// See [`ReviewTemplate`].
+    pub fn load_review_template(&self) -> Result<Option<ReviewTemplate>> {
+        let proof_dir = self.get_proofs_dir_path_opt()?;
+        let candidates = proof_dir
+            .into_iter()
+            .chain([self.user_dir_path()])
+            .map(|dir| dir.join(REVIEW_TEMPLATE_FILE_NAME));
+        for path in candidates {
+            if path.exists() {
+                return ReviewTemplate::read_from_yaml_file(&path).map(Some);
+            }
+        }
+        Ok(None)
+    }
+
     /// set `open_cmd` in the config
     pub fn store_config_open_cmd(&self, cmd: String) -> Result<()> {
         let mut config = self.load_user_config()?;
@@ -1202,6 +2164,283 @@ impl Local {
         Ok(())
     }
 
+    /// Proofs committed to the proof repo, but not in its upstream branch yet
+    ///
+    /// All the committed proofs if the current branch has no upstream.
+    pub fn unpublished_proofs(&self) -> Result<Vec<proof::Proof>> {
+        let proof_dir = self.get_proofs_dir_path()?;
+        let repo = git2::Repository::open(&proof_dir)?;
+        let head_commit = repo.head()?.peel_to_commit()?.id();
+        let published = self.published_proof_signatures()?;
+        let proofs = proofs_at_git_commit(&proof_dir, &head_commit.to_string(), None)?;
+        Ok(proofs
+            .into_iter()
+            .filter(|proof| !published.contains(proof.signature()))
+            .collect())
+    }
+
+    /// Signatures of the proofs in the upstream branch of the proof repo
+    fn published_proof_signatures(&self) -> Result<HashSet<String>> {
+        let proof_dir = self.get_proofs_dir_path()?;
+        let repo = git2::Repository::open(&proof_dir)?;
+        let upstream_commit = git2::Branch::wrap(repo.head()?)
+            .upstream()
+            .and_then(|upstream| upstream.get().peel_to_commit())
+            .ok();
+        Ok(match upstream_commit {
+            Some(commit) => proofs_at_git_commit(&proof_dir, &commit.id().to_string(), None)?
+                .into_iter()
+                .map(|proof| proof.signature().to_owned())
+                .collect(),
+            None => HashSet::new(),
+        })
+    }
+
+    /// The latest proof of the current Id in its proof repo, as checked out
+    pub fn last_own_proof(&self) -> Result<Option<OwnProof>> {
+        let current_id = self.get_current_userid()?;
+        let proof_dir = self.get_proofs_dir_path()?;
+        let Some((proof, path)) = proof_files_iter_for_path(proof_dir.clone(), None, None)
+            .filter(|(proof, _)| proof.from().id == current_id)
+            .max_by_key(|(proof, _)| proof.date_utc())
+        else {
+            return Ok(None);
+        };
+        let published = self
+            .published_proof_signatures()?
+            .contains(proof.signature());
+        let rel_path = path.strip_prefix(&proof_dir).unwrap_or(&path).to_owned();
+        Ok(Some(OwnProof {
+            proof,
+            rel_path,
+            published,
+        }))
+    }
+
+    /// Take `own` out of the file it's in, and add the change to the git index
+    ///
+    /// The file is deleted if that was its only proof.
+    pub fn remove_own_proof(&self, own: &OwnProof) -> Result<()> {
+        let proof_dir = self.get_proofs_dir_path()?;
+        let path = proof_dir.join(&own.rel_path);
+        let content = fs::read_to_string(&path)?;
+        // stored by `insert`, followed by an empty line
+        let text = format!("{}\n", own.proof);
+        let Some(start) = content.find(&text) else {
+            return Err(Error::ProofNotInFile(path.into()));
+        };
+        let rest = format!("{}{}", &content[..start], &content[start + text.len()..]);
+
+        let repo = git2::Repository::open(&proof_dir)?;
+        let mut index = repo.index()?;
+        if rest.trim().is_empty() {
+            fs::remove_file(&path)?;
+            index.remove_path(&own.rel_path)?;
+        } else {
+            util::store_str_to_file(&path, &rest).map_err(|e| Error::FileWrite(e, path))?;
+            index.add_path(&own.rel_path)?;
+        }
+        index.write()?;
+        Ok(())
+    }
+
+    /// Proof files (`*.crev`) of the proof repo, as committed or added to the git index
+    ///
+    /// With `unpublished_only`, just the ones changed in commits not in the
+    /// upstream branch yet, or in the index. Deleted files are skipped.
+    /// See [`crate::validate`].
+    pub fn proof_files_to_validate(
+        &self,
+        unpublished_only: bool,
+    ) -> Result<Vec<(PathBuf, Vec<u8>)>> {
+        let proof_dir = self.get_proofs_dir_path()?;
+        let repo = git2::Repository::open(&proof_dir)?;
+        let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+        let base_tree = if unpublished_only {
+            repo.head()
+                .ok()
+                .and_then(|head| git2::Branch::wrap(head).upstream().ok())
+                .and_then(|upstream| upstream.get().peel_to_tree().ok())
+        } else {
+            None
+        };
+
+        let committed = repo.diff_tree_to_tree(base_tree.as_ref(), head_tree.as_ref(), None)?;
+        let in_index = repo.diff_tree_to_index(head_tree.as_ref(), None, None)?;
+        // index entries come last, and replace committed versions of the same file
+        let mut files = BTreeMap::new();
+        for diff in [committed, in_index] {
+            for delta in diff.deltas() {
+                if delta.status() == git2::Delta::Deleted {
+                    continue;
+                }
+                let file = delta.new_file();
+                let Some(path) = file.path() else {
+                    continue;
+                };
+                if path.extension().map_or(true, |ext| ext != "crev") {
+                    continue;
+                }
+                let blob = repo.find_blob(file.id())?;
+                files.insert(path.to_owned(), blob.content().to_vec());
+            }
+        }
+        Ok(files.into_iter().collect())
+    }
+
+    /// Write `index.json` and its signed manifest for `id`'s reviews,
+    /// and add them to the proof repo
+    ///
+    /// See [`crate::proof_index`].
+    pub fn write_proof_index(&self, id: &UnlockedId) -> Result<()> {
+        let proof_dir = self.get_proofs_dir_path()?;
+        let index =
+            ProofIndex::from_proofs(&id.id.id, proofs_iter_for_path(proof_dir.clone(), None));
+        let index_json = index.to_json()?;
+        let manifest = SignedManifest::sign(id, index_json.as_bytes())?;
+
+        let rel_dir = PathBuf::from(id.id.id.to_string());
+        fs::create_dir_all(proof_dir.join(&rel_dir))?;
+        for (file_name, content) in [
+            (proof_index::INDEX_FILE_NAME, index_json),
+            (proof_index::MANIFEST_FILE_NAME, manifest.to_json()?),
+        ] {
+            let rel_path = rel_dir.join(file_name);
+            let path = proof_dir.join(&rel_path);
+            util::store_str_to_file(&path, &content).map_err(|e| Error::FileWrite(e, path))?;
+            self.proof_dir_git_add_path(&rel_path)?;
+        }
+        Ok(())
+    }
+
+    /// Proofs kept aside instead of being stored, see [`crate::staging`]
+    #[must_use]
+    pub fn proof_staging(&self) -> ProofStaging {
+        ProofStaging::new(self.data_path.join("staged-proofs"))
+    }
+
+    /// Store all staged proofs of the current Id, and commit them together
+    ///
+    /// Proofs signed by other Ids are left staged. Returns how many were stored.
+    pub fn commit_staged_proofs(&self, commit_msg: &str) -> Result<usize> {
+        let current_id = self.get_current_userid()?;
+        let staging = self.proof_staging();
+        let mut count = 0;
+        for staged in staging.list()? {
+            if staged.proof.from().id != current_id {
+                warn!(
+                    "Staged proof {} is signed by {}, not the current Id. Left staged.",
+                    staged.name,
+                    staged.proof.from().id
+                );
+                continue;
+            }
+            self.insert(&staged.proof)?;
+            staging.remove(&staged.name)?;
+            count += 1;
+        }
+        if count > 0 {
+            self.proof_dir_commit(commit_msg)?;
+        }
+        Ok(count)
+    }
+
+    /// Proofs kept back until a date, see [`crate::embargo`]
+    #[must_use]
+    pub fn proof_embargo(&self) -> ProofEmbargo {
+        ProofEmbargo::new(self.data_path.join("embargoed-proofs"))
+    }
+
+    fn local_waivers_path(&self) -> PathBuf {
+        self.data_path.join("waivers")
+    }
+
+    /// Keep a signed waiver on this machine only, instead of in the proof repository
+    ///
+    /// `load_db` loads it along with the user's own proofs.
+    pub fn store_local_waiver(&self, proof: &proof::Proof) -> Result<()> {
+        let path = self.local_waivers_path().join("waivers.crev");
+        let mut text = if path.exists() {
+            fs::read_to_string(&path)?
+        } else {
+            String::new()
+        };
+        text.push_str(&format!("{proof}\n"));
+        crev_common::store_str_to_file(&path, &text)?;
+        Ok(())
+    }
+
+    fn imported_proofs_path(&self) -> PathBuf {
+        self.data_path.join("imported-proofs")
+    }
+
+    /// Proofs converted from `source` (eg. `rustsec`), see [`crate::imported`]
+    #[must_use]
+    pub fn imported_proofs(&self, source: &str) -> ImportedProofs {
+        ImportedProofs::new(self.imported_proofs_path().join(source))
+    }
+
+    /// Store the embargoed proofs of the current Id whose date has passed, and commit them
+    ///
+    /// Returns how many were stored.
+    pub fn release_due_embargoed_proofs(&self) -> Result<usize> {
+        let now = crev_common::now();
+        let due: Vec<_> = self
+            .proof_embargo()
+            .list()?
+            .into_iter()
+            .filter(|embargoed| embargoed.is_due(&now))
+            .collect();
+        self.release_embargoed_proofs(&due)
+    }
+
+    /// Store `embargoed` proofs of the current Id now, whatever their dates, and commit them
+    ///
+    /// Proofs signed by other Ids are left embargoed. Returns how many were stored.
+    pub fn release_embargoed_proofs(&self, embargoed: &[EmbargoedProof]) -> Result<usize> {
+        let current_id = self.get_current_userid()?;
+        let embargo = self.proof_embargo();
+        let mut count = 0;
+        for embargoed in embargoed {
+            if embargoed.proof.from().id != current_id {
+                warn!(
+                    "Embargoed proof {} is signed by {}, not the current Id. Left embargoed.",
+                    embargoed.name,
+                    embargoed.proof.from().id
+                );
+                continue;
+            }
+            self.insert(&embargoed.proof)?;
+            embargo.remove(&embargoed.name)?;
+            count += 1;
+        }
+        if count > 0 {
+            self.proof_dir_commit(&format!("Release {count} embargoed proof(s)"))?;
+        }
+        Ok(count)
+    }
+
+    /// Whether the checked out branch of the proof repo was pushed to `origin` yet
+    ///
+    /// A new yearly branch (see [`ProofRepoLayout::YearlyBranches`]) isn't,
+    /// until it's published the first time. If `origin` can't be asked, it's
+    /// assumed that it was.
+    pub fn proof_dir_branch_is_on_origin(&self) -> Result<bool> {
+        let proof_dir = self.get_proofs_dir_path()?;
+        let repo = git2::Repository::open(&proof_dir)?;
+        let head = repo.head()?;
+        let Some(branch) = head.shorthand() else {
+            return Ok(true);
+        };
+        let status = std::process::Command::new("git")
+            .args(["ls-remote", "--exit-code", "--heads", "origin", branch])
+            .current_dir(&proof_dir)
+            .stdout(std::process::Stdio::null())
+            .status()?;
+        // exit code of no matching refs
+        Ok(status.code() != Some(2))
+    }
+
     /// Add a commit to user's proof repo
     pub fn proof_dir_commit(&self, commit_msg: &str) -> Result<()> {
         let proof_dir = self.get_proofs_dir_path()?;
@@ -1223,14 +2462,29 @@ impl Local {
             .signature()
             .or_else(|_| git2::Signature::now("unconfigured", "nobody@crev.dev"))?;
 
-        repo.commit(
-            Some("HEAD"),
-            &signature,
-            &signature,
-            commit_msg,
-            &tree,
-            parents,
-        )?;
+        let Some(signing) = self.load_user_config()?.commit_signing else {
+            repo.commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                commit_msg,
+                &tree,
+                parents,
+            )?;
+            return Ok(());
+        };
+
+        let buf = repo.commit_create_buffer(&signature, &signature, commit_msg, &tree, parents)?;
+        let content = buf.as_str().expect("commit buffer is UTF-8");
+        let commit_signature = signing.sign(content)?;
+        let oid = repo.commit_signed(content, &commit_signature, None)?;
+        // `commit_signed` doesn't move `HEAD` (or the branch it points to)
+        let branch = repo
+            .find_reference("HEAD")?
+            .symbolic_target()
+            .unwrap_or("HEAD")
+            .to_owned();
+        repo.reference(&branch, oid, true, commit_msg)?;
 
         Ok(())
     }
@@ -1274,6 +2528,32 @@ impl Local {
         Ok(locked_id)
     }
 
+    /// Replace the current Id `old` with a new one, using the same proof repository
+    ///
+    /// Stores both halves of the handshake: the [`proof::Successor`] proof
+    /// signed by `old`, and the same one signed by the new Id, which
+    /// becomes the current Id. The callback should provide a passphrase.
+    pub fn rotate_current_id(
+        &self,
+        old: &UnlockedId,
+        read_new_passphrase: impl FnOnce() -> std::io::Result<String>,
+    ) -> Result<id::LockedId> {
+        let new = UnlockedId::generate(old.id.url.clone());
+        let passphrase = read_new_passphrase()?;
+        let locked_id = id::LockedId::from_unlocked_id(&new, &passphrase)?;
+        self.save_locked_id(&locked_id)?;
+
+        for signer in [old, &new] {
+            let successor = signer
+                .as_public_id()
+                .create_successor_proof(old.as_public_id().clone(), new.as_public_id().clone())?;
+            self.insert(&successor.sign_by(signer)?)?;
+        }
+        self.save_current_id(&new.id.id)?;
+        self.proof_dir_commit(&format!("Rotate Id {} to {}", old.id.id, new.id.id))?;
+        Ok(locked_id)
+    }
+
     /// Set given Id as the current one
     pub fn switch_id(&self, id_str: &str) -> Result<()> {
         let id: Id = Id::crevid_from_str(id_str)?;
@@ -1303,12 +2583,27 @@ impl Local {
 
     /// All proofs from all local repos, regardless of current user's URL
     fn all_local_proofs(&self) -> impl Iterator<Item = proof::Proof> {
-        match self.user_proofs_path_opt() {
-            Some(path) => {
-                Box::new(proofs_iter_for_path(path)) as Box<dyn Iterator<Item = proof::Proof>>
-            }
-            None => Box::new(vec![].into_iter()),
-        }
+        self.all_local_proofs_with_files(None)
+            .map(|(proof, _)| proof)
+    }
+
+    /// `all_local_proofs`, with the files they were read from
+    fn all_local_proofs_with_files(
+        &self,
+        cache: Option<ProofCache>,
+    ) -> impl Iterator<Item = (proof::Proof, Option<PathBuf>)> {
+        self.user_proofs_path_opt()
+            .into_iter()
+            .flat_map(move |path| {
+                let other_branches = fs::read_dir(&path)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|entry| entry.ok())
+                    .flat_map(|entry| proofs_in_other_yearly_branches(&entry.path(), None));
+                proof_files_iter_for_path(path, None, cache.clone())
+                    .map(|(proof, file)| (proof, Some(file)))
+                    .chain(other_branches.map(|proof| (proof, None)))
+            })
     }
 
     #[rustfmt::skip]
@@ -1331,24 +2626,30 @@ impl Local {
 
 impl ProofStore for Local {
     fn insert(&self, proof: &proof::Proof) -> Result<()> {
-        let rel_store_path = self.get_proof_rel_store_path(
-            proof,
-            &self
-                .user_config
-                .lock()
-                .unwrap()
-                .as_ref()
-                .expect("User config loaded")
-                .host_salt,
-        );
-        let path = self.get_proofs_dir_path()?.join(&rel_store_path);
+        let (host_salt, layout, post_store_cmd) = {
+            let config = self.user_config.lock().unwrap();
+            let config = config.as_ref().expect("User config loaded");
+            (
+                config.host_salt.clone(),
+                config.proof_repo_layout,
+                config.hooks.post_store.clone(),
+            )
+        };
+        let rel_store_path = self.get_proof_rel_store_path(proof, &host_salt, layout);
+        let proof_dir = self.get_proofs_dir_path()?;
+        if layout == ProofRepoLayout::YearlyBranches {
+            let repo = git2::Repository::open(&proof_dir)?;
+            let branch = util::git::yearly_branch_name(crev_common::now().year());
+            util::git::switch_to_branch_or_orphan(&repo, &branch)?;
+        }
+        let path = proof_dir.join(&rel_store_path);
 
         fs::create_dir_all(path.parent().expect("Not a root dir"))?;
         let mut file = fs::OpenOptions::new()
             .append(true)
             .create(true)
             .write(true)
-            .open(path)?;
+            .open(&path)?;
 
         file.write_all(proof.to_string().as_bytes())?;
         file.write_all(b"\n")?;
@@ -1356,6 +2657,26 @@ impl ProofStore for Local {
         drop(file);
 
         self.proof_dir_git_add_path(&rel_store_path)?;
+        self.hooks.proof_inserted(&ProofInserted {
+            proof,
+            rel_path: &rel_store_path,
+        });
+        if let Some(cmd) = post_store_cmd {
+            // the proof is stored already, failing now would only hide that
+            if let Err(e) = hooks::run_hook_cmd(
+                "post-store",
+                &cmd,
+                &proof_dir,
+                &[
+                    ("CREV_PROOF_KIND", OsStr::new(proof.kind())),
+                    ("CREV_PROOF_PATH", path.as_os_str()),
+                ],
+                proof.to_string().as_bytes(),
+                false,
+            ) {
+                warn!("{e}. The proof was stored anyway.");
+            }
+        }
 
         Ok(())
     }
@@ -1365,6 +2686,27 @@ impl ProofStore for Local {
     }
 }
 
+/// Touched in `.git` of a proof repo checkout whenever it's fetched, see `Local::set_fetch_max_age`
+const LAST_FETCH_MARKER: &str = "crev-last-fetch";
+
+/// Git locks older than this are left by interrupted processes, see `fetch_remote_git`
+const STALE_GIT_LOCK_AGE: Duration = Duration::from_secs(10 * 60);
+
+fn mark_fetched(dir: &Path) {
+    let path = dir.join(".git").join(LAST_FETCH_MARKER);
+    if let Err(e) = fs::write(&path, crev_common::now().to_rfc3339()) {
+        debug!("Can't write {}: {}", path.display(), e);
+    }
+}
+
+fn fetched_within(dir: &Path, max_age: Duration) -> bool {
+    fs::metadata(dir.join(".git").join(LAST_FETCH_MARKER))
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_some_and(|age| age < max_age)
+}
+
 /// Scans cache for checked out repos and their origin urls
 fn remotes_checkouts_iter(path: PathBuf) -> Result<impl Iterator<Item = (PathBuf, Url)>> {
     let dir = std::fs::read_dir(path)?;
@@ -1389,15 +2731,142 @@ fn remotes_checkouts_iter(path: PathBuf) -> Result<impl Iterator<Item = (PathBuf
 /// Scan a directory of git checkouts. Assumes fetch source is the origin URL.
 fn proofs_iter_for_remotes_checkouts(
     path: PathBuf,
-) -> Result<impl Iterator<Item = (proof::Proof, crev_wot::FetchSource)>> {
-    Ok(remotes_checkouts_iter(path)?.flat_map(|(path, url)| {
+    limits: &ProofLimits,
+    cache: Option<ProofCache>,
+) -> Result<impl Iterator<Item = (proof::Proof, crev_wot::ProofOrigin)> + '_> {
+    Ok(remotes_checkouts_iter(path)?.flat_map(move |(path, url)| {
+        let max_file_size = limits.max_file_size_for(&url.url);
         let fetch_source = crev_wot::FetchSource::Url(Arc::new(url));
-        proofs_iter_for_path(path).map(move |p| (p, fetch_source.clone()))
+        let other_branches = proofs_in_other_yearly_branches(&path, max_file_size);
+        proof_files_iter_for_path(path, max_file_size, cache.clone())
+            .map(|(proof, file)| (proof, Some(file)))
+            .chain(other_branches.into_iter().map(|proof| (proof, None)))
+            .map(move |(proof, file)| {
+                let origin = crev_wot::ProofOrigin {
+                    fetched_from: fetch_source.clone(),
+                    file,
+                };
+                (proof, origin)
+            })
     }))
 }
 
+/// Proofs of a proof repo checkout, including its yearly branches that aren't checked out
+fn proofs_iter_for_repo(
+    path: PathBuf,
+    max_file_size: Option<u64>,
+) -> impl Iterator<Item = proof::Proof> {
+    let other_branches = proofs_in_other_yearly_branches(&path, max_file_size);
+    proofs_iter_for_path(path, max_file_size).chain(other_branches)
+}
+
+/// See [`ProofRepoLayout::YearlyBranches`]
+fn proofs_in_other_yearly_branches(dir: &Path, max_file_size: Option<u64>) -> Vec<proof::Proof> {
+    let commits = match git2::Repository::open(dir)
+        .and_then(|repo| util::git::other_yearly_branch_commits(&repo))
+    {
+        Ok(commits) => commits,
+        Err(e) => {
+            debug!("No yearly branches in {}: {}", dir.display(), e);
+            return vec![];
+        }
+    };
+    commits
+        .into_iter()
+        .flat_map(|commit| {
+            proofs_at_git_commit(dir, &commit.to_string(), max_file_size).unwrap_or_else(|e| {
+                warn!("Can't read {} of {}: {}", commit, dir.display(), e);
+                vec![]
+            })
+        })
+        .collect()
+}
+
+/// The checked out commit of a proof repo, and those of its other yearly branches
+fn pinned_commits(repo: &git2::Repository) -> Result<Vec<String>> {
+    let head = repo.head()?.peel_to_commit()?.id();
+    Ok(std::iter::once(head)
+        .chain(util::git::other_yearly_branch_commits(repo)?)
+        .map(|commit| commit.to_string())
+        .collect())
+}
+
+/// Proofs in a git checkout as of `commit`, regardless of what is checked out
+///
+/// Files bigger than `max_file_size` are skipped.
+fn proofs_at_git_commit(
+    dir: &Path,
+    commit: &str,
+    max_file_size: Option<u64>,
+) -> Result<Vec<proof::Proof>> {
+    let repo = git2::Repository::open(dir)?;
+    let tree = repo.find_commit(git2::Oid::from_str(commit)?)?.tree()?;
+
+    let mut blobs = vec![];
+    tree.walk(git2::TreeWalkMode::PreOrder, |parent, entry| {
+        let name = entry.name().unwrap_or(".");
+        // skip dotfiles, like `proofs_iter_for_path`
+        if name.starts_with('.') {
+            return git2::TreeWalkResult::Skip;
+        }
+        if entry.kind() == Some(git2::ObjectType::Blob) && name.ends_with(".crev") {
+            blobs.push((format!("{parent}{name}"), entry.id()));
+        }
+        git2::TreeWalkResult::Ok
+    })?;
+
+    let mut proofs = vec![];
+    for (path, oid) in blobs {
+        let blob = repo.find_blob(oid)?;
+        if is_over_file_size_limit(&path, blob.size() as u64, max_file_size) {
+            continue;
+        }
+        match proof::Proof::parse_from(blob.content()) {
+            Ok(parsed) => proofs.extend(parsed.into_iter().filter(|proof| {
+                proof
+                    .verify()
+                    .map_err(|e| {
+                        error!(
+                            "Verification failed for proof signed '{}' in {}: {} ",
+                            proof.signature(),
+                            path,
+                            e
+                        );
+                    })
+                    .is_ok()
+            })),
+            Err(e) => error!("Error parsing proofs in {}: {}", path, e),
+        }
+    }
+    Ok(proofs)
+}
+
 /// Scan a git checkout or any subdirectory obtained from a known URL
-fn proofs_iter_for_path(path: PathBuf) -> impl Iterator<Item = proof::Proof> {
+fn proofs_iter_for_path(
+    path: PathBuf,
+    max_file_size: Option<u64>,
+) -> impl Iterator<Item = proof::Proof> {
+    proof_files_iter_for_path(path, max_file_size, None).map(|(proof, _)| proof)
+}
+
+/// Warn about, and tell to skip, a proof file bigger than `max_file_size`
+fn is_over_file_size_limit(path: &str, size: u64, max_file_size: Option<u64>) -> bool {
+    let over_limit = max_file_size.is_some_and(|max| max < size);
+    if over_limit {
+        warn!("Skipping {path}: {size} bytes is more than the proof file size limit");
+    }
+    over_limit
+}
+
+/// Proofs in `*.crev` files under `path`, with the file each is in
+///
+/// Files bigger than `max_file_size` are skipped. Proofs with invalid signatures
+/// are skipped too, unless the file is in `cache` already.
+fn proof_files_iter_for_path(
+    path: PathBuf,
+    max_file_size: Option<u64>,
+    cache: Option<ProofCache>,
+) -> impl Iterator<Item = (proof::Proof, PathBuf)> {
     use std::ffi::OsStr;
     let file_iter = walkdir::WalkDir::new(&path)
         .into_iter()
@@ -1406,7 +2875,7 @@ fn proofs_iter_for_path(path: PathBuf) -> impl Iterator<Item = proof::Proof> {
         .map_err(move |e| {
             Error::ErrorIteratingLocalProofStore(Box::new((path.clone(), e.to_string())))
         })
-        .filter_map_ok(|entry| {
+        .filter_map_ok(move |entry| {
             let path = entry.path();
             if !path.is_file() {
                 return None;
@@ -1414,24 +2883,29 @@ fn proofs_iter_for_path(path: PathBuf) -> impl Iterator<Item = proof::Proof> {
 
             let osext_match: &OsStr = "crev".as_ref();
             match path.extension() {
-                Some(osext) if osext == osext_match => Some(path.to_owned()),
-                _ => None,
+                Some(osext) if osext == osext_match => {}
+                _ => return None,
+            }
+            let size = entry.metadata().map_or(0, |metadata| metadata.len());
+            if is_over_file_size_limit(&path.display().to_string(), size, max_file_size) {
+                return None;
             }
+            Some(path.to_owned())
         });
 
-    fn parse_proofs(path: &Path) -> Result<Vec<proof::Proof>> {
-        let mut file = BufReader::new(std::fs::File::open(path)?);
-        Ok(proof::Proof::parse_from(&mut file)?)
-    }
-
-    file_iter
-        .filter_map(|maybe_path| {
-            maybe_path
-                .map_err(|e| error!("Failed scanning for proofs: {}", e))
-                .ok()
-        })
-        .filter_map(|path| match parse_proofs(&path) {
-            Ok(proofs) => Some(proofs.into_iter().filter_map(move |proof| {
+    /// Proofs with valid signatures in the file at `path`
+    fn read_proofs(path: &Path, cache: Option<&ProofCache>) -> Result<Vec<proof::Proof>> {
+        let metadata = fs::metadata(path)?;
+        if let Some(proofs) = cache.and_then(|cache| cache.get_unchanged(path, &metadata)) {
+            return Ok(proofs);
+        }
+        let content = fs::read(path)?;
+        if let Some(proofs) = cache.and_then(|cache| cache.get(path, &metadata, &content)) {
+            return Ok(proofs);
+        }
+        let proofs: Vec<_> = proof::Proof::parse_from(content.as_slice())?
+            .into_iter()
+            .filter(|proof| {
                 proof
                     .verify()
                     .map_err(|e| {
@@ -1442,9 +2916,23 @@ fn proofs_iter_for_path(path: PathBuf) -> impl Iterator<Item = proof::Proof> {
                             e
                         );
                     })
-                    .ok()
-                    .map(|()| proof)
-            })),
+                    .is_ok()
+            })
+            .collect();
+        if let Some(cache) = cache {
+            cache.insert(path, &metadata, &content, &proofs);
+        }
+        Ok(proofs)
+    }
+
+    file_iter
+        .filter_map(|maybe_path| {
+            maybe_path
+                .map_err(|e| error!("Failed scanning for proofs: {}", e))
+                .ok()
+        })
+        .filter_map(move |path| match read_proofs(&path, cache.as_ref()) {
+            Ok(proofs) => Some(proofs.into_iter().map(move |proof| (proof, path.clone()))),
             Err(e) => {
                 error!("Error parsing proofs in {}: {}", path.display(), e);
                 None
@@ -0,0 +1,69 @@
+//! Non-interactive sources of Id passphrases
+//!
+//! Besides asking the user, a passphrase can come from a command
+//! (eg. a password manager like `pass`), or from the OS keyring
+//! (secret-service, macOS keychain, Windows credential manager)
+//! when built with the `keyring` feature.
+use crate::Result;
+use crev_data::Id;
+use std::{ffi::OsStr, io};
+
+/// Service name of keyring entries; the user name is the Id
+#[cfg(feature = "keyring")]
+const KEYRING_SERVICE: &str = "crev";
+
+/// Run a shell command and use its (trimmed) output as the passphrase
+pub fn passphrase_from_cmd(cmd: &OsStr) -> io::Result<String> {
+    let stdout = crev_common::run_with_shell_cmd_capture_stdout(cmd, None)?;
+    Ok(String::from_utf8_lossy(&stdout).trim().to_owned())
+}
+
+#[must_use]
+pub fn keyring_supported() -> bool {
+    cfg!(feature = "keyring")
+}
+
+#[cfg(feature = "keyring")]
+fn keyring_entry(id: &Id) -> Result<keyring::Entry> {
+    Ok(keyring::Entry::new(KEYRING_SERVICE, &id.to_string())?)
+}
+
+/// Passphrase of `id` stored in the OS keyring, if any
+#[cfg(feature = "keyring")]
+pub fn keyring_get(id: &Id) -> Result<Option<String>> {
+    match keyring_entry(id)?.get_password() {
+        Ok(passphrase) => Ok(Some(passphrase)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(not(feature = "keyring"))]
+pub fn keyring_get(_id: &Id) -> Result<Option<String>> {
+    Ok(None)
+}
+
+#[cfg(feature = "keyring")]
+pub fn keyring_set(id: &Id, passphrase: &str) -> Result<()> {
+    Ok(keyring_entry(id)?.set_password(passphrase)?)
+}
+
+#[cfg(not(feature = "keyring"))]
+pub fn keyring_set(_id: &Id, _passphrase: &str) -> Result<()> {
+    Err(crate::Error::KeyringNotSupported)
+}
+
+/// Remove the passphrase of `id` from the keyring. Returns `false` if there was none.
+#[cfg(feature = "keyring")]
+pub fn keyring_delete(id: &Id) -> Result<bool> {
+    match keyring_entry(id)?.delete_credential() {
+        Ok(()) => Ok(true),
+        Err(keyring::Error::NoEntry) => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(not(feature = "keyring"))]
+pub fn keyring_delete(_id: &Id) -> Result<bool> {
+    Err(crate::Error::KeyringNotSupported)
+}
@@ -0,0 +1,44 @@
+//! Inputs of a verification, pinned for reproducibility
+//!
+//! Records the exact proof repository commits (your own ones included), the
+//! Id whose web of trust is used, and the crates.io data a `verify` used, so
+//! it can be repeated later, or on another machine, with exactly the same
+//! inputs. Everything is pinned by content, never by local paths or times.
+//! See [`crate::Local::pin_inputs`] and [`crate::Local::load_db_pinned`].
+use crev_data::Id;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct PinnedInputs {
+    /// The Id whose web of trust was computed (`--for-id`, or the current Id)
+    pub for_id: Option<Id>,
+    /// Proof repository URL -> the git commits of it that were used: the
+    /// checked out one first, then those of its other yearly branches
+    pub proof_repos: BTreeMap<String, Vec<String>>,
+    /// crates.io data that was used, by crate name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub crates_io: BTreeMap<String, PinnedCrate>,
+}
+
+/// crates.io data of a crate, as it was used
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct PinnedCrate {
+    /// Version -> download counts
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub downloads: BTreeMap<String, PinnedDownloads>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owners: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PinnedDownloads {
+    /// Of the version
+    pub version: u64,
+    /// Of all versions
+    pub total: u64,
+    /// Of all versions, in the last 90 days
+    pub recent: u64,
+}
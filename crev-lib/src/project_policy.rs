@@ -0,0 +1,392 @@
+//! Verification policy of a project, committed next to its root `Cargo.toml`
+//!
+//! Sets the least requirements for the dependencies of the project (command
+//! line flags can only make them stricter), requirements for some crates,
+//! crates exempt from verification until a date, and crates that must be
+//! reviewed by specific Ids, like "code owners" for dependencies:
+//!
+//! ```yaml
+//! trust-level: medium
+//! redundancy: 2
+//! understanding: low
+//! requirements:
+//!   - crates: ["serde*"]
+//!     redundancy: 1
+//!   - crates: ["*-sys"]
+//!     thoroughness: medium
+//! exemptions:
+//!   - crates: ["internal-*"]
+//!     until: 2025-06-01
+//!     reason: vendored, audited separately
+//! groups:
+//!   security-team:
+//!     - FYlr8YoYGVvDwHQxqEIs89reKKDy-oWisoO0qXXEfHE
+//! rules:
+//!   - crates: ["ring", "rustls*"]
+//!     reviewers: [security-team]
+//!     min-reviews: 1
+//!   - crates: ["tokio*"]
+//!     reviewers: [async-reviewers@FYlr8YoYGVvDwHQxqEIs89reKKDy-oWisoO0qXXEfHE]
+//! ```
+//!
+//! For the requirements of a crate, the first entry matching its name applies.
+//! All matching `rules` apply. Their `reviewers` are group names, Ids, or
+//! `NAME@ID` groups published by the Id (`id group publish`), with their
+//! current members as far as the fetched proofs go.
+//!
+//! Frontends can keep settings of their own in the same file; keys this module
+//! doesn't know are left for them.
+use crate::{Error, Result, VerificationRequirements};
+use chrono::NaiveDate;
+use crev_data::{
+    proof::{self, CommonOps},
+    Digest, Id, Level, Rating, Version,
+};
+use serde::{Deserialize, Deserializer};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    path::{Path, PathBuf},
+};
+
+/// Names of the policy file, in order of preference
+pub const FILE_NAMES: [&str; 2] = [".crev-policy.yaml", "crev-policy.yaml"];
+
+/// The policy file in `dir`, if there is one
+#[must_use]
+pub fn find_in(dir: &Path) -> Option<PathBuf> {
+    FILE_NAMES
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.exists())
+}
+
+/// Simple glob: `*` matches any (possibly empty) sequence of characters
+#[must_use]
+pub fn crate_name_matches(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<_> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // no `*` at all
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(pos) => rest = &rest[pos + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+fn matches_any(patterns: &[String], name: &str) -> bool {
+    patterns
+        .iter()
+        .any(|pattern| crate_name_matches(pattern, name))
+}
+
+fn deserialize_date<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NaiveDate, D::Error> {
+    let date = String::deserialize(deserializer)?;
+    NaiveDate::parse_from_str(&date, "%Y-%m-%d").map_err(serde::de::Error::custom)
+}
+
+/// Verification requirements for some crates; settings that aren't given are the global ones
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct RequirementsRule {
+    /// Crate names; `*` matches any characters
+    crates: Vec<String>,
+    trust_level: Option<Level>,
+    understanding: Option<Level>,
+    thoroughness: Option<Level>,
+    redundancy: Option<u64>,
+}
+
+impl RequirementsRule {
+    fn apply(&self, global: &VerificationRequirements) -> VerificationRequirements {
+        VerificationRequirements {
+            trust_level: self.trust_level.unwrap_or(global.trust_level),
+            understanding: self.understanding.unwrap_or(global.understanding),
+            thoroughness: self.thoroughness.unwrap_or(global.thoroughness),
+            redundancy: self.redundancy.unwrap_or(global.redundancy),
+        }
+    }
+}
+
+/// As written in the policy, eg. `serde*: redundancy 2`
+impl fmt::Display for RequirementsRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut settings = vec![];
+        if let Some(level) = self.trust_level {
+            settings.push(format!("trust-level {level}"));
+        }
+        if let Some(level) = self.understanding {
+            settings.push(format!("understanding {level}"));
+        }
+        if let Some(level) = self.thoroughness {
+            settings.push(format!("thoroughness {level}"));
+        }
+        if let Some(redundancy) = self.redundancy {
+            settings.push(format!("redundancy {redundancy}"));
+        }
+        write!(f, "{}: {}", self.crates.join(", "), settings.join(", "))
+    }
+}
+
+/// Crates that don't have to pass verification until a date
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Exemption {
+    /// Crate names; `*` matches any characters
+    crates: Vec<String>,
+    /// Last day it applies
+    #[serde(deserialize_with = "deserialize_date")]
+    pub until: NaiveDate,
+    #[serde(default)]
+    pub reason: String,
+}
+
+/// As written in the policy, eg. `internal-*: until 2025-06-01 (vendored)`
+impl fmt::Display for Exemption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: until {}", self.crates.join(", "), self.until)?;
+        if !self.reason.is_empty() {
+            write!(f, " ({})", self.reason)?;
+        }
+        Ok(())
+    }
+}
+
+/// Crates that need positive reviews by some Ids, as written
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct ReviewerRuleSpec {
+    /// Crate names; `*` matches any characters
+    crates: Vec<String>,
+    /// Group names, published groups (`NAME@ID`) or Ids
+    reviewers: Vec<String>,
+    #[serde(default = "default_min_reviews")]
+    min_reviews: usize,
+}
+
+fn default_min_reviews() -> usize {
+    1
+}
+
+/// Crates that need positive reviews by some Ids, with the reviewers resolved
+#[derive(Debug, Clone)]
+pub struct ReviewerRule {
+    crates: Vec<String>,
+    /// `reviewers` as written, for messages
+    reviewers_label: String,
+    ids: HashSet<Id>,
+    /// Published groups, as owner and name; members are looked up when checking
+    published_groups: Vec<(Id, String)>,
+    min_reviews: usize,
+}
+
+impl ReviewerRule {
+    fn resolve(
+        spec: ReviewerRuleSpec,
+        groups: &HashMap<String, Vec<String>>,
+    ) -> Result<Self, String> {
+        let parse_id = |s: &str| {
+            Id::crevid_from_str(s)
+                .map_err(|e| format!("`{s}` is neither a group nor a valid Id: {e}"))
+        };
+        let mut ids = HashSet::new();
+        let mut published_groups = vec![];
+        for reviewer in &spec.reviewers {
+            match groups.get(reviewer) {
+                Some(group) => {
+                    for id in group {
+                        ids.insert(parse_id(id)?);
+                    }
+                }
+                None => match reviewer.rsplit_once('@') {
+                    Some((name, owner)) => {
+                        let owner = Id::crevid_from_str(owner)
+                            .map_err(|e| format!("'{owner}' is not a valid crev Id: {e}"))?;
+                        if !proof::is_valid_group_name(name) {
+                            return Err(format!("'{name}' is not a valid group name"));
+                        }
+                        published_groups.push((owner, name.to_owned()));
+                    }
+                    None => {
+                        ids.insert(parse_id(reviewer)?);
+                    }
+                },
+            }
+        }
+        Ok(Self {
+            crates: spec.crates,
+            reviewers_label: spec.reviewers.join(", "),
+            ids,
+            published_groups,
+            min_reviews: spec.min_reviews,
+        })
+    }
+
+    /// Why the crate with `digest` doesn't meet the rule, if it doesn't
+    fn check(
+        &self,
+        name: &str,
+        version: &Version,
+        digest: &Digest,
+        db: &crev_wot::ProofDB,
+    ) -> Option<String> {
+        let group_members: HashSet<&Id> = self
+            .published_groups
+            .iter()
+            .filter_map(|(owner, name)| db.get_group(owner, name))
+            .flat_map(|group| group.ids.iter().map(|member| &member.id))
+            .collect();
+        let count = db
+            .get_package_reviews_by_digest(digest)
+            .filter(|review| {
+                self.ids.contains(&review.from().id) || group_members.contains(&review.from().id)
+            })
+            .filter(|review| {
+                let review = review.review_possibly_none();
+                !review.is_none() && Rating::Neutral <= review.rating
+            })
+            .count();
+        (count < self.min_reviews).then(|| {
+            format!(
+                "{} {}: needs {} positive review{} by {}, found {}",
+                name,
+                version,
+                self.min_reviews,
+                if self.min_reviews == 1 { "" } else { "s" },
+                self.reviewers_label,
+                count
+            )
+        })
+    }
+}
+
+/// The policy file, see the module docs
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ProjectPolicy {
+    trust_level: Option<Level>,
+    understanding: Option<Level>,
+    thoroughness: Option<Level>,
+    redundancy: Option<u64>,
+    #[serde(default)]
+    requirements: Vec<RequirementsRule>,
+    #[serde(default)]
+    exemptions: Vec<Exemption>,
+    /// Named lists of Ids, usable in `reviewers` of `rules`
+    #[serde(default)]
+    groups: HashMap<String, Vec<String>>,
+    #[serde(default)]
+    rules: Vec<ReviewerRuleSpec>,
+    /// `rules`, resolved when parsing
+    #[serde(skip)]
+    reviewer_rules: Vec<ReviewerRule>,
+}
+
+impl ProjectPolicy {
+    fn parse(content: &str) -> Result<Self, String> {
+        let mut policy: Self = serde_yaml::from_str(content).map_err(|e| e.to_string())?;
+        policy.reviewer_rules = std::mem::take(&mut policy.rules)
+            .into_iter()
+            .map(|spec| ReviewerRule::resolve(spec, &policy.groups))
+            .collect::<Result<_, _>>()?;
+        if let Some(rule) = policy
+            .requirements
+            .iter()
+            .find(|rule| rule.crates.is_empty())
+        {
+            return Err(format!("requirements `{rule}` don't apply to any crates"));
+        }
+        if let Some(exemption) = policy.exemptions.iter().find(|e| e.crates.is_empty()) {
+            return Err(format!(
+                "exemption `{exemption}` doesn't apply to any crates"
+            ));
+        }
+        Ok(policy)
+    }
+
+    pub fn from_yaml(content: &str) -> Result<Self> {
+        Self::parse(content).map_err(|e| Error::InvalidProjectPolicy(e.into()))
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        Self::parse(&content)
+            .map_err(|e| Error::InvalidProjectPolicy(format!("{}: {e}", path.display()).into()))
+    }
+
+    /// `global` requirements, made stricter by those of the project
+    #[must_use]
+    pub fn requirements(&self, global: &VerificationRequirements) -> VerificationRequirements {
+        global.stricter(&VerificationRequirements {
+            trust_level: self.trust_level.unwrap_or(global.trust_level),
+            understanding: self.understanding.unwrap_or(global.understanding),
+            thoroughness: self.thoroughness.unwrap_or(global.thoroughness),
+            redundancy: self.redundancy.unwrap_or(global.redundancy),
+        })
+    }
+
+    /// Requirements for the crate `name`: those of the project, overridden by
+    /// the first matching rule, if any
+    #[must_use]
+    pub fn requirements_for(
+        &self,
+        name: &str,
+        global: &VerificationRequirements,
+    ) -> (VerificationRequirements, Option<&RequirementsRule>) {
+        let project = self.requirements(global);
+        match self
+            .requirements
+            .iter()
+            .find(|rule| matches_any(&rule.crates, name))
+        {
+            Some(rule) => (rule.apply(&project), Some(rule)),
+            None => (project, None),
+        }
+    }
+
+    #[must_use]
+    pub fn has_exemptions(&self) -> bool {
+        !self.exemptions.is_empty()
+    }
+
+    /// The exemption of the crate `name` that still applies on `today`, if any
+    #[must_use]
+    pub fn exemption_for(&self, name: &str, today: NaiveDate) -> Option<&Exemption> {
+        self.exemptions
+            .iter()
+            .find(|exemption| today <= exemption.until && matches_any(&exemption.crates, name))
+    }
+
+    /// Exemptions that don't apply on `today` anymore, to be removed from the policy
+    pub fn expired_exemptions(&self, today: NaiveDate) -> impl Iterator<Item = &Exemption> {
+        self.exemptions
+            .iter()
+            .filter(move |exemption| exemption.until < today)
+    }
+
+    /// Why the crate `name` with `digest` doesn't meet the reviewer `rules`;
+    /// empty if it does
+    #[must_use]
+    pub fn check_reviewers(
+        &self,
+        name: &str,
+        version: &Version,
+        digest: &Digest,
+        db: &crev_wot::ProofDB,
+    ) -> Vec<String> {
+        self.reviewer_rules
+            .iter()
+            .filter(|rule| matches_any(&rule.crates, name))
+            .filter_map(|rule| rule.check(name, version, digest, db))
+            .collect()
+    }
+}
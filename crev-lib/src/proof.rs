@@ -7,6 +7,13 @@ fn proof_store_names(proof: &proof::Proof) -> (&str, Option<&str>) {
         proof::CodeReview::KIND => ("reviews", Some("code")),
         proof::PackageReview::KIND => ("reviews", Some("package")),
         proof::Trust::KIND => ("trust", None),
+        proof::Epoch::KIND => ("epoch", None),
+        proof::ReviewRequest::KIND => ("requests", None),
+        proof::IssueUpdate::KIND => ("issues", None),
+        proof::Group::KIND => ("groups", None),
+        proof::Successor::KIND => ("successors", None),
+        proof::Waiver::KIND => ("waivers", None),
+        proof::Encrypted::KIND => ("encrypted", None),
         _ => ("other", None),
     }
 }
@@ -58,7 +65,9 @@ pub fn store_id_trust_proof(
     Ok(())
 }
 
-fn create_id_trust_commit_message(ids: &[crev_data::Id], trust_level: TrustLevel) -> String {
+/// Commit message of a trust proof for `ids`, eg. `Add trust for <id>`
+#[must_use]
+pub fn create_id_trust_commit_message(ids: &[crev_data::Id], trust_level: TrustLevel) -> String {
     let string_ids = ids
         .iter()
         .map(|id| id.to_string())
@@ -0,0 +1,253 @@
+//! On-disk cache of proofs already read and checked
+//!
+//! `Local::load_db` reads every proof file of every proof repo, and checks
+//! every signature, on every run. With the `proof-cache` feature, proofs of
+//! each file are kept in an embedded database (sled), keyed by the digest of
+//! the file's content, and the size and modification time of each file path
+//! are recorded with that digest. Files whose size and modification time
+//! didn't change since the last run aren't read again; other files are read
+//! and hashed, and found by their digest if the content is the same. Either
+//! way, cached proofs are rebuilt from their stored body and signature without
+//! checking signatures again.
+//!
+//! Entries of files that weren't read during a `load_db` are removed after it,
+//! so the cache only holds the proof repos as they are now.
+use crev_data::proof;
+#[cfg(feature = "proof-cache")]
+use crev_data::proof::CommonOps;
+#[cfg(feature = "proof-cache")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "proof-cache")]
+use std::{
+    collections::HashSet,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+use std::{fs::Metadata, path::Path};
+
+/// Files modified more recently than that may change again without a
+/// different modification time, so their stats aren't recorded
+#[cfg(feature = "proof-cache")]
+const RACY_MODIFICATION: Duration = Duration::from_secs(2);
+
+/// Size and modification time of a proof file, and the digest of its content then
+#[cfg(feature = "proof-cache")]
+#[derive(Serialize, Deserialize, PartialEq)]
+struct FileStats {
+    size: u64,
+    modified_secs: u64,
+    modified_nanos: u32,
+    digest: Vec<u8>,
+}
+
+#[cfg(feature = "proof-cache")]
+impl FileStats {
+    /// `None` if the modification time is unknown, or too recent to rely on
+    fn new(metadata: &Metadata, digest: Vec<u8>) -> Option<Self> {
+        let modified = metadata.modified().ok()?;
+        if SystemTime::now().duration_since(modified).ok()? < RACY_MODIFICATION {
+            return None;
+        }
+        let modified = modified.duration_since(SystemTime::UNIX_EPOCH).ok()?;
+        Some(Self {
+            size: metadata.len(),
+            modified_secs: modified.as_secs(),
+            modified_nanos: modified.subsec_nanos(),
+            digest,
+        })
+    }
+}
+
+/// A proof as read from a proof file
+#[cfg(feature = "proof-cache")]
+#[derive(Serialize, Deserialize)]
+struct CachedProof {
+    body: String,
+    signature: String,
+    /// Only needed for proofs in the legacy format, without `kind` in the body
+    kind: String,
+}
+
+#[cfg(feature = "proof-cache")]
+impl CachedProof {
+    fn new(proof: &proof::Proof) -> Self {
+        Self {
+            body: proof.body().to_owned(),
+            signature: proof.signature().to_owned(),
+            kind: proof.kind().to_owned(),
+        }
+    }
+
+    fn into_proof(self) -> Option<proof::Proof> {
+        match proof::Proof::from_parts(self.body.clone(), self.signature.clone()) {
+            Ok(proof) => Some(proof),
+            Err(crev_data::Error::KindFieldMissing) => {
+                proof::Proof::from_legacy_parts(self.body, self.signature, self.kind).ok()
+            }
+            Err(_) => None,
+        }
+    }
+}
+
+/// Proofs with valid signatures of proof files, by the digest of the file
+///
+/// Cloning is cheap; clones share the database.
+#[derive(Clone)]
+pub struct ProofCache {
+    #[cfg(feature = "proof-cache")]
+    tree: sled::Tree,
+    /// `FileStats` by file path
+    #[cfg(feature = "proof-cache")]
+    stats_tree: sled::Tree,
+    /// Keys of both trees looked up since opening, see `prune_unused`
+    #[cfg(feature = "proof-cache")]
+    used: Arc<Mutex<HashSet<Vec<u8>>>>,
+    /// Paths under it are kept relative to it, so they stay valid if it's moved
+    #[cfg(feature = "proof-cache")]
+    root: Option<PathBuf>,
+}
+
+#[cfg(feature = "proof-cache")]
+impl ProofCache {
+    /// Open (or create) the cache in `dir`
+    ///
+    /// Files under `root` are recorded by their path relative to it, see
+    /// [`crate::local::PORTABLE_ROOT_ENV`]. Fails if another process has it open.
+    pub fn open(dir: &Path, root: Option<&Path>) -> Result<Self, sled::Error> {
+        let db = sled::open(dir)?;
+        Ok(Self {
+            tree: db.open_tree("proof-files")?,
+            stats_tree: db.open_tree("file-stats")?,
+            used: Arc::default(),
+            root: root.map(Path::to_owned),
+        })
+    }
+
+    fn path_key(&self, path: &Path) -> Vec<u8> {
+        let path = self
+            .root
+            .as_deref()
+            .and_then(|root| path.strip_prefix(root).ok())
+            .unwrap_or(path);
+        path.to_string_lossy().as_bytes().to_vec()
+    }
+
+    fn get_by_digest(&self, key: Vec<u8>) -> Option<Vec<proof::Proof>> {
+        let value = self.tree.get(&key).ok().flatten();
+        self.used.lock().unwrap().insert(key);
+        let cached: Vec<CachedProof> = serde_cbor::from_slice(&value?).ok()?;
+        cached.into_iter().map(CachedProof::into_proof).collect()
+    }
+
+    /// Record that the file at `path`, as it is now, has content with `digest`
+    fn record_stats(&self, path: &Path, metadata: &Metadata, digest: Vec<u8>) {
+        let key = self.path_key(path);
+        if let Some(value) =
+            FileStats::new(metadata, digest).and_then(|stats| serde_cbor::to_vec(&stats).ok())
+        {
+            let _ = self.stats_tree.insert(key.as_slice(), value);
+        } else {
+            let _ = self.stats_tree.remove(key.as_slice());
+        }
+        self.used.lock().unwrap().insert(key);
+    }
+
+    /// Proofs of the file at `path`, if it has the same size and modification
+    /// time as when it was read before
+    ///
+    /// The proofs had valid signatures when they were stored.
+    pub fn get_unchanged(&self, path: &Path, metadata: &Metadata) -> Option<Vec<proof::Proof>> {
+        let value = self.stats_tree.get(self.path_key(path)).ok().flatten()?;
+        let recorded: FileStats = serde_cbor::from_slice(&value).ok()?;
+        if FileStats::new(metadata, recorded.digest.clone()).as_ref() != Some(&recorded) {
+            return None;
+        }
+        let proofs = self.get_by_digest(recorded.digest)?;
+        self.used.lock().unwrap().insert(self.path_key(path));
+        Some(proofs)
+    }
+
+    /// Proofs of the file at `path` with this `content`, if a file with the
+    /// same content was read before
+    ///
+    /// The proofs had valid signatures when they were stored.
+    pub fn get(
+        &self,
+        path: &Path,
+        metadata: &Metadata,
+        content: &[u8],
+    ) -> Option<Vec<proof::Proof>> {
+        let key = crev_common::blake2b256sum(content).to_vec();
+        let proofs = self.get_by_digest(key.clone())?;
+        self.record_stats(path, metadata, key);
+        Some(proofs)
+    }
+
+    /// Remember the checked `proofs` of the file at `path` with this `content`
+    pub fn insert(
+        &self,
+        path: &Path,
+        metadata: &Metadata,
+        content: &[u8],
+        proofs: &[proof::Proof],
+    ) {
+        let key = crev_common::blake2b256sum(content).to_vec();
+        let cached: Vec<_> = proofs.iter().map(CachedProof::new).collect();
+        let res = serde_cbor::to_vec(&cached)
+            .map_err(|e| e.to_string())
+            .and_then(|value| {
+                self.tree
+                    .insert(key.as_slice(), value)
+                    .map_err(|e| e.to_string())
+            });
+        if let Err(e) = res {
+            log::debug!("Can't cache proofs: {e}");
+            return;
+        }
+        self.used.lock().unwrap().insert(key.clone());
+        self.record_stats(path, metadata, key);
+    }
+
+    /// Remove entries of files that weren't looked up since opening
+    pub fn prune_unused(&self) {
+        let used = self.used.lock().unwrap();
+        for tree in [&self.tree, &self.stats_tree] {
+            for key in tree.iter().keys().flatten() {
+                if !used.contains(key.as_ref()) {
+                    let _ = tree.remove(key);
+                }
+            }
+            if let Err(e) = tree.flush() {
+                log::debug!("Can't write the proof cache: {e}");
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "proof-cache"))]
+impl ProofCache {
+    pub fn get_unchanged(&self, _path: &Path, _metadata: &Metadata) -> Option<Vec<proof::Proof>> {
+        None
+    }
+
+    pub fn get(
+        &self,
+        _path: &Path,
+        _metadata: &Metadata,
+        _content: &[u8],
+    ) -> Option<Vec<proof::Proof>> {
+        None
+    }
+
+    pub fn insert(
+        &self,
+        _path: &Path,
+        _metadata: &Metadata,
+        _content: &[u8],
+        _proofs: &[proof::Proof],
+    ) {
+    }
+
+    pub fn prune_unused(&self) {}
+}
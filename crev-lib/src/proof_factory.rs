@@ -0,0 +1,213 @@
+//! Creating signed proofs without a local crev config directory
+//!
+//! `Local` reads the Id, the proof database and activity records from disk.
+//! Services that keep all of that elsewhere (eg. a review web app)
+//! can use [`ProofFactory`] to turn in-memory inputs into signed
+//! [`proof::Proof`]s, and store them wherever they want.
+use crate::{id::LockedId, Result};
+use crev_data::{
+    proof::{
+        self,
+        review::package::{Advisory, Flags, Issue},
+        ContentExt, OverrideItem,
+    },
+    Digest, PublicId, Review, TrustLevel, UnlockedId, Version,
+};
+use std::collections::HashSet;
+
+/// Creates and signs proofs by one Id
+pub struct ProofFactory {
+    id: UnlockedId,
+}
+
+impl ProofFactory {
+    #[must_use]
+    pub fn new(id: UnlockedId) -> Self {
+        Self { id }
+    }
+
+    /// Unlock an Id stored by the caller (eg. the YAML of a `LockedId`)
+    pub fn from_locked_id(locked_id: &LockedId, passphrase: &str) -> Result<Self> {
+        Ok(Self::new(locked_id.to_unlocked(passphrase)?))
+    }
+
+    #[must_use]
+    pub fn id(&self) -> &PublicId {
+        &self.id.id
+    }
+
+    /// Start a trust proof for `ids` (with their proof repo URLs, if known)
+    #[must_use]
+    pub fn trust(&self, ids: Vec<PublicId>, trust_level: TrustLevel) -> TrustProofBuilder<'_> {
+        TrustProofBuilder {
+            factory: self,
+            ids,
+            trust_level,
+            comment: String::new(),
+            override_: vec![],
+        }
+    }
+
+    /// Start a package review proof, with an empty (none) review
+    #[must_use]
+    pub fn package_review(&self, package: proof::PackageInfo) -> PackageReviewProofBuilder<'_> {
+        PackageReviewProofBuilder {
+            factory: self,
+            package,
+            diff_base: None,
+            review: Review::new_none(),
+            issues: vec![],
+            advisories: vec![],
+            flags: Flags::default(),
+            alternatives: HashSet::new(),
+            comment: String::new(),
+            override_: vec![],
+        }
+    }
+}
+
+/// `PackageInfo` of a crate release, with its crev digest
+#[must_use]
+pub fn package_info(
+    source: &str,
+    name: &str,
+    version: Version,
+    digest: &Digest,
+) -> proof::PackageInfo {
+    proof::PackageInfo {
+        id: proof::PackageVersionId::new(source.to_owned(), name.to_owned(), version),
+        digest: digest.as_slice().to_vec(),
+        digest_type: proof::default_digest_type(),
+        revision: String::new(),
+        revision_type: proof::default_revision_type(),
+        digest_ignore: None,
+    }
+}
+
+/// See [`ProofFactory::trust`]
+pub struct TrustProofBuilder<'f> {
+    factory: &'f ProofFactory,
+    ids: Vec<PublicId>,
+    trust_level: TrustLevel,
+    comment: String,
+    override_: Vec<OverrideItem>,
+}
+
+impl TrustProofBuilder<'_> {
+    #[must_use]
+    pub fn comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = comment.into();
+        self
+    }
+
+    #[must_use]
+    pub fn override_(mut self, override_: Vec<OverrideItem>) -> Self {
+        self.override_ = override_;
+        self
+    }
+
+    /// Unsigned content, eg. to show it for confirmation first
+    pub fn build(self) -> Result<proof::Trust> {
+        if self.ids.is_empty() {
+            return Err(crate::Error::NoIdsGiven);
+        }
+        let mut trust =
+            self.factory
+                .id
+                .id
+                .create_trust_proof(&self.ids, self.trust_level, self.override_)?;
+        trust.comment = self.comment;
+        Ok(trust)
+    }
+
+    pub fn sign(self) -> Result<proof::Proof> {
+        let factory = self.factory;
+        Ok(self.build()?.sign_by(&factory.id)?)
+    }
+}
+
+/// See [`ProofFactory::package_review`]
+pub struct PackageReviewProofBuilder<'f> {
+    factory: &'f ProofFactory,
+    package: proof::PackageInfo,
+    diff_base: Option<proof::PackageInfo>,
+    review: Review,
+    issues: Vec<Issue>,
+    advisories: Vec<Advisory>,
+    flags: Flags,
+    alternatives: HashSet<proof::PackageId>,
+    comment: String,
+    override_: Vec<OverrideItem>,
+}
+
+impl PackageReviewProofBuilder<'_> {
+    #[must_use]
+    pub fn review(mut self, review: Review) -> Self {
+        self.review = review;
+        self
+    }
+
+    /// Make it a differential review against an older version
+    #[must_use]
+    pub fn diff_base(mut self, diff_base: proof::PackageInfo) -> Self {
+        self.diff_base = Some(diff_base);
+        self
+    }
+
+    #[must_use]
+    pub fn issue(mut self, issue: Issue) -> Self {
+        self.issues.push(issue);
+        self
+    }
+
+    #[must_use]
+    pub fn advisory(mut self, advisory: Advisory) -> Self {
+        self.advisories.push(advisory);
+        self
+    }
+
+    #[must_use]
+    pub fn flags(mut self, flags: Flags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    #[must_use]
+    pub fn alternative(mut self, alternative: proof::PackageId) -> Self {
+        self.alternatives.insert(alternative);
+        self
+    }
+
+    #[must_use]
+    pub fn comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = comment.into();
+        self
+    }
+
+    #[must_use]
+    pub fn override_(mut self, override_: Vec<OverrideItem>) -> Self {
+        self.override_ = override_;
+        self
+    }
+
+    /// Unsigned content, eg. to show it for confirmation first
+    pub fn build(self) -> Result<proof::review::Package> {
+        let mut review = self.factory.id.id.create_package_review_proof(
+            self.package,
+            self.review,
+            self.override_,
+            self.comment,
+        )?;
+        review.diff_base = self.diff_base;
+        review.issues = self.issues;
+        review.advisories = self.advisories;
+        review.flags = self.flags;
+        review.alternatives = self.alternatives;
+        Ok(review)
+    }
+
+    pub fn sign(self) -> Result<proof::Proof> {
+        let factory = self.factory;
+        Ok(self.build()?.sign_by(&factory.id)?)
+    }
+}
@@ -0,0 +1,136 @@
+//! Machine-readable summary of a proof repository
+//!
+//! `index.json` lists the package reviews of an Id, so that web viewers
+//! and aggregators can read a proof repo without parsing every proof file.
+//! The digest of `index.json` is recorded in `index-manifest.json`,
+//! which is signed by the Id.
+use crate::{Error, Result};
+use crev_common::serde::{as_base64, from_base64};
+use crev_data::{
+    id::UnlockedId,
+    proof::{self, review::Rating, CommonOps},
+    Id, Version,
+};
+use serde::{Deserialize, Serialize};
+
+pub const INDEX_FILE_NAME: &str = "index.json";
+pub const MANIFEST_FILE_NAME: &str = "index-manifest.json";
+
+const CURRENT_INDEX_SERIALIZATION_VERSION: i64 = -1;
+
+/// One package review in the index
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndexEntry {
+    pub source: String,
+    #[serde(rename = "crate")]
+    pub name: String,
+    pub version: Version,
+    #[serde(serialize_with = "as_base64", deserialize_with = "from_base64")]
+    pub digest: Vec<u8>,
+    pub rating: Rating,
+    /// RFC 3339
+    pub date: String,
+    /// Signature of the proof, to find it in the proof files
+    pub signature: String,
+}
+
+/// Content of `index.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProofIndex {
+    pub version: i64,
+    pub id: String,
+    pub reviews: Vec<IndexEntry>,
+}
+
+impl ProofIndex {
+    /// Index of package reviews made by `id` among `proofs`
+    pub fn from_proofs(id: &Id, proofs: impl Iterator<Item = proof::Proof>) -> Self {
+        let mut reviews: Vec<_> = proofs
+            .filter(|proof| proof.kind() == proof::PackageReview::KIND && proof.author_id() == id)
+            .filter_map(|proof| {
+                let review = proof.parse_content::<proof::review::Package>().ok()?;
+                Some(IndexEntry {
+                    source: review.package.id.id.source.clone(),
+                    name: review.package.id.id.name.clone(),
+                    version: review.package.id.version.clone(),
+                    digest: review.package.digest.clone(),
+                    rating: review.review_possibly_none().rating,
+                    date: review.date().to_rfc3339(),
+                    signature: proof.signature().to_owned(),
+                })
+            })
+            .collect();
+        reviews.sort_by(|a, b| {
+            (&a.source, &a.name, &a.version, &a.date)
+                .cmp(&(&b.source, &b.name, &b.version, &b.date))
+        });
+
+        Self {
+            version: CURRENT_INDEX_SERIALIZATION_VERSION,
+            id: id.to_string(),
+            reviews,
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+/// The signed part of `index-manifest.json`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestBody {
+    pub version: i64,
+    pub id: String,
+    /// RFC 3339
+    pub date: String,
+    /// blake2b256 of the exact content of `index.json`
+    #[serde(
+        rename = "index-digest",
+        serialize_with = "as_base64",
+        deserialize_with = "from_base64"
+    )]
+    pub index_digest: Vec<u8>,
+}
+
+impl ManifestBody {
+    /// The bytes that get signed: compact JSON of the body
+    fn signed_bytes(&self) -> Result<Vec<u8>> {
+        Ok(serde_json::to_vec(self)?)
+    }
+}
+
+/// Content of `index-manifest.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedManifest {
+    #[serde(flatten)]
+    pub body: ManifestBody,
+    pub signature: String,
+}
+
+impl SignedManifest {
+    pub fn sign(id: &UnlockedId, index_json: &[u8]) -> Result<Self> {
+        let body = ManifestBody {
+            version: CURRENT_INDEX_SERIALIZATION_VERSION,
+            id: id.id.id.to_string(),
+            date: crev_common::now().to_rfc3339(),
+            index_digest: crev_common::blake2b256sum(index_json).to_vec(),
+        };
+        let signature = crev_common::base64_encode(&id.sign(&body.signed_bytes()?));
+        Ok(Self { body, signature })
+    }
+
+    /// Check that `index_json` is the one this manifest was signed for
+    pub fn verify(&self, index_json: &[u8]) -> Result<()> {
+        if crev_common::blake2b256sum(index_json).as_slice() != self.body.index_digest.as_slice() {
+            return Err(Error::ProofIndexDigestMismatch);
+        }
+        let id = Id::crevid_from_str(&self.body.id)?;
+        id.verify_signature(&self.body.signed_bytes()?, &self.signature)?;
+        Ok(())
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
@@ -0,0 +1,80 @@
+//! Who to trust next
+//!
+//! Ranks Ids you don't trust yet by how many of your unverified dependencies
+//! would pass verification if you trusted them.
+use crate::{verify_package_digest, VerificationRequirements, VerificationStatus};
+use crev_data::{Digest, Id, TrustLevel};
+use crev_wot::{ProofDB, TrustSet};
+use std::collections::HashMap;
+
+/// An Id worth trusting, see [`recommend_ids_to_trust`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrustRecommendation {
+    pub id: Id,
+    /// Unverified digests that would become verified
+    pub covered: usize,
+    /// Unverified digests reviewed by it that Ids you trust reviewed too
+    pub overlap: usize,
+}
+
+/// Rank untrusted reviewers of `unverified` digests by how many of them trusting
+/// the reviewer would verify
+///
+/// The trust is simulated at the lowest level that counts for `requirements`,
+/// and only for the reviewer itself, not the Ids it trusts. Best first;
+/// reviewers that wouldn't verify anything are left out.
+#[must_use]
+pub fn recommend_ids_to_trust(
+    for_id: &Id,
+    unverified: &[Digest],
+    trust_set: &TrustSet,
+    requirements: &VerificationRequirements,
+    db: &ProofDB,
+) -> Vec<TrustRecommendation> {
+    let mut reviewed_by: HashMap<Id, Vec<&Digest>> = HashMap::new();
+    let mut reviewed_by_trusted: Vec<&Digest> = vec![];
+    for digest in unverified {
+        for review in db.get_package_reviews_by_digest(digest) {
+            let id = review.common.from.id;
+            if trust_set.is_trusted(&id) {
+                reviewed_by_trusted.push(digest);
+            } else if &id != for_id && !trust_set.is_distrusted(&id) {
+                let digests = reviewed_by.entry(id).or_default();
+                if !digests.contains(&digest) {
+                    digests.push(digest);
+                }
+            }
+        }
+    }
+
+    let level = TrustLevel::from(requirements.trust_level).max(TrustLevel::Low);
+    let mut recommendations: Vec<_> = reviewed_by
+        .into_iter()
+        .filter_map(|(id, digests)| {
+            let trust_set = trust_set.with_directly_trusted(for_id, &id, level);
+            let covered = digests
+                .iter()
+                .filter(|&&digest| {
+                    verify_package_digest(digest, &trust_set, requirements, db)
+                        == VerificationStatus::Verified
+                })
+                .count();
+            let overlap = digests
+                .iter()
+                .filter(|&&digest| reviewed_by_trusted.contains(&digest))
+                .count();
+            (covered > 0).then_some(TrustRecommendation {
+                id,
+                covered,
+                overlap,
+            })
+        })
+        .collect();
+    recommendations.sort_by(|a, b| {
+        b.covered
+            .cmp(&a.covered)
+            .then(b.overlap.cmp(&a.overlap))
+            .then_with(|| a.id.cmp(&b.id))
+    });
+    recommendations
+}
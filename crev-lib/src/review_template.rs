@@ -0,0 +1,160 @@
+//! Review templates: an organization's minimal checklist for package reviews
+//!
+//! A `review-template.yaml` in the proof repository (shared with everyone
+//! using it), or else in the user config dir, adds sections to the comment of
+//! new review drafts, and lists what a review must have before it's signed:
+//!
+//! ```yaml
+//! sections:
+//!   - title: Unsafe code
+//!     hint: Is every `unsafe` block sound?
+//!     required: true
+//!   - title: Build script
+//! min-thoroughness: low
+//! min-understanding: medium
+//! require-comment: true
+//! ```
+//!
+//! Sections are `## <title>` lines in the comment; a required section
+//! needs some text under its title.
+use crate::{Error, Result};
+use crev_data::{proof::review, Level};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+pub const REVIEW_TEMPLATE_FILE_NAME: &str = "review-template.yaml";
+
+const SECTION_PREFIX: &str = "## ";
+
+/// A part of the review comment the template asks for
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct TemplateSection {
+    pub title: String,
+    /// What to write about, shown above the draft
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct ReviewTemplate {
+    #[serde(default)]
+    pub sections: Vec<TemplateSection>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_thoroughness: Option<Level>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_understanding: Option<Level>,
+    #[serde(default)]
+    pub require_comment: bool,
+}
+
+/// Text of the `## <title>` sections of `comment`, by title
+fn comment_sections(comment: &str) -> Vec<(&str, String)> {
+    let mut sections: Vec<(&str, String)> = vec![];
+    for line in comment.lines() {
+        if let Some(title) = line.strip_prefix(SECTION_PREFIX) {
+            sections.push((title.trim(), String::new()));
+        } else if let Some((_, text)) = sections.last_mut() {
+            text.push_str(line);
+            text.push('\n');
+        }
+    }
+    sections
+}
+
+impl ReviewTemplate {
+    pub fn read_from_yaml_file(path: &Path) -> Result<Self> {
+        crev_common::read_from_yaml_file(path).map_err(|e| Error::ReviewTemplate(Box::new(e)))
+    }
+
+    /// Add the sections missing from the comment of `review`
+    pub fn pre_populate(&self, review: &mut review::Package) {
+        let existing: Vec<_> = comment_sections(&review.comment)
+            .into_iter()
+            .map(|(title, _)| title.to_owned())
+            .collect();
+        for section in &self.sections {
+            if existing.iter().any(|title| title == &section.title) {
+                continue;
+            }
+            if !review.comment.is_empty() {
+                review.comment.push_str("\n\n");
+            }
+            review.comment.push_str(SECTION_PREFIX);
+            review.comment.push_str(&section.title);
+            review.comment.push('\n');
+        }
+    }
+
+    /// The sections and their hints, to show above the draft
+    #[must_use]
+    pub fn draft_comment(&self) -> Option<String> {
+        if self.sections.is_empty() {
+            return None;
+        }
+        let mut text = String::from("Review template sections:");
+        for section in &self.sections {
+            text.push_str("\n  ");
+            text.push_str(&section.title);
+            if section.required {
+                text.push_str(" (required)");
+            }
+            if let Some(hint) = &section.hint {
+                text.push_str(": ");
+                text.push_str(hint);
+            }
+        }
+        Some(text)
+    }
+
+    /// What `review` lacks according to the template
+    #[must_use]
+    pub fn missing(&self, review: &review::Package) -> Vec<String> {
+        let mut missing = vec![];
+        let levels = review.review_possibly_none();
+        let levels = [
+            ("thoroughness", self.min_thoroughness, levels.thoroughness),
+            (
+                "understanding",
+                self.min_understanding,
+                levels.understanding,
+            ),
+        ];
+        for (name, min, level) in levels {
+            if let Some(min) = min.filter(|&min| level < min) {
+                missing.push(format!("{name} must be at least {min}"));
+            }
+        }
+        // pre-populated titles alone don't count
+        let has_text = review
+            .comment
+            .lines()
+            .any(|line| !line.starts_with(SECTION_PREFIX) && !line.trim().is_empty());
+        if self.require_comment && !has_text {
+            missing.push("comment is required".into());
+        }
+        let sections = comment_sections(&review.comment);
+        for section in self.sections.iter().filter(|s| s.required) {
+            let filled = sections
+                .iter()
+                .any(|(title, text)| *title == section.title && !text.trim().is_empty());
+            if !filled {
+                missing.push(format!("section `{}` is required", section.title));
+            }
+        }
+        missing
+    }
+
+    /// Fails if `review` lacks anything the template requires
+    pub fn ensure_satisfied(&self, review: &review::Package) -> Result<()> {
+        let missing = self.missing(review);
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::ReviewTemplateNotSatisfied(missing.join(", ").into()))
+        }
+    }
+}
@@ -1 +1,96 @@
+//! Proofs created, but not yet added to the proof repository
+//!
+//! Proofs can be kept aside in the data directory instead of being stored
+//! right away, so several can be made, looked at, and then dropped or
+//! committed in one batch with `Local::commit_staged_proofs`.
+use crate::Result;
+use crev_data::proof::{self, CommonOps};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
 
+const STAGED_PROOF_SUFFIX: &str = ".proof.crev";
+
+/// A staged proof, with the name to refer to it by
+#[derive(Debug)]
+pub struct StagedProof {
+    /// Digest of the proof, base64-encoded
+    pub name: String,
+    pub proof: proof::Proof,
+}
+
+/// Directory of staged proofs, one file each, named after their digests
+#[derive(Debug, Clone)]
+pub struct ProofStaging {
+    dir: PathBuf,
+}
+
+impl ProofStaging {
+    pub(crate) fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{name}{STAGED_PROOF_SUFFIX}"))
+    }
+
+    /// Stage `proof`, returning its name
+    pub fn add(&self, proof: &proof::Proof) -> Result<String> {
+        fs::create_dir_all(&self.dir)?;
+        let name = crev_common::base64_encode(proof.digest());
+        crev_common::store_str_to_file(&self.path(&name), &proof.to_string())?;
+        Ok(name)
+    }
+
+    /// All staged proofs, oldest first
+    pub fn list(&self) -> Result<Vec<StagedProof>> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(e.into()),
+        };
+        let mut staged = vec![];
+        for entry in entries {
+            let path = entry?.path();
+            let Some(name) = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_suffix(STAGED_PROOF_SUFFIX))
+            else {
+                continue;
+            };
+            for proof in proof::Proof::parse_from(fs::File::open(&path)?)? {
+                staged.push(StagedProof {
+                    name: name.to_owned(),
+                    proof,
+                });
+            }
+        }
+        staged.sort_by_key(|staged| staged.proof.date_utc());
+        Ok(staged)
+    }
+
+    /// Staged proofs with names starting with `prefix`
+    pub fn find(&self, prefix: &str) -> Result<Vec<StagedProof>> {
+        Ok(self
+            .list()?
+            .into_iter()
+            .filter(|staged| staged.name.starts_with(prefix))
+            .collect())
+    }
+
+    /// Unstage the proof named `name`. Returns `false` if there was none.
+    pub fn remove(&self, name: &str) -> Result<bool> {
+        match fs::remove_file(self.path(name)) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
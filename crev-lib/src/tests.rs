@@ -97,6 +97,115 @@ NtGu3z1Jtnj6wx8INBrVujcOPz61BiGmJS-UoAOe0XQutatFsEbgAcAo7rBvZz4Q-ccNXIFZtKnXhBDM
     Ok(())
 }
 
+#[test]
+fn validate_proof_file_finds_problems() -> Result<()> {
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let package = crev_data::proof::PackageInfo {
+        id: PackageVersionId::new(
+            "source".into(),
+            "name".into(),
+            Version::parse("1.0.0").unwrap(),
+        ),
+        revision: String::new(),
+        revision_type: crev_data::proof::default_revision_type(),
+        digest_ignore: None,
+        digest: vec![18; 32],
+        digest_type: crev_data::proof::default_digest_type(),
+    };
+    let proof = a
+        .as_public_id()
+        .create_package_review_proof(
+            package,
+            crev_data::proof::review::Review::new_positive(),
+            vec![],
+            "fine".into(),
+        )?
+        .sign_by(&a)?;
+
+    let file = format!("{proof}\n");
+    assert!(validate::validate_proof_file(file.as_bytes()).is_empty());
+
+    let not_canonical = format!("\n{proof}");
+    assert!(matches!(
+        validate::validate_proof_file(not_canonical.as_bytes())[..],
+        [validate::ProofProblem::NotCanonical]
+    ));
+
+    let tampered = file.replace("rating: positive", "rating: negative");
+    assert!(matches!(
+        validate::validate_proof_file(tampered.as_bytes())[..],
+        [validate::ProofProblem::InvalidSignature { index: 1, .. }]
+    ));
+
+    let truncated = &file[..file.len() / 2];
+    assert!(matches!(
+        validate::validate_proof_file(truncated.as_bytes())[..],
+        [validate::ProofProblem::Unparsable(_)]
+    ));
+
+    Ok(())
+}
+
+// Only the recipients can read an encrypted proof, and `ProofDB` indexes
+// it as is until someone who can decrypts it
+#[test]
+fn encrypted_proof_round_trip() -> Result<()> {
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let c = UnlockedId::generate_for_git_url("https://c");
+    let package = crev_data::proof::PackageInfo {
+        id: PackageVersionId::new(
+            "source".into(),
+            "name".into(),
+            Version::parse("1.0.0").unwrap(),
+        ),
+        revision: String::new(),
+        revision_type: crev_data::proof::default_revision_type(),
+        digest_ignore: None,
+        digest: vec![18; 32],
+        digest_type: crev_data::proof::default_digest_type(),
+    };
+    let review = a
+        .as_public_id()
+        .create_package_review_proof(
+            package,
+            crev_data::proof::review::Review::new_positive(),
+            vec![],
+            "internal only".into(),
+        )?
+        .sign_by(&a)?;
+
+    let proof = encryption::encrypt_proof(&review, &[b.id.id.clone()], &a)?;
+    assert!(!proof.to_string().contains("internal only"));
+    assert!(validate::validate_proof_file(format!("{proof}\n").as_bytes()).is_empty());
+    let encrypted: crev_data::proof::Encrypted = proof.parse_content()?;
+    assert_eq!(encrypted.recipients.len(), 2);
+
+    for id in [&a, &b] {
+        let decrypted = encryption::decrypt_proof(&encrypted, id)?.expect("a recipient");
+        assert_eq!(decrypted.signature(), review.signature());
+    }
+    assert!(encryption::decrypt_proof(&encrypted, &c)?.is_none());
+
+    let mut tampered = encrypted.clone();
+    *tampered.ciphertext.last_mut().unwrap() ^= 1;
+    assert!(encryption::decrypt_proof(&tampered, &b).is_err());
+
+    let mut db = ProofDB::new();
+    db.import_from_iter(vec![(proof, FetchSource::LocalUser)].into_iter());
+    assert_eq!(db.get_encrypted_proofs().count(), 1);
+    assert_eq!(db.get_package_review_count("source", Some("name"), None), 0);
+
+    db.import_decrypted(|encrypted| encryption::decrypt_proof(encrypted, &c).ok().flatten());
+    assert_eq!(db.get_encrypted_proofs().count(), 1);
+
+    db.import_decrypted(|encrypted| encryption::decrypt_proof(encrypted, &b).ok().flatten());
+    assert_eq!(db.get_encrypted_proofs().count(), 0);
+    assert_eq!(db.get_package_review_count("source", Some("name"), None), 1);
+
+    Ok(())
+}
+
 #[test]
 fn dont_consider_an_empty_review_as_valid() -> Result<()> {
     let url = FetchSource::Url(Arc::new(Url::new_git("https://a")));
@@ -110,6 +219,7 @@ fn dont_consider_an_empty_review_as_valid() -> Result<()> {
         ),
         revision: String::new(),
         revision_type: crev_data::proof::default_revision_type(),
+        digest_ignore: None,
         digest: digest.to_vec(),
         digest_type: crev_data::proof::default_digest_type(),
     };
@@ -140,3 +250,1265 @@ fn dont_consider_an_empty_review_as_valid() -> Result<()> {
 
     Ok(())
 }
+
+// Only positive reviews meeting the requirements count towards verification
+#[test]
+fn verifying_reviewers_meet_requirements() -> Result<()> {
+    let url = FetchSource::Url(Arc::new(Url::new_git("https://a")));
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let digest = [15; 32];
+    let package = crev_data::proof::PackageInfo {
+        id: PackageVersionId::new(
+            "source".into(),
+            "name".into(),
+            Version::parse("1.0.0").unwrap(),
+        ),
+        revision: String::new(),
+        revision_type: crev_data::proof::default_revision_type(),
+        digest_ignore: None,
+        digest: digest.to_vec(),
+        digest_type: crev_data::proof::default_digest_type(),
+    };
+
+    let review = crev_data::proof::review::Review::new_positive();
+    let proof = a
+        .as_public_id()
+        .create_package_review_proof(package, review, vec![], "fine".into())?
+        .sign_by(&a)?;
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(vec![proof].into_iter().map(|x| (x, url.clone())));
+    let trust_set = trustdb.calculate_trust_set(&a.id.id, &default());
+    let digest = Digest::from(digest);
+    let mut requirements = VerificationRequirements {
+        thoroughness: Level::Low,
+        understanding: Level::None,
+        trust_level: Level::None,
+        redundancy: 1,
+    };
+    assert_eq!(
+        verifying_reviewers(&digest, &trust_set, &requirements, &trustdb),
+        vec![a.id.id.clone()]
+    );
+    requirements.thoroughness = Level::High;
+    assert!(verifying_reviewers(&digest, &trust_set, &requirements, &trustdb).is_empty());
+
+    Ok(())
+}
+
+// Every review of the version gets the reason it does or doesn't count.
+#[test]
+fn explain_package_verification_gives_reasons() -> Result<()> {
+    let url = FetchSource::Url(Arc::new(Url::new_git("https://a")));
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let package_id = PackageVersionId::new(
+        "source".into(),
+        "name".into(),
+        Version::parse("1.0.0").unwrap(),
+    );
+    let package = |digest: [u8; 32]| crev_data::proof::PackageInfo {
+        id: package_id.clone(),
+        revision: String::new(),
+        revision_type: crev_data::proof::default_revision_type(),
+        digest_ignore: None,
+        digest: digest.to_vec(),
+        digest_type: crev_data::proof::default_digest_type(),
+    };
+
+    let review = crev_data::proof::review::Review::new_positive();
+    let by_a = a
+        .as_public_id()
+        .create_package_review_proof(package([16; 32]), review.clone(), vec![], "".into())?
+        .sign_by(&a)?;
+    let by_b = b
+        .as_public_id()
+        .create_package_review_proof(package([17; 32]), review, vec![], "".into())?
+        .sign_by(&b)?;
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(vec![by_a, by_b].into_iter().map(|x| (x, url.clone())));
+    let trust_set = trustdb.calculate_trust_set(&a.id.id, &default());
+    let digest = Digest::from([16; 32]);
+    let mut requirements = VerificationRequirements {
+        thoroughness: Level::Low,
+        understanding: Level::None,
+        trust_level: Level::None,
+        redundancy: 1,
+    };
+    let verdicts = |requirements: &VerificationRequirements| {
+        let mut verdicts: Vec<_> =
+            explain_package_verification(&digest, &package_id, &trust_set, requirements, &trustdb)
+                .into_iter()
+                .map(|(review, verdict)| (review.common.from.id, verdict))
+                .collect();
+        verdicts.sort_by_key(|(id, _)| *id != a.id.id);
+        verdicts
+    };
+    assert_eq!(
+        verdicts(&requirements),
+        vec![
+            (a.id.id.clone(), ReviewVerdict::Counted),
+            (b.id.id.clone(), ReviewVerdict::DigestMismatch),
+        ]
+    );
+    requirements.thoroughness = Level::High;
+    assert_eq!(
+        verdicts(&requirements)[0],
+        (
+            a.id.id.clone(),
+            ReviewVerdict::ThoroughnessTooLow(Level::Low)
+        )
+    );
+    requirements.thoroughness = Level::Low;
+
+    // a review of a patch doesn't verify the package, even with its digest
+    let mut patch_review = trustdb
+        .get_package_reviews_by_digest(&digest)
+        .next()
+        .expect("reviewed");
+    patch_review.patch = Some(crev_data::proof::review::Patch {
+        digest: vec![18; 32],
+        files: vec!["src/lib.rs".into()],
+        lines_changed: 2,
+        lines_total: 100,
+    });
+    patch_review.common.date += chrono::Duration::seconds(1);
+    trustdb.import_from_iter(std::iter::once((patch_review.sign_by(&a)?, url.clone())));
+    let verdict_of_a =
+        explain_package_verification(&digest, &package_id, &trust_set, &requirements, &trustdb)
+            .into_iter()
+            .find(|(review, _)| review.common.from.id == a.id.id)
+            .map(|(_, verdict)| verdict);
+    assert_eq!(verdict_of_a, Some(ReviewVerdict::PatchOnly));
+    assert!(!verify_package_digest(&digest, &trust_set, &requirements, &trustdb).is_verified());
+
+    Ok(())
+}
+
+// A negative review fails verification, and is the one reported as the reason.
+#[test]
+fn negative_reviews_are_reported() -> Result<()> {
+    let url = FetchSource::Url(Arc::new(Url::new_git("https://a")));
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let digest = [14; 32];
+    let package = crev_data::proof::PackageInfo {
+        id: PackageVersionId::new(
+            "source".into(),
+            "name".into(),
+            Version::parse("1.0.0").unwrap(),
+        ),
+        revision: String::new(),
+        revision_type: crev_data::proof::default_revision_type(),
+        digest_ignore: None,
+        digest: digest.to_vec(),
+        digest_type: crev_data::proof::default_digest_type(),
+    };
+
+    let mut review = crev_data::proof::review::Review::new_none();
+    review.rating = crev_data::Rating::Negative;
+    let proof = a
+        .as_public_id()
+        .create_package_review_proof(package, review, vec![], "steals keys".into())?
+        .sign_by(&a)?;
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(vec![proof].into_iter().map(|x| (x, url.clone())));
+    let trust_set = trustdb.calculate_trust_set(&a.id.id, &default());
+    let digest = Digest::from(digest);
+    assert_eq!(
+        verify_package_digest(&digest, &trust_set, &default(), &trustdb),
+        VerificationStatus::Negative
+    );
+    let negative = negative_package_reviews(&digest, &trust_set, &trustdb);
+    assert_eq!(negative.len(), 1);
+    assert_eq!(negative[0].comment, "steals keys");
+
+    Ok(())
+}
+
+// A retried URL moves between `failed` and `completed`,
+// and the state survives a YAML roundtrip.
+#[test]
+fn fetch_all_state_records_progress() -> Result<()> {
+    let mut state = fetch_state::FetchAllState::default();
+    state.record_failure("https://a", "timeout".into());
+    state.record_success("https://b");
+    assert_eq!(state.attempted_urls().count(), 2);
+
+    state.record_success("https://a");
+    assert!(state.failed.is_empty());
+    assert_eq!(state.completed.len(), 2);
+
+    let yaml = serde_yaml::to_string(&state)?;
+    let restored: fetch_state::FetchAllState = serde_yaml::from_str(&yaml)?;
+    assert_eq!(restored.completed, state.completed);
+    assert!(!restored.finished);
+    Ok(())
+}
+
+// An interrupted digest computation must not return a (wrong) digest
+#[test]
+fn dir_digest_is_cancellable() -> Result<()> {
+    let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+    let ignore_list = default();
+    let cancel = crev_common::CancellationToken::new();
+
+    get_dir_digest_cancellable(&dir, &ignore_list, &cancel)?;
+
+    cancel.cancel();
+    assert!(matches!(
+        get_dir_digest_cancellable(&dir, &ignore_list, &cancel),
+        Err(Error::Cancelled(_))
+    ));
+    Ok(())
+}
+
+// Exact entries match only their own path, `*` entries match file names anywhere
+#[test]
+fn digest_ignore_list_patterns() -> Result<()> {
+    use std::path::{Path, PathBuf};
+    let ignore_list: fnv::FnvHashSet<PathBuf> = util::DEFAULT_DIGEST_IGNORE
+        .iter()
+        .map(PathBuf::from)
+        .collect();
+
+    for ignored in ["target", ".git", "src/.lib.rs.swp", "src/lib.rs~", "x.swo"] {
+        assert!(util::is_ignored_path(Path::new(ignored), &ignore_list));
+    }
+    for kept in ["src/target", "src/lib.rs", "Cargo.toml", "src/swp"] {
+        assert!(!util::is_ignored_path(Path::new(kept), &ignore_list));
+    }
+    Ok(())
+}
+
+// A subtree digest doesn't depend on files outside of the subtree, and ignore
+// list entries of the package apply to the subtree too.
+#[test]
+fn subtree_digest() -> Result<()> {
+    use std::path::{Path, PathBuf};
+    let package_root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let no_ignore = fnv::FnvHashSet::default();
+    let excluded = get_subtree_digest(package_root, "src", &["tests.rs".into()], &no_ignore)?;
+    let ignore_list: fnv::FnvHashSet<_> = [PathBuf::from("src/tests.rs")].into_iter().collect();
+    assert_eq!(
+        get_subtree_digest(package_root, "src", &[], &ignore_list)?,
+        excluded
+    );
+    assert_ne!(
+        get_subtree_digest(package_root, "src", &[], &no_ignore)?,
+        excluded
+    );
+    assert!(get_subtree_digest(package_root, "no-such-dir", &[], &no_ignore).is_err());
+
+    let mut subtree = crev_data::proof::review::Subtree {
+        path: "src".into(),
+        exclude: vec!["tests.rs".into()],
+        digest: excluded.into_vec(),
+    };
+    let contains = |subtree: &_| package_contains_subtree(package_root, subtree, &no_ignore);
+    assert!(contains(&subtree)?);
+    subtree.exclude.clear();
+    assert!(!contains(&subtree)?);
+    subtree.path = "no-such-dir".into();
+    assert!(!contains(&subtree)?);
+    Ok(())
+}
+
+// The index lists only the Id's own package reviews, and its manifest
+// detects any later modification of `index.json`.
+#[test]
+fn proof_index_signed_manifest() -> Result<()> {
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let package = crev_data::proof::PackageInfo {
+        id: PackageVersionId::new(
+            "source".into(),
+            "name".into(),
+            Version::parse("1.0.0").unwrap(),
+        ),
+        revision: String::new(),
+        revision_type: crev_data::proof::default_revision_type(),
+        digest_ignore: None,
+        digest: [7; 32].to_vec(),
+        digest_type: crev_data::proof::default_digest_type(),
+    };
+    let review = crev_data::proof::review::Review::new_positive();
+    let proofs = vec![
+        a.as_public_id()
+            .create_package_review_proof(package.clone(), review.clone(), vec![], "a".into())?
+            .sign_by(&a)?,
+        b.as_public_id()
+            .create_package_review_proof(package, review, vec![], "b".into())?
+            .sign_by(&b)?,
+    ];
+
+    let index = proof_index::ProofIndex::from_proofs(&a.id.id, proofs.into_iter());
+    assert_eq!(index.reviews.len(), 1);
+    assert_eq!(index.reviews[0].digest, [7; 32].to_vec());
+
+    let index_json = index.to_json()?;
+    let manifest = proof_index::SignedManifest::sign(&a, index_json.as_bytes())?;
+    let manifest: proof_index::SignedManifest = serde_json::from_str(&manifest.to_json()?)?;
+    manifest.verify(index_json.as_bytes())?;
+
+    let tampered = index_json.replace("1.0.0", "1.0.1");
+    assert!(matches!(
+        manifest.verify(tampered.as_bytes()),
+        Err(Error::ProofIndexDigestMismatch)
+    ));
+    Ok(())
+}
+
+// An exported attestation names the crate by its digest, and any change
+// to the signed statement is detected.
+#[test]
+fn attestation_envelope_roundtrip() -> Result<()> {
+    let id = UnlockedId::generate_for_git_url("https://a");
+    let package = crev_data::proof::PackageInfo {
+        id: PackageVersionId::new(
+            crev_data::SOURCE_CRATES_IO.into(),
+            "name".into(),
+            Version::parse("1.0.0").unwrap(),
+        ),
+        revision: String::new(),
+        revision_type: crev_data::proof::default_revision_type(),
+        digest_ignore: None,
+        digest: [0xab; 32].to_vec(),
+        digest_type: crev_data::proof::default_digest_type(),
+    };
+    let review = id.as_public_id().create_package_review_proof(
+        package,
+        crev_data::proof::review::Review::new_positive(),
+        vec![],
+        "lgtm".into(),
+    )?;
+
+    let statement = attestation::Statement::for_package_review(&review)?;
+    assert_eq!(statement.subject[0].name, "pkg:cargo/name@1.0.0");
+    assert_eq!(statement.subject[0].digest["blake2b256"], "ab".repeat(32));
+
+    let envelope = attestation::Envelope::sign(&id, &statement)?;
+    let mut envelope: attestation::Envelope = serde_json::from_str(&envelope.to_json()?)?;
+    let verified = envelope.verify()?;
+    assert_eq!(verified.predicate["comment"], "lgtm");
+
+    let mut tampered = serde_json::to_value(&verified)?;
+    tampered["predicate"]["comment"] = "pwned".into();
+    envelope.payload = crev_common::base64_encode(&serde_json::to_vec(&tampered)?);
+    assert!(envelope.verify().is_err());
+    Ok(())
+}
+
+// Activities recorded before sessions existed still load, and
+// the suggested thoroughness follows the share of files visited.
+#[test]
+fn review_session_coverage() -> Result<()> {
+    let old: ReviewActivity =
+        serde_yaml::from_str("timestamp: \"2024-01-01T00:00:00+00:00\"\ndiff_base: ~\n")?;
+    assert!(old.session.is_none());
+
+    let mut session = ReviewSession::new(45, 10);
+    assert_eq!(session.suggested_thoroughness(), Level::None);
+    session.visited = (0..3).map(|i| format!("src/{i}.rs").into()).collect();
+    assert_eq!(session.suggested_thoroughness(), Level::Low);
+    session.visited = (0..10).map(|i| format!("src/{i}.rs").into()).collect();
+    assert_eq!(session.suggested_thoroughness(), Level::High);
+
+    let mut activity = ReviewActivity::new(None);
+    activity.session = Some(session);
+    let restored: ReviewActivity = serde_yaml::from_str(&serde_yaml::to_string(&activity)?)?;
+    assert_eq!(restored.session.map(|s| s.visited.len()), Some(10));
+    Ok(())
+}
+
+// Proofs made with `ProofFactory` don't need a `Local`,
+// and verify like any other proof.
+#[test]
+fn proof_factory_signs_without_local() -> Result<()> {
+    let author = UnlockedId::generate_for_git_url("https://a");
+    let other = UnlockedId::generate_for_git_url("https://b");
+    let factory = ProofFactory::new(author);
+
+    let trust = factory
+        .trust(vec![other.as_public_id().clone()], TrustLevel::Medium)
+        .comment("known")
+        .sign()?;
+    trust.verify()?;
+    let trust = trust.parse_content::<crev_data::proof::Trust>()?;
+    assert_eq!(trust.trust, TrustLevel::Medium);
+    assert_eq!(trust.comment, "known");
+
+    let package = proof_factory::package_info(
+        "source",
+        "name",
+        Version::parse("1.0.0").unwrap(),
+        &Digest::from([1; 32]),
+    );
+    let review = factory
+        .package_review(package)
+        .review(crev_data::Review::new_positive())
+        .comment("lgtm")
+        .sign()?;
+    review.verify()?;
+    assert_eq!(review.author_id(), &factory.id().id);
+    let review = review.parse_content::<crev_data::proof::review::Package>()?;
+    assert_eq!(review.review_possibly_none().rating, Rating::Positive);
+
+    assert!(factory.trust(vec![], TrustLevel::High).sign().is_err());
+    Ok(())
+}
+
+// Error codes are stable, and their first digit gives the category,
+// so scripts can match on either.
+#[test]
+fn error_codes_and_categories() {
+    assert_eq!(Error::IncorrectPassphrase.code(), "E203");
+    assert_eq!(Error::IncorrectPassphrase.category(), ErrorCategory::Id);
+    assert_eq!(
+        Error::ProofIndexDigestMismatch.category(),
+        ErrorCategory::Integrity
+    );
+    assert_eq!(
+        Error::from(crev_common::CancelledError::ByUser).category(),
+        ErrorCategory::Cancelled
+    );
+    assert_eq!(
+        serde_json::to_string(&ErrorCategory::Unsupported).unwrap(),
+        "\"unsupported\""
+    );
+}
+
+// Usage counters are grouped by month, and count each reviewed crate once.
+#[test]
+fn usage_stats_per_month() -> Result<()> {
+    use crate::usage::{UsageEvent, UsageStats};
+
+    let october = chrono::NaiveDate::from_ymd_opt(2024, 10, 3).unwrap();
+    let november = chrono::NaiveDate::from_ymd_opt(2024, 11, 30).unwrap();
+    let mut stats = UsageStats::default();
+    stats.record(UsageEvent::Review { crate_name: "log" }, &october);
+    stats.record(UsageEvent::Review { crate_name: "log" }, &october);
+    stats.record(UsageEvent::VerifyRun, &october);
+    stats.record(UsageEvent::VerifyRun, &november);
+
+    let restored: UsageStats = serde_yaml::from_str(&serde_yaml::to_string(&stats)?)?;
+    let october = &restored.months["2024-10"];
+    assert_eq!((october.reviews, october.verify_runs), (2, 1));
+    assert_eq!(october.crates_reviewed.len(), 1);
+    assert_eq!(restored.months["2024-11"].verify_runs, 1);
+    Ok(())
+}
+
+// Only reviewers whose trust would verify something get recommended,
+// and reviews by already trusted Ids count towards both.
+#[test]
+fn recommend_ids_to_trust_by_coverage() -> Result<()> {
+    let url = FetchSource::Url(Arc::new(Url::new_git("https://a")));
+    let me = UnlockedId::generate_for_git_url("https://me");
+    let trusted = ProofFactory::new(UnlockedId::generate_for_git_url("https://trusted"));
+    let b = ProofFactory::new(UnlockedId::generate_for_git_url("https://b"));
+    let c = ProofFactory::new(UnlockedId::generate_for_git_url("https://c"));
+    let digests = [Digest::from([1; 32]), Digest::from([2; 32])];
+
+    let review = |factory: &ProofFactory, digest: &Digest| {
+        let package =
+            proof_factory::package_info("source", "name", Version::parse("1.0.0").unwrap(), digest);
+        factory
+            .package_review(package)
+            .review(crev_data::Review::new_positive())
+            .sign()
+    };
+    let proofs = vec![
+        me.create_signed_trust_proof(vec![trusted.id()], TrustLevel::Low, vec![])?,
+        review(&trusted, &digests[0])?,
+        review(&b, &digests[0])?,
+        review(&b, &digests[1])?,
+        review(&c, &digests[1])?,
+    ];
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(proofs.into_iter().map(|x| (x, url.clone())));
+    let trust_set = trustdb.calculate_trust_set(&me.id.id, &default());
+    let requirements = VerificationRequirements {
+        thoroughness: Level::None,
+        understanding: Level::None,
+        trust_level: Level::Low,
+        redundancy: 2,
+    };
+
+    let recommendations =
+        recommend::recommend_ids_to_trust(&me.id.id, &digests, &trust_set, &requirements, &trustdb);
+    assert_eq!(
+        recommendations,
+        [recommend::TrustRecommendation {
+            id: b.id().id.clone(),
+            covered: 1,
+            overlap: 1,
+        }]
+    );
+    Ok(())
+}
+
+// Callbacks run in the order they were registered, until cleared
+#[test]
+fn hooks_run_in_order() -> Result<()> {
+    let id = UnlockedId::generate_for_git_url("https://a");
+    let proof = id.create_signed_trust_proof(vec![id.as_public_id()], TrustLevel::Low, vec![])?;
+    let event = hooks::ProofInserted {
+        proof: &proof,
+        rel_path: std::path::Path::new("trust/2020-01.proof.crev"),
+    };
+
+    let calls = Arc::new(std::sync::Mutex::new(vec![]));
+    let hooks = hooks::Hooks::default();
+    for name in ["first", "second"] {
+        let calls = calls.clone();
+        hooks.add_proof_inserted(Box::new(move |event| {
+            assert_eq!(event.proof.kind(), crev_data::proof::Trust::KIND);
+            calls.lock().unwrap().push(name);
+        }));
+    }
+    hooks.proof_inserted(&event);
+    hooks.clear();
+    hooks.proof_inserted(&event);
+    assert_eq!(*calls.lock().unwrap(), ["first", "second"]);
+
+    Ok(())
+}
+
+// Hook commands get their input on stdin and the environment they were given,
+// and failures name the hook.
+#[cfg(unix)]
+#[test]
+fn hook_cmds_contract() -> Result<()> {
+    let dir = std::env::temp_dir();
+    let env = [("CREV_PROOF_KIND", std::ffi::OsStr::new("package review"))];
+    let output = hooks::run_hook_cmd(
+        "pre-sign",
+        "cat; echo \" $CREV_HOOK $CREV_PROOF_KIND\"",
+        &dir,
+        &env,
+        b"draft",
+        true,
+    )?;
+    assert_eq!(output, b"draft pre-sign package review\n");
+
+    // hooks don't have to read their input
+    let err = hooks::run_hook_cmd("post-store", "exit 3", &dir, &env, b"proof", false).unwrap_err();
+    assert_eq!(err.code(), "E110");
+    assert!(err.to_string().contains("`post-store` hook"));
+    Ok(())
+}
+
+// Staged proofs can be found by a prefix of their name and dropped
+#[test]
+fn proof_staging() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!(
+        "crev-staging-test-{}",
+        crev_common::base64_encode(&crev_common::rand::random_vec(8))
+    ));
+    let staging = staging::ProofStaging::new(dir.clone());
+    assert!(staging.list()?.is_empty());
+
+    let id = UnlockedId::generate_for_git_url("https://a");
+    let proof = id.create_signed_trust_proof(vec![id.as_public_id()], TrustLevel::Low, vec![])?;
+    let name = staging.add(&proof)?;
+    let staged = staging.list()?;
+    assert_eq!(staged.len(), 1);
+    assert_eq!(staged[0].name, name);
+    assert_eq!(staged[0].proof.digest(), proof.digest());
+    assert_eq!(staging.find(&name[..4])?.len(), 1);
+
+    assert!(staging.remove(&name)?);
+    assert!(!staging.remove(&name)?);
+    assert!(staging.list()?.is_empty());
+    std::fs::remove_dir_all(dir)?;
+    Ok(())
+}
+
+// A crate from a registry has the same registry-normalized digest as its source
+#[test]
+fn registry_normalized_digest_matches_source() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!(
+        "crev-normalized-test-{}",
+        crev_common::base64_encode(&crev_common::rand::random_vec(8))
+    ));
+    let (registry, git) = (dir.join("registry"), dir.join("git"));
+    for root in [&registry, &git] {
+        std::fs::create_dir_all(root.join("src"))?;
+        std::fs::write(root.join("src/lib.rs"), "pub fn f() {}\n")?;
+    }
+    let manifest = "[package]\nname = \"x\"\nversion.workspace = true\n";
+    std::fs::write(git.join("Cargo.toml"), manifest)?;
+    std::fs::write(registry.join("Cargo.toml.orig"), manifest)?;
+    std::fs::write(
+        registry.join("Cargo.toml"),
+        "[package]\nname = \"x\"\nversion = \"1.0.0\"\n",
+    )?;
+    std::fs::write(registry.join(".cargo_vcs_info.json"), "{}")?;
+    std::fs::write(registry.join(".cargo-ok"), "")?;
+
+    let policy = DigestIgnorePolicy::registry_normalized();
+    assert_eq!(
+        DigestIgnorePolicy::by_id(policy.id())?,
+        DigestIgnorePolicy::registry_normalized()
+    );
+    assert_eq!(policy.digest(&registry)?, policy.digest(&git)?);
+    let cargo_crate = DigestIgnorePolicy::cargo_crate();
+    assert_ne!(cargo_crate.digest(&registry)?, cargo_crate.digest(&git)?);
+
+    std::fs::write(git.join("src/lib.rs"), "pub fn g() {}\n")?;
+    assert_ne!(policy.digest(&registry)?, policy.digest(&git)?);
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+// Reviews are matched using the digest ignore policy they were made with,
+// and the ones made with unknown policies can't be told apart.
+#[test]
+fn digest_ignore_policy_matches_package() -> Result<()> {
+    use std::path::{Path, PathBuf};
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+    let policy = DigestIgnorePolicy::cargo_crate();
+    let digest = policy.digest(&root)?;
+    let mut package = crev_data::proof::PackageInfo {
+        id: PackageVersionId::new(
+            "source".into(),
+            "name".into(),
+            Version::parse("1.0.0").unwrap(),
+        ),
+        digest: digest.as_slice().to_vec(),
+        digest_type: crev_data::proof::default_digest_type(),
+        revision: String::new(),
+        revision_type: crev_data::proof::default_revision_type(),
+        digest_ignore: None,
+    };
+    assert_eq!(DigestIgnorePolicy::of_package(&package)?, policy);
+    assert!(policy.matches_package(&package, &digest, &root));
+
+    let other = DigestIgnorePolicy::new("other", [PathBuf::from("tests.rs")]);
+    let other_digest = other.digest(&root)?;
+    assert_ne!(other_digest, digest);
+    // the review's own policy is used
+    assert!(other.matches_package(&package, &other_digest, &root));
+    let wrong_digest = crev_data::proof::PackageInfo {
+        digest: other_digest.as_slice().to_vec(),
+        ..package.clone()
+    };
+    assert!(!other.matches_package(&wrong_digest, &other_digest, &root));
+
+    package.digest_ignore = Some("from-the-future".into());
+    assert!(DigestIgnorePolicy::of_package(&package).is_err());
+    assert!(policy.matches_package(&package, &digest, &root));
+    Ok(())
+}
+
+// Dependencies are verified by the digest of their source code
+#[test]
+fn verify_dependencies_reports() -> Result<()> {
+    use std::path::Path;
+    let url = FetchSource::Url(Arc::new(Url::new_git("https://a")));
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+    let version = Version::parse("1.0.0").unwrap();
+    let package = crev_data::proof::PackageInfo {
+        id: PackageVersionId::new(
+            crev_data::SOURCE_CRATES_IO.into(),
+            "name".into(),
+            version.clone(),
+        ),
+        digest: DigestIgnorePolicy::cargo_crate()
+            .digest(&root)?
+            .as_slice()
+            .to_vec(),
+        digest_type: crev_data::proof::default_digest_type(),
+        revision: String::new(),
+        revision_type: crev_data::proof::default_revision_type(),
+        digest_ignore: None,
+    };
+    let review = a
+        .as_public_id()
+        .create_package_review_proof(
+            package,
+            crev_data::proof::review::Review::new_positive(),
+            vec![],
+            "".into(),
+        )?
+        .sign_by(&a)?;
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(vec![(review, url)].into_iter());
+    let trust_set = trustdb.calculate_trust_set(&a.id.id, &default());
+    let dependency = |name: &str, is_local_source_code| verify::Dependency {
+        source: crev_data::SOURCE_CRATES_IO.into(),
+        name: name.into(),
+        version: version.clone(),
+        root: root.clone(),
+        is_local_source_code,
+    };
+    let reports = verify::verify_dependencies(
+        &[dependency("name", false), dependency("local", true)],
+        &trust_set,
+        &VerificationRequirements {
+            thoroughness: Level::Low,
+            understanding: Level::Low,
+            trust_level: Level::Low,
+            redundancy: 1,
+        },
+        &trustdb,
+    )?;
+
+    assert_eq!(reports[0].status, VerificationStatus::Verified);
+    assert_eq!(reports[0].version_reviews, 1);
+    assert!(reports[0].trusted_reviewers.contains(a.as_public_id()));
+    assert!(reports[0].digest_mismatches.is_empty());
+    assert_eq!(reports[1].status, VerificationStatus::Local);
+    assert!(reports[1].digest.is_none());
+    Ok(())
+}
+
+// A review of a subtree counts only for the subtree digest, not for the
+// whole package, even though it's found among the reviews of the version.
+#[test]
+fn subtree_reviews_verify_only_the_subtree() -> Result<()> {
+    use std::path::Path;
+    let url = FetchSource::Url(Arc::new(Url::new_git("https://a")));
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let version = Version::parse("1.0.0").unwrap();
+    let policy = DigestIgnorePolicy::cargo_crate();
+    let digest = policy.digest(root)?;
+    let subtree_digest = get_subtree_digest(root, "src", &[], policy.ignore_list())?;
+    let package = crev_data::proof::PackageInfo {
+        id: PackageVersionId::new(
+            crev_data::SOURCE_CRATES_IO.into(),
+            "name".into(),
+            version.clone(),
+        ),
+        digest: subtree_digest.as_slice().to_vec(),
+        digest_type: crev_data::proof::default_digest_type(),
+        revision: String::new(),
+        revision_type: crev_data::proof::default_revision_type(),
+        digest_ignore: None,
+    };
+    let mut review = a.as_public_id().create_package_review_proof(
+        package,
+        crev_data::proof::review::Review::new_positive(),
+        vec![],
+        "".into(),
+    )?;
+    review.subtree = Some(crev_data::proof::review::Subtree {
+        path: "src".into(),
+        exclude: vec![],
+        digest: subtree_digest.as_slice().to_vec(),
+    });
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(std::iter::once((review.sign_by(&a)?, url)));
+    let trust_set = trustdb.calculate_trust_set(&a.id.id, &default());
+    let requirements = VerificationRequirements {
+        thoroughness: Level::Low,
+        understanding: Level::Low,
+        trust_level: Level::Low,
+        redundancy: 1,
+    };
+    let report = verify::verify_crate(
+        &verify::Dependency {
+            source: crev_data::SOURCE_CRATES_IO.into(),
+            name: "name".into(),
+            version,
+            root: root.to_owned(),
+            is_local_source_code: false,
+        },
+        Some(digest),
+        &policy,
+        &trust_set,
+        &requirements,
+        &trustdb,
+    );
+
+    assert_eq!(report.status, VerificationStatus::Insufficient);
+    assert_eq!(report.version_reviews, 1);
+    assert!(report.trusted_reviewers.is_empty());
+    assert!(report.digest_mismatches.is_empty());
+    assert_eq!(report.subtrees.len(), 1);
+    assert_eq!(report.subtrees[0].path, "src");
+    assert_eq!(report.subtrees[0].status, VerificationStatus::Verified);
+    Ok(())
+}
+
+// Git dependencies are digested by the files committed in their checkout
+#[test]
+fn git_crate_digest_ignores_untracked_files() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!(
+        "crev-git-digest-test-{}",
+        crev_common::base64_encode(&crev_common::rand::random_vec(8))
+    ));
+    let root = dir.join("member");
+    std::fs::create_dir_all(root.join("src"))?;
+    std::fs::write(dir.join("Cargo.toml"), "[workspace]\n")?;
+    std::fs::write(root.join("Cargo.toml"), "[package]\nname = \"member\"\n")?;
+    std::fs::write(root.join("src/lib.rs"), "pub fn f() {}\n")?;
+    let policy = DigestIgnorePolicy::cargo_crate();
+    let expected = policy.digest(&root)?;
+
+    let repo = git2::Repository::init(&dir)?;
+    let mut index = repo.index()?;
+    index.add_all(["*"], git2::IndexAddOption::DEFAULT, None)?;
+    let tree = repo.find_tree(index.write_tree()?)?;
+    let signature = git2::Signature::now("crev", "crev@example.com")?;
+    repo.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])?;
+
+    std::fs::write(root.join(".cargo-ok"), "")?;
+    std::fs::write(root.join("untracked.rs"), "")?;
+    assert_eq!(
+        verify::get_git_crate_digest(&root, policy.ignore_list())?,
+        expected
+    );
+    assert_ne!(policy.digest(&root)?, expected);
+
+    let outside = std::env::temp_dir();
+    assert!(verify::get_git_crate_digest(&outside, policy.ignore_list()).is_err());
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+// Fetching a proof repo is skipped if its remote refs didn't change
+#[test]
+fn proof_repo_up_to_date_with_origin() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!(
+        "crev-fetch-test-{}",
+        crev_common::base64_encode(&crev_common::rand::random_vec(8))
+    ));
+    let origin_dir = dir.join("origin");
+    std::fs::create_dir_all(&origin_dir)?;
+    let origin = git2::Repository::init(&origin_dir)?;
+    let signature = git2::Signature::now("crev", "crev@example.com")?;
+    let commit = |message: &str| -> Result<()> {
+        std::fs::write(origin_dir.join("proof.crev"), message)?;
+        let mut index = origin.index()?;
+        index.add_path(Path::new("proof.crev"))?;
+        index.write()?;
+        let tree = origin.find_tree(index.write_tree()?)?;
+        let parent = origin.head().ok().and_then(|h| h.peel_to_commit().ok());
+        let parents: Vec<_> = parent.iter().collect();
+        origin.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parents,
+        )?;
+        Ok(())
+    };
+    commit("first")?;
+
+    let cancel = crev_common::CancellationToken::new();
+    let url = format!("file://{}", origin_dir.display());
+    let checkout = util::git::clone_shallow(&url, dir.join("checkout"), &cancel, None)?;
+    assert!(util::git::is_up_to_date_with_origin(&checkout, &cancel)?);
+
+    commit("second")?;
+    assert!(!util::git::is_up_to_date_with_origin(&checkout, &cancel)?);
+    util::git::fetch_and_checkout_git_repo(&checkout, &cancel, None)?;
+    assert!(util::git::is_up_to_date_with_origin(&checkout, &cancel)?);
+    util::git::check_checkout(&checkout)?;
+
+    // an interrupted fetch left a lock and a dangling `HEAD`
+    let git_dir = dir.join("checkout").join(".git");
+    std::fs::write(git_dir.join("index.lock"), "")?;
+    assert_eq!(
+        util::git::remove_stale_locks(&dir.join("checkout"), std::time::Duration::ZERO),
+        1
+    );
+    assert!(!git_dir.join("index.lock").exists());
+    std::fs::write(git_dir.join("HEAD"), "ref: refs/heads/never-written\n")?;
+    let err = util::git::check_checkout(&checkout).unwrap_err();
+    assert!(util::git::is_corrupted(&err));
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+// Files a registry package wouldn't have don't count in git crate digests
+#[test]
+fn git_crate_digest_leaves_out_unpublished_files() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!(
+        "crev-git-export-test-{}",
+        crev_common::base64_encode(&crev_common::rand::random_vec(8))
+    ));
+    let published = dir.join("published");
+    std::fs::create_dir_all(published.join("src"))?;
+    let manifest = "[package]\nname = \"member\"\nexclude = [\"benches/\"]\n";
+    std::fs::write(published.join("Cargo.toml"), manifest)?;
+    std::fs::write(published.join("src/lib.rs"), "pub fn f() {}\n")?;
+    let policy = DigestIgnorePolicy::cargo_crate();
+    let expected = policy.digest(&published)?;
+
+    let root = dir.join("repo");
+    std::fs::create_dir_all(root.join("src"))?;
+    std::fs::create_dir_all(root.join("docs"))?;
+    std::fs::create_dir_all(root.join("benches"))?;
+    std::fs::create_dir_all(root.join("fuzz"))?;
+    std::fs::write(root.join("Cargo.toml"), manifest)?;
+    std::fs::write(root.join("src/lib.rs"), "pub fn f() {}\n")?;
+    std::fs::write(
+        root.join(".gitattributes"),
+        "docs/** export-ignore\n.gitattributes export-ignore\n",
+    )?;
+    std::fs::write(root.join("docs/guide.md"), "")?;
+    std::fs::write(root.join("benches/bench.rs"), "")?;
+    std::fs::write(root.join("fuzz/Cargo.toml"), "[package]\nname = \"fuzz\"\n")?;
+
+    let repo = git2::Repository::init(&root)?;
+    let mut index = repo.index()?;
+    index.add_all(["*"], git2::IndexAddOption::DEFAULT, None)?;
+    index.write()?;
+    let tree = repo.find_tree(index.write_tree()?)?;
+    let signature = git2::Signature::now("crev", "crev@example.com")?;
+    repo.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])?;
+
+    assert_eq!(
+        verify::get_git_crate_digest(&root, policy.ignore_list())?,
+        expected
+    );
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+// Warnings serialize with their code, and the code of the error they came from
+#[test]
+fn warning_serializes_with_codes() -> Result<()> {
+    let warning = Warning::FetchError(
+        "https://example.com/proofs".into(),
+        Error::GitUrlNotConfigured,
+        PathBuf::from("/tmp/proofs"),
+    );
+    let json: serde_json::Value = serde_json::to_value(&warning)?;
+    assert_eq!(json["level"], "warning");
+    assert_eq!(json["code"], "W105");
+    assert_eq!(json["error-code"], "E304");
+    assert_eq!(json["url"], "https://example.com/proofs");
+    assert_eq!(json["path"], "/tmp/proofs");
+    assert_eq!(
+        json["causes"][0],
+        "current Id has been created without a git URL"
+    );
+
+    Ok(())
+}
+
+// A review template adds its sections to the draft, and rejects reviews
+// that leave required parts empty
+#[test]
+fn review_template_requires_sections() -> Result<()> {
+    let template: review_template::ReviewTemplate = serde_yaml::from_str(
+        "sections:
+  - title: Unsafe code
+    required: true
+  - title: Build script
+min-thoroughness: low
+require-comment: true
+",
+    )?;
+    let id = UnlockedId::generate_for_git_url("https://example.com/crev-proofs");
+    let package = crev_data::proof::PackageInfo {
+        id: PackageVersionId::new(
+            "source".into(),
+            "name".into(),
+            Version::parse("1.0.0").unwrap(),
+        ),
+        revision: String::new(),
+        revision_type: crev_data::proof::default_revision_type(),
+        digest_ignore: None,
+        digest: vec![0; 32],
+        digest_type: crev_data::proof::default_digest_type(),
+    };
+    let mut review = id.as_public_id().create_package_review_proof(
+        package,
+        crev_data::proof::review::Review::new_none(),
+        vec![],
+        "Looks fine.".into(),
+    )?;
+
+    template.pre_populate(&mut review);
+    assert_eq!(
+        review.comment,
+        "Looks fine.\n\n## Unsafe code\n\n\n## Build script\n"
+    );
+    template.pre_populate(&mut review);
+    assert_eq!(review.comment.matches("## Unsafe code").count(), 1);
+    assert_eq!(
+        template.missing(&review),
+        [
+            "thoroughness must be at least low",
+            "section `Unsafe code` is required"
+        ]
+    );
+
+    *review.review_possibly_none_mut() = crev_data::proof::review::Review::new_positive();
+    review.comment = review
+        .comment
+        .replace("## Unsafe code\n", "## Unsafe code\nNone.\n");
+    template.ensure_satisfied(&review)?;
+
+    review.comment = "## Unsafe code\nNone.\n".into();
+    assert!(template.ensure_satisfied(&review).is_ok());
+    review.comment.clear();
+    assert!(matches!(
+        template.ensure_satisfied(&review),
+        Err(Error::ReviewTemplateNotSatisfied(_))
+    ));
+    Ok(())
+}
+
+// Embargoed proofs are listed soonest first, and are due once their date passes
+#[test]
+fn embargoed_proofs_are_due_after_their_date() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!(
+        "crev-embargo-test-{}",
+        crev_common::base64_encode(&crev_common::rand::random_vec(8))
+    ));
+    let embargo = embargo::ProofEmbargo::new(dir.clone());
+    assert!(embargo.list()?.is_empty());
+
+    let id = UnlockedId::generate_for_git_url("https://example.com/crev-proofs");
+    let other = UnlockedId::generate_for_git_url("https://example.com/other-proofs");
+    let now = crev_common::now();
+    let mut names = vec![];
+    for (days, level) in [(7, TrustLevel::Low), (-1, TrustLevel::High)] {
+        let proof = id
+            .as_public_id()
+            .create_trust_proof([other.as_public_id()], level, vec![])?
+            .sign_by(&id)?;
+        let publish_after = now + chrono::Duration::try_days(days).unwrap();
+        names.push(embargo.add(&proof, publish_after)?);
+    }
+
+    let listed = embargo.list()?;
+    assert_eq!(listed.len(), 2);
+    assert_eq!(listed[0].name, names[1]);
+    assert!(listed[0].is_due(&now));
+    assert!(!listed[1].is_due(&now));
+    assert_eq!(embargo.find(&names[0])?.len(), 1);
+
+    assert!(embargo.remove(&names[1])?);
+    assert!(!embargo.remove(&names[1])?);
+    assert_eq!(embargo.list()?.len(), 1);
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+#[test]
+fn project_policy_requirements_and_exemptions() -> Result<()> {
+    let policy = project_policy::ProjectPolicy::from_yaml(
+        "trust-level: medium\nredundancy: 2\nrequirements:\n  - crates: [\"*-sys\"]\n    redundancy: 1\nexemptions:\n  - crates: [\"internal-*\"]\n    until: 2025-06-01\n    reason: vendored\ngroups: {}\n",
+    )?;
+    let global = VerificationRequirements {
+        trust_level: Level::Low,
+        understanding: Level::Low,
+        thoroughness: Level::None,
+        redundancy: 1,
+    };
+
+    // the project's requirements are the least ones
+    let requirements = policy.requirements(&global);
+    assert_eq!(requirements.trust_level, Level::Medium);
+    assert_eq!(requirements.understanding, Level::Low);
+    assert_eq!(requirements.redundancy, 2);
+    let stricter = VerificationRequirements {
+        redundancy: 3,
+        ..global.clone()
+    };
+    assert_eq!(policy.requirements(&stricter).redundancy, 3);
+
+    let (requirements, rule) = policy.requirements_for("openssl-sys", &global);
+    assert!(rule.is_some());
+    assert_eq!(requirements.redundancy, 1);
+    assert_eq!(requirements.trust_level, Level::Medium);
+
+    let day = |d| chrono::NaiveDate::from_ymd_opt(2025, 6, d).unwrap();
+    let exemption = policy.exemption_for("internal-utils", day(1)).unwrap();
+    assert_eq!(
+        exemption.to_string(),
+        "internal-*: until 2025-06-01 (vendored)"
+    );
+    assert!(policy.exemption_for("serde", day(1)).is_none());
+    assert!(policy.exemption_for("internal-utils", day(2)).is_none());
+    assert_eq!(policy.expired_exemptions(day(2)).count(), 1);
+
+    assert!(project_policy::ProjectPolicy::from_yaml(
+        "exemptions:\n  - crates: [a]\n    until: soon\n"
+    )
+    .is_err());
+    Ok(())
+}
+
+#[test]
+fn project_policy_reviewer_rules() -> Result<()> {
+    let reviewer = UnlockedId::generate_for_git_url("https://a");
+    let policy = project_policy::ProjectPolicy::from_yaml(&format!(
+        "groups:\n  security-team: [{}]\nrules:\n  - crates: [\"ring\", \"rustls*\"]\n    reviewers: [security-team]\n",
+        reviewer.id.id
+    ))?;
+    let db = ProofDB::new();
+    let version = crev_data::Version::parse("1.0.0").unwrap();
+    let digest = Digest::from([0; 32]);
+
+    assert_eq!(
+        policy.check_reviewers("rustls-webpki", &version, &digest, &db),
+        ["rustls-webpki 1.0.0: needs 1 positive review by security-team, found 0"]
+    );
+    assert!(policy
+        .check_reviewers("serde", &version, &digest, &db)
+        .is_empty());
+
+    assert!(project_policy::ProjectPolicy::from_yaml(
+        "rules:\n  - crates: [ring]\n    reviewers: [unknown-team]\n"
+    )
+    .is_err());
+    Ok(())
+}
+
+/// Held by tests that open a `Local` in a portable root, set with a process-wide env var
+static PORTABLE_ROOT_ENV_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+// A portable root keeps working once moved elsewhere, see `CREV_PORTABLE_ROOT`
+#[test]
+fn portable_root_can_be_moved() -> Result<()> {
+    let _env = PORTABLE_ROOT_ENV_LOCK
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    let dir = std::env::temp_dir().join(format!(
+        "crev-portable-test-{}",
+        crev_common::base64_encode(&crev_common::rand::random_vec(8))
+    ));
+    let old_root = dir.join("old");
+    let new_root = dir.join("new");
+
+    std::env::set_var(local::PORTABLE_ROOT_ENV, &old_root);
+    let local = Local::auto_create_or_open()?;
+    assert_eq!(local.portable_root(), Some(old_root.as_path()));
+    local.generate_id(None, false, || Ok(String::new()), &mut vec![])?;
+    let id = local.read_current_unlocked_id(&|| Ok(String::new()))?;
+    let other = UnlockedId::generate_for_git_url("https://example.com/other-proofs");
+    let proof = id
+        .as_public_id()
+        .create_trust_proof([other.as_public_id()], TrustLevel::Medium, vec![])?
+        .sign_by(&id)?;
+    local.insert(&proof)?;
+    local.proof_dir_commit("Add trust")?;
+    local.load_db()?;
+    drop(local);
+
+    std::fs::rename(&old_root, &new_root)?;
+    std::env::set_var(local::PORTABLE_ROOT_ENV, &new_root);
+    let local = Local::auto_open();
+    std::env::remove_var(local::PORTABLE_ROOT_ENV);
+    let local = local?;
+    assert_eq!(local.read_current_id()?, id.id.id);
+    assert!(local.get_proofs_dir_path()?.starts_with(&new_root));
+    let db = local.load_db()?;
+    assert!(db
+        .get_trust_proof_between(&id.id.id, &other.id.id)
+        .is_some());
+
+    // nothing written refers to where it was before
+    let old_root = old_root.to_string_lossy();
+    for entry in walkdir::WalkDir::new(&new_root) {
+        let entry = entry.map_err(std::io::Error::from)?;
+        if entry.file_type().is_file() {
+            let content = std::fs::read(entry.path())?;
+            assert!(
+                !content
+                    .windows(old_root.len())
+                    .any(|window| window == old_root.as_bytes()),
+                "{} has the old path",
+                entry.path().display()
+            );
+        }
+    }
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+// Pinned inputs read proof repos at the pinned commits, and pin only what
+// another machine can fetch
+#[test]
+fn pinned_inputs_are_read_at_pinned_commits() -> Result<()> {
+    let _env = PORTABLE_ROOT_ENV_LOCK
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    let dir = std::env::temp_dir().join(format!(
+        "crev-pin-test-{}",
+        crev_common::base64_encode(&crev_common::rand::random_vec(8))
+    ));
+    std::env::set_var(local::PORTABLE_ROOT_ENV, &dir);
+    let local = Local::auto_create_or_open();
+    std::env::remove_var(local::PORTABLE_ROOT_ENV);
+    let local = local?;
+    local.generate_id(None, false, || Ok(String::new()), &mut vec![])?;
+    let id = local.read_current_unlocked_id(&|| Ok(String::new()))?;
+
+    // a fetched proof repo of someone else
+    let url = "https://example.com/other-proofs";
+    let other = UnlockedId::generate_for_git_url(url);
+    let checkout_dir = local.get_remote_git_cache_path(url)?;
+    std::fs::create_dir_all(&checkout_dir)?;
+    let checkout = git2::Repository::init(&checkout_dir)?;
+    checkout.remote("origin", url)?;
+    let signature = git2::Signature::now("crev", "crev@example.com")?;
+    let commit_trust = |file: &str, trusted: &UnlockedId| -> Result<()> {
+        let proof = other
+            .as_public_id()
+            .create_trust_proof([trusted.as_public_id()], TrustLevel::High, vec![])?
+            .sign_by(&other)?;
+        std::fs::write(checkout_dir.join(file), proof.to_string())?;
+        let mut index = checkout.index()?;
+        index.add_path(Path::new(file))?;
+        let tree = checkout.find_tree(index.write_tree()?)?;
+        let parent = checkout.head().ok().and_then(|h| h.peel_to_commit().ok());
+        let parents: Vec<_> = parent.iter().collect();
+        checkout.commit(Some("HEAD"), &signature, &signature, file, &tree, &parents)?;
+        Ok(())
+    };
+    let first = UnlockedId::generate_for_git_url("https://example.com/first");
+    commit_trust("first.crev", &first)?;
+
+    let pinned = local.pin_inputs(None)?;
+    assert_eq!(pinned.for_id, Some(id.id.id.clone()));
+    assert_eq!(pinned.proof_repos[url].len(), 1);
+    let pinned: pinned_inputs::PinnedInputs =
+        serde_yaml::from_str(&serde_yaml::to_string(&pinned)?)?;
+
+    // fetched again since
+    let second = UnlockedId::generate_for_git_url("https://example.com/second");
+    commit_trust("second.crev", &second)?;
+    let db = local.load_db_pinned(&pinned)?;
+    assert!(db
+        .get_trust_proof_between(&other.id.id, &first.id.id)
+        .is_some());
+    assert!(db
+        .get_trust_proof_between(&other.id.id, &second.id.id)
+        .is_none());
+
+    // own proofs without a URL can't be fetched elsewhere
+    let proof = id
+        .as_public_id()
+        .create_trust_proof([other.as_public_id()], TrustLevel::Medium, vec![])?
+        .sign_by(&id)?;
+    local.insert(&proof)?;
+    assert!(matches!(
+        local.pin_inputs(None),
+        Err(Error::CantPinInputs(_))
+    ));
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
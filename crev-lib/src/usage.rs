@@ -0,0 +1,50 @@
+//! Opt-in, local-only usage counters
+//!
+//! Enabled with `usage-stats: true` in the user config. The counters are
+//! only stored in the data dir, for the user's own `cargo crev stats me`;
+//! nothing is ever sent anywhere.
+use chrono::Datelike;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Something worth counting
+#[derive(Debug, Clone, Copy)]
+pub enum UsageEvent<'a> {
+    /// A package review was signed and stored
+    Review { crate_name: &'a str },
+    /// Dependencies were verified
+    VerifyRun,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct MonthlyUsage {
+    #[serde(default)]
+    pub reviews: u64,
+    #[serde(default)]
+    pub verify_runs: u64,
+    /// Names of the crates reviewed
+    #[serde(default)]
+    pub crates_reviewed: BTreeSet<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageStats {
+    /// Keyed by `YYYY-MM`
+    #[serde(default)]
+    pub months: BTreeMap<String, MonthlyUsage>,
+}
+
+impl UsageStats {
+    pub fn record(&mut self, event: UsageEvent<'_>, date: &impl Datelike) {
+        let month = format!("{:04}-{:02}", date.year(), date.month());
+        let usage = self.months.entry(month).or_default();
+        match event {
+            UsageEvent::Review { crate_name } => {
+                usage.reviews += 1;
+                usage.crates_reviewed.insert(crate_name.to_owned());
+            }
+            UsageEvent::VerifyRun => usage.verify_runs += 1,
+        }
+    }
+}
@@ -0,0 +1,121 @@
+//! Which files of a crate in a git checkout end up in its published package
+//!
+//! Registry packages don't have everything the repository has: files marked
+//! `export-ignore` in `.gitattributes`, files left out by `package.include`
+//! and `package.exclude` of the manifest, and other packages nested in the
+//! crate's directory. Comparing a git checkout with a registry package has to
+//! leave these out, or they show up as unexpected differences.
+use crate::{Error, Result};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use log::warn;
+use std::path::{Path, PathBuf};
+
+/// The patterns of `include` or `exclude`, gitignore-style like cargo reads them
+fn package_patterns(root: &Path, patterns: &[String]) -> Gitignore {
+    let mut builder = GitignoreBuilder::new(root);
+    for pattern in patterns {
+        if let Err(e) = builder.add_line(None, pattern) {
+            warn!("Ignoring invalid package pattern `{pattern}`: {e}");
+        }
+    }
+    builder.build().unwrap_or_else(|e| {
+        warn!("Ignoring package patterns: {e}");
+        Gitignore::empty()
+    })
+}
+
+fn string_list(package: &toml::Table, key: &str) -> Option<Vec<String>> {
+    Some(
+        package
+            .get(key)?
+            .as_array()?
+            .iter()
+            .filter_map(|value| value.as_str().map(ToOwned::to_owned))
+            .collect(),
+    )
+}
+
+/// Files of a crate in a git checkout that a registry package would have
+pub struct ExportFilter<'repo> {
+    repo: &'repo git2::Repository,
+    /// Of the crate root, relative to the root of the checkout
+    prefix: PathBuf,
+    include: Option<Gitignore>,
+    exclude: Gitignore,
+    /// Directories of other packages inside the crate, relative to its root
+    nested_packages: Vec<PathBuf>,
+}
+
+impl<'repo> ExportFilter<'repo> {
+    /// For the crate in `prefix` of `repo`, with the `Cargo.toml` in `crate_root`
+    ///
+    /// `nested_packages` are the crate's subdirectories with their own `Cargo.toml`.
+    pub fn new(
+        repo: &'repo git2::Repository,
+        crate_root: &Path,
+        prefix: PathBuf,
+        nested_packages: Vec<PathBuf>,
+    ) -> Result<Self> {
+        let manifest_path = crate_root.join("Cargo.toml");
+        let manifest: toml::Table =
+            std::fs::read_to_string(&manifest_path)?
+                .parse()
+                .map_err(|e: toml::de::Error| {
+                    Error::InvalidManifest(Box::new((manifest_path, e.to_string())))
+                })?;
+        let package = manifest.get("package").and_then(|p| p.as_table());
+        let include = package
+            .and_then(|p| string_list(p, "include"))
+            .map(|patterns| package_patterns(crate_root, &patterns));
+        let exclude = package
+            .and_then(|p| string_list(p, "exclude"))
+            .map_or_else(Gitignore::empty, |patterns| {
+                package_patterns(crate_root, &patterns)
+            });
+        Ok(Self {
+            repo,
+            prefix,
+            include,
+            exclude,
+            nested_packages,
+        })
+    }
+
+    fn is_export_ignored(&self, rel_path: &Path) -> bool {
+        // committed attributes only, so local settings don't change digests
+        let flags = git2::AttrCheckFlags::INDEX_ONLY | git2::AttrCheckFlags::NO_SYSTEM;
+        self.repo
+            .get_attr(&self.prefix.join(rel_path), "export-ignore", flags)
+            .is_ok_and(|value| matches!(git2::AttrValue::from_string(value), git2::AttrValue::True))
+    }
+
+    /// Is the file at `rel_path` (relative to the crate root) in the published package
+    ///
+    /// `Cargo.toml` always is; with `include` set, `exclude` doesn't apply,
+    /// as with cargo.
+    #[must_use]
+    pub fn is_exported(&self, rel_path: &Path) -> bool {
+        if rel_path == Path::new("Cargo.toml") {
+            return true;
+        }
+        if self
+            .nested_packages
+            .iter()
+            .any(|dir| rel_path.starts_with(dir))
+        {
+            return false;
+        }
+        if self.is_export_ignored(rel_path) {
+            return false;
+        }
+        match &self.include {
+            Some(include) => include
+                .matched_path_or_any_parents(rel_path, false)
+                .is_ignore(),
+            None => !self
+                .exclude
+                .matched_path_or_any_parents(rel_path, false)
+                .is_ignore(),
+        }
+    }
+}
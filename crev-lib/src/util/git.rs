@@ -1,7 +1,15 @@
 use crate::Result;
-use git2::{ErrorClass, ErrorCode};
-use log::debug;
-use std::path::Path;
+use crev_common::CancellationToken;
+use git2::{CertificateCheckStatus, Cred, CredentialType, ErrorClass, ErrorCode};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeSet,
+    fmt,
+    io::{self, IsTerminal, Write},
+    path::{Path, PathBuf},
+    process::{Command, Output, Stdio},
+};
 
 #[derive(PartialEq, Debug, Default)]
 pub struct GitUrlComponents {
@@ -47,38 +55,568 @@ pub fn parse_git_url_https(http_url: &str) -> Option<GitUrlComponents> {
     })
 }
 
+/// The remote repository is gone, so there's no point in fetching it again
 #[must_use]
 pub fn is_unrecoverable(err: &git2::Error) -> bool {
     matches!(
         (err.class(), err.code()),
         // GitHub's way of saying 404
-        (ErrorClass::Http, ErrorCode::Auth) |
-        (ErrorClass::Repository, ErrorCode::NotFound) |
-        // corrupted loose reference
-        (ErrorClass::Reference, ErrorCode::GenericError)
+        (ErrorClass::Http, ErrorCode::Auth)
     )
 }
 
-pub fn fetch_and_checkout_git_repo(repo: &git2::Repository) -> Result<(), git2::Error> {
-    let mut fetch_options = default_fetch_options();
-    repo.find_remote("origin")?
-        .fetch::<String>(&[], Some(&mut fetch_options), None)?;
+/// The local checkout is broken (missing objects, corrupted references or
+/// index, no repository at all), and has to be cloned again
+#[must_use]
+pub fn is_corrupted(err: &git2::Error) -> bool {
+    err.code() != ErrorCode::Locked
+        && matches!(
+            err.class(),
+            ErrorClass::Repository
+                | ErrorClass::Reference
+                | ErrorClass::Odb
+                | ErrorClass::Object
+                | ErrorClass::Tree
+                | ErrorClass::Index
+                | ErrorClass::Zlib
+        )
+}
+
+/// Check that `HEAD` of a checkout resolves to a commit with its tree, and
+/// that it still has `origin` to fetch from
+///
+/// Interrupted fetches can leave a (shallow) checkout with `HEAD` pointing
+/// at objects that were never written. Errors are [`is_corrupted`].
+pub fn check_checkout(repo: &git2::Repository) -> Result<(), git2::Error> {
+    let corrupted = |e: git2::Error| {
+        git2::Error::new(
+            ErrorCode::GenericError,
+            ErrorClass::Repository,
+            format!("broken checkout: {}", e.message()),
+        )
+    };
+    repo.head()
+        .and_then(|head| head.peel_to_commit())
+        .and_then(|commit| commit.tree())
+        .map_err(corrupted)?;
+    repo.find_remote("origin").map_err(corrupted)?;
+    Ok(())
+}
+
+/// Remove lock files left in `.git` of `repo_dir` by interrupted git processes
+///
+/// Only locks older than `min_age`, as newer ones may belong to a process
+/// that is still running. Returns how many were removed.
+pub fn remove_stale_locks(repo_dir: &Path, min_age: std::time::Duration) -> usize {
+    walkdir::WalkDir::new(repo_dir.join(".git"))
+        .into_iter()
+        .filter_entry(|e| e.file_name() != "objects")
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            e.file_type().is_file() && e.path().extension().is_some_and(|ext| ext == "lock")
+        })
+        .filter(|e| {
+            e.metadata()
+                .ok()
+                .and_then(|metadata| metadata.modified().ok())
+                .and_then(|modified| modified.elapsed().ok())
+                .is_some_and(|age| age >= min_age)
+        })
+        .filter(|e| match std::fs::remove_file(e.path()) {
+            Ok(()) => true,
+            Err(err) => {
+                warn!("Can't remove {}: {}", e.path().display(), err);
+                false
+            }
+        })
+        .count()
+}
+
+/// Fetch `origin` and check out what was fetched
+///
+/// The transfer is aborted if `cancel` gets cancelled, or after `max_bytes`,
+/// see `is_size_limit_error`.
+pub fn fetch_and_checkout_git_repo(
+    repo: &git2::Repository,
+    cancel: &CancellationToken,
+    max_bytes: Option<u64>,
+) -> Result<(), git2::Error> {
+    let mut remote = repo.find_remote("origin")?;
+    let url = remote.url().unwrap_or_default().to_owned();
+    let mut fetch_options = cancellable_fetch_options(&url, cancel, max_bytes);
+    if repo.is_shallow() {
+        fetch_options.depth(1);
+    }
+    remote.fetch::<String>(&[], Some(&mut fetch_options), None)?;
+    cancel_checkpoint(cancel)?;
     repo.set_head("FETCH_HEAD")?;
     let mut opts = git2::build::CheckoutBuilder::new();
     opts.force();
     repo.checkout_head(Some(&mut opts))
 }
 
+/// Are the branches and `HEAD` of `origin` the same as when `repo` was last fetched?
+///
+/// Only lists the refs of the remote, like `git ls-remote`, which is much
+/// cheaper than a fetch when nothing changed.
+pub fn is_up_to_date_with_origin(
+    repo: &git2::Repository,
+    cancel: &CancellationToken,
+) -> Result<bool, git2::Error> {
+    let mut remote = repo.find_remote("origin")?;
+    let port = ssh_port(remote.url().unwrap_or_default());
+    let mut callbacks = git2::RemoteCallbacks::new();
+    callbacks.credentials(credentials_callback());
+    callbacks.certificate_check(move |cert, host| check_certificate(cert, host, port));
+    let mut proxy_options = git2::ProxyOptions::new();
+    proxy_options.auto();
+    let connection =
+        remote.connect_auth(git2::Direction::Fetch, Some(callbacks), Some(proxy_options))?;
+    cancel_checkpoint(cancel)?;
+
+    let local_head = repo.head()?.peel_to_commit()?.id();
+    let mut has_head = false;
+    for remote_head in connection.list()? {
+        let local = if remote_head.name() == "HEAD" {
+            has_head = true;
+            Some(local_head)
+        } else if let Some(branch) = remote_head.name().strip_prefix("refs/heads/") {
+            repo.refname_to_id(&format!("refs/remotes/origin/{branch}"))
+                .ok()
+        } else {
+            continue;
+        };
+        if local != Some(remote_head.oid()) {
+            return Ok(false);
+        }
+    }
+    Ok(has_head)
+}
+
 /// Make a git clone with the default fetch options
+///
+/// The transfer is aborted if `cancel` gets cancelled, or after `max_bytes`,
+/// see `is_size_limit_error`.
 pub fn clone<P: AsRef<Path>>(
     url: &str,
     path: P,
+    cancel: &CancellationToken,
+    max_bytes: Option<u64>,
+) -> std::result::Result<git2::Repository, git2::Error> {
+    clone_with_depth(url, path.as_ref(), cancel, max_bytes, 0)
+}
+
+/// Like `clone`, but only the latest commit, which has all the proofs
+///
+/// Later fetches of the clone stay shallow. libgit2 can't make shallow
+/// clones of local repositories, so these are cloned in full.
+pub fn clone_shallow<P: AsRef<Path>>(
+    url: &str,
+    path: P,
+    cancel: &CancellationToken,
+    max_bytes: Option<u64>,
 ) -> std::result::Result<git2::Repository, git2::Error> {
-    debug!("Cloning {} to {}", url, path.as_ref().display());
-    let fetch_options = default_fetch_options();
+    let is_local = url.starts_with("file://") || Path::new(url).is_absolute();
+    let depth = if is_local { 0 } else { 1 };
+    clone_with_depth(url, path.as_ref(), cancel, max_bytes, depth)
+}
+
+fn clone_with_depth(
+    url: &str,
+    path: &Path,
+    cancel: &CancellationToken,
+    max_bytes: Option<u64>,
+    depth: i32,
+) -> std::result::Result<git2::Repository, git2::Error> {
+    debug!("Cloning {} to {}", url, path.display());
+    let mut fetch_options = cancellable_fetch_options(url, cancel, max_bytes);
+    fetch_options.depth(depth);
     git2::build::RepoBuilder::new()
         .fetch_options(fetch_options)
-        .clone(url, path.as_ref())
+        .clone(url, path)
+}
+
+/// Branches of proof repos with the proofs of one year, eg. `crev-proofs-2024`
+pub const YEARLY_BRANCH_PREFIX: &str = "crev-proofs-";
+
+#[must_use]
+pub fn yearly_branch_name(year: i32) -> String {
+    format!("{YEARLY_BRANCH_PREFIX}{year}")
+}
+
+/// Check out the (local) `branch`, starting it without proofs if it doesn't exist yet
+///
+/// A branch that exists only in `origin` is started from there. Proof files
+/// of the previous branch are removed from the working tree, so commits on a
+/// new branch don't carry them along. Other files, like the README, stay.
+/// Proofs that weren't committed yet are carried over to `branch`. The branch
+/// tracks the one of the same name in `origin`, once it's pushed.
+pub fn switch_to_branch_or_orphan(
+    repo: &git2::Repository,
+    branch: &str,
+) -> Result<(), git2::Error> {
+    let refname = format!("refs/heads/{branch}");
+    let head = repo.find_reference("HEAD")?;
+    if head.symbolic_target() == Some(refname.as_str()) {
+        return Ok(());
+    }
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| git2::Error::from_str("bare repository"))?;
+    let uncommitted = take_uncommitted_proofs(repo)?;
+
+    let has_origin = repo.find_remote("origin").is_ok();
+    if repo.find_reference(&refname).is_err() {
+        if let Ok(remote_branch) = repo.find_reference(&format!("refs/remotes/origin/{branch}")) {
+            repo.branch(branch, &remote_branch.peel_to_commit()?, false)?;
+        }
+    }
+    if repo.find_reference(&refname).is_ok() {
+        repo.set_head(&refname)?;
+        let mut opts = git2::build::CheckoutBuilder::new();
+        opts.force();
+        repo.checkout_head(Some(&mut opts))?;
+    } else {
+        debug!("Starting new branch {}", branch);
+        let mut index = repo.index()?;
+        let proof_paths: Vec<_> = index
+            .iter()
+            .map(|entry| PathBuf::from(String::from_utf8_lossy(&entry.path).as_ref()))
+            .filter(|path| is_proof_file(path))
+            .collect();
+        for path in proof_paths {
+            index.remove_path(&path)?;
+            match std::fs::remove_file(workdir.join(&path)) {
+                // an uncommitted one, taken already
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                Err(e) => warn!("Can't remove {}: {}", path.display(), e),
+                Ok(()) => {}
+            }
+        }
+        index.write()?;
+        repo.set_head(&refname)?;
+    }
+    if has_origin {
+        let mut config = repo.config()?;
+        config.set_str(&format!("branch.{branch}.remote"), "origin")?;
+        config.set_str(&format!("branch.{branch}.merge"), &refname)?;
+    }
+
+    let mut index = repo.index()?;
+    for (path, appended) in uncommitted {
+        debug!("Carrying over uncommitted {}", path.display());
+        append_to_file(&workdir.join(&path), &appended)
+            .map_err(|e| git2::Error::from_str(&format!("{}: {e}", path.display())))?;
+        index.add_path(&path)?;
+    }
+    index.write()
+}
+
+fn is_proof_file(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "crev")
+}
+
+/// Proof files changed since `HEAD`, staged or not, and what was appended to each
+///
+/// Proof files are only ever appended to, so that is all of the uncommitted
+/// proofs. The files are reverted to how they are in `HEAD` in the working tree.
+fn take_uncommitted_proofs(
+    repo: &git2::Repository,
+) -> Result<Vec<(PathBuf, Vec<u8>)>, git2::Error> {
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| git2::Error::from_str("bare repository"))?;
+    let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+    let mut opts = git2::StatusOptions::new();
+    opts.include_untracked(true).recurse_untracked_dirs(true);
+    let mut uncommitted = vec![];
+    for entry in repo.statuses(Some(&mut opts))?.iter() {
+        let Some(path) = entry.path().map(PathBuf::from) else {
+            continue;
+        };
+        if !is_proof_file(&path) || entry.status().intersects(git2::Status::WT_DELETED) {
+            continue;
+        }
+        let io_err = |e: io::Error| git2::Error::from_str(&format!("{}: {e}", path.display()));
+        let content = std::fs::read(workdir.join(&path)).map_err(io_err)?;
+        let committed = head_tree
+            .as_ref()
+            .and_then(|tree| tree.get_path(&path).ok())
+            .and_then(|entry| repo.find_blob(entry.id()).ok());
+        match &committed {
+            Some(blob) => std::fs::write(workdir.join(&path), blob.content()),
+            None => std::fs::remove_file(workdir.join(&path)),
+        }
+        .map_err(io_err)?;
+        let appended = committed
+            .and_then(|blob| content.strip_prefix(blob.content()).map(<[u8]>::to_vec))
+            .unwrap_or(content);
+        if !appended.is_empty() {
+            uncommitted.push((path, appended));
+        }
+    }
+    Ok(uncommitted)
+}
+
+fn append_to_file(path: &Path, content: &[u8]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(path)?
+        .write_all(content)
+}
+
+/// Branches proofs were committed to before switching to yearly branches
+const PRE_YEARLY_BRANCHES: [&str; 2] = ["master", "main"];
+
+/// Commits of the yearly branches (local or fetched) that aren't checked out
+///
+/// Includes `master` (or `main`), with the proofs from before the first
+/// yearly branch. Commits already in the history of the checked out one are
+/// left out. See [`YEARLY_BRANCH_PREFIX`].
+pub fn other_yearly_branch_commits(repo: &git2::Repository) -> Result<Vec<git2::Oid>, git2::Error> {
+    let head = repo.head().ok().and_then(|head| head.target());
+    let mut commits = vec![];
+    for branch in repo.branches(None)? {
+        let (branch, _) = branch?;
+        // remote ones are eg. `origin/crev-proofs-2024`
+        let is_proof_branch = branch
+            .name()?
+            .and_then(|name| name.rsplit('/').next())
+            .is_some_and(|name| {
+                name.starts_with(YEARLY_BRANCH_PREFIX) || PRE_YEARLY_BRANCHES.contains(&name)
+            });
+        if !is_proof_branch {
+            continue;
+        }
+        let Some(oid) = branch.get().target() else {
+            continue;
+        };
+        let in_head = head.is_some_and(|head| {
+            head == oid || repo.graph_descendant_of(head, oid).unwrap_or(false)
+        });
+        if !in_head && !commits.contains(&oid) {
+            commits.push(oid);
+        }
+    }
+    Ok(commits)
+}
+
+#[test]
+fn switch_to_yearly_branch_test() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!(
+        "crev-yearly-branch-test-{}",
+        crev_common::base64_encode(&crev_common::rand::random_vec(8))
+    ));
+    let repo = git2::Repository::init(&dir)?;
+    repo.remote("origin", "https://example.com/crev-proofs")?;
+    repo.set_head("refs/heads/master")?;
+    let signature = git2::Signature::now("crev", "crev@example.com")?;
+    let commit = |message: &str| -> Result<git2::Oid> {
+        let mut index = repo.index()?;
+        index.add_all(["*"], git2::IndexAddOption::DEFAULT, None)?;
+        let tree = repo.find_tree(index.write_tree()?)?;
+        index.write()?;
+        let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        let parents: Vec<_> = parent.iter().collect();
+        Ok(repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parents,
+        )?)
+    };
+    std::fs::write(dir.join("README.md"), "proofs\n")?;
+    std::fs::write(dir.join("old.crev"), "committed\n")?;
+    let master = commit("old proofs")?;
+
+    // not committed yet when the year changes
+    append_to_file(&dir.join("old.crev"), b"appended\n")?;
+    std::fs::write(dir.join("new.crev"), "new\n")?;
+
+    switch_to_branch_or_orphan(&repo, "crev-proofs-2024")?;
+    assert_eq!(
+        repo.find_reference("HEAD")?.symbolic_target(),
+        Some("refs/heads/crev-proofs-2024")
+    );
+    assert_eq!(std::fs::read_to_string(dir.join("old.crev"))?, "appended\n");
+    assert_eq!(std::fs::read_to_string(dir.join("new.crev"))?, "new\n");
+    assert!(dir.join("README.md").exists());
+    let index = repo.index()?;
+    assert!(index.get_path(Path::new("old.crev"), 0).is_some());
+    assert!(index.get_path(Path::new("new.crev"), 0).is_some());
+    assert_eq!(
+        repo.config()?.get_string("branch.crev-proofs-2024.merge")?,
+        "refs/heads/crev-proofs-2024"
+    );
+
+    commit("new proofs")?;
+    assert_eq!(other_yearly_branch_commits(&repo)?, vec![master]);
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+/// Kind of signatures of git commits, like git's `gpg.format`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CommitSigningFormat {
+    Gpg,
+    Ssh,
+}
+
+/// How to sign commits of your proof repo
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommitSigning {
+    pub format: CommitSigningFormat,
+    /// GPG key id, or the path of the SSH private key
+    pub key: String,
+}
+
+impl CommitSigning {
+    /// Sign a commit buffer, like git does with `gpg` or `ssh-keygen`
+    pub fn sign(&self, commit: &str) -> io::Result<String> {
+        let mut cmd = match self.format {
+            CommitSigningFormat::Gpg => {
+                let mut cmd = Command::new("gpg");
+                cmd.args(["--detach-sign", "--armor", "--local-user", &self.key]);
+                cmd
+            }
+            CommitSigningFormat::Ssh => {
+                let mut cmd = Command::new("ssh-keygen");
+                cmd.args(["-Y", "sign", "-n", "git", "-f", &self.key]);
+                cmd
+            }
+        };
+        let output = run_with_stdin(&mut cmd, commit.as_bytes())?;
+        if !output.status.success() {
+            return Err(io::Error::other(format!(
+                "{:?} failed: {}",
+                cmd.get_program(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        String::from_utf8(output.stdout).map_err(io::Error::other)
+    }
+}
+
+fn run_with_stdin(cmd: &mut Command, input: &[u8]) -> io::Result<Output> {
+    let mut child = cmd
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    child
+        .stdin
+        .take()
+        .expect("stdin was piped")
+        .write_all(input)?;
+    child.wait_with_output()
+}
+
+/// Signature of the commit checked out in a proof repo
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommitSignatureStatus {
+    Unsigned,
+    /// Good signature, checked with `gpg`
+    Valid {
+        signer: String,
+    },
+    /// Bad signature, checked with `gpg`
+    Invalid,
+    /// Signed, but it couldn't be checked (unknown key, no `gpg`, SSH signature)
+    Unverified(CommitSigningFormat),
+}
+
+impl fmt::Display for CommitSignatureStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommitSignatureStatus::Unsigned => f.write_str("unsigned"),
+            CommitSignatureStatus::Valid { signer } => write!(f, "signed by {signer}"),
+            CommitSignatureStatus::Invalid => f.write_str("BAD signature"),
+            CommitSignatureStatus::Unverified(CommitSigningFormat::Gpg) => {
+                f.write_str("signed (gpg, not verified)")
+            }
+            CommitSignatureStatus::Unverified(CommitSigningFormat::Ssh) => {
+                f.write_str("signed (ssh, not verified)")
+            }
+        }
+    }
+}
+
+/// Check the signature of `HEAD` of `repo`
+pub fn head_commit_signature_status(
+    repo: &git2::Repository,
+) -> Result<CommitSignatureStatus, git2::Error> {
+    let oid = repo.head()?.peel_to_commit()?.id();
+    let (signature, signed_data) = match repo.extract_signature(&oid, None) {
+        Ok(extracted) => extracted,
+        Err(e) if e.code() == ErrorCode::NotFound => return Ok(CommitSignatureStatus::Unsigned),
+        Err(e) => return Err(e),
+    };
+    let signature = signature.as_str().unwrap_or_default();
+    if signature.starts_with("-----BEGIN SSH SIGNATURE-----") {
+        return Ok(CommitSignatureStatus::Unverified(CommitSigningFormat::Ssh));
+    }
+
+    // gpg needs either the signature or the data in a file
+    let verified = tempfile::NamedTempFile::new().and_then(|mut signature_file| {
+        signature_file.write_all(signature.as_bytes())?;
+        run_with_stdin(
+            Command::new("gpg")
+                .args(["--status-fd=1", "--verify"])
+                .arg(signature_file.path())
+                .arg("-"),
+            &signed_data,
+        )
+    });
+    Ok(match verified {
+        Ok(output) => gpg_verify_status(&String::from_utf8_lossy(&output.stdout)),
+        Err(_) => CommitSignatureStatus::Unverified(CommitSigningFormat::Gpg),
+    })
+}
+
+/// Interpret `gpg --status-fd` output of `--verify`
+fn gpg_verify_status(status: &str) -> CommitSignatureStatus {
+    for line in status.lines() {
+        if let Some(rest) = line.strip_prefix("[GNUPG:] GOODSIG ") {
+            let signer = rest.split_once(' ').map_or(rest, |(_key_id, uid)| uid);
+            return CommitSignatureStatus::Valid {
+                signer: signer.to_owned(),
+            };
+        }
+        if line.starts_with("[GNUPG:] BADSIG ") {
+            return CommitSignatureStatus::Invalid;
+        }
+    }
+    CommitSignatureStatus::Unverified(CommitSigningFormat::Gpg)
+}
+
+#[test]
+fn gpg_verify_status_test() {
+    assert_eq!(
+        gpg_verify_status(
+            "[GNUPG:] NEWSIG\n\
+             [GNUPG:] GOODSIG 0123456789ABCDEF Jane Doe <jane@example.com>\n\
+             [GNUPG:] VALIDSIG 0123\n"
+        ),
+        CommitSignatureStatus::Valid {
+            signer: "Jane Doe <jane@example.com>".into()
+        }
+    );
+    assert_eq!(
+        gpg_verify_status("[GNUPG:] BADSIG 0123456789ABCDEF Jane Doe <jane@example.com>\n"),
+        CommitSignatureStatus::Invalid
+    );
+    assert_eq!(
+        gpg_verify_status("[GNUPG:] ERRSIG 0123456789ABCDEF 22 10 00 1700000000 9\n"),
+        CommitSignatureStatus::Unverified(CommitSigningFormat::Gpg)
+    );
 }
 
 /// Get the default fetch options to use when fetching or cloneing
@@ -95,6 +633,319 @@ pub fn default_fetch_options<'a>() -> git2::FetchOptions<'a> {
     fetch_options
 }
 
+/// `default_fetch_options` for `url` that stop the transfer once `cancel` is cancelled,
+/// or more than `max_bytes` were received
+///
+/// Authenticates like the git CLI would, see `credentials_callback` and `check_certificate`.
+#[must_use]
+pub fn cancellable_fetch_options<'a>(
+    url: &str,
+    cancel: &'a CancellationToken,
+    max_bytes: Option<u64>,
+) -> git2::FetchOptions<'a> {
+    let port = ssh_port(url);
+    let mut callbacks = git2::RemoteCallbacks::new();
+    callbacks.transfer_progress(move |progress| {
+        !cancel.is_cancelled()
+            && max_bytes.map_or(true, |max| progress.received_bytes() as u64 <= max)
+    });
+    callbacks.credentials(credentials_callback());
+    callbacks.certificate_check(move |cert, host| check_certificate(cert, host, port));
+    let mut fetch_options = default_fetch_options();
+    fetch_options.remote_callbacks(callbacks);
+
+    fetch_options
+}
+
+/// Did the transfer stop because it went over `max_bytes` of `cancellable_fetch_options`?
+#[must_use]
+pub fn is_size_limit_error(err: &git2::Error, cancel: &CancellationToken) -> bool {
+    err.code() == ErrorCode::User && !cancel.is_cancelled()
+}
+
+/// Same as a failed HTTP authentication, so `is_unrecoverable` treats it the same
+fn auth_error(msg: &str) -> git2::Error {
+    git2::Error::new(ErrorCode::Auth, ErrorClass::Http, msg)
+}
+
+/// Credentials for libgit2, the way the git CLI gets them
+///
+/// SSH keys come from ssh-agent, and HTTPS usernames and passwords (tokens)
+/// from the configured `credential.helper`s. libgit2 calls this again
+/// after every rejected credential, so each kind is tried only once.
+fn credentials_callback(
+) -> impl FnMut(&str, Option<&str>, CredentialType) -> Result<Cred, git2::Error> {
+    let mut tried = CredentialType::empty();
+    move |url, username_from_url, allowed| {
+        let username = username_from_url.unwrap_or("git");
+        let mut try_next = |kind: CredentialType| {
+            if !allowed.contains(kind) || tried.contains(kind) {
+                return false;
+            }
+            tried |= kind;
+            true
+        };
+        if try_next(CredentialType::USERNAME) {
+            Cred::username(username)
+        } else if try_next(CredentialType::SSH_KEY) {
+            debug!("Trying ssh-agent for {}", url);
+            Cred::ssh_key_from_agent(username)
+        } else if try_next(CredentialType::USER_PASS_PLAINTEXT) {
+            debug!("Trying git credential helpers for {}", url);
+            let config = git2::Config::open_default()?;
+            Cred::credential_helper(&config, url, username_from_url)
+                .map_err(|_| auth_error("no credentials from git credential helpers"))
+        } else if try_next(CredentialType::DEFAULT) {
+            Cred::default()
+        } else {
+            Err(auth_error(
+                "authentication failed (tried ssh-agent and git credential helpers)",
+            ))
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum KnownHost {
+    Match,
+    /// The host is known with a different key of the same type
+    Mismatch,
+    Unknown,
+}
+
+/// Key type (eg. `ssh-ed25519`) from the SSH wire format of a public key
+fn ssh_key_type(key: &[u8]) -> Option<&str> {
+    let len = u32::from_be_bytes(key.get(..4)?.try_into().ok()?) as usize;
+    std::str::from_utf8(key.get(4..4 + len)?).ok()
+}
+
+/// Port of an `ssh://` url, if it's not the default one
+///
+/// scp-like urls (`git@host:path`) can't have one.
+fn ssh_port(url: &str) -> Option<u16> {
+    let rest = ["ssh://", "git+ssh://", "ssh+git://"]
+        .iter()
+        .find_map(|scheme| url.strip_prefix(scheme))?;
+    let authority = rest.split('/').next()?;
+    let host_port = authority.rsplit_once('@').map_or(authority, |(_, h)| h);
+    let (_, port) = host_port.rsplit_once(':')?;
+    port.parse().ok().filter(|&port| port != 22)
+}
+
+/// How `known_hosts` names `host`: `host`, or `[host]:port` for other ports than 22
+fn known_host_name(host: &str, port: Option<u16>) -> String {
+    match port {
+        Some(port) => format!("[{host}]:{port}"),
+        None => host.to_owned(),
+    }
+}
+
+/// Look up `host` (on `port`, if it's not 22) in the content of a `known_hosts` file
+///
+/// Hashed host names aren't supported; those hosts are left to libgit2.
+fn lookup_known_host(known_hosts: &str, host: &str, port: Option<u16>, key: &[u8]) -> KnownHost {
+    let key_type = ssh_key_type(key);
+    let name = known_host_name(host, port);
+    // ssh accepts both for the default port
+    let default_port_name = format!("[{host}]:22");
+    let mut res = KnownHost::Unknown;
+    for line in known_hosts.lines() {
+        let mut fields = line.split_whitespace();
+        let (Some(hosts), Some(line_key_type), Some(line_key)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        if hosts.starts_with('#')
+            || hosts.starts_with('@')
+            || !hosts
+                .split(',')
+                .any(|h| h == name || (port.is_none() && h == default_port_name))
+        {
+            continue;
+        }
+        if crev_common::base64_standard_decode(line_key).is_ok_and(|line_key| line_key == key) {
+            return KnownHost::Match;
+        }
+        if Some(line_key_type) == key_type {
+            res = KnownHost::Mismatch;
+        }
+    }
+    res
+}
+
+fn known_hosts_path() -> Option<PathBuf> {
+    Some(
+        directories::BaseDirs::new()?
+            .home_dir()
+            .join(".ssh")
+            .join("known_hosts"),
+    )
+}
+
+/// Hosts whose keys were rejected in the host key prompt
+///
+/// The lock is held while prompting, so fetches running in parallel ask
+/// one at a time, and only once per host.
+static HOST_KEY_PROMPT: std::sync::Mutex<BTreeSet<String>> = std::sync::Mutex::new(BTreeSet::new());
+
+/// Check SSH host keys against `~/.ssh/known_hosts`, like the ssh CLI
+///
+/// Unknown hosts are confirmed interactively and then remembered;
+/// without a terminal, the decision is left to libgit2.
+fn check_certificate(
+    cert: &git2::cert::Cert<'_>,
+    host: &str,
+    port: Option<u16>,
+) -> Result<CertificateCheckStatus, git2::Error> {
+    let Some(hostkey) = cert.as_hostkey() else {
+        return Ok(CertificateCheckStatus::CertificatePassthrough);
+    };
+    let (Some(key), Some(path)) = (hostkey.hostkey(), known_hosts_path()) else {
+        return Ok(CertificateCheckStatus::CertificatePassthrough);
+    };
+    let key_type = ssh_key_type(key).unwrap_or("unknown");
+    let name = known_host_name(host, port);
+    let rejected = || {
+        git2::Error::new(
+            ErrorCode::Certificate,
+            ErrorClass::Ssh,
+            format!("host key for {name} not accepted"),
+        )
+    };
+    let read_known_hosts = || std::fs::read_to_string(&path).unwrap_or_default();
+    match lookup_known_host(&read_known_hosts(), host, port, key) {
+        KnownHost::Match => Ok(CertificateCheckStatus::CertificateOk),
+        KnownHost::Mismatch => Err(git2::Error::new(
+            ErrorCode::Certificate,
+            ErrorClass::Ssh,
+            format!(
+                "{key_type} host key for {name} doesn't match the one in {}",
+                path.display()
+            ),
+        )),
+        KnownHost::Unknown => {
+            if !std::io::stdin().is_terminal() || !std::io::stderr().is_terminal() {
+                return Ok(CertificateCheckStatus::CertificatePassthrough);
+            }
+            let mut rejected_hosts = HOST_KEY_PROMPT
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            // another fetch could have asked about the same host in the meantime
+            if rejected_hosts.contains(&name) {
+                return Err(rejected());
+            }
+            if lookup_known_host(&read_known_hosts(), host, port, key) == KnownHost::Match {
+                return Ok(CertificateCheckStatus::CertificateOk);
+            }
+            let fingerprint = hostkey
+                .hash_sha256()
+                .map_or_else(|| "?".to_string(), crev_common::base64_standard_encode);
+            eprintln!("The authenticity of host '{name}' can't be established.");
+            eprintln!(
+                "{key_type} key fingerprint is SHA256:{}.",
+                fingerprint.trim_end_matches('=')
+            );
+            if crev_common::yes_or_no_was_y("Continue connecting (y/n)?")
+                .map_err(|e| git2::Error::from_str(&e.to_string()))?
+                != Some(true)
+            {
+                rejected_hosts.insert(name.clone());
+                return Err(rejected());
+            }
+            let line = format!(
+                "{name} {key_type} {}\n",
+                crev_common::base64_standard_encode(key)
+            );
+            if let Err(e) = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .and_then(|mut file| file.write_all(line.as_bytes()))
+            {
+                warn!("Couldn't add {} to {}: {}", name, path.display(), e);
+            }
+            Ok(CertificateCheckStatus::CertificateOk)
+        }
+    }
+}
+
+#[test]
+fn lookup_known_host_test() {
+    let mut key = vec![0, 0, 0, 11];
+    key.extend_from_slice(b"ssh-ed25519");
+    key.extend_from_slice(&[1, 2, 3]);
+    let mut other_key = key.clone();
+    other_key.push(4);
+    let known_hosts = format!(
+        "# comment\n\
+         |1|hashed|host ssh-ed25519 AAAA\n\
+         example.com,github.com ssh-ed25519 {}\n\
+         gitlab.com ssh-rsa {}\n\
+         [git.example.org]:2222 ssh-ed25519 {0}\n\
+         [codeberg.org]:22 ssh-ed25519 {0}\n",
+        crev_common::base64_standard_encode(&key),
+        crev_common::base64_standard_encode(&other_key),
+    );
+
+    assert_eq!(
+        lookup_known_host(&known_hosts, "github.com", None, &key),
+        KnownHost::Match
+    );
+    assert_eq!(
+        lookup_known_host(&known_hosts, "github.com", None, &other_key),
+        KnownHost::Mismatch
+    );
+    // known, but with a key of a different type
+    assert_eq!(
+        lookup_known_host(&known_hosts, "gitlab.com", None, &key),
+        KnownHost::Unknown
+    );
+    assert_eq!(
+        lookup_known_host(&known_hosts, "git.sr.ht", None, &key),
+        KnownHost::Unknown
+    );
+    // other ports than 22 are known as `[host]:port`
+    assert_eq!(
+        lookup_known_host(&known_hosts, "git.example.org", Some(2222), &key),
+        KnownHost::Match
+    );
+    assert_eq!(
+        lookup_known_host(&known_hosts, "git.example.org", None, &key),
+        KnownHost::Unknown
+    );
+    assert_eq!(
+        lookup_known_host(&known_hosts, "github.com", Some(2222), &key),
+        KnownHost::Unknown
+    );
+    assert_eq!(
+        lookup_known_host(&known_hosts, "codeberg.org", None, &key),
+        KnownHost::Match
+    );
+}
+
+#[test]
+fn ssh_port_test() {
+    assert_eq!(ssh_port("ssh://git@example.com:2222/a/b.git"), Some(2222));
+    assert_eq!(ssh_port("ssh://example.com:22/a/b.git"), None);
+    assert_eq!(ssh_port("ssh://git@example.com/a/b.git"), None);
+    assert_eq!(ssh_port("ssh://[::1]:2222/a/b.git"), Some(2222));
+    assert_eq!(ssh_port("git@example.com:a/b.git"), None);
+    assert_eq!(ssh_port("https://example.com:8443/a/b.git"), None);
+}
+
+/// Error out (like an aborted transfer would) if `cancel` was cancelled
+fn cancel_checkpoint(cancel: &CancellationToken) -> Result<(), git2::Error> {
+    if cancel.is_cancelled() {
+        return Err(git2::Error::new(
+            ErrorCode::User,
+            ErrorClass::None,
+            "interrupted",
+        ));
+    }
+    Ok(())
+}
+
 #[test]
 fn parse_git_url_https_test() {
     assert_eq!(
@@ -1,11 +1,12 @@
-use crev_common::sanitize_name_for_fs;
 pub use crev_common::{run_with_shell_cmd, store_str_to_file, store_to_file_with};
-use crev_data::proof;
+use crev_common::{sanitize_name_for_fs, CancellationToken};
+use crev_data::{proof, DigestType};
 use std::borrow::Cow;
 use std::ffi::OsStr;
 use std::io;
 use std::path::{Path, PathBuf};
 
+pub mod export;
 pub mod git;
 
 pub fn get_documentation_for(content: &impl proof::Content) -> &'static str {
@@ -29,21 +30,78 @@ pub fn chmod_path_to_600(path: &Path) -> io::Result<()> {
     Ok(())
 }
 
+/// Local artifacts that are never a part of a published package:
+/// VCS metadata, build output and editor swap/backup files
+///
+/// See `is_ignored_path` for the syntax.
+pub const DEFAULT_DIGEST_IGNORE: &[&str] = &[".git", "target", "*.swp", "*.swo", "*~"];
+
+/// Is `rel_path` on the ignore list
+///
+/// Entries are paths relative to the root of the digested directory,
+/// except entries starting with `*`, which match the end of
+/// a file name at any depth (eg. `*.swp`).
+pub fn is_ignored_path(rel_path: &Path, ignore_list: &fnv::FnvHashSet<PathBuf>) -> bool {
+    if ignore_list.contains(rel_path) {
+        return true;
+    }
+    let Some(file_name) = rel_path.file_name().and_then(OsStr::to_str) else {
+        return false;
+    };
+    ignore_list.iter().any(|entry| {
+        entry
+            .to_str()
+            .and_then(|entry| entry.strip_prefix('*'))
+            .is_some_and(|suffix| file_name.ends_with(suffix))
+    })
+}
+
+/// Recursive digest of `root_path` with the hash function of `digest_type`,
+/// of the entries `filter` accepts
+fn get_recursive_digest_with(
+    digest_type: DigestType,
+    root_path: &Path,
+    filter: impl Fn(&walkdir::DirEntry) -> bool,
+) -> std::result::Result<Vec<u8>, crev_recursive_digest::DigestError> {
+    fn digest_with<H>(
+        root_path: &Path,
+        filter: impl Fn(&walkdir::DirEntry) -> bool,
+    ) -> std::result::Result<Vec<u8>, crev_recursive_digest::DigestError>
+    where
+        H: blake2::digest::Digest + blake2::digest::FixedOutputReset,
+    {
+        crev_recursive_digest::RecursiveDigest::<H, _, _>::new()
+            .filter(filter)
+            .build()
+            .get_digest_of(root_path)
+    }
+
+    match digest_type {
+        DigestType::Blake2b => digest_with::<crev_common::Blake2b256>(root_path, filter),
+        DigestType::Sha256 => digest_with::<sha2::Sha256>(root_path, filter),
+        DigestType::Sha512 => digest_with::<sha2::Sha512>(root_path, filter),
+    }
+}
+
 pub fn get_recursive_digest_for_paths(
     root_path: &Path,
     paths: fnv::FnvHashSet<PathBuf>,
 ) -> std::result::Result<crev_data::Digest, crev_recursive_digest::DigestError> {
-    let h = crev_recursive_digest::RecursiveDigest::<crev_common::Blake2b256, _, _>::new()
-        .filter(|entry| {
-            let rel_path = entry
-                .path()
-                .strip_prefix(root_path)
-                .expect("must be prefix");
-            paths.contains(rel_path)
-        })
-        .build();
+    get_recursive_digest_for_paths_with(DigestType::default(), root_path, paths)
+}
 
-    let digest_vec = h.get_digest_of(root_path)?;
+pub fn get_recursive_digest_for_paths_with(
+    digest_type: DigestType,
+    root_path: &Path,
+    paths: fnv::FnvHashSet<PathBuf>,
+) -> std::result::Result<crev_data::Digest, crev_recursive_digest::DigestError> {
+    let digest_vec = get_recursive_digest_with(digest_type, root_path, |entry| {
+        let rel_path = entry
+            .path()
+            .strip_prefix(root_path)
+            .expect("must be prefix");
+        paths.contains(rel_path)
+    })?;
     Ok(crev_data::Digest::from_bytes(&digest_vec).unwrap())
 }
 
@@ -51,17 +109,45 @@ pub fn get_recursive_digest_for_dir(
     root_path: &Path,
     rel_path_ignore_list: &fnv::FnvHashSet<PathBuf>,
 ) -> std::result::Result<Vec<u8>, crev_recursive_digest::DigestError> {
-    let h = crev_recursive_digest::RecursiveDigest::<crev_common::Blake2b256, _, _>::new()
-        .filter(|entry| {
-            let rel_path = entry
-                .path()
-                .strip_prefix(root_path)
-                .expect("must be prefix");
-            !rel_path_ignore_list.contains(rel_path)
-        })
-        .build();
+    get_recursive_digest_for_dir_cancellable(
+        root_path,
+        rel_path_ignore_list,
+        &CancellationToken::new(),
+    )
+}
 
-    h.get_digest_of(root_path)
+/// Once `cancel` is cancelled, all remaining entries are skipped,
+/// so the result is garbage and the caller must check the token.
+pub fn get_recursive_digest_for_dir_cancellable(
+    root_path: &Path,
+    rel_path_ignore_list: &fnv::FnvHashSet<PathBuf>,
+    cancel: &CancellationToken,
+) -> std::result::Result<Vec<u8>, crev_recursive_digest::DigestError> {
+    get_recursive_digest_for_dir_with(
+        DigestType::default(),
+        root_path,
+        rel_path_ignore_list,
+        cancel,
+    )
+}
+
+/// Like `get_recursive_digest_for_dir_cancellable`, with the hash function of `digest_type`
+pub fn get_recursive_digest_for_dir_with(
+    digest_type: DigestType,
+    root_path: &Path,
+    rel_path_ignore_list: &fnv::FnvHashSet<PathBuf>,
+    cancel: &CancellationToken,
+) -> std::result::Result<Vec<u8>, crev_recursive_digest::DigestError> {
+    get_recursive_digest_with(digest_type, root_path, |entry| {
+        if cancel.is_cancelled() {
+            return false;
+        }
+        let rel_path = entry
+            .path()
+            .strip_prefix(root_path)
+            .expect("must be prefix");
+        !is_ignored_path(rel_path, rel_path_ignore_list)
+    })
 }
 
 fn mark_dangerous_name(
@@ -155,6 +241,18 @@ fn mark_dangerous_name(
     }
 }
 
+/// Total size of the files in `dir`, in bytes, including dotfiles like `.git`
+#[must_use]
+pub fn dir_size(dir: &Path) -> u64 {
+    walkdir::WalkDir::new(dir)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.metadata().ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
 /// Make a copy of the directory, but skip or rename all files that are potentially dangerous in Cargo projects
 pub fn copy_dir_sanitized(
     src_dir: &Path,
@@ -0,0 +1,98 @@
+//! Checks of proof files before they are published
+//!
+//! Other users' crev silently skips proofs it can't parse or verify, so a
+//! broken proof pushed to a proof repo just disappears for everyone else.
+//! `validate_proof_file` finds such problems early, eg. in a git `pre-push` hook.
+use crev_data::proof::{self, CommonOps, ContentDeserialize};
+
+/// A problem with a proof file
+#[derive(Debug, thiserror::Error)]
+pub enum ProofProblem {
+    #[error("Not UTF-8")]
+    NotUtf8,
+
+    #[error("Can't be parsed: {0}")]
+    Unparsable(#[source] crev_data::Error),
+
+    #[error("Proof {index}: invalid signature: {error}")]
+    InvalidSignature {
+        index: usize,
+        #[source]
+        error: crev_data::Error,
+    },
+
+    #[error("Proof {index}: invalid `{kind}` content: {error}")]
+    InvalidContent {
+        index: usize,
+        kind: String,
+        #[source]
+        error: crev_data::Error,
+    },
+
+    #[error("Proof {index}: unknown kind `{kind}`")]
+    UnknownKind { index: usize, kind: String },
+
+    #[error("Not in the canonical format crev writes proofs in")]
+    NotCanonical,
+}
+
+/// Check the content of a proof with its expected type
+fn validate_content<T: ContentDeserialize>(proof: &proof::Proof) -> Result<(), crev_data::Error> {
+    proof.parse_content::<T>().map(drop)
+}
+
+/// Check a proof file: parsing, signatures, content schema, and formatting
+///
+/// Proofs are counted from 1. Returns no problems if the file is fine.
+#[must_use]
+pub fn validate_proof_file(content: &[u8]) -> Vec<ProofProblem> {
+    let Ok(text) = std::str::from_utf8(content) else {
+        return vec![ProofProblem::NotUtf8];
+    };
+    let proofs = match proof::Proof::parse_from(text.as_bytes()) {
+        Ok(proofs) => proofs,
+        Err(e) => return vec![ProofProblem::Unparsable(e)],
+    };
+
+    let mut problems = vec![];
+    for (index, proof) in (1..).zip(&proofs) {
+        if let Err(error) = proof.verify() {
+            problems.push(ProofProblem::InvalidSignature { index, error });
+        }
+        let kind = proof.kind();
+        let content = match kind {
+            proof::CodeReview::KIND => validate_content::<proof::CodeReview>(proof),
+            proof::PackageReview::KIND => validate_content::<proof::PackageReview>(proof),
+            proof::Trust::KIND => validate_content::<proof::Trust>(proof),
+            proof::Epoch::KIND => validate_content::<proof::Epoch>(proof),
+            proof::ReviewRequest::KIND => validate_content::<proof::ReviewRequest>(proof),
+            proof::IssueUpdate::KIND => validate_content::<proof::IssueUpdate>(proof),
+            proof::Group::KIND => validate_content::<proof::Group>(proof),
+            proof::Successor::KIND => validate_content::<proof::Successor>(proof),
+            proof::Waiver::KIND => validate_content::<proof::Waiver>(proof),
+            proof::TrustSnapshot::KIND => validate_content::<proof::TrustSnapshot>(proof),
+            proof::Encrypted::KIND => validate_content::<proof::Encrypted>(proof),
+            _ => {
+                problems.push(ProofProblem::UnknownKind {
+                    index,
+                    kind: kind.to_owned(),
+                });
+                continue;
+            }
+        };
+        if let Err(error) = content {
+            problems.push(ProofProblem::InvalidContent {
+                index,
+                kind: kind.to_owned(),
+                error,
+            });
+        }
+    }
+
+    // what `Local::insert` writes
+    let canonical: String = proofs.iter().map(|proof| format!("{proof}\n")).collect();
+    if problems.is_empty() && text != canonical {
+        problems.push(ProofProblem::NotCanonical);
+    }
+    problems
+}
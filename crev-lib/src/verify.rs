@@ -0,0 +1,538 @@
+//! Verification of dependencies, the way `cargo crev verify` does it
+//!
+//! For tools that want crev's verdict on their dependencies (IDE plugins,
+//! CI bots) without running the `cargo crev` binary. They find the crates
+//! and their source code (eg. with `cargo metadata`), and get a
+//! [`CrateVerificationReport`] for every one of them.
+use crate::{
+    find_latest_trusted_version, get_dir_digest_cancellable, negative_package_reviews,
+    package_contains_subtree, util, util::export::ExportFilter, verify_package_digest,
+    DigestIgnorePolicy, Error, Result, VerificationRequirements, VerificationStatus,
+};
+use crev_common::CancellationToken;
+use crev_data::{
+    proof::{self, review, CommonOps},
+    Digest, DigestType, Level, PublicId, Version, SOURCE_GIT_PREFIX,
+};
+use crev_wot::{ProofDB, TrustSet};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeSet, HashSet},
+    ffi::OsStr,
+    path::{Path, PathBuf},
+};
+
+/// A crate to verify
+#[derive(Debug, Clone)]
+pub struct Dependency {
+    /// [`crev_data::SOURCE_CRATES_IO`], or a git repository (see [`SOURCE_GIT_PREFIX`])
+    pub source: String,
+    pub name: String,
+    pub version: Version,
+    /// Directory with the source code of the crate
+    pub root: PathBuf,
+    /// Neither from a registry nor git (eg. a path dependency), so not reviewed as is
+    pub is_local_source_code: bool,
+}
+
+impl Dependency {
+    #[must_use]
+    pub fn is_git(&self) -> bool {
+        self.source.starts_with(SOURCE_GIT_PREFIX)
+    }
+}
+
+/// An issue reported against a crate, and not closed by an issue update
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OpenIssue {
+    pub id: String,
+    /// Highest reported severity, see `crev_wot::IssueDetails::severity`
+    pub severity: Level,
+}
+
+/// What crev knows about one dependency
+#[derive(Debug, Clone)]
+pub struct CrateVerificationReport {
+    pub name: String,
+    pub version: Version,
+    /// `None` for local source code
+    pub digest: Option<Digest>,
+    pub status: VerificationStatus,
+    /// Reviewers of this version trusted at least as much as required
+    pub trusted_reviewers: HashSet<PublicId>,
+    /// Reviews of this version, by anyone
+    pub version_reviews: usize,
+    /// Reviews of any version, by anyone
+    pub total_reviews: usize,
+    pub latest_trusted_version: Option<Version>,
+    /// Someone reported a different digest, the local copy is possibly wrong
+    pub digest_mismatches: Vec<review::Package>,
+    /// Open issues reported by trusted Ids, most severe first
+    pub open_issues: Vec<OpenIssue>,
+    /// Number of open issues reported by anyone
+    pub all_open_issues: usize,
+    /// Reviews by trusted Ids making the crate fail with a negative status
+    pub negative_reviews: Vec<review::Package>,
+    /// A trusted Id flagged the crate as unmaintained
+    pub is_unmaintained: bool,
+    /// Directories of the crate reviewed on their own that are the same
+    /// locally; their reviews don't count for the whole crate
+    pub subtrees: Vec<SubtreeVerification>,
+}
+
+impl CrateVerificationReport {
+    #[must_use]
+    pub fn is_verified(&self) -> bool {
+        self.status.is_verified()
+    }
+}
+
+/// A directory of a crate reviewed on its own (`review --subtree`)
+#[derive(Debug, Clone)]
+pub struct SubtreeVerification {
+    pub path: String,
+    pub exclude: Vec<String>,
+    /// Of the subtree digest, counting only reviews of this subtree
+    pub status: VerificationStatus,
+}
+
+/// How many of the dependencies are verified
+///
+/// Local source code isn't reviewed as is, so it's not counted at all.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerificationCoverage {
+    pub verified: usize,
+    pub total: usize,
+}
+
+impl VerificationCoverage {
+    #[must_use]
+    pub fn of<'a>(reports: impl IntoIterator<Item = &'a CrateVerificationReport>) -> Self {
+        let mut coverage = Self::default();
+        for report in reports {
+            coverage.add(report.status);
+        }
+        coverage
+    }
+
+    pub fn add(&mut self, status: VerificationStatus) {
+        match status {
+            VerificationStatus::Local => {}
+            VerificationStatus::Verified => {
+                self.verified += 1;
+                self.total += 1;
+            }
+            VerificationStatus::Insufficient | VerificationStatus::Negative => self.total += 1,
+        }
+    }
+
+    /// Verified share, rounded down so it's 100 only if all are verified
+    ///
+    /// `None` if there's nothing to verify.
+    #[must_use]
+    pub fn percent(&self) -> Option<u8> {
+        if self.total == 0 {
+            return None;
+        }
+        Some(u8::try_from(self.verified * 100 / self.total).unwrap_or(100))
+    }
+}
+
+/// Digest of a crate from a registry at `root`, computed the way reviewers compute it
+///
+/// If nobody reviewed that digest, but some of the files `local_ignore_list`
+/// leaves out were added to the local copy (eg. `target`), and the digest
+/// without them was reviewed, that digest is returned instead.
+pub fn get_registry_crate_digest(
+    db: &ProofDB,
+    root: &Path,
+    policy: &DigestIgnorePolicy,
+    local_ignore_list: &fnv::FnvHashSet<PathBuf>,
+    cancel: &CancellationToken,
+) -> Result<Digest> {
+    let digest = get_dir_digest_cancellable(root, policy.ignore_list(), cancel)?;
+    if local_ignore_list == policy.ignore_list()
+        || db.get_package_reviews_by_digest(&digest).next().is_some()
+    {
+        return Ok(digest);
+    }
+    let local_digest = get_dir_digest_cancellable(root, local_ignore_list, cancel)?;
+    Ok(select_registry_crate_digest(db, digest, Some(local_digest)))
+}
+
+/// The digest [`get_registry_crate_digest`] picks, for digests computed beforehand
+///
+/// `digest` is computed with the policy's ignore list, `local_digest` (if
+/// different) with the local one.
+#[must_use]
+pub fn select_registry_crate_digest(
+    db: &ProofDB,
+    digest: Digest,
+    local_digest: Option<Digest>,
+) -> Digest {
+    match local_digest {
+        Some(local_digest)
+            if db.get_package_reviews_by_digest(&digest).next().is_none()
+                && db
+                    .get_package_reviews_by_digest(&local_digest)
+                    .next()
+                    .is_some() =>
+        {
+            local_digest
+        }
+        _ => digest,
+    }
+}
+
+/// Digest of a crate in a git checkout at `root`
+///
+/// Only files committed to the repository are digested, so files cargo or
+/// a build add to the checkout don't change it. `root` can be a subdirectory
+/// of the checkout, for crates in a workspace. Files a registry package
+/// wouldn't have are left out too, see [`ExportFilter`].
+pub fn get_git_crate_digest(root: &Path, ignore_list: &fnv::FnvHashSet<PathBuf>) -> Result<Digest> {
+    get_git_crate_digest_with(root, ignore_list, DigestType::default())
+}
+
+/// Like [`get_git_crate_digest`], with the hash function of `digest_type`
+pub fn get_git_crate_digest_with(
+    root: &Path,
+    ignore_list: &fnv::FnvHashSet<PathBuf>,
+    digest_type: DigestType,
+) -> Result<Digest> {
+    let repo = git2::Repository::discover(root)?;
+    let not_a_checkout = || Error::NotAGitCheckout(root.into());
+    let workdir = repo.workdir().ok_or_else(not_a_checkout)?;
+    let prefix = root
+        .canonicalize()?
+        .strip_prefix(workdir.canonicalize()?)
+        .map_err(|_| not_a_checkout())?
+        .to_owned();
+
+    let mut paths = vec![];
+    repo.head()?
+        .peel_to_tree()?
+        .walk(git2::TreeWalkMode::PreOrder, |dir, entry| {
+            if entry.kind() != Some(git2::ObjectType::Blob) {
+                return git2::TreeWalkResult::Ok;
+            }
+            let Some(name) = entry.name() else {
+                return git2::TreeWalkResult::Ok;
+            };
+            if let Ok(path) = Path::new(dir).join(name).strip_prefix(&prefix) {
+                if !util::is_ignored_path(path, ignore_list) {
+                    paths.push(path.to_owned());
+                }
+            }
+            git2::TreeWalkResult::Ok
+        })?;
+
+    let nested_packages = paths
+        .iter()
+        .filter(|path| path.file_name() == Some(OsStr::new("Cargo.toml")))
+        .filter_map(|path| path.parent())
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .map(Path::to_owned)
+        .collect();
+    let filter = ExportFilter::new(&repo, root, prefix, nested_packages)?;
+    let mut paths: fnv::FnvHashSet<_> = paths
+        .into_iter()
+        .filter(|path| filter.is_exported(path))
+        .collect();
+    // the digest walks the directories too, so they have to be let through
+    let dirs: Vec<_> = paths
+        .iter()
+        .flat_map(|path| path.ancestors().skip(1))
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .map(Path::to_owned)
+        .collect();
+    paths.extend(dirs);
+    Ok(util::get_recursive_digest_for_paths_with(
+        digest_type,
+        root,
+        paths,
+    )?)
+}
+
+/// Reviews of this crate version that were made for different code
+///
+/// `digest` is of the code at `root`, computed with `policy`. Reviews made
+/// with other policies are compared with the digest computed with theirs.
+/// Reviews of a subtree (`review --subtree`) still apply if the subtree
+/// at `root` is the same.
+#[must_use]
+pub fn get_crate_digest_mismatches(
+    db: &ProofDB,
+    source: &str,
+    name: &str,
+    version: &Version,
+    digest: &Digest,
+    root: &Path,
+    policy: &DigestIgnorePolicy,
+) -> Vec<review::Package> {
+    db.get_package_reviews_for_package(source, Some(name), Some(version))
+        // imported proofs (eg. from RustSec) aren't about any particular digest
+        .filter(|review| !review.package.digest.is_empty())
+        // reviews of a patch have the digest of the patch
+        .filter(|review| review.patch.is_none())
+        .filter(|review| !policy.matches_package(&review.package, digest, root))
+        .filter(|review| {
+            !review.subtree.as_ref().is_some_and(|subtree| {
+                package_contains_subtree(root, subtree, policy.ignore_list()).unwrap_or(false)
+            })
+        })
+        .cloned()
+        .collect()
+}
+
+/// Verify one dependency, with its `digest` already computed (`None` for local source code)
+///
+/// `policy` is the one `digest` was computed with.
+#[must_use]
+pub fn verify_crate(
+    dependency: &Dependency,
+    digest: Option<Digest>,
+    policy: &DigestIgnorePolicy,
+    trust_set: &TrustSet,
+    requirements: &VerificationRequirements,
+    db: &ProofDB,
+) -> CrateVerificationReport {
+    let (source, name, version) = (
+        dependency.source.as_str(),
+        dependency.name.as_str(),
+        &dependency.version,
+    );
+    let digest_mismatches = digest
+        .as_ref()
+        .map(|digest| {
+            get_crate_digest_mismatches(db, source, name, version, digest, &dependency.root, policy)
+        })
+        .unwrap_or_default();
+    let mut status = match digest.as_ref() {
+        Some(digest) => verify_package_digest(digest, trust_set, requirements, db),
+        None => VerificationStatus::Local,
+    };
+    if status == VerificationStatus::Insufficient && !dependency.is_git() {
+        if let Some(normalized_status) =
+            verify_registry_normalized(dependency, trust_set, requirements, db)
+        {
+            status = normalized_status;
+        }
+    }
+    if status == VerificationStatus::Insufficient && digest.is_some() {
+        if let Some(other_status) =
+            verify_other_digest_types(dependency, trust_set, requirements, db)
+        {
+            status = other_status;
+        }
+    }
+    let negative_reviews = match (&digest, status) {
+        (Some(digest), VerificationStatus::Negative) => {
+            negative_package_reviews(digest, trust_set, db)
+        }
+        _ => vec![],
+    };
+
+    let version_reviews: Vec<_> = db
+        .get_package_reviews_for_package(source, Some(name), Some(version))
+        .collect();
+    let trusted_reviewers = version_reviews
+        .iter()
+        // reviewed a patch or a subtree, not the package
+        .filter(|review| review.patch.is_none() && review.subtree.is_none())
+        .map(|review| review.from().clone())
+        .filter(|id| trust_set.get_effective_trust_level(&id.id) >= requirements.trust_level.into())
+        .collect();
+    let subtrees = if digest.is_some() {
+        verify_subtrees(
+            &version_reviews,
+            &dependency.root,
+            policy,
+            trust_set,
+            requirements,
+            db,
+        )
+    } else {
+        vec![]
+    };
+
+    let issues_from_trusted = db.get_open_issues_for_version(
+        source,
+        name,
+        version,
+        trust_set,
+        requirements.trust_level.into(),
+    );
+    let all_open_issues = db
+        .get_open_issues_for_version(source, name, version, trust_set, Level::None.into())
+        .len();
+    let mut open_issues: Vec<_> = issues_from_trusted
+        .into_iter()
+        .map(|(id, details)| OpenIssue {
+            id,
+            severity: details.severity,
+        })
+        .collect();
+    open_issues.sort_by(|a, b| b.severity.cmp(&a.severity).then_with(|| a.id.cmp(&b.id)));
+
+    let proof_pkg_id = proof::PackageId {
+        source: source.into(),
+        name: name.to_string(),
+    };
+    let is_unmaintained = db
+        .get_pkg_flags(&proof_pkg_id)
+        .any(|(id, flags)| trust_set.is_trusted(id) && flags.unmaintained);
+
+    CrateVerificationReport {
+        name: name.to_string(),
+        version: version.clone(),
+        digest,
+        status,
+        trusted_reviewers,
+        version_reviews: version_reviews.len(),
+        total_reviews: db.get_package_review_count(source, Some(name), None),
+        latest_trusted_version: find_latest_trusted_version(
+            trust_set,
+            source,
+            name,
+            requirements,
+            db,
+        ),
+        digest_mismatches,
+        open_issues,
+        all_open_issues,
+        negative_reviews,
+        is_unmaintained,
+        subtrees,
+    }
+}
+
+/// Subtrees that `reviews` are of, if they are the same at `root`
+fn verify_subtrees(
+    reviews: &[&review::Package],
+    root: &Path,
+    policy: &DigestIgnorePolicy,
+    trust_set: &TrustSet,
+    requirements: &VerificationRequirements,
+    db: &ProofDB,
+) -> Vec<SubtreeVerification> {
+    let mut seen = HashSet::new();
+    reviews
+        .iter()
+        .filter_map(|review| review.subtree.as_ref())
+        .filter(|subtree| seen.insert((&subtree.path, &subtree.exclude, &subtree.digest)))
+        .filter(|subtree| {
+            package_contains_subtree(root, subtree, policy.ignore_list()).unwrap_or(false)
+        })
+        .filter_map(|subtree| {
+            let digest = Digest::from_bytes(&subtree.digest)?;
+            Some(SubtreeVerification {
+                path: subtree.path.clone(),
+                exclude: subtree.exclude.clone(),
+                status: verify_package_digest(&digest, trust_set, requirements, db),
+            })
+        })
+        .collect()
+}
+
+/// Verify with the digest of [`DigestIgnorePolicy::registry_normalized`], if any review
+/// of this version was made with it (eg. a review of the source in git)
+fn verify_registry_normalized(
+    dependency: &Dependency,
+    trust_set: &TrustSet,
+    requirements: &VerificationRequirements,
+    db: &ProofDB,
+) -> Option<VerificationStatus> {
+    let policy = DigestIgnorePolicy::registry_normalized();
+    let has_normalized_reviews = db
+        .get_package_reviews_for_package(
+            &dependency.source,
+            Some(&dependency.name),
+            Some(&dependency.version),
+        )
+        .any(|review| review.package.digest_ignore.as_deref() == Some(policy.id()));
+    if !has_normalized_reviews {
+        return None;
+    }
+    let digest = policy.digest(&dependency.root).ok()?;
+    Some(verify_package_digest(&digest, trust_set, requirements, db))
+}
+
+/// Verify with digests of other hash functions (eg. `sha256`), if any review
+/// of this version was made with them
+fn verify_other_digest_types(
+    dependency: &Dependency,
+    trust_set: &TrustSet,
+    requirements: &VerificationRequirements,
+    db: &ProofDB,
+) -> Option<VerificationStatus> {
+    let digest_types: BTreeSet<_> = db
+        .get_package_reviews_for_package(
+            &dependency.source,
+            Some(&dependency.name),
+            Some(&dependency.version),
+        )
+        .filter_map(|review| {
+            let digest_type = review.package.known_digest_type()?;
+            let policy = DigestIgnorePolicy::of_package(&review.package).ok()?;
+            (digest_type != DigestType::default()).then(|| (digest_type, policy.id().to_owned()))
+        })
+        .collect();
+    digest_types
+        .into_iter()
+        .filter_map(|(digest_type, policy)| {
+            let policy = DigestIgnorePolicy::by_id(&policy).ok()?;
+            let digest = if dependency.is_git() {
+                get_git_crate_digest_with(&dependency.root, policy.ignore_list(), digest_type)
+            } else {
+                policy.digest_with(&dependency.root, digest_type)
+            }
+            .ok()?;
+            Some(verify_package_digest(&digest, trust_set, requirements, db))
+        })
+        .find(|status| *status != VerificationStatus::Insufficient)
+}
+
+/// Verify all `dependencies` with the same `requirements`
+///
+/// Digests are computed with [`DigestIgnorePolicy::cargo_crate`], like reviews
+/// made with `cargo crev`. Git dependencies are digested with
+/// [`get_git_crate_digest`].
+pub fn verify_dependencies(
+    dependencies: &[Dependency],
+    trust_set: &TrustSet,
+    requirements: &VerificationRequirements,
+    db: &ProofDB,
+) -> Result<Vec<CrateVerificationReport>> {
+    let policy = DigestIgnorePolicy::cargo_crate();
+    let cancel = CancellationToken::default();
+    dependencies
+        .iter()
+        .map(|dependency| {
+            let digest = if dependency.is_local_source_code {
+                None
+            } else if dependency.is_git() {
+                Some(get_git_crate_digest(
+                    &dependency.root,
+                    policy.ignore_list(),
+                )?)
+            } else {
+                Some(get_registry_crate_digest(
+                    db,
+                    &dependency.root,
+                    &policy,
+                    policy.ignore_list(),
+                    &cancel,
+                )?)
+            };
+            Ok(verify_crate(
+                dependency,
+                digest,
+                &policy,
+                trust_set,
+                requirements,
+                db,
+            ))
+        })
+        .collect()
+}
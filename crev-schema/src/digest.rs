@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// This is a cryptographic hash of everything in a crate, which reliably identifies its exact source code
+///
+/// 32 bytes, or 64 for [`DigestType::Sha512`].
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct Digest(Vec<u8>);
+
+impl From<[u8; 32]> for Digest {
+    fn from(arr: [u8; 32]) -> Self {
+        Self(arr.to_vec())
+    }
+}
+
+impl Digest {
+    #[must_use]
+    pub fn as_slice(&self) -> &[u8] {
+        &self.0
+    }
+
+    #[must_use]
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() == 32 || bytes.len() == 64 {
+            Some(Self(bytes.to_vec()))
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    pub fn into_vec(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+impl fmt::Display for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&crate::util::base64_encode(&self.0))
+    }
+}
+
+/// Hash function of package digests (`digest_type` of proofs)
+#[derive(
+    Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Default,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum DigestType {
+    /// Blake2b with 256 bits of output
+    #[default]
+    Blake2b,
+    Sha256,
+    Sha512,
+}
+
+impl DigestType {
+    pub const ALL: [Self; 3] = [Self::Blake2b, Self::Sha256, Self::Sha512];
+
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Blake2b => "blake2b",
+            Self::Sha256 => "sha256",
+            Self::Sha512 => "sha512",
+        }
+    }
+
+    /// Of digests, in bytes
+    #[must_use]
+    pub fn output_len(self) -> usize {
+        match self {
+            Self::Blake2b | Self::Sha256 => 32,
+            Self::Sha512 => 64,
+        }
+    }
+}
+
+impl fmt::Display for DigestType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+#[error(
+    "Unknown digest type `{}`, must be one of: blake2b, sha256, sha512",
+    _0
+)]
+pub struct UnknownDigestType(pub Box<str>);
+
+impl std::str::FromStr for DigestType {
+    type Err = UnknownDigestType;
+
+    fn from_str(s: &str) -> Result<Self, UnknownDigestType> {
+        Self::ALL
+            .into_iter()
+            .find(|digest_type| digest_type.as_str() == s)
+            .ok_or_else(|| UnknownDigestType(s.into()))
+    }
+}
@@ -0,0 +1,320 @@
+use crate::{
+    proof::{self, OverrideItem},
+    util::{self, as_base64, from_base64},
+    Url,
+};
+use derive_builder::Builder;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum IdType {
+    #[serde(rename = "crev")]
+    Crev,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum IdError {
+    #[error("wrong length of crev id, expected 32 bytes, got {}", _0)]
+    WrongIdLength(usize),
+    #[error("Invalid CrevId: {}", _0)]
+    InvalidCrevId(Box<str>),
+    #[error("Invalid signature: {}", _0)]
+    InvalidSignature(Box<str>),
+    #[error("Invalid public key: {}", _0)]
+    InvalidPublicKey(Box<str>),
+    #[error("Invalid secret key: {}", _0)]
+    InvalidSecretKey(Box<str>),
+}
+
+impl fmt::Display for IdType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use IdType::Crev;
+        f.write_str(match self {
+            Crev => "crev",
+        })
+    }
+}
+
+/// An Id supported by `crev` system
+///
+/// Right now it's only native `CrevID`, but in future at least GPG
+/// should be supported.
+#[derive(Clone, Serialize, Deserialize, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(tag = "id-type")]
+pub enum Id {
+    #[serde(rename = "crev")]
+    Crev {
+        #[serde(serialize_with = "as_base64", deserialize_with = "from_base64")]
+        id: Vec<u8>,
+    },
+}
+
+impl fmt::Debug for Id {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Id::Crev { id } => f.write_str(&util::base64_encode(id)),
+        }
+    }
+}
+
+impl fmt::Display for Id {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Id::Crev { id } => f.write_str(&util::base64_encode(id)),
+        }
+    }
+}
+
+impl Id {
+    pub fn new_crev(bytes: Vec<u8>) -> Result<Self, IdError> {
+        if bytes.len() != 32 {
+            return Err(IdError::WrongIdLength(bytes.len()));
+        }
+        Ok(Id::Crev { id: bytes })
+    }
+
+    pub fn crevid_from_str(s: &str) -> Result<Self, IdError> {
+        let bytes =
+            util::base64_decode(s).map_err(|e| IdError::InvalidCrevId(e.to_string().into()))?;
+        Self::new_crev(bytes)
+    }
+
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Id::Crev { id } => id.clone(),
+        }
+    }
+
+    #[cfg(feature = "crypto")]
+    pub fn verify_signature(&self, content: &[u8], sig_str: &str) -> Result<(), IdError> {
+        use ed25519_dalek::{Verifier, VerifyingKey};
+
+        match self {
+            Id::Crev { id } => {
+                let pubkey = VerifyingKey::from_bytes(
+                    id.as_slice()
+                        .try_into()
+                        .map_err(|_| IdError::WrongIdLength(id.len()))?,
+                )
+                .map_err(|e| IdError::InvalidPublicKey(e.to_string().into()))?;
+
+                let sig_bytes = util::base64_decode(sig_str)
+                    .map_err(|e| IdError::InvalidSignature(e.to_string().into()))?;
+                let signature = ed25519_dalek::Signature::try_from(sig_bytes.as_slice())
+                    .map_err(|e| IdError::InvalidSignature(e.to_string().into()))?;
+                pubkey
+                    .verify(content, &signature)
+                    .map_err(|e| IdError::InvalidSignature(e.to_string().into()))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A unique ID accompanied by publicly identifying data.
+#[derive(Clone, Debug, Builder, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct PublicId {
+    #[serde(flatten)]
+    pub id: Id,
+    #[serde(flatten)]
+    pub url: Option<Url>,
+}
+
+impl PublicId {
+    #[must_use]
+    pub fn new(id: Id, url: Url) -> Self {
+        Self { id, url: Some(url) }
+    }
+
+    #[must_use]
+    pub fn new_id_only(id: Id) -> Self {
+        Self { id, url: None }
+    }
+
+    pub fn new_from_pubkey(v: Vec<u8>, url: Option<Url>) -> Result<Self, IdError> {
+        Ok(Self {
+            id: Id::new_crev(v)?,
+            url,
+        })
+    }
+
+    pub fn new_crevid_from_base64(s: &str, url: Url) -> Result<Self, IdError> {
+        let v = util::base64_decode(s).map_err(|e| IdError::InvalidCrevId(e.to_string().into()))?;
+        Ok(Self {
+            id: Id::new_crev(v)?,
+            url: Some(url),
+        })
+    }
+
+    #[must_use]
+    pub fn url_display(&self) -> &str {
+        match &self.url {
+            Some(url) => &url.url,
+            None => "(no url)",
+        }
+    }
+
+    pub fn create_trust_proof<'a>(
+        &self,
+        ids: impl IntoIterator<Item = &'a PublicId>,
+        trust_level: proof::trust::TrustLevel,
+        override_: Vec<OverrideItem>,
+    ) -> Result<proof::Trust, proof::BuildProofError> {
+        proof::TrustBuilder::default()
+            .from(self.clone())
+            .trust(trust_level)
+            .ids(ids.into_iter().cloned().collect())
+            .override_(override_)
+            .build()
+            .map_err(|e| proof::BuildProofError(e.to_string().into()))
+    }
+
+    /// Proof marking the truncation of the proof repository, see [`proof::Epoch`]
+    pub fn create_epoch_proof(
+        &self,
+        archive: Url,
+        digest: &crate::Digest,
+    ) -> Result<proof::Epoch, proof::BuildProofError> {
+        proof::EpochBuilder::default()
+            .from(self.clone())
+            .archive(archive)
+            .digest(digest.as_slice().to_vec())
+            .build()
+            .map_err(|e| proof::BuildProofError(e.to_string().into()))
+    }
+
+    /// Snapshot of the trust set computed by this Id, see [`proof::TrustSnapshot`]
+    pub fn create_trust_snapshot(
+        &self,
+        params: proof::TrustSnapshotParams,
+        trusted: Vec<proof::TrustSnapshotEntry>,
+        distrusted: Vec<Id>,
+    ) -> Result<proof::TrustSnapshot, proof::BuildProofError> {
+        proof::TrustSnapshotBuilder::default()
+            .from(self.clone())
+            .params(params)
+            .trusted(trusted)
+            .distrusted(distrusted)
+            .build()
+            .map_err(|e| proof::BuildProofError(e.to_string().into()))
+    }
+
+    /// Ask for reviews of a package version, see [`proof::ReviewRequest`]
+    pub fn create_review_request_proof(
+        &self,
+        package: proof::PackageVersionId,
+        comment: String,
+    ) -> Result<proof::ReviewRequest, proof::BuildProofError> {
+        proof::ReviewRequestBuilder::default()
+            .from(self.clone())
+            .package(package)
+            .comment(comment)
+            .build()
+            .map_err(|e| proof::BuildProofError(e.to_string().into()))
+    }
+
+    /// Publish the group `name` of `ids`, see [`proof::Group`]
+    pub fn create_group_proof(
+        &self,
+        name: String,
+        ids: Vec<PublicId>,
+        comment: String,
+    ) -> Result<proof::Group, proof::BuildProofError> {
+        proof::GroupBuilder::default()
+            .from(self.clone())
+            .name(name)
+            .ids(ids)
+            .comment(comment)
+            .build()
+            .map_err(|e| proof::BuildProofError(e.to_string().into()))
+    }
+
+    /// Designate `successor` as the replacement of `predecessor`, see [`proof::Successor`]
+    ///
+    /// Both Ids have to sign one.
+    pub fn create_successor_proof(
+        &self,
+        predecessor: PublicId,
+        successor: PublicId,
+    ) -> Result<proof::Successor, proof::BuildProofError> {
+        proof::SuccessorBuilder::default()
+            .from(self.clone())
+            .predecessor(predecessor)
+            .successor(successor)
+            .build()
+            .map_err(|e| proof::BuildProofError(e.to_string().into()))
+    }
+
+    /// Close or amend an issue reported in the package review proof
+    /// with the `reported_in` digest, see [`proof::IssueUpdate`]
+    pub fn create_issue_update_proof(
+        &self,
+        package: proof::PackageId,
+        issue: String,
+        reported_in: &proof::Digest,
+        action: proof::IssueAction,
+        comment: String,
+    ) -> Result<proof::IssueUpdate, proof::BuildProofError> {
+        proof::IssueUpdateBuilder::default()
+            .from(self.clone())
+            .package(package)
+            .issue(issue)
+            .reported_in(reported_in.0.to_vec())
+            .action(action)
+            .comment(comment)
+            .build()
+            .map_err(|e| proof::BuildProofError(e.to_string().into()))
+    }
+
+    /// Let a package version pass verification until `expires`, see [`proof::Waiver`]
+    pub fn create_waiver_proof(
+        &self,
+        package: proof::PackageVersionId,
+        expires: chrono::NaiveDate,
+        reason: String,
+    ) -> Result<proof::Waiver, proof::BuildProofError> {
+        proof::WaiverBuilder::default()
+            .from(self.clone())
+            .package(package)
+            .expires(expires)
+            .reason(reason)
+            .build()
+            .map_err(|e| proof::BuildProofError(e.to_string().into()))
+    }
+
+    /// Another proof of this Id, already encrypted, see [`proof::Encrypted`]
+    pub fn create_encrypted_proof(
+        &self,
+        ephemeral: Vec<u8>,
+        recipients: Vec<proof::EncryptedRecipient>,
+        ciphertext: Vec<u8>,
+    ) -> Result<proof::Encrypted, proof::BuildProofError> {
+        proof::EncryptedBuilder::default()
+            .from(self.clone())
+            .ephemeral(ephemeral)
+            .recipients(recipients)
+            .ciphertext(ciphertext)
+            .build()
+            .map_err(|e| proof::BuildProofError(e.to_string().into()))
+    }
+
+    pub fn create_package_review_proof(
+        &self,
+        package: proof::PackageInfo,
+        review: proof::review::Review,
+        override_: Vec<OverrideItem>,
+        comment: String,
+    ) -> Result<proof::review::Package, proof::BuildProofError> {
+        proof::review::PackageBuilder::default()
+            .from(self.clone())
+            .package(package)
+            .review(review)
+            .override_(override_)
+            .comment(comment)
+            .build()
+            .map_err(|e| proof::BuildProofError(e.to_string().into()))
+    }
+}
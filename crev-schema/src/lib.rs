@@ -0,0 +1,30 @@
+//! Data types of `crev` proofs, and their serialization formats
+//!
+//! Only the schema, for tools that read or write proofs: no signing,
+//! no verification, and no dependencies beyond serialization.
+//! `crev-data` re-exports everything here, and adds the crypto; it enables
+//! the `crypto` feature for the few methods of these types that need it.
+#![allow(clippy::default_trait_access)]
+#![allow(clippy::missing_errors_doc)]
+#![allow(clippy::module_name_repetitions)]
+
+#[macro_use]
+pub mod util;
+pub mod digest;
+pub mod id;
+pub mod level;
+pub mod proof;
+pub mod url;
+pub use semver::Version;
+
+pub use crate::{
+    digest::Digest,
+    id::{Id, PublicId},
+    level::Level,
+    proof::{
+        review,
+        review::{Rating, Review},
+        trust::TrustLevel,
+    },
+    url::Url,
+};
@@ -0,0 +1,40 @@
+use crate::util::{as_base64, as_rfc3339_fixed, from_base64, from_rfc3339_fixed};
+use chrono::{self, prelude::*};
+use derive_builder::Builder;
+use serde::{Deserialize, Serialize};
+
+pub type Date = chrono::DateTime<FixedOffset>;
+pub type DateUtc = chrono::DateTime<Utc>;
+
+/// Reference to original proof when reissuing
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OriginalReference {
+    /// original proof digest (blake2b256)
+    #[serde(serialize_with = "as_base64", deserialize_with = "from_base64")]
+    pub proof: Vec<u8>,
+    /// Any text
+    #[serde(skip_serializing_if = "String::is_empty", default = "Default::default")]
+    pub comment: String,
+}
+
+/// A `Common` part of every `Content` format
+#[derive(Clone, Builder, Debug, Serialize, Deserialize)]
+pub struct Common {
+    /// Type of this review/trust/whatever file
+    pub kind: Option<String>,
+    /// A version, to allow future backward-incompatible extensions
+    /// and changes.
+    pub version: i64,
+    #[builder(default = "crate::util::now()")]
+    #[serde(
+        serialize_with = "as_rfc3339_fixed",
+        deserialize_with = "from_rfc3339_fixed"
+    )]
+    /// Timestamp of proof creation
+    pub date: chrono::DateTime<FixedOffset>,
+    /// Author of the proof
+    pub from: crate::PublicId,
+    /// Reference to original proof when reissuing
+    #[serde(skip_serializing_if = "Option::is_none", default = "Option::default")]
+    pub original: Option<OriginalReference>,
+}
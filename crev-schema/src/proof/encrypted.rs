@@ -0,0 +1,81 @@
+use crate::{
+    proof,
+    util::{as_base64, from_base64},
+    Id,
+};
+use derive_builder::Builder;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+const CURRENT_ENCRYPTED_PROOF_SERIALIZATION_VERSION: i64 = -1;
+
+fn cur_version() -> i64 {
+    CURRENT_ENCRYPTED_PROOF_SERIALIZATION_VERSION
+}
+
+/// A recipient of an [`Encrypted`] proof
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EncryptedRecipient {
+    #[serde(flatten)]
+    pub id: Id,
+    /// Key of the `ciphertext`, encrypted to the Id
+    #[serde(serialize_with = "as_base64", deserialize_with = "from_base64")]
+    pub key: Vec<u8>,
+}
+
+/// Body of an Encrypted proof
+///
+/// Another signed proof of the author (eg. a package review), that only the
+/// `recipients` can read. Everyone else can check who published it and when,
+/// but not what it says, so organizations can share private reviews
+/// through public proof repositories.
+#[derive(Clone, Debug, Builder, Serialize, Deserialize)]
+pub struct Encrypted {
+    #[serde(flatten)]
+    pub common: proof::Common,
+    /// Public X25519 key the keys of `recipients` were encrypted with
+    #[serde(serialize_with = "as_base64", deserialize_with = "from_base64")]
+    pub ephemeral: Vec<u8>,
+    pub recipients: Vec<EncryptedRecipient>,
+    /// The signed proof
+    #[serde(serialize_with = "as_base64", deserialize_with = "from_base64")]
+    pub ciphertext: Vec<u8>,
+    /// Not encrypted
+    #[serde(skip_serializing_if = "String::is_empty", default = "Default::default")]
+    #[builder(default = "Default::default()")]
+    pub comment: String,
+}
+
+impl EncryptedBuilder {
+    pub fn from<VALUE: Into<crate::PublicId>>(&mut self, value: VALUE) -> &mut Self {
+        if let Some(ref mut common) = self.common {
+            common.from = value.into();
+        } else {
+            self.common = Some(proof::Common {
+                kind: Some(Encrypted::KIND.into()),
+                version: cur_version(),
+                date: crate::util::now(),
+                from: value.into(),
+                original: None,
+            });
+        }
+        self
+    }
+}
+
+impl fmt::Display for Encrypted {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        serde_content_serialize!(self, fmt);
+        Ok(())
+    }
+}
+
+impl Encrypted {
+    pub const KIND: &'static str = "encrypted";
+
+    /// The recipient entry of `id`, if it's one of the recipients
+    #[must_use]
+    pub fn recipient(&self, id: &Id) -> Option<&EncryptedRecipient> {
+        self.recipients.iter().find(|recipient| &recipient.id == id)
+    }
+}
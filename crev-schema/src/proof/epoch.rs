@@ -0,0 +1,76 @@
+use crate::{
+    proof,
+    util::{as_base64, from_base64},
+};
+use derive_builder::Builder;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+const CURRENT_EPOCH_PROOF_SERIALIZATION_VERSION: i64 = -1;
+
+fn cur_version() -> i64 {
+    CURRENT_EPOCH_PROOF_SERIALIZATION_VERSION
+}
+
+/// Body of an Epoch Proof
+///
+/// Marks a truncation of the author's proof repository: their proofs
+/// signed before this one were moved to the `archive` repository,
+/// which is only fetched on request. `digest` is the `archive_digest`
+/// of the archived proofs (see `crev-data`).
+#[derive(Clone, Debug, Builder, Serialize, Deserialize)]
+pub struct Epoch {
+    #[serde(flatten)]
+    pub common: proof::Common,
+    pub archive: crate::Url,
+    #[serde(serialize_with = "as_base64", deserialize_with = "from_base64")]
+    pub digest: Vec<u8>,
+    #[serde(skip_serializing_if = "String::is_empty", default = "Default::default")]
+    #[builder(default = "Default::default()")]
+    pub comment: String,
+}
+
+impl EpochBuilder {
+    pub fn from<VALUE: Into<crate::PublicId>>(&mut self, value: VALUE) -> &mut Self {
+        if let Some(ref mut common) = self.common {
+            common.from = value.into();
+        } else {
+            self.common = Some(proof::Common {
+                kind: Some(Epoch::KIND.into()),
+                version: cur_version(),
+                date: crate::util::now(),
+                from: value.into(),
+                original: None,
+            });
+        }
+        self
+    }
+}
+
+impl fmt::Display for Epoch {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        serde_content_serialize!(self, fmt);
+        Ok(())
+    }
+}
+
+impl Epoch {
+    pub const KIND: &'static str = "epoch";
+
+    /// Do `proofs` (eg. fetched from the `archive`) contain exactly the archived proofs
+    pub fn archive_matches<'a, P: ArchivedProof + 'a>(
+        &self,
+        proofs: impl IntoIterator<Item = &'a P>,
+    ) -> bool {
+        P::archive_digest(proofs).as_slice() == self.digest.as_slice()
+    }
+}
+
+/// Proofs an [`Epoch`] can be checked against
+///
+/// Implemented by the signed proofs of `crev-data`, with its `archive_digest`.
+pub trait ArchivedProof {
+    fn archive_digest<'a>(proofs: impl IntoIterator<Item = &'a Self>) -> crate::Digest
+    where
+        Self: 'a;
+}
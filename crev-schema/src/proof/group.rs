@@ -0,0 +1,75 @@
+use crate::{proof, Id};
+use derive_builder::Builder;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+const CURRENT_GROUP_PROOF_SERIALIZATION_VERSION: i64 = -1;
+
+fn cur_version() -> i64 {
+    CURRENT_GROUP_PROOF_SERIALIZATION_VERSION
+}
+
+/// Body of a Group Proof
+///
+/// A named set of Ids, published by its owner, so others can trust them
+/// (or require their reviews) all at once. Groups are identified by the
+/// owner and the `name`; the most recent proof replaces the previous ones.
+#[derive(Clone, Debug, Builder, Serialize, Deserialize)]
+pub struct Group {
+    #[serde(flatten)]
+    pub common: proof::Common,
+    pub name: String,
+    pub ids: Vec<crate::PublicId>,
+    #[serde(skip_serializing_if = "String::is_empty", default = "Default::default")]
+    #[builder(default = "Default::default()")]
+    pub comment: String,
+}
+
+impl GroupBuilder {
+    pub fn from<VALUE: Into<crate::PublicId>>(&mut self, value: VALUE) -> &mut Self {
+        if let Some(ref mut common) = self.common {
+            common.from = value.into();
+        } else {
+            self.common = Some(proof::Common {
+                kind: Some(Group::KIND.into()),
+                version: cur_version(),
+                date: crate::util::now(),
+                from: value.into(),
+                original: None,
+            });
+        }
+        self
+    }
+}
+
+impl fmt::Display for Group {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        serde_content_serialize!(self, fmt);
+        Ok(())
+    }
+}
+
+impl Group {
+    pub const KIND: &'static str = "group";
+}
+
+/// Reference to the group `name` published by `from`, eg. in trust proofs
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct GroupRef {
+    pub from: crate::PublicId,
+    pub name: String,
+}
+
+impl GroupRef {
+    #[must_use]
+    pub fn key(&self) -> (&Id, &str) {
+        (&self.from.id, &self.name)
+    }
+}
+
+/// As given on the command line: `name@id`
+impl fmt::Display for GroupRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}@{}", self.name, self.from.id)
+    }
+}
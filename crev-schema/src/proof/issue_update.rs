@@ -0,0 +1,89 @@
+use crate::{
+    proof,
+    util::{as_base64, from_base64},
+    Level,
+};
+use derive_builder::Builder;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+const CURRENT_ISSUE_UPDATE_PROOF_SERIALIZATION_VERSION: i64 = -1;
+
+fn cur_version() -> i64 {
+    CURRENT_ISSUE_UPDATE_PROOF_SERIALIZATION_VERSION
+}
+
+/// What an [`IssueUpdate`] does to the issue
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum IssueAction {
+    /// Fixed, or not an issue after all
+    Close,
+    /// Same as the issue with this id; the report counts towards that one
+    Duplicate(String),
+    /// Different severity than reported
+    Severity(Level),
+}
+
+impl fmt::Display for IssueAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IssueAction::Close => f.write_str("close"),
+            IssueAction::Duplicate(of) => write!(f, "duplicate of {of}"),
+            IssueAction::Severity(severity) => write!(f, "severity {severity}"),
+        }
+    }
+}
+
+/// Body of an Issue Update Proof
+///
+/// Closes or amends an issue reported in a package review, without
+/// waiting for an advisory in a later version. Refers to one report:
+/// the issue id in the package review proof with the `reported-in` digest.
+#[derive(Clone, Debug, Builder, Serialize, Deserialize)]
+pub struct IssueUpdate {
+    #[serde(flatten)]
+    pub common: proof::Common,
+    pub package: proof::PackageId,
+    /// Id of the issue, as reported
+    pub issue: String,
+    /// Digest of the package review proof that reported the issue
+    #[serde(
+        rename = "reported-in",
+        serialize_with = "as_base64",
+        deserialize_with = "from_base64"
+    )]
+    pub reported_in: Vec<u8>,
+    pub action: IssueAction,
+    #[serde(skip_serializing_if = "String::is_empty", default = "Default::default")]
+    #[builder(default = "Default::default()")]
+    pub comment: String,
+}
+
+impl IssueUpdateBuilder {
+    pub fn from<VALUE: Into<crate::PublicId>>(&mut self, value: VALUE) -> &mut Self {
+        if let Some(ref mut common) = self.common {
+            common.from = value.into();
+        } else {
+            self.common = Some(proof::Common {
+                kind: Some(IssueUpdate::KIND.into()),
+                version: cur_version(),
+                date: crate::util::now(),
+                from: value.into(),
+                original: None,
+            });
+        }
+        self
+    }
+}
+
+impl fmt::Display for IssueUpdate {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        serde_content_serialize!(self, fmt);
+        Ok(())
+    }
+}
+
+impl IssueUpdate {
+    pub const KIND: &'static str = "issue update";
+}
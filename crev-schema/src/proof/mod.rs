@@ -0,0 +1,81 @@
+//! Bodies of proofs: reviews, trust and the rest
+
+pub use crate::proof::content::{Common, CommonBuilder, Date, DateUtc, OriginalReference};
+use crate::PublicId;
+pub use encrypted::*;
+pub use epoch::*;
+pub use group::*;
+pub use issue_update::*;
+pub use package_info::*;
+pub use review::{Code as CodeReview, Package as PackageReview, *};
+pub use review_request::*;
+pub use revision::*;
+use serde::{Deserialize, Serialize};
+pub use successor::*;
+pub use trust::*;
+pub use trust_snapshot::*;
+pub use waiver::*;
+
+pub mod content;
+pub mod encrypted;
+pub mod epoch;
+pub mod group;
+pub mod issue_update;
+pub mod package_info;
+pub mod review;
+pub mod review_request;
+pub mod revision;
+pub mod successor;
+pub mod trust;
+pub mod trust_snapshot;
+pub mod waiver;
+
+/// Digest of a signed proof (blake2b256 of the whole proof)
+#[derive(Debug, Clone)]
+pub struct Digest(pub [u8; 32]);
+
+impl Digest {
+    #[must_use]
+    pub fn to_base64(&self) -> String {
+        crate::util::base64_encode(&self.0)
+    }
+}
+
+/// Proof built without all the required fields, see `PublicId::create_*`
+#[derive(thiserror::Error, Debug)]
+#[error("Error building proof: {}", _0)]
+pub struct BuildProofError(pub Box<str>);
+
+fn equals_default_digest_type(s: &str) -> bool {
+    s == default_digest_type()
+}
+
+#[must_use]
+pub fn default_digest_type() -> String {
+    crate::digest::DigestType::default().to_string()
+}
+
+fn equals_default_revision_type(s: &str) -> bool {
+    s == default_revision_type()
+}
+
+#[must_use]
+pub fn default_revision_type() -> String {
+    "git".into()
+}
+
+fn equals_default<T: Default + PartialEq>(t: &T) -> bool {
+    *t == Default::default()
+}
+
+/// A particular ID to override judgment of
+///
+/// Used to correct (well, discard really) specific judgments without
+/// loosing the overall work.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OverrideItem {
+    #[serde(flatten)]
+    pub id: PublicId,
+    #[serde(skip_serializing_if = "String::is_empty", default = "Default::default")]
+    pub comment: String,
+}
@@ -1,6 +1,8 @@
-use crate::proof;
-
-use crev_common::serde::{as_base64, from_base64};
+use crate::{
+    digest::DigestType,
+    proof,
+    util::{as_base64, from_base64},
+};
 use derive_builder::Builder;
 pub use semver::Version;
 use serde::{Deserialize, Serialize};
@@ -46,7 +48,26 @@ pub struct PackageInfo {
     pub digest: Vec<u8>,
     #[serde(
         skip_serializing_if = "proof::equals_default_digest_type",
-        default = "proof::default_digest_type"
+        default = "proof::default_digest_type",
+        alias = "digest-type"
     )]
     pub digest_type: String,
+
+    /// Rules for files left out of the `digest` (`crev_lib::DigestIgnorePolicy`);
+    /// `None` in older proofs, which were all made with `cargo-crate-v1`
+    #[serde(
+        rename = "digest-ignore",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    #[builder(default)]
+    pub digest_ignore: Option<String>,
+}
+
+impl PackageInfo {
+    /// Hash function of `digest`; `None` if it's not one this version knows
+    #[must_use]
+    pub fn known_digest_type(&self) -> Option<DigestType> {
+        self.digest_type.parse().ok()
+    }
 }
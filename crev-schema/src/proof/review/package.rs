@@ -0,0 +1,531 @@
+use crate::{
+    proof::{self, OriginalReference, OverrideItem},
+    util::{as_base64, from_base64, is_equal_default, is_set_empty, is_vec_empty},
+    Level,
+};
+use derive_builder::Builder;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    default::Default,
+    fmt::{self, Debug},
+    ops,
+};
+use typed_builder::TypedBuilder;
+
+const CURRENT_PACKAGE_REVIEW_PROOF_SERIALIZATION_VERSION: i64 = -1;
+
+fn cur_version() -> i64 {
+    CURRENT_PACKAGE_REVIEW_PROOF_SERIALIZATION_VERSION
+}
+
+/// Possible flags to mark on the package
+#[derive(Clone, Builder, Debug, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct Flags {
+    #[serde(default = "Default::default", skip_serializing_if = "is_equal_default")]
+    pub unmaintained: bool,
+}
+
+impl ops::Add<Flags> for Flags {
+    type Output = Self;
+    fn add(self, other: Flags) -> Self {
+        Self {
+            unmaintained: self.unmaintained || other.unmaintained,
+        }
+    }
+}
+
+/// Body of a Package Review Proof
+#[derive(Clone, Builder, Debug, Serialize, Deserialize)]
+// TODO: https://github.com/colin-kiegel/rust-derive-builder/issues/136
+pub struct Package {
+    #[serde(flatten)]
+    pub common: proof::Common,
+
+    #[serde(rename = "package")]
+    pub package: proof::PackageInfo,
+
+    #[serde(skip_serializing_if = "Option::is_none", default = "Default::default")]
+    #[serde(rename = "package-diff-base")]
+    #[builder(default = "Default::default()")]
+    pub diff_base: Option<proof::PackageInfo>,
+
+    /// The review is of this patch, rather than of the whole package
+    #[serde(skip_serializing_if = "Option::is_none", default = "Default::default")]
+    #[builder(default = "Default::default()")]
+    pub patch: Option<Patch>,
+
+    /// The review is of this directory, rather than of the whole package
+    #[serde(skip_serializing_if = "Option::is_none", default = "Default::default")]
+    #[builder(default = "Default::default()")]
+    pub subtree: Option<Subtree>,
+
+    /// Parts of the package the reviewer went through (eg. `src/parser`),
+    /// relative to its root; empty if the review doesn't say
+    #[builder(default = "Default::default()")]
+    #[serde(
+        skip_serializing_if = "is_vec_empty",
+        default = "Default::default",
+        rename = "covered-paths"
+    )]
+    pub covered_paths: Vec<String>,
+
+    #[builder(default = "Default::default()")]
+    #[serde(default = "Default::default", skip_serializing_if = "is_equal_default")]
+    review: super::Review,
+
+    #[builder(default = "Default::default()")]
+    #[serde(skip_serializing_if = "is_vec_empty", default = "Default::default")]
+    pub issues: Vec<Issue>,
+
+    #[builder(default = "Default::default()")]
+    #[serde(skip_serializing_if = "is_vec_empty", default = "Default::default")]
+    pub advisories: Vec<Advisory>,
+
+    #[serde(default = "Default::default", skip_serializing_if = "is_equal_default")]
+    #[builder(default = "Default::default()")]
+    pub flags: Flags,
+
+    #[builder(default = "Default::default()")]
+    #[serde(skip_serializing_if = "is_set_empty", default = "Default::default")]
+    pub alternatives: HashSet<proof::PackageId>,
+
+    #[serde(skip_serializing_if = "String::is_empty", default = "Default::default")]
+    #[builder(default = "Default::default()")]
+    pub comment: String,
+
+    #[builder(default = "Default::default()")]
+    #[serde(
+        default = "Default::default",
+        skip_serializing_if = "Vec::is_empty",
+        rename = "override"
+    )]
+    pub override_: Vec<OverrideItem>,
+}
+
+/// A patch reviewed outside of crev (eg. an upstream PR)
+///
+/// Recorded in package reviews made with `review --from-patch`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct Patch {
+    /// Blake2b256 of the patch file
+    #[serde(serialize_with = "as_base64", deserialize_with = "from_base64")]
+    pub digest: Vec<u8>,
+    /// Files of the package the patch changes, relative to its root
+    #[serde(skip_serializing_if = "is_vec_empty", default = "Default::default")]
+    pub files: Vec<String>,
+    /// Lines added and removed by the patch
+    #[serde(default = "Default::default")]
+    pub lines_changed: u64,
+    /// Lines in the package's Rust source files
+    #[serde(default = "Default::default")]
+    pub lines_total: u64,
+}
+
+/// A directory of a package reviewed on its own (eg. `src` of a big package)
+///
+/// Recorded in package reviews made with `review --subtree`. The package
+/// digest of such a review is `digest` too, so it never counts for the whole
+/// package; `digest` can be checked against any copy of the package.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct Subtree {
+    /// Relative to the package root, `/`-separated
+    pub path: String,
+    /// Paths relative to `path` left out of the review and of `digest`
+    #[serde(skip_serializing_if = "is_vec_empty", default = "Default::default")]
+    pub exclude: Vec<String>,
+    /// Recursive digest of the directory, like the package digest
+    #[serde(serialize_with = "as_base64", deserialize_with = "from_base64")]
+    pub digest: Vec<u8>,
+}
+
+impl Subtree {
+    /// Relative, `/`-separated, and without `.` or `..` components
+    #[must_use]
+    pub fn is_valid_path(path: &str) -> bool {
+        !path.is_empty()
+            && path
+                .split('/')
+                .all(|c| !c.is_empty() && c != "." && c != ".." && !c.contains('\\'))
+    }
+}
+
+/// What part of a package a review is of
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReviewCoverage {
+    /// The whole package
+    Full,
+    /// The changes since `Package::diff_base`
+    Diff,
+    /// A patch of the package, see `Package::patch`
+    Patch,
+    /// One directory, see `Package::subtree`
+    Subtree,
+}
+
+impl fmt::Display for ReviewCoverage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Full => "full",
+            Self::Diff => "diff",
+            Self::Patch => "patch",
+            Self::Subtree => "subtree",
+        })
+    }
+}
+
+/// How a package review identifies the reviewed code, and how much of it was
+/// reviewed, for weighing reviews against each other
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct ReviewScope {
+    pub digest_type: String,
+    pub revision_type: String,
+    /// Empty if it's not known
+    #[serde(skip_serializing_if = "String::is_empty", default)]
+    pub revision: String,
+    pub coverage: ReviewCoverage,
+    /// Version the changes of a `Diff` review are from
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub diff_base: Option<Version>,
+    /// See `Package::covered_paths`
+    #[serde(skip_serializing_if = "is_vec_empty", default)]
+    pub covered_paths: Vec<String>,
+}
+
+impl fmt::Display for ReviewScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} review", self.coverage)?;
+        if let Some(base) = &self.diff_base {
+            write!(f, " from {base}")?;
+        }
+        write!(f, ", {} digest", self.digest_type)?;
+        if !self.revision.is_empty() {
+            write!(f, ", {} revision {}", self.revision_type, self.revision)?;
+        }
+        if !self.covered_paths.is_empty() {
+            write!(f, ", covering {}", self.covered_paths.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl PackageBuilder {
+    pub fn from<VALUE: Into<crate::PublicId>>(&mut self, value: VALUE) -> &mut Self {
+        if let Some(ref mut common) = self.common {
+            common.from = value.into();
+        } else {
+            self.common = Some(proof::Common {
+                kind: Some(Package::KIND.into()),
+                version: cur_version(),
+                date: crate::util::now(),
+                from: value.into(),
+                original: None,
+            });
+        }
+        self
+    }
+}
+
+impl fmt::Display for Package {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        serde_content_serialize!(self, fmt);
+        Ok(())
+    }
+}
+
+impl Package {
+    pub fn touch_date(&mut self) {
+        self.common.date = crate::util::now();
+    }
+
+    pub fn change_from(&mut self, id: crate::PublicId) {
+        self.common.from = id;
+    }
+
+    pub fn set_original_reference(&mut self, orig_reference: OriginalReference) {
+        self.common.original = Some(orig_reference);
+    }
+
+    pub fn ensure_kind_is_backfilled(&mut self) {
+        if self.common.kind.is_none() {
+            // backfill "kind" for old reviews
+            self.common.kind = Some(Self::KIND.to_string());
+        }
+    }
+}
+
+impl Package {
+    pub const KIND: &'static str = "package review";
+
+    #[must_use]
+    pub fn scope(&self) -> ReviewScope {
+        let coverage = if self.patch.is_some() {
+            ReviewCoverage::Patch
+        } else if self.subtree.is_some() {
+            ReviewCoverage::Subtree
+        } else if self.diff_base.is_some() {
+            ReviewCoverage::Diff
+        } else {
+            ReviewCoverage::Full
+        };
+        ReviewScope {
+            digest_type: self.package.digest_type.clone(),
+            revision_type: self.package.revision_type.clone(),
+            revision: self.package.revision.clone(),
+            coverage,
+            diff_base: self.diff_base.as_ref().map(|base| base.id.version.clone()),
+            covered_paths: self.covered_paths.clone(),
+        }
+    }
+
+    /// Whether the review went through `path` (relative to the package root)
+    ///
+    /// Reviews declaring `covered_paths` cover those paths and everything
+    /// under them. Otherwise a full review covers everything, a subtree
+    /// review its directory (but not what it leaves out), and reviews of
+    /// changes nothing in particular.
+    #[must_use]
+    pub fn covers_path(&self, path: &str) -> bool {
+        let is_within = |dir: &str| {
+            path == dir
+                || path
+                    .strip_prefix(dir)
+                    .is_some_and(|rest| rest.starts_with('/'))
+        };
+        if !self.covered_paths.is_empty() {
+            return self.covered_paths.iter().any(|dir| is_within(dir));
+        }
+        match self.scope().coverage {
+            ReviewCoverage::Full => true,
+            ReviewCoverage::Subtree => self.subtree.as_ref().is_some_and(|subtree| {
+                is_within(&subtree.path)
+                    && !subtree
+                        .exclude
+                        .iter()
+                        .any(|exclude| is_within(&format!("{}/{exclude}", subtree.path)))
+            }),
+            ReviewCoverage::Diff | ReviewCoverage::Patch => false,
+        }
+    }
+
+    #[must_use]
+    pub fn is_advisory_for(&self, version: &Version) -> bool {
+        for advisory in &self.advisories {
+            if advisory.is_for_version_when_reported_in_version(version, &self.package.id.version) {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Get the `Review`
+    ///
+    /// This forces the user to handle reviews that are
+    /// empty (everything is set to None) explicitly.
+    #[must_use]
+    pub fn review(&self) -> Option<&super::Review> {
+        if self.review.is_none() {
+            None
+        } else {
+            Some(&self.review)
+        }
+    }
+
+    /// Get the underlying review.
+    ///
+    /// The caller is responsible for handling the case where
+    /// `review.is_none()`.
+    #[must_use]
+    pub fn review_possibly_none(&self) -> &super::Review {
+        &self.review
+    }
+
+    pub fn review_possibly_none_mut(&mut self) -> &mut super::Review {
+        &mut self.review
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum VersionRange {
+    Minor,
+    Major,
+    #[default]
+    All,
+}
+
+#[derive(Debug, Clone)]
+pub struct VersionRangeParseError(());
+
+impl fmt::Display for VersionRangeParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Could not parse an incorrect advisory range value")
+    }
+}
+
+impl std::error::Error for VersionRangeParseError {}
+
+impl std::str::FromStr for VersionRange {
+    type Err = VersionRangeParseError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Ok(match s {
+            "all" => VersionRange::All,
+            "major" => VersionRange::Major,
+            "minor" => VersionRange::Minor,
+            _ => return Err(VersionRangeParseError(())),
+        })
+    }
+}
+
+impl VersionRange {
+    fn all() -> Self {
+        VersionRange::All
+    }
+
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    fn is_all_ref(&self) -> bool {
+        VersionRange::All == *self
+    }
+}
+
+/// Advisory to upgrade to the package version
+///
+/// Advisory means a general important fix was included in this
+/// release, and all previous releases were potentially affected.
+/// We don't play with exact ranges.
+#[derive(Clone, TypedBuilder, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[derive(Default)]
+pub struct Advisory {
+    pub ids: Vec<String>,
+
+    #[builder(default)]
+    pub severity: Level,
+
+    #[builder(default)]
+    #[serde(
+        default = "VersionRange::all",
+        skip_serializing_if = "VersionRange::is_all_ref"
+    )]
+    pub range: VersionRange,
+
+    #[builder(default)]
+    #[serde(default = "Default::default")]
+    pub comment: String,
+}
+
+impl From<VersionRange> for Advisory {
+    fn from(r: VersionRange) -> Self {
+        Advisory {
+            range: r,
+            ..Default::default()
+        }
+    }
+}
+
+impl Advisory {
+    #[must_use]
+    pub fn is_for_version_when_reported_in_version(
+        &self,
+        for_version: &Version,
+        in_pkg_version: &Version,
+    ) -> bool {
+        if for_version < in_pkg_version {
+            match self.range {
+                VersionRange::All => return true,
+                VersionRange::Major => {
+                    if in_pkg_version.major == for_version.major {
+                        return true;
+                    }
+                }
+                VersionRange::Minor => {
+                    if in_pkg_version.major == for_version.major
+                        && in_pkg_version.minor == for_version.minor
+                    {
+                        return true;
+                    }
+                }
+            }
+        }
+        false
+    }
+}
+
+/// Issue with a package version
+///
+/// `Issue` is a kind of opposite of [`Advisory`]. It reports
+/// a problem with package in a given version. It leaves the
+/// question open if any previous and following versions might
+/// also be affected, but will be considered open and affecting
+/// all following versions within the `range` until an advisory
+/// is found for it, matching the id.
+#[derive(Clone, TypedBuilder, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Issue {
+    pub id: String,
+    #[builder(default)]
+    pub severity: Level,
+
+    #[builder(default)]
+    #[serde(
+        default = "VersionRange::all",
+        skip_serializing_if = "VersionRange::is_all_ref"
+    )]
+    pub range: VersionRange,
+
+    #[builder(default)]
+    #[serde(default = "Default::default")]
+    pub comment: String,
+}
+
+impl Issue {
+    #[must_use]
+    pub fn new(id: String) -> Self {
+        Self {
+            id,
+            range: Default::default(),
+            severity: Default::default(),
+            comment: Default::default(),
+        }
+    }
+    #[must_use]
+    pub fn new_with_severity(id: String, severity: Level) -> Self {
+        Self {
+            id,
+            range: Default::default(),
+            severity,
+            comment: Default::default(),
+        }
+    }
+    #[must_use]
+    pub fn is_for_version_when_reported_in_version(
+        &self,
+        for_version: &Version,
+        in_pkg_version: &Version,
+    ) -> bool {
+        if for_version >= in_pkg_version {
+            match self.range {
+                VersionRange::All => return true,
+                VersionRange::Major => {
+                    if in_pkg_version.major == for_version.major {
+                        return true;
+                    }
+                }
+                VersionRange::Minor => {
+                    if in_pkg_version.major == for_version.major
+                        && in_pkg_version.minor == for_version.minor
+                    {
+                        return true;
+                    }
+                }
+            }
+        }
+        false
+    }
+}
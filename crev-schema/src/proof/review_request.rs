@@ -0,0 +1,52 @@
+use crate::proof;
+use derive_builder::Builder;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+const CURRENT_REVIEW_REQUEST_PROOF_SERIALIZATION_VERSION: i64 = -1;
+
+fn cur_version() -> i64 {
+    CURRENT_REVIEW_REQUEST_PROOF_SERIALIZATION_VERSION
+}
+
+/// Body of a Review Request Proof
+///
+/// Asks for reviews of a package version, eg. by its maintainer
+/// before or after a release. Doesn't say anything about the package itself.
+#[derive(Clone, Debug, Builder, Serialize, Deserialize)]
+pub struct ReviewRequest {
+    #[serde(flatten)]
+    pub common: proof::Common,
+    pub package: proof::PackageVersionId,
+    #[serde(skip_serializing_if = "String::is_empty", default = "Default::default")]
+    #[builder(default = "Default::default()")]
+    pub comment: String,
+}
+
+impl ReviewRequestBuilder {
+    pub fn from<VALUE: Into<crate::PublicId>>(&mut self, value: VALUE) -> &mut Self {
+        if let Some(ref mut common) = self.common {
+            common.from = value.into();
+        } else {
+            self.common = Some(proof::Common {
+                kind: Some(ReviewRequest::KIND.into()),
+                version: cur_version(),
+                date: crate::util::now(),
+                from: value.into(),
+                original: None,
+            });
+        }
+        self
+    }
+}
+
+impl fmt::Display for ReviewRequest {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        serde_content_serialize!(self, fmt);
+        Ok(())
+    }
+}
+
+impl ReviewRequest {
+    pub const KIND: &'static str = "review request";
+}
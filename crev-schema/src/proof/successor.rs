@@ -0,0 +1,55 @@
+use crate::{proof, PublicId};
+use derive_builder::Builder;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+const CURRENT_SUCCESSOR_PROOF_SERIALIZATION_VERSION: i64 = -1;
+
+fn cur_version() -> i64 {
+    CURRENT_SUCCESSOR_PROOF_SERIALIZATION_VERSION
+}
+
+/// Body of a Successor Proof
+///
+/// The `predecessor` Id designates `successor` as its replacement, eg. when
+/// rotating keys. It takes two proofs with the same Ids, one signed by each,
+/// so neither Id can claim the other without its consent. Trust in the
+/// predecessor then counts as trust in the successor.
+#[derive(Clone, Debug, Builder, Serialize, Deserialize)]
+pub struct Successor {
+    #[serde(flatten)]
+    pub common: proof::Common,
+    pub predecessor: PublicId,
+    pub successor: PublicId,
+    #[serde(skip_serializing_if = "String::is_empty", default = "Default::default")]
+    #[builder(default = "Default::default()")]
+    pub comment: String,
+}
+
+impl SuccessorBuilder {
+    pub fn from<VALUE: Into<crate::PublicId>>(&mut self, value: VALUE) -> &mut Self {
+        if let Some(ref mut common) = self.common {
+            common.from = value.into();
+        } else {
+            self.common = Some(proof::Common {
+                kind: Some(Successor::KIND.into()),
+                version: cur_version(),
+                date: crate::util::now(),
+                from: value.into(),
+                original: None,
+            });
+        }
+        self
+    }
+}
+
+impl fmt::Display for Successor {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        serde_content_serialize!(self, fmt);
+        Ok(())
+    }
+}
+
+impl Successor {
+    pub const KIND: &'static str = "successor";
+}
@@ -0,0 +1,124 @@
+use crate::{proof, Level};
+
+use derive_builder::Builder;
+use serde::{Deserialize, Serialize};
+
+use std::fmt;
+
+use super::OverrideItem;
+
+const CURRENT_TRUST_PROOF_SERIALIZATION_VERSION: i64 = -1;
+
+fn cur_version() -> i64 {
+    CURRENT_TRUST_PROOF_SERIALIZATION_VERSION
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialOrd, Ord, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TrustLevel {
+    Distrust,
+    None,
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl fmt::Display for TrustLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use TrustLevel::*;
+        f.pad(match self {
+            Distrust => "distrust",
+            None => "none",
+            Low => "low",
+            Medium => "medium",
+            High => "high",
+        })
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+#[error("Can't convert string to TrustLevel. Possible values are: \"none\" or \"untrust\", \"low\", \"medium\", \"high\" and \"distrust\".")]
+pub struct FromStrErr;
+
+impl std::str::FromStr for TrustLevel {
+    type Err = FromStrErr;
+
+    fn from_str(s: &str) -> std::result::Result<TrustLevel, FromStrErr> {
+        Ok(match s {
+            "none" | "untrust" => TrustLevel::None,
+            "low" => TrustLevel::Low,
+            "medium" => TrustLevel::Medium,
+            "high" => TrustLevel::High,
+            "distrust" => TrustLevel::Distrust,
+            _ => return Err(FromStrErr),
+        })
+    }
+}
+
+impl std::convert::From<Level> for TrustLevel {
+    fn from(l: Level) -> Self {
+        match l {
+            Level::None => TrustLevel::None,
+            Level::Low => TrustLevel::Low,
+            Level::Medium => TrustLevel::Medium,
+            Level::High => TrustLevel::High,
+        }
+    }
+}
+
+/// Body of a Trust Proof
+#[derive(Clone, Debug, Builder, Serialize, Deserialize)]
+pub struct Trust {
+    #[serde(flatten)]
+    pub common: proof::Common,
+    pub ids: Vec<crate::PublicId>,
+    /// Groups whose current members are trusted like `ids`, see [`proof::Group`]
+    #[serde(default = "Default::default", skip_serializing_if = "Vec::is_empty")]
+    #[builder(default = "Default::default()")]
+    pub groups: Vec<proof::GroupRef>,
+    #[builder(default = "Default::default()")]
+    pub trust: TrustLevel,
+    #[serde(skip_serializing_if = "String::is_empty", default = "Default::default")]
+    #[builder(default = "Default::default()")]
+    pub comment: String,
+    #[serde(
+        default = "Default::default",
+        skip_serializing_if = "Vec::is_empty",
+        rename = "override"
+    )]
+    #[builder(default = "Default::default()")]
+    pub override_: Vec<OverrideItem>,
+}
+
+impl TrustBuilder {
+    pub fn from<VALUE: Into<crate::PublicId>>(&mut self, value: VALUE) -> &mut Self {
+        if let Some(ref mut common) = self.common {
+            common.from = value.into();
+        } else {
+            self.common = Some(proof::Common {
+                kind: Some(Trust::KIND.into()),
+                version: cur_version(),
+                date: crate::util::now(),
+                from: value.into(),
+                original: None,
+            });
+        }
+        self
+    }
+}
+
+impl fmt::Display for Trust {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        serde_content_serialize!(self, fmt);
+        Ok(())
+    }
+}
+
+impl Trust {
+    pub const KIND: &'static str = "trust";
+
+    pub fn touch_date(&mut self) {
+        self.common.date = crate::util::now();
+    }
+}
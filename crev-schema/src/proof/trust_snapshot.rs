@@ -0,0 +1,121 @@
+use crate::{
+    proof::{self, trust::TrustLevel},
+    Id,
+};
+use derive_builder::Builder;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+const CURRENT_TRUST_SNAPSHOT_SERIALIZATION_VERSION: i64 = -1;
+
+fn cur_version() -> i64 {
+    CURRENT_TRUST_SNAPSHOT_SERIALIZATION_VERSION
+}
+
+/// Parameters the trust set of a [`TrustSnapshot`] was computed with
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct TrustSnapshotParams {
+    pub max_distance: u64,
+    pub high_trust_distance: u64,
+    pub medium_trust_distance: u64,
+    pub low_trust_distance: u64,
+    pub none_trust_distance: u64,
+    pub distrust_distance: u64,
+    pub distrust_min_reporter_trust: TrustLevel,
+    pub distrust_quorum: u64,
+}
+
+/// A trusted Id in a [`TrustSnapshot`]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TrustSnapshotEntry {
+    #[serde(flatten)]
+    pub id: Id,
+    /// Effective trust level
+    pub level: TrustLevel,
+    /// From the author, in the units of the params
+    pub distance: u64,
+}
+
+/// A trust proof ignored in a [`TrustSnapshot`], because of trust overrides
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TrustSnapshotIgnoredTrust {
+    pub from: Id,
+    pub to: Id,
+    /// Highest effective trust level of the Ids overriding it
+    pub level: TrustLevel,
+}
+
+/// A package review ignored in a [`TrustSnapshot`], because of review overrides
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TrustSnapshotIgnoredReview {
+    pub from: Id,
+    pub package: proof::PackageVersionId,
+    /// Highest effective trust level of the Ids overriding it
+    pub level: TrustLevel,
+}
+
+/// Body of a Trust Snapshot
+///
+/// The trust set the author computed at `date`: everyone they trust
+/// (directly or through others) and distrust, and the trust proofs and
+/// reviews their overrides leave out. It's a standalone signed
+/// document, not meant for proof repositories; others can compare it with
+/// their own trust set, or use it to reproduce the author's verification.
+#[derive(Clone, Debug, Builder, Serialize, Deserialize)]
+pub struct TrustSnapshot {
+    #[serde(flatten)]
+    pub common: proof::Common,
+    pub params: TrustSnapshotParams,
+    #[serde(skip_serializing_if = "Vec::is_empty", default = "Default::default")]
+    #[builder(default = "Default::default()")]
+    pub trusted: Vec<TrustSnapshotEntry>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default = "Default::default")]
+    #[builder(default = "Default::default()")]
+    pub distrusted: Vec<Id>,
+    #[serde(
+        rename = "ignored-trust",
+        skip_serializing_if = "Vec::is_empty",
+        default = "Default::default"
+    )]
+    #[builder(default = "Default::default()")]
+    pub ignored_trust: Vec<TrustSnapshotIgnoredTrust>,
+    #[serde(
+        rename = "ignored-reviews",
+        skip_serializing_if = "Vec::is_empty",
+        default = "Default::default"
+    )]
+    #[builder(default = "Default::default()")]
+    pub ignored_reviews: Vec<TrustSnapshotIgnoredReview>,
+    #[serde(skip_serializing_if = "String::is_empty", default = "Default::default")]
+    #[builder(default = "Default::default()")]
+    pub comment: String,
+}
+
+impl TrustSnapshotBuilder {
+    pub fn from<VALUE: Into<crate::PublicId>>(&mut self, value: VALUE) -> &mut Self {
+        if let Some(ref mut common) = self.common {
+            common.from = value.into();
+        } else {
+            self.common = Some(proof::Common {
+                kind: Some(TrustSnapshot::KIND.into()),
+                version: cur_version(),
+                date: crate::util::now(),
+                from: value.into(),
+                original: None,
+            });
+        }
+        self
+    }
+}
+
+impl fmt::Display for TrustSnapshot {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        serde_content_serialize!(self, fmt);
+        Ok(())
+    }
+}
+
+impl TrustSnapshot {
+    pub const KIND: &'static str = "trust snapshot";
+}
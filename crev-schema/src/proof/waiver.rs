@@ -0,0 +1,66 @@
+use crate::{
+    proof,
+    util::{as_date, from_date},
+};
+use chrono::NaiveDate;
+use derive_builder::Builder;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+const CURRENT_WAIVER_PROOF_SERIALIZATION_VERSION: i64 = -1;
+
+fn cur_version() -> i64 {
+    CURRENT_WAIVER_PROOF_SERIALIZATION_VERSION
+}
+
+/// Body of a Waiver Proof
+///
+/// Lets a package version pass verification without enough reviews until
+/// a date, eg. to ship while reviews are pending. Doesn't say anything about
+/// the package itself.
+#[derive(Clone, Debug, Builder, Serialize, Deserialize)]
+pub struct Waiver {
+    #[serde(flatten)]
+    pub common: proof::Common,
+    pub package: proof::PackageVersionId,
+    /// Last day the waiver applies
+    #[serde(serialize_with = "as_date", deserialize_with = "from_date")]
+    pub expires: NaiveDate,
+    pub reason: String,
+    #[serde(skip_serializing_if = "String::is_empty", default = "Default::default")]
+    #[builder(default = "Default::default()")]
+    pub comment: String,
+}
+
+impl WaiverBuilder {
+    pub fn from<VALUE: Into<crate::PublicId>>(&mut self, value: VALUE) -> &mut Self {
+        if let Some(ref mut common) = self.common {
+            common.from = value.into();
+        } else {
+            self.common = Some(proof::Common {
+                kind: Some(Waiver::KIND.into()),
+                version: cur_version(),
+                date: crate::util::now(),
+                from: value.into(),
+                original: None,
+            });
+        }
+        self
+    }
+}
+
+impl fmt::Display for Waiver {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        serde_content_serialize!(self, fmt);
+        Ok(())
+    }
+}
+
+impl Waiver {
+    pub const KIND: &'static str = "waiver";
+
+    #[must_use]
+    pub fn is_expired(&self, today: NaiveDate) -> bool {
+        self.expires < today
+    }
+}
@@ -0,0 +1,123 @@
+//! Serialization helpers shared by the proof types
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use chrono::{offset::FixedOffset, DateTime, NaiveDate};
+use serde::{de::Error as _, Deserialize, Deserializer, Serializer};
+use std::{collections::HashSet, fmt};
+
+/// URL-safe base64 without padding, as used in Ids and digests
+pub fn base64_encode<T: ?Sized + AsRef<[u8]>>(input: &T) -> String {
+    URL_SAFE_NO_PAD.encode(input)
+}
+
+pub fn base64_decode<T: ?Sized + AsRef<[u8]>>(input: &T) -> Result<Vec<u8>, base64::DecodeError> {
+    URL_SAFE_NO_PAD.decode(input)
+}
+
+/// Current local time, as recorded in new proofs
+#[must_use]
+pub fn now() -> DateTime<FixedOffset> {
+    let date = chrono::offset::Local::now();
+    date.with_timezone(date.offset())
+}
+
+pub fn from_base64<'d, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+where
+    D: Deserializer<'d>,
+{
+    String::deserialize(deserializer)
+        .and_then(|string| base64_decode(&string).map_err(|err| D::Error::custom(err.to_string())))
+}
+
+pub fn as_base64<T, S>(key: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: AsRef<[u8]>,
+    S: Serializer,
+{
+    serializer.serialize_str(&base64_encode(key.as_ref()))
+}
+
+pub fn from_rfc3339_fixed<'d, D>(deserializer: D) -> Result<DateTime<FixedOffset>, D::Error>
+where
+    D: Deserializer<'d>,
+{
+    String::deserialize(deserializer)
+        .and_then(|string| {
+            DateTime::<FixedOffset>::parse_from_rfc3339(&string)
+                .map_err(|err| D::Error::custom(err.to_string()))
+        })
+        .map(|dt| dt.with_timezone(&dt.timezone()))
+}
+
+pub fn as_rfc3339_fixed<S>(key: &DateTime<FixedOffset>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(&key.to_rfc3339())
+}
+
+pub fn from_date<'d, D>(deserializer: D) -> Result<NaiveDate, D::Error>
+where
+    D: Deserializer<'d>,
+{
+    String::deserialize(deserializer).and_then(|string| {
+        NaiveDate::parse_from_str(&string, "%Y-%m-%d")
+            .map_err(|err| D::Error::custom(err.to_string()))
+    })
+}
+
+/// As `YYYY-MM-DD`
+pub fn as_date<S>(date: &NaiveDate, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(&date.format("%Y-%m-%d").to_string())
+}
+
+pub fn is_equal_default<T: Default + PartialEq>(t: &T) -> bool {
+    *t == T::default()
+}
+
+pub fn is_vec_empty<T>(t: &[T]) -> bool {
+    t.is_empty()
+}
+
+#[must_use]
+pub fn is_set_empty<T>(t: &HashSet<T>) -> bool {
+    t.is_empty()
+}
+
+/// Write out a value as YAML without a `---` prefix
+pub fn write_as_headerless_yaml<T: serde::Serialize>(t: &T, f: &mut dyn fmt::Write) -> fmt::Result {
+    let s = serde_yaml::to_string(t).map_err(|_| fmt::Error)?;
+    f.write_str(&s)
+}
+
+pub fn write_comment_proof(comment: &str, f: &mut dyn fmt::Write) -> fmt::Result {
+    if comment.is_empty() {
+        return Ok(());
+    }
+    writeln!(f, "comment: |-")?;
+    for line in comment.lines() {
+        writeln!(f, "  {line}")?;
+    }
+    Ok(())
+}
+
+/// Proof body of a content type with `common` and `comment` fields
+///
+/// The comment goes last, as a YAML block, for readability.
+macro_rules! serde_content_serialize {
+    ($self: ident, $fmt: ident) => {
+        // Remove comment for manual formatting
+        let mut clone = $self.clone();
+        let mut comment = String::new();
+        std::mem::swap(&mut comment, &mut clone.comment);
+
+        if clone.common.kind.is_none() {
+            clone.common.kind = Some(Self::KIND.into());
+        }
+
+        $crate::util::write_as_headerless_yaml(&clone, $fmt)?;
+        $crate::util::write_comment_proof(comment.as_str(), $fmt)?;
+    };
+}
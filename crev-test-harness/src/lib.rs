@@ -0,0 +1,464 @@
+//! End-to-end test harness for `cargo-crev`
+//!
+//! Drives the real `cargo-crev` binary against a fake crates.io (a cargo
+//! local registry in a temporary directory), with proof repositories
+//! published to local bare git repositories, so whole review, trust, fetch
+//! and verify scenarios can run without network access:
+//!
+//! ```ignore
+//! use crev_test_harness::Harness;
+//!
+//! let harness = Harness::new(env!("CARGO_BIN_EXE_cargo-crev"));
+//! harness.add_crate("leftpad", "1.0.0", "pub fn pad() {}\n");
+//! let project = harness.project("app", &[("leftpad", "1.0.0")]);
+//!
+//! let alice = harness.user("alice");
+//! alice.review(&project, "leftpad").assert_success();
+//! let alice_proofs = harness.proof_remote("alice");
+//! alice.publish_proofs(&alice_proofs);
+//!
+//! let bob = harness.user("bob");
+//! bob.fetch(&alice_proofs).assert_success();
+//! bob.trust(&alice).assert_success();
+//! bob.verify(&project).assert_success();
+//! ```
+//!
+//! Everything lives in one temporary directory, removed when the `Harness`
+//! is dropped. Helpers panic with the command output on failure, as tests would.
+use flate2::{write::GzEncoder, Compression};
+use sha2::{Digest, Sha256};
+use std::{
+    ffi::OsStr,
+    fmt, fs,
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Output, Stdio},
+};
+
+/// Passphrase of all the Ids created by the harness
+pub const PASSPHRASE: &str = "crev-test-harness";
+
+/// Enough answers for the confirmation prompts of proof editing
+///
+/// The editor is `true`, so drafts are used as they are, after confirming
+/// that's intended (and that lint warnings are fine).
+const CONFIRMATIONS: &str = "y\ny\ny\n";
+
+/// Temporary world with a fake registry, cargo projects, crev users and proof remotes
+pub struct Harness {
+    exe: PathBuf,
+    dir: tempfile::TempDir,
+}
+
+impl Harness {
+    /// Harness running the `cargo-crev` binary at `exe`
+    ///
+    /// In integration tests of `cargo-crev` that's `env!("CARGO_BIN_EXE_cargo-crev")`.
+    #[track_caller]
+    pub fn new(exe: impl Into<PathBuf>) -> Self {
+        let harness = Self {
+            exe: exe.into(),
+            dir: tempfile::tempdir().expect("can't create a temporary directory"),
+        };
+        create_dir(&harness.registry_path().join("index"));
+        create_dir(&harness.cargo_home());
+        let config = format!(
+            "[source.crates-io]\nreplace-with = \"fake-registry\"\n\n[source.fake-registry]\nlocal-registry = {:?}\n\n[net]\noffline = true\n",
+            harness.registry_path().display().to_string()
+        );
+        write_file(&harness.cargo_home().join("config.toml"), &config);
+        harness
+    }
+
+    /// The temporary directory everything is in
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// `CARGO_HOME` of all commands; crates.io is replaced with the fake registry there
+    #[must_use]
+    pub fn cargo_home(&self) -> PathBuf {
+        self.path().join("cargo-home")
+    }
+
+    fn registry_path(&self) -> PathBuf {
+        self.path().join("registry")
+    }
+
+    /// Publish `name` `version` to the fake registry, with `lib_rs` as its `src/lib.rs`
+    #[track_caller]
+    pub fn add_crate(&self, name: &str, version: &str, lib_rs: &str) {
+        let crate_file = crate_file(name, version, lib_rs);
+        let cksum: String = Sha256::digest(&crate_file)
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect();
+        fs::write(
+            self.registry_path().join(format!("{name}-{version}.crate")),
+            &crate_file,
+        )
+        .expect("can't write a .crate file");
+
+        let entry = serde_json::json!({
+            "name": name,
+            "vers": version,
+            "deps": [],
+            "cksum": cksum,
+            "features": {},
+            "yanked": false,
+        });
+        let index_path = self.registry_path().join("index").join(index_path(name));
+        create_dir(index_path.parent().expect("index path has a parent"));
+        let mut index = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&index_path)
+            .expect("can't open a registry index file");
+        writeln!(index, "{entry}").expect("can't write a registry index file");
+    }
+
+    /// A cargo project depending on exact versions of fake registry crates, with `Cargo.lock`
+    #[track_caller]
+    pub fn project(&self, name: &str, deps: &[(&str, &str)]) -> PathBuf {
+        self.project_with_optional(name, deps, &[])
+    }
+
+    /// Like [`Self::project`], with `optional` dependencies too
+    ///
+    /// Each optional dependency has the implicit feature of its name, and the
+    /// `default` feature enables all of them.
+    #[track_caller]
+    pub fn project_with_optional(
+        &self,
+        name: &str,
+        deps: &[(&str, &str)],
+        optional: &[(&str, &str)],
+    ) -> PathBuf {
+        let dir = self.path().join("projects").join(name);
+        create_dir(&dir.join("src"));
+        let mut manifest = format!(
+            "[package]\nname = \"{name}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[workspace]\n\n[dependencies]\n"
+        );
+        for (dep, version) in deps {
+            manifest.push_str(&format!("{dep} = \"={version}\"\n"));
+        }
+        for (dep, version) in optional {
+            manifest.push_str(&format!(
+                "{dep} = {{ version = \"={version}\", optional = true }}\n"
+            ));
+        }
+        if !optional.is_empty() {
+            let features: Vec<_> = optional
+                .iter()
+                .map(|(dep, _)| format!("\"{dep}\""))
+                .collect();
+            manifest.push_str(&format!(
+                "\n[features]\ndefault = [{}]\n",
+                features.join(", ")
+            ));
+        }
+        write_file(&dir.join("Cargo.toml"), &manifest);
+        write_file(&dir.join("src").join("lib.rs"), "");
+
+        let cargo = std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+        let output = Command::new(cargo)
+            .arg("generate-lockfile")
+            .current_dir(&dir)
+            .env("CARGO_HOME", self.cargo_home())
+            .env("CARGO_NET_OFFLINE", "true")
+            .output()
+            .expect("can't run cargo");
+        RunOutput {
+            command: "cargo generate-lockfile".into(),
+            output,
+        }
+        .assert_success();
+        dir
+    }
+
+    /// An empty bare git repository to publish proofs to
+    #[track_caller]
+    pub fn proof_remote(&self, name: &str) -> ProofRemote {
+        let path = self.path().join("remotes").join(format!("{name}.git"));
+        let repo = git2::Repository::init_bare(&path).expect("can't create a bare git repo");
+        repo.set_head("refs/heads/master")
+            .expect("can't set HEAD of a bare git repo");
+        ProofRemote { path }
+    }
+
+    /// A crev user with a new Id, and their own config and data directories
+    #[track_caller]
+    pub fn user(&self, name: &str) -> User<'_> {
+        let home = self.path().join("users").join(name);
+        create_dir(&home);
+        // commits to proof repositories need an author
+        write_file(
+            &home.join(".gitconfig"),
+            &format!("[user]\n\tname = {name}\n\temail = {name}@example.com\n"),
+        );
+        let mut user = User {
+            harness: self,
+            home,
+            id: String::new(),
+        };
+        user.run(&user.home, ["id", "new"], "").assert_success();
+        let current = user.run(&user.home, ["id", "current"], "");
+        current.assert_success();
+        user.id = current
+            .stdout()
+            .lines()
+            .find(|line| line.ends_with("(current)"))
+            .and_then(|line| line.split_whitespace().next())
+            .unwrap_or_else(|| panic!("no current Id after `id new`: {current:?}"))
+            .to_owned();
+        user
+    }
+}
+
+/// Bare git repository proofs are published to and fetched from
+pub struct ProofRemote {
+    path: PathBuf,
+}
+
+impl ProofRemote {
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// `file://` URL to fetch from
+    #[must_use]
+    pub fn url(&self) -> String {
+        format!("file://{}", self.path.display())
+    }
+}
+
+/// A crev user of a `Harness`, running commands with their own Id
+pub struct User<'h> {
+    harness: &'h Harness,
+    home: PathBuf,
+    id: String,
+}
+
+impl User<'_> {
+    /// The user's Id
+    #[must_use]
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Home directory of the user, and the crev root directory of the commands
+    #[must_use]
+    pub fn home(&self) -> &Path {
+        &self.home
+    }
+
+    /// `cargo-crev crev`, set up to run as this user, offline
+    #[must_use]
+    pub fn command(&self) -> Command {
+        let mut command = Command::new(&self.harness.exe);
+        command
+            .arg("crev")
+            .env("CARGO_CREV_ROOT_DIR_OVERRIDE", &self.home)
+            .env("HOME", &self.home)
+            .env("CARGO_HOME", self.harness.cargo_home())
+            .env("CARGO_NET_OFFLINE", "true")
+            .env("CREV_PASSPHRASE", PASSPHRASE)
+            .env("EDITOR", "true")
+            .env("VISUAL", "true")
+            .env_remove("CREV_PASSPHRASE_CMD")
+            .env_remove("CREV_PORTABLE_ROOT")
+            .env_remove("XDG_CONFIG_HOME");
+        command
+    }
+
+    /// Run `cargo crev <args>` in `cwd`, with `stdin` as the input
+    #[track_caller]
+    pub fn run<I, S>(&self, cwd: &Path, args: I, stdin: &str) -> RunOutput
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let args: Vec<_> = args.into_iter().map(|a| a.as_ref().to_owned()).collect();
+        let mut child = self
+            .command()
+            .args(&args)
+            .current_dir(cwd)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap_or_else(|e| panic!("Failed to run {}: {e}", self.harness.exe.display()));
+
+        let stdin_data = stdin.to_owned();
+        let mut child_stdin = child.stdin.take().expect("stdin is piped");
+        std::thread::spawn(move || {
+            // the command may exit without reading it all
+            let _ = child_stdin.write_all(stdin_data.as_bytes());
+        });
+        RunOutput {
+            command: format!(
+                "cargo crev {}",
+                args.iter()
+                    .map(|a| a.to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            ),
+            output: child.wait_with_output().expect("child process lost"),
+        }
+    }
+
+    /// Review the dependency `crate_name` of `project`, accepting the default draft
+    #[track_caller]
+    pub fn review(&self, project: &Path, crate_name: &str) -> RunOutput {
+        self.run(
+            project,
+            ["crate", "review", crate_name, "--skip-activity-check"],
+            CONFIRMATIONS,
+        )
+    }
+
+    /// Trust `other` at the `high` level
+    #[track_caller]
+    pub fn trust(&self, other: &User<'_>) -> RunOutput {
+        self.run(
+            &self.home,
+            ["id", "trust", "--level", "high", other.id()],
+            "",
+        )
+    }
+
+    /// Fetch proofs from `remote`
+    #[track_caller]
+    pub fn fetch(&self, remote: &ProofRemote) -> RunOutput {
+        self.run(
+            &self.home,
+            ["repo", "fetch", "url", remote.url().as_str()],
+            "",
+        )
+    }
+
+    /// `crate verify` in `project`
+    #[track_caller]
+    pub fn verify(&self, project: &Path) -> RunOutput {
+        self.run(project, ["crate", "verify"], "")
+    }
+
+    /// Push the committed proofs of the user to `remote`
+    #[track_caller]
+    pub fn publish_proofs(&self, remote: &ProofRemote) {
+        let dir = self.run(&self.home, ["repo", "dir"], "");
+        dir.assert_success();
+        let repo = git2::Repository::open(dir.stdout().trim())
+            .unwrap_or_else(|e| panic!("can't open the proof repo of {}: {e}", self.id));
+        let head = repo.head().expect("proof repo has no commits");
+        let head = head.name().expect("HEAD is a branch");
+        repo.remote_anonymous(&remote.url())
+            .and_then(|mut r| r.push(&[format!("+{head}:refs/heads/master")], None))
+            .unwrap_or_else(|e| panic!("can't push proofs to {}: {e}", remote.url()));
+    }
+}
+
+/// Output of a command, with helpers to check it
+pub struct RunOutput {
+    command: String,
+    pub output: Output,
+}
+
+impl RunOutput {
+    #[must_use]
+    pub fn success(&self) -> bool {
+        self.output.status.success()
+    }
+
+    #[must_use]
+    pub fn stdout(&self) -> String {
+        String::from_utf8_lossy(&self.output.stdout).into_owned()
+    }
+
+    #[must_use]
+    pub fn stderr(&self) -> String {
+        String::from_utf8_lossy(&self.output.stderr).into_owned()
+    }
+
+    #[track_caller]
+    pub fn assert_success(&self) -> &Self {
+        assert!(self.success(), "expected success: {self:?}");
+        self
+    }
+
+    #[track_caller]
+    pub fn assert_failure(&self) -> &Self {
+        assert!(!self.success(), "expected failure: {self:?}");
+        self
+    }
+}
+
+impl fmt::Debug for RunOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "`{}` exited with {}\n--- stdout\n{}\n--- stderr\n{}",
+            self.command,
+            self.output.status,
+            self.stdout(),
+            self.stderr()
+        )
+    }
+}
+
+/// Path of the index file of `name` in a registry, as cargo lays it out
+fn index_path(name: &str) -> PathBuf {
+    let name = name.to_lowercase();
+    match name.len() {
+        1 => Path::new("1").join(&name),
+        2 => Path::new("2").join(&name),
+        3 => Path::new("3").join(&name[..1]).join(&name),
+        _ => Path::new(&name[..2]).join(&name[2..4]).join(&name),
+    }
+}
+
+/// `.crate` file (gzipped tarball) with a minimal manifest and `lib_rs`
+fn crate_file(name: &str, version: &str, lib_rs: &str) -> Vec<u8> {
+    let manifest =
+        format!("[package]\nname = \"{name}\"\nversion = \"{version}\"\nedition = \"2021\"\n");
+    let mut tar = tar::Builder::new(GzEncoder::new(vec![], Compression::default()));
+    for (path, content) in [("Cargo.toml", manifest.as_str()), ("src/lib.rs", lib_rs)] {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        tar.append_data(
+            &mut header,
+            format!("{name}-{version}/{path}"),
+            content.as_bytes(),
+        )
+        .expect("can't build a .crate file");
+    }
+    tar.into_inner()
+        .and_then(GzEncoder::finish)
+        .expect("can't build a .crate file")
+}
+
+#[track_caller]
+fn create_dir(path: &Path) {
+    fs::create_dir_all(path).unwrap_or_else(|e| panic!("can't create {}: {e}", path.display()));
+}
+
+#[track_caller]
+fn write_file(path: &Path, content: &str) {
+    fs::write(path, content).unwrap_or_else(|e| panic!("can't write {}: {e}", path.display()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_paths_match_cargo() {
+        assert_eq!(index_path("a"), Path::new("1/a"));
+        assert_eq!(index_path("ab"), Path::new("2/ab"));
+        assert_eq!(index_path("abc"), Path::new("3/a/abc"));
+        assert_eq!(index_path("Serde_json"), Path::new("se/rd/serde_json"));
+    }
+}
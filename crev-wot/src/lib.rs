@@ -26,13 +26,16 @@ use crev_data::{
     Digest, Id, Level, RegistrySource, Url, Version,
 };
 use default::default;
-use log::debug;
+use log::{debug, warn};
 use std::{
     collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    fmt,
+    path::PathBuf,
     sync,
 };
 
 pub mod trust_set;
+use trust_set::Skipped;
 pub use trust_set::TrustSet;
 
 #[derive(thiserror::Error, Debug)]
@@ -42,6 +45,25 @@ pub enum Error {
 
     #[error("{}", _0)]
     Data(#[from] crev_data::Error),
+
+    #[error("Invalid signature: {}", _0)]
+    InvalidSignature(crev_data::Error),
+}
+
+impl From<crev_data::proof::BuildProofError> for Error {
+    fn from(e: crev_data::proof::BuildProofError) -> Self {
+        Self::Data(e.into())
+    }
+}
+
+impl Error {
+    fn rejection_reason(&self) -> RejectionReason {
+        match self {
+            Error::UnknownProofType(_) => RejectionReason::UnknownKind,
+            Error::Data(_) => RejectionReason::Malformed,
+            Error::InvalidSignature(_) => RejectionReason::InvalidSignature,
+        }
+    }
 }
 
 type Result<T, E = Error> = std::result::Result<T, E>;
@@ -55,6 +77,113 @@ pub enum FetchSource {
     LocalUser,
 }
 
+/// Where a proof has been loaded from, see `ProofDB::get_proof_origin`
+#[derive(Debug, Clone)]
+pub struct ProofOrigin {
+    pub fetched_from: FetchSource,
+    /// The file it was read from; `None` if it wasn't read from a file,
+    /// eg. from git history of a proof repo
+    pub file: Option<PathBuf>,
+}
+
+impl From<FetchSource> for ProofOrigin {
+    fn from(fetched_from: FetchSource) -> Self {
+        Self {
+            fetched_from,
+            file: None,
+        }
+    }
+}
+
+impl fmt::Display for ProofOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.fetched_from {
+            FetchSource::Url(url) => f.write_str(&url.url)?,
+            FetchSource::LocalUser => f.write_str("own proof repo")?,
+        }
+        match &self.file {
+            Some(file) => write!(f, " ({})", file.display()),
+            None => f.write_str(" (not from a file)"),
+        }
+    }
+}
+
+/// Why a proof was not imported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RejectionReason {
+    InvalidSignature,
+    /// Content didn't parse as its kind of proof
+    Malformed,
+    /// A kind of proof this version doesn't know
+    UnknownKind,
+    /// More proofs from the same URL than allowed, see `ProofDB::limit_proofs_per_url`
+    OverLimit,
+}
+
+impl fmt::Display for RejectionReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            RejectionReason::InvalidSignature => "invalid signature",
+            RejectionReason::Malformed => "malformed",
+            RejectionReason::UnknownKind => "unknown kind",
+            RejectionReason::OverLimit => "over the proof limit of the repo",
+        })
+    }
+}
+
+/// What happened to the proofs given to `ProofDB::import_from_iter`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    /// New and current proofs
+    pub accepted: usize,
+    /// Proofs imported before
+    pub duplicates: usize,
+    /// New proofs that an already imported, more recent proof of the same author
+    /// about the same thing takes precedence over
+    pub overridden: usize,
+    pub rejected: BTreeMap<RejectionReason, usize>,
+}
+
+impl ImportReport {
+    #[must_use]
+    pub fn rejected_count(&self) -> usize {
+        self.rejected.values().sum()
+    }
+}
+
+impl std::ops::AddAssign for ImportReport {
+    fn add_assign(&mut self, other: Self) {
+        self.accepted += other.accepted;
+        self.duplicates += other.duplicates;
+        self.overridden += other.overridden;
+        for (reason, count) in other.rejected {
+            *self.rejected.entry(reason).or_default() += count;
+        }
+    }
+}
+
+impl fmt::Display for ImportReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} accepted, {} duplicates, {} overridden, {} rejected",
+            self.accepted,
+            self.duplicates,
+            self.overridden,
+            self.rejected_count()
+        )?;
+        if !self.rejected.is_empty() {
+            let reasons: Vec<_> = self
+                .rejected
+                .iter()
+                .map(|(reason, count)| format!("{count} {reason}"))
+                .collect();
+            write!(f, " ({})", reasons.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
 /// A `T` with a timestamp
 ///
 /// This allows easily keeping track of a most recent version
@@ -153,6 +282,14 @@ impl From<&review::Package> for PkgVersionReviewId {
     }
 }
 
+/// An author can have one issue update in effect for each reported issue
+#[derive(Hash, Debug, Clone, PartialEq, Eq)]
+struct IssueUpdateKey {
+    from: Id,
+    issue: String,
+    reported_in: Vec<u8>,
+}
+
 /// An unique id for a review by a given author of a given package.
 ///
 /// Similar to `PackageVersionReviewId`, but where
@@ -239,12 +376,33 @@ impl AlternativesData {
 
 pub type TimestampedTrustDetails = Timestamped<TrustDetails>;
 
+/// Latest trust proof of one Id for another, see `ProofDB::get_trust_graph_from`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrustEdge {
+    pub from: Id,
+    pub to: Id,
+    pub level: TrustLevel,
+    pub date: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone)]
 pub struct TrustDetails {
     level: TrustLevel,
     override_: HashSet<Id>,
 }
 
+impl From<&proof::Trust> for TrustDetails {
+    fn from(trust: &proof::Trust) -> Self {
+        TrustDetails {
+            level: trust.trust,
+            override_: trust.override_.iter().map(|o| o.id.id.clone()).collect(),
+        }
+    }
+}
+
+/// Owner and name of a group, see [`proof::Group`]
+type GroupKey = (Id, String);
+
 /// In memory database tracking information from proofs
 ///
 /// After population, used for calculating the effective trust set, etc.
@@ -256,6 +414,8 @@ pub struct TrustDetails {
 pub struct ProofDB {
     /// who -(trusts)-> whom
     trust_id_to_id: HashMap<Id, HashMap<Id, TimestampedTrustDetails>>,
+    /// who -(trusts members of)-> which group
+    trust_id_to_group: HashMap<Id, HashMap<GroupKey, TimestampedTrustDetails>>,
     /// who -(is being trusted by -> whom
     reverse_trust_id_to_id: HashMap<Id, HashMap<Id, TimestampedTrustLevel>>,
 
@@ -276,6 +436,12 @@ pub struct ProofDB {
     // all trust proofs here
     trust_proofs_by_signature: HashMap<Signature, proof::Trust>,
 
+    // all code reviews here
+    code_review_by_signature: HashMap<Signature, review::Code>,
+
+    // lowercased words of the comments of proofs, to the signatures of the proofs
+    comment_index: HashMap<String, HashSet<Signature>>,
+
     // we can get the to the review through the signature from these two
     package_review_signatures_by_package_digest:
         HashMap<Vec<u8>, HashMap<PkgVersionReviewId, TimestampedSignature>>,
@@ -295,6 +461,43 @@ pub struct ProofDB {
     // given an Id of an author, get the list of all package version id that were produced by it
     from_id_to_package_reviews: HashMap<Id, HashSet<proof::PackageVersionId>>,
 
+    // signatures of trust proofs and package reviews with the Id in their `override` list;
+    // includes superseded proofs, so they have to be filtered on lookup
+    override_signatures_by_overridden_id: HashMap<Id, HashSet<Signature>>,
+
+    // epoch proofs (proof repo truncations) of each author
+    epochs_by_id: HashMap<Id, HashMap<Signature, proof::Epoch>>,
+
+    // the most recent review request of every requester, for each package version
+    review_requests: HashMap<proof::PackageVersionId, HashMap<Id, proof::ReviewRequest>>,
+
+    // the most recent waiver of every author, for each package version
+    waivers: HashMap<proof::PackageVersionId, HashMap<Id, proof::Waiver>>,
+
+    // the most recent issue update of every author, for each reported issue
+    issue_updates: HashMap<proof::PackageId, HashMap<IssueUpdateKey, proof::IssueUpdate>>,
+
+    // the most recent group proof of every owner, for each group name
+    groups: HashMap<GroupKey, proof::Group>,
+
+    // signers of successor proofs, by predecessor and successor, with the dates
+    // of their proofs; a successor counts once both Ids signed, see `get_successor`
+    successor_signers: HashMap<Id, HashMap<Id, HashMap<Id, DateTime<Utc>>>>,
+
+    // encrypted proofs not decrypted (yet), see `import_decrypted`
+    encrypted_by_signature: HashMap<Signature, proof::Encrypted>,
+
+    // signatures of all the proofs imported, to tell duplicates apart
+    imported_signatures: HashSet<Signature>,
+
+    // where each proof was first loaded from
+    origin_by_signature: HashMap<Signature, ProofOrigin>,
+
+    // proofs imported from each URL, and how many are allowed, see `limit_proofs_per_url`
+    proof_count_by_url: HashMap<String, usize>,
+    max_proofs_per_url: Option<usize>,
+    unlimited_urls: HashSet<String>,
+
     // original data about pkg alternatives
     // for every package_id, we store a map of ids that had alternatives for it,
     // and a timestamped signature of the proof, so we keep track of only
@@ -314,9 +517,12 @@ impl Default for ProofDB {
     fn default() -> Self {
         ProofDB {
             trust_id_to_id: default(),
+            trust_id_to_group: default(),
             reverse_trust_id_to_id: default(),
             ids_to_trust_proof_signatures: default(),
             trust_proofs_by_signature: default(),
+            code_review_by_signature: default(),
+            comment_index: default(),
             url_by_id_self_reported: default(),
             url_by_id_reported_by_others: default(),
             package_review_signatures_by_package_digest: default(),
@@ -327,6 +533,19 @@ impl Default for ProofDB {
             package_alternatives: default(),
             package_flags: default(),
             from_id_to_package_reviews: default(),
+            override_signatures_by_overridden_id: default(),
+            epochs_by_id: default(),
+            review_requests: default(),
+            waivers: default(),
+            issue_updates: default(),
+            groups: default(),
+            successor_signers: default(),
+            encrypted_by_signature: default(),
+            imported_signatures: default(),
+            origin_by_signature: default(),
+            proof_count_by_url: default(),
+            max_proofs_per_url: None,
+            unlimited_urls: default(),
 
             insertion_counter: 0,
             derived_alternatives: sync::RwLock::new(AlternativesData::new()),
@@ -334,9 +553,32 @@ impl Default for ProofDB {
     }
 }
 
+/// A trust proof that could be retracted, see `ProofDB::suggest_trust_prune`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PruneSuggestion {
+    /// The effective trust set is the same without it
+    Redundant { id: Id, trust_level: TrustLevel },
+    /// No recent proofs by the Id; `None` if none at all are known
+    Stale {
+        id: Id,
+        trust_level: TrustLevel,
+        latest_proof: Option<DateTime<Utc>>,
+    },
+}
+
+impl PruneSuggestion {
+    #[must_use]
+    pub fn id(&self) -> &Id {
+        match self {
+            PruneSuggestion::Redundant { id, .. } | PruneSuggestion::Stale { id, .. } => id,
+        }
+    }
+}
+
 /// Result of `get_open_issues_for_version`
 #[derive(Default, Debug)]
 pub struct IssueDetails {
+    /// The highest severity of the reports, as amended by issue updates
     pub severity: Level,
     /// Reviews that reported a given issue by `issues` field
     pub issues: HashSet<PkgVersionReviewId>,
@@ -436,6 +678,19 @@ impl ProofDB {
             .map(|(id, flags)| (id, &flags.value))
     }
 
+    /// Current package reviews of all sources
+    pub fn get_all_pkg_reviews(&self) -> impl Iterator<Item = &proof::review::Package> {
+        self.package_reviews
+            .values()
+            .flat_map(|map| map.values())
+            .flat_map(|map| map.values())
+            .flatten()
+            .map(move |pkg_review_id| {
+                self.get_pkg_review_by_pkg_review_id(pkg_review_id)
+                    .expect("exists")
+            })
+    }
+
     /// Use `"https://crates.io"` to get all crates-io reviews
     pub fn get_pkg_reviews_for_source<'a>(
         &'a self,
@@ -650,7 +905,9 @@ impl ProofDB {
     ///
     /// These are calculated from `advisories` and `issues` fields
     /// of the package reviews of reviewers intside a given `trust_set`
-    /// of at least given `trust_level_required`.
+    /// of at least given `trust_level_required`, and issue updates
+    /// of the same reviewers: reports can be closed, moved to
+    /// the issue they duplicate, or get a different severity.
     pub fn get_open_issues_for_version(
         &self,
         source: RegistrySource<'_>,
@@ -664,6 +921,12 @@ impl ProofDB {
 
         // Here we track all the reported issue by issue id
         let mut issue_reports_by_id: HashMap<String, IssueDetails> = HashMap::new();
+        // and the severity of each report, after issue updates
+        let mut report_severities: HashMap<(String, PkgVersionReviewId), Level> = HashMap::new();
+        let package = proof::PackageId {
+            source: source.to_owned(),
+            name: name.to_owned(),
+        };
 
         // First we go through all the reports in previous versions with `issues` fields and collect these.
         // Easy.
@@ -681,11 +944,22 @@ impl ProofDB {
                 )
             })
         {
-            issue_reports_by_id
-                .entry(issue.id.clone())
-                .or_default()
-                .issues
-                .insert(PkgVersionReviewId::from(review));
+            let report = PkgVersionReviewId::from(review);
+            if let Some((id, severity)) = self.resolve_issue_report(
+                &package,
+                &issue.id,
+                issue.severity,
+                &report,
+                trust_set,
+                trust_level_required,
+            ) {
+                issue_reports_by_id
+                    .entry(id.clone())
+                    .or_default()
+                    .issues
+                    .insert(report.clone());
+                report_severities.insert((id, report), severity);
+            }
         }
 
         // Now the complicated part. We go through all the advisories for all the versions
@@ -716,11 +990,22 @@ impl ProofDB {
                 &review.package.id.version,
             ) {
                 for id in &advisory.ids {
-                    issue_reports_by_id
-                        .entry(id.clone())
-                        .or_default()
-                        .issues
-                        .insert(PkgVersionReviewId::from(review));
+                    let report = PkgVersionReviewId::from(review);
+                    if let Some((id, severity)) = self.resolve_issue_report(
+                        &package,
+                        id,
+                        advisory.severity,
+                        &report,
+                        trust_set,
+                        trust_level_required,
+                    ) {
+                        issue_reports_by_id
+                            .entry(id.clone())
+                            .or_default()
+                            .issues
+                            .insert(report.clone());
+                        report_severities.insert((id, report), severity);
+                    }
                 }
             }
 
@@ -753,6 +1038,17 @@ impl ProofDB {
         issue_reports_by_id
             .into_iter()
             .filter(|(_id, markers)| !markers.issues.is_empty() || !markers.advisories.is_empty())
+            .map(|(id, mut markers)| {
+                if let Some(severity) = markers
+                    .issues
+                    .iter()
+                    .filter_map(|report| report_severities.get(&(id.clone(), report.clone())))
+                    .max()
+                {
+                    markers.severity = *severity;
+                }
+                (id, markers)
+            })
             .collect()
     }
 
@@ -815,12 +1111,63 @@ impl ProofDB {
             .fold(0, |count, (_id, set)| count + set.len())
     }
 
-    fn add_code_review(&mut self, review: &review::Code, fetched_from: &FetchSource) {
+    fn add_code_review(
+        &mut self,
+        review: review::Code,
+        signature: &str,
+        fetched_from: &FetchSource,
+    ) {
         let from = &review.from();
         self.record_url_from_from_field(&review.date_utc(), from, fetched_from);
-        for _file in &review.files {
-            // not implemented right now; just ignore
+        self.index_comment(signature, &review.comment);
+        self.code_review_by_signature
+            .entry(signature.to_owned())
+            .or_insert(review);
+    }
+
+    /// Code reviews, with their signatures, in no particular order
+    pub fn all_code_reviews(&self) -> impl Iterator<Item = (&str, &review::Code)> {
+        self.code_review_by_signature
+            .iter()
+            .map(|(signature, review)| (signature.as_str(), review))
+    }
+
+    /// Trust proofs (superseded ones too), with their signatures, in no particular order
+    pub fn all_trust_proofs(&self) -> impl Iterator<Item = (&str, &proof::Trust)> {
+        self.trust_proofs_by_signature
+            .iter()
+            .map(|(signature, trust)| (signature.as_str(), trust))
+    }
+
+    fn index_comment(&mut self, signature: &str, comment: &str) {
+        for word in comment_words(comment) {
+            self.comment_index
+                .entry(word)
+                .or_default()
+                .insert(signature.to_owned());
+        }
+    }
+
+    /// Signatures of proofs with comments containing all the words of `query`, ignoring case
+    ///
+    /// Comments of package reviews include the ones of their issues and advisories.
+    pub fn find_by_comment(&self, query: &str) -> HashSet<&str> {
+        let mut words = comment_words(query);
+        let Some(first) = words.next() else {
+            return HashSet::new();
+        };
+        let mut found: HashSet<&str> = self
+            .comment_index
+            .get(&first)
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect();
+        for word in words {
+            let with_word = self.comment_index.get(&word);
+            found.retain(|signature| with_word.is_some_and(|s| s.contains(*signature)));
         }
+        found
     }
 
     fn add_package_review(
@@ -829,7 +1176,7 @@ impl ProofDB {
         signature: &str,
         fetched_from: &FetchSource,
         proof_digest: proof::Digest,
-    ) {
+    ) -> bool {
         self.insertion_counter += 1;
 
         let from = review.from();
@@ -847,10 +1194,13 @@ impl ProofDB {
             .and_modify(|s| s.update_to_more_recent(&timestamp_signature))
             .or_insert_with(|| timestamp_signature.clone());
 
-        self.package_review_signatures_by_pkg_review_id
+        let is_current = self
+            .package_review_signatures_by_pkg_review_id
             .entry(pkg_review_id.clone())
             .and_modify(|s| s.update_to_more_recent(&timestamp_signature))
-            .or_insert_with(|| timestamp_signature.clone());
+            .or_insert_with(|| timestamp_signature.clone())
+            .value
+            == signature;
 
         self.proof_digest_by_pkg_review_id
             .entry(pkg_review_id.clone())
@@ -862,6 +1212,8 @@ impl ProofDB {
             .or_default()
             .insert(pkg_review_id.package_version_id.clone());
 
+        self.record_overrides(&review.override_, signature);
+
         self.package_reviews
             .entry(review.package.id.id.source.clone())
             .or_default()
@@ -885,9 +1237,20 @@ impl ProofDB {
             .and_modify(|f| f.update_to_more_recent(&timestamp_flags))
             .or_insert_with(|| timestamp_flags);
 
+        self.index_comment(signature, &review.comment);
+        for comment in review
+            .issues
+            .iter()
+            .map(|issue| &issue.comment)
+            .chain(review.advisories.iter().map(|advisory| &advisory.comment))
+        {
+            self.index_comment(signature, comment);
+        }
         self.package_review_by_signature
             .entry(signature.to_owned())
             .or_insert(review);
+
+        is_current
     }
 
     pub fn get_package_review_count(
@@ -941,14 +1304,7 @@ impl ProofDB {
         trust_proof: &proof::Trust,
         signature: &str,
     ) {
-        let trust = TrustDetails {
-            level: trust_proof.trust,
-            override_: trust_proof
-                .override_
-                .iter()
-                .map(|o| o.id.id.clone())
-                .collect(),
-        };
+        let trust = TrustDetails::from(trust_proof);
 
         let tl = TimestampedTrustLevel {
             value: trust.level,
@@ -984,18 +1340,371 @@ impl ProofDB {
             .or_insert_with(|| tl);
     }
 
-    fn add_trust(&mut self, trust: &proof::Trust, signature: &str, fetched_from: &FetchSource) {
+    fn record_overrides(&mut self, override_: &[proof::OverrideItem], signature: &str) {
+        for item in override_ {
+            self.override_signatures_by_overridden_id
+                .entry(item.id.id.clone())
+                .or_default()
+                .insert(signature.to_owned());
+        }
+    }
+
+    fn add_trust(
+        &mut self,
+        trust: &proof::Trust,
+        signature: &str,
+        fetched_from: &FetchSource,
+    ) -> bool {
         let from = &trust.from();
         self.record_url_from_from_field(&trust.date_utc(), from, fetched_from);
+        self.record_overrides(&trust.override_, signature);
+        self.index_comment(signature, &trust.comment);
         for to in &trust.ids {
             self.add_trust_raw(&from.id, &to.id, trust.date_utc(), trust, signature);
         }
-        for to in &trust.ids {
+        let td = TimestampedTrustDetails {
+            value: TrustDetails::from(trust),
+            date: trust.date_utc(),
+        };
+        for group in &trust.groups {
+            self.trust_id_to_group
+                .entry(from.id.clone())
+                .or_default()
+                .entry((group.from.id.clone(), group.name.clone()))
+                .and_modify(|e| e.update_to_more_recent(&td))
+                .or_insert_with(|| td.clone());
+        }
+        for to in trust
+            .ids
+            .iter()
+            .chain(trust.groups.iter().map(|group| &group.from))
+        {
             // Others should not be making verified claims about this URL,
             // regardless of where these proofs were fetched from, because only
             // owner of the Id is authoritative.
             self.record_url_from_to_field(&trust.date_utc(), to);
         }
+        trust.ids.iter().any(|to| {
+            self.ids_to_trust_proof_signatures[&(from.id.clone(), to.id.clone())].value == signature
+        }) || trust.groups.iter().any(|group| {
+            self.trust_id_to_group[&from.id][&(group.from.id.clone(), group.name.clone())].date
+                == trust.date_utc()
+        })
+    }
+
+    fn add_group(&mut self, group: proof::Group, fetched_from: &FetchSource) -> bool {
+        let from = group.from().clone();
+        self.record_url_from_from_field(&group.date_utc(), &from, fetched_from);
+        for member in &group.ids {
+            self.record_url_from_to_field(&group.date_utc(), member);
+        }
+        let key = (from.id, group.name.clone());
+        match self.groups.get(&key) {
+            Some(existing) if group.date_utc() < existing.date_utc() => false,
+            _ => {
+                self.groups.insert(key, group);
+                true
+            }
+        }
+    }
+
+    /// The current version of the group `name` published by `owner`
+    pub fn get_group(&self, owner: &Id, name: &str) -> Option<&proof::Group> {
+        self.groups.get(&(owner.clone(), name.to_owned()))
+    }
+
+    /// Current groups published by `owner`, in no particular order
+    pub fn get_groups_of<'a>(&'a self, owner: &'a Id) -> impl Iterator<Item = &'a proof::Group> {
+        self.groups
+            .iter()
+            .filter(move |((group_owner, _), _)| group_owner == owner)
+            .map(|(_, group)| group)
+    }
+
+    fn add_successor(&mut self, successor: &proof::Successor, fetched_from: &FetchSource) -> bool {
+        let from = successor.from();
+        let date = successor.date_utc();
+        self.record_url_from_from_field(&date, from, fetched_from);
+        for id in [&successor.predecessor, &successor.successor] {
+            if id.id != from.id {
+                self.record_url_from_to_field(&date, id);
+            }
+        }
+        let signers = self
+            .successor_signers
+            .entry(successor.predecessor.id.clone())
+            .or_default()
+            .entry(successor.successor.id.clone())
+            .or_default();
+        match signers.get(&from.id) {
+            Some(existing) if date <= *existing => false,
+            _ => {
+                signers.insert(from.id.clone(), date);
+                true
+            }
+        }
+    }
+
+    /// The Id that replaced `id`, if both of them signed a successor proof for it
+    ///
+    /// The most recent one, if `id` designated more than one.
+    pub fn get_successor(&self, id: &Id) -> Option<&Id> {
+        self.successor_signers
+            .get(id)?
+            .iter()
+            .filter(|(successor, signers)| {
+                signers.contains_key(id) && signers.contains_key(*successor)
+            })
+            .max_by_key(|(_, signers)| signers.values().max())
+            .map(|(successor, _)| successor)
+    }
+
+    /// The successor of `id`, its successor, and so on
+    pub fn get_successors_of<'a>(&'a self, id: &'a Id) -> Vec<&'a Id> {
+        let mut successors: Vec<&Id> = vec![];
+        let mut current = id;
+        while let Some(successor) = self.get_successor(current) {
+            if successor == id || successors.contains(&successor) {
+                break;
+            }
+            successors.push(successor);
+            current = successor;
+        }
+        successors
+    }
+
+    fn add_encrypted(
+        &mut self,
+        encrypted: proof::Encrypted,
+        signature: &str,
+        fetched_from: &FetchSource,
+    ) {
+        self.record_url_from_from_field(&encrypted.date_utc(), encrypted.from(), fetched_from);
+        self.encrypted_by_signature
+            .insert(signature.to_owned(), encrypted);
+    }
+
+    /// Encrypted proofs that weren't decrypted, by anyone
+    pub fn get_encrypted_proofs(&self) -> impl Iterator<Item = &proof::Encrypted> {
+        self.encrypted_by_signature.values()
+    }
+
+    /// Import the proofs inside the encrypted proofs `decrypt` can decrypt
+    ///
+    /// `decrypt` returns the signed proof inside, if it has a key for it.
+    /// The decrypted proofs count as loaded from where their encrypted proof was,
+    /// and `get_encrypted_proofs` no longer lists them.
+    pub fn import_decrypted(
+        &mut self,
+        mut decrypt: impl FnMut(&proof::Encrypted) -> Option<proof::Proof>,
+    ) -> ImportReport {
+        let decrypted: Vec<_> = self
+            .encrypted_by_signature
+            .iter()
+            .filter_map(|(signature, encrypted)| Some((signature.clone(), decrypt(encrypted)?)))
+            .collect();
+        let mut proofs = vec![];
+        for (signature, proof) in decrypted {
+            self.encrypted_by_signature.remove(&signature);
+            if let Some(origin) = self.origin_by_signature.get(&signature) {
+                proofs.push((proof, origin.clone()));
+            }
+        }
+        self.import_with_origin(proofs.into_iter(), true)
+    }
+
+    fn add_epoch(&mut self, epoch: proof::Epoch, signature: &str, fetched_from: &FetchSource) {
+        let from = epoch.from().clone();
+        self.record_url_from_from_field(&epoch.date_utc(), &from, fetched_from);
+        self.epochs_by_id
+            .entry(from.id)
+            .or_default()
+            .insert(signature.to_owned(), epoch);
+    }
+
+    /// Epoch proofs of `id`, oldest first: its proofs signed before
+    /// an epoch may be only in the archive of that epoch
+    pub fn get_epochs(&self, id: &Id) -> Vec<&proof::Epoch> {
+        let mut epochs: Vec<_> = self
+            .epochs_by_id
+            .get(id)
+            .into_iter()
+            .flat_map(|epochs| epochs.values())
+            .collect();
+        epochs.sort_by_key(|epoch| epoch.date_utc());
+        epochs
+    }
+
+    pub fn all_epochs(&self) -> impl Iterator<Item = &proof::Epoch> {
+        self.epochs_by_id
+            .values()
+            .flat_map(|epochs| epochs.values())
+    }
+
+    /// Returns `false` if a more recent request of the same author takes precedence
+    fn add_review_request(
+        &mut self,
+        request: proof::ReviewRequest,
+        fetched_from: &FetchSource,
+    ) -> bool {
+        let from = request.from().clone();
+        self.record_url_from_from_field(&request.date_utc(), &from, fetched_from);
+        let requests = self
+            .review_requests
+            .entry(request.package.clone())
+            .or_default();
+        match requests.get(&from.id) {
+            Some(existing) if request.date_utc() < existing.date_utc() => false,
+            _ => {
+                requests.insert(from.id, request);
+                true
+            }
+        }
+    }
+
+    /// Review requests for a package version, one per requester
+    pub fn get_review_requests_for_package_version(
+        &self,
+        package: &proof::PackageVersionId,
+    ) -> impl Iterator<Item = &proof::ReviewRequest> {
+        self.review_requests
+            .get(package)
+            .into_iter()
+            .flat_map(|requests| requests.values())
+    }
+
+    pub fn all_review_requests(&self) -> impl Iterator<Item = &proof::ReviewRequest> {
+        self.review_requests
+            .values()
+            .flat_map(|requests| requests.values())
+    }
+
+    /// Returns `false` if a more recent waiver of the same author takes precedence
+    fn add_waiver(&mut self, waiver: proof::Waiver, fetched_from: &FetchSource) -> bool {
+        let from = waiver.from().clone();
+        self.record_url_from_from_field(&waiver.date_utc(), &from, fetched_from);
+        let waivers = self.waivers.entry(waiver.package.clone()).or_default();
+        match waivers.get(&from.id) {
+            Some(existing) if waiver.date_utc() < existing.date_utc() => false,
+            _ => {
+                waivers.insert(from.id, waiver);
+                true
+            }
+        }
+    }
+
+    /// Waivers of a package version, one per author, expired or not
+    pub fn get_waivers_for_package_version(
+        &self,
+        package: &proof::PackageVersionId,
+    ) -> impl Iterator<Item = &proof::Waiver> {
+        self.waivers
+            .get(package)
+            .into_iter()
+            .flat_map(|waivers| waivers.values())
+    }
+
+    pub fn all_waivers(&self) -> impl Iterator<Item = &proof::Waiver> {
+        self.waivers.values().flat_map(|waivers| waivers.values())
+    }
+
+    /// The most recent issue update of each author and report
+    pub fn all_issue_updates(&self) -> impl Iterator<Item = &proof::IssueUpdate> {
+        self.issue_updates
+            .values()
+            .flat_map(|updates| updates.values())
+    }
+
+    /// Returns `false` if a more recent update of the same author and report takes precedence
+    fn add_issue_update(&mut self, update: proof::IssueUpdate, fetched_from: &FetchSource) -> bool {
+        let from = update.from().clone();
+        self.record_url_from_from_field(&update.date_utc(), &from, fetched_from);
+        let key = IssueUpdateKey {
+            from: from.id,
+            issue: update.issue.clone(),
+            reported_in: update.reported_in.clone(),
+        };
+        let updates = self
+            .issue_updates
+            .entry(update.package.clone())
+            .or_default();
+        match updates.get(&key) {
+            Some(existing) if update.date_utc() < existing.date_utc() => false,
+            _ => {
+                updates.insert(key, update);
+                true
+            }
+        }
+    }
+
+    /// Issue updates of a package, one per author and reported issue
+    pub fn get_issue_updates_for_package(
+        &self,
+        package: &proof::PackageId,
+    ) -> impl Iterator<Item = &proof::IssueUpdate> {
+        self.issue_updates
+            .get(package)
+            .into_iter()
+            .flat_map(|updates| updates.values())
+    }
+
+    /// The most recent update of `issue_id` as reported in `report`, by a trusted author
+    fn get_issue_update_for_report(
+        &self,
+        package: &proof::PackageId,
+        issue_id: &str,
+        report: &PkgVersionReviewId,
+        trust_set: &TrustSet,
+        trust_level_required: TrustLevel,
+    ) -> Option<&proof::IssueUpdate> {
+        let digest = &self.proof_digest_by_pkg_review_id.get(report)?.value;
+        self.get_issue_updates_for_package(package)
+            .filter(|update| update.issue == issue_id && update.reported_in == digest.0)
+            .filter(|update| {
+                trust_set.get_effective_trust_level(&update.from().id) >= trust_level_required
+            })
+            .max_by_key(|update| update.date_utc())
+    }
+
+    /// Id and severity a report of `issue_id` counts with after issue updates;
+    /// `None` if the issue was closed
+    ///
+    /// Duplicates are resolved to their target first, so that closing the
+    /// target or changing its severity applies to its duplicates too.
+    fn resolve_issue_report(
+        &self,
+        package: &proof::PackageId,
+        issue_id: &str,
+        severity: Level,
+        report: &PkgVersionReviewId,
+        trust_set: &TrustSet,
+        trust_level_required: TrustLevel,
+    ) -> Option<(String, Level)> {
+        let mut issue_id = issue_id.to_owned();
+        let mut duplicates = BTreeSet::new();
+        loop {
+            match self
+                .get_issue_update_for_report(
+                    package,
+                    &issue_id,
+                    report,
+                    trust_set,
+                    trust_level_required,
+                )
+                .map(|update| &update.action)
+            {
+                None => return Some((issue_id, severity)),
+                Some(proof::IssueAction::Close) => return None,
+                Some(proof::IssueAction::Duplicate(of)) => {
+                    // a cycle of duplicates stops where it started
+                    if !duplicates.insert(issue_id.clone()) {
+                        return Some((issue_id, severity));
+                    }
+                    issue_id = of.clone();
+                }
+                Some(proof::IssueAction::Severity(severity)) => return Some((issue_id, *severity)),
+            }
+        }
     }
 
     pub fn all_known_ids(&self) -> BTreeSet<Id> {
@@ -1017,6 +1726,45 @@ impl ProofDB {
             .flat_map(|map| map.iter().map(|(id, trust_level)| (id, trust_level.value)))
     }
 
+    /// Current trust proofs that override (ignore) trust proofs of `id`
+    pub fn get_trust_proofs_overriding<'s>(
+        &'s self,
+        id: &Id,
+    ) -> impl Iterator<Item = &'s proof::Trust> + 's {
+        self.override_signatures_by_overridden_id
+            .get(id)
+            .into_iter()
+            .flatten()
+            .filter_map(move |signature| {
+                let trust = self.trust_proofs_by_signature.get(signature)?;
+                let is_current = trust.ids.iter().any(|to| {
+                    self.ids_to_trust_proof_signatures
+                        .get(&(trust.from().id.clone(), to.id.clone()))
+                        .is_some_and(|current| &current.value == signature)
+                });
+                is_current.then_some(trust)
+            })
+    }
+
+    /// Current package reviews that override (ignore) package reviews of `id`
+    pub fn get_package_reviews_overriding<'s>(
+        &'s self,
+        id: &Id,
+    ) -> impl Iterator<Item = &'s review::Package> + 's {
+        self.override_signatures_by_overridden_id
+            .get(id)
+            .into_iter()
+            .flatten()
+            .filter_map(move |signature| {
+                let review = self.package_review_by_signature.get(signature)?;
+                let is_current = self
+                    .package_review_signatures_by_pkg_review_id
+                    .get(&PkgVersionReviewId::from(review))
+                    .is_some_and(|current| &current.value == signature);
+                is_current.then_some(review)
+            })
+    }
+
     /// Get all Ids that authored a proof (with total count)
     pub fn all_author_ids(&self) -> BTreeMap<Id, usize> {
         let mut res = BTreeMap::new();
@@ -1031,6 +1779,26 @@ impl ProofDB {
         res
     }
 
+    /// Where the proof with `signature` has been loaded from first
+    pub fn get_proof_origin(&self, signature: &str) -> Option<&ProofOrigin> {
+        self.origin_by_signature.get(signature)
+    }
+
+    /// Where the (current) `review` has been loaded from
+    pub fn get_pkg_review_origin(&self, review: &review::Package) -> Option<&ProofOrigin> {
+        self.get_proof_origin(self.get_pkg_review_signature(review)?)
+    }
+
+    /// Signature of the (current) `review`
+    pub fn get_pkg_review_signature(&self, review: &review::Package) -> Option<&str> {
+        Some(
+            &self
+                .package_review_signatures_by_pkg_review_id
+                .get(&PkgVersionReviewId::from(review))?
+                .value,
+        )
+    }
+
     pub fn get_package_review_by_signature<'a>(
         &'a self,
         signature: &str,
@@ -1099,12 +1867,21 @@ impl ProofDB {
         }
     }
 
-    fn add_proof(&mut self, proof: &proof::Proof, fetched_from: FetchSource) -> Result<()> {
-        proof
-            .verify()
-            .expect("All proofs were supposed to be valid here");
-        match proof.kind() {
-            proof::CodeReview::KIND => self.add_code_review(&proof.parse_content()?, &fetched_from),
+    /// Returns `false` if a more recent proof takes precedence over it
+    fn add_proof(
+        &mut self,
+        proof: &proof::Proof,
+        fetched_from: FetchSource,
+        verify_signature: bool,
+    ) -> Result<bool> {
+        if verify_signature {
+            proof.verify().map_err(Error::InvalidSignature)?;
+        }
+        Ok(match proof.kind() {
+            proof::CodeReview::KIND => {
+                self.add_code_review(proof.parse_content()?, proof.signature(), &fetched_from);
+                true
+            }
             proof::PackageReview::KIND => self.add_package_review(
                 proof.parse_content()?,
                 proof.signature(),
@@ -1112,29 +1889,199 @@ impl ProofDB {
                 proof::Digest(*proof.digest()),
             ),
             proof::Trust::KIND => {
-                self.add_trust(&proof.parse_content()?, proof.signature(), &fetched_from);
+                self.add_trust(&proof.parse_content()?, proof.signature(), &fetched_from)
+            }
+            proof::Epoch::KIND => {
+                self.add_epoch(proof.parse_content()?, proof.signature(), &fetched_from);
+                true
+            }
+            proof::ReviewRequest::KIND => {
+                self.add_review_request(proof.parse_content()?, &fetched_from)
+            }
+            proof::IssueUpdate::KIND => {
+                self.add_issue_update(proof.parse_content()?, &fetched_from)
+            }
+            proof::Group::KIND => self.add_group(proof.parse_content()?, &fetched_from),
+            proof::Successor::KIND => self.add_successor(&proof.parse_content()?, &fetched_from),
+            proof::Waiver::KIND => self.add_waiver(proof.parse_content()?, &fetched_from),
+            proof::Encrypted::KIND => {
+                self.add_encrypted(proof.parse_content()?, proof.signature(), &fetched_from);
+                true
             }
             other => return Err(Error::UnknownProofType(other.into())),
+        })
+    }
+
+    /// Reject proofs fetched from a URL once `max` of them were imported
+    ///
+    /// So a proof repo with a huge number of proofs can't exhaust memory.
+    /// Proofs from `unlimited_urls`, and the user's own proofs, are never rejected.
+    pub fn limit_proofs_per_url(
+        &mut self,
+        max: usize,
+        unlimited_urls: impl IntoIterator<Item = String>,
+    ) {
+        self.max_proofs_per_url = Some(max);
+        self.unlimited_urls = unlimited_urls.into_iter().collect();
+    }
+
+    /// The URL `source` counts against the proof limit of, if it's limited
+    fn limited_url<'a>(&self, source: &'a FetchSource) -> Option<&'a str> {
+        self.max_proofs_per_url?;
+        match source {
+            FetchSource::Url(url) if !self.unlimited_urls.contains(&url.url) => {
+                Some(url.url.as_str())
+            }
+            _ => None,
         }
+    }
 
-        Ok(())
+    /// Import proofs one by one; ones that can't be imported are skipped,
+    /// and counted in the report
+    pub fn import_from_iter(
+        &mut self,
+        i: impl Iterator<Item = (proof::Proof, FetchSource)>,
+    ) -> ImportReport {
+        self.import_from_iter_with_origin(i.map(|(proof, source)| (proof, source.into())))
+    }
+
+    /// Like `import_from_iter`, keeping track of the files proofs were read from
+    pub fn import_from_iter_with_origin(
+        &mut self,
+        i: impl Iterator<Item = (proof::Proof, ProofOrigin)>,
+    ) -> ImportReport {
+        self.import_with_origin(i, true)
     }
 
-    pub fn import_from_iter(&mut self, i: impl Iterator<Item = (proof::Proof, FetchSource)>) {
-        for (proof, fetch_source) in i {
-            // ignore errors
-            if let Err(e) = self.add_proof(&proof, fetch_source) {
-                debug!("Ignoring proof: {}", e);
+    /// Like `import_from_iter_with_origin`, for proofs with signatures checked already
+    ///
+    /// Checking signatures is most of the work of importing, and it's wasted
+    /// on proofs that were just read by code that checked them.
+    pub fn import_verified_from_iter_with_origin(
+        &mut self,
+        i: impl Iterator<Item = (proof::Proof, ProofOrigin)>,
+    ) -> ImportReport {
+        self.import_with_origin(i, false)
+    }
+
+    fn import_with_origin(
+        &mut self,
+        i: impl Iterator<Item = (proof::Proof, ProofOrigin)>,
+        verify_signatures: bool,
+    ) -> ImportReport {
+        let mut report = ImportReport::default();
+        let mut urls_over_limit = HashSet::new();
+        for (proof, origin) in i {
+            let limited_url = self.limited_url(&origin.fetched_from).map(str::to_owned);
+            if let (Some(url), Some(max)) = (&limited_url, self.max_proofs_per_url) {
+                let count = self.proof_count_by_url.get(url).copied().unwrap_or(0);
+                if max <= count && !self.imported_signatures.contains(proof.signature()) {
+                    if urls_over_limit.insert(url.clone()) {
+                        warn!("{url} has more than {max} proofs; ignoring the rest");
+                    }
+                    *report
+                        .rejected
+                        .entry(RejectionReason::OverLimit)
+                        .or_default() += 1;
+                    continue;
+                }
+            }
+            match self.add_proof(&proof, origin.fetched_from.clone(), verify_signatures) {
+                Ok(is_current) => {
+                    if !self
+                        .imported_signatures
+                        .insert(proof.signature().to_owned())
+                    {
+                        report.duplicates += 1;
+                        continue;
+                    }
+                    if let Some(url) = limited_url {
+                        *self.proof_count_by_url.entry(url).or_default() += 1;
+                    }
+                    self.origin_by_signature
+                        .insert(proof.signature().to_owned(), origin);
+                    if is_current {
+                        report.accepted += 1;
+                    } else {
+                        report.overridden += 1;
+                    }
+                }
+                Err(e) => {
+                    debug!("Ignoring proof: {}", e);
+                    *report.rejected.entry(e.rejection_reason()).or_default() += 1;
+                }
             }
         }
+        report
     }
 
+    /// Trust of `id` for others, including the members of groups it trusts
+    ///
+    /// A trust proof for a member directly takes precedence over trust for
+    /// its groups; between groups, the most recent trust proof does.
     fn get_trust_details_list_of_id(&self, id: &Id) -> impl Iterator<Item = (&TrustDetails, &Id)> {
-        self.trust_id_to_id
-            .get(id)
+        let direct = self.trust_id_to_id.get(id);
+        let mut via_groups: HashMap<&Id, &TimestampedTrustDetails> = HashMap::new();
+        for (key, trust) in self.trust_id_to_group.get(id).into_iter().flatten() {
+            for member in self
+                .groups
+                .get(key)
+                .into_iter()
+                .flat_map(|group| &group.ids)
+            {
+                if member.id == *id || direct.is_some_and(|direct| direct.contains_key(&member.id))
+                {
+                    continue;
+                }
+                via_groups
+                    .entry(&member.id)
+                    .and_modify(|current| {
+                        if current.date < trust.date {
+                            *current = trust;
+                        }
+                    })
+                    .or_insert(trust);
+            }
+        }
+        // trust for an Id counts for its successors, unless they are trusted directly;
+        // the lowest level wins, so distrust of a predecessor is never lost
+        let mut via_successors: HashMap<&Id, &TrustDetails> = HashMap::new();
+        let edges = direct
+            .into_iter()
+            .flatten()
+            .map(|(to, trust)| (to, &trust.value))
+            .chain(via_groups.iter().map(|(to, trust)| (*to, &trust.value)));
+        for (to, trust) in edges {
+            for successor in self.get_successors_of(to) {
+                if successor == id
+                    || direct.is_some_and(|direct| direct.contains_key(successor))
+                    || via_groups.contains_key(successor)
+                {
+                    continue;
+                }
+                via_successors
+                    .entry(successor)
+                    .and_modify(|current| {
+                        if trust.level < current.level {
+                            *current = trust;
+                        }
+                    })
+                    .or_insert(trust);
+            }
+        }
+        direct
             .map(|map| map.iter().map(|(id, trust)| (&trust.value, id)))
             .into_iter()
             .flatten()
+            .chain(via_groups.into_iter().map(|(id, trust)| (&trust.value, id)))
+            .chain(via_successors.into_iter().map(|(id, trust)| (trust, id)))
+    }
+
+    /// Number of packages (versions) `id` has a current review of
+    pub fn get_package_review_count_by_author(&self, id: &Id) -> usize {
+        self.from_id_to_package_reviews
+            .get(id)
+            .map_or(0, HashSet::len)
     }
 
     /// Only for direct relationship. See `calculate_trust_set`.
@@ -1144,7 +2091,8 @@ impl ProofDB {
             .and_then(|sig| self.trust_proofs_by_signature.get(&sig.value))
     }
 
-    fn get_package_reviews_by_author<'iter, 's: 'iter, 'id: 'iter>(
+    /// Current package reviews by `id`, in no particular order
+    pub fn get_package_reviews_by_author<'iter, 's: 'iter, 'id: 'iter>(
         &'s self,
         id: &'id Id,
     ) -> impl Iterator<Item = &'s review::Package> + 'iter {
@@ -1169,6 +2117,115 @@ impl ProofDB {
         TrustSet::from(self, for_id, params)
     }
 
+    /// Like `calculate_trust_set`, but as if `from` had no trust proof for `to`
+    pub fn calculate_trust_set_without_edge(
+        &self,
+        for_id: &Id,
+        params: &TrustDistanceParams,
+        from: &Id,
+        to: &Id,
+    ) -> TrustSet {
+        TrustSet::from_skipping(self, for_id, params, Skipped::Edge(from, to))
+    }
+
+    /// Like `calculate_trust_set`, but as if nobody trusted `id`
+    ///
+    /// Ids trusted only through `id` aren't trusted either.
+    pub fn calculate_trust_set_without_id(
+        &self,
+        for_id: &Id,
+        params: &TrustDistanceParams,
+        id: &Id,
+    ) -> TrustSet {
+        TrustSet::from_skipping(self, for_id, params, Skipped::Id(id))
+    }
+
+    /// Only for direct relationship. See `calculate_trust_set`.
+    pub fn get_direct_trust_of<'s>(
+        &'s self,
+        id: &Id,
+    ) -> impl Iterator<Item = (&'s Id, TrustLevel)> + 's {
+        self.trust_id_to_id
+            .get(id)
+            .into_iter()
+            .flat_map(|map| map.iter().map(|(id, trust)| (id, trust.value.level)))
+    }
+
+    /// Trust proofs of all Ids reachable from `root` by positive trust
+    ///
+    /// Unlike `calculate_trust_set`, there's no distance limit. Edges to
+    /// distrusted Ids (or trusted at level `none`) are included, but not
+    /// followed. Ordered by `from`, then `to`.
+    pub fn get_trust_graph_from(&self, root: &Id) -> Vec<TrustEdge> {
+        let mut visited = BTreeSet::from([root.clone()]);
+        let mut pending = vec![root.clone()];
+        let mut edges = vec![];
+        while let Some(from) = pending.pop() {
+            for (to, trust) in self.trust_id_to_id.get(&from).into_iter().flatten() {
+                let level = trust.value.level;
+                if level > TrustLevel::None && visited.insert(to.clone()) {
+                    pending.push(to.clone());
+                }
+                edges.push(TrustEdge {
+                    from: from.clone(),
+                    to: to.clone(),
+                    level,
+                    date: trust.date,
+                });
+            }
+        }
+        edges.sort_by(|a, b| (&a.from, &a.to).cmp(&(&b.from, &b.to)));
+        edges
+    }
+
+    /// Date of the most recent trust proof or package review by `id`
+    pub fn get_latest_proof_date_of(&self, id: &Id) -> Option<DateTime<Utc>> {
+        let trust_dates = self
+            .trust_id_to_id
+            .get(id)
+            .into_iter()
+            .flat_map(|map| map.values().map(|trust| trust.date));
+        let review_dates = self
+            .get_package_reviews_by_author(id)
+            .map(|review| review.date_utc());
+        trust_dates.chain(review_dates).max()
+    }
+
+    /// Positive trust proofs of `for_id` that could be retracted: to Ids with no
+    /// proofs since `stale_before`, or that don't change the effective trust set at all
+    pub fn suggest_trust_prune(
+        &self,
+        for_id: &Id,
+        params: &TrustDistanceParams,
+        stale_before: DateTime<Utc>,
+    ) -> Vec<PruneSuggestion> {
+        let trust_set = self.calculate_trust_set(for_id, params);
+        let mut suggestions = vec![];
+        for (id, trust_level) in self.get_direct_trust_of(for_id) {
+            if trust_level <= TrustLevel::None || id == for_id {
+                continue;
+            }
+            let latest_proof = self.get_latest_proof_date_of(id);
+            if latest_proof.map_or(true, |date| date < stale_before) {
+                suggestions.push(PruneSuggestion::Stale {
+                    id: id.clone(),
+                    trust_level,
+                    latest_proof,
+                });
+            } else if self
+                .calculate_trust_set_without_edge(for_id, params, for_id, id)
+                .has_same_effective_trust(&trust_set)
+            {
+                suggestions.push(PruneSuggestion::Redundant {
+                    id: id.clone(),
+                    trust_level,
+                });
+            }
+        }
+        suggestions.sort_by(|a, b| a.id().cmp(b.id()));
+        suggestions
+    }
+
     /// Finds which URL is the latest and claimed to belong to the given Id.
     /// The result indicates how reliable information this is.
     pub fn lookup_url(&self, id: &Id) -> UrlOfId<'_> {
@@ -1233,6 +2290,25 @@ impl<'a> UrlOfId<'a> {
     }
 }
 
+/// How distrust reported by Ids in the WoT takes effect
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DistrustPolicy {
+    /// Distrust reported by Ids with a lower effective trust level is ignored
+    pub min_reporter_trust: TrustLevel,
+    /// Number of different Ids that need to report distrust before it takes effect
+    pub quorum: usize,
+}
+
+impl Default for DistrustPolicy {
+    /// Any Id in the WoT can distrust anyone on its own
+    fn default() -> Self {
+        Self {
+            min_reporter_trust: TrustLevel::None,
+            quorum: 1,
+        }
+    }
+}
+
 pub struct TrustDistanceParams {
     pub max_distance: u64,
     pub high_trust_distance: u64,
@@ -1240,6 +2316,7 @@ pub struct TrustDistanceParams {
     pub low_trust_distance: u64,
     pub none_trust_distance: u64,
     pub distrust_distance: u64,
+    pub distrust_policy: DistrustPolicy,
 }
 
 impl TrustDistanceParams {
@@ -1252,6 +2329,7 @@ impl TrustDistanceParams {
             low_trust_distance: 1,
             none_trust_distance: 1,
             distrust_distance: 1,
+            distrust_policy: DistrustPolicy::default(),
         }
     }
 
@@ -1276,6 +2354,22 @@ impl Default for TrustDistanceParams {
             low_trust_distance: 5,
             none_trust_distance: 11,
             distrust_distance: 11,
+            distrust_policy: DistrustPolicy::default(),
+        }
+    }
+}
+
+impl From<&TrustDistanceParams> for proof::TrustSnapshotParams {
+    fn from(params: &TrustDistanceParams) -> Self {
+        Self {
+            max_distance: params.max_distance,
+            high_trust_distance: params.high_trust_distance,
+            medium_trust_distance: params.medium_trust_distance,
+            low_trust_distance: params.low_trust_distance,
+            none_trust_distance: params.none_trust_distance,
+            distrust_distance: params.distrust_distance,
+            distrust_min_reporter_trust: params.distrust_policy.min_reporter_trust,
+            distrust_quorum: params.distrust_policy.quorum as u64,
         }
     }
 }
@@ -1299,6 +2393,13 @@ impl OverrideSourcesDetails {
     }
 }
 
+/// Lowercased words of `text`, for `ProofDB::find_by_comment`
+fn comment_words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+}
+
 #[test]
 fn db_is_send_sync() {
     fn is<T: Send + Sync>() {}
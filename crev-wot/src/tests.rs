@@ -6,7 +6,9 @@ use crev_data::{
 use default::default;
 use std::sync::Arc;
 
+mod groups;
 mod issues;
+mod successors;
 
 fn trust_proof(from: &UnlockedId, to: &UnlockedId, level: TrustLevel) -> Result<proof::Proof> {
     Ok(from.create_signed_trust_proof(vec![to.as_public_id()], level, vec![])?)
@@ -61,6 +63,7 @@ fn proofdb_distance() -> Result<()> {
         none_trust_distance: 112,
         distrust_distance: 112,
         max_distance: 111,
+        distrust_policy: default(),
     };
     let mut trustdb = ProofDB::new();
 
@@ -113,6 +116,7 @@ fn overwriting_reviews() -> Result<()> {
         digest_type: crev_data::proof::default_digest_type(),
         revision: String::new(),
         revision_type: crev_data::proof::default_revision_type(),
+        digest_ignore: None,
     };
 
     let proof1 = a
@@ -185,6 +189,7 @@ fn proofdb_distrust() -> Result<()> {
         none_trust_distance: 10001,
         distrust_distance: 10001,
         max_distance: 10000,
+        distrust_policy: default(),
     };
     let mut trustdb = ProofDB::new();
 
@@ -220,6 +225,103 @@ fn proofdb_distrust() -> Result<()> {
     Ok(())
 }
 
+// Distrust from Ids with effective trust below `min_reporter_trust`
+// has no effect.
+#[test]
+fn proofdb_distrust_policy_min_reporter_trust() -> Result<()> {
+    let url = FetchSource::Url(Arc::new(Url::new_git("https://a")));
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let c = UnlockedId::generate_for_git_url("https://c");
+    let d = UnlockedId::generate_for_git_url("https://d");
+
+    let mut distance_params = TrustDistanceParams {
+        high_trust_distance: 1,
+        medium_trust_distance: 10,
+        low_trust_distance: 100,
+        none_trust_distance: 10001,
+        distrust_distance: 10001,
+        max_distance: 10000,
+        distrust_policy: DistrustPolicy {
+            min_reporter_trust: TrustLevel::Medium,
+            quorum: 1,
+        },
+    };
+    let mut trustdb = ProofDB::new();
+
+    trustdb.import_from_iter(
+        vec![
+            trust_high(&a, &b)?,
+            trust_high(&a, &c)?,
+            trust_low(&b, &d)?,
+            trust_distrust(&d, &c)?,
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+
+    let trust_set = trustdb.calculate_trust_set(a.as_ref(), &distance_params);
+    assert_eq!(
+        trust_set.get_trusted_ids_refs(),
+        collection![a.as_ref(), b.as_ref(), c.as_ref(), d.as_ref()]
+    );
+
+    distance_params.distrust_policy.min_reporter_trust = TrustLevel::Low;
+    let trust_set = trustdb.calculate_trust_set(a.as_ref(), &distance_params);
+    assert_eq!(
+        trust_set.get_trusted_ids_refs(),
+        collection![a.as_ref(), b.as_ref(), d.as_ref()]
+    );
+    Ok(())
+}
+
+// With a quorum, a single distrust is only pending,
+// and takes effect once enough Ids report it.
+#[test]
+fn proofdb_distrust_policy_quorum() -> Result<()> {
+    let url = FetchSource::Url(Arc::new(Url::new_git("https://a")));
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let c = UnlockedId::generate_for_git_url("https://c");
+    let d = UnlockedId::generate_for_git_url("https://d");
+
+    let distance_params = TrustDistanceParams {
+        high_trust_distance: 1,
+        medium_trust_distance: 10,
+        low_trust_distance: 100,
+        none_trust_distance: 10001,
+        distrust_distance: 10001,
+        max_distance: 10000,
+        distrust_policy: DistrustPolicy {
+            min_reporter_trust: TrustLevel::None,
+            quorum: 2,
+        },
+    };
+    let mut trustdb = ProofDB::new();
+
+    trustdb.import_from_iter(
+        vec![
+            trust_high(&a, &b)?,
+            trust_high(&a, &c)?,
+            trust_medium(&a, &d)?,
+            trust_distrust(&b, &d)?,
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+
+    let trust_set = trustdb.calculate_trust_set(a.as_ref(), &distance_params);
+    assert!(trust_set.is_trusted(d.as_ref()));
+    assert!(trust_set.distrust_pending.contains_key(&d.id.id));
+
+    trustdb.import_from_iter(vec![(trust_distrust(&c, &d)?, url)].into_iter());
+
+    let trust_set = trustdb.calculate_trust_set(a.as_ref(), &distance_params);
+    assert!(!trust_set.is_trusted(d.as_ref()));
+    assert_eq!(trust_set.distrusted[&d.id.id].reported_by.len(), 2);
+    Ok(())
+}
+
 #[test]
 fn proofdb_trust_ignore_override() -> Result<()> {
     let url = FetchSource::Url(Arc::new(Url::new_git("https://a")));
@@ -235,6 +337,7 @@ fn proofdb_trust_ignore_override() -> Result<()> {
         none_trust_distance: 10001,
         distrust_distance: 10001,
         max_distance: 10000,
+        distrust_policy: default(),
     };
 
     let mut trustdb = ProofDB::new();
@@ -307,3 +410,555 @@ fn proofdb_trust_ignore_override() -> Result<()> {
     }
     Ok(())
 }
+
+// Overrides are indexed by the overridden Id, and only the
+// current (most recent) proof of the overriding author counts
+#[test]
+fn proofdb_overrides_mentioning_id() -> Result<()> {
+    let url = FetchSource::Url(Arc::new(Url::new_git("https://example.com")));
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let c = UnlockedId::generate_for_git_url("https://c");
+    let d = UnlockedId::generate_for_git_url("https://d");
+
+    let with_override = {
+        let mut c_to_d_unsigned =
+            c.id.create_trust_proof(vec![d.as_public_id()], TrustLevel::None, vec![])?;
+        c_to_d_unsigned.override_.push(OverrideItem {
+            id: b.as_public_id().clone(),
+            comment: "ignore".into(),
+        });
+        c_to_d_unsigned.sign_by(&c)?
+    };
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![with_override.clone()]
+            .into_iter()
+            .map(|x| (x, url.clone())),
+    );
+    assert_eq!(trustdb.get_trust_proofs_overriding(&b.id.id).count(), 1);
+    assert_eq!(trustdb.get_trust_proofs_overriding(&c.id.id).count(), 0);
+
+    #[allow(deprecated)]
+    std::thread::sleep_ms(1);
+    let without_override = trust_low(&c, &d)?;
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![with_override, without_override]
+            .into_iter()
+            .map(|x| (x, url.clone())),
+    );
+    assert_eq!(trustdb.get_trust_proofs_overriding(&b.id.id).count(), 0);
+
+    Ok(())
+}
+
+// Epoch proofs are kept per author, oldest first, without duplicates
+#[test]
+fn proofdb_epochs() -> Result<()> {
+    let url = FetchSource::Url(Arc::new(Url::new_git("https://example.com")));
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+
+    let epoch = |archive: &str| -> Result<proof::Proof> {
+        let digest = proof::archive_digest([]);
+        Ok(a.id
+            .create_epoch_proof(Url::new_git(archive), &digest)?
+            .sign_by(&a)?)
+    };
+    let old = epoch("https://a-archive-1")?;
+    #[allow(deprecated)]
+    std::thread::sleep_ms(1);
+    let new = epoch("https://a-archive-2")?;
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![new.clone(), old, new]
+            .into_iter()
+            .map(|x| (x, url.clone())),
+    );
+    let archives: Vec<_> = trustdb
+        .get_epochs(&a.id.id)
+        .into_iter()
+        .map(|e| e.archive.url.as_str())
+        .collect();
+    assert_eq!(archives, ["https://a-archive-1", "https://a-archive-2"]);
+    assert!(trustdb.get_epochs(&b.id.id).is_empty());
+    assert_eq!(trustdb.all_epochs().count(), 2);
+
+    Ok(())
+}
+
+// Direct trust that is implied by other paths, or goes to Ids with no
+// recent proofs, is suggested for retraction
+#[test]
+fn proofdb_suggest_trust_prune() -> Result<()> {
+    let url = FetchSource::Url(Arc::new(Url::new_git("https://example.com")));
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let c = UnlockedId::generate_for_git_url("https://c");
+    let d = UnlockedId::generate_for_git_url("https://d");
+    let e = UnlockedId::generate_for_git_url("https://e");
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            trust_high(&a, &b)?,
+            trust_high(&b, &c)?,
+            trust_low(&a, &c)?,
+            trust_low(&c, &d)?,
+            trust_low(&a, &e)?,
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+
+    let stale_before = crev_common::now().with_timezone(&Utc) - chrono::Duration::days(365);
+    let suggestions =
+        trustdb.suggest_trust_prune(a.as_ref(), &TrustDistanceParams::default(), stale_before);
+    let mut expected = vec![
+        PruneSuggestion::Redundant {
+            id: c.id.id.clone(),
+            trust_level: TrustLevel::Low,
+        },
+        PruneSuggestion::Stale {
+            id: e.id.id.clone(),
+            trust_level: TrustLevel::Low,
+            latest_proof: None,
+        },
+    ];
+    expected.sort_by(|a, b| a.id().cmp(b.id()));
+    assert_eq!(suggestions, expected);
+
+    Ok(())
+}
+
+// Removing an Id from the web of trust also removes Ids trusted only through it
+#[test]
+fn proofdb_trust_set_without_id() -> Result<()> {
+    let url = FetchSource::Url(Arc::new(Url::new_git("https://example.com")));
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let c = UnlockedId::generate_for_git_url("https://c");
+    let d = UnlockedId::generate_for_git_url("https://d");
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            trust_high(&a, &b)?,
+            trust_high(&a, &c)?,
+            trust_high(&b, &d)?,
+            trust_high(&c, &d)?,
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+    let params = TrustDistanceParams::default();
+
+    let trust_set = trustdb.calculate_trust_set_without_id(a.as_ref(), &params, b.as_ref());
+    assert_eq!(
+        trust_set.get_trusted_ids_refs(),
+        collection![a.as_ref(), c.as_ref(), d.as_ref()]
+    );
+
+    trustdb.import_from_iter(vec![(trust_low(&c, &b)?, url)].into_iter());
+    let trust_set = trustdb.calculate_trust_set_without_id(a.as_ref(), &params, c.as_ref());
+    assert_eq!(
+        trust_set.get_trusted_ids_refs(),
+        collection![a.as_ref(), b.as_ref(), d.as_ref()]
+    );
+
+    Ok(())
+}
+
+// The trust graph follows positive trust only, without a distance limit
+#[test]
+fn proofdb_trust_graph() -> Result<()> {
+    let url = FetchSource::Url(Arc::new(Url::new_git("https://example.com")));
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let c = UnlockedId::generate_for_git_url("https://c");
+    let d = UnlockedId::generate_for_git_url("https://d");
+    let e = UnlockedId::generate_for_git_url("https://e");
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            trust_low(&a, &b)?,
+            trust_low(&b, &c)?,
+            trust_low(&c, &a)?,
+            trust_distrust(&c, &d)?,
+            trust_high(&d, &e)?,
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+
+    let edges: BTreeSet<_> = trustdb
+        .get_trust_graph_from(a.as_ref())
+        .into_iter()
+        .map(|edge| (edge.from, edge.to, edge.level))
+        .collect();
+    let edge =
+        |from: &UnlockedId, to: &UnlockedId, level| (from.id.id.clone(), to.id.id.clone(), level);
+    assert_eq!(
+        edges,
+        BTreeSet::from([
+            edge(&a, &b, TrustLevel::Low),
+            edge(&b, &c, TrustLevel::Low),
+            edge(&c, &a, TrustLevel::Low),
+            edge(&c, &d, TrustLevel::Distrust),
+        ])
+    );
+    Ok(())
+}
+
+// Importing reports new proofs, proofs seen before, and proofs
+// that a more recent one of the same author takes precedence over
+#[test]
+fn proofdb_import_report() -> Result<()> {
+    let url = FetchSource::Url(Arc::new(Url::new_git("https://example.com")));
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+
+    let old = trust_low(&a, &b)?;
+    #[allow(deprecated)]
+    std::thread::sleep_ms(1);
+    let new = trust_high(&a, &b)?;
+
+    let mut trustdb = ProofDB::new();
+    let report = trustdb.import_from_iter(
+        vec![new.clone(), new, old]
+            .into_iter()
+            .map(|x| (x, url.clone())),
+    );
+    assert_eq!(
+        report,
+        ImportReport {
+            accepted: 1,
+            duplicates: 1,
+            overridden: 1,
+            rejected: default(),
+        }
+    );
+    assert_eq!(
+        report.to_string(),
+        "1 accepted, 1 duplicates, 1 overridden, 0 rejected"
+    );
+
+    Ok(())
+}
+
+// Trust sets with the same effective trust have the same digest, no matter
+// the trust path; a changed trust level changes it
+#[test]
+fn trust_set_stable_digest() -> Result<()> {
+    let url = FetchSource::Url(Arc::new(Url::new_git("https://example.com")));
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let c = UnlockedId::generate_for_git_url("https://c");
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![trust_high(&a, &b)?, trust_low(&b, &c)?]
+            .into_iter()
+            .map(|x| (x, url.clone())),
+    );
+    let params = TrustDistanceParams::default();
+    let through_b = trustdb.calculate_trust_set(a.as_ref(), &params);
+
+    trustdb.import_from_iter(vec![(trust_low(&a, &c)?, url.clone())].into_iter());
+    let direct = trustdb.calculate_trust_set(a.as_ref(), &params);
+    assert_eq!(through_b.stable_digest(), direct.stable_digest());
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![trust_high(&a, &b)?, trust_medium(&b, &c)?]
+            .into_iter()
+            .map(|x| (x, url.clone())),
+    );
+    let medium = trustdb.calculate_trust_set(a.as_ref(), &params);
+    assert_ne!(through_b.stable_digest(), medium.stable_digest());
+
+    Ok(())
+}
+
+// Only the most recent review request of each requester is kept per package version
+#[test]
+fn proofdb_review_requests() -> Result<()> {
+    let url = FetchSource::Url(Arc::new(Url::new_git("https://example.com")));
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let package = |version: &str| {
+        proof::PackageVersionId::new(
+            "source".into(),
+            "name".into(),
+            Version::parse(version).unwrap(),
+        )
+    };
+    let request = |id: &UnlockedId, version: &str, comment: &str| -> Result<proof::Proof> {
+        Ok(id
+            .id
+            .create_review_request_proof(package(version), comment.into())?
+            .sign_by(id)?)
+    };
+
+    let old = request(&a, "1.0.0", "old")?;
+    #[allow(deprecated)]
+    std::thread::sleep_ms(1);
+    let new = request(&a, "1.0.0", "new")?;
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            new,
+            old,
+            request(&b, "1.0.0", "")?,
+            request(&a, "2.0.0", "")?,
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+    let mut comments: Vec<_> = trustdb
+        .get_review_requests_for_package_version(&package("1.0.0"))
+        .map(|r| r.comment.as_str())
+        .collect();
+    comments.sort_unstable();
+    assert_eq!(comments, ["", "new"]);
+    assert_eq!(trustdb.all_review_requests().count(), 3);
+
+    Ok(())
+}
+
+// A more recent waiver of the same author replaces the previous one, even if it expires sooner
+#[test]
+fn proofdb_waivers() -> Result<()> {
+    let url = FetchSource::Url(Arc::new(Url::new_git("https://example.com")));
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let package = proof::PackageVersionId::new(
+        "source".into(),
+        "name".into(),
+        Version::parse("1.0.0").unwrap(),
+    );
+    let date = |s: &str| chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+    let waiver = |id: &UnlockedId, expires: &str| -> Result<proof::Proof> {
+        Ok(id
+            .id
+            .create_waiver_proof(package.clone(), date(expires), "pending review".into())?
+            .sign_by(id)?)
+    };
+
+    let old = waiver(&a, "2025-06-01")?;
+    #[allow(deprecated)]
+    std::thread::sleep_ms(1);
+    let new = waiver(&a, "2025-03-01")?;
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![new, old, waiver(&b, "2025-06-01")?]
+            .into_iter()
+            .map(|x| (x, url.clone())),
+    );
+    let mut expires: Vec<_> = trustdb
+        .get_waivers_for_package_version(&package)
+        .map(|w| w.expires)
+        .collect();
+    expires.sort_unstable();
+    assert_eq!(expires, [date("2025-03-01"), date("2025-06-01")]);
+
+    let expired: Vec<_> = trustdb
+        .all_waivers()
+        .filter(|w| w.is_expired(date("2025-04-01")))
+        .map(|w| &w.common.from.id)
+        .collect();
+    assert_eq!(expired, [&a.id.id]);
+
+    Ok(())
+}
+
+// A signed snapshot of a trust set gives back the same effective trust
+#[test]
+fn trust_set_snapshot_roundtrip() -> Result<()> {
+    let url = FetchSource::Url(Arc::new(Url::new_git("https://example.com")));
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let c = UnlockedId::generate_for_git_url("https://c");
+    let d = UnlockedId::generate_for_git_url("https://d");
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            trust_high(&a, &b)?,
+            trust_low(&b, &c)?,
+            trust_distrust(&a, &d)?,
+            {
+                let mut a_to_c_unsigned =
+                    a.id.create_trust_proof(vec![c.as_public_id()], TrustLevel::Low, vec![])?;
+                a_to_c_unsigned.override_.push(OverrideItem {
+                    id: b.as_public_id().clone(),
+                    comment: String::new(),
+                });
+                a_to_c_unsigned.sign_by(&a)?
+            },
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+    let params = TrustDistanceParams::default();
+    let trust_set = trustdb.calculate_trust_set(a.as_ref(), &params);
+    assert_eq!(trust_set.trust_ignore_overrides.len(), 1);
+
+    let (trusted, distrusted) = trust_set.snapshot_entries();
+    let mut snapshot =
+        a.id.create_trust_snapshot((&params).into(), trusted, distrusted)?;
+    (snapshot.ignored_trust, snapshot.ignored_reviews) = trust_set.snapshot_overrides();
+    let proof = snapshot.sign_by(&a)?;
+    let parsed = proof::Proof::parse_from(proof.to_string().as_bytes())?;
+    parsed[0].verify()?;
+    let snapshot: proof::TrustSnapshot = parsed[0].parse_content()?;
+
+    let from_snapshot = TrustSet::from_snapshot(&snapshot);
+    assert!(from_snapshot.has_same_effective_trust(&trust_set));
+    assert_eq!(from_snapshot.stable_digest(), trust_set.stable_digest());
+    assert!(from_snapshot.is_distrusted(&d.id.id));
+    assert!(from_snapshot
+        .trust_ignore_overrides
+        .contains_key(&(b.id.id.clone(), c.id.id.clone())));
+    assert_eq!(
+        from_snapshot.get_effective_trust_level(&c.id.id),
+        TrustLevel::Low
+    );
+
+    Ok(())
+}
+
+// Proofs remember where they were loaded from first
+#[test]
+fn proof_origin() -> Result<()> {
+    let url = FetchSource::Url(Arc::new(Url::new_git("https://example.com")));
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let proof = trust_high(&a, &b)?;
+
+    let mut trustdb = ProofDB::new();
+    let origin = ProofOrigin {
+        fetched_from: FetchSource::LocalUser,
+        file: Some("trust/2024-01.proof.crev".into()),
+    };
+    trustdb.import_from_iter_with_origin(vec![(proof.clone(), origin)].into_iter());
+    let report = trustdb.import_from_iter(vec![(proof.clone(), url)].into_iter());
+    assert_eq!(report.duplicates, 1);
+
+    let origin = trustdb
+        .get_proof_origin(proof.signature())
+        .expect("imported");
+    assert!(matches!(origin.fetched_from, FetchSource::LocalUser));
+    assert_eq!(
+        origin.to_string(),
+        "own proof repo (trust/2024-01.proof.crev)"
+    );
+    assert!(trustdb.get_proof_origin("unknown").is_none());
+
+    Ok(())
+}
+
+// Proofs can be found by words of their comments, in any case
+#[test]
+fn find_by_comment() -> Result<()> {
+    let url = FetchSource::Url(Arc::new(Url::new_git("https://a")));
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let package = crev_data::proof::PackageInfo {
+        id: proof::PackageVersionId::new(
+            "source".into(),
+            "name".into(),
+            Version::parse("1.0.0").unwrap(),
+        ),
+        digest: vec![0; 32],
+        digest_type: crev_data::proof::default_digest_type(),
+        revision: String::new(),
+        revision_type: crev_data::proof::default_revision_type(),
+        digest_ignore: None,
+    };
+    let review = a
+        .as_public_id()
+        .create_package_review_proof(
+            package,
+            default(),
+            vec![],
+            "Uses unsafe for SIMD, looks sound.".into(),
+        )?
+        .sign_by(&a)?;
+    let mut trust =
+        a.as_public_id()
+            .create_trust_proof(vec![b.as_public_id()], TrustLevel::High, vec![])?;
+    trust.comment = "Knows unsafe code well".into();
+    let trust = trust.sign_by(&a)?;
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![review.clone(), trust.clone()]
+            .into_iter()
+            .map(|x| (x, url.clone())),
+    );
+
+    assert_eq!(
+        trustdb.find_by_comment("UNSAFE"),
+        HashSet::from([review.signature(), trust.signature()])
+    );
+    assert_eq!(
+        trustdb.find_by_comment("unsafe simd"),
+        HashSet::from([review.signature()])
+    );
+    assert!(trustdb.find_by_comment("uns").is_empty());
+    assert!(trustdb.find_by_comment("").is_empty());
+    let found = trustdb.get_all_pkg_reviews().next().expect("imported");
+    assert_eq!(
+        trustdb.get_pkg_review_signature(found),
+        Some(review.signature())
+    );
+
+    Ok(())
+}
+
+// Proofs from a URL over the limit are rejected, unless the URL is unlimited
+#[test]
+fn proofdb_limit_proofs_per_url() -> Result<()> {
+    let url = FetchSource::Url(Arc::new(Url::new_git("https://example.com")));
+    let big = FetchSource::Url(Arc::new(Url::new_git("https://big.example.com")));
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let c = UnlockedId::generate_for_git_url("https://c");
+    let d = UnlockedId::generate_for_git_url("https://d");
+
+    let mut trustdb = ProofDB::new();
+    trustdb.limit_proofs_per_url(2, vec!["https://big.example.com".to_owned()]);
+    let first = trust_high(&a, &b)?;
+    let report = trustdb.import_from_iter(
+        vec![first.clone(), trust_high(&a, &c)?, trust_high(&a, &d)?]
+            .into_iter()
+            .map(|x| (x, url.clone())),
+    );
+    assert_eq!(report.accepted, 2);
+    assert_eq!(report.rejected.get(&RejectionReason::OverLimit), Some(&1));
+
+    // seen before, so not over the limit
+    let report = trustdb.import_from_iter(vec![(first, url.clone())].into_iter());
+    assert_eq!(report.duplicates, 1);
+
+    let report = trustdb.import_from_iter(
+        vec![
+            trust_high(&b, &a)?,
+            trust_high(&b, &c)?,
+            trust_high(&b, &d)?,
+        ]
+        .into_iter()
+        .map(|x| (x, big.clone())),
+    );
+    assert_eq!(report.accepted, 3);
+    assert_eq!(report.rejected_count(), 0);
+
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use super::*;
+use std::collections::HashSet;
+
+fn group_proof(owner: &UnlockedId, name: &str, members: &[&UnlockedId]) -> Result<proof::Proof> {
+    Ok(owner
+        .as_public_id()
+        .create_group_proof(
+            name.into(),
+            members.iter().map(|id| id.as_public_id().clone()).collect(),
+            String::new(),
+        )?
+        .sign_by(owner)?)
+}
+
+fn trust_group(
+    from: &UnlockedId,
+    owner: &UnlockedId,
+    name: &str,
+    level: TrustLevel,
+) -> Result<proof::Proof> {
+    let mut trust = from
+        .as_public_id()
+        .create_trust_proof(vec![], level, vec![])?;
+    trust.groups.push(proof::GroupRef {
+        from: owner.as_public_id().clone(),
+        name: name.into(),
+    });
+    Ok(trust.sign_by(from)?)
+}
+
+// Trusting a group trusts its current members (but not its owner),
+// and direct trust proofs take precedence.
+#[test]
+fn group_members_are_trusted() -> Result<()> {
+    let url = FetchSource::Url(Arc::new(Url::new_git("https://a")));
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let owner = UnlockedId::generate_for_git_url("https://owner");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let c = UnlockedId::generate_for_git_url("https://c");
+    let d = UnlockedId::generate_for_git_url("https://d");
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![trust_group(&a, &owner, "team", TrustLevel::Low)?]
+            .into_iter()
+            .map(|x| (x, url.clone())),
+    );
+    let trust_set = trustdb.calculate_trust_set(a.as_ref(), &default());
+    assert_eq!(
+        trust_set.get_trusted_ids_refs(),
+        HashSet::from([a.as_ref()])
+    );
+
+    trustdb.import_from_iter(
+        vec![group_proof(&owner, "team", &[&b, &c])?]
+            .into_iter()
+            .map(|x| (x, url.clone())),
+    );
+    let trust_set = trustdb.calculate_trust_set(a.as_ref(), &default());
+    assert_eq!(
+        trust_set.get_trusted_ids_refs(),
+        HashSet::from([a.as_ref(), b.as_ref(), c.as_ref()])
+    );
+    assert_eq!(
+        trust_set.get_effective_trust_level(b.as_ref()),
+        TrustLevel::Low
+    );
+
+    // only the most recent version of the group counts
+    trustdb.import_from_iter(
+        vec![
+            group_proof(&owner, "team", &[&b, &d])?,
+            trust_proof(&a, &b, TrustLevel::None)?,
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+    let trust_set = trustdb.calculate_trust_set(a.as_ref(), &default());
+    assert_eq!(
+        trust_set.get_trusted_ids_refs(),
+        HashSet::from([a.as_ref(), d.as_ref()])
+    );
+    assert_eq!(
+        trustdb
+            .get_group(owner.as_ref(), "team")
+            .map(|g| g.ids.len()),
+        Some(2)
+    );
+
+    Ok(())
+}
@@ -3,7 +3,7 @@ use super::*;
 use crev_data::{
     proof,
     review::{Advisory, Issue, VersionRange},
-    TrustLevel, UnlockedId, Version,
+    Level, TrustLevel, UnlockedId, Version,
 };
 
 const SOURCE: &str = "SOURCE_ID";
@@ -37,6 +37,7 @@ fn build_proof_with_advisories(
         digest_type: proof::default_digest_type(),
         revision: String::new(),
         revision_type: proof::default_revision_type(),
+        digest_ignore: None,
     };
     let review = proof::review::PackageBuilder::default()
         .from(id.id.clone())
@@ -56,6 +57,7 @@ fn build_proof_with_issues(id: &UnlockedId, version: Version, issues: Vec<Issue>
         digest_type: proof::default_digest_type(),
         revision: String::new(),
         revision_type: proof::default_revision_type(),
+        digest_ignore: None,
     };
     let review = proof::review::PackageBuilder::default()
         .from(id.id.clone())
@@ -326,3 +328,84 @@ fn issues_sanity() -> Result<()> {
     );
     Ok(())
 }
+
+fn build_issue_update(
+    id: &UnlockedId,
+    issue: &str,
+    reported_in: &proof::Proof,
+    action: proof::IssueAction,
+) -> proof::Proof {
+    id.as_public_id()
+        .create_issue_update_proof(
+            proof::PackageId {
+                source: SOURCE.into(),
+                name: NAME.into(),
+            },
+            issue.into(),
+            &proof::Digest(*reported_in.digest()),
+            action,
+            String::new(),
+        )
+        .unwrap()
+        .sign_by(id)
+        .unwrap()
+}
+
+#[test]
+fn issue_updates() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let id = UnlockedId::generate_for_git_url("https://a");
+    let stranger = UnlockedId::generate_for_git_url("https://b");
+    let mut trustdb = ProofDB::new();
+    let trust_set = trustdb.calculate_trust_set(id.as_ref(), &TrustDistanceParams::new_no_wot());
+    let version = Version::parse("1.0.0").unwrap();
+    let open_issues = |trustdb: &ProofDB| {
+        trustdb.get_open_issues_for_version(SOURCE, NAME, &version, &trust_set, TrustLevel::Medium)
+    };
+
+    let report = build_proof_with_issues(
+        &id,
+        version.clone(),
+        vec![
+            build_issue("issueX"),
+            Issue::builder()
+                .id("issueY".into())
+                .severity(Level::Low)
+                .build(),
+        ],
+    );
+    trustdb.import_from_iter(vec![(report.clone(), url.clone())].into_iter());
+    assert_eq!(open_issues(&trustdb).len(), 2);
+
+    // updates of untrusted authors are ignored
+    let update = build_issue_update(&stranger, "issueX", &report, proof::IssueAction::Close);
+    trustdb.import_from_iter(vec![(update, url.clone())].into_iter());
+    assert_eq!(open_issues(&trustdb).len(), 2);
+
+    let update = build_issue_update(
+        &id,
+        "issueY",
+        &report,
+        proof::IssueAction::Severity(Level::High),
+    );
+    trustdb.import_from_iter(vec![(update, url.clone())].into_iter());
+    assert_eq!(open_issues(&trustdb)["issueY"].severity, Level::High);
+
+    let update = build_issue_update(
+        &id,
+        "issueY",
+        &report,
+        proof::IssueAction::Duplicate("issueX".into()),
+    );
+    trustdb.import_from_iter(vec![(update, url.clone())].into_iter());
+    let issues = open_issues(&trustdb);
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues["issueX"].issues.len(), 1);
+
+    // closing the target closes its duplicates too
+    let update = build_issue_update(&id, "issueX", &report, proof::IssueAction::Close);
+    trustdb.import_from_iter(vec![(update, url)].into_iter());
+    assert_eq!(open_issues(&trustdb).len(), 0);
+
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use super::*;
+use std::collections::HashSet;
+
+fn successor_proof(
+    signer: &UnlockedId,
+    predecessor: &UnlockedId,
+    successor: &UnlockedId,
+) -> Result<proof::Proof> {
+    Ok(signer
+        .as_public_id()
+        .create_successor_proof(
+            predecessor.as_public_id().clone(),
+            successor.as_public_id().clone(),
+        )?
+        .sign_by(signer)?)
+}
+
+// Trust in a rotated Id moves to its successor, once both Ids signed the
+// successor proof
+#[test]
+fn trust_moves_to_successor() -> Result<()> {
+    let url = FetchSource::Url(Arc::new(Url::new_git("https://a")));
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let old = UnlockedId::generate_for_git_url("https://b");
+    let new = UnlockedId::generate_for_git_url("https://b");
+    let newer = UnlockedId::generate_for_git_url("https://b");
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![trust_medium(&a, &old)?, successor_proof(&old, &old, &new)?]
+            .into_iter()
+            .map(|x| (x, url.clone())),
+    );
+    // only one half of the handshake
+    assert_eq!(trustdb.get_successor(old.as_ref()), None);
+    let trust_set = trustdb.calculate_trust_set(a.as_ref(), &default());
+    assert_eq!(
+        trust_set.get_trusted_ids_refs(),
+        HashSet::from([a.as_ref(), old.as_ref()])
+    );
+
+    trustdb.import_from_iter(
+        vec![successor_proof(&new, &old, &new)?]
+            .into_iter()
+            .map(|x| (x, url.clone())),
+    );
+    assert_eq!(trustdb.get_successor(old.as_ref()), Some(new.as_ref()));
+    let trust_set = trustdb.calculate_trust_set(a.as_ref(), &default());
+    assert_eq!(
+        trust_set.get_effective_trust_level(new.as_ref()),
+        TrustLevel::Medium
+    );
+
+    // rotations chain, and direct trust takes precedence
+    trustdb.import_from_iter(
+        vec![
+            successor_proof(&new, &new, &newer)?,
+            successor_proof(&newer, &new, &newer)?,
+            trust_low(&a, &new)?,
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+    assert_eq!(
+        trustdb.get_successors_of(old.as_ref()),
+        [new.as_ref(), newer.as_ref()]
+    );
+    let trust_set = trustdb.calculate_trust_set(a.as_ref(), &default());
+    assert_eq!(
+        trust_set.get_effective_trust_level(new.as_ref()),
+        TrustLevel::Low
+    );
+    assert_eq!(
+        trust_set.get_effective_trust_level(newer.as_ref()),
+        TrustLevel::Low
+    );
+    Ok(())
+}
+
+// A third Id can't sign a successor proof for others
+#[test]
+fn successor_signed_by_other_id_is_rejected() -> Result<()> {
+    let old = UnlockedId::generate_for_git_url("https://b");
+    let new = UnlockedId::generate_for_git_url("https://b");
+    let other = UnlockedId::generate_for_git_url("https://c");
+    let by_other = successor_proof(&other, &old, &new)?;
+    assert!(by_other.parse_content::<proof::Successor>().is_err());
+    let to_itself = successor_proof(&old, &old, &old)?;
+    assert!(to_itself.parse_content::<proof::Successor>().is_err());
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![by_other, successor_proof(&new, &old, &new)?]
+            .into_iter()
+            .map(|x| (x, FetchSource::LocalUser)),
+    );
+    assert_eq!(trustdb.get_successor(old.as_ref()), None);
+    Ok(())
+}
@@ -70,6 +70,10 @@ pub struct TrustSet {
 
     pub trusted: HashMap<Id, TrustedIdDetails>,
     pub distrusted: HashMap<Id, DistrustedIdDetails>,
+    /// Ids reported as distrusted, but not by enough Ids to take effect
+    ///
+    /// See [`DistrustPolicy::quorum`].
+    pub distrust_pending: HashMap<Id, DistrustedIdDetails>,
 
     // "ignore trust from `Id` to `Id`, as overridden by some other Ids with an effective `TrustLevel`s
     pub trust_ignore_overrides: HashMap<(Id, Id), OverrideSourcesDetails>,
@@ -78,15 +82,45 @@ pub struct TrustSet {
     pub package_review_ignore_override: HashMap<PkgVersionReviewId, OverrideSourcesDetails>,
 }
 
+/// Trust proofs to leave out of a [`TrustSet`], to see what difference they make
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Skipped<'a> {
+    Nothing,
+    /// Trust proof of `.0` for `.1`
+    Edge(&'a Id, &'a Id),
+    /// All trust proofs for the Id, so it's not trusted at all
+    Id(&'a Id),
+}
+
+impl Skipped<'_> {
+    fn skips(self, from: &Id, to: &Id) -> bool {
+        match self {
+            Skipped::Nothing => false,
+            Skipped::Edge(skipped_from, skipped_to) => skipped_from == from && skipped_to == to,
+            Skipped::Id(skipped) => skipped == to,
+        }
+    }
+}
+
 impl TrustSet {
     pub fn from(db: &ProofDB, for_id: &Id, params: &TrustDistanceParams) -> TrustSet {
+        Self::from_skipping(db, for_id, params, Skipped::Nothing)
+    }
+
+    /// Like [`TrustSet::from`], but as if the `skipped` trust proofs didn't exist
+    pub(crate) fn from_skipping(
+        db: &ProofDB,
+        for_id: &Id,
+        params: &TrustDistanceParams,
+        skipped: Skipped<'_>,
+    ) -> TrustSet {
         let mut distrusted = HashMap::new();
 
         // We keep retrying the whole thing, with more and more
         // distrusted Ids
         loop {
             let prev_distrusted_len = distrusted.len();
-            let trust_set = Self::from_inner_loop(db, for_id, params, distrusted);
+            let trust_set = Self::from_inner_loop(db, for_id, params, distrusted, skipped);
             if trust_set.distrusted.len() <= prev_distrusted_len {
                 return trust_set;
             }
@@ -106,6 +140,7 @@ impl TrustSet {
         for_id: &Id,
         params: &TrustDistanceParams,
         distrusted: HashMap<Id, DistrustedIdDetails>,
+        skipped: Skipped<'_>,
     ) -> Self {
         /// Node that is to be visited
         ///
@@ -189,6 +224,9 @@ impl TrustSet {
             }
 
             for (trust_details, candidate_id) in db.get_trust_details_list_of_id(&current.id) {
+                if skipped.skips(&current.id, candidate_id) {
+                    continue;
+                }
                 let direct_trust = trust_details.level;
                 let current_overrides = &trust_details.override_;
 
@@ -276,6 +314,17 @@ impl TrustSet {
                 // However banning by the same trust level node, does not prevent
                 // the node from banning others.
                 if direct_trust == TrustLevel::Distrust {
+                    let policy = &params.distrust_policy;
+                    if current.effective_trust_level < policy.min_reporter_trust {
+                        debug!(
+                            "Ignoring distrust of {} by {}: trust level {} is lower than the required {}",
+                            candidate_id,
+                            current.id,
+                            current.effective_trust_level,
+                            policy.min_reporter_trust
+                        );
+                        continue;
+                    }
                     debug!(
                         "Adding {} to distrusted list (via {})",
                         candidate_id, current.id
@@ -284,8 +333,11 @@ impl TrustSet {
                     // progress as possible before restaring building the WoT, and
                     // we will not visit any node that was marked as distrusted,
                     // because we check it for every node to be visited
-                    let _ = current_trust_set
-                        .record_distrusted_id(candidate_id.clone(), current.id.clone());
+                    let _ = current_trust_set.record_distrusted_id(
+                        candidate_id.clone(),
+                        current.id.clone(),
+                        policy.quorum,
+                    );
 
                     continue;
                 }
@@ -389,18 +441,28 @@ impl TrustSet {
 
     /// Record that an Id is reported as distrusted
     ///
+    /// The Id becomes distrusted only after `quorum` different Ids reported it,
+    /// until then the reports are kept in `distrust_pending`.
+    ///
     /// Return `true` if it was previously considered as trusted,
     /// and so that WoT traversal needs to be restarted
-    fn record_distrusted_id(&mut self, subject: Id, reported_by: Id) -> bool {
-        let res = self.trusted.remove(&subject).is_some();
-
-        self.distrusted
-            .entry(subject)
-            .or_default()
-            .reported_by
-            .insert(reported_by);
+    fn record_distrusted_id(&mut self, subject: Id, reported_by: Id, quorum: usize) -> bool {
+        if let Some(details) = self.distrusted.get_mut(&subject) {
+            details.reported_by.insert(reported_by);
+        } else {
+            let pending = self.distrust_pending.entry(subject.clone()).or_default();
+            pending.reported_by.insert(reported_by);
+            if pending.reported_by.len() < quorum {
+                return false;
+            }
+            let details = self
+                .distrust_pending
+                .remove(&subject)
+                .expect("inserted above");
+            self.distrusted.insert(subject.clone(), details);
+        }
 
-        res
+        self.trusted.remove(&subject).is_some()
     }
 
     /// Record that an Id is reported as trusted
@@ -457,6 +519,186 @@ impl TrustSet {
         }
     }
 
+    /// Same Ids trusted with the same effective trust levels, and the same Ids distrusted
+    #[must_use]
+    pub fn has_same_effective_trust(&self, other: &TrustSet) -> bool {
+        self.trusted.len() == other.trusted.len()
+            && self.trusted.iter().all(|(id, details)| {
+                other.trusted.get(id).is_some_and(|other_details| {
+                    other_details.effective_trust_level == details.effective_trust_level
+                })
+            })
+            && self.distrusted.len() == other.distrusted.len()
+            && self
+                .distrusted
+                .keys()
+                .all(|id| other.distrusted.contains_key(id))
+    }
+
+    /// Digest of the effective trust, independent of how it was derived
+    ///
+    /// Covers the trusted Ids with their levels, the distrusted Ids and
+    /// the overrides in effect, so equal digests give equal verification results
+    /// for the same proofs. Stable between runs, eg. to key cached results.
+    #[must_use]
+    pub fn stable_digest(&self) -> Digest {
+        let mut lines = BTreeSet::new();
+        for (id, details) in &self.trusted {
+            lines.insert(format!("trust {id} {}", details.effective_trust_level));
+        }
+        for id in self.distrusted.keys() {
+            lines.insert(format!("distrust {id}"));
+        }
+        for (from, to) in self.trust_ignore_overrides.keys() {
+            lines.insert(format!("ignore-trust {from} {to}"));
+        }
+        for review_id in self.package_review_ignore_override.keys() {
+            let package = &review_id.package_version_id;
+            lines.insert(format!(
+                "ignore-review {} {} {} {}",
+                review_id.from, package.id.source, package.id.name, package.version
+            ));
+        }
+        let text = lines.into_iter().join("\n");
+        Digest::from(crev_common::blake2b256sum(text.as_bytes()))
+    }
+
+    /// Trusted Ids (with levels and distances) and distrusted Ids, sorted,
+    /// for a [`proof::TrustSnapshot`]
+    #[must_use]
+    pub fn snapshot_entries(&self) -> (Vec<proof::TrustSnapshotEntry>, Vec<Id>) {
+        let mut trusted: Vec<_> = self
+            .trusted
+            .iter()
+            .map(|(id, details)| proof::TrustSnapshotEntry {
+                id: id.clone(),
+                level: details.effective_trust_level,
+                distance: details.distance,
+            })
+            .collect();
+        trusted.sort_by(|a, b| a.id.cmp(&b.id));
+        let distrusted = self.distrusted.keys().cloned().sorted().collect();
+        (trusted, distrusted)
+    }
+
+    /// Trust proofs and package reviews ignored because of overrides, sorted,
+    /// for a [`proof::TrustSnapshot`]
+    #[must_use]
+    pub fn snapshot_overrides(
+        &self,
+    ) -> (
+        Vec<proof::TrustSnapshotIgnoredTrust>,
+        Vec<proof::TrustSnapshotIgnoredReview>,
+    ) {
+        let level =
+            |sources: &OverrideSourcesDetails| sources.max_level().unwrap_or(TrustLevel::None);
+        let ignored_trust = self
+            .trust_ignore_overrides
+            .iter()
+            .map(|((from, to), sources)| proof::TrustSnapshotIgnoredTrust {
+                from: from.clone(),
+                to: to.clone(),
+                level: level(sources),
+            })
+            .sorted_by(|a, b| (&a.from, &a.to).cmp(&(&b.from, &b.to)))
+            .collect();
+        let ignored_reviews = self
+            .package_review_ignore_override
+            .iter()
+            .map(|(review_id, sources)| proof::TrustSnapshotIgnoredReview {
+                from: review_id.from.clone(),
+                package: review_id.package_version_id.clone(),
+                level: level(sources),
+            })
+            .sorted_by_cached_key(|ignored| {
+                (
+                    ignored.from.clone(),
+                    ignored.package.id.source.clone(),
+                    ignored.package.id.name.clone(),
+                    ignored.package.version.clone(),
+                )
+            })
+            .collect();
+        (ignored_trust, ignored_reviews)
+    }
+
+    /// Trust set as recorded in a snapshot, with everything reported by its author
+    ///
+    /// The overrides in effect are those recorded, as if made by the author.
+    #[must_use]
+    pub fn from_snapshot(snapshot: &proof::TrustSnapshot) -> TrustSet {
+        let author = &snapshot.common.from.id;
+        let sources = |level: TrustLevel| {
+            let mut sources = OverrideSourcesDetails::default();
+            sources.insert(author.clone(), level);
+            sources
+        };
+        TrustSet {
+            trusted: snapshot
+                .trusted
+                .iter()
+                .map(|entry| {
+                    let details = TrustedIdDetails {
+                        distance: entry.distance,
+                        effective_trust_level: entry.level,
+                        reported_by: HashMap::from([(author.clone(), entry.level)]),
+                    };
+                    (entry.id.clone(), details)
+                })
+                .collect(),
+            distrusted: snapshot
+                .distrusted
+                .iter()
+                .map(|id| {
+                    let details = DistrustedIdDetails {
+                        reported_by: HashSet::from([author.clone()]),
+                    };
+                    (id.clone(), details)
+                })
+                .collect(),
+            trust_ignore_overrides: snapshot
+                .ignored_trust
+                .iter()
+                .map(|ignored| {
+                    (
+                        (ignored.from.clone(), ignored.to.clone()),
+                        sources(ignored.level),
+                    )
+                })
+                .collect(),
+            package_review_ignore_override: snapshot
+                .ignored_reviews
+                .iter()
+                .map(|ignored| {
+                    let review_id = PkgVersionReviewId {
+                        from: ignored.from.clone(),
+                        package_version_id: ignored.package.clone(),
+                    };
+                    (review_id, sources(ignored.level))
+                })
+                .collect(),
+            ..TrustSet::default()
+        }
+    }
+
+    /// Copy with `id` trusted directly by `root` at `level`, eg. to see what difference it would make
+    ///
+    /// Only `id` itself is added, not the Ids it trusts.
+    #[must_use]
+    pub fn with_directly_trusted(&self, root: &Id, id: &Id, level: TrustLevel) -> TrustSet {
+        let mut trust_set = self.clone();
+        trust_set.distrusted.remove(id);
+        trust_set.trusted.insert(
+            id.clone(),
+            TrustedIdDetails {
+                distance: 0,
+                effective_trust_level: level,
+                reported_by: HashMap::from([(root.clone(), level)]),
+            },
+        );
+        trust_set
+    }
+
     #[must_use]
     pub fn get_effective_trust_level(&self, id: &Id) -> TrustLevel {
         self.get_effective_trust_level_opt(id)